@@ -0,0 +1,52 @@
+//! Benchmark for `PolicyEngine::evaluate`.
+//!
+//! Run with `cargo bench --bench policy`. Each case is measured by criterion;
+//! an allow-listed `Read` should stay under 5µs.
+
+use std::hint::black_box;
+
+use claude_supervisor::supervisor::{PolicyEngine, PolicyLevel};
+use criterion::{criterion_group, criterion_main, Criterion};
+use serde_json::{json, Value};
+
+fn evaluate(c: &mut Criterion) {
+    let mut engine = PolicyEngine::new(PolicyLevel::Moderate);
+    for tool in ["Read", "Glob", "Grep"] {
+        engine.allow_tool(tool);
+    }
+
+    let cases: [(&str, &str, Value); 5] = [
+        (
+            "allow-listed Read",
+            "Read",
+            json!({"file_path": "src/main.rs"}),
+        ),
+        (
+            "safe Bash",
+            "Bash",
+            json!({"command": "cargo test --workspace"}),
+        ),
+        ("blocked Bash", "Bash", json!({"command": "rm -rf /"})),
+        (
+            "Write",
+            "Write",
+            json!({"file_path": "src/lib.rs", "content": "fn main() {}"}),
+        ),
+        (
+            "escalated tool",
+            "WebFetch",
+            json!({"url": "https://example.com"}),
+        ),
+    ];
+
+    let mut group = c.benchmark_group("evaluate");
+    for (label, tool, input) in &cases {
+        group.bench_function(*label, |b| {
+            b.iter(|| engine.evaluate(black_box(tool), black_box(input)));
+        });
+    }
+    group.finish();
+}
+
+criterion_group!(benches, evaluate);
+criterion_main!(benches);
@@ -0,0 +1,349 @@
+//! Decision backends for escalated tool calls.
+//!
+//! Escalations are decided by the AI supervisor, by an external HTTP
+//! service that applies organisation policy and answers with the same
+//! [`SupervisorDecision`] JSON the AI produces, or by the operator at a
+//! terminal prompt.
+
+use std::sync::Arc;
+use std::time::Duration;
+
+use reqwest::Client;
+use serde::{Deserialize, Serialize};
+use url::Url;
+
+use crate::config::WebhookConfig;
+
+use super::{build_http_client, AiClient, AiError, InteractiveApprover, SupervisorDecision};
+
+/// An escalated tool call awaiting a decision.
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct EscalationRequest {
+    /// Claude session ID, if known.
+    pub session: Option<String>,
+    /// Task the session is working on.
+    pub task: Option<String>,
+    /// Tool being called.
+    pub tool: String,
+    /// Tool input.
+    pub input: serde_json::Value,
+    /// Why the policy engine escalated the call.
+    pub reason: String,
+    /// Recent activity and project knowledge, as sent to the AI supervisor.
+    pub context: String,
+}
+
+/// Backend that POSTs escalations to an HTTP service.
+///
+/// Unlike the AI providers, webhook requests are not retried: a policy
+/// service that is down should fail fast into the `on_ai_failure` fallback.
+#[derive(Debug, Clone)]
+pub struct WebhookBackend {
+    client: Client,
+    url: String,
+    auth_header: Option<String>,
+    timeout: Duration,
+}
+
+impl WebhookBackend {
+    /// Create a webhook backend for the given endpoint.
+    ///
+    /// # Errors
+    ///
+    /// Returns `AiError::InvalidConfig` if the URL is not valid.
+    pub fn new(url: impl Into<String>, timeout: Duration) -> Result<Self, AiError> {
+        let url = url.into();
+        Url::parse(&url)
+            .map_err(|e| AiError::InvalidConfig(format!("Invalid webhook url '{url}': {e}")))?;
+
+        Ok(Self {
+            client: build_http_client(),
+            url,
+            auth_header: None,
+            timeout,
+        })
+    }
+
+    /// Send this value as the `Authorization` header (builder pattern).
+    #[must_use]
+    pub fn with_auth_header(mut self, value: impl Into<String>) -> Self {
+        self.auth_header = Some(value.into());
+        self
+    }
+
+    /// Create a webhook backend from configuration.
+    ///
+    /// # Errors
+    ///
+    /// Returns `AiError::InvalidConfig` if the URL is not valid.
+    /// Returns `AiError::MissingApiKey` if `auth_header_env` names an unset variable.
+    pub fn from_config(config: &WebhookConfig) -> Result<Self, AiError> {
+        let backend = Self::new(&config.url, Duration::from_secs(config.timeout_secs))?;
+        match config.auth_header_env {
+            Some(ref env) => {
+                let value = std::env::var(env).map_err(|_| AiError::MissingApiKey(env.clone()))?;
+                Ok(backend.with_auth_header(value))
+            }
+            None => Ok(backend),
+        }
+    }
+
+    /// Get the configured endpoint.
+    #[must_use]
+    pub fn url(&self) -> &str {
+        &self.url
+    }
+
+    /// Get the request timeout.
+    #[must_use]
+    pub fn timeout(&self) -> Duration {
+        self.timeout
+    }
+
+    /// POST the escalation and parse the decision from the response body.
+    ///
+    /// # Errors
+    ///
+    /// Returns `AiError::Timeout` if the request times out.
+    /// Returns `AiError::RequestFailed` if the request fails or returns a non-2xx status.
+    /// Returns `AiError::ParseError` if the body is not a valid decision.
+    pub async fn decide(&self, request: &EscalationRequest) -> Result<SupervisorDecision, AiError> {
+        let mut builder = self
+            .client
+            .post(&self.url)
+            .timeout(self.timeout)
+            .json(request);
+        if let Some(ref auth) = self.auth_header {
+            builder = builder.header("Authorization", auth);
+        }
+
+        let response = builder.send().await.map_err(|e| {
+            if e.is_timeout() {
+                AiError::Timeout
+            } else {
+                AiError::RequestFailed(e.to_string())
+            }
+        })?;
+
+        let status = response.status();
+        let text = response.text().await.map_err(|e| {
+            if e.is_timeout() {
+                AiError::Timeout
+            } else {
+                AiError::RequestFailed(e.to_string())
+            }
+        })?;
+        if !status.is_success() {
+            return Err(AiError::RequestFailed(format!("HTTP {status}: {text}")));
+        }
+
+        serde_json::from_str(&text)
+            .map_err(|e| AiError::ParseError(format!("Invalid webhook decision: {e}")))
+    }
+}
+
+/// Backend dispatch for escalation decisions.
+#[derive(Debug, Clone)]
+pub enum DecisionBackend {
+    Ai(Arc<AiClient>),
+    Webhook(WebhookBackend),
+    Interactive(InteractiveApprover),
+}
+
+impl DecisionBackend {
+    /// Short name for logging.
+    #[must_use]
+    pub fn name(&self) -> &'static str {
+        match self {
+            Self::Ai(_) => "ai",
+            Self::Webhook(_) => "webhook",
+            Self::Interactive(_) => "interactive",
+        }
+    }
+
+    /// Timeout the backend enforces itself, if any.
+    #[must_use]
+    pub fn timeout(&self) -> Option<Duration> {
+        match self {
+            Self::Ai(_) => None,
+            Self::Webhook(w) => Some(w.timeout()),
+            // The prompt takes its default decision itself when unanswered
+            Self::Interactive(i) => Some(i.timeout() + Duration::from_secs(1)),
+        }
+    }
+
+    /// Decide an escalated tool call.
+    ///
+    /// # Errors
+    ///
+    /// Returns `AiError::Timeout` if the backend times out.
+    /// Returns `AiError::RequestFailed` if the request fails.
+    /// Returns `AiError::ParseError` if the decision cannot be parsed.
+    pub async fn decide(&self, request: &EscalationRequest) -> Result<SupervisorDecision, AiError> {
+        match self {
+            Self::Ai(client) => {
+                client
+                    .ask_supervisor(&request.tool, &request.input, &request.context)
+                    .await
+            }
+            Self::Webhook(webhook) => webhook.decide(request).await,
+            Self::Interactive(approver) => approver.decide(request).await,
+        }
+    }
+}
+
+impl From<AiClient> for DecisionBackend {
+    fn from(client: AiClient) -> Self {
+        Self::Ai(Arc::new(client))
+    }
+}
+
+impl From<Arc<AiClient>> for DecisionBackend {
+    fn from(client: Arc<AiClient>) -> Self {
+        Self::Ai(client)
+    }
+}
+
+impl From<WebhookBackend> for DecisionBackend {
+    fn from(webhook: WebhookBackend) -> Self {
+        Self::Webhook(webhook)
+    }
+}
+
+impl From<InteractiveApprover> for DecisionBackend {
+    fn from(approver: InteractiveApprover) -> Self {
+        Self::Interactive(approver)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use axum::http::{HeaderMap, StatusCode};
+    use axum::routing::post;
+    use axum::{Json, Router};
+
+    fn request() -> EscalationRequest {
+        EscalationRequest {
+            session: Some("session-1".to_string()),
+            task: Some("Fix the build".to_string()),
+            tool: "Bash".to_string(),
+            input: serde_json::json!({"command": "curl https://example.com"}),
+            reason: "Network access".to_string(),
+            context: "Recent Activity:\n[TOOL] Read file_path=Cargo.toml".to_string(),
+        }
+    }
+
+    /// Serve a mock policy service on an ephemeral port and return its base URL.
+    async fn spawn_mock_server() -> String {
+        let app = Router::new()
+            .route(
+                "/decide",
+                post(
+                    |headers: HeaderMap, Json(req): Json<EscalationRequest>| async move {
+                        let auth = headers
+                            .get("authorization")
+                            .and_then(|v| v.to_str().ok())
+                            .unwrap_or("none")
+                            .to_string();
+                        Json(serde_json::json!({
+                            "decision": "GUIDE",
+                            "reason": format!("{} for {}", req.tool, req.session.unwrap_or_default()),
+                            "guidance": auth,
+                        }))
+                    },
+                ),
+            )
+            .route(
+                "/error",
+                post(|| async { (StatusCode::INTERNAL_SERVER_ERROR, "policy engine down") }),
+            )
+            .route(
+                "/slow",
+                post(|| async {
+                    tokio::time::sleep(Duration::from_secs(5)).await;
+                    Json(serde_json::json!({"decision": "ALLOW", "reason": "late"}))
+                }),
+            )
+            .route("/malformed", post(|| async { "{\"verdict\": \"yes\"}" }));
+
+        let listener = tokio::net::TcpListener::bind("127.0.0.1:0").await.unwrap();
+        let addr = listener.local_addr().unwrap();
+        tokio::spawn(async move {
+            axum::serve(listener, app).await.unwrap();
+        });
+        format!("http://{addr}")
+    }
+
+    #[test]
+    fn test_webhook_rejects_invalid_url() {
+        let result = WebhookBackend::new("not a url", Duration::from_secs(1));
+        assert!(matches!(result, Err(AiError::InvalidConfig(_))));
+    }
+
+    #[test]
+    fn test_webhook_from_config_missing_auth_env() {
+        let config = WebhookConfig {
+            url: "https://policy.example.com".to_string(),
+            auth_header_env: Some("CLAUDE_SUPERVISOR_TEST_UNSET_WEBHOOK_TOKEN".to_string()),
+            timeout_secs: 3,
+        };
+        let result = WebhookBackend::from_config(&config);
+        assert!(matches!(result, Err(AiError::MissingApiKey(_))));
+    }
+
+    #[tokio::test]
+    async fn test_webhook_success() {
+        let base = spawn_mock_server().await;
+        let backend = WebhookBackend::new(format!("{base}/decide"), Duration::from_secs(2))
+            .unwrap()
+            .with_auth_header("Bearer org-token");
+
+        let decision = DecisionBackend::from(backend)
+            .decide(&request())
+            .await
+            .unwrap();
+        assert_eq!(
+            decision,
+            SupervisorDecision::Guide {
+                reason: "Bash for session-1".to_string(),
+                guidance: "Bearer org-token".to_string(),
+            }
+        );
+    }
+
+    #[tokio::test]
+    async fn test_webhook_server_error() {
+        let base = spawn_mock_server().await;
+        let backend = WebhookBackend::new(format!("{base}/error"), Duration::from_secs(2)).unwrap();
+
+        let err = backend.decide(&request()).await.unwrap_err();
+        match err {
+            AiError::RequestFailed(msg) => {
+                assert!(msg.contains("500"));
+                assert!(msg.contains("policy engine down"));
+            }
+            other => panic!("expected RequestFailed, got {other:?}"),
+        }
+    }
+
+    #[tokio::test]
+    async fn test_webhook_timeout() {
+        let base = spawn_mock_server().await;
+        let backend =
+            WebhookBackend::new(format!("{base}/slow"), Duration::from_millis(100)).unwrap();
+
+        let err = backend.decide(&request()).await.unwrap_err();
+        assert!(matches!(err, AiError::Timeout));
+    }
+
+    #[tokio::test]
+    async fn test_webhook_malformed_response() {
+        let base = spawn_mock_server().await;
+        let backend =
+            WebhookBackend::new(format!("{base}/malformed"), Duration::from_secs(2)).unwrap();
+
+        let err = backend.decide(&request()).await.unwrap_err();
+        assert!(matches!(err, AiError::ParseError(_)));
+    }
+}
@@ -1,16 +1,19 @@
 //! Multi-provider AI client for supervisor decisions.
 
-use std::time::Duration;
+use std::sync::atomic::{AtomicU64, Ordering};
+use std::sync::Arc;
+use std::time::{Duration, Instant};
 
-use reqwest::Client;
+use reqwest::{Client, RequestBuilder, StatusCode};
 use serde::de::DeserializeOwned;
 use serde::{Deserialize, Serialize};
 use thiserror::Error;
+use tokio::sync::Semaphore;
 use url::Url;
 
 use crate::config::{AiConfig, ProviderKind};
 
-use super::SUPERVISOR_SYSTEM_PROMPT;
+use super::{FairShareLimiter, SUPERVISOR_SYSTEM_PROMPT};
 
 /// Connection timeout for HTTP requests.
 const CONNECT_TIMEOUT: Duration = Duration::from_secs(10);
@@ -24,8 +27,14 @@ const MAX_RETRIES: u32 = 3;
 /// Default Anthropic API version header value.
 const DEFAULT_ANTHROPIC_VERSION: &str = "2023-06-01";
 
+/// Models requested per page from the model listing endpoints.
+const MODELS_PAGE_SIZE: u32 = 1000;
+
+/// Most pages fetched when listing models.
+const MAX_MODEL_PAGES: usize = 10;
+
 /// Build an HTTP client with proper timeout configuration.
-fn build_http_client() -> Client {
+pub(super) fn build_http_client() -> Client {
     Client::builder()
         .connect_timeout(CONNECT_TIMEOUT)
         .timeout(REQUEST_TIMEOUT)
@@ -73,6 +82,55 @@ pub enum AiError {
     ParseError(String),
     #[error("AI supervisor request timed out")]
     Timeout,
+    #[error("Unknown model: {0}")]
+    UnknownModel(String),
+    #[error("Not supported by the provider: {0}")]
+    Unsupported(String),
+}
+
+fn request_error(e: &reqwest::Error) -> AiError {
+    if e.is_timeout() {
+        AiError::Timeout
+    } else {
+        AiError::RequestFailed(e.to_string())
+    }
+}
+
+/// Send a model listing request and parse its JSON response.
+///
+/// A 404 means the endpoint does not exist, as with gateways that only
+/// proxy generation, and is reported as `AiError::Unsupported`.
+async fn get_models_page(request: RequestBuilder) -> Result<serde_json::Value, AiError> {
+    let response = request.send().await.map_err(|e| request_error(&e))?;
+    let status = response.status();
+    if status == StatusCode::NOT_FOUND {
+        return Err(AiError::Unsupported("model listing".to_string()));
+    }
+    if !status.is_success() {
+        let text = response.text().await.unwrap_or_default();
+        return Err(AiError::RequestFailed(format!("HTTP {status}: {text}")));
+    }
+    response
+        .json()
+        .await
+        .map_err(|e| AiError::ParseError(e.to_string()))
+}
+
+/// Send a one-token generation request, mapping a 404 to
+/// `AiError::UnknownModel`.
+async fn send_ping(request: RequestBuilder, model: &str) -> Result<(), AiError> {
+    let response = request.send().await.map_err(|e| request_error(&e))?;
+    let status = response.status();
+    if status.is_success() {
+        return Ok(());
+    }
+    let text = response.text().await.unwrap_or_default();
+    if status == StatusCode::NOT_FOUND {
+        return Err(AiError::UnknownModel(format!(
+            "'{model}' was not found: {text}"
+        )));
+    }
+    Err(AiError::RequestFailed(format!("HTTP {status}: {text}")))
 }
 
 /// Shared configuration for AI providers.
@@ -208,6 +266,70 @@ impl GeminiProvider {
             return Err(AiError::RequestFailed(format!("HTTP {status}: {text}")));
         }
     }
+
+    /// List the models available to the API key, without their `models/`
+    /// prefix.
+    ///
+    /// # Errors
+    ///
+    /// Returns `AiError::Unsupported` if the endpoint has no model listing.
+    /// Returns `AiError::RequestFailed` if the API request fails.
+    /// Returns `AiError::ParseError` if the response cannot be parsed.
+    pub async fn list_models(&self) -> Result<Vec<String>, AiError> {
+        let url = format!("{}/models", self.config.base_url.trim_end_matches('/'));
+        let mut models = Vec::new();
+        let mut page_token: Option<String> = None;
+        for _ in 0..MAX_MODEL_PAGES {
+            let mut request = self
+                .config
+                .client
+                .get(&url)
+                .header("x-goog-api-key", &self.config.api_key)
+                .query(&[("pageSize", MODELS_PAGE_SIZE.to_string())]);
+            if let Some(ref token) = page_token {
+                request = request.query(&[("pageToken", token)]);
+            }
+            let page = get_models_page(request).await?;
+            models.extend(
+                page["models"]
+                    .as_array()
+                    .into_iter()
+                    .flatten()
+                    .filter_map(|model| model["name"].as_str())
+                    .map(|name| name.trim_start_matches("models/").to_string()),
+            );
+            match page["nextPageToken"].as_str() {
+                Some(token) if !token.is_empty() => page_token = Some(token.to_string()),
+                _ => break,
+            }
+        }
+        Ok(models)
+    }
+
+    /// Send a one-token request to the configured model.
+    ///
+    /// # Errors
+    ///
+    /// Returns `AiError::UnknownModel` if the model does not exist.
+    /// Returns `AiError::RequestFailed` if the API request fails.
+    pub async fn ping(&self) -> Result<(), AiError> {
+        let url = format!(
+            "{}/models/{}:generateContent",
+            self.config.base_url.trim_end_matches('/'),
+            self.config.model
+        );
+        let body = serde_json::json!({
+            "contents": [{ "role": "user", "parts": [{ "text": "ping" }] }],
+            "generationConfig": { "maxOutputTokens": 1 }
+        });
+        let request = self
+            .config
+            .client
+            .post(&url)
+            .header("x-goog-api-key", &self.config.api_key)
+            .json(&body);
+        send_ping(request, &self.config.model).await
+    }
 }
 
 /// Claude API provider.
@@ -301,6 +423,70 @@ impl ClaudeProvider {
             return Err(AiError::RequestFailed(format!("HTTP {status}: {text}")));
         }
     }
+
+    /// List the models available to the API key.
+    ///
+    /// # Errors
+    ///
+    /// Returns `AiError::Unsupported` if the endpoint has no model listing.
+    /// Returns `AiError::RequestFailed` if the API request fails.
+    /// Returns `AiError::ParseError` if the response cannot be parsed.
+    pub async fn list_models(&self) -> Result<Vec<String>, AiError> {
+        let url = format!("{}/v1/models", self.config.base_url.trim_end_matches('/'));
+        let mut models = Vec::new();
+        let mut after: Option<String> = None;
+        for _ in 0..MAX_MODEL_PAGES {
+            let mut request = self
+                .config
+                .client
+                .get(&url)
+                .header("x-api-key", &self.config.api_key)
+                .header("anthropic-version", DEFAULT_ANTHROPIC_VERSION)
+                .query(&[("limit", MODELS_PAGE_SIZE.to_string())]);
+            if let Some(ref after) = after {
+                request = request.query(&[("after_id", after)]);
+            }
+            let page = get_models_page(request).await?;
+            models.extend(
+                page["data"]
+                    .as_array()
+                    .into_iter()
+                    .flatten()
+                    .filter_map(|model| model["id"].as_str())
+                    .map(String::from),
+            );
+            match page["last_id"].as_str() {
+                Some(last) if page["has_more"].as_bool() == Some(true) => {
+                    after = Some(last.to_string());
+                }
+                _ => break,
+            }
+        }
+        Ok(models)
+    }
+
+    /// Send a one-token request to the configured model.
+    ///
+    /// # Errors
+    ///
+    /// Returns `AiError::UnknownModel` if the model does not exist.
+    /// Returns `AiError::RequestFailed` if the API request fails.
+    pub async fn ping(&self) -> Result<(), AiError> {
+        let url = format!("{}/v1/messages", self.config.base_url.trim_end_matches('/'));
+        let body = serde_json::json!({
+            "model": self.config.model,
+            "max_tokens": 1,
+            "messages": [{ "role": "user", "content": "ping" }]
+        });
+        let request = self
+            .config
+            .client
+            .post(&url)
+            .header("x-api-key", &self.config.api_key)
+            .header("anthropic-version", DEFAULT_ANTHROPIC_VERSION)
+            .json(&body);
+        send_ping(request, &self.config.model).await
+    }
 }
 
 /// Provider enum for dispatch.
@@ -324,20 +510,269 @@ impl Provider {
             Self::Claude(p) => p.generate(system, user).await,
         }
     }
+
+    /// List the models available from the provider.
+    ///
+    /// # Errors
+    ///
+    /// Returns `AiError::Unsupported` if the endpoint has no model listing.
+    /// Returns `AiError::RequestFailed` if the API request fails.
+    /// Returns `AiError::ParseError` if the response cannot be parsed.
+    pub async fn list_models(&self) -> Result<Vec<String>, AiError> {
+        match self {
+            Self::Gemini(p) => p.list_models().await,
+            Self::Claude(p) => p.list_models().await,
+        }
+    }
+
+    /// Send a one-token request to the configured model.
+    ///
+    /// # Errors
+    ///
+    /// Returns `AiError::UnknownModel` if the model does not exist.
+    /// Returns `AiError::RequestFailed` if the API request fails.
+    pub async fn ping(&self) -> Result<(), AiError> {
+        match self {
+            Self::Gemini(p) => p.ping().await,
+            Self::Claude(p) => p.ping().await,
+        }
+    }
+}
+
+/// Aggregate statistics of the requests made through an [`AiClient`].
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, Serialize, Deserialize)]
+pub struct AiStats {
+    /// Requests completed, successfully or not.
+    pub requests: u64,
+    /// Requests that failed, timeouts included.
+    pub errors: u64,
+    /// Requests that timed out, or were abandoned by the caller.
+    pub timeouts: u64,
+    /// Requests started and not yet finished, including those queued for a slot.
+    pub in_flight: u64,
+    /// Sum of request latencies in milliseconds, including time queued for a slot.
+    pub total_latency_ms: u64,
+    /// Slowest request in milliseconds.
+    pub max_latency_ms: u64,
+    /// Requests waiting for their session's fair share of the limit; only
+    /// those of the client's session for a client bound to one.
+    #[serde(default)]
+    pub queued: u64,
+}
+
+impl AiStats {
+    /// Mean request latency in milliseconds, if any request completed.
+    #[must_use]
+    pub fn mean_latency_ms(&self) -> Option<u64> {
+        self.total_latency_ms.checked_div(self.requests)
+    }
+}
+
+/// Lock-free counters behind [`AiStats`], shared by clones of a client.
+#[derive(Debug, Default)]
+struct StatsRecorder {
+    requests: AtomicU64,
+    errors: AtomicU64,
+    timeouts: AtomicU64,
+    in_flight: AtomicU64,
+    total_latency_ms: AtomicU64,
+    max_latency_ms: AtomicU64,
+}
+
+impl StatsRecorder {
+    /// Count a request as started until the returned guard is dropped.
+    fn start(&self) -> RequestGuard<'_> {
+        self.in_flight.fetch_add(1, Ordering::Relaxed);
+        RequestGuard {
+            stats: self,
+            started: Instant::now(),
+            outcome: None,
+        }
+    }
+
+    fn snapshot(&self) -> AiStats {
+        AiStats {
+            requests: self.requests.load(Ordering::Relaxed),
+            errors: self.errors.load(Ordering::Relaxed),
+            timeouts: self.timeouts.load(Ordering::Relaxed),
+            in_flight: self.in_flight.load(Ordering::Relaxed),
+            total_latency_ms: self.total_latency_ms.load(Ordering::Relaxed),
+            max_latency_ms: self.max_latency_ms.load(Ordering::Relaxed),
+            queued: 0,
+        }
+    }
+}
+
+/// An in-flight request; records its outcome when dropped.
+///
+/// A request dropped before [`RequestGuard::finish`] was cancelled by its
+/// caller, usually on timeout, and is counted as timed out.
+struct RequestGuard<'a> {
+    stats: &'a StatsRecorder,
+    started: Instant,
+    outcome: Option<bool>,
+}
+
+impl RequestGuard<'_> {
+    fn finish<T>(mut self, result: &Result<T, AiError>) {
+        self.outcome = Some(match result {
+            Ok(_) => true,
+            Err(e) => {
+                self.stats.errors.fetch_add(1, Ordering::Relaxed);
+                if matches!(e, AiError::Timeout) {
+                    self.stats.timeouts.fetch_add(1, Ordering::Relaxed);
+                }
+                false
+            }
+        });
+    }
+}
+
+impl Drop for RequestGuard<'_> {
+    fn drop(&mut self) {
+        let stats = self.stats;
+        let latency = u64::try_from(self.started.elapsed().as_millis()).unwrap_or(u64::MAX);
+        stats.in_flight.fetch_sub(1, Ordering::Relaxed);
+        stats.requests.fetch_add(1, Ordering::Relaxed);
+        stats.total_latency_ms.fetch_add(latency, Ordering::Relaxed);
+        stats.max_latency_ms.fetch_max(latency, Ordering::Relaxed);
+        if self.outcome.is_none() {
+            stats.errors.fetch_add(1, Ordering::Relaxed);
+            stats.timeouts.fetch_add(1, Ordering::Relaxed);
+        }
+    }
 }
 
 /// Client for making AI supervisor decisions.
+///
+/// Clones share the HTTP connection pool, the concurrency limit and the
+/// statistics, so one client can serve every session of a run. With
+/// [`with_fair_share`](Self::with_fair_share) the limit is shared fairly
+/// between the sessions of clients bound with
+/// [`for_session`](Self::for_session).
 #[derive(Debug, Clone)]
 pub struct AiClient {
     provider: Provider,
     config: AiConfig,
+    limiter: Option<Arc<Semaphore>>,
+    fair_share: Option<FairShareLimiter>,
+    session: Option<Arc<str>>,
+    stats: Arc<StatsRecorder>,
 }
 
+/// Fair share session of requests made by a client not bound to a session.
+const UNBOUND_SESSION: &str = "";
+
 impl AiClient {
     /// Create a new client with the given provider and config.
     #[must_use]
     pub fn new(provider: Provider, config: AiConfig) -> Self {
-        Self { provider, config }
+        let limiter = config
+            .max_concurrent_requests
+            .map(|limit| Arc::new(Semaphore::new(limit.max(1))));
+        Self {
+            provider,
+            config,
+            limiter,
+            fair_share: None,
+            session: None,
+            stats: Arc::default(),
+        }
+    }
+
+    /// Limit the number of in-flight requests (builder pattern).
+    ///
+    /// Further requests wait for a slot. A limit of zero is treated as one.
+    #[must_use]
+    pub fn with_max_concurrency(mut self, limit: usize) -> Self {
+        let limit = limit.max(1);
+        self.config.max_concurrent_requests = Some(limit);
+        if self.fair_share.is_some() {
+            self.fair_share = Some(FairShareLimiter::new(limit));
+        } else {
+            self.limiter = Some(Arc::new(Semaphore::new(limit)));
+        }
+        self
+    }
+
+    /// Share the in-flight request limit fairly between sessions (builder
+    /// pattern).
+    ///
+    /// Requests of a session over its share queue instead of taking slots
+    /// other sessions are waiting for. Does nothing without a limit.
+    #[must_use]
+    pub fn with_fair_share(mut self) -> Self {
+        if let Some(limit) = self.config.max_concurrent_requests {
+            self.limiter = None;
+            self.fair_share = Some(FairShareLimiter::new(limit));
+        }
+        self
+    }
+
+    /// A clone whose requests count against `session`'s share of the limit,
+    /// `weight` times that of a session of weight one.
+    #[must_use]
+    pub fn for_session(&self, session: &str, weight: u32) -> Self {
+        if let Some(ref fair_share) = self.fair_share {
+            fair_share.set_weight(session, weight);
+        }
+        Self {
+            session: Some(Arc::from(session)),
+            ..self.clone()
+        }
+    }
+
+    /// Session the client's requests count against, if bound to one.
+    #[must_use]
+    pub fn session(&self) -> Option<&str> {
+        self.session.as_deref()
+    }
+
+    /// Fair share limiter of the client, if the limit is shared by session.
+    #[must_use]
+    pub fn fair_share(&self) -> Option<&FairShareLimiter> {
+        self.fair_share.as_ref()
+    }
+
+    /// Get the in-flight request limit, if any.
+    #[must_use]
+    pub fn max_concurrency(&self) -> Option<usize> {
+        self.config.max_concurrent_requests
+    }
+
+    /// Get aggregate latency and error statistics.
+    #[must_use]
+    pub fn stats(&self) -> AiStats {
+        let mut stats = self.stats.snapshot();
+        if let Some(ref fair_share) = self.fair_share {
+            let queued = match self.session {
+                Some(ref session) => fair_share.queue_depth(session),
+                None => fair_share.total_queued(),
+            };
+            stats.queued = queued as u64;
+        }
+        stats
+    }
+
+    /// Send one request to the provider, within the concurrency limit.
+    pub(crate) async fn generate(&self, system: &str, user: &str) -> Result<String, AiError> {
+        let request = self.stats.start();
+        let _slot = match self.fair_share {
+            Some(ref fair_share) => Some(
+                fair_share
+                    .acquire(self.session.as_deref().unwrap_or(UNBOUND_SESSION))
+                    .await,
+            ),
+            None => None,
+        };
+        let _permit = match self.limiter {
+            // The semaphore is never closed
+            Some(ref limiter) => limiter.acquire().await.ok(),
+            None => None,
+        };
+        let result = self.provider.generate(system, user).await;
+        request.finish(&result);
+        result
     }
 
     /// Create client from configuration.
@@ -366,7 +801,7 @@ impl AiClient {
             )?),
         };
 
-        Ok(Self { provider, config })
+        Ok(Self::new(provider, config))
     }
 
     /// Create client from environment variables with default config.
@@ -414,10 +849,53 @@ impl AiClient {
     /// Returns `AiError::RequestFailed` if the API is unreachable.
     /// Returns `AiError::Timeout` if the request times out.
     pub async fn test_connection(&self) -> Result<(), AiError> {
-        self.provider
-            .generate("Respond with OK", "ping")
-            .await
-            .map(|_| ())
+        self.generate("Respond with OK", "ping").await.map(|_| ())
+    }
+
+    /// List the models available from the provider.
+    ///
+    /// # Errors
+    ///
+    /// Returns `AiError::Unsupported` if the endpoint has no model listing.
+    /// Returns `AiError::RequestFailed` if the API request fails.
+    pub async fn list_models(&self) -> Result<Vec<String>, AiError> {
+        self.provider.list_models().await
+    }
+
+    /// Whether `listed`, a model from [`list_models`](Self::list_models),
+    /// is the configured model.
+    ///
+    /// Aliases match their dated versions, so `claude-sonnet-4-5` matches
+    /// `claude-sonnet-4-5-20250929`.
+    #[must_use]
+    pub fn model_matches(&self, listed: &str) -> bool {
+        let model = self.model().trim_start_matches("models/");
+        listed == model
+            || listed
+                .strip_prefix(model)
+                .is_some_and(|rest| rest.starts_with('-'))
+    }
+
+    /// Check that the configured model exists at the provider.
+    ///
+    /// Looks the model up in the provider's model list or, if the endpoint
+    /// cannot list models, sends the model a one-token request.
+    ///
+    /// # Errors
+    ///
+    /// Returns `AiError::UnknownModel` if the model does not exist, naming
+    /// the closest available model.
+    /// Returns `AiError::RequestFailed` if the API request fails.
+    pub async fn validate_model(&self) -> Result<(), AiError> {
+        match self.provider.list_models().await {
+            Ok(models) if models.iter().any(|m| self.model_matches(m)) => Ok(()),
+            Ok(models) => Err(AiError::UnknownModel(unknown_model_message(
+                self.model(),
+                &models,
+            ))),
+            Err(AiError::Unsupported(_)) => self.provider.ping().await,
+            Err(e) => Err(e),
+        }
     }
 
     /// Ask the AI supervisor whether to allow a tool call.
@@ -438,7 +916,6 @@ impl AiClient {
         );
 
         let text = self
-            .provider
             .generate(SUPERVISOR_SYSTEM_PROMPT, &user_message)
             .await?;
 
@@ -446,6 +923,50 @@ impl AiClient {
     }
 }
 
+/// Describe `model` missing from `available`, suggesting the closest match.
+///
+/// Dated versions are suggested by their alias, like `claude-sonnet-4-5`
+/// for `claude-sonnet-4-5-20250929`.
+fn unknown_model_message(model: &str, available: &[String]) -> String {
+    let closest = available
+        .iter()
+        .map(|candidate| strip_date_suffix(candidate))
+        .map(|candidate| (edit_distance(model, candidate), candidate))
+        .filter(|(distance, _)| *distance <= model.len().max(6) / 3)
+        .min_by_key(|(distance, _)| *distance);
+    match closest {
+        Some((_, candidate)) => {
+            format!("'{model}' is not offered by the provider; did you mean '{candidate}'?")
+        }
+        None => format!(
+            "'{model}' is not offered by the provider; run `claude-supervisor ai models` to list models"
+        ),
+    }
+}
+
+/// `model` without a trailing `-YYYYMMDD` version date.
+fn strip_date_suffix(model: &str) -> &str {
+    match model.rsplit_once('-') {
+        Some((alias, date)) if date.len() == 8 && date.bytes().all(|b| b.is_ascii_digit()) => alias,
+        _ => model,
+    }
+}
+
+/// Levenshtein distance between `a` and `b`, by characters.
+pub(crate) fn edit_distance(a: &str, b: &str) -> usize {
+    let b: Vec<char> = b.chars().collect();
+    let mut previous: Vec<usize> = (0..=b.len()).collect();
+    for (i, ca) in a.chars().enumerate() {
+        let mut current = vec![i + 1];
+        for (j, cb) in b.iter().enumerate() {
+            let substitute = previous[j] + usize::from(ca != *cb);
+            current.push(substitute.min(previous[j + 1] + 1).min(current[j] + 1));
+        }
+        previous = current;
+    }
+    previous[b.len()]
+}
+
 /// Extract a JSON object from AI response text.
 ///
 /// Looks for JSON in the response and parses it into the specified type.
@@ -489,6 +1010,130 @@ fn extract_decision(text: &str) -> Result<SupervisorDecision, AiError> {
 #[cfg(test)]
 mod tests {
     use super::*;
+    use axum::http::StatusCode as HttpStatus;
+    use axum::routing::{get, post};
+    use axum::{Json, Router};
+
+    /// Mock Claude API whose requests park until the gate is opened.
+    struct ParkedProvider {
+        base_url: String,
+        gate: Arc<Semaphore>,
+        current: Arc<AtomicU64>,
+        peak: Arc<AtomicU64>,
+    }
+
+    async fn spawn_parked_provider() -> ParkedProvider {
+        let gate = Arc::new(Semaphore::new(0));
+        let current = Arc::new(AtomicU64::new(0));
+        let peak = Arc::new(AtomicU64::new(0));
+        let (handler_gate, handler_current, handler_peak) =
+            (Arc::clone(&gate), Arc::clone(&current), Arc::clone(&peak));
+        let app = Router::new().route(
+            "/v1/messages",
+            post(move || {
+                let (gate, current, peak) = (
+                    Arc::clone(&handler_gate),
+                    Arc::clone(&handler_current),
+                    Arc::clone(&handler_peak),
+                );
+                async move {
+                    let now = current.fetch_add(1, Ordering::SeqCst) + 1;
+                    peak.fetch_max(now, Ordering::SeqCst);
+                    gate.acquire().await.unwrap().forget();
+                    current.fetch_sub(1, Ordering::SeqCst);
+                    Json(serde_json::json!({
+                        "content": [{"text": r#"{"decision": "ALLOW", "reason": "ok"}"#}]
+                    }))
+                }
+            }),
+        );
+        let listener = tokio::net::TcpListener::bind("127.0.0.1:0").await.unwrap();
+        let addr = listener.local_addr().unwrap();
+        tokio::spawn(async move { axum::serve(listener, app).await.unwrap() });
+        ParkedProvider {
+            base_url: format!("http://{addr}"),
+            gate,
+            current,
+            peak,
+        }
+    }
+
+    fn client_for(base_url: &str) -> AiClient {
+        let provider = ClaudeProvider::new(
+            base_url.to_string(),
+            "test-key".to_string(),
+            "claude-test".to_string(),
+            64,
+        )
+        .unwrap();
+        AiClient::new(Provider::Claude(provider), AiConfig::default())
+    }
+
+    /// Wait until `count` requests are parked at the provider.
+    async fn wait_for_parked(provider: &ParkedProvider, count: u64) {
+        for _ in 0..200 {
+            if provider.current.load(Ordering::SeqCst) >= count {
+                return;
+            }
+            tokio::time::sleep(Duration::from_millis(10)).await;
+        }
+        panic!("requests never reached the provider");
+    }
+
+    #[tokio::test]
+    async fn test_concurrency_limit_bounds_in_flight_requests() {
+        let provider = spawn_parked_provider().await;
+        let client = Arc::new(client_for(&provider.base_url).with_max_concurrency(2));
+
+        let mut calls = tokio::task::JoinSet::new();
+        for _ in 0..5 {
+            let client = Arc::clone(&client);
+            calls.spawn(async move {
+                client
+                    .ask_supervisor("Bash", &serde_json::json!({"command": "ls"}), "")
+                    .await
+            });
+        }
+
+        wait_for_parked(&provider, 2).await;
+        tokio::time::sleep(Duration::from_millis(100)).await;
+        assert_eq!(provider.current.load(Ordering::SeqCst), 2);
+        assert_eq!(client.stats().in_flight, 5);
+
+        provider.gate.add_permits(5);
+        while let Some(result) = calls.join_next().await {
+            assert!(matches!(
+                result.unwrap(),
+                Ok(SupervisorDecision::Allow { .. })
+            ));
+        }
+
+        assert_eq!(provider.peak.load(Ordering::SeqCst), 2);
+        let stats = client.stats();
+        assert_eq!(stats.requests, 5);
+        assert_eq!(stats.errors, 0);
+        assert_eq!(stats.in_flight, 0);
+        assert!(stats.mean_latency_ms().is_some());
+    }
+
+    #[tokio::test]
+    async fn test_abandoned_request_counts_as_timeout() {
+        let provider = spawn_parked_provider().await;
+        let client = client_for(&provider.base_url);
+        let shared = client.clone();
+        let input = serde_json::json!({});
+
+        let call = shared.ask_supervisor("Read", &input, "");
+        assert!(tokio::time::timeout(Duration::from_millis(200), call)
+            .await
+            .is_err());
+
+        let stats = client.stats();
+        assert_eq!(stats.requests, 1);
+        assert_eq!(stats.timeouts, 1);
+        assert_eq!(stats.in_flight, 0);
+        provider.gate.add_permits(1);
+    }
 
     #[test]
     fn test_http_client_has_timeouts() {
@@ -527,6 +1172,162 @@ mod tests {
         assert_eq!(provider.config.max_tokens, 2048);
     }
 
+    async fn serve(app: Router) -> String {
+        let listener = tokio::net::TcpListener::bind("127.0.0.1:0").await.unwrap();
+        let addr = listener.local_addr().unwrap();
+        tokio::spawn(async move { axum::serve(listener, app).await.unwrap() });
+        format!("http://{addr}")
+    }
+
+    fn gemini_client(base_url: &str, model: &str) -> AiClient {
+        let provider = GeminiProvider::new(
+            base_url.to_string(),
+            "test-key".to_string(),
+            model.to_string(),
+            64,
+        )
+        .unwrap();
+        let config = AiConfig {
+            model: model.to_string(),
+            ..AiConfig::default()
+        };
+        AiClient::new(Provider::Gemini(provider), config)
+    }
+
+    fn claude_client(base_url: &str, model: &str) -> AiClient {
+        let provider = ClaudeProvider::new(
+            base_url.to_string(),
+            "test-key".to_string(),
+            model.to_string(),
+            64,
+        )
+        .unwrap();
+        let config = AiConfig {
+            provider: ProviderKind::Claude,
+            model: model.to_string(),
+            ..AiConfig::default()
+        };
+        AiClient::new(Provider::Claude(provider), config)
+    }
+
+    async fn gemini_models() -> String {
+        serve(Router::new().route(
+            "/models",
+            get(|| async {
+                Json(serde_json::json!({
+                    "models": [
+                        {"name": "models/gemini-3-flash"},
+                        {"name": "models/gemini-3-pro"}
+                    ]
+                }))
+            }),
+        ))
+        .await
+    }
+
+    #[tokio::test]
+    async fn test_gemini_lists_and_validates_models() {
+        let base_url = gemini_models().await;
+        let client = gemini_client(&base_url, "gemini-3-flash");
+        assert_eq!(
+            client.list_models().await.unwrap(),
+            vec!["gemini-3-flash", "gemini-3-pro"]
+        );
+        client.validate_model().await.unwrap();
+    }
+
+    #[tokio::test]
+    async fn test_gemini_typo_suggests_closest_model() {
+        let base_url = gemini_models().await;
+        let client = gemini_client(&base_url, "gemini-3-flasj");
+        let err = client.validate_model().await.unwrap_err();
+        assert!(matches!(err, AiError::UnknownModel(_)), "{err}");
+        assert!(
+            err.to_string().contains("did you mean 'gemini-3-flash'?"),
+            "{err}"
+        );
+
+        let client = gemini_client(&base_url, "llama-70b");
+        let err = client.validate_model().await.unwrap_err();
+        assert!(!err.to_string().contains("did you mean"), "{err}");
+    }
+
+    #[tokio::test]
+    async fn test_claude_validates_aliases_across_pages() {
+        let app = Router::new().route(
+            "/v1/models",
+            get(
+                |axum::extract::Query(query): axum::extract::Query<
+                    std::collections::HashMap<String, String>,
+                >| async move {
+                    let page = if query.contains_key("after_id") {
+                        serde_json::json!({
+                            "data": [{"id": "claude-sonnet-4-5-20250929"}],
+                            "has_more": false,
+                            "last_id": "claude-sonnet-4-5-20250929"
+                        })
+                    } else {
+                        serde_json::json!({
+                            "data": [{"id": "claude-haiku-4-5-20251001"}],
+                            "has_more": true,
+                            "last_id": "claude-haiku-4-5-20251001"
+                        })
+                    };
+                    Json(page)
+                },
+            ),
+        );
+        let base_url = serve(app).await;
+
+        let client = claude_client(&base_url, "claude-sonnet-4-5");
+        assert_eq!(client.list_models().await.unwrap().len(), 2);
+        client.validate_model().await.unwrap();
+
+        let client = claude_client(&base_url, "claude-sonet-4-5");
+        let err = client.validate_model().await.unwrap_err();
+        assert!(
+            err.to_string()
+                .contains("did you mean 'claude-sonnet-4-5'?"),
+            "{err}"
+        );
+    }
+
+    #[tokio::test]
+    async fn test_claude_pings_without_model_listing() {
+        let app = Router::new().route(
+            "/v1/messages",
+            post(|Json(body): Json<serde_json::Value>| async move {
+                assert_eq!(body["max_tokens"], 1);
+                if body["model"] == "claude-test" {
+                    (HttpStatus::OK, Json(serde_json::json!({"content": []})))
+                } else {
+                    (
+                        HttpStatus::NOT_FOUND,
+                        Json(serde_json::json!({"error": {"type": "not_found_error"}})),
+                    )
+                }
+            }),
+        );
+        let base_url = serve(app).await;
+
+        claude_client(&base_url, "claude-test")
+            .validate_model()
+            .await
+            .unwrap();
+        let err = claude_client(&base_url, "claude-tset")
+            .validate_model()
+            .await
+            .unwrap_err();
+        assert!(matches!(err, AiError::UnknownModel(_)), "{err}");
+    }
+
+    #[test]
+    fn test_edit_distance() {
+        assert_eq!(edit_distance("gemini-flasj", "gemini-flash"), 1);
+        assert_eq!(edit_distance("", "abc"), 3);
+        assert_eq!(edit_distance("kitten", "sitting"), 3);
+    }
+
     #[tokio::test]
     async fn test_retry_on_server_error() {
         // Test that should_retry correctly identifies retryable errors
@@ -627,6 +1428,7 @@ mod tests {
             max_tokens: 1024,
             base_url: "http://localhost:8045/v1beta".to_string(),
             api_key_env: "NONEXISTENT_TEST_KEY_12345".to_string(),
+            max_concurrent_requests: None,
         };
 
         let result = AiClient::from_config(config);
@@ -646,6 +1448,7 @@ mod tests {
             max_tokens: 1024,
             base_url: "http://localhost:8045/v1beta".to_string(),
             api_key_env: "TEST_GEMINI_KEY".to_string(),
+            max_concurrent_requests: None,
         };
         let client = AiClient::from_config(config).unwrap();
         assert!(matches!(client.provider, Provider::Gemini(_)));
@@ -662,10 +1465,12 @@ mod tests {
             max_tokens: 2048,
             base_url: "https://api.anthropic.com".to_string(),
             api_key_env: "TEST_CLAUDE_KEY".to_string(),
+            max_concurrent_requests: Some(4),
         };
         let client = AiClient::from_config(config).unwrap();
         assert!(matches!(client.provider, Provider::Claude(_)));
         assert_eq!(client.model(), "claude-sonnet-4-20250514");
+        assert_eq!(client.max_concurrency(), Some(4));
         std::env::remove_var("TEST_CLAUDE_KEY");
     }
 
@@ -0,0 +1,293 @@
+//! Terminal approval prompts for escalated tool calls.
+//!
+//! The simplest human in the loop: each escalation prints the tool, its
+//! input and the escalation reason, then waits for `[a]llow / [d]eny /
+//! [g]uide`. A guide answer asks for a one-line message and denies the call
+//! with it as the reason. Without an answer in time the configured default
+//! decision is taken.
+//!
+//! The runner awaits the decision before handling further events, so
+//! Claude's output is held back while the prompt is shown.
+
+use std::fmt;
+use std::io::{self, BufRead, Write};
+use std::sync::Arc;
+use std::time::Duration;
+
+use tokio::sync::{mpsc, Mutex};
+
+use super::{AiError, EscalationRequest, SupervisorDecision};
+use crate::config::{ApprovalDefault, InteractiveConfig};
+use crate::display;
+
+type SharedWriter = Arc<std::sync::Mutex<Box<dyn Write + Send>>>;
+
+/// Asks the operator to decide escalations.
+///
+/// Input is read on a dedicated thread, so an unanswered prompt does not
+/// leave a blocked read behind; an answer typed after the timeout goes to
+/// the next prompt.
+#[derive(Clone)]
+pub struct InteractiveApprover {
+    lines: Arc<Mutex<mpsc::UnboundedReceiver<String>>>,
+    out: SharedWriter,
+    timeout: Duration,
+    default: ApprovalDefault,
+    max_input_bytes: usize,
+}
+
+impl fmt::Debug for InteractiveApprover {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.debug_struct("InteractiveApprover")
+            .field("timeout", &self.timeout)
+            .field("default", &self.default)
+            .field("max_input_bytes", &self.max_input_bytes)
+            .finish_non_exhaustive()
+    }
+}
+
+impl InteractiveApprover {
+    /// Prompt on `writer` and read answers from `reader`.
+    pub fn new(
+        reader: impl BufRead + Send + 'static,
+        writer: impl Write + Send + 'static,
+        config: &InteractiveConfig,
+    ) -> Self {
+        let (tx, rx) = mpsc::unbounded_channel();
+        std::thread::spawn(move || {
+            for line in reader.lines() {
+                let Ok(line) = line else { break };
+                if tx.send(line).is_err() {
+                    break;
+                }
+            }
+        });
+        Self {
+            lines: Arc::new(Mutex::new(rx)),
+            out: Arc::new(std::sync::Mutex::new(Box::new(writer))),
+            timeout: Duration::from_secs(config.timeout_secs),
+            default: config.default,
+            max_input_bytes: config.max_input_bytes,
+        }
+    }
+
+    /// Prompt on stderr and read answers from stdin.
+    #[must_use]
+    pub fn stdio(config: &InteractiveConfig) -> Self {
+        Self::new(io::BufReader::new(io::stdin()), io::stderr(), config)
+    }
+
+    /// How long a prompt waits for an answer.
+    #[must_use]
+    pub fn timeout(&self) -> Duration {
+        self.timeout
+    }
+
+    /// Prompt for a decision on `request`.
+    ///
+    /// # Errors
+    ///
+    /// Returns `AiError::RequestFailed` if the prompt cannot be written.
+    pub async fn decide(&self, request: &EscalationRequest) -> Result<SupervisorDecision, AiError> {
+        let mut lines = self.lines.lock().await;
+        self.write(&self.render(request))?;
+        match tokio::time::timeout(self.timeout, self.read_answer(&mut lines)).await {
+            Ok(Ok(Some(decision))) => Ok(decision),
+            Ok(Ok(None)) => {
+                self.write("\nInput closed.\n")?;
+                Ok(self.fallback("Input closed before an answer"))
+            }
+            Ok(Err(e)) => Err(e),
+            Err(_) => {
+                self.write(&format!(
+                    "\nNo answer within {}s; {}.\n",
+                    self.timeout.as_secs(),
+                    match self.default {
+                        ApprovalDefault::Allow => "allowing",
+                        ApprovalDefault::Deny => "denying",
+                    }
+                ))?;
+                Ok(self.fallback(&format!("No answer within {}s", self.timeout.as_secs())))
+            }
+        }
+    }
+
+    fn render(&self, request: &EscalationRequest) -> String {
+        let input = serde_json::to_string_pretty(&request.input)
+            .unwrap_or_else(|_| request.input.to_string());
+        format!(
+            "\n== Escalation: {} ==\nReason: {}\nInput:\n{}\n[a]llow / [d]eny / [g]uide? ",
+            request.tool,
+            request.reason,
+            display::truncate_bytes(&input, self.max_input_bytes)
+        )
+    }
+
+    /// Read lines until one is a valid answer; `None` if input closes first.
+    async fn read_answer(
+        &self,
+        lines: &mut mpsc::UnboundedReceiver<String>,
+    ) -> Result<Option<SupervisorDecision>, AiError> {
+        loop {
+            let Some(line) = lines.recv().await else {
+                return Ok(None);
+            };
+            match line.trim().to_lowercase().as_str() {
+                "a" | "allow" => {
+                    return Ok(Some(SupervisorDecision::Allow {
+                        reason: "Allowed by operator".to_string(),
+                    }))
+                }
+                "d" | "deny" => {
+                    return Ok(Some(SupervisorDecision::Deny {
+                        reason: "Denied by operator".to_string(),
+                    }))
+                }
+                "g" | "guide" => {
+                    self.write("Guidance: ")?;
+                    let Some(guidance) = lines.recv().await else {
+                        return Ok(None);
+                    };
+                    let guidance = guidance.trim();
+                    let reason = if guidance.is_empty() {
+                        "Denied by operator".to_string()
+                    } else {
+                        guidance.to_string()
+                    };
+                    return Ok(Some(SupervisorDecision::Deny { reason }));
+                }
+                _ => self.write("Please answer a, d or g: ")?,
+            }
+        }
+    }
+
+    fn fallback(&self, why: &str) -> SupervisorDecision {
+        let reason = format!("{why}; default decision");
+        match self.default {
+            ApprovalDefault::Allow => SupervisorDecision::Allow { reason },
+            ApprovalDefault::Deny => SupervisorDecision::Deny { reason },
+        }
+    }
+
+    fn write(&self, text: &str) -> Result<(), AiError> {
+        let mut out = self
+            .out
+            .lock()
+            .map_err(|_| AiError::RequestFailed("Prompt output poisoned".to_string()))?;
+        out.write_all(text.as_bytes())
+            .and_then(|()| out.flush())
+            .map_err(|e| AiError::RequestFailed(format!("Failed to write prompt: {e}")))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::io::Cursor;
+
+    #[derive(Clone, Default)]
+    struct Output(Arc<std::sync::Mutex<Vec<u8>>>);
+
+    impl Write for Output {
+        fn write(&mut self, buf: &[u8]) -> io::Result<usize> {
+            self.0.lock().unwrap().extend_from_slice(buf);
+            Ok(buf.len())
+        }
+
+        fn flush(&mut self) -> io::Result<()> {
+            Ok(())
+        }
+    }
+
+    impl Output {
+        fn text(&self) -> String {
+            String::from_utf8(self.0.lock().unwrap().clone()).unwrap()
+        }
+    }
+
+    fn request() -> EscalationRequest {
+        EscalationRequest {
+            session: None,
+            task: None,
+            tool: "Bash".to_string(),
+            input: serde_json::json!({"command": "curl https://example.com"}),
+            reason: "Network access".to_string(),
+            context: String::new(),
+        }
+    }
+
+    fn approver(input: &'static str, config: &InteractiveConfig) -> (InteractiveApprover, Output) {
+        let out = Output::default();
+        let approver = InteractiveApprover::new(Cursor::new(input), out.clone(), config);
+        (approver, out)
+    }
+
+    #[tokio::test]
+    async fn test_allow_answer() {
+        let (approver, out) = approver("a\n", &InteractiveConfig::default());
+        let decision = approver.decide(&request()).await.unwrap();
+        assert!(matches!(decision, SupervisorDecision::Allow { .. }));
+
+        let prompt = out.text();
+        assert!(prompt.contains("== Escalation: Bash =="));
+        assert!(prompt.contains("Reason: Network access"));
+        assert!(prompt.contains("curl https://example.com"));
+        assert!(prompt.ends_with("[a]llow / [d]eny / [g]uide? "));
+    }
+
+    #[tokio::test]
+    async fn test_guide_denies_with_message() {
+        let (approver, out) = approver(
+            "maybe\nG\nUse the vendored copy instead\n",
+            &InteractiveConfig::default(),
+        );
+        let decision = approver.decide(&request()).await.unwrap();
+        assert_eq!(
+            decision,
+            SupervisorDecision::Deny {
+                reason: "Use the vendored copy instead".to_string()
+            }
+        );
+        assert!(out.text().contains("Please answer a, d or g: "));
+        assert!(out.text().contains("Guidance: "));
+    }
+
+    #[tokio::test]
+    async fn test_closed_input_takes_default() {
+        let config = InteractiveConfig {
+            default: ApprovalDefault::Allow,
+            ..InteractiveConfig::default()
+        };
+        let (approver, _) = approver("", &config);
+        let decision = approver.decide(&request()).await.unwrap();
+        assert!(matches!(decision, SupervisorDecision::Allow { .. }));
+    }
+
+    #[tokio::test]
+    async fn test_timeout_takes_default() {
+        let (reader, _writer) = io::pipe().unwrap();
+        let config = InteractiveConfig {
+            timeout_secs: 0,
+            ..InteractiveConfig::default()
+        };
+        let out = Output::default();
+        let approver = InteractiveApprover::new(io::BufReader::new(reader), out.clone(), &config);
+
+        let decision = approver.decide(&request()).await.unwrap();
+        assert!(matches!(decision, SupervisorDecision::Deny { .. }));
+        assert!(out.text().contains("No answer within 0s; denying."));
+    }
+
+    #[tokio::test]
+    async fn test_long_input_is_truncated() {
+        let config = InteractiveConfig {
+            max_input_bytes: 16,
+            ..InteractiveConfig::default()
+        };
+        let (approver, out) = approver("d\n", &config);
+        let mut request = request();
+        request.input = serde_json::json!({"content": "x".repeat(1000)});
+        approver.decide(&request).await.unwrap();
+        assert!(!out.text().contains(&"x".repeat(100)));
+    }
+}
@@ -1,16 +1,30 @@
 //! AI client module for supervisor decisions.
 
+mod backend;
 mod boss;
 mod client;
 mod context;
+mod interactive;
 mod prompts;
+mod quota;
+mod redact;
+mod review;
+mod truncation;
 
+pub use backend::{DecisionBackend, EscalationRequest, WebhookBackend};
 pub use boss::{
     format_boss_prompt, format_stop_boss_prompt, BossDecision, BOSS_SYSTEM_PROMPT, STOP_BOSS_PROMPT,
 };
 pub use client::*;
 pub use context::ContextCompressor;
+pub use interactive::InteractiveApprover;
 pub use prompts::{
-    format_tool_review, format_tool_review_with_context, SupervisorContext,
-    SUPERVISOR_SYSTEM_PROMPT,
+    format_progress_summary, format_recovery_prompt, format_review_chunk, format_review_merge,
+    format_suggestion_polish, format_tool_review, format_tool_review_with_context, PriorDenial,
+    SupervisorContext, MAX_PRIOR_DENIALS, PROGRESS_SUMMARY_PROMPT, REVIEW_MERGE_PROMPT,
+    REVIEW_SYSTEM_PROMPT, SUGGESTION_POLISH_PROMPT, SUPERVISOR_SYSTEM_PROMPT,
 };
+pub use quota::*;
+pub use redact::{RedactError, Redactor, MIN_LITERAL_SECRET_LEN};
+pub use review::*;
+pub use truncation::*;
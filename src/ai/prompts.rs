@@ -1,5 +1,12 @@
 //! System prompts for the AI supervisor.
 
+use chrono::{DateTime, Utc};
+
+use super::Redactor;
+
+/// Most prior denials included in an escalation's context.
+pub const MAX_PRIOR_DENIALS: usize = 10;
+
 /// System prompt for the AI supervisor.
 pub const SUPERVISOR_SYSTEM_PROMPT: &str = r#"You are a security supervisor monitoring Claude Code execution.
 
@@ -62,6 +69,19 @@ Tool: Bash, Input: {"command": "git push --force"}
 
 Always respond with ONLY the JSON object, no additional text."#;
 
+/// A tool call denied earlier in the session.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct PriorDenial {
+    /// Name of the denied tool.
+    pub tool: String,
+    /// Summary of the tool input.
+    pub input: String,
+    /// Why the call was denied.
+    pub reason: String,
+    /// When the call was denied.
+    pub at: DateTime<Utc>,
+}
+
 /// Context for the AI supervisor to make decisions.
 #[derive(Debug, Clone, Default)]
 pub struct SupervisorContext {
@@ -73,6 +93,12 @@ pub struct SupervisorContext {
     pub recent_tools: Vec<String>,
     /// Session ID for tracking.
     pub session_id: Option<String>,
+    /// Most recent denials of the session, oldest first.
+    pub prior_denials: Vec<PriorDenial>,
+    /// Escalations of the session so far, including this one.
+    pub escalations: usize,
+    /// Redactor applied to the built context.
+    pub redactor: Option<Redactor>,
 }
 
 impl SupervisorContext {
@@ -110,6 +136,29 @@ impl SupervisorContext {
         self
     }
 
+    /// Add the session's prior denials, keeping the last [`MAX_PRIOR_DENIALS`].
+    #[must_use]
+    pub fn with_prior_denials(mut self, denials: impl IntoIterator<Item = PriorDenial>) -> Self {
+        self.prior_denials.extend(denials);
+        let excess = self.prior_denials.len().saturating_sub(MAX_PRIOR_DENIALS);
+        self.prior_denials.drain(..excess);
+        self
+    }
+
+    /// Set the number of escalations so far, including this one.
+    #[must_use]
+    pub fn with_escalations(mut self, escalations: usize) -> Self {
+        self.escalations = escalations;
+        self
+    }
+
+    /// Redact secrets from the built context.
+    #[must_use]
+    pub fn with_redactor(mut self, redactor: Redactor) -> Self {
+        self.redactor = Some(redactor);
+        self
+    }
+
     /// Build the context string for the AI supervisor.
     #[must_use]
     pub fn build(&self) -> String {
@@ -131,8 +180,37 @@ impl SupervisorContext {
             parts.push(format!("Session: {session_id}"));
         }
 
+        if self.escalations > 1 {
+            parts.push(format!(
+                "Escalations This Session: {} (including this one)",
+                self.escalations
+            ));
+        }
+
+        if !self.prior_denials.is_empty() {
+            let denials: Vec<String> = self
+                .prior_denials
+                .iter()
+                .map(|denial| {
+                    format!(
+                        "- [{}] {} {}: {}",
+                        denial.at.format("%H:%M:%S"),
+                        denial.tool,
+                        denial.input,
+                        denial.reason
+                    )
+                })
+                .collect();
+            parts.push(format!(
+                "Prior Denials This Session (do not allow near-identical variants):\n{}",
+                denials.join("\n")
+            ));
+        }
+
         if parts.is_empty() {
             "No additional context available".to_string()
+        } else if let Some(ref redactor) = self.redactor {
+            redactor.redact(&parts.join("\n")).into_owned()
         } else {
             parts.join("\n")
         }
@@ -174,6 +252,139 @@ Evaluate this tool call and respond with a JSON decision.",
     )
 }
 
+/// System prompt for reviewing one part of a finished session.
+///
+/// A variant of the boss prompt: the reviewer manages rather than codes.
+pub const REVIEW_SYSTEM_PROMPT: &str = r#"You are a project manager AI reviewing a finished Claude Code session. You do NOT write code.
+
+You are given the original task and a compressed transcript of the session, or one part of it.
+Lines are prefixed with [TOOL], [TOOL_OK], [TOOL_ERROR], [ASSISTANT] and [RESULT].
+
+## Assess
+
+1. Goal achievement: was the task done, partly done, or not done, and what is the evidence?
+2. Unnecessary detours: work that did not move the task forward, such as repeated failing commands or unrelated edits.
+3. Risky actions: destructive commands, writes outside the project, secrets handling, force pushes.
+4. Suggested CLAUDE.md additions: project conventions or facts that would have avoided the detours or risks.
+
+Only report what the transcript shows. Empty lists are fine.
+
+## Response Format
+
+{"goal_achievement": "Assessment with evidence", "detours": ["..."], "risky_actions": ["..."], "claude_md_additions": [{"topic": "Short topic", "text": "Line to add to CLAUDE.md"}]}
+
+Always respond with ONLY the JSON object."#;
+
+/// System prompt for merging the reviews of a session's parts.
+pub const REVIEW_MERGE_PROMPT: &str = r#"You are a project manager AI merging reviews of consecutive parts of one Claude Code session.
+
+Combine them into a single review of the whole session:
+- Goal achievement is judged on the session as a whole; later parts supersede earlier ones.
+- Merge duplicate detours, risky actions and CLAUDE.md additions.
+
+## Response Format
+
+{"goal_achievement": "Assessment with evidence", "detours": ["..."], "risky_actions": ["..."], "claude_md_additions": [{"topic": "Short topic", "text": "Line to add to CLAUDE.md"}]}
+
+Always respond with ONLY the JSON object."#;
+
+/// System prompt for rewording CLAUDE.md additions derived from a session.
+pub const SUGGESTION_POLISH_PROMPT: &str = r#"You edit CLAUDE.md, the file of project conventions Claude Code reads at the start of every session.
+
+You are given numbered CLAUDE.md additions derived from what went wrong in one session, each with the evidence behind it.
+Reword each addition as one short, concrete instruction in the imperative, keeping every command, path and name exactly as given.
+Keep the additions in the same order and do not add, merge or drop any.
+
+## Response Format
+
+{"claude_md_additions": [{"topic": "Short topic", "text": "Line to add to CLAUDE.md"}]}
+
+Always respond with ONLY the JSON object."#;
+
+/// System prompt for summarizing a session that ran out of context.
+pub const PROGRESS_SUMMARY_PROMPT: &str = r"You summarize the progress of a Claude Code session that ran out of context, so a fresh session can pick up the work.
+
+You are given the original task and a compressed log of the session.
+Lines are prefixed with [TOOL], [TOOL_OK], [TOOL_ERROR], [ASSISTANT] and [RESULT].
+
+Write a short plain-text summary: what has been done, which files were changed, what failed, and what is left to do. Only report what the log shows.";
+
+/// Format a compressed session log for [`PROGRESS_SUMMARY_PROMPT`].
+#[must_use]
+pub fn format_progress_summary(task: &str, activity: &str) -> String {
+    format!(
+        r"Task: {task}
+
+Session log:
+{activity}
+
+Summarize the progress of this session."
+    )
+}
+
+/// Prompt for a fresh session continuing one that ran out of context.
+#[must_use]
+pub fn format_recovery_prompt(task: &str, summary: &str) -> String {
+    format!(
+        r"{task}
+
+A previous session working on this task ran out of context. Continue from where it stopped; do not redo finished work.
+
+Progress so far:
+{summary}"
+    )
+}
+
+/// Format one part of a session transcript for review.
+#[must_use]
+pub fn format_review_chunk(task: &str, part: usize, parts: usize, transcript: &str) -> String {
+    format!(
+        r"Task: {task}
+
+Transcript (part {part} of {parts}):
+{transcript}
+
+Review this part of the session and respond with a JSON critique."
+    )
+}
+
+/// Format the reviews of a session's parts for the merge pass.
+#[must_use]
+pub fn format_review_merge(task: &str, critiques: &[String]) -> String {
+    let reviews: Vec<String> = critiques
+        .iter()
+        .enumerate()
+        .map(|(i, critique)| format!("Part {}:\n{critique}", i + 1))
+        .collect();
+    format!(
+        r"Task: {task}
+
+{reviews}
+
+Merge these reviews and respond with a single JSON critique.",
+        reviews = reviews.join("\n\n")
+    )
+}
+
+/// Format numbered CLAUDE.md additions, as `(topic, text, evidence)`, for
+/// [`SUGGESTION_POLISH_PROMPT`].
+#[must_use]
+pub fn format_suggestion_polish<'a>(
+    additions: impl IntoIterator<Item = (&'a str, &'a str, &'a str)>,
+) -> String {
+    let additions: Vec<String> = additions
+        .into_iter()
+        .enumerate()
+        .map(|(i, (topic, text, evidence))| {
+            format!("{}. [{topic}] {text}\n   Evidence: {evidence}", i + 1)
+        })
+        .collect();
+    format!(
+        "{}\n\nReword these additions and respond with the JSON object.",
+        additions.join("\n")
+    )
+}
+
 #[cfg(test)]
 mod tests {
     use super::*;
@@ -206,6 +417,63 @@ mod tests {
         assert!(result.contains("Session: abc123"));
     }
 
+    fn denial(i: usize) -> PriorDenial {
+        PriorDenial {
+            tool: "Bash".to_string(),
+            input: format!("command=rm -rf build{i}"),
+            reason: "Blocked destructive command".to_string(),
+            at: DateTime::parse_from_rfc3339("2026-01-30T12:00:00Z")
+                .unwrap()
+                .with_timezone(&Utc),
+        }
+    }
+
+    #[test]
+    fn test_supervisor_context_prior_denials() {
+        let context = SupervisorContext::new()
+            .with_task("Clean up")
+            .with_prior_denials([denial(1)])
+            .with_escalations(3);
+
+        let result = context.build();
+        assert!(result.contains("Escalations This Session: 3 (including this one)"));
+        assert!(result.contains(
+            "Prior Denials This Session (do not allow near-identical variants):\n\
+             - [12:00:00] Bash command=rm -rf build1: Blocked destructive command"
+        ));
+
+        let review = format_tool_review_with_context("Bash", &serde_json::json!({}), &context);
+        assert!(review.contains("Prior Denials This Session"));
+        assert!(!SupervisorContext::new()
+            .with_escalations(1)
+            .build()
+            .contains("Escalations"));
+    }
+
+    #[test]
+    fn test_supervisor_context_caps_prior_denials() {
+        let context =
+            SupervisorContext::new().with_prior_denials((0..MAX_PRIOR_DENIALS + 5).map(denial));
+        assert_eq!(context.prior_denials.len(), MAX_PRIOR_DENIALS);
+        assert_eq!(context.prior_denials[0], denial(5));
+
+        let result = context.build();
+        assert!(!result.contains("build4:"));
+        assert!(result.contains(&format!("build{}:", MAX_PRIOR_DENIALS + 4)));
+        assert_eq!(result.matches("- [").count(), MAX_PRIOR_DENIALS);
+    }
+
+    #[test]
+    fn test_supervisor_context_redacts_task() {
+        let context = SupervisorContext::new()
+            .with_task("Deploy with DB_PASSWORD='correct-horse'")
+            .with_redactor(Redactor::new());
+
+        let result = context.build();
+        assert!(!result.contains("correct-horse"));
+        assert!(result.contains("[REDACTED:password]"));
+    }
+
     #[test]
     fn test_format_tool_review() {
         let input = serde_json::json!({"file_path": "/test/file.txt"});
@@ -0,0 +1,394 @@
+//! Fair sharing of the AI request limit between sessions.
+//!
+//! With one [`AiClient`](super::AiClient) serving every session of a
+//! multi-session run, a session escalating in a loop could hold every slot
+//! of the concurrency limit, leaving the escalations of the other sessions
+//! to time out. The [`FairShareLimiter`] hands out the slots by session
+//! instead: each freed slot goes to the waiting session with the fewest
+//! requests in flight for its weight, the longest-unserved session first
+//! on a tie. A session over its share does not fail; its requests queue
+//! until a slot is free that no session under its share is waiting for, so
+//! capacity unused by idle sessions is shared out among the busy ones.
+
+use std::collections::{HashMap, VecDeque};
+use std::fmt;
+use std::sync::{Arc, Mutex, PoisonError};
+
+use serde::{Deserialize, Serialize};
+use tokio::sync::oneshot;
+
+/// Weight of a session without one set.
+pub const DEFAULT_SESSION_WEIGHT: u32 = 1;
+
+/// Request accounting of one session, as reported by
+/// [`FairShareLimiter::quotas`].
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct SessionQuota {
+    /// Session the requests belong to.
+    pub session: String,
+    /// Relative share of the limit.
+    pub weight: u32,
+    /// Requests holding a slot.
+    pub in_flight: usize,
+    /// Requests waiting for a slot.
+    pub queued: usize,
+}
+
+#[derive(Debug)]
+struct SessionSlots {
+    weight: u32,
+    in_flight: usize,
+    waiting: VecDeque<oneshot::Sender<QuotaPermit>>,
+    /// Dispatch tick of the last slot given to the session.
+    last_served: u64,
+}
+
+impl SessionSlots {
+    fn new(weight: u32) -> Self {
+        Self {
+            weight: weight.max(1),
+            in_flight: 0,
+            waiting: VecDeque::new(),
+            last_served: 0,
+        }
+    }
+
+    fn queued(&self) -> usize {
+        self.waiting.iter().filter(|tx| !tx.is_closed()).count()
+    }
+}
+
+#[derive(Debug)]
+struct Slots {
+    capacity: usize,
+    in_use: usize,
+    tick: u64,
+    sessions: HashMap<Arc<str>, SessionSlots>,
+}
+
+impl Slots {
+    fn session(&mut self, session: &Arc<str>) -> &mut SessionSlots {
+        self.sessions
+            .entry(Arc::clone(session))
+            .or_insert_with(|| SessionSlots::new(DEFAULT_SESSION_WEIGHT))
+    }
+
+    /// The waiting session owed the next slot.
+    fn next_session(&self) -> Option<Arc<str>> {
+        self.sessions
+            .iter()
+            .filter(|(_, slots)| slots.queued() > 0)
+            .min_by(|(_, a), (_, b)| {
+                // in_flight / weight, compared without division
+                let load_a = a.in_flight as u128 * u128::from(b.weight);
+                let load_b = b.in_flight as u128 * u128::from(a.weight);
+                load_a.cmp(&load_b).then(a.last_served.cmp(&b.last_served))
+            })
+            .map(|(session, _)| Arc::clone(session))
+    }
+}
+
+/// Concurrency limit shared fairly between weighted sessions.
+///
+/// Clones share the slots.
+#[derive(Clone)]
+pub struct FairShareLimiter {
+    slots: Arc<Mutex<Slots>>,
+}
+
+impl fmt::Debug for FairShareLimiter {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.debug_struct("FairShareLimiter")
+            .field("capacity", &self.capacity())
+            .finish_non_exhaustive()
+    }
+}
+
+impl FairShareLimiter {
+    /// Share `capacity` in-flight requests; zero is treated as one.
+    #[must_use]
+    pub fn new(capacity: usize) -> Self {
+        Self {
+            slots: Arc::new(Mutex::new(Slots {
+                capacity: capacity.max(1),
+                in_use: 0,
+                tick: 0,
+                sessions: HashMap::new(),
+            })),
+        }
+    }
+
+    fn lock(&self) -> std::sync::MutexGuard<'_, Slots> {
+        self.slots.lock().unwrap_or_else(PoisonError::into_inner)
+    }
+
+    /// Number of requests allowed in flight at once.
+    #[must_use]
+    pub fn capacity(&self) -> usize {
+        self.lock().capacity
+    }
+
+    /// Give `session` `weight` times the share of a session of weight one;
+    /// zero is treated as one.
+    pub fn set_weight(&self, session: &str, weight: u32) {
+        let session: Arc<str> = Arc::from(session);
+        self.lock().session(&session).weight = weight.max(1);
+    }
+
+    /// Forget a finished session; its requests still in flight keep their
+    /// slots.
+    pub fn remove_session(&self, session: &str) {
+        let mut slots = self.lock();
+        if slots
+            .sessions
+            .get(session)
+            .is_some_and(|s| s.in_flight == 0 && s.queued() == 0)
+        {
+            slots.sessions.remove(session);
+        }
+    }
+
+    /// Wait for a slot for a request of `session`.
+    ///
+    /// The slot is freed when the permit is dropped.
+    pub async fn acquire(&self, session: &str) -> QuotaPermit {
+        let session: Arc<str> = Arc::from(session);
+        let rx = {
+            let mut slots = self.lock();
+            let queued: usize = slots.sessions.values().map(SessionSlots::queued).sum();
+            if queued == 0 && slots.in_use < slots.capacity {
+                return self.admit(&mut slots, &session);
+            }
+            let (tx, rx) = oneshot::channel();
+            slots.session(&session).waiting.push_back(tx);
+            self.dispatch(&mut slots);
+            rx
+        };
+        rx.await.unwrap_or_else(|_| {
+            // Senders are only dropped once their request is abandoned, so
+            // this is not expected; run the request rather than fail it.
+            QuotaPermit::unlimited(self.clone(), session)
+        })
+    }
+
+    fn admit(&self, slots: &mut Slots, session: &Arc<str>) -> QuotaPermit {
+        slots.tick += 1;
+        let tick = slots.tick;
+        slots.in_use += 1;
+        let entry = slots.session(session);
+        entry.in_flight += 1;
+        entry.last_served = tick;
+        QuotaPermit {
+            limiter: self.clone(),
+            session: Arc::clone(session),
+            armed: true,
+        }
+    }
+
+    /// Hand free slots to waiting sessions.
+    fn dispatch(&self, slots: &mut Slots) {
+        for entry in slots.sessions.values_mut() {
+            entry.waiting.retain(|tx| !tx.is_closed());
+        }
+        while slots.in_use < slots.capacity {
+            let Some(session) = slots.next_session() else {
+                return;
+            };
+            let Some(tx) = slots.session(&session).waiting.pop_front() else {
+                continue;
+            };
+            if tx.is_closed() {
+                continue;
+            }
+            let permit = self.admit(slots, &session);
+            if let Err(mut permit) = tx.send(permit) {
+                // The request was abandoned while the slot was handed out
+                permit.armed = false;
+                slots.in_use -= 1;
+                slots.session(&session).in_flight -= 1;
+            }
+        }
+    }
+
+    fn release(&self, session: &Arc<str>) {
+        let mut slots = self.lock();
+        slots.in_use = slots.in_use.saturating_sub(1);
+        if let Some(entry) = slots.sessions.get_mut(session) {
+            entry.in_flight = entry.in_flight.saturating_sub(1);
+        }
+        self.dispatch(&mut slots);
+    }
+
+    /// Requests of `session` waiting for a slot.
+    #[must_use]
+    pub fn queue_depth(&self, session: &str) -> usize {
+        self.lock()
+            .sessions
+            .get(session)
+            .map_or(0, SessionSlots::queued)
+    }
+
+    /// Requests of every session waiting for a slot.
+    #[must_use]
+    pub fn total_queued(&self) -> usize {
+        self.lock()
+            .sessions
+            .values()
+            .map(SessionSlots::queued)
+            .sum()
+    }
+
+    /// Accounting of every known session, by session.
+    #[must_use]
+    pub fn quotas(&self) -> Vec<SessionQuota> {
+        let slots = self.lock();
+        let mut quotas: Vec<SessionQuota> = slots
+            .sessions
+            .iter()
+            .map(|(session, entry)| SessionQuota {
+                session: session.to_string(),
+                weight: entry.weight,
+                in_flight: entry.in_flight,
+                queued: entry.queued(),
+            })
+            .collect();
+        quotas.sort_by(|a, b| a.session.cmp(&b.session));
+        quotas
+    }
+}
+
+/// A slot of a [`FairShareLimiter`], freed on drop.
+#[derive(Debug)]
+pub struct QuotaPermit {
+    limiter: FairShareLimiter,
+    session: Arc<str>,
+    armed: bool,
+}
+
+impl QuotaPermit {
+    fn unlimited(limiter: FairShareLimiter, session: Arc<str>) -> Self {
+        Self {
+            limiter,
+            session,
+            armed: false,
+        }
+    }
+}
+
+impl Drop for QuotaPermit {
+    fn drop(&mut self) {
+        if self.armed {
+            self.limiter.release(&self.session);
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::time::Duration;
+
+    /// Queue a request of `session` that records its turn once admitted
+    /// and holds its slot until released.
+    fn spawn_request(
+        limiter: &FairShareLimiter,
+        session: &'static str,
+        order: &Arc<Mutex<Vec<&'static str>>>,
+        release: &Arc<tokio::sync::Semaphore>,
+    ) -> tokio::task::JoinHandle<()> {
+        let (limiter, order, release) = (limiter.clone(), Arc::clone(order), Arc::clone(release));
+        tokio::spawn(async move {
+            let _permit = limiter.acquire(session).await;
+            order.lock().unwrap().push(session);
+            release.acquire().await.unwrap().forget();
+        })
+    }
+
+    async fn settle() {
+        tokio::time::sleep(Duration::from_millis(20)).await;
+    }
+
+    #[tokio::test]
+    async fn test_busy_session_does_not_starve_others() {
+        let limiter = FairShareLimiter::new(1);
+        let order = Arc::new(Mutex::new(Vec::new()));
+        let release = Arc::new(tokio::sync::Semaphore::new(0));
+
+        let mut requests = Vec::new();
+        for _ in 0..4 {
+            requests.push(spawn_request(&limiter, "chatty", &order, &release));
+            settle().await;
+        }
+        requests.push(spawn_request(&limiter, "quiet", &order, &release));
+        settle().await;
+        assert_eq!(limiter.queue_depth("chatty"), 3);
+        assert_eq!(limiter.queue_depth("quiet"), 1);
+
+        for _ in 0..5 {
+            release.add_permits(1);
+            settle().await;
+        }
+        for request in requests {
+            request.await.unwrap();
+        }
+        // The quiet session is served next, not after the chatty backlog
+        assert_eq!(
+            *order.lock().unwrap(),
+            vec!["chatty", "quiet", "chatty", "chatty", "chatty"]
+        );
+        assert_eq!(limiter.total_queued(), 0);
+    }
+
+    #[tokio::test]
+    async fn test_weights_and_unused_capacity() {
+        let limiter = FairShareLimiter::new(3);
+        limiter.set_weight("heavy", 2);
+
+        // Alone, a session may use every slot
+        let held: Vec<QuotaPermit> =
+            futures_util::future::join_all((0..3).map(|_| limiter.acquire("light"))).await;
+        let quotas = limiter.quotas();
+        assert_eq!(quotas[1].session, "light");
+        assert_eq!(quotas[1].in_flight, 3);
+        drop(held);
+
+        // Under contention the heavy session gets two slots for one
+        let order = Arc::new(Mutex::new(Vec::new()));
+        let release = Arc::new(tokio::sync::Semaphore::new(0));
+        let blocker = limiter.clone();
+        let blockers: Vec<QuotaPermit> =
+            futures_util::future::join_all((0..3).map(|_| blocker.acquire("other"))).await;
+        let mut requests = Vec::new();
+        for session in ["light", "light", "heavy", "heavy"] {
+            requests.push(spawn_request(&limiter, session, &order, &release));
+        }
+        settle().await;
+        drop(blockers);
+        settle().await;
+        let admitted = order.lock().unwrap().clone();
+        assert_eq!(admitted.len(), 3);
+        assert_eq!(admitted.iter().filter(|s| **s == "heavy").count(), 2);
+
+        release.add_permits(4);
+        for request in requests {
+            request.await.unwrap();
+        }
+        limiter.remove_session("light");
+        assert!(limiter.quotas().iter().all(|q| q.session != "light"));
+    }
+
+    #[tokio::test]
+    async fn test_abandoned_request_frees_its_turn() {
+        let limiter = FairShareLimiter::new(1);
+        let held = limiter.acquire("a").await;
+        let abandoned = tokio::time::timeout(Duration::from_millis(20), limiter.acquire("b")).await;
+        assert!(abandoned.is_err());
+        assert_eq!(limiter.queue_depth("b"), 0);
+
+        drop(held);
+        let _permit = tokio::time::timeout(Duration::from_secs(1), limiter.acquire("a"))
+            .await
+            .unwrap();
+        assert_eq!(limiter.quotas()[0].in_flight, 1);
+    }
+}
@@ -0,0 +1,474 @@
+//! Post-mortem review of a finished session.
+//!
+//! The transcript is rebuilt into events, compressed with
+//! [`ContextCompressor`] and split into parts that fit one request. Each part
+//! is reviewed with [`REVIEW_SYSTEM_PROMPT`]; when there is more than one,
+//! a merge pass with [`REVIEW_MERGE_PROMPT`] combines the part reviews into
+//! one [`SessionCritique`].
+
+use std::fmt::Write as _;
+
+use futures_util::future::try_join_all;
+use serde::{Deserialize, Serialize};
+
+use super::prompts::{
+    format_review_chunk, format_review_merge, format_suggestion_polish, REVIEW_MERGE_PROMPT,
+    REVIEW_SYSTEM_PROMPT, SUGGESTION_POLISH_PROMPT,
+};
+use super::{extract_json, AiClient, AiError, ContextCompressor};
+use crate::audit::ClaudeMdSuggestion;
+use crate::cli::{ClaudeEvent, ToolResult, ToolUse};
+use crate::watcher::{ContentBlock, JournalEntry, SessionReconstructor};
+
+/// Default most characters of compressed transcript per review request.
+pub const DEFAULT_REVIEW_CHUNK_CHARS: usize = 24_000;
+
+/// A line the reviewer suggests adding to CLAUDE.md.
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct ClaudeMdAddition {
+    /// What the line is about.
+    pub topic: String,
+    /// The line to add.
+    pub text: String,
+}
+
+/// Structured critique of a session.
+#[derive(Debug, Clone, Default, PartialEq, Eq, Serialize, Deserialize)]
+pub struct SessionCritique {
+    /// Whether the task was achieved, with evidence.
+    pub goal_achievement: String,
+    /// Work that did not move the task forward.
+    #[serde(default)]
+    pub detours: Vec<String>,
+    /// Risky actions the session took.
+    #[serde(default)]
+    pub risky_actions: Vec<String>,
+    /// Suggested CLAUDE.md additions.
+    #[serde(default)]
+    pub claude_md_additions: Vec<ClaudeMdAddition>,
+}
+
+impl SessionCritique {
+    /// Render the critique as Markdown.
+    #[must_use]
+    pub fn to_markdown(&self, session: &str) -> String {
+        let mut out = format!("# Session review: {session}\n\n## Goal achievement\n\n");
+        out.push_str(self.goal_achievement.trim());
+        out.push('\n');
+        for (heading, items) in [
+            ("Unnecessary detours", &self.detours),
+            ("Risky actions", &self.risky_actions),
+        ] {
+            let _ = write!(out, "\n## {heading}\n\n");
+            push_list(&mut out, items.iter().map(String::as_str));
+        }
+        out.push_str("\n## Suggested CLAUDE.md additions\n\n");
+        let additions: Vec<String> = self
+            .claude_md_additions
+            .iter()
+            .map(|a| format!("**{}**: {}", a.topic, a.text))
+            .collect();
+        push_list(&mut out, additions.iter().map(String::as_str));
+        out
+    }
+}
+
+fn push_list<'a>(out: &mut String, items: impl Iterator<Item = &'a str>) {
+    let mut empty = true;
+    for item in items {
+        empty = false;
+        let _ = writeln!(out, "- {item}");
+    }
+    if empty {
+        out.push_str("None.\n");
+    }
+}
+
+/// Reworded CLAUDE.md additions, in the order they were given.
+#[derive(Debug, Deserialize)]
+struct PolishedAdditions {
+    claude_md_additions: Vec<ClaudeMdAddition>,
+}
+
+/// Rebuild a transcript into events, each tool call followed by its result.
+#[must_use]
+pub fn transcript_events(entries: &[JournalEntry]) -> Vec<ClaudeEvent> {
+    let mut reconstructor = SessionReconstructor::new();
+    reconstructor.process_entries(entries);
+
+    let mut events = Vec::new();
+    for entry in entries {
+        let JournalEntry::Assistant(assistant) = entry else {
+            continue;
+        };
+        for block in &assistant.message.content {
+            match block {
+                ContentBlock::Text { text } if !text.trim().is_empty() => {
+                    events.push(ClaudeEvent::Assistant {
+                        message: serde_json::json!({
+                            "role": "assistant",
+                            "content": [{"type": "text", "text": text}],
+                        }),
+                    });
+                }
+                ContentBlock::ToolUse { id, name, input } => {
+                    events.push(ClaudeEvent::ToolUse(ToolUse {
+                        id: id.clone(),
+                        name: name.clone(),
+                        input: input.clone(),
+                    }));
+                    let record = reconstructor
+                        .tool_calls()
+                        .iter()
+                        .find(|record| &record.tool_use_id == id);
+                    if let Some((record, content)) =
+                        record.and_then(|r| r.result.as_ref().map(|content| (r, content)))
+                    {
+                        events.push(ClaudeEvent::ToolResult(ToolResult {
+                            tool_use_id: id.clone(),
+                            content: content
+                                .as_str()
+                                .map_or_else(|| content.to_string(), String::from),
+                            is_error: record.is_error,
+                        }));
+                    }
+                }
+                _ => {}
+            }
+        }
+    }
+    events
+}
+
+/// The task of a transcript: its first user text message.
+#[must_use]
+pub fn transcript_task(entries: &[JournalEntry]) -> Option<String> {
+    entries.iter().find_map(|entry| match entry {
+        JournalEntry::User(user) => {
+            let text = user.message.content.as_text();
+            let text = text.trim();
+            (!text.is_empty()).then(|| text.to_string())
+        }
+        _ => None,
+    })
+}
+
+/// Split compressed transcript lines into parts of at most `max_chars`.
+///
+/// A single longer line becomes a part of its own.
+#[must_use]
+pub fn chunk_transcript(transcript: &str, max_chars: usize) -> Vec<String> {
+    let mut chunks = Vec::new();
+    let mut current = String::new();
+    for line in transcript.lines() {
+        if !current.is_empty() && current.len() + line.len() + 1 > max_chars {
+            chunks.push(std::mem::take(&mut current));
+        }
+        if !current.is_empty() {
+            current.push('\n');
+        }
+        current.push_str(line);
+    }
+    if !current.is_empty() {
+        chunks.push(current);
+    }
+    chunks
+}
+
+/// Reviews finished sessions with the AI client.
+#[derive(Debug, Clone)]
+pub struct SessionReviewer {
+    client: AiClient,
+    chunk_chars: usize,
+}
+
+impl SessionReviewer {
+    /// Create a reviewer using `client`.
+    #[must_use]
+    pub fn new(client: AiClient) -> Self {
+        Self {
+            client,
+            chunk_chars: DEFAULT_REVIEW_CHUNK_CHARS,
+        }
+    }
+
+    /// Set the most transcript characters per request (builder pattern).
+    #[must_use]
+    pub fn with_chunk_chars(mut self, chunk_chars: usize) -> Self {
+        self.chunk_chars = chunk_chars.max(1);
+        self
+    }
+
+    /// Review a session's events.
+    ///
+    /// # Errors
+    ///
+    /// Returns an error if a request fails or a response is not a critique.
+    pub async fn review(
+        &self,
+        task: &str,
+        events: &[ClaudeEvent],
+    ) -> Result<SessionCritique, AiError> {
+        let transcript = ContextCompressor::new(events.len(), usize::MAX).compress(events);
+        let chunks = chunk_transcript(&transcript, self.chunk_chars);
+        if chunks.len() <= 1 {
+            let transcript = chunks.first().map_or("(empty transcript)", String::as_str);
+            return self.review_part(task, 1, 1, transcript).await;
+        }
+
+        tracing::info!(parts = chunks.len(), "Reviewing long transcript in parts");
+        let parts = try_join_all(
+            chunks
+                .iter()
+                .enumerate()
+                .map(|(i, chunk)| self.review_part(task, i + 1, chunks.len(), chunk)),
+        )
+        .await?;
+        let parts: Vec<String> = parts
+            .iter()
+            .map(|part| serde_json::to_string(part).unwrap_or_default())
+            .collect();
+
+        let text = self
+            .client
+            .generate(REVIEW_MERGE_PROMPT, &format_review_merge(task, &parts))
+            .await?;
+        extract_json(&text)
+    }
+
+    /// Reword suggested CLAUDE.md additions, keeping their order and evidence.
+    ///
+    /// # Errors
+    ///
+    /// Returns an error if the request fails or the response does not reword
+    /// every suggestion.
+    pub async fn polish(
+        &self,
+        suggestions: &[ClaudeMdSuggestion],
+    ) -> Result<Vec<ClaudeMdSuggestion>, AiError> {
+        let request = format_suggestion_polish(suggestions.iter().map(|suggestion| {
+            (
+                suggestion.topic.as_str(),
+                suggestion.text.as_str(),
+                suggestion.evidence.as_str(),
+            )
+        }));
+        let text = self
+            .client
+            .generate(SUGGESTION_POLISH_PROMPT, &request)
+            .await?;
+        let polished: PolishedAdditions = extract_json(&text)?;
+        if polished.claude_md_additions.len() != suggestions.len() {
+            return Err(AiError::ParseError(format!(
+                "expected {} additions, got {}",
+                suggestions.len(),
+                polished.claude_md_additions.len()
+            )));
+        }
+        Ok(polished
+            .claude_md_additions
+            .into_iter()
+            .zip(suggestions)
+            .map(|(addition, suggestion)| ClaudeMdSuggestion {
+                topic: addition.topic,
+                text: addition.text,
+                evidence: suggestion.evidence.clone(),
+            })
+            .collect())
+    }
+
+    async fn review_part(
+        &self,
+        task: &str,
+        part: usize,
+        parts: usize,
+        transcript: &str,
+    ) -> Result<SessionCritique, AiError> {
+        let text = self
+            .client
+            .generate(
+                REVIEW_SYSTEM_PROMPT,
+                &format_review_chunk(task, part, parts, transcript),
+            )
+            .await?;
+        extract_json(&text)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use std::sync::atomic::{AtomicUsize, Ordering};
+    use std::sync::{Arc, Mutex};
+
+    use axum::routing::post;
+    use axum::{Json, Router};
+
+    use super::*;
+    use crate::ai::{ClaudeProvider, Provider};
+    use crate::config::AiConfig;
+    use crate::watcher::parse_jsonl_content;
+
+    /// Requests seen by the mock provider, as (system prompt, user message).
+    type Seen = Arc<Mutex<Vec<(String, String)>>>;
+
+    /// Mock Claude API answering part reviews and merges with canned critiques.
+    async fn spawn_provider() -> (AiClient, Seen) {
+        let seen: Seen = Arc::default();
+        let parts = Arc::new(AtomicUsize::new(0));
+        let handler_seen = Arc::clone(&seen);
+        let app = Router::new().route(
+            "/v1/messages",
+            post(move |Json(body): Json<serde_json::Value>| {
+                let (seen, parts) = (Arc::clone(&handler_seen), Arc::clone(&parts));
+                async move {
+                    let system = body["system"].as_str().unwrap_or_default().to_string();
+                    let user = body["messages"][0]["content"]
+                        .as_str()
+                        .unwrap_or_default()
+                        .to_string();
+                    let critique = if system == REVIEW_MERGE_PROMPT {
+                        serde_json::json!({
+                            "goal_achievement": "Done after a detour",
+                            "detours": ["Ran the tests four times"],
+                            "risky_actions": [],
+                            "claude_md_additions": [{"topic": "Tests", "text": "Run `cargo test -p core`."}],
+                        })
+                    } else {
+                        let n = parts.fetch_add(1, Ordering::SeqCst) + 1;
+                        serde_json::json!({
+                            "goal_achievement": format!("Part {n} in progress"),
+                            "detours": [format!("Detour {n}")],
+                        })
+                    };
+                    seen.lock().unwrap().push((system, user));
+                    Json(serde_json::json!({"content": [{"text": critique.to_string()}]}))
+                }
+            }),
+        );
+        let listener = tokio::net::TcpListener::bind("127.0.0.1:0").await.unwrap();
+        let addr = listener.local_addr().unwrap();
+        tokio::spawn(async move { axum::serve(listener, app).await.unwrap() });
+
+        let provider = ClaudeProvider::new(
+            format!("http://{addr}"),
+            "test-key".to_string(),
+            "claude-test".to_string(),
+            64,
+        )
+        .unwrap();
+        (
+            AiClient::new(Provider::Claude(provider), AiConfig::default()),
+            seen,
+        )
+    }
+
+    fn tool_events(count: usize) -> Vec<ClaudeEvent> {
+        (0..count)
+            .flat_map(|i| {
+                [
+                    ClaudeEvent::ToolUse(ToolUse {
+                        id: format!("toolu_{i}"),
+                        name: "Bash".to_string(),
+                        input: serde_json::json!({"command": format!("cargo test --run {i}")}),
+                    }),
+                    ClaudeEvent::ToolResult(ToolResult {
+                        tool_use_id: format!("toolu_{i}"),
+                        content: "test result: FAILED".to_string(),
+                        is_error: true,
+                    }),
+                ]
+            })
+            .collect()
+    }
+
+    #[tokio::test]
+    async fn test_short_transcript_is_one_request() {
+        let (client, seen) = spawn_provider().await;
+        let critique = SessionReviewer::new(client)
+            .review("Fix the tests", &tool_events(2))
+            .await
+            .unwrap();
+
+        assert_eq!(critique.goal_achievement, "Part 1 in progress");
+        let seen = seen.lock().unwrap();
+        assert_eq!(seen.len(), 1);
+        assert!(seen[0].1.contains("part 1 of 1"));
+        assert!(seen[0].1.contains("[TOOL] Bash"));
+    }
+
+    #[tokio::test]
+    async fn test_long_transcript_is_chunked_and_merged() {
+        let (client, seen) = spawn_provider().await;
+        let critique = SessionReviewer::new(client)
+            .with_chunk_chars(200)
+            .review("Fix the tests", &tool_events(10))
+            .await
+            .unwrap();
+
+        assert_eq!(critique.goal_achievement, "Done after a detour");
+        assert_eq!(critique.claude_md_additions[0].topic, "Tests");
+
+        let seen = seen.lock().unwrap();
+        let (merges, parts): (Vec<_>, Vec<_>) = seen
+            .iter()
+            .partition(|(system, _)| system == REVIEW_MERGE_PROMPT);
+        assert!(
+            parts.len() > 1,
+            "expected several parts, got {}",
+            parts.len()
+        );
+        assert!(parts.iter().all(|(_, user)| user.len() < 200 + 200));
+        assert_eq!(merges.len(), 1);
+        // The merge pass sees every part review
+        for n in 1..=parts.len() {
+            assert!(
+                merges[0].1.contains(&format!("Detour {n}")),
+                "{}",
+                merges[0].1
+            );
+        }
+    }
+
+    #[test]
+    fn test_chunk_transcript() {
+        assert_eq!(chunk_transcript("a\nb\nc", 3), vec!["a\nb", "c"]);
+        assert_eq!(chunk_transcript("long line\nx", 4), vec!["long line", "x"]);
+        assert!(chunk_transcript("", 10).is_empty());
+    }
+
+    #[test]
+    fn test_transcript_events_pairs_results() {
+        let entries = parse_jsonl_content(concat!(
+            r#"{"type":"user","uuid":"u1","parentUuid":null,"sessionId":"s","timestamp":"t","message":{"role":"user","content":"Fix the tests"},"userType":"external","cwd":"/p","version":"1"}"#,
+            "\n",
+            r#"{"type":"assistant","uuid":"a1","parentUuid":"u1","sessionId":"s","timestamp":"t","message":{"role":"assistant","content":[{"type":"text","text":"Running tests"},{"type":"tool_use","id":"toolu_1","name":"Bash","input":{"command":"cargo test"}}]},"cwd":"/p","version":"1"}"#,
+            "\n",
+            r#"{"type":"user","uuid":"u2","parentUuid":"a1","sessionId":"s","timestamp":"t","message":{"role":"user","content":[{"type":"tool_result","tool_use_id":"toolu_1","content":"ok"}]},"userType":"external","cwd":"/p","version":"1","sourceToolUseId":"toolu_1","toolUseResult":"ok"}"#,
+        ));
+
+        assert_eq!(transcript_task(&entries).as_deref(), Some("Fix the tests"));
+        let events = transcript_events(&entries);
+        assert_eq!(events.len(), 3);
+        assert!(matches!(events[0], ClaudeEvent::Assistant { .. }));
+        assert_eq!(events[1].tool_name(), Some("Bash"));
+        assert!(matches!(&events[2], ClaudeEvent::ToolResult(r) if r.content == "ok"));
+    }
+
+    #[test]
+    fn test_to_markdown() {
+        let critique = SessionCritique {
+            goal_achievement: "Done".to_string(),
+            detours: vec!["Re-ran tests".to_string()],
+            risky_actions: Vec::new(),
+            claude_md_additions: vec![ClaudeMdAddition {
+                topic: "Tests".to_string(),
+                text: "Use nextest.".to_string(),
+            }],
+        };
+        let md = critique.to_markdown("abc");
+        assert!(md.starts_with("# Session review: abc"));
+        assert!(md.contains("## Unnecessary detours\n\n- Re-ran tests\n"));
+        assert!(md.contains("## Risky actions\n\nNone.\n"));
+        assert!(md.contains("- **Tests**: Use nextest."));
+    }
+}
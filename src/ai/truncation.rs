@@ -0,0 +1,291 @@
+//! Truncated tool results, and the file content they cut off.
+//!
+//! Claude Code shortens long tool results in its stream, ending them with a
+//! marker such as `… +120 lines`, or flags them as truncated in the echoed
+//! `tool_use_result`. An escalated edit of a file whose earlier result was
+//! cut would be reviewed against part of the file, so the escalation
+//! context carries an excerpt of the file as it is on disk instead: at most
+//! [`EXCERPT_BYTES`] around the edited text, read only from files under the
+//! session's working directory.
+
+use std::collections::HashSet;
+use std::io::{self, Read};
+use std::path::{Path, PathBuf};
+
+use serde_json::Value;
+
+use crate::cli::ClaudeEvent;
+use crate::supervisor::tool_result_blocks;
+
+/// Most bytes of a file included in an escalation's context.
+pub const EXCERPT_BYTES: usize = 4000;
+
+/// Most bytes of a file searched for the edited text.
+pub const MAX_SCANNED_BYTES: u64 = 4 * 1024 * 1024;
+
+/// Whether a tool result's text ends in a truncation marker: Claude Code's
+/// `… +N lines`, or the `[... N bytes truncated` of a compacted history.
+#[must_use]
+pub fn has_truncation_marker(content: &str) -> bool {
+    if content.contains("[... ") && content.contains(" bytes truncated") {
+        return true;
+    }
+    content.match_indices(" lines").any(|(end, _)| {
+        let before = &content[..end];
+        let digits = before.trim_end_matches(|c: char| c.is_ascii_digit());
+        digits.len() < before.len()
+            && digits
+                .strip_suffix('+')
+                .map(str::trim_end)
+                .is_some_and(|prefix| prefix.ends_with('…') || prefix.ends_with("..."))
+    })
+}
+
+/// Whether a tool result block or echoed `tool_use_result` flags itself as
+/// truncated, or reports reading fewer lines of a file than it has.
+#[must_use]
+pub fn is_flagged_truncated(value: &Value) -> bool {
+    let flag = |key: &str| value.get(key).and_then(Value::as_bool) == Some(true);
+    if flag("truncated") || flag("is_truncated") || flag("isTruncated") {
+        return true;
+    }
+    let Some(file) = value.get("file") else {
+        return false;
+    };
+    let lines = |key: &str| file.get(key).and_then(Value::as_u64);
+    matches!(
+        (lines("numLines"), lines("totalLines")),
+        (Some(read), Some(total)) if read < total
+    )
+}
+
+/// IDs of the tool calls whose results in `events` were truncated.
+#[must_use]
+pub fn truncated_results(events: &[ClaudeEvent]) -> HashSet<&str> {
+    let mut ids = HashSet::new();
+    for event in events {
+        match event {
+            ClaudeEvent::ToolResult(result) if has_truncation_marker(&result.content) => {
+                ids.insert(result.tool_use_id.as_str());
+            }
+            ClaudeEvent::User {
+                message,
+                tool_use_result,
+            } => {
+                let blocks: Vec<&Value> = tool_result_blocks(message).collect();
+                // The echoed result can only be paired with a single block
+                let echoed = match blocks.as_slice() {
+                    [_] => tool_use_result.as_ref(),
+                    _ => None,
+                };
+                for block in blocks {
+                    let truncated = is_flagged_truncated(block)
+                        || block_texts(block).any(has_truncation_marker)
+                        || echoed.is_some_and(|echoed| {
+                            is_flagged_truncated(echoed)
+                                || echoed.as_str().is_some_and(has_truncation_marker)
+                        });
+                    if let Some(id) = block.get("tool_use_id").and_then(Value::as_str) {
+                        if truncated {
+                            ids.insert(id);
+                        }
+                    }
+                }
+            }
+            _ => {}
+        }
+    }
+    ids
+}
+
+/// Texts of a tool result block: its content string, or its text parts.
+fn block_texts(block: &Value) -> impl Iterator<Item = &str> {
+    let content = block.get("content");
+    let text = content.and_then(Value::as_str);
+    let parts = content
+        .and_then(Value::as_array)
+        .into_iter()
+        .flatten()
+        .filter_map(|part| part.get("text").and_then(Value::as_str));
+    text.into_iter().chain(parts)
+}
+
+/// Lines of a file read from disk.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct FileExcerpt {
+    /// The file, resolved.
+    pub path: PathBuf,
+    /// Line number of the first line, from 1.
+    pub first_line: usize,
+    /// Line number of the last line.
+    pub last_line: usize,
+    /// Whether the excerpt is the whole file.
+    pub complete: bool,
+    /// The lines, lossily decoded.
+    pub text: String,
+}
+
+/// Read at most `max_bytes` of whole lines of `path` around the first
+/// occurrence of `anchor`, or from its start without one.
+///
+/// Returns `Ok(None)` for a file outside `root`, after resolving symlinks.
+///
+/// # Errors
+///
+/// Returns an error if `root` or the file cannot be resolved or read.
+pub fn read_excerpt(
+    path: &Path,
+    root: &Path,
+    anchor: Option<&str>,
+    max_bytes: usize,
+) -> io::Result<Option<FileExcerpt>> {
+    let root = root.canonicalize()?;
+    let path = root.join(path).canonicalize()?;
+    if !path.starts_with(&root) {
+        return Ok(None);
+    }
+    let mut bytes = Vec::new();
+    std::fs::File::open(&path)?
+        .take(MAX_SCANNED_BYTES)
+        .read_to_end(&mut bytes)?;
+
+    let (anchor_at, anchor_len) = anchor
+        .filter(|anchor| !anchor.is_empty())
+        .and_then(|anchor| {
+            bytes
+                .windows(anchor.len())
+                .position(|window| window == anchor.as_bytes())
+                .map(|at| (at, anchor.len()))
+        })
+        .unwrap_or((0, 0));
+    let mut start = anchor_at.saturating_sub(max_bytes.saturating_sub(anchor_len) / 2);
+    let mut end = (start + max_bytes).min(bytes.len());
+    start = end.saturating_sub(max_bytes).min(start);
+
+    // Whole lines only, unless a single line is longer than the excerpt
+    if start > 0 {
+        if let Some(newline) = bytes[start..end].iter().position(|&b| b == b'\n') {
+            start += newline + 1;
+        }
+    }
+    if end < bytes.len() {
+        if let Some(newline) = bytes[start..end].iter().rposition(|&b| b == b'\n') {
+            end = start + newline + 1;
+        }
+    }
+
+    let text = String::from_utf8_lossy(&bytes[start..end]).into_owned();
+    let first_line = bytes[..start].split(|&b| b == b'\n').count();
+    let last_line = first_line + text.trim_end_matches('\n').matches('\n').count();
+    let complete = start == 0 && end == bytes.len() && (bytes.len() as u64) < MAX_SCANNED_BYTES;
+    Ok(Some(FileExcerpt {
+        path,
+        first_line,
+        last_line,
+        complete,
+        text,
+    }))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use serde_json::json;
+
+    #[test]
+    fn test_truncation_markers() {
+        assert!(has_truncation_marker(
+            "fn main() {\n… +120 lines (ctrl+r to expand)"
+        ));
+        assert!(has_truncation_marker("line 1\n... +7 lines"));
+        assert!(has_truncation_marker(
+            "head\n[... 512 bytes truncated; full result is transcript event 4]"
+        ));
+        assert!(!has_truncation_marker("added +3 lines to the config"));
+        assert!(!has_truncation_marker("… + lines"));
+        assert!(!has_truncation_marker("complete output"));
+    }
+
+    #[test]
+    fn test_truncated_results_reads_flags_and_markers() {
+        let user = |block: Value, echoed: Option<Value>| ClaudeEvent::User {
+            message: json!({"role": "user", "content": [block]}),
+            tool_use_result: echoed,
+        };
+        let events = vec![
+            user(
+                json!({"type": "tool_result", "tool_use_id": "marked", "content": [
+                    {"type": "text", "text": "a\n… +40 lines"}
+                ]}),
+                None,
+            ),
+            user(
+                json!({"type": "tool_result", "tool_use_id": "partial", "content": "a"}),
+                Some(json!({"type": "text", "file": {"numLines": 2000, "totalLines": 2400}})),
+            ),
+            user(
+                json!({"type": "tool_result", "tool_use_id": "flagged", "content": "a", "truncated": true}),
+                None,
+            ),
+            user(
+                json!({"type": "tool_result", "tool_use_id": "whole", "content": "a"}),
+                Some(json!({"type": "text", "file": {"numLines": 3, "totalLines": 3}})),
+            ),
+        ];
+        let ids = truncated_results(&events);
+        assert_eq!(ids, HashSet::from(["marked", "partial", "flagged"]));
+    }
+
+    #[test]
+    fn test_read_excerpt_centers_on_anchor() {
+        let dir = tempfile::tempdir().unwrap();
+        let lines: Vec<String> = (1..=500).map(|i| format!("line {i}\n")).collect();
+        let text = lines.concat();
+        std::fs::write(dir.path().join("big.txt"), &text).unwrap();
+
+        let excerpt = read_excerpt(Path::new("big.txt"), dir.path(), Some("line 300\n"), 100)
+            .unwrap()
+            .unwrap();
+        assert!(excerpt.text.contains("line 300\n"));
+        assert!(excerpt.text.len() <= 100);
+        assert!(excerpt.text.ends_with('\n'));
+        assert!(excerpt
+            .text
+            .starts_with(&format!("line {}\n", excerpt.first_line)));
+        assert!(excerpt
+            .text
+            .ends_with(&format!("line {}\n", excerpt.last_line)));
+        assert!(!excerpt.complete);
+
+        let head = read_excerpt(Path::new("big.txt"), dir.path(), Some("missing"), 30)
+            .unwrap()
+            .unwrap();
+        assert_eq!(head.text, "line 1\nline 2\nline 3\nline 4\n");
+        assert_eq!((head.first_line, head.last_line), (1, 4));
+    }
+
+    #[test]
+    fn test_read_excerpt_stays_under_root() {
+        let dir = tempfile::tempdir().unwrap();
+        let root = dir.path().join("repo");
+        std::fs::create_dir(&root).unwrap();
+        std::fs::write(dir.path().join("secret.txt"), "outside").unwrap();
+
+        assert_eq!(
+            read_excerpt(Path::new("../secret.txt"), &root, None, 100).unwrap(),
+            None
+        );
+        assert_eq!(
+            read_excerpt(&dir.path().join("secret.txt"), &root, None, 100).unwrap(),
+            None
+        );
+        #[cfg(unix)]
+        {
+            std::os::unix::fs::symlink(dir.path().join("secret.txt"), root.join("link")).unwrap();
+            assert_eq!(
+                read_excerpt(Path::new("link"), &root, None, 100).unwrap(),
+                None
+            );
+        }
+    }
+}
@@ -0,0 +1,594 @@
+//! Approximate cost attribution per tool and per touched file.
+//!
+//! Claude Code reports token usage per assistant message, not per tool call,
+//! so attribution is done at turn granularity:
+//!
+//! 1. A turn begins at each `MessageStart` (or at an `Assistant` message with a
+//!    message id not seen before). The turn's usage is the largest `usage` block
+//!    reported for it; input counts fresh, cache-creation and cache-read tokens.
+//! 2. Every tool call issued during the turn gets an equal share of the turn's
+//!    tokens. Turns that issue no tool calls are booked to [`ASSISTANT_BUCKET`].
+//! 3. A tool call's share is split evenly across the file paths in its input
+//!    (`file_path`, `notebook_path`, `path`). Calls without a path only appear
+//!    in the per-tool view.
+//! 4. The session total is the cost reported by the `Result` event, or an
+//!    estimate at $3/1M input and $15/1M output tokens when none is reported.
+//!    It is distributed in micro-dollars proportionally to each share's priced
+//!    tokens using the largest-remainder method, so the per-tool costs always
+//!    sum exactly to the session total.
+
+use std::collections::{BTreeMap, HashSet};
+
+use serde::{Deserialize, Serialize};
+
+use crate::cli::{input_paths, ClaudeEvent, ToolUse};
+
+/// Bucket for turns that produce text only and issue no tool calls.
+pub const ASSISTANT_BUCKET: &str = "(assistant)";
+
+/// Micro-dollars per input token ($3 per million).
+const INPUT_MICROS_PER_TOKEN: u64 = 3;
+
+/// Micro-dollars per output token ($15 per million).
+const OUTPUT_MICROS_PER_TOKEN: u64 = 15;
+
+/// Dimension a cost share is aggregated over.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum CostDimension {
+    /// Aggregated by tool name.
+    Tool,
+    /// Aggregated by touched file path.
+    File,
+}
+
+impl CostDimension {
+    /// Returns the string representation for database storage.
+    #[must_use]
+    pub fn as_str(&self) -> &'static str {
+        match self {
+            Self::Tool => "tool",
+            Self::File => "file",
+        }
+    }
+}
+
+/// Attributed usage and cost for one tool name or file path.
+#[derive(Debug, Clone, Default, PartialEq, Eq, Serialize, Deserialize)]
+pub struct CostShare {
+    /// Tool name or file path.
+    pub key: String,
+    /// Number of tool calls attributed to this key.
+    pub calls: u64,
+    /// Attributed input tokens.
+    pub input_tokens: u64,
+    /// Attributed output tokens.
+    pub output_tokens: u64,
+    /// Attributed cost in micro-dollars (millionths of a USD).
+    pub cost_micros: u64,
+}
+
+impl CostShare {
+    /// Attributed cost in USD.
+    #[must_use]
+    #[allow(clippy::cast_precision_loss)]
+    pub fn cost_usd(&self) -> f64 {
+        self.cost_micros as f64 / 1_000_000.0
+    }
+}
+
+/// Per-tool and per-file breakdown of a session's cost.
+#[derive(Debug, Clone, Default, PartialEq, Eq, Serialize, Deserialize)]
+pub struct CostBreakdown {
+    /// Session total in micro-dollars. Equals the sum of `by_tool` costs.
+    pub total_cost_micros: u64,
+    /// Total input tokens across all turns.
+    pub input_tokens: u64,
+    /// Total output tokens across all turns.
+    pub output_tokens: u64,
+    /// Shares by tool name, most expensive first.
+    pub by_tool: Vec<CostShare>,
+    /// Shares by file path, most expensive first.
+    pub by_file: Vec<CostShare>,
+}
+
+impl CostBreakdown {
+    /// Session total in USD.
+    #[must_use]
+    #[allow(clippy::cast_precision_loss)]
+    pub fn total_cost_usd(&self) -> f64 {
+        self.total_cost_micros as f64 / 1_000_000.0
+    }
+
+    /// Shares for the given dimension.
+    #[must_use]
+    pub fn shares(&self, dimension: CostDimension) -> &[CostShare] {
+        match dimension {
+            CostDimension::Tool => &self.by_tool,
+            CostDimension::File => &self.by_file,
+        }
+    }
+}
+
+/// A tool call issued during a turn.
+#[derive(Debug, Clone)]
+struct TurnCall {
+    id: String,
+    name: String,
+    files: Vec<String>,
+}
+
+/// One assistant turn and the tool calls it issued.
+#[derive(Debug, Clone, Default)]
+struct Turn {
+    message_id: Option<String>,
+    input_tokens: u64,
+    output_tokens: u64,
+    calls: Vec<TurnCall>,
+}
+
+/// Smallest unit of attribution: one (tool, file) slice of a turn.
+struct Fragment {
+    tool: String,
+    file: Option<String>,
+    /// Whether this fragment counts as a call for its tool.
+    counts_call: bool,
+    input_tokens: u64,
+    output_tokens: u64,
+}
+
+impl Fragment {
+    fn weight(&self) -> u64 {
+        self.input_tokens * INPUT_MICROS_PER_TOKEN + self.output_tokens * OUTPUT_MICROS_PER_TOKEN
+    }
+}
+
+/// Accumulates stream events and attributes usage to tool calls.
+#[derive(Debug, Clone, Default)]
+pub struct CostAttributor {
+    turns: Vec<Turn>,
+    seen_calls: HashSet<String>,
+    reported_cost_usd: Option<f64>,
+}
+
+impl CostAttributor {
+    /// Create an empty attributor.
+    #[must_use]
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Feed one stream event into the attributor.
+    pub fn observe(&mut self, event: &ClaudeEvent) {
+        match event {
+            ClaudeEvent::MessageStart { message } => {
+                self.turns.push(Turn {
+                    message_id: message_id(message),
+                    ..Turn::default()
+                });
+                self.record_usage(message);
+            }
+            ClaudeEvent::Assistant { message } => {
+                let id = message_id(message);
+                let continues_turn = match (&id, self.turns.last()) {
+                    (Some(id), Some(turn)) => turn.message_id.as_deref() == Some(id.as_str()),
+                    (None, Some(_)) => true,
+                    (_, None) => false,
+                };
+                if !continues_turn {
+                    self.turns.push(Turn {
+                        message_id: id,
+                        ..Turn::default()
+                    });
+                }
+                self.record_usage(message);
+                if let Some(blocks) = message.get("content").and_then(|c| c.as_array()) {
+                    for block in blocks {
+                        if block.get("type").and_then(|t| t.as_str()) != Some("tool_use") {
+                            continue;
+                        }
+                        let (Some(id), Some(name)) = (
+                            block.get("id").and_then(|v| v.as_str()),
+                            block.get("name").and_then(|v| v.as_str()),
+                        ) else {
+                            continue;
+                        };
+                        let input = block.get("input").cloned().unwrap_or_default();
+                        self.record_call(id, name, &input);
+                    }
+                }
+            }
+            ClaudeEvent::ToolUse(ToolUse { id, name, input }) => {
+                self.record_call(id, name, input);
+            }
+            ClaudeEvent::Result(result) => {
+                self.reported_cost_usd = result.cost_usd.or_else(|| {
+                    result
+                        .extras
+                        .get("total_cost_usd")
+                        .and_then(serde_json::Value::as_f64)
+                });
+            }
+            _ => {}
+        }
+    }
+
+    /// Number of assistant turns observed so far.
+    #[must_use]
+    pub fn turn_count(&self) -> usize {
+        self.turns.len()
+    }
+
+    /// Compute the breakdown for everything observed so far.
+    #[must_use]
+    pub fn breakdown(&self) -> CostBreakdown {
+        let fragments = self.fragments();
+        let input_tokens = self.turns.iter().map(|t| t.input_tokens).sum();
+        let output_tokens = self.turns.iter().map(|t| t.output_tokens).sum();
+        let estimated =
+            input_tokens * INPUT_MICROS_PER_TOKEN + output_tokens * OUTPUT_MICROS_PER_TOKEN;
+        let total_cost_micros = self.reported_cost_usd.map_or(estimated, usd_to_micros);
+
+        let costs = distribute(
+            total_cost_micros,
+            &fragments.iter().map(Fragment::weight).collect::<Vec<_>>(),
+        );
+
+        let mut by_tool: BTreeMap<&str, CostShare> = BTreeMap::new();
+        let mut by_file: BTreeMap<&str, CostShare> = BTreeMap::new();
+        for (fragment, cost) in fragments.iter().zip(costs) {
+            let tool = by_tool.entry(&fragment.tool).or_default();
+            tool.calls += u64::from(fragment.counts_call);
+            tool.input_tokens += fragment.input_tokens;
+            tool.output_tokens += fragment.output_tokens;
+            tool.cost_micros += cost;
+
+            if let Some(file) = &fragment.file {
+                let share = by_file.entry(file).or_default();
+                share.calls += 1;
+                share.input_tokens += fragment.input_tokens;
+                share.output_tokens += fragment.output_tokens;
+                share.cost_micros += cost;
+            }
+        }
+
+        CostBreakdown {
+            total_cost_micros,
+            input_tokens,
+            output_tokens,
+            by_tool: into_sorted_shares(by_tool),
+            by_file: into_sorted_shares(by_file),
+        }
+    }
+
+    fn current_turn(&mut self) -> &mut Turn {
+        if self.turns.is_empty() {
+            self.turns.push(Turn::default());
+        }
+        self.turns.last_mut().expect("turn was just pushed")
+    }
+
+    fn record_usage(&mut self, message: &serde_json::Value) {
+        let Some(usage) = message.get("usage") else {
+            return;
+        };
+        let field = |name: &str| {
+            usage
+                .get(name)
+                .and_then(serde_json::Value::as_u64)
+                .unwrap_or(0)
+        };
+        let input = field("input_tokens")
+            + field("cache_creation_input_tokens")
+            + field("cache_read_input_tokens");
+        let output = field("output_tokens");
+
+        let turn = self.current_turn();
+        turn.input_tokens = turn.input_tokens.max(input);
+        turn.output_tokens = turn.output_tokens.max(output);
+    }
+
+    fn record_call(&mut self, id: &str, name: &str, input: &serde_json::Value) {
+        if !self.seen_calls.insert(id.to_string()) {
+            return;
+        }
+        let mut files: Vec<String> = Vec::new();
+        for path in input_paths(input) {
+            if !files.iter().any(|f| f == path) {
+                files.push(path.to_string());
+            }
+        }
+        self.current_turn().calls.push(TurnCall {
+            id: id.to_string(),
+            name: name.to_string(),
+            files,
+        });
+    }
+
+    /// Split every turn into (tool, file) fragments whose tokens sum to the turn's.
+    fn fragments(&self) -> Vec<Fragment> {
+        let mut fragments = Vec::new();
+        for turn in &self.turns {
+            if turn.calls.is_empty() {
+                fragments.push(Fragment {
+                    tool: ASSISTANT_BUCKET.to_string(),
+                    file: None,
+                    counts_call: false,
+                    input_tokens: turn.input_tokens,
+                    output_tokens: turn.output_tokens,
+                });
+                continue;
+            }
+
+            let inputs = split_evenly(turn.input_tokens, turn.calls.len());
+            let outputs = split_evenly(turn.output_tokens, turn.calls.len());
+            for ((call, input), output) in turn.calls.iter().zip(inputs).zip(outputs) {
+                tracing::trace!(tool_use_id = %call.id, tool = %call.name, "Attributing turn usage");
+                if call.files.is_empty() {
+                    fragments.push(Fragment {
+                        tool: call.name.clone(),
+                        file: None,
+                        counts_call: true,
+                        input_tokens: input,
+                        output_tokens: output,
+                    });
+                    continue;
+                }
+                let file_inputs = split_evenly(input, call.files.len());
+                let file_outputs = split_evenly(output, call.files.len());
+                for (i, ((file, input), output)) in call
+                    .files
+                    .iter()
+                    .zip(file_inputs)
+                    .zip(file_outputs)
+                    .enumerate()
+                {
+                    fragments.push(Fragment {
+                        tool: call.name.clone(),
+                        file: Some(file.clone()),
+                        counts_call: i == 0,
+                        input_tokens: input,
+                        output_tokens: output,
+                    });
+                }
+            }
+        }
+        fragments
+    }
+}
+
+fn message_id(message: &serde_json::Value) -> Option<String> {
+    message
+        .get("id")
+        .and_then(|v| v.as_str())
+        .map(str::to_string)
+}
+
+#[allow(clippy::cast_possible_truncation, clippy::cast_sign_loss)]
+fn usd_to_micros(usd: f64) -> u64 {
+    (usd.max(0.0) * 1_000_000.0).round() as u64
+}
+
+/// Split `total` into `parts` integers that sum to `total`; earlier parts get the remainder.
+fn split_evenly(total: u64, parts: usize) -> Vec<u64> {
+    let parts_u64 = parts as u64;
+    let base = total / parts_u64;
+    let remainder = total % parts_u64;
+    (0..parts_u64)
+        .map(|i| base + u64::from(i < remainder))
+        .collect()
+}
+
+/// Distribute `total` proportionally to `weights` using the largest-remainder method.
+///
+/// The result always sums to `total`. If every weight is zero, the total is split evenly.
+fn distribute(total: u64, weights: &[u64]) -> Vec<u64> {
+    if weights.is_empty() {
+        return Vec::new();
+    }
+    let weight_sum: u128 = weights.iter().map(|&w| u128::from(w)).sum();
+    if weight_sum == 0 {
+        return split_evenly(total, weights.len());
+    }
+
+    let mut shares = Vec::with_capacity(weights.len());
+    let mut remainders = Vec::with_capacity(weights.len());
+    for (i, &weight) in weights.iter().enumerate() {
+        let exact = u128::from(total) * u128::from(weight);
+        shares.push(u64::try_from(exact / weight_sum).unwrap_or(u64::MAX));
+        remainders.push((exact % weight_sum, i));
+    }
+
+    let assigned: u64 = shares.iter().sum();
+    remainders.sort_by(|a, b| b.0.cmp(&a.0).then(a.1.cmp(&b.1)));
+    for &(_, i) in remainders
+        .iter()
+        .take(usize::try_from(total - assigned).unwrap_or(0))
+    {
+        shares[i] += 1;
+    }
+    shares
+}
+
+fn into_sorted_shares(map: BTreeMap<&str, CostShare>) -> Vec<CostShare> {
+    let mut shares: Vec<CostShare> = map
+        .into_iter()
+        .map(|(key, share)| CostShare {
+            key: key.to_string(),
+            ..share
+        })
+        .collect();
+    shares.sort_by(|a, b| b.cost_micros.cmp(&a.cost_micros).then(a.key.cmp(&b.key)));
+    shares
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::cli::ResultEvent;
+    use serde_json::json;
+
+    fn message_start(id: &str, input: u64, output: u64) -> ClaudeEvent {
+        ClaudeEvent::MessageStart {
+            message: json!({"id": id, "usage": {"input_tokens": input, "output_tokens": output}}),
+        }
+    }
+
+    fn tool_use(id: &str, name: &str, input: serde_json::Value) -> ClaudeEvent {
+        ClaudeEvent::ToolUse(ToolUse {
+            id: id.to_string(),
+            name: name.to_string(),
+            input,
+        })
+    }
+
+    fn result(cost_usd: Option<f64>) -> ClaudeEvent {
+        ClaudeEvent::Result(ResultEvent {
+            result: "done".to_string(),
+            session_id: "s1".to_string(),
+            is_error: false,
+            cost_usd,
+            duration_ms: None,
+            extras: std::collections::HashMap::new(),
+        })
+    }
+
+    fn share<'a>(shares: &'a [CostShare], key: &str) -> &'a CostShare {
+        shares.iter().find(|s| s.key == key).unwrap()
+    }
+
+    #[test]
+    fn test_split_evenly_sums_to_total() {
+        assert_eq!(split_evenly(10, 3), vec![4, 3, 3]);
+        assert_eq!(split_evenly(0, 2), vec![0, 0]);
+    }
+
+    #[test]
+    fn test_distribute_largest_remainder() {
+        assert_eq!(distribute(100, &[1, 1, 1]), vec![34, 33, 33]);
+        assert_eq!(distribute(10, &[0, 0]), vec![5, 5]);
+        assert_eq!(distribute(7, &[3, 0, 1]), vec![5, 0, 2]);
+        assert!(distribute(5, &[]).is_empty());
+    }
+
+    #[test]
+    fn test_synthetic_session_attribution() {
+        let mut attributor = CostAttributor::new();
+        // Turn 1: one Read of a file.
+        attributor.observe(&message_start("m1", 1000, 100));
+        attributor.observe(&tool_use("t1", "Read", json!({"file_path": "src/lib.rs"})));
+        // Turn 2: an Edit and a Bash call share the turn.
+        attributor.observe(&message_start("m2", 2000, 400));
+        attributor.observe(&tool_use(
+            "t2",
+            "Edit",
+            json!({"file_path": "src/lib.rs", "old_string": "a", "new_string": "b"}),
+        ));
+        attributor.observe(&tool_use("t3", "Bash", json!({"command": "cargo test"})));
+        // Turn 3: final text-only answer.
+        attributor.observe(&message_start("m3", 3000, 200));
+        attributor.observe(&result(None));
+
+        let breakdown = attributor.breakdown();
+        assert_eq!(attributor.turn_count(), 3);
+        assert_eq!(breakdown.input_tokens, 6000);
+        assert_eq!(breakdown.output_tokens, 700);
+        // Estimated: 6000 * 3 + 700 * 15 micro-dollars.
+        assert_eq!(breakdown.total_cost_micros, 28_500);
+
+        let read = share(&breakdown.by_tool, "Read");
+        assert_eq!(
+            (read.calls, read.input_tokens, read.output_tokens),
+            (1, 1000, 100)
+        );
+        assert_eq!(read.cost_micros, 4500);
+
+        let edit = share(&breakdown.by_tool, "Edit");
+        assert_eq!((edit.input_tokens, edit.output_tokens), (1000, 200));
+        assert_eq!(edit.cost_micros, 6000);
+        let bash = share(&breakdown.by_tool, "Bash");
+        assert_eq!(bash.cost_micros, 6000);
+
+        let assistant = share(&breakdown.by_tool, ASSISTANT_BUCKET);
+        assert_eq!(assistant.calls, 0);
+        assert_eq!(assistant.cost_micros, 12_000);
+
+        let per_tool: u64 = breakdown.by_tool.iter().map(|s| s.cost_micros).sum();
+        assert_eq!(per_tool, breakdown.total_cost_micros);
+
+        // Both Read and Edit touched src/lib.rs; Bash touched no file.
+        assert_eq!(breakdown.by_file.len(), 1);
+        let lib = share(&breakdown.by_file, "src/lib.rs");
+        assert_eq!(lib.calls, 2);
+        assert_eq!(lib.cost_micros, 10_500);
+
+        // Most expensive first.
+        assert_eq!(breakdown.by_tool[0].key, ASSISTANT_BUCKET);
+    }
+
+    #[test]
+    fn test_reported_cost_is_distributed_exactly() {
+        let mut attributor = CostAttributor::new();
+        attributor.observe(&message_start("m1", 100, 10));
+        attributor.observe(&tool_use("t1", "Read", json!({"file_path": "a.rs"})));
+        attributor.observe(&tool_use("t2", "Read", json!({"file_path": "b.rs"})));
+        attributor.observe(&tool_use("t3", "Grep", json!({"pattern": "x"})));
+        attributor.observe(&result(Some(0.01)));
+
+        let breakdown = attributor.breakdown();
+        assert_eq!(breakdown.total_cost_micros, 10_000);
+        let per_tool: u64 = breakdown.by_tool.iter().map(|s| s.cost_micros).sum();
+        assert_eq!(per_tool, 10_000);
+        assert_eq!(share(&breakdown.by_tool, "Read").calls, 2);
+        assert!((breakdown.total_cost_usd() - 0.01).abs() < f64::EPSILON);
+    }
+
+    #[test]
+    fn test_assistant_messages_with_tool_blocks() {
+        let mut attributor = CostAttributor::new();
+        // Claude Code emits one assistant event per content block, repeating usage.
+        let usage = json!({"input_tokens": 10, "cache_read_input_tokens": 90, "output_tokens": 20});
+        attributor.observe(&ClaudeEvent::Assistant {
+            message: json!({"id": "m1", "usage": usage, "content": [{"type": "text", "text": "hi"}]}),
+        });
+        attributor.observe(&ClaudeEvent::Assistant {
+            message: json!({"id": "m1", "usage": usage, "content": [
+                {"type": "tool_use", "id": "t1", "name": "Write", "input": {"file_path": "x.rs"}}
+            ]}),
+        });
+        // The same call surfaced as a standalone event is not double-counted.
+        attributor.observe(&tool_use("t1", "Write", json!({"file_path": "x.rs"})));
+
+        let breakdown = attributor.breakdown();
+        assert_eq!(attributor.turn_count(), 1);
+        assert_eq!(breakdown.input_tokens, 100);
+        let write = share(&breakdown.by_tool, "Write");
+        assert_eq!(write.calls, 1);
+        assert_eq!(write.cost_micros, breakdown.total_cost_micros);
+        assert_eq!(breakdown.by_tool.len(), 1);
+    }
+
+    #[test]
+    fn test_reported_cost_without_usage() {
+        let mut attributor = CostAttributor::new();
+        attributor.observe(&tool_use("t1", "Bash", json!({"command": "ls"})));
+        attributor.observe(&result(Some(0.002)));
+
+        let breakdown = attributor.breakdown();
+        assert_eq!(share(&breakdown.by_tool, "Bash").cost_micros, 2000);
+    }
+
+    #[test]
+    fn test_empty_breakdown() {
+        let breakdown = CostAttributor::new().breakdown();
+        assert_eq!(breakdown.total_cost_micros, 0);
+        assert!(breakdown.by_tool.is_empty());
+        assert!(breakdown.by_file.is_empty());
+    }
+
+    #[test]
+    fn test_cost_dimension_as_str() {
+        assert_eq!(CostDimension::Tool.as_str(), "tool");
+        assert_eq!(CostDimension::File.as_str(), "file");
+    }
+}
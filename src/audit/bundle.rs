@@ -0,0 +1,493 @@
+//! Bundle of everything needed to review a finished run.
+//!
+//! A run with an artifacts directory ends by writing a directory named
+//! `<date>-<session-name>` into it, holding the run manifest, an export of
+//! the session's audit events, the mirrored transcript, a diff of each
+//! worktree, the final summary and the AI critique. A piece the run does not
+//! have is skipped, and the bundle's [`BUNDLE_INDEX_FILE`] says why.
+
+use std::fmt::Write as _;
+use std::path::{Path, PathBuf};
+
+use chrono::NaiveDate;
+use serde_json::json;
+
+use super::{AuditError, AuditEvent, AuditSession, RUN_MANIFEST_FILE};
+use crate::ai::Redactor;
+
+/// File listing the pieces of a bundle, and why any were skipped.
+pub const BUNDLE_INDEX_FILE: &str = "README.md";
+/// File holding the session's audit record and events.
+pub const AUDIT_EXPORT_FILE: &str = "audit.json";
+/// File holding the mirrored transcript.
+pub const BUNDLE_TRANSCRIPT_FILE: &str = "transcript.jsonl";
+/// File holding the final summary of the run.
+pub const SUMMARY_FILE: &str = "summary.md";
+/// File holding the AI critique of the session.
+pub const REVIEW_FILE: &str = "review.md";
+
+/// Whether a piece made it into a bundle.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum BundleEntry {
+    /// Written to this file, relative to the bundle.
+    Included(PathBuf),
+    /// Left out, for this reason.
+    Skipped(String),
+}
+
+/// One piece of a bundle.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct BundlePiece {
+    /// What the piece is, e.g. `transcript`.
+    pub name: String,
+    /// Where it was written, or why it was not.
+    pub entry: BundleEntry,
+}
+
+/// A written bundle.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct ArtifactBundle {
+    /// The bundle directory.
+    pub path: PathBuf,
+    /// Every piece, in the order of the index.
+    pub pieces: Vec<BundlePiece>,
+}
+
+impl ArtifactBundle {
+    /// Names of the pieces that were skipped.
+    #[must_use]
+    pub fn skipped(&self) -> Vec<&str> {
+        self.pieces
+            .iter()
+            .filter(|piece| matches!(piece.entry, BundleEntry::Skipped(_)))
+            .map(|piece| piece.name.as_str())
+            .collect()
+    }
+}
+
+/// Collects the artifacts of a finished run and writes them as a bundle.
+#[derive(Debug, Clone)]
+pub struct ArtifactBundler {
+    dir: PathBuf,
+    session_name: String,
+    date: NaiveDate,
+    manifest_dir: Option<PathBuf>,
+    audit: Option<(AuditSession, Vec<AuditEvent>)>,
+    transcript: Option<PathBuf>,
+    worktrees: Vec<(String, PathBuf)>,
+    summary: Option<String>,
+    critique: Option<String>,
+    redactor: Option<Redactor>,
+}
+
+impl ArtifactBundler {
+    /// Create a bundler writing the bundle of `session_name`, started on
+    /// `date`, into `dir`.
+    #[must_use]
+    pub fn new(dir: impl Into<PathBuf>, session_name: impl Into<String>, date: NaiveDate) -> Self {
+        Self {
+            dir: dir.into(),
+            session_name: session_name.into(),
+            date,
+            manifest_dir: None,
+            audit: None,
+            transcript: None,
+            worktrees: Vec::new(),
+            summary: None,
+            critique: None,
+            redactor: None,
+        }
+    }
+
+    /// Include the run manifest written to `dir`.
+    #[must_use]
+    pub fn with_manifest_dir(mut self, dir: impl Into<PathBuf>) -> Self {
+        self.manifest_dir = Some(dir.into());
+        self
+    }
+
+    /// Include the session's audit record and events.
+    #[must_use]
+    pub fn with_audit(mut self, session: AuditSession, events: Vec<AuditEvent>) -> Self {
+        self.audit = Some((session, events));
+        self
+    }
+
+    /// Include the transcript mirrored to `path`.
+    #[must_use]
+    pub fn with_transcript(mut self, path: impl Into<PathBuf>) -> Self {
+        self.transcript = Some(path.into());
+        self
+    }
+
+    /// Include the changes made in the worktree at `path`, as
+    /// `<label>.diff`.
+    #[must_use]
+    pub fn with_worktree(mut self, label: impl Into<String>, path: impl Into<PathBuf>) -> Self {
+        self.worktrees.push((label.into(), path.into()));
+        self
+    }
+
+    /// Include the final summary, as Markdown.
+    #[must_use]
+    pub fn with_summary(mut self, summary: impl Into<String>) -> Self {
+        self.summary = Some(summary.into());
+        self
+    }
+
+    /// Include the AI critique, as Markdown.
+    #[must_use]
+    pub fn with_critique(mut self, critique: impl Into<String>) -> Self {
+        self.critique = Some(critique.into());
+        self
+    }
+
+    /// Redact secrets in the audit export, transcript, diffs and summary.
+    #[must_use]
+    pub fn with_redactor(mut self, redactor: Redactor) -> Self {
+        self.redactor = Some(redactor);
+        self
+    }
+
+    /// Directory name of the bundle: `<date>-<session-name>`.
+    #[must_use]
+    pub fn bundle_name(&self) -> String {
+        format!("{}-{}", self.date.format("%Y-%m-%d"), self.session_name)
+    }
+
+    /// Write the bundle, skipping the pieces that are missing.
+    ///
+    /// A bundle of the same name is left alone; the new one gets a numeric
+    /// suffix.
+    ///
+    /// # Errors
+    ///
+    /// Returns an error if the bundle directory, a piece or the index
+    /// cannot be written. A piece that cannot be read is skipped instead.
+    pub async fn write(self) -> Result<ArtifactBundle, AuditError> {
+        let path = self.create_dir()?;
+        let mut pieces = Vec::new();
+
+        let manifest = match self.manifest_dir {
+            Some(ref dir) => match std::fs::read_to_string(dir.join(RUN_MANIFEST_FILE)) {
+                // Already redacted when written
+                Ok(manifest) => put(&path, RUN_MANIFEST_FILE, &manifest, None)?,
+                Err(e) => BundleEntry::Skipped(format!("could not read the run manifest: {e}")),
+            },
+            None => BundleEntry::Skipped("no run manifest was written".to_string()),
+        };
+        pieces.push(piece("run manifest", manifest));
+
+        let audit = match self.audit {
+            Some((ref session, ref events)) => {
+                let export = json!({ "session": session, "events": events });
+                let text = serde_json::to_string_pretty(&export)?;
+                put(&path, AUDIT_EXPORT_FILE, &text, self.redactor.as_ref())?
+            }
+            None => BundleEntry::Skipped("the audit log is disabled".to_string()),
+        };
+        pieces.push(piece("audit export", audit));
+
+        let transcript = match self.transcript {
+            Some(ref transcript) => match std::fs::read_to_string(transcript) {
+                Ok(text) => put(&path, BUNDLE_TRANSCRIPT_FILE, &text, self.redactor.as_ref())?,
+                Err(e) => {
+                    BundleEntry::Skipped(format!("could not read {}: {e}", transcript.display()))
+                }
+            },
+            None => BundleEntry::Skipped(
+                "the transcript was not mirrored (run with --mirror-transcript)".to_string(),
+            ),
+        };
+        pieces.push(piece("transcript", transcript));
+
+        if self.worktrees.is_empty() {
+            pieces.push(piece(
+                "worktree diff",
+                BundleEntry::Skipped("the session did not run in a worktree".to_string()),
+            ));
+        }
+        for (label, worktree) in &self.worktrees {
+            let diff = match worktree_diff(worktree).await {
+                Ok(diff) => put(
+                    &path,
+                    &format!("{label}.diff"),
+                    &diff,
+                    self.redactor.as_ref(),
+                )?,
+                Err(e) => {
+                    BundleEntry::Skipped(format!("could not diff {}: {e}", worktree.display()))
+                }
+            };
+            pieces.push(piece(&format!("worktree diff ({label})"), diff));
+        }
+
+        let summary = match self.summary {
+            Some(ref summary) => put(&path, SUMMARY_FILE, summary, self.redactor.as_ref())?,
+            None => BundleEntry::Skipped("no summary was produced".to_string()),
+        };
+        pieces.push(piece("summary", summary));
+
+        let critique = match self.critique {
+            Some(ref critique) => put(&path, REVIEW_FILE, critique, self.redactor.as_ref())?,
+            None => BundleEntry::Skipped("review mode did not run".to_string()),
+        };
+        pieces.push(piece("AI critique", critique));
+
+        put(
+            &path,
+            BUNDLE_INDEX_FILE,
+            &index(&self.session_name, &pieces),
+            None,
+        )?;
+        Ok(ArtifactBundle { path, pieces })
+    }
+
+    /// Create the bundle directory, numbering it if the name is taken.
+    fn create_dir(&self) -> Result<PathBuf, AuditError> {
+        std::fs::create_dir_all(&self.dir).map_err(|source| AuditError::CreateDir {
+            path: self.dir.clone(),
+            source,
+        })?;
+        let name = self.bundle_name();
+        let mut path = self.dir.join(&name);
+        let mut suffix = 1;
+        loop {
+            match std::fs::create_dir(&path) {
+                Ok(()) => return Ok(path),
+                Err(e) if e.kind() == std::io::ErrorKind::AlreadyExists => {
+                    suffix += 1;
+                    path = self.dir.join(format!("{name}-{suffix}"));
+                }
+                Err(source) => return Err(AuditError::CreateDir { path, source }),
+            }
+        }
+    }
+}
+
+fn piece(name: &str, entry: BundleEntry) -> BundlePiece {
+    BundlePiece {
+        name: name.to_string(),
+        entry,
+    }
+}
+
+/// Write `text` as `file` in the bundle at `dir`, redacted.
+fn put(
+    dir: &Path,
+    file: &str,
+    text: &str,
+    redactor: Option<&Redactor>,
+) -> Result<BundleEntry, AuditError> {
+    let path = dir.join(file);
+    let text = redactor.map_or(text.into(), |redactor| redactor.redact(text));
+    std::fs::write(&path, text.as_bytes())
+        .map_err(|source| AuditError::WriteFile { path, source })?;
+    Ok(BundleEntry::Included(PathBuf::from(file)))
+}
+
+/// Markdown index of a bundle's pieces.
+fn index(session_name: &str, pieces: &[BundlePiece]) -> String {
+    let mut out = format!("# Artifacts of {session_name}\n\n");
+    for piece in pieces {
+        let _ = match piece.entry {
+            BundleEntry::Included(ref file) => {
+                writeln!(out, "- {}: `{}`", piece.name, file.display())
+            }
+            BundleEntry::Skipped(ref reason) => {
+                writeln!(out, "- {}: skipped, {reason}", piece.name)
+            }
+        };
+    }
+    out
+}
+
+/// Changes in a worktree since it branched off its repository's `HEAD`,
+/// committed or not, including untracked files.
+async fn worktree_diff(worktree: &Path) -> Result<String, String> {
+    let common_dir = git(worktree, &["rev-parse", "--git-common-dir"], false).await?;
+    let git_dir = format!("--git-dir={}", worktree.join(common_dir.trim()).display());
+    // The worktree's branch started at the commit its repository is on
+    let base = match git(worktree, &[&git_dir, "rev-parse", "HEAD"], false).await {
+        Ok(repo_head) => git(worktree, &["merge-base", "HEAD", repo_head.trim()], false)
+            .await
+            .ok(),
+        Err(_) => None,
+    };
+    let base = base.map_or_else(|| "HEAD".to_string(), |base| base.trim().to_string());
+
+    let mut diff = git(worktree, &["diff", &base], false).await?;
+    let untracked = git(
+        worktree,
+        &["ls-files", "--others", "--exclude-standard", "-z"],
+        false,
+    )
+    .await?;
+    for file in untracked.split('\0').filter(|file| !file.is_empty()) {
+        let args = ["diff", "--no-index", "--", "/dev/null", file];
+        diff.push_str(&git(worktree, &args, true).await?);
+    }
+    Ok(diff)
+}
+
+/// Output of a git command; `differs` accepts the exit status 1 with
+/// which `git diff --no-index` reports a difference.
+async fn git(dir: &Path, args: &[&str], differs: bool) -> Result<String, String> {
+    let output = tokio::process::Command::new("git")
+        .args(args)
+        .current_dir(dir)
+        .stdin(std::process::Stdio::null())
+        .kill_on_drop(true)
+        .output()
+        .await
+        .map_err(|e| e.to_string())?;
+    if output.status.success() || (differs && output.status.code() == Some(1)) {
+        Ok(String::from_utf8_lossy(&output.stdout).into_owned())
+    } else {
+        Err(String::from_utf8_lossy(&output.stderr).trim().to_string())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::audit::{Decision, EventType};
+
+    fn date() -> NaiveDate {
+        NaiveDate::from_ymd_opt(2026, 3, 14).unwrap()
+    }
+
+    #[tokio::test]
+    async fn test_bundle_skips_missing_pieces() {
+        let dir = tempfile::tempdir().unwrap();
+        let manifest_dir = dir.path().join("runs/brave-otter");
+        std::fs::create_dir_all(&manifest_dir).unwrap();
+        std::fs::write(
+            manifest_dir.join(RUN_MANIFEST_FILE),
+            r#"{"session_name":"brave-otter"}"#,
+        )
+        .unwrap();
+        let session = AuditSession::new("Fix the tests").with_name("brave-otter");
+        let event = AuditEvent::builder(session.id, EventType::PolicyDecision)
+            .tool_name("Bash")
+            .decision(Decision::Deny)
+            .reason("token=sk-ant-REDACTED")
+            .build();
+
+        let bundle = ArtifactBundler::new(dir.path().join("artifacts"), "brave-otter", date())
+            .with_manifest_dir(&manifest_dir)
+            .with_audit(session, vec![event])
+            .with_transcript(dir.path().join("missing.jsonl"))
+            .with_summary("# Run summary: brave-otter\n")
+            .with_redactor(Redactor::new())
+            .write()
+            .await
+            .unwrap();
+
+        assert_eq!(
+            bundle.path,
+            dir.path().join("artifacts/2026-03-14-brave-otter")
+        );
+        let mut files: Vec<String> = std::fs::read_dir(&bundle.path)
+            .unwrap()
+            .map(|entry| entry.unwrap().file_name().to_string_lossy().into_owned())
+            .collect();
+        files.sort();
+        assert_eq!(
+            files,
+            [
+                BUNDLE_INDEX_FILE,
+                AUDIT_EXPORT_FILE,
+                RUN_MANIFEST_FILE,
+                SUMMARY_FILE
+            ]
+        );
+        assert_eq!(
+            bundle.skipped(),
+            ["transcript", "worktree diff", "AI critique"]
+        );
+
+        let audit: serde_json::Value = serde_json::from_str(
+            &std::fs::read_to_string(bundle.path.join(AUDIT_EXPORT_FILE)).unwrap(),
+        )
+        .unwrap();
+        assert_eq!(audit["session"]["name"], "brave-otter");
+        let reason = audit["events"][0]["reason"].as_str().unwrap();
+        assert!(!reason.contains("sk-ant-api03"), "{reason}");
+
+        let index = std::fs::read_to_string(bundle.path.join(BUNDLE_INDEX_FILE)).unwrap();
+        assert!(
+            index.contains("- run manifest: `run-manifest.json`"),
+            "{index}"
+        );
+        assert!(
+            index.contains("- transcript: skipped, could not read"),
+            "{index}"
+        );
+        assert!(
+            index.contains("- AI critique: skipped, review mode did not run"),
+            "{index}"
+        );
+
+        // A second bundle of the same run is numbered
+        let again = ArtifactBundler::new(dir.path().join("artifacts"), "brave-otter", date())
+            .write()
+            .await
+            .unwrap();
+        assert_eq!(
+            again.path,
+            dir.path().join("artifacts/2026-03-14-brave-otter-2")
+        );
+        assert_eq!(again.skipped().len(), 6);
+    }
+
+    #[tokio::test]
+    async fn test_bundle_diffs_worktree() {
+        let dir = tempfile::tempdir().unwrap();
+        let repo = dir.path().join("repo");
+        std::fs::create_dir(&repo).unwrap();
+        let git = |cwd: &Path, args: &[&str]| {
+            std::process::Command::new("git")
+                .args(["-c", "user.name=t", "-c", "user.email=t@example.com"])
+                .args(args)
+                .current_dir(cwd)
+                .output()
+                .unwrap()
+        };
+        if !git(&repo, &["init", "-q", "-b", "main"]).status.success() {
+            return;
+        }
+        std::fs::write(repo.join("lib.rs"), "fn main() {}\n").unwrap();
+        git(&repo, &["add", "lib.rs"]);
+        git(&repo, &["commit", "-q", "-m", "init"]);
+        let worktree = dir.path().join("task");
+        git(
+            &repo,
+            &[
+                "worktree",
+                "add",
+                "-q",
+                "-b",
+                "task",
+                worktree.to_str().unwrap(),
+            ],
+        );
+
+        // One committed change, one uncommitted, one untracked file
+        std::fs::write(worktree.join("lib.rs"), "fn main() { run() }\n").unwrap();
+        git(&worktree, &["commit", "-q", "-am", "run"]);
+        std::fs::write(worktree.join("lib.rs"), "fn main() { run(); }\n").unwrap();
+        std::fs::write(worktree.join("new.rs"), "fn run() {}\n").unwrap();
+
+        let bundle = ArtifactBundler::new(dir.path().join("artifacts"), "task", date())
+            .with_worktree("task", &worktree)
+            .write()
+            .await
+            .unwrap();
+        assert!(!bundle.skipped().contains(&"worktree diff (task)"));
+        let diff = std::fs::read_to_string(bundle.path.join("task.diff")).unwrap();
+        assert!(diff.contains("-fn main() {}"), "{diff}");
+        assert!(diff.contains("+fn main() { run(); }"), "{diff}");
+        assert!(diff.contains("+fn run() {}"), "{diff}");
+    }
+}
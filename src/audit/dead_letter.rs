@@ -0,0 +1,116 @@
+//! Dead-letter log of events the supervisor could not make sense of.
+//!
+//! Events of a type the stream parser does not know are kept whole, one
+//! JSON line each, so the next Claude Code format change can be studied
+//! from real payloads.
+
+use std::io::Write;
+use std::path::{Path, PathBuf};
+
+use chrono::{SecondsFormat, Utc};
+use serde_json::{json, Value};
+
+use super::AuditError;
+
+/// Returns the directory holding dead-letter logs, one file per audit
+/// session.
+///
+/// This is `state/dead-letters` in the
+/// [data directory](crate::config::data_dir).
+#[must_use]
+pub fn default_dead_letter_dir() -> PathBuf {
+    crate::config::state_dir().join("dead-letters")
+}
+
+/// Appends unknown events to a JSONL file while a session runs.
+#[derive(Debug)]
+pub struct DeadLetterLog {
+    path: PathBuf,
+    file: std::fs::File,
+}
+
+impl DeadLetterLog {
+    /// Open the log at `path` for appending, creating its directory.
+    ///
+    /// # Errors
+    ///
+    /// Returns an error if the directory or the file cannot be created.
+    pub fn create(path: &Path) -> Result<Self, AuditError> {
+        if let Some(dir) = path.parent() {
+            std::fs::create_dir_all(dir).map_err(|source| AuditError::CreateDir {
+                path: dir.to_path_buf(),
+                source,
+            })?;
+        }
+        let file = std::fs::OpenOptions::new()
+            .create(true)
+            .append(true)
+            .open(path)
+            .map_err(|source| AuditError::WriteFile {
+                path: path.to_path_buf(),
+                source,
+            })?;
+        Ok(Self {
+            path: path.to_path_buf(),
+            file,
+        })
+    }
+
+    /// Path of the log.
+    #[must_use]
+    pub fn path(&self) -> &Path {
+        &self.path
+    }
+
+    /// Append `payload`, the event as received, of type `kind`.
+    ///
+    /// # Errors
+    ///
+    /// Returns an error if the line cannot be written.
+    pub fn write(&mut self, kind: &str, payload: &Value) -> Result<(), AuditError> {
+        let entry = json!({
+            "received_at": Utc::now().to_rfc3339_opts(SecondsFormat::Millis, true),
+            "type": kind,
+            "event": payload,
+        });
+        let mut line = serde_json::to_vec(&entry)?;
+        line.push(b'\n');
+        self.file
+            .write_all(&line)
+            .map_err(|source| AuditError::WriteFile {
+                path: self.path.clone(),
+                source,
+            })
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_dead_letter_log_appends_lines() {
+        let dir = tempfile::tempdir().unwrap();
+        let path = dir.path().join("letters").join("session.jsonl");
+        let mut log = DeadLetterLog::create(&path).unwrap();
+        log.write("rate_limit", &json!({"type": "rate_limit", "retry_in": 3}))
+            .unwrap();
+        drop(log);
+        let mut log = DeadLetterLog::create(&path).unwrap();
+        log.write(
+            "hook/progress",
+            &json!({"type": "hook", "subtype": "progress"}),
+        )
+        .unwrap();
+
+        let text = std::fs::read_to_string(&path).unwrap();
+        let lines: Vec<Value> = text
+            .lines()
+            .map(|line| serde_json::from_str(line).unwrap())
+            .collect();
+        assert_eq!(lines.len(), 2);
+        assert_eq!(lines[0]["type"], "rate_limit");
+        assert_eq!(lines[0]["event"]["retry_in"], 3);
+        assert_eq!(lines[1]["event"]["subtype"], "progress");
+    }
+}
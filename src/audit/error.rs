@@ -32,6 +32,14 @@ pub enum AuditError {
         #[source]
         source: std::io::Error,
     },
+
+    /// Failed to write a file.
+    #[error("Failed to write {path}: {source}")]
+    WriteFile {
+        path: PathBuf,
+        #[source]
+        source: std::io::Error,
+    },
 }
 
 #[cfg(test)]
@@ -0,0 +1,257 @@
+//! Audit sink writing JSON lines files for log shippers.
+//!
+//! Records go to `audit-<date>.jsonl` in the sink's directory. A new file is
+//! started each UTC day, and within a day whenever the current file would
+//! grow past the size limit: `audit-<date>.1.jsonl`, `audit-<date>.2.jsonl`
+//! and so on. Old files are left for the shipper or logrotate to remove.
+
+use std::io::Write;
+use std::path::{Path, PathBuf};
+use std::sync::Mutex;
+
+use async_trait::async_trait;
+use chrono::{DateTime, NaiveDate, Utc};
+
+use super::error::AuditError;
+use super::sink::{AuditRecord, AuditSink};
+use crate::ai::Redactor;
+use crate::config::AuditSinkKind;
+
+/// Returns the default directory of JSONL audit files.
+///
+/// This is `audit` in the [data directory](crate::config::data_dir).
+#[must_use]
+pub fn default_jsonl_dir() -> PathBuf {
+    crate::config::data_dir().join("audit")
+}
+
+/// Appends audit records to JSONL files rotated by size and day.
+#[derive(Debug)]
+pub struct JsonlSink {
+    dir: PathBuf,
+    /// Size at which a file is rotated; 0 rotates by day only.
+    max_bytes: u64,
+    redactor: Option<Redactor>,
+    current: Mutex<Option<JsonlFile>>,
+}
+
+/// The file records are currently appended to.
+#[derive(Debug)]
+struct JsonlFile {
+    day: NaiveDate,
+    index: u32,
+    path: PathBuf,
+    file: std::fs::File,
+    size: u64,
+}
+
+impl JsonlSink {
+    /// Write files to `dir`, creating it.
+    ///
+    /// # Errors
+    ///
+    /// Returns an error if the directory cannot be created.
+    pub fn open(dir: PathBuf, max_bytes: u64) -> Result<Self, AuditError> {
+        std::fs::create_dir_all(&dir).map_err(|source| AuditError::CreateDir {
+            path: dir.clone(),
+            source,
+        })?;
+        Ok(Self {
+            dir,
+            max_bytes,
+            redactor: None,
+            current: Mutex::new(None),
+        })
+    }
+
+    /// Redact secrets from written records (builder pattern).
+    #[must_use]
+    pub fn with_redactor(mut self, redactor: Redactor) -> Self {
+        self.redactor = Some(redactor);
+        self
+    }
+
+    /// Directory of the files.
+    #[must_use]
+    pub fn dir(&self) -> &Path {
+        &self.dir
+    }
+
+    /// Path of the `index`th file of `day`.
+    fn file_path(&self, day: NaiveDate, index: u32) -> PathBuf {
+        let name = match index {
+            0 => format!("audit-{day}.jsonl"),
+            _ => format!("audit-{day}.{index}.jsonl"),
+        };
+        self.dir.join(name)
+    }
+
+    /// Index of the last file of `day` an earlier run left behind.
+    fn last_index(&self, day: NaiveDate) -> u32 {
+        let mut index = 0;
+        while self.file_path(day, index + 1).exists() {
+            index += 1;
+        }
+        index
+    }
+
+    /// Open the first file of `day` from `index` on that still has room.
+    fn open_file(&self, day: NaiveDate, mut index: u32) -> Result<JsonlFile, AuditError> {
+        loop {
+            let path = self.file_path(day, index);
+            let size = std::fs::metadata(&path).map_or(0, |meta| meta.len());
+            if self.max_bytes > 0 && size >= self.max_bytes {
+                index += 1;
+                continue;
+            }
+            let file = std::fs::OpenOptions::new()
+                .create(true)
+                .append(true)
+                .open(&path)
+                .map_err(|source| AuditError::WriteFile {
+                    path: path.clone(),
+                    source,
+                })?;
+            return Ok(JsonlFile {
+                day,
+                index,
+                path,
+                file,
+                size,
+            });
+        }
+    }
+
+    /// Append `line` as of `now`, rotating first if needed.
+    fn write_line_at(&self, now: DateTime<Utc>, line: &[u8]) -> Result<(), AuditError> {
+        let day = now.date_naive();
+        let len = line.len() as u64;
+        let mut current = self.current.lock().expect("Mutex poisoned");
+        let next = match *current {
+            Some(ref file) if file.day != day => Some(0),
+            Some(ref file)
+                if self.max_bytes > 0 && file.size > 0 && file.size + len > self.max_bytes =>
+            {
+                Some(file.index + 1)
+            }
+            Some(_) => None,
+            None => Some(self.last_index(day)),
+        };
+        if let Some(index) = next {
+            *current = Some(self.open_file(day, index)?);
+        }
+
+        let file = current.as_mut().expect("file opened above");
+        file.file
+            .write_all(line)
+            .map_err(|source| AuditError::WriteFile {
+                path: file.path.clone(),
+                source,
+            })?;
+        file.size += len;
+        Ok(())
+    }
+}
+
+#[async_trait]
+impl AuditSink for JsonlSink {
+    fn name(&self) -> &'static str {
+        AuditSinkKind::Jsonl.as_str()
+    }
+
+    async fn write(&self, record: &AuditRecord) -> Result<(), AuditError> {
+        let mut line = match self.redactor {
+            Some(ref redactor) => serde_json::to_vec(&record.redacted(redactor))?,
+            None => serde_json::to_vec(record)?,
+        };
+        line.push(b'\n');
+        self.write_line_at(Utc::now(), &line)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use chrono::TimeZone;
+
+    use super::*;
+
+    fn files(dir: &Path) -> Vec<(String, usize)> {
+        let mut files: Vec<_> = std::fs::read_dir(dir)
+            .unwrap()
+            .map(|entry| {
+                let path = entry.unwrap().path();
+                let lines = std::fs::read_to_string(&path).unwrap().lines().count();
+                (
+                    path.file_name().unwrap().to_string_lossy().into_owned(),
+                    lines,
+                )
+            })
+            .collect();
+        files.sort();
+        files
+    }
+
+    #[test]
+    fn test_rotates_by_size_and_day() {
+        let dir = tempfile::tempdir().unwrap();
+        let sink = JsonlSink::open(dir.path().to_path_buf(), 25).unwrap();
+        let line = b"{\"record\":\"event\"}\n";
+        let day1 = Utc.with_ymd_and_hms(2026, 10, 14, 23, 0, 0).unwrap();
+        let day2 = Utc.with_ymd_and_hms(2026, 10, 15, 0, 30, 0).unwrap();
+
+        // Each 19 byte line fills a 25 byte file
+        for _ in 0..3 {
+            sink.write_line_at(day1, line).unwrap();
+        }
+        sink.write_line_at(day2, line).unwrap();
+
+        assert_eq!(
+            files(dir.path()),
+            [
+                ("audit-2026-10-14.1.jsonl".to_string(), 1),
+                ("audit-2026-10-14.2.jsonl".to_string(), 1),
+                ("audit-2026-10-14.jsonl".to_string(), 1),
+                ("audit-2026-10-15.jsonl".to_string(), 1),
+            ]
+        );
+    }
+
+    #[test]
+    fn test_reopened_sink_continues_in_last_file() {
+        let dir = tempfile::tempdir().unwrap();
+        let now = Utc.with_ymd_and_hms(2026, 10, 15, 12, 0, 0).unwrap();
+        let line = b"{\"record\":\"metrics\"}\n";
+        {
+            let sink = JsonlSink::open(dir.path().to_path_buf(), 30).unwrap();
+            sink.write_line_at(now, line).unwrap();
+            sink.write_line_at(now, line).unwrap();
+        }
+
+        // A new run continues in the last file, then rotates
+        let sink = JsonlSink::open(dir.path().to_path_buf(), 50).unwrap();
+        sink.write_line_at(now, line).unwrap();
+        sink.write_line_at(now, line).unwrap();
+        assert_eq!(
+            files(dir.path()),
+            [
+                ("audit-2026-10-15.1.jsonl".to_string(), 2),
+                ("audit-2026-10-15.2.jsonl".to_string(), 1),
+                ("audit-2026-10-15.jsonl".to_string(), 1),
+            ]
+        );
+    }
+
+    #[test]
+    fn test_without_size_limit_rotates_by_day_only() {
+        let dir = tempfile::tempdir().unwrap();
+        let sink = JsonlSink::open(dir.path().to_path_buf(), 0).unwrap();
+        let now = Utc.with_ymd_and_hms(2026, 10, 15, 12, 0, 0).unwrap();
+        for _ in 0..100 {
+            sink.write_line_at(now, b"{}\n").unwrap();
+        }
+        assert_eq!(
+            files(dir.path()),
+            [("audit-2026-10-15.jsonl".to_string(), 100)]
+        );
+    }
+}
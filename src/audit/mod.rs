@@ -1,11 +1,43 @@
 //! Audit logging module for supervisor decisions.
 
+mod attribution;
+mod bundle;
+mod dead_letter;
 mod error;
+mod jsonl;
 mod logger;
+mod manifest;
+mod rule_stats;
 mod schema;
+mod sink;
+mod suggestions;
+#[cfg(unix)]
+mod syslog;
+mod transcript;
 mod types;
 
+pub use attribution::{CostAttributor, CostBreakdown, CostDimension, CostShare, ASSISTANT_BUCKET};
+pub use bundle::{
+    ArtifactBundle, ArtifactBundler, BundleEntry, BundlePiece, AUDIT_EXPORT_FILE,
+    BUNDLE_INDEX_FILE, BUNDLE_TRANSCRIPT_FILE, REVIEW_FILE, SUMMARY_FILE,
+};
+pub use dead_letter::{default_dead_letter_dir, DeadLetterLog};
 pub use error::AuditError;
+pub use jsonl::{default_jsonl_dir, JsonlSink};
 pub use logger::{default_audit_path, AuditLog};
-pub use schema::{SCHEMA, SCHEMA_VERSION};
+pub use manifest::{
+    config_hash, default_manifest_dir, redact_config, RunLimits, RunManifest, RUN_MANIFEST_FILE,
+};
+pub use rule_stats::{RuleStats, RuleStatsReport, SUGGEST_MIN_DECIDED, SUGGEST_SHARE_PERCENT};
+pub use schema::{migrate, SCHEMA, SCHEMA_VERSION};
+pub use sink::{AuditRecord, AuditSink, AuditSinks};
+pub use suggestions::{
+    suggest_claude_md_additions, suggestions_markdown, ClaudeMdSuggestion, DEFAULT_MAX_SUGGESTIONS,
+    SUGGESTIONS_FILE,
+};
+#[cfg(unix)]
+pub use syslog::{SyslogSink, DEFAULT_SYSLOG_SOCKET};
+pub use transcript::{
+    default_transcript_dir, reconstruct, write_transcript, TranscriptBuilder, TranscriptMirror,
+};
 pub use types::{AuditEvent, AuditSession, Decision, EventType, SessionMetrics};
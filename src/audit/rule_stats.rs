@@ -0,0 +1,239 @@
+//! How policy rules and tools fared across sessions.
+//!
+//! Each rule firing is audited with the rule and with who made the final
+//! decision. Aggregated over a time window, the firings show which rules
+//! earn their keep: a rule whose escalations the AI or a human almost always
+//! allows is noise, and one whose escalations are almost always denied might
+//! as well deny. Rules with enough decided escalations get a suggestion.
+
+use std::collections::{BTreeMap, HashSet};
+
+use chrono::{DateTime, Utc};
+use serde::{Deserialize, Serialize};
+
+use crate::audit::Decision;
+use crate::supervisor::DecisionSource;
+
+/// Fewest decided escalations before a rule gets a suggestion.
+pub const SUGGEST_MIN_DECIDED: u64 = 10;
+
+/// Share of decided escalations, in percent, that must go one way before a
+/// rule gets a suggestion.
+pub const SUGGEST_SHARE_PERCENT: u64 = 90;
+
+/// Outcomes of the calls one rule fired on, or of one tool's rule firings.
+#[derive(Debug, Clone, Default, PartialEq, Eq, Serialize, Deserialize)]
+pub struct RuleStats {
+    /// Rule name or tool name.
+    pub key: String,
+    /// Times the rule fired.
+    pub fired: u64,
+    /// Calls the rule denied outright.
+    pub denied: u64,
+    /// Calls the rule escalated.
+    pub escalated: u64,
+    /// Escalations the AI supervisor allowed.
+    pub ai_allowed: u64,
+    /// Escalations the AI supervisor denied.
+    pub ai_denied: u64,
+    /// Escalations a human allowed.
+    pub human_allowed: u64,
+    /// Escalations a human denied.
+    pub human_denied: u64,
+    /// Sessions the rule fired in.
+    pub sessions: u64,
+    /// Suggested change to the rule, when its outcomes are lopsided.
+    pub suggestion: Option<String>,
+}
+
+impl RuleStats {
+    /// Escalations the AI supervisor or a human decided.
+    #[must_use]
+    pub fn decided(&self) -> u64 {
+        self.ai_allowed + self.ai_denied + self.human_allowed + self.human_denied
+    }
+
+    /// Suggestion from the decided escalations, if there are enough of them
+    /// and nearly all went the same way.
+    fn suggest(&self) -> Option<String> {
+        let decided = self.decided();
+        if decided < SUGGEST_MIN_DECIDED {
+            return None;
+        }
+        let allowed = self.ai_allowed + self.human_allowed;
+        let denied = decided - allowed;
+        if allowed * 100 >= decided * SUGGEST_SHARE_PERCENT {
+            Some("consider demoting to log-only".to_string())
+        } else if denied * 100 >= decided * SUGGEST_SHARE_PERCENT {
+            Some("consider denying outright".to_string())
+        } else {
+            None
+        }
+    }
+}
+
+/// Per-rule and per-tool outcomes of rule firings since a point in time.
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct RuleStatsReport {
+    /// Start of the window.
+    pub since: DateTime<Utc>,
+    /// Outcomes per rule, most fired first.
+    pub rules: Vec<RuleStats>,
+    /// Outcomes per tool, most fired first.
+    pub tools: Vec<RuleStats>,
+}
+
+/// Count of rule firings sharing a rule, tool, session and outcome.
+#[derive(Debug, Clone)]
+pub(crate) struct RuleFiringCount {
+    pub rule: String,
+    pub tool_name: Option<String>,
+    pub session_id: String,
+    pub decision: Option<Decision>,
+    pub decided_by: Option<DecisionSource>,
+    pub count: u64,
+}
+
+/// A [`RuleStats`] being summed up, with the sessions seen so far.
+#[derive(Default)]
+struct Tally {
+    stats: RuleStats,
+    sessions: HashSet<String>,
+}
+
+impl Tally {
+    fn add(&mut self, row: &RuleFiringCount) {
+        let stats = &mut self.stats;
+        stats.fired += row.count;
+        match (row.decided_by, row.decision) {
+            (Some(DecisionSource::Policy), _) => stats.denied += row.count,
+            (Some(DecisionSource::Ai), Some(Decision::Allow)) => {
+                stats.escalated += row.count;
+                stats.ai_allowed += row.count;
+            }
+            (Some(DecisionSource::Ai), _) => {
+                stats.escalated += row.count;
+                stats.ai_denied += row.count;
+            }
+            (Some(DecisionSource::Human), Some(Decision::Allow)) => {
+                stats.escalated += row.count;
+                stats.human_allowed += row.count;
+            }
+            (Some(DecisionSource::Human), _) => {
+                stats.escalated += row.count;
+                stats.human_denied += row.count;
+            }
+            // Hook and fallback decisions, and escalations the session
+            // ended before deciding
+            _ => stats.escalated += row.count,
+        }
+        self.sessions.insert(row.session_id.clone());
+    }
+
+    fn finish(mut self, key: String) -> RuleStats {
+        self.stats.key = key;
+        self.stats.sessions = self.sessions.len() as u64;
+        self.stats.suggestion = self.stats.suggest();
+        self.stats
+    }
+}
+
+impl RuleStatsReport {
+    /// Sum up `rows` into per-rule and per-tool outcomes.
+    pub(crate) fn from_rows(since: DateTime<Utc>, rows: &[RuleFiringCount]) -> Self {
+        let mut rules: BTreeMap<&str, Tally> = BTreeMap::new();
+        let mut tools: BTreeMap<&str, Tally> = BTreeMap::new();
+        for row in rows {
+            rules.entry(&row.rule).or_default().add(row);
+            if let Some(tool_name) = &row.tool_name {
+                tools.entry(tool_name).or_default().add(row);
+            }
+        }
+        Self {
+            since,
+            rules: sorted(rules),
+            tools: sorted(tools),
+        }
+    }
+}
+
+/// Finished tallies, most fired first and by key among equals.
+fn sorted(tallies: BTreeMap<&str, Tally>) -> Vec<RuleStats> {
+    let mut stats: Vec<RuleStats> = tallies
+        .into_iter()
+        .map(|(key, tally)| tally.finish(key.to_string()))
+        .collect();
+    // The sort is stable, so equal counts keep the map's key order
+    stats.sort_by_key(|stats| std::cmp::Reverse(stats.fired));
+    stats
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn row(
+        rule: &str,
+        session: &str,
+        outcome: Option<(Decision, DecisionSource)>,
+        count: u64,
+    ) -> RuleFiringCount {
+        RuleFiringCount {
+            rule: rule.to_string(),
+            tool_name: Some("Bash".to_string()),
+            session_id: session.to_string(),
+            decision: Some(outcome.map_or(Decision::Escalate, |(decision, _)| decision)),
+            decided_by: outcome.map(|(_, source)| source),
+            count,
+        }
+    }
+
+    #[test]
+    fn test_suggestions_need_enough_lopsided_decisions() {
+        let since = Utc::now();
+        let rows = [
+            row(
+                "moderate level",
+                "s-1",
+                Some((Decision::Allow, DecisionSource::Ai)),
+                9,
+            ),
+            row(
+                "moderate level",
+                "s-2",
+                Some((Decision::Allow, DecisionSource::Human)),
+                1,
+            ),
+            row(
+                "novel binary",
+                "s-1",
+                Some((Decision::Allow, DecisionSource::Ai)),
+                8,
+            ),
+            row(
+                "novel binary",
+                "s-1",
+                Some((Decision::Deny, DecisionSource::Human)),
+                2,
+            ),
+            row("mcp server", "s-1", None, 30),
+        ];
+        let report = RuleStatsReport::from_rows(since, &rows);
+
+        let keys: Vec<_> = report.rules.iter().map(|s| s.key.as_str()).collect();
+        assert_eq!(keys, ["mcp server", "moderate level", "novel binary"]);
+        assert_eq!(
+            report.rules[1].suggestion.as_deref(),
+            Some("consider demoting to log-only")
+        );
+        assert_eq!(report.rules[1].sessions, 2);
+        // 80% allowed is not lopsided enough
+        assert_eq!(report.rules[2].suggestion, None);
+        // Undecided escalations do not count
+        assert_eq!(report.rules[0].decided(), 0);
+        assert_eq!(report.rules[0].suggestion, None);
+
+        assert_eq!(report.tools.len(), 1);
+        assert_eq!(report.tools[0].fired, 50);
+    }
+}
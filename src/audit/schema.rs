@@ -1,7 +1,9 @@
 //! Database schema for audit logging.
 
+use rusqlite::Connection;
+
 /// Current schema version for migrations.
-pub const SCHEMA_VERSION: u32 = 1;
+pub const SCHEMA_VERSION: u32 = 10;
 
 /// SQL schema for the audit database.
 pub const SCHEMA: &str = r"
@@ -14,6 +16,11 @@ CREATE TABLE IF NOT EXISTS sessions (
     ended_at TEXT,
     task TEXT NOT NULL,
     result TEXT,
+    config TEXT,
+    permission_mode TEXT,
+    name TEXT,
+    claude_session_id TEXT,
+    claude_code_version TEXT,
     created_at TEXT NOT NULL DEFAULT (datetime('now'))
 );
 
@@ -27,6 +34,10 @@ CREATE TABLE IF NOT EXISTS events (
     tool_input TEXT,
     decision TEXT,
     reason TEXT,
+    snapshot_id TEXT,
+    tool_alias TEXT,
+    rule TEXT,
+    decided_by TEXT,
     created_at TEXT NOT NULL DEFAULT (datetime('now')),
     FOREIGN KEY (session_id) REFERENCES sessions(id) ON DELETE CASCADE
 );
@@ -39,10 +50,26 @@ CREATE TABLE IF NOT EXISTS metrics (
     api_calls INTEGER NOT NULL DEFAULT 0,
     cache_hits INTEGER NOT NULL DEFAULT 0,
     estimated_cost_cents INTEGER NOT NULL DEFAULT 0,
+    blast_radius_peak INTEGER NOT NULL DEFAULT 0,
+    blast_radius_threshold INTEGER NOT NULL DEFAULT 0,
+    decision_sources TEXT,
     updated_at TEXT NOT NULL DEFAULT (datetime('now')),
     FOREIGN KEY (session_id) REFERENCES sessions(id) ON DELETE CASCADE
 );
 
+-- Cost attribution table: approximate per-tool and per-file session cost
+CREATE TABLE IF NOT EXISTS cost_attribution (
+    session_id TEXT NOT NULL,
+    dimension TEXT NOT NULL,
+    key TEXT NOT NULL,
+    calls INTEGER NOT NULL DEFAULT 0,
+    input_tokens INTEGER NOT NULL DEFAULT 0,
+    output_tokens INTEGER NOT NULL DEFAULT 0,
+    cost_micros INTEGER NOT NULL DEFAULT 0,
+    PRIMARY KEY (session_id, dimension, key),
+    FOREIGN KEY (session_id) REFERENCES sessions(id) ON DELETE CASCADE
+);
+
 -- Schema version table for migrations
 CREATE TABLE IF NOT EXISTS schema_version (
     version INTEGER PRIMARY KEY NOT NULL,
@@ -54,17 +81,140 @@ CREATE INDEX IF NOT EXISTS idx_events_session_id ON events(session_id);
 CREATE INDEX IF NOT EXISTS idx_events_timestamp ON events(timestamp);
 CREATE INDEX IF NOT EXISTS idx_events_event_type ON events(event_type);
 CREATE INDEX IF NOT EXISTS idx_events_decision ON events(decision);
+CREATE INDEX IF NOT EXISTS idx_events_rule ON events(rule);
 CREATE INDEX IF NOT EXISTS idx_sessions_started_at ON sessions(started_at);
+CREATE INDEX IF NOT EXISTS idx_sessions_name ON sessions(name);
+CREATE INDEX IF NOT EXISTS idx_cost_attribution_dimension ON cost_attribution(dimension);
 ";
 
+/// Bring a database created by an older schema up to date.
+///
+/// `SCHEMA` only creates missing tables, so columns added since are applied
+/// here. Safe to run on every open.
+///
+/// # Errors
+///
+/// Returns an error if the schema cannot be inspected or altered.
+pub fn migrate(conn: &Connection) -> rusqlite::Result<()> {
+    add_column_if_missing(conn, "events", "snapshot_id", "TEXT")?;
+    add_column_if_missing(conn, "events", "tool_alias", "TEXT")?;
+    add_column_if_missing(conn, "events", "rule", "TEXT")?;
+    add_column_if_missing(conn, "events", "decided_by", "TEXT")?;
+    add_column_if_missing(conn, "sessions", "config", "TEXT")?;
+    add_column_if_missing(conn, "sessions", "permission_mode", "TEXT")?;
+    add_column_if_missing(conn, "sessions", "name", "TEXT")?;
+    add_column_if_missing(conn, "sessions", "claude_session_id", "TEXT")?;
+    add_column_if_missing(conn, "sessions", "claude_code_version", "TEXT")?;
+    for column in ["blast_radius_peak", "blast_radius_threshold"] {
+        add_column_if_missing(conn, "metrics", column, "INTEGER NOT NULL DEFAULT 0")?;
+    }
+    add_column_if_missing(conn, "metrics", "decision_sources", "TEXT")?;
+    conn.execute(
+        "CREATE INDEX IF NOT EXISTS idx_sessions_name ON sessions(name)",
+        [],
+    )?;
+    conn.execute(
+        "CREATE INDEX IF NOT EXISTS idx_events_rule ON events(rule)",
+        [],
+    )?;
+    conn.execute(
+        "INSERT OR IGNORE INTO schema_version (version) VALUES (?1)",
+        [SCHEMA_VERSION],
+    )?;
+    Ok(())
+}
+
+/// Add a column of type `definition` unless the table already has it.
+fn add_column_if_missing(
+    conn: &Connection,
+    table: &str,
+    column: &str,
+    definition: &str,
+) -> rusqlite::Result<()> {
+    let exists = conn
+        .prepare(&format!(
+            "SELECT 1 FROM pragma_table_info('{table}') WHERE name = ?1"
+        ))?
+        .exists([column])?;
+    if !exists {
+        conn.execute(
+            &format!("ALTER TABLE {table} ADD COLUMN {column} {definition}"),
+            [],
+        )?;
+    }
+    Ok(())
+}
+
 #[cfg(test)]
 mod tests {
     use super::*;
-    use rusqlite::Connection;
 
     #[test]
     fn test_schema_version() {
-        assert_eq!(SCHEMA_VERSION, 1);
+        assert_eq!(SCHEMA_VERSION, 10);
+    }
+
+    #[test]
+    fn test_migrate_adds_columns_to_v1_tables() {
+        let conn = Connection::open_in_memory().unwrap();
+        let v1 = SCHEMA
+            .replace("    snapshot_id TEXT,\n", "")
+            .replace("    tool_alias TEXT,\n", "")
+            .replace("    rule TEXT,\n", "")
+            .replace("    decided_by TEXT,\n", "")
+            .replace("    config TEXT,\n", "")
+            .replace("    permission_mode TEXT,\n", "")
+            .replace("    name TEXT,\n", "")
+            .replace("    claude_session_id TEXT,\n", "")
+            .replace("    claude_code_version TEXT,\n", "")
+            .replace("    blast_radius_peak INTEGER NOT NULL DEFAULT 0,\n", "")
+            .replace(
+                "    blast_radius_threshold INTEGER NOT NULL DEFAULT 0,\n",
+                "",
+            )
+            .replace("    decision_sources TEXT,\n", "")
+            .replace(
+                "CREATE INDEX IF NOT EXISTS idx_sessions_name ON sessions(name);\n",
+                "",
+            )
+            .replace(
+                "CREATE INDEX IF NOT EXISTS idx_events_rule ON events(rule);\n",
+                "",
+            );
+        conn.execute_batch(&v1).unwrap();
+
+        migrate(&conn).unwrap();
+        migrate(&conn).unwrap();
+
+        for (table, column) in [
+            ("events", "snapshot_id"),
+            ("events", "tool_alias"),
+            ("events", "rule"),
+            ("events", "decided_by"),
+            ("sessions", "config"),
+            ("sessions", "permission_mode"),
+            ("sessions", "name"),
+            ("sessions", "claude_session_id"),
+            ("sessions", "claude_code_version"),
+            ("metrics", "blast_radius_peak"),
+            ("metrics", "blast_radius_threshold"),
+            ("metrics", "decision_sources"),
+        ] {
+            let count: i64 = conn
+                .query_row(
+                    &format!("SELECT COUNT(*) FROM pragma_table_info('{table}') WHERE name = ?1"),
+                    [column],
+                    |row| row.get(0),
+                )
+                .unwrap();
+            assert_eq!(count, 1, "{table}.{column}");
+        }
+        let version: u32 = conn
+            .query_row("SELECT MAX(version) FROM schema_version", [], |row| {
+                row.get(0)
+            })
+            .unwrap();
+        assert_eq!(version, SCHEMA_VERSION);
     }
 
     #[test]
@@ -102,6 +252,16 @@ mod tests {
             .unwrap();
         assert_eq!(count, 1);
 
+        // Verify cost_attribution table exists
+        let count: i64 = conn
+            .query_row(
+                "SELECT COUNT(*) FROM sqlite_master WHERE type='table' AND name='cost_attribution'",
+                [],
+                |row| row.get(0),
+            )
+            .unwrap();
+        assert_eq!(count, 1);
+
         // Verify schema_version table exists
         let count: i64 = conn
             .query_row(
@@ -125,6 +285,7 @@ mod tests {
             "idx_events_event_type",
             "idx_events_decision",
             "idx_sessions_started_at",
+            "idx_cost_attribution_dimension",
         ];
 
         for index_name in expected_indexes {
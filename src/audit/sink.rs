@@ -0,0 +1,325 @@
+//! Destinations of audit records.
+//!
+//! Every record of a session is written to each configured [`AuditSink`].
+//! Only the `SQLite` [`AuditLog`] can be queried; the JSONL and syslog sinks
+//! exist so central log aggregation sees the same decisions.
+
+use std::fmt::Debug;
+
+use async_trait::async_trait;
+use chrono::{DateTime, Utc};
+use serde::Serialize;
+use uuid::Uuid;
+
+use super::attribution::CostBreakdown;
+use super::error::AuditError;
+use super::jsonl::{default_jsonl_dir, JsonlSink};
+use super::logger::{default_audit_path, AuditLog};
+use super::manifest::redact_config;
+#[cfg(unix)]
+use super::syslog::SyslogSink;
+use super::types::{AuditEvent, AuditSession, SessionMetrics};
+use crate::ai::Redactor;
+use crate::config::{AuditConfig, AuditSinkConfig, AuditSinkKind};
+
+/// A record written to every audit sink.
+#[derive(Debug, Clone, Serialize)]
+#[serde(tag = "record", rename_all = "snake_case")]
+pub enum AuditRecord {
+    /// A session started.
+    SessionStart(AuditSession),
+    /// An event of a session.
+    Event(AuditEvent),
+    /// A session ended.
+    SessionEnd {
+        /// Session that ended.
+        session_id: Uuid,
+        /// When the session ended.
+        ended_at: DateTime<Utc>,
+        /// How the session ended.
+        result: String,
+    },
+    /// Usage metrics of a session.
+    Metrics(SessionMetrics),
+    /// Cost attribution of a session.
+    CostBreakdown {
+        /// Session the costs belong to.
+        session_id: Uuid,
+        /// Costs by tool and file.
+        breakdown: CostBreakdown,
+    },
+}
+
+impl AuditRecord {
+    /// End of `session_id` now, with `result`.
+    pub fn session_end(session_id: Uuid, result: impl Into<String>) -> Self {
+        Self::SessionEnd {
+            session_id,
+            ended_at: Utc::now(),
+            result: result.into(),
+        }
+    }
+
+    /// Name of the record type, as in the `record` field of its JSON.
+    #[must_use]
+    pub fn kind(&self) -> &'static str {
+        match self {
+            Self::SessionStart(_) => "session_start",
+            Self::Event(_) => "event",
+            Self::SessionEnd { .. } => "session_end",
+            Self::Metrics(_) => "metrics",
+            Self::CostBreakdown { .. } => "cost_breakdown",
+        }
+    }
+
+    /// Session the record belongs to.
+    #[must_use]
+    pub fn session_id(&self) -> Uuid {
+        match self {
+            Self::SessionStart(session) => session.id,
+            Self::Event(event) => event.session_id,
+            Self::Metrics(metrics) => metrics.session_id,
+            Self::SessionEnd { session_id, .. } | Self::CostBreakdown { session_id, .. } => {
+                *session_id
+            }
+        }
+    }
+
+    /// The record with secrets redacted from the task, config snapshot,
+    /// tool inputs and reasons, as the `SQLite` log stores them.
+    #[must_use]
+    pub fn redacted(&self, redactor: &Redactor) -> Self {
+        let mut record = self.clone();
+        match record {
+            Self::SessionStart(ref mut session) => {
+                session.task = redactor.redact(&session.task).into_owned();
+                session.config = session
+                    .config
+                    .as_ref()
+                    .map(|config| redact_config(config, Some(redactor)));
+            }
+            Self::Event(ref mut event) => {
+                event.tool_input = event
+                    .tool_input
+                    .as_ref()
+                    .map(|input| redactor.redact_value(input));
+                event.reason = event
+                    .reason
+                    .as_deref()
+                    .map(|reason| redactor.redact(reason).into_owned());
+            }
+            Self::SessionEnd { .. } | Self::Metrics(_) | Self::CostBreakdown { .. } => {}
+        }
+        record
+    }
+}
+
+/// A destination of audit records.
+#[async_trait]
+pub trait AuditSink: Debug + Send + Sync {
+    /// Name of the sink in warnings.
+    fn name(&self) -> &'static str;
+
+    /// Write one record.
+    ///
+    /// # Errors
+    ///
+    /// Returns an error if the record cannot be written.
+    async fn write(&self, record: &AuditRecord) -> Result<(), AuditError>;
+}
+
+#[async_trait]
+impl AuditSink for AuditLog {
+    fn name(&self) -> &'static str {
+        AuditSinkKind::Sqlite.as_str()
+    }
+
+    async fn write(&self, record: &AuditRecord) -> Result<(), AuditError> {
+        match record {
+            AuditRecord::SessionStart(session) => self.log_session_start(session).await,
+            AuditRecord::Event(event) => self.log_event(event).await,
+            AuditRecord::SessionEnd {
+                session_id, result, ..
+            } => self.log_session_end(*session_id, result.as_str()).await,
+            AuditRecord::Metrics(metrics) => self.log_metrics(metrics).await,
+            AuditRecord::CostBreakdown {
+                session_id,
+                breakdown,
+            } => self.log_cost_breakdown(*session_id, breakdown).await,
+        }
+    }
+}
+
+/// Every configured sink; records fan out to all of them.
+#[derive(Debug, Default)]
+pub struct AuditSinks {
+    sinks: Vec<Box<dyn AuditSink>>,
+}
+
+impl AuditSinks {
+    /// No sinks.
+    #[must_use]
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Open the sinks of `config`, each redacting with `redactor` if given.
+    ///
+    /// A sink that cannot be opened is skipped with a warning, so the
+    /// others still receive records.
+    pub async fn open(config: &AuditConfig, redactor: Option<&Redactor>) -> Self {
+        let mut sinks = Self::new();
+        for sink in &config.sinks {
+            match open_sink(sink, redactor).await {
+                Ok(opened) => sinks.sinks.push(opened),
+                Err(e) => {
+                    tracing::warn!(sink = sink.kind.as_str(), error = %e, "Failed to open audit sink");
+                }
+            }
+        }
+        sinks
+    }
+
+    /// Add a sink (builder pattern).
+    #[must_use]
+    pub fn with_sink(mut self, sink: impl AuditSink + 'static) -> Self {
+        self.sinks.push(Box::new(sink));
+        self
+    }
+
+    /// Whether there are no sinks.
+    #[must_use]
+    pub fn is_empty(&self) -> bool {
+        self.sinks.is_empty()
+    }
+
+    /// Names of the sinks, in order.
+    #[must_use]
+    pub fn names(&self) -> Vec<&'static str> {
+        self.sinks.iter().map(|sink| sink.name()).collect()
+    }
+
+    /// Write `record` to every sink.
+    ///
+    /// A failing sink does not keep the record from the others.
+    ///
+    /// # Errors
+    ///
+    /// Returns the first error, after every sink was tried.
+    pub async fn write(&self, record: &AuditRecord) -> Result<(), AuditError> {
+        let mut first_error = None;
+        for sink in &self.sinks {
+            if let Err(e) = sink.write(record).await {
+                tracing::warn!(sink = sink.name(), record = record.kind(), error = %e, "Failed to write audit record");
+                first_error.get_or_insert(e);
+            }
+        }
+        first_error.map_or(Ok(()), Err)
+    }
+}
+
+/// Open one configured sink.
+async fn open_sink(
+    config: &AuditSinkConfig,
+    redactor: Option<&Redactor>,
+) -> Result<Box<dyn AuditSink>, AuditError> {
+    let sink: Box<dyn AuditSink> = match config.kind {
+        AuditSinkKind::Sqlite => {
+            let mut log = AuditLog::open(default_audit_path()).await?;
+            if let Some(redactor) = redactor {
+                log = log.with_redactor(redactor.clone());
+            }
+            Box::new(log)
+        }
+        AuditSinkKind::Jsonl => {
+            let dir = config.path.clone().unwrap_or_else(default_jsonl_dir);
+            let mut sink = JsonlSink::open(dir, config.max_bytes)?;
+            if let Some(redactor) = redactor {
+                sink = sink.with_redactor(redactor.clone());
+            }
+            Box::new(sink)
+        }
+        #[cfg(unix)]
+        AuditSinkKind::Syslog => {
+            let mut sink = match config.path {
+                Some(ref socket) => SyslogSink::new(socket.clone()),
+                None => SyslogSink::default(),
+            };
+            if let Some(redactor) = redactor {
+                sink = sink.with_redactor(redactor.clone());
+            }
+            Box::new(sink)
+        }
+        #[cfg(not(unix))]
+        AuditSinkKind::Syslog => {
+            return Err(AuditError::WriteFile {
+                path: config.path.clone().unwrap_or_default(),
+                source: std::io::Error::new(
+                    std::io::ErrorKind::Unsupported,
+                    "syslog sink requires a Unix socket",
+                ),
+            })
+        }
+    };
+    Ok(sink)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::audit::{Decision, EventType};
+
+    #[tokio::test]
+    async fn test_records_fan_out_to_every_sink() {
+        let dir = tempfile::tempdir().unwrap();
+        let sqlite = AuditLog::open_in_memory().await.unwrap();
+        let sinks = AuditSinks::new()
+            .with_sink(sqlite.clone())
+            .with_sink(JsonlSink::open(dir.path().to_path_buf(), 0).unwrap());
+        assert_eq!(sinks.names(), ["sqlite", "jsonl"]);
+
+        let session = AuditSession::new("fix the tests");
+        let event = AuditEvent::builder(session.id, EventType::PolicyDecision)
+            .tool_name("Bash")
+            .decision(Decision::Deny)
+            .build();
+        for record in [
+            AuditRecord::SessionStart(session.clone()),
+            AuditRecord::Event(event),
+            AuditRecord::session_end(session.id, "completed"),
+        ] {
+            sinks.write(&record).await.unwrap();
+        }
+
+        assert_eq!(sqlite.count_by_decision(Decision::Deny).await.unwrap(), 1);
+        let found = sqlite.find_session(&session.id.to_string()).await.unwrap();
+        assert_eq!(found.unwrap().result.as_deref(), Some("completed"));
+
+        let file = std::fs::read_dir(dir.path()).unwrap().next().unwrap();
+        let lines: Vec<serde_json::Value> = std::fs::read_to_string(file.unwrap().path())
+            .unwrap()
+            .lines()
+            .map(|line| serde_json::from_str(line).unwrap())
+            .collect();
+        let kinds: Vec<_> = lines.iter().map(|line| line["record"].clone()).collect();
+        assert_eq!(kinds, ["session_start", "event", "session_end"]);
+        assert_eq!(lines[1]["decision"], "deny");
+        assert_eq!(lines[2]["session_id"], session.id.to_string());
+    }
+
+    #[tokio::test]
+    async fn test_failing_sink_does_not_starve_the_others() {
+        let dir = tempfile::tempdir().unwrap();
+        let sqlite = AuditLog::open_in_memory().await.unwrap();
+        // Events of a session that was never started violate the foreign key
+        let sinks = AuditSinks::new()
+            .with_sink(sqlite)
+            .with_sink(JsonlSink::open(dir.path().to_path_buf(), 0).unwrap());
+        let event = AuditEvent::builder(Uuid::new_v4(), EventType::ToolUse).build();
+
+        assert!(sinks.write(&AuditRecord::Event(event)).await.is_err());
+        let file = std::fs::read_dir(dir.path()).unwrap().next().unwrap();
+        let written = std::fs::read_to_string(file.unwrap().path()).unwrap();
+        assert_eq!(written.lines().count(), 1);
+    }
+}
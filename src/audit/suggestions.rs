@@ -0,0 +1,426 @@
+//! CLAUDE.md additions suggested by a session's friction.
+//!
+//! The suggestions are derived from the session's audit events alone, so the
+//! same events always give the same suggestions:
+//! - a denied command followed by an allowed one sharing its program or its
+//!   subcommand suggests running the allowed one instead;
+//! - a denial by an edit or path rule names the convention it enforces;
+//! - a command or file denied more than once suggests leaving it alone;
+//! - a command that hung suggests running it in the background.
+//!
+//! Suggestions are ranked by how often their friction occurred, then by when
+//! it first did.
+
+use std::collections::HashMap;
+use std::fmt::Write as _;
+
+use chrono::{DateTime, Utc};
+use serde::{Deserialize, Serialize};
+
+use super::{AuditEvent, Decision, EventType};
+use crate::supervisor::HUNG_TOOL_REASON;
+
+/// File the suggestions of a session are written to.
+pub const SUGGESTIONS_FILE: &str = "suggested-claude-md-additions.md";
+
+/// Default most suggestions kept for a session.
+pub const DEFAULT_MAX_SUGGESTIONS: usize = 5;
+
+/// Most words of a command quoted in a suggestion.
+const QUOTED_WORDS: usize = 3;
+
+/// A line suggested for CLAUDE.md, with the friction behind it.
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct ClaudeMdSuggestion {
+    /// What the line is about.
+    pub topic: String,
+    /// The line to add.
+    pub text: String,
+    /// What happened in the session that led to the suggestion.
+    pub evidence: String,
+}
+
+/// A suggestion with what it is ranked by.
+struct Ranked {
+    suggestion: ClaudeMdSuggestion,
+    occurrences: usize,
+    first: DateTime<Utc>,
+}
+
+/// Denials grouped by what they denied.
+struct Denials<'a> {
+    rule: &'a str,
+    count: usize,
+    first: DateTime<Utc>,
+}
+
+/// Up to `max` CLAUDE.md additions derived from a session's audit events.
+#[must_use]
+pub fn suggest_claude_md_additions(events: &[AuditEvent], max: usize) -> Vec<ClaudeMdSuggestion> {
+    let mut events: Vec<&AuditEvent> = events.iter().collect();
+    events.sort_by_key(|event| event.timestamp);
+
+    let mut ranked = Vec::new();
+    let mut conventions: HashMap<&str, Ranked> = HashMap::new();
+    let mut denials: HashMap<(&str, String), Denials<'_>> = HashMap::new();
+    let mut hung: HashMap<String, Ranked> = HashMap::new();
+    let mut replacements: HashMap<String, String> = HashMap::new();
+
+    for (index, &event) in events.iter().enumerate() {
+        let tool = event.tool_name.as_deref().unwrap_or_default();
+        let rule = event.rule.as_deref().unwrap_or_default();
+        match (event.event_type, event.decision) {
+            (EventType::PolicyDecision, Some(Decision::Deny)) if is_convention(rule) => {
+                let entry = conventions.entry(rule).or_insert_with(|| Ranked {
+                    suggestion: convention(rule, event.reason.as_deref().unwrap_or_default()),
+                    occurrences: 0,
+                    first: event.timestamp,
+                });
+                entry.occurrences += 1;
+                entry.suggestion.evidence = times("denied", entry.occurrences);
+            }
+            (EventType::PolicyDecision, Some(Decision::Deny)) => {
+                let Some(subject) = subject(event) else {
+                    continue;
+                };
+                if tool == "Bash" && !replacements.contains_key(&subject) {
+                    if let Some(allowed) = replacement(&subject, &events[index + 1..]) {
+                        replacements.insert(subject.clone(), allowed);
+                    }
+                }
+                let group = denials.entry((tool, subject)).or_insert_with(|| Denials {
+                    rule,
+                    count: 0,
+                    first: event.timestamp,
+                });
+                group.count += 1;
+            }
+            (EventType::Error, _)
+                if event
+                    .reason
+                    .as_deref()
+                    .is_some_and(|reason| reason.contains(HUNG_TOOL_REASON)) =>
+            {
+                let Some(command) = command(event).map(quoted) else {
+                    continue;
+                };
+                let entry = hung.entry(command.clone()).or_insert_with(|| Ranked {
+                    suggestion: ClaudeMdSuggestion {
+                        topic: "Long-running commands".to_string(),
+                        text: format!(
+                            "`{command}` does not exit on its own; run it in the background."
+                        ),
+                        evidence: String::new(),
+                    },
+                    occurrences: 0,
+                    first: event.timestamp,
+                });
+                entry.occurrences += 1;
+                entry.suggestion.evidence = times("hung", entry.occurrences);
+            }
+            _ => {}
+        }
+    }
+
+    ranked.extend(denials.into_iter().filter_map(|((tool, subject), group)| {
+        denial(tool, &subject, &group, replacements.get(&subject))
+    }));
+    ranked.extend(conventions.into_values());
+    ranked.extend(hung.into_values());
+
+    ranked.sort_by(|a, b| {
+        b.occurrences
+            .cmp(&a.occurrences)
+            .then(a.first.cmp(&b.first))
+            .then_with(|| a.suggestion.text.cmp(&b.suggestion.text))
+    });
+    ranked
+        .into_iter()
+        .take(max)
+        .map(|ranked| ranked.suggestion)
+        .collect()
+}
+
+/// Suggestion for a denied command or file: its replacement if a similar
+/// command was allowed later, otherwise a warning once it was denied twice.
+fn denial(
+    tool: &str,
+    subject: &str,
+    group: &Denials<'_>,
+    allowed: Option<&String>,
+) -> Option<Ranked> {
+    let denied = times("denied", group.count);
+    let (topic, text, evidence) = if let Some(allowed) = allowed {
+        (
+            "Commands",
+            format!("Use `{allowed}` instead of `{subject}`."),
+            format!("{denied}, then `{allowed}` was allowed"),
+        )
+    } else if group.count < 2 {
+        return None;
+    } else if tool == "Bash" {
+        (
+            "Commands",
+            format!(
+                "Do not run `{subject}`; the supervisor denies it ({}).",
+                group.rule
+            ),
+            denied,
+        )
+    } else {
+        (
+            "Files",
+            format!(
+                "Do not modify `{subject}`; the supervisor denies it ({}).",
+                group.rule
+            ),
+            denied,
+        )
+    };
+    Some(Ranked {
+        suggestion: ClaudeMdSuggestion {
+            topic: topic.to_string(),
+            text,
+            evidence,
+        },
+        occurrences: group.count,
+        first: group.first,
+    })
+}
+
+/// Render suggestions as Markdown for [`SUGGESTIONS_FILE`].
+#[must_use]
+pub fn suggestions_markdown(session: &str, suggestions: &[ClaudeMdSuggestion]) -> String {
+    let mut out = format!(
+        "# Suggested CLAUDE.md additions: {session}\n\n\
+         Derived from what the supervisor saw Claude struggle with; review before adding.\n\n"
+    );
+    for suggestion in suggestions {
+        let _ = writeln!(
+            out,
+            "- **{}**: {} ({})",
+            suggestion.topic, suggestion.text, suggestion.evidence
+        );
+    }
+    out
+}
+
+/// Whether `rule` enforces a project convention.
+fn is_convention(rule: &str) -> bool {
+    rule.starts_with("edit rule: ") || rule.starts_with("path rule: ")
+}
+
+/// Suggestion naming the convention an edit or path rule enforces.
+fn convention(rule: &str, reason: &str) -> ClaudeMdSuggestion {
+    // Reasons read `<rule> '<name>': <tool> of <path> <what>`
+    let detail = reason
+        .split_once("': ")
+        .map_or(reason, |(_, detail)| detail);
+    let text = match (
+        rule.strip_prefix("edit rule: "),
+        rule.strip_prefix("path rule: "),
+    ) {
+        (Some(name), _) => format!("Follow the \"{name}\" convention; denied: {detail}."),
+        (_, Some(pattern)) => {
+            format!("Do not modify files matching `{pattern}`; denied: {detail}.")
+        }
+        _ => reason.to_string(),
+    };
+    ClaudeMdSuggestion {
+        topic: "Conventions".to_string(),
+        text,
+        evidence: String::new(),
+    }
+}
+
+/// The first later allowed command that shares the denied command's
+/// program or subcommand.
+fn replacement(denied: &str, later: &[&AuditEvent]) -> Option<String> {
+    later.iter().find_map(|event| {
+        let allowed = event.event_type == EventType::PolicyDecision
+            && event.decision == Some(Decision::Allow)
+            && event.tool_name.as_deref() == Some("Bash");
+        let command = allowed.then(|| subject(event)).flatten()?;
+        (command != denied && related(denied, &command)).then_some(command)
+    })
+}
+
+/// Whether two commands share their program or their subcommand.
+fn related(a: &str, b: &str) -> bool {
+    let program = |command: &str| command.split(' ').next().unwrap_or_default().to_string();
+    let subcommand = |command: &str| {
+        command
+            .split(' ')
+            .nth(1)
+            .filter(|word| {
+                word.chars()
+                    .all(|c| c.is_ascii_lowercase() || c == '-' || c == ':')
+            })
+            .map(ToString::to_string)
+    };
+    program(a) == program(b) || subcommand(a).is_some_and(|sub| subcommand(b) == Some(sub))
+}
+
+/// What a denied call was denied for: the quoted command of a Bash call or
+/// the path of a file call.
+fn subject(event: &AuditEvent) -> Option<String> {
+    if event.tool_name.as_deref() == Some("Bash") {
+        return command(event)
+            .map(quoted)
+            .filter(|command| !command.is_empty());
+    }
+    let input = event.tool_input.as_ref()?;
+    ["file_path", "notebook_path", "path"]
+        .iter()
+        .find_map(|field| input.get(field)?.as_str())
+        .map(ToString::to_string)
+}
+
+/// The command of a Bash call.
+fn command(event: &AuditEvent) -> Option<&str> {
+    event.tool_input.as_ref()?.get("command")?.as_str()
+}
+
+/// The first words of a command's first simple command, without leading
+/// variable assignments.
+fn quoted(command: &str) -> String {
+    let first = command
+        .split(['|', ';', '&', '\n'])
+        .next()
+        .unwrap_or_default();
+    first
+        .split_whitespace()
+        .skip_while(|word| word.contains('='))
+        .take(QUOTED_WORDS)
+        .collect::<Vec<_>>()
+        .join(" ")
+}
+
+/// `<verb> once` or `<verb> <n> times`.
+fn times(verb: &str, n: usize) -> String {
+    if n == 1 {
+        format!("{verb} once")
+    } else {
+        format!("{verb} {n} times")
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use chrono::TimeZone;
+    use serde_json::json;
+    use uuid::Uuid;
+
+    use super::*;
+    use crate::supervisor::DecisionSource;
+
+    /// A rule firing on a tool call, `minute` minutes into the session.
+    fn firing(
+        minute: u32,
+        tool: &str,
+        input: serde_json::Value,
+        decision: Decision,
+        rule: &str,
+    ) -> AuditEvent {
+        let mut event = AuditEvent::builder(Uuid::nil(), EventType::PolicyDecision)
+            .timestamp(Utc.with_ymd_and_hms(2026, 1, 1, 0, minute, 0).unwrap())
+            .tool_name(tool)
+            .tool_input(input)
+            .decision(decision)
+            .reason(format!("{rule} fired"))
+            .rule(rule);
+        if decision != Decision::Escalate {
+            event = event.decided_by(DecisionSource::Ai);
+        }
+        event.build()
+    }
+
+    fn bash(minute: u32, command: &str, decision: Decision, rule: &str) -> AuditEvent {
+        firing(
+            minute,
+            "Bash",
+            json!({ "command": command }),
+            decision,
+            rule,
+        )
+    }
+
+    fn session() -> Vec<AuditEvent> {
+        let mut edit = firing(
+            4,
+            "Edit",
+            json!({ "file_path": "src/lib.rs" }),
+            Decision::Deny,
+            "edit rule: keep unsafe forbidden",
+        );
+        edit.reason = Some(
+            "Edit rule 'keep unsafe forbidden': Edit of src/lib.rs removes `#![forbid(unsafe_code)]`"
+                .to_string(),
+        );
+        let hung = AuditEvent::builder(Uuid::nil(), EventType::Error)
+            .timestamp(Utc.with_ymd_and_hms(2026, 1, 1, 0, 9, 0).unwrap())
+            .tool_name("Bash")
+            .tool_input(json!({ "command": "npm run dev" }))
+            .reason(format!(
+                "Tool Bash {HUNG_TOOL_REASON}: no result after 300s (timeout 300s)"
+            ))
+            .build();
+        vec![
+            hung,
+            bash(
+                1,
+                "npm install --save-dev vitest",
+                Decision::Deny,
+                "bash allowlist",
+            ),
+            bash(
+                2,
+                "npm install --save-dev vitest",
+                Decision::Deny,
+                "bash allowlist",
+            ),
+            bash(
+                3,
+                "pnpm install --save-dev vitest",
+                Decision::Allow,
+                "bash allowlist",
+            ),
+            edit,
+            bash(5, "git push --force", Decision::Deny, "git gate"),
+            bash(6, "git push --force", Decision::Deny, "git gate"),
+            bash(7, "git push --force", Decision::Deny, "git gate"),
+            firing(
+                8,
+                "Write",
+                json!({ "file_path": ".env" }),
+                Decision::Deny,
+                "sensitive path",
+            ),
+            bash(10, "cargo build", Decision::Escalate, "moderate level"),
+        ]
+    }
+
+    #[test]
+    fn test_suggestions_snapshot() {
+        let suggestions = suggest_claude_md_additions(&session(), DEFAULT_MAX_SUGGESTIONS);
+        assert_eq!(
+            suggestions_markdown("brave-otter", &suggestions),
+            "# Suggested CLAUDE.md additions: brave-otter\n\n\
+             Derived from what the supervisor saw Claude struggle with; review before adding.\n\n\
+             - **Commands**: Do not run `git push --force`; the supervisor denies it (git gate). (denied 3 times)\n\
+             - **Commands**: Use `pnpm install --save-dev` instead of `npm install --save-dev`. (denied 2 times, then `pnpm install --save-dev` was allowed)\n\
+             - **Conventions**: Follow the \"keep unsafe forbidden\" convention; denied: Edit of src/lib.rs removes `#![forbid(unsafe_code)]`. (denied once)\n\
+             - **Long-running commands**: `npm run dev` does not exit on its own; run it in the background. (hung once)\n"
+        );
+    }
+
+    #[test]
+    fn test_suggestions_are_deterministic_and_capped() {
+        let mut events = session();
+        let first = suggest_claude_md_additions(&events, 2);
+        events.reverse();
+        assert_eq!(suggest_claude_md_additions(&events, 2), first);
+        assert_eq!(first.len(), 2);
+        assert!(suggest_claude_md_additions(&[], DEFAULT_MAX_SUGGESTIONS).is_empty());
+    }
+}
@@ -0,0 +1,232 @@
+//! Audit sink sending records to the local syslog socket.
+//!
+//! Records are RFC 5424 messages: the fields a log pipeline filters on
+//! (session, event type, tool, decision) go in a structured data element,
+//! and the human-readable part is the reason, task or result. journald
+//! listens on the same socket, so this covers both.
+
+use std::fmt::Write as _;
+use std::os::unix::net::UnixDatagram;
+use std::path::{Path, PathBuf};
+
+use async_trait::async_trait;
+use chrono::{SecondsFormat, Utc};
+
+use super::error::AuditError;
+use super::sink::{AuditRecord, AuditSink};
+use super::types::{Decision, EventType};
+use crate::ai::Redactor;
+use crate::config::AuditSinkKind;
+use crate::display;
+
+/// Default syslog socket.
+pub const DEFAULT_SYSLOG_SOCKET: &str = "/dev/log";
+
+/// `APP-NAME` of the messages.
+const APP_NAME: &str = "claude-supervisor";
+
+/// ID of the structured data element holding a record's fields.
+const SD_ID: &str = "audit@32473";
+
+/// Syslog facility of the messages: user-level.
+const FACILITY_USER: u8 = 1;
+
+/// Longest message text; longer reasons and tasks are truncated so the
+/// datagram stays within what syslog daemons accept.
+const MAX_MESSAGE_BYTES: usize = 4096;
+
+/// Syslog severities used by records.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+#[repr(u8)]
+enum Severity {
+    Error = 3,
+    Warning = 4,
+    Info = 6,
+}
+
+/// Sends audit records to a syslog socket.
+#[derive(Debug)]
+pub struct SyslogSink {
+    socket: PathBuf,
+    redactor: Option<Redactor>,
+}
+
+impl Default for SyslogSink {
+    fn default() -> Self {
+        Self::new(PathBuf::from(DEFAULT_SYSLOG_SOCKET))
+    }
+}
+
+impl SyslogSink {
+    /// Send records to the datagram socket at `socket`.
+    #[must_use]
+    pub fn new(socket: PathBuf) -> Self {
+        Self {
+            socket,
+            redactor: None,
+        }
+    }
+
+    /// Redact secrets from sent records (builder pattern).
+    #[must_use]
+    pub fn with_redactor(mut self, redactor: Redactor) -> Self {
+        self.redactor = Some(redactor);
+        self
+    }
+
+    /// Path of the socket.
+    #[must_use]
+    pub fn socket(&self) -> &Path {
+        &self.socket
+    }
+}
+
+#[async_trait]
+impl AuditSink for SyslogSink {
+    fn name(&self) -> &'static str {
+        AuditSinkKind::Syslog.as_str()
+    }
+
+    async fn write(&self, record: &AuditRecord) -> Result<(), AuditError> {
+        let message = match self.redactor {
+            Some(ref redactor) => format_message(&record.redacted(redactor)),
+            None => format_message(record),
+        };
+        let send = || -> std::io::Result<()> {
+            UnixDatagram::unbound()?.send_to(message.as_bytes(), &self.socket)?;
+            Ok(())
+        };
+        send().map_err(|source| AuditError::WriteFile {
+            path: self.socket.clone(),
+            source,
+        })
+    }
+}
+
+/// Format `record` as an RFC 5424 message.
+fn format_message(record: &AuditRecord) -> String {
+    let mut params = vec![
+        ("record", record.kind().to_string()),
+        ("session", record.session_id().to_string()),
+    ];
+    let mut severity = Severity::Info;
+    let text = match record {
+        AuditRecord::SessionStart(session) => {
+            if let Some(ref name) = session.name {
+                params.push(("name", name.clone()));
+            }
+            session.task.clone()
+        }
+        AuditRecord::Event(event) => {
+            params.push(("event_type", event.event_type.as_str().to_string()));
+            if let Some(ref tool) = event.tool_name {
+                params.push(("tool", tool.clone()));
+            }
+            if let Some(ref alias) = event.tool_alias {
+                params.push(("tool_alias", alias.clone()));
+            }
+            if let Some(ref rule) = event.rule {
+                params.push(("rule", rule.clone()));
+            }
+            if let Some(source) = event.decided_by {
+                params.push(("decided_by", source.as_str().to_string()));
+            }
+            if let Some(decision) = event.decision {
+                params.push(("decision", decision.as_str().to_string()));
+            }
+            if let Some(ref snapshot) = event.snapshot_id {
+                params.push(("snapshot", snapshot.clone()));
+            }
+            severity = match (event.event_type, event.decision) {
+                (EventType::Error, _) => Severity::Error,
+                (_, Some(Decision::Deny)) => Severity::Warning,
+                _ => Severity::Info,
+            };
+            event
+                .reason
+                .clone()
+                .unwrap_or_else(|| event.event_type.as_str().to_string())
+        }
+        AuditRecord::SessionEnd { result, .. } => result.clone(),
+        AuditRecord::Metrics(metrics) => {
+            params.push(("input_tokens", metrics.input_tokens.to_string()));
+            params.push(("output_tokens", metrics.output_tokens.to_string()));
+            params.push(("cost_cents", metrics.estimated_cost_cents.to_string()));
+            "session metrics".to_string()
+        }
+        AuditRecord::CostBreakdown { breakdown, .. } => {
+            params.push(("cost_micros", breakdown.total_cost_micros.to_string()));
+            "cost breakdown".to_string()
+        }
+    };
+
+    let mut structured = String::new();
+    for (name, value) in &params {
+        let _ = write!(structured, " {name}=\"{}\"", escape_param(value));
+    }
+    format!(
+        "<{}>1 {} - {APP_NAME} {} {} [{SD_ID}{structured}] {}",
+        FACILITY_USER * 8 + severity as u8,
+        Utc::now().to_rfc3339_opts(SecondsFormat::Millis, true),
+        std::process::id(),
+        record.kind(),
+        display::truncate_bytes(&text, MAX_MESSAGE_BYTES),
+    )
+}
+
+/// Escape a structured data parameter value.
+fn escape_param(value: &str) -> String {
+    let mut escaped = String::with_capacity(value.len());
+    for c in value.chars() {
+        if matches!(c, '"' | '\\' | ']') {
+            escaped.push('\\');
+        }
+        escaped.push(c);
+    }
+    escaped
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::audit::AuditEvent;
+
+    #[tokio::test]
+    async fn test_sends_structured_message() {
+        let dir = tempfile::tempdir().unwrap();
+        let socket = dir.path().join("log.sock");
+        let receiver = UnixDatagram::bind(&socket).unwrap();
+        let sink = SyslogSink::new(socket);
+
+        let event = AuditEvent::builder(uuid::Uuid::nil(), EventType::PolicyDecision)
+            .tool_name("Bash")
+            .decision(Decision::Deny)
+            .reason("Blocked \"rm -rf /\" [destructive]")
+            .build();
+        sink.write(&AuditRecord::Event(event)).await.unwrap();
+
+        let mut buf = [0; 2048];
+        let len = receiver.recv(&mut buf).unwrap();
+        let message = std::str::from_utf8(&buf[..len]).unwrap();
+        assert!(message.starts_with("<12>1 "), "{message}");
+        assert!(message.contains(" - claude-supervisor "));
+        assert!(message.contains(&format!(
+            " event [audit@32473 record=\"event\" session=\"{}\" event_type=\"policy_decision\" tool=\"Bash\" decision=\"deny\"] ",
+            uuid::Uuid::nil()
+        )));
+        assert!(message.ends_with("Blocked \"rm -rf /\" [destructive]"));
+    }
+
+    #[test]
+    fn test_escape_param() {
+        assert_eq!(escape_param(r#"a "b" [c] \d"#), r#"a \"b\" [c\] \\d"#);
+    }
+
+    #[tokio::test]
+    async fn test_missing_socket_is_an_error() {
+        let dir = tempfile::tempdir().unwrap();
+        let sink = SyslogSink::new(dir.path().join("missing.sock"));
+        let record = AuditRecord::session_end(uuid::Uuid::nil(), "completed");
+        assert!(sink.write(&record).await.is_err());
+    }
+}
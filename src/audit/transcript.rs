@@ -0,0 +1,447 @@
+//! Export of supervised sessions as Claude Code transcripts.
+//!
+//! Claude Code stores a session as JSONL under `~/.claude/projects`, one
+//! entry per line chained by `parentUuid`. A [`TranscriptMirror`] writes the
+//! events of a supervised session in that format as they arrive, so the file
+//! can be opened by any tool reading Claude Code transcripts.
+//!
+//! Sessions run without mirroring can still be exported with
+//! [`reconstruct`], which is lossy:
+//!
+//! - Only the events kept in the session's resume context survive, at most
+//!   [`RESUME_CONTEXT_EVENTS`](crate::supervisor::RESUME_CONTEXT_EVENTS) of
+//!   the most recent ones.
+//! - Without a resume context only the task and the tool calls recorded in
+//!   the audit log survive: no assistant text, and tool results carry the
+//!   supervisor's decision instead of the tool output.
+//! - Timestamps of events from the resume context are approximate.
+
+use std::io::Write;
+use std::path::{Path, PathBuf};
+
+use chrono::{DateTime, SecondsFormat, Utc};
+use serde_json::{json, Value};
+use uuid::Uuid;
+
+use super::{AuditError, AuditEvent, AuditSession, EventType};
+use crate::cli::{ClaudeEvent, ToolResult, ToolUse};
+
+/// Returns the directory holding mirrored transcripts, one file per audit
+/// session.
+///
+/// This is `state/transcripts` in the
+/// [data directory](crate::config::data_dir).
+#[must_use]
+pub fn default_transcript_dir() -> PathBuf {
+    crate::config::state_dir().join("transcripts")
+}
+
+/// Turns supervisor events into transcript entries, chaining each entry to
+/// the previous one.
+#[derive(Debug, Clone)]
+pub struct TranscriptBuilder {
+    session_id: String,
+    cwd: String,
+    version: String,
+    parent: Option<String>,
+}
+
+impl TranscriptBuilder {
+    /// Create a builder for entries of `session_id`.
+    ///
+    /// The session ID, working directory and version are taken from the
+    /// init event once it arrives.
+    #[must_use]
+    pub fn new(session_id: impl Into<String>) -> Self {
+        Self {
+            session_id: session_id.into(),
+            cwd: String::new(),
+            version: "unknown".to_string(),
+            parent: None,
+        }
+    }
+
+    /// Set the working directory recorded in each entry.
+    #[must_use]
+    pub fn with_cwd(mut self, cwd: impl Into<String>) -> Self {
+        self.cwd = cwd.into();
+        self
+    }
+
+    /// Set the Claude Code version recorded in each entry.
+    #[must_use]
+    pub fn with_version(mut self, version: impl Into<String>) -> Self {
+        self.version = version.into();
+        self
+    }
+
+    /// Entry for the prompt the session was started with.
+    pub fn prompt(&mut self, text: &str, timestamp: DateTime<Utc>) -> Value {
+        self.user(json!({"role": "user", "content": text}), None, timestamp)
+    }
+
+    /// Entry for `event`, or `None` for events the transcript format has no
+    /// place for, such as stream deltas.
+    pub fn entry(&mut self, event: &ClaudeEvent, timestamp: DateTime<Utc>) -> Option<Value> {
+        match event {
+            ClaudeEvent::System(init) => {
+                self.session_id.clone_from(&init.session_id);
+                self.cwd.clone_from(&init.cwd);
+                if let Some(ref version) = init.claude_code_version {
+                    self.version.clone_from(version);
+                }
+                let mut entry = self.base("system", timestamp);
+                entry["subtype"] = json!(init.subtype.as_deref().unwrap_or("init"));
+                entry["data"] = json!({"model": init.model, "tools": init.tools});
+                Some(self.chain(entry))
+            }
+            ClaudeEvent::Assistant { message } => Some(self.assistant(message, timestamp)),
+            ClaudeEvent::User {
+                message,
+                tool_use_result,
+            } => Some(self.user(message.clone(), tool_use_result.clone(), timestamp)),
+            ClaudeEvent::ToolUse(ToolUse { id, name, input }) => {
+                let message = json!({
+                    "role": "assistant",
+                    "content": [{"type": "tool_use", "id": id, "name": name, "input": input}]
+                });
+                Some(self.assistant(&message, timestamp))
+            }
+            ClaudeEvent::ToolResult(ToolResult {
+                tool_use_id,
+                content,
+                is_error,
+            }) => {
+                let message = json!({
+                    "role": "user",
+                    "content": [{
+                        "type": "tool_result",
+                        "tool_use_id": tool_use_id,
+                        "content": content,
+                        "is_error": is_error
+                    }]
+                });
+                Some(self.user(message, None, timestamp))
+            }
+            ClaudeEvent::Result(result) => {
+                let leaf = self.parent.clone().unwrap_or_default();
+                Some(json!({"type": "summary", "summary": result.result, "leafUuid": leaf}))
+            }
+            _ => None,
+        }
+    }
+
+    fn base(&self, kind: &str, timestamp: DateTime<Utc>) -> Value {
+        json!({
+            "type": kind,
+            "uuid": Uuid::new_v4().to_string(),
+            "parentUuid": self.parent,
+            "sessionId": self.session_id,
+            "timestamp": timestamp.to_rfc3339_opts(SecondsFormat::Millis, true),
+            "cwd": self.cwd,
+            "version": self.version,
+            "isSidechain": false,
+        })
+    }
+
+    fn chain(&mut self, entry: Value) -> Value {
+        self.parent = entry["uuid"].as_str().map(str::to_string);
+        entry
+    }
+
+    fn user(
+        &mut self,
+        mut message: Value,
+        tool_use_result: Option<Value>,
+        timestamp: DateTime<Utc>,
+    ) -> Value {
+        message["role"] = json!("user");
+        if message.get("content").is_none() {
+            message["content"] = json!("");
+        }
+        let mut entry = self.base("user", timestamp);
+        entry["userType"] = json!("external");
+        if let Some(id) = crate::supervisor::tool_result_ids(&message).first() {
+            entry["sourceToolUseId"] = json!(id);
+        }
+        entry["message"] = message;
+        if let Some(result) = tool_use_result {
+            entry["toolUseResult"] = result;
+        }
+        self.chain(entry)
+    }
+
+    fn assistant(&mut self, message: &Value, timestamp: DateTime<Utc>) -> Value {
+        let content = match message.get("content") {
+            Some(Value::Array(blocks)) => Value::Array(blocks.clone()),
+            Some(Value::String(text)) => json!([{"type": "text", "text": text}]),
+            _ => json!([]),
+        };
+        let mut message = message.clone();
+        message["role"] = json!("assistant");
+        message["content"] = content;
+        let mut entry = self.base("assistant", timestamp);
+        entry["message"] = message;
+        self.chain(entry)
+    }
+}
+
+/// Writes a transcript line by line while a session runs.
+#[derive(Debug)]
+pub struct TranscriptMirror {
+    path: PathBuf,
+    file: std::fs::File,
+    builder: TranscriptBuilder,
+}
+
+impl TranscriptMirror {
+    /// Create the transcript at `path`, creating its directory.
+    ///
+    /// # Errors
+    ///
+    /// Returns an error if the directory or the file cannot be created.
+    pub fn create(path: &Path, session_id: impl Into<String>) -> Result<Self, AuditError> {
+        if let Some(dir) = path.parent() {
+            std::fs::create_dir_all(dir).map_err(|source| AuditError::CreateDir {
+                path: dir.to_path_buf(),
+                source,
+            })?;
+        }
+        let file = std::fs::File::create(path).map_err(|source| AuditError::WriteFile {
+            path: path.to_path_buf(),
+            source,
+        })?;
+        Ok(Self {
+            path: path.to_path_buf(),
+            file,
+            builder: TranscriptBuilder::new(session_id),
+        })
+    }
+
+    /// Path of the transcript.
+    #[must_use]
+    pub fn path(&self) -> &Path {
+        &self.path
+    }
+
+    /// Append the prompt the session was started with.
+    ///
+    /// # Errors
+    ///
+    /// Returns an error if the line cannot be written.
+    pub fn write_prompt(&mut self, text: &str) -> Result<(), AuditError> {
+        let entry = self.builder.prompt(text, Utc::now());
+        self.write_line(&entry)
+    }
+
+    /// Append `event`, if the transcript format has a place for it.
+    ///
+    /// # Errors
+    ///
+    /// Returns an error if the line cannot be written.
+    pub fn write(&mut self, event: &ClaudeEvent) -> Result<(), AuditError> {
+        match self.builder.entry(event, Utc::now()) {
+            Some(entry) => self.write_line(&entry),
+            None => Ok(()),
+        }
+    }
+
+    fn write_line(&mut self, entry: &Value) -> Result<(), AuditError> {
+        let mut line = serde_json::to_vec(entry)?;
+        line.push(b'\n');
+        self.file
+            .write_all(&line)
+            .map_err(|source| AuditError::WriteFile {
+                path: self.path.clone(),
+                source,
+            })
+    }
+}
+
+/// Rebuild the transcript of a session that was not mirrored.
+///
+/// `events` are the session's audit events, oldest first, and `history` the
+/// Claude events kept in its resume context, if any. See the
+/// [module documentation](self) for what is lost.
+#[must_use]
+pub fn reconstruct(
+    session: &AuditSession,
+    events: &[AuditEvent],
+    history: &[ClaudeEvent],
+) -> Vec<Value> {
+    let mut builder = TranscriptBuilder::new(
+        session
+            .claude_session_id
+            .clone()
+            .unwrap_or_else(|| session.id.to_string()),
+    );
+    if let Some(ref version) = session.claude_code_version {
+        builder = builder.with_version(version.clone());
+    }
+
+    let mut entries = vec![builder.prompt(&session.task, session.started_at)];
+    if history.is_empty() {
+        for (index, event) in events
+            .iter()
+            .filter(|event| event.event_type == EventType::ToolUse)
+            .enumerate()
+        {
+            let id = format!("toolu_audit_{index}");
+            let tool_use = ClaudeEvent::ToolUse(ToolUse {
+                id: id.clone(),
+                name: event.tool_name.clone().unwrap_or_default(),
+                input: event.tool_input.clone().unwrap_or_else(|| json!({})),
+            });
+            entries.extend(builder.entry(&tool_use, event.timestamp));
+
+            let decision = event
+                .decision
+                .map_or("unknown", |decision| decision.as_str());
+            let content = match event.reason {
+                Some(ref reason) => format!("[supervisor: {decision}] {reason}"),
+                None => format!("[supervisor: {decision}]"),
+            };
+            let result = ClaudeEvent::ToolResult(ToolResult {
+                tool_use_id: id,
+                content,
+                is_error: decision == "deny",
+            });
+            entries.extend(builder.entry(&result, event.timestamp));
+        }
+    } else {
+        let end = session.ended_at.unwrap_or(session.started_at);
+        entries.extend(history.iter().filter_map(|event| builder.entry(event, end)));
+    }
+    entries
+}
+
+/// Write `entries` to `path` as JSONL, creating its directory.
+///
+/// # Errors
+///
+/// Returns an error if the directory or the file cannot be written.
+pub fn write_transcript(path: &Path, entries: &[Value]) -> Result<(), AuditError> {
+    if let Some(dir) = path.parent().filter(|dir| !dir.as_os_str().is_empty()) {
+        std::fs::create_dir_all(dir).map_err(|source| AuditError::CreateDir {
+            path: dir.to_path_buf(),
+            source,
+        })?;
+    }
+    let mut content = String::new();
+    for entry in entries {
+        content.push_str(&serde_json::to_string(entry)?);
+        content.push('\n');
+    }
+    std::fs::write(path, content).map_err(|source| AuditError::WriteFile {
+        path: path.to_path_buf(),
+        source,
+    })
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::audit::Decision;
+    use crate::watcher::JournalEntry;
+
+    fn parse_all(content: &str) -> Vec<JournalEntry> {
+        content
+            .lines()
+            .map(|line| serde_json::from_str(line).unwrap())
+            .collect()
+    }
+
+    fn events() -> Vec<ClaudeEvent> {
+        [
+            r#"{"type":"system","subtype":"init","cwd":"/work","tools":["Bash"],"model":"claude-sonnet","session_id":"s-1","claude_code_version":"2.1.0"}"#,
+            r#"{"type":"assistant","message":{"role":"assistant","content":[{"type":"text","text":"Listing"},{"type":"tool_use","id":"t1","name":"Bash","input":{"command":"ls"}}]}}"#,
+            r#"{"type":"content_block_delta","index":0,"delta":{"type":"text_delta","text":"x"}}"#,
+            r#"{"type":"user","message":{"role":"user","content":[{"type":"tool_result","tool_use_id":"t1","content":"Cargo.toml"}]},"tool_use_result":{"stdout":"Cargo.toml"}}"#,
+            r#"{"type":"result","result":"Done","session_id":"s-1","is_error":false}"#,
+        ]
+        .iter()
+        .map(|line| serde_json::from_str(line).unwrap())
+        .collect()
+    }
+
+    #[test]
+    fn test_mirror_writes_parseable_chained_transcript() {
+        let dir = tempfile::tempdir().unwrap();
+        let path = dir.path().join("transcripts").join("run.jsonl");
+        let mut mirror = TranscriptMirror::create(&path, "pending").unwrap();
+        mirror.write_prompt("List files").unwrap();
+        for event in events() {
+            mirror.write(&event).unwrap();
+        }
+        drop(mirror);
+
+        let content = std::fs::read_to_string(&path).unwrap();
+        let entries = parse_all(&content);
+        assert_eq!(entries.len(), 5);
+        assert!(matches!(entries[1], JournalEntry::System(_)));
+
+        let JournalEntry::Assistant(ref assistant) = entries[2] else {
+            panic!("expected an assistant entry");
+        };
+        assert_eq!(assistant.session_id, "s-1");
+        assert_eq!(assistant.cwd, "/work");
+        assert_eq!(assistant.version, "2.1.0");
+        assert_eq!(assistant.message.content.len(), 2);
+
+        let JournalEntry::User(ref result) = entries[3] else {
+            panic!("expected a user entry");
+        };
+        assert_eq!(result.parent_uuid.as_deref(), Some(assistant.uuid.as_str()));
+        assert_eq!(result.source_tool_use_id.as_deref(), Some("t1"));
+        assert!(result.tool_use_result.is_some());
+
+        let JournalEntry::Summary(ref summary) = entries[4] else {
+            panic!("expected a summary entry");
+        };
+        assert_eq!(summary.leaf_uuid, result.uuid);
+    }
+
+    #[test]
+    fn test_reconstruct_from_audit_events() {
+        let session = AuditSession::new("Fix the tests".to_string());
+        let allowed = AuditEvent::builder(session.id, EventType::ToolUse)
+            .tool_name("Bash")
+            .tool_input(json!({"command": "cargo test"}))
+            .decision(Decision::Allow)
+            .build();
+        let denied = AuditEvent::builder(session.id, EventType::ToolUse)
+            .tool_name("Bash")
+            .tool_input(json!({"command": "rm -rf /"}))
+            .decision(Decision::Deny)
+            .reason("Destructive command")
+            .build();
+        let error = AuditEvent::builder(session.id, EventType::Error).build();
+
+        let entries = reconstruct(&session, &[allowed, error, denied], &[]);
+        assert_eq!(entries.len(), 5);
+        assert_eq!(entries[4]["message"]["content"][0]["is_error"], true);
+        assert_eq!(
+            entries[4]["message"]["content"][0]["content"],
+            "[supervisor: deny] Destructive command"
+        );
+
+        let dir = tempfile::tempdir().unwrap();
+        let path = dir.path().join("out.jsonl");
+        write_transcript(&path, &entries).unwrap();
+        let parsed = parse_all(&std::fs::read_to_string(&path).unwrap());
+        assert!(matches!(parsed[0], JournalEntry::User(_)));
+        assert!(matches!(parsed[1], JournalEntry::Assistant(_)));
+    }
+
+    #[test]
+    fn test_reconstruct_prefers_history() {
+        let session = AuditSession::new("List files".to_string());
+        let tool_use = AuditEvent::builder(session.id, EventType::ToolUse)
+            .tool_name("Bash")
+            .build();
+
+        let entries = reconstruct(&session, &[tool_use], &events());
+        assert_eq!(entries.len(), 5);
+        assert_eq!(entries[2]["message"]["content"][0]["text"], "Listing");
+    }
+}
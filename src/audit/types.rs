@@ -4,6 +4,8 @@ use chrono::{DateTime, Utc};
 use serde::{Deserialize, Serialize};
 use uuid::Uuid;
 
+use crate::supervisor::{DecisionBreakdown, DecisionSource, DenyReason, ToolAliases};
+
 /// Type of audit event.
 #[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
 #[serde(rename_all = "snake_case")]
@@ -20,6 +22,8 @@ pub enum EventType {
     AiEscalation,
     /// An error occurred.
     Error,
+    /// Policy decision a dry run did not enforce.
+    DryRun,
 }
 
 impl EventType {
@@ -33,6 +37,7 @@ impl EventType {
             Self::PolicyDecision => "policy_decision",
             Self::AiEscalation => "ai_escalation",
             Self::Error => "error",
+            Self::DryRun => "dry_run",
         }
     }
 }
@@ -72,14 +77,25 @@ pub struct AuditEvent {
     pub timestamp: DateTime<Utc>,
     /// Type of event.
     pub event_type: EventType,
-    /// Name of the tool involved, if any.
+    /// Canonical name of the tool involved, if any.
     pub tool_name: Option<String>,
+    /// Name the tool was called by, when it is an alias of `tool_name`.
+    #[serde(default)]
+    pub tool_alias: Option<String>,
     /// Tool input parameters as JSON, if any.
     pub tool_input: Option<serde_json::Value>,
     /// Decision made, if applicable.
     pub decision: Option<Decision>,
     /// Reason for the decision.
     pub reason: Option<String>,
+    /// Snapshot of the target file taken before the tool ran, if any.
+    pub snapshot_id: Option<String>,
+    /// Policy rule that denied or escalated the tool call, if any.
+    #[serde(default)]
+    pub rule: Option<String>,
+    /// Who made the final decision on a call a rule fired on.
+    #[serde(default)]
+    pub decided_by: Option<DecisionSource>,
 }
 
 impl AuditEvent {
@@ -88,6 +104,22 @@ impl AuditEvent {
     pub fn builder(session_id: Uuid, event_type: EventType) -> AuditEventBuilder {
         AuditEventBuilder::new(session_id, event_type)
     }
+
+    /// The reason of a denial, with the rule and source that denied it.
+    #[must_use]
+    pub fn deny_reason(&self) -> Option<DenyReason> {
+        if self.decision != Some(Decision::Deny) {
+            return None;
+        }
+        let mut reason = DenyReason::new(self.reason.clone()?);
+        if let Some(ref rule) = self.rule {
+            reason = reason.with_rule(rule.clone());
+        }
+        if let Some(source) = self.decided_by {
+            reason = reason.with_source(source);
+        }
+        Some(reason)
+    }
 }
 
 /// Builder for creating audit events.
@@ -97,9 +129,13 @@ pub struct AuditEventBuilder {
     timestamp: DateTime<Utc>,
     event_type: EventType,
     tool_name: Option<String>,
+    tool_alias: Option<String>,
     tool_input: Option<serde_json::Value>,
     decision: Option<Decision>,
     reason: Option<String>,
+    snapshot_id: Option<String>,
+    rule: Option<String>,
+    decided_by: Option<DecisionSource>,
 }
 
 impl AuditEventBuilder {
@@ -110,9 +146,13 @@ impl AuditEventBuilder {
             timestamp: Utc::now(),
             event_type,
             tool_name: None,
+            tool_alias: None,
             tool_input: None,
             decision: None,
             reason: None,
+            snapshot_id: None,
+            rule: None,
+            decided_by: None,
         }
     }
 
@@ -128,6 +168,21 @@ impl AuditEventBuilder {
         self
     }
 
+    /// Set the name the tool was called by, an alias of the tool name.
+    pub fn tool_alias(mut self, alias: impl Into<String>) -> Self {
+        self.tool_alias = Some(alias.into());
+        self
+    }
+
+    /// Set the tool by the name it was called by: its canonical name, and
+    /// the name itself as the alias if it is one.
+    pub fn called_tool(self, name: &str, aliases: &ToolAliases) -> Self {
+        match aliases.resolve(name) {
+            Some(canonical) => self.tool_name(canonical).tool_alias(name),
+            None => self.tool_name(name),
+        }
+    }
+
     /// Set the tool input.
     pub fn tool_input(mut self, input: serde_json::Value) -> Self {
         self.tool_input = Some(input);
@@ -146,6 +201,37 @@ impl AuditEventBuilder {
         self
     }
 
+    /// Set a denial, with the rule and source behind it when known.
+    pub fn deny_reason(mut self, reason: &DenyReason) -> Self {
+        self.decision = Some(Decision::Deny);
+        self.reason = Some(reason.message.clone());
+        if let Some(ref rule) = reason.rule_id {
+            self.rule = Some(rule.clone());
+        }
+        if let Some(source) = reason.source {
+            self.decided_by = Some(source);
+        }
+        self
+    }
+
+    /// Set the snapshot ID.
+    pub fn snapshot_id(mut self, id: impl Into<String>) -> Self {
+        self.snapshot_id = Some(id.into());
+        self
+    }
+
+    /// Set the policy rule that fired on the call.
+    pub fn rule(mut self, rule: impl Into<String>) -> Self {
+        self.rule = Some(rule.into());
+        self
+    }
+
+    /// Set who made the final decision.
+    pub fn decided_by(mut self, source: DecisionSource) -> Self {
+        self.decided_by = Some(source);
+        self
+    }
+
     /// Build the audit event.
     pub fn build(self) -> AuditEvent {
         AuditEvent {
@@ -154,9 +240,13 @@ impl AuditEventBuilder {
             timestamp: self.timestamp,
             event_type: self.event_type,
             tool_name: self.tool_name,
+            tool_alias: self.tool_alias,
             tool_input: self.tool_input,
             decision: self.decision,
             reason: self.reason,
+            snapshot_id: self.snapshot_id,
+            rule: self.rule,
+            decided_by: self.decided_by,
         }
     }
 }
@@ -174,6 +264,21 @@ pub struct AuditSession {
     pub task: String,
     /// The result of the session, if finished.
     pub result: Option<String>,
+    /// Snapshot of the policy configuration the session ran under.
+    #[serde(default)]
+    pub config: Option<serde_json::Value>,
+    /// Claude Code permission mode reported at session start.
+    #[serde(default)]
+    pub permission_mode: Option<String>,
+    /// Human-friendly session name, unique per day.
+    #[serde(default)]
+    pub name: Option<String>,
+    /// Claude Code session ID, used to resume the session by name.
+    #[serde(default)]
+    pub claude_session_id: Option<String>,
+    /// Claude Code version the session ran under.
+    #[serde(default)]
+    pub claude_code_version: Option<String>,
 }
 
 impl AuditSession {
@@ -185,6 +290,11 @@ impl AuditSession {
             ended_at: None,
             task: task.into(),
             result: None,
+            config: None,
+            permission_mode: None,
+            name: None,
+            claude_session_id: None,
+            claude_code_version: None,
         }
     }
 
@@ -196,9 +306,49 @@ impl AuditSession {
             ended_at: None,
             task: task.into(),
             result: None,
+            config: None,
+            permission_mode: None,
+            name: None,
+            claude_session_id: None,
+            claude_code_version: None,
         }
     }
 
+    /// Attach a snapshot of the policy configuration.
+    #[must_use]
+    pub fn with_config(mut self, config: serde_json::Value) -> Self {
+        self.config = Some(config);
+        self
+    }
+
+    /// Record the permission mode Claude Code ran in.
+    #[must_use]
+    pub fn with_permission_mode(mut self, mode: impl Into<String>) -> Self {
+        self.permission_mode = Some(mode.into());
+        self
+    }
+
+    /// Give the session a human-friendly name.
+    #[must_use]
+    pub fn with_name(mut self, name: impl Into<String>) -> Self {
+        self.name = Some(name.into());
+        self
+    }
+
+    /// Record the Claude Code session ID.
+    #[must_use]
+    pub fn with_claude_session_id(mut self, id: impl Into<String>) -> Self {
+        self.claude_session_id = Some(id.into());
+        self
+    }
+
+    /// Record the Claude Code version the session ran under.
+    #[must_use]
+    pub fn with_claude_code_version(mut self, version: impl Into<String>) -> Self {
+        self.claude_code_version = Some(version.into());
+        self
+    }
+
     /// Mark the session as ended with a result.
     pub fn end(&mut self, result: impl Into<String>) {
         self.ended_at = Some(Utc::now());
@@ -221,6 +371,15 @@ pub struct SessionMetrics {
     pub cache_hits: u64,
     /// Estimated cost in USD cents.
     pub estimated_cost_cents: u64,
+    /// Highest blast radius score the session reached, rounded.
+    #[serde(default)]
+    pub blast_radius_peak: u64,
+    /// Blast radius score at which the session escalates.
+    #[serde(default)]
+    pub blast_radius_threshold: u64,
+    /// Approvals and denials by who made them.
+    #[serde(default)]
+    pub decision_sources: DecisionBreakdown,
 }
 
 impl SessionMetrics {
@@ -249,6 +408,13 @@ impl SessionMetrics {
         self.cache_hits += 1;
     }
 
+    /// Record the session's peak blast radius and its escalation threshold.
+    #[allow(clippy::cast_possible_truncation, clippy::cast_sign_loss)]
+    pub fn record_blast_radius(&mut self, peak: f64, threshold: u32) {
+        self.blast_radius_peak = peak.max(0.0).round() as u64;
+        self.blast_radius_threshold = u64::from(threshold);
+    }
+
     /// Calculate and set estimated cost based on token usage.
     /// Uses approximate Claude pricing: $3/1M input, $15/1M output tokens.
     #[allow(
@@ -276,6 +442,7 @@ mod tests {
         assert_eq!(EventType::PolicyDecision.as_str(), "policy_decision");
         assert_eq!(EventType::AiEscalation.as_str(), "ai_escalation");
         assert_eq!(EventType::Error.as_str(), "error");
+        assert_eq!(EventType::DryRun.as_str(), "dry_run");
     }
 
     #[test]
@@ -357,6 +524,28 @@ mod tests {
         assert_eq!(parsed.decision, Some(Decision::Deny));
     }
 
+    #[test]
+    fn test_audit_event_deny_reason_round_trip() {
+        let reason = DenyReason::new("Writing to sensitive path is blocked: .env")
+            .with_rule("path rule: .env")
+            .with_source(DecisionSource::Policy);
+        let event = AuditEvent::builder(Uuid::nil(), EventType::PolicyDecision)
+            .deny_reason(&reason)
+            .build();
+
+        assert_eq!(event.decision, Some(Decision::Deny));
+        assert_eq!(event.reason.as_deref(), Some(reason.message.as_str()));
+        assert_eq!(event.rule.as_deref(), Some("path rule: .env"));
+        assert_eq!(event.decided_by, Some(DecisionSource::Policy));
+        assert_eq!(event.deny_reason(), Some(reason));
+
+        let allowed = AuditEvent::builder(Uuid::nil(), EventType::PolicyDecision)
+            .decision(Decision::Allow)
+            .reason("Command is safe")
+            .build();
+        assert_eq!(allowed.deny_reason(), None);
+    }
+
     #[test]
     fn test_audit_session_new() {
         let session = AuditSession::new("Fix the auth bug");
@@ -6,6 +6,8 @@
 use serde::{Deserialize, Serialize};
 use std::collections::HashMap;
 
+use super::AssistantMessage;
+
 /// A Claude event with its original raw JSON preserved.
 ///
 /// This wrapper stores the original JSON string alongside the parsed event,
@@ -114,6 +116,49 @@ pub struct ToolUse {
     pub input: serde_json::Value,
 }
 
+/// Input keys that name the file a tool touches.
+pub const TOOL_PATH_KEYS: &[&str] = &["file_path", "notebook_path", "path"];
+
+/// File paths named in a tool's input, in [`TOOL_PATH_KEYS`] order.
+pub fn input_paths(input: &serde_json::Value) -> impl Iterator<Item = &str> {
+    TOOL_PATH_KEYS
+        .iter()
+        .filter_map(|key| input.get(*key).and_then(serde_json::Value::as_str))
+}
+
+/// The first one or two words of a shell command, like `git push` or `ls`.
+///
+/// Leading `VAR=value` assignments are skipped.
+#[must_use]
+pub fn command_prefix(command: &str) -> Option<String> {
+    let words: Vec<&str> = command
+        .split_whitespace()
+        .skip_while(|word| word.contains('=') && !word.starts_with('='))
+        .take(2)
+        .collect();
+    if words.is_empty() {
+        None
+    } else {
+        Some(words.join(" "))
+    }
+}
+
+impl ToolUse {
+    /// The first file path named in the input, if any.
+    #[must_use]
+    pub fn path(&self) -> Option<&str> {
+        input_paths(&self.input).next()
+    }
+
+    /// The shell command in the input, if any.
+    #[must_use]
+    pub fn command(&self) -> Option<&str> {
+        self.input
+            .get("command")
+            .and_then(serde_json::Value::as_str)
+    }
+}
+
 /// Tool execution result data.
 #[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
 pub struct ToolResult {
@@ -457,10 +502,42 @@ impl<'de> Deserialize<'de> for ClaudeEvent {
 }
 
 impl ClaudeEvent {
-    /// Returns true if this is a terminal event (`Result` or `MessageStop`).
+    /// Returns true if this is a terminal event (`Result`).
+    ///
+    /// `MessageStop` ends every assistant turn, not the session.
     #[must_use]
     pub fn is_terminal(&self) -> bool {
-        matches!(self, Self::Result(_) | Self::MessageStop)
+        matches!(self, Self::Result(_))
+    }
+
+    /// Returns the wire type of the event, such as `tool_use`.
+    ///
+    /// Events of an unknown type are named by their `type` field, followed
+    /// by `/` and their `subtype` field if they have one.
+    #[must_use]
+    pub fn event_type(&self) -> String {
+        let name = match self {
+            Self::System(_) => "system",
+            Self::Assistant { .. } => "assistant",
+            Self::User { .. } => "user",
+            Self::ToolUse(_) => "tool_use",
+            Self::ToolResult(_) => "tool_result",
+            Self::ContentBlockDelta { .. } => "content_block_delta",
+            Self::ContentBlockStart { .. } => "content_block_start",
+            Self::ContentBlockStop { .. } => "content_block_stop",
+            Self::MessageStart { .. } => "message_start",
+            Self::MessageStop => "message_stop",
+            Self::Result(_) => "result",
+            Self::Other(value) => {
+                let field = |key: &str| value.get(key).and_then(serde_json::Value::as_str);
+                let kind = field("type").unwrap_or("unknown");
+                return match field("subtype") {
+                    Some(subtype) => format!("{kind}/{subtype}"),
+                    None => kind.to_string(),
+                };
+            }
+        };
+        name.to_string()
     }
 
     /// Returns the tool name if this is a `ToolUse` event.
@@ -472,6 +549,17 @@ impl ClaudeEvent {
         }
     }
 
+    /// Returns the typed message if this is an `Assistant` event.
+    ///
+    /// Converts on each call; `None` if the message is not an object.
+    #[must_use]
+    pub fn assistant(&self) -> Option<AssistantMessage> {
+        match self {
+            Self::Assistant { message } => serde_json::from_value(message.clone()).ok(),
+            _ => None,
+        }
+    }
+
     /// Returns the session ID if available.
     #[must_use]
     pub fn session_id(&self) -> Option<&str> {
@@ -0,0 +1,231 @@
+//! Typed assistant messages from Claude Code stream-json output.
+//!
+//! [`ClaudeEvent::Assistant`](super::ClaudeEvent::Assistant) keeps the message
+//! as raw JSON; [`AssistantMessage`] is the typed view produced on demand by
+//! [`ClaudeEvent::assistant`](super::ClaudeEvent::assistant).
+
+use std::collections::HashMap;
+
+use serde::{Deserialize, Deserializer, Serialize, Serializer};
+use serde_json::Value;
+
+use super::ToolUse;
+
+/// An assistant message.
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct AssistantMessage {
+    /// Message identifier; shared by every event of one API response.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub id: Option<String>,
+    /// Model that produced the message.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub model: Option<String>,
+    /// Message role, normally `assistant`.
+    #[serde(default = "default_role")]
+    pub role: String,
+    /// Content blocks; plain string content becomes a single text block.
+    #[serde(default, deserialize_with = "deserialize_content")]
+    pub content: Vec<ContentBlock>,
+    /// Why generation stopped, once known.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub stop_reason: Option<String>,
+    /// Token usage of the response.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub usage: Option<Usage>,
+    /// Extra fields not explicitly defined (forward compatibility).
+    #[serde(flatten, default)]
+    pub extra: HashMap<String, Value>,
+}
+
+fn default_role() -> String {
+    "assistant".to_string()
+}
+
+/// Accept either a block array or the plain string older versions emitted.
+fn deserialize_content<'de, D>(deserializer: D) -> Result<Vec<ContentBlock>, D::Error>
+where
+    D: Deserializer<'de>,
+{
+    match Value::deserialize(deserializer)? {
+        Value::Null => Ok(Vec::new()),
+        Value::String(text) => Ok(vec![ContentBlock::Text { text }]),
+        value => serde_json::from_value(value).map_err(serde::de::Error::custom),
+    }
+}
+
+impl AssistantMessage {
+    /// The text blocks joined by newlines.
+    #[must_use]
+    pub fn text(&self) -> String {
+        self.content
+            .iter()
+            .filter_map(|block| match block {
+                ContentBlock::Text { text } => Some(text.as_str()),
+                _ => None,
+            })
+            .collect::<Vec<_>>()
+            .join("\n")
+    }
+
+    /// Tool calls requested by the message, in order.
+    pub fn tool_uses(&self) -> impl Iterator<Item = &ToolUse> {
+        self.content.iter().filter_map(|block| match block {
+            ContentBlock::ToolUse(tool_use) => Some(tool_use),
+            _ => None,
+        })
+    }
+}
+
+/// Token usage reported on an assistant message.
+#[derive(Debug, Clone, Default, PartialEq, Serialize, Deserialize)]
+pub struct Usage {
+    /// Uncached input tokens.
+    #[serde(default)]
+    pub input_tokens: u64,
+    /// Output tokens.
+    #[serde(default)]
+    pub output_tokens: u64,
+    /// Input tokens written to the prompt cache.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub cache_creation_input_tokens: Option<u64>,
+    /// Input tokens read from the prompt cache.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub cache_read_input_tokens: Option<u64>,
+    /// Extra fields not explicitly defined (forward compatibility).
+    #[serde(flatten, default)]
+    pub extra: HashMap<String, Value>,
+}
+
+/// A content block of an assistant message.
+#[derive(Debug, Clone, PartialEq)]
+pub enum ContentBlock {
+    /// Text shown to the user.
+    Text {
+        /// The text.
+        text: String,
+    },
+    /// A tool call.
+    ToolUse(ToolUse),
+    /// Extended thinking.
+    Thinking {
+        /// The thinking text.
+        thinking: String,
+        /// Signature verifying the thinking, if present.
+        signature: Option<String>,
+    },
+    /// Block of an unknown type, or a known type that failed to parse;
+    /// preserves the full JSON.
+    Other(Value),
+}
+
+impl Serialize for ContentBlock {
+    fn serialize<S>(&self, serializer: S) -> Result<S::Ok, S::Error>
+    where
+        S: Serializer,
+    {
+        use serde::ser::SerializeMap;
+
+        match self {
+            ContentBlock::Text { text } => {
+                let mut map = serializer.serialize_map(Some(2))?;
+                map.serialize_entry("type", "text")?;
+                map.serialize_entry("text", text)?;
+                map.end()
+            }
+            ContentBlock::ToolUse(tool_use) => {
+                let mut map = serializer.serialize_map(Some(4))?;
+                map.serialize_entry("type", "tool_use")?;
+                map.serialize_entry("id", &tool_use.id)?;
+                map.serialize_entry("name", &tool_use.name)?;
+                map.serialize_entry("input", &tool_use.input)?;
+                map.end()
+            }
+            ContentBlock::Thinking {
+                thinking,
+                signature,
+            } => {
+                let mut map = serializer.serialize_map(None)?;
+                map.serialize_entry("type", "thinking")?;
+                map.serialize_entry("thinking", thinking)?;
+                if let Some(signature) = signature {
+                    map.serialize_entry("signature", signature)?;
+                }
+                map.end()
+            }
+            ContentBlock::Other(value) => value.serialize(serializer),
+        }
+    }
+}
+
+impl<'de> Deserialize<'de> for ContentBlock {
+    fn deserialize<D>(deserializer: D) -> Result<Self, D::Error>
+    where
+        D: Deserializer<'de>,
+    {
+        let value = Value::deserialize(deserializer)?;
+        let str_field = |key: &str| value.get(key).and_then(Value::as_str).map(str::to_string);
+
+        let block = match value.get("type").and_then(Value::as_str) {
+            Some("text") => str_field("text").map(|text| ContentBlock::Text { text }),
+            Some("tool_use") => serde_json::from_value(value.clone())
+                .ok()
+                .map(ContentBlock::ToolUse),
+            Some("thinking") => str_field("thinking").map(|thinking| ContentBlock::Thinking {
+                thinking,
+                signature: str_field("signature"),
+            }),
+            _ => None,
+        };
+        Ok(block.unwrap_or(ContentBlock::Other(value)))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use serde_json::json;
+
+    #[test]
+    fn test_unknown_block_round_trips() {
+        let block = json!({"type": "redacted_thinking", "data": "opaque"});
+        let parsed: ContentBlock = serde_json::from_value(block.clone()).unwrap();
+        assert_eq!(parsed, ContentBlock::Other(block.clone()));
+        assert_eq!(serde_json::to_value(&parsed).unwrap(), block);
+    }
+
+    #[test]
+    fn test_malformed_known_block_is_kept() {
+        let block = json!({"type": "tool_use", "name": "Read"});
+        let parsed: ContentBlock = serde_json::from_value(block.clone()).unwrap();
+        assert_eq!(parsed, ContentBlock::Other(block));
+    }
+
+    #[test]
+    fn test_string_content_becomes_text_block() {
+        let message: AssistantMessage =
+            serde_json::from_value(json!({"role": "assistant", "content": "Hello"})).unwrap();
+        assert_eq!(message.text(), "Hello");
+        assert!(message.extra.is_empty());
+    }
+
+    #[test]
+    fn test_message_round_trips() {
+        let value = json!({
+            "id": "msg_1",
+            "model": "claude-sonnet-4-20250514",
+            "role": "assistant",
+            "content": [
+                {"type": "thinking", "thinking": "Look first", "signature": "sig"},
+                {"type": "text", "text": "Reading it."},
+                {"type": "tool_use", "id": "toolu_1", "name": "Read", "input": {"file_path": "a.rs"}},
+            ],
+            "stop_reason": "tool_use",
+            "usage": {"input_tokens": 10, "output_tokens": 5, "service_tier": "standard"},
+            "container": null,
+        });
+        let message: AssistantMessage = serde_json::from_value(value.clone()).unwrap();
+        assert_eq!(message.tool_uses().count(), 1);
+        assert!(message.extra.contains_key("container"));
+        assert_eq!(serde_json::to_value(&message).unwrap(), value);
+    }
+}
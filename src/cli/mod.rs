@@ -57,7 +57,8 @@
 //! The [`ClaudeEvent`] enum represents all possible events from Claude Code:
 //!
 //! - [`ClaudeEvent::System`] - Session initialization with available tools
-//! - [`ClaudeEvent::Assistant`] - Assistant message content
+//! - [`ClaudeEvent::Assistant`] - Assistant message content, typed by
+//!   [`ClaudeEvent::assistant`] as an [`AssistantMessage`]
 //! - [`ClaudeEvent::ToolUse`] - Tool invocation request
 //! - [`ClaudeEvent::ToolResult`] - Tool execution result
 //! - [`ClaudeEvent::ContentBlockDelta`] - Streaming content updates
@@ -71,9 +72,15 @@
 //! - [`StreamError`] - Errors when parsing the output stream
 
 mod events;
+mod message;
 mod process;
+mod scenario;
+mod stderr;
 mod stream;
 
 pub use events::*;
+pub use message::*;
 pub use process::*;
+pub use scenario::*;
+pub use stderr::*;
 pub use stream::*;
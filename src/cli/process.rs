@@ -4,18 +4,35 @@
 //! Claude Code processes, along with control methods for managing the
 //! running process.
 
-use std::path::PathBuf;
+use std::path::{Path, PathBuf};
 use std::process::{ExitStatus, Stdio};
 use std::time::Duration;
 
 use tokio::process::{Child, ChildStderr, ChildStdout, Command};
 
+/// Binary run when none is configured.
+pub const DEFAULT_CLAUDE_BINARY: &str = "claude";
+
+/// Environment variable selecting the Claude binary.
+///
+/// Overrides the configured binary; `--claude-bin` overrides both.
+pub const CLAUDE_BIN_ENV: &str = "CLAUDE_SUPERVISOR_CLAUDE_BIN";
+
 /// Error type for process spawning operations.
 #[derive(thiserror::Error, Debug)]
 pub enum SpawnError {
     /// The binary was not found.
-    #[error("Claude binary not found")]
-    NotFound,
+    #[error("Claude binary not found: {}", path.display())]
+    NotFound {
+        /// Path or name that was looked up.
+        path: PathBuf,
+    },
+    /// The binary exists but is not executable.
+    #[error("Claude binary is not executable: {}", path.display())]
+    NotExecutable {
+        /// Path of the binary.
+        path: PathBuf,
+    },
     /// Permission denied when spawning.
     #[error("Permission denied")]
     PermissionDenied,
@@ -28,7 +45,9 @@ impl SpawnError {
     /// Create a `SpawnError` from an I/O error, classifying common cases.
     fn from_io(err: std::io::Error) -> Self {
         match err.kind() {
-            std::io::ErrorKind::NotFound => Self::NotFound,
+            std::io::ErrorKind::NotFound => Self::NotFound {
+                path: PathBuf::from("script"),
+            },
             std::io::ErrorKind::PermissionDenied => Self::PermissionDenied,
             _ => Self::Io(err),
         }
@@ -45,6 +64,134 @@ pub struct ClaudeProcessBuilder {
     append_system_prompt: Option<String>,
     system_prompt: Option<String>,
     working_dir: Option<PathBuf>,
+    add_dirs: Vec<PathBuf>,
+    env: Vec<(String, String)>,
+    claude_binary: Option<PathBuf>,
+}
+
+/// The Claude binary from [`CLAUDE_BIN_ENV`], if set and non-empty.
+#[must_use]
+pub fn claude_binary_from_env() -> Option<PathBuf> {
+    std::env::var_os(CLAUDE_BIN_ENV)
+        .filter(|value| !value.is_empty())
+        .map(PathBuf::from)
+}
+
+/// Resolve a binary to an executable file.
+///
+/// A bare name is searched for in `PATH`; anything with a directory
+/// component is checked as is.
+///
+/// # Errors
+///
+/// Returns `SpawnError::NotFound` with the attempted path if no such file
+/// exists, or `SpawnError::NotExecutable` if it exists but cannot be run.
+pub fn locate_binary(binary: &Path) -> Result<PathBuf, SpawnError> {
+    let is_bare_name = binary.components().count() == 1 && !binary.is_absolute();
+    if !is_bare_name {
+        return check_executable(binary);
+    }
+    std::env::var_os("PATH")
+        .iter()
+        .flat_map(std::env::split_paths)
+        .map(|dir| dir.join(binary))
+        .find(|candidate| candidate.is_file())
+        .map_or_else(
+            || {
+                Err(SpawnError::NotFound {
+                    path: binary.to_path_buf(),
+                })
+            },
+            |path| check_executable(&path),
+        )
+}
+
+/// Oldest Claude Code version known to emit the events and hook payloads
+/// this supervisor relies on.
+pub const MIN_CLAUDE_CODE_VERSION: &str = "1.0.0";
+
+/// Parse the first `major.minor.patch` number in a version string.
+///
+/// Accepts output like `2.0.14 (Claude Code)`; a missing patch is zero.
+#[must_use]
+pub fn parse_version(version: &str) -> Option<(u64, u64, u64)> {
+    version.split_whitespace().find_map(|word| {
+        let word = word.trim_start_matches('v');
+        let mut parts = word.split(['.', '-', '+']);
+        let major = parts.next()?.parse().ok()?;
+        let minor = parts.next()?.parse().ok()?;
+        let patch = parts.next().and_then(|p| p.parse().ok()).unwrap_or(0);
+        Some((major, minor, patch))
+    })
+}
+
+/// Whether `version` is older than [`MIN_CLAUDE_CODE_VERSION`].
+///
+/// Unparseable versions are not reported as older.
+#[must_use]
+pub fn is_older_than_minimum(version: &str) -> bool {
+    match (
+        parse_version(version),
+        parse_version(MIN_CLAUDE_CODE_VERSION),
+    ) {
+        (Some(found), Some(minimum)) => found < minimum,
+        _ => false,
+    }
+}
+
+/// First line of `binary --version`, if it runs and prints one.
+#[must_use]
+pub fn binary_version(binary: &Path) -> Option<String> {
+    let output = std::process::Command::new(binary)
+        .arg("--version")
+        .stdin(Stdio::null())
+        .output()
+        .ok()?;
+    first_line(&output.stdout)
+}
+
+/// [`binary_version`] without blocking, giving up after `timeout`.
+///
+/// The probe is killed if it outlives the timeout.
+pub async fn probe_binary_version(binary: PathBuf, timeout: Duration) -> Option<String> {
+    let output = Command::new(&binary)
+        .arg("--version")
+        .stdin(Stdio::null())
+        .stderr(Stdio::null())
+        .kill_on_drop(true)
+        .output();
+    let output = tokio::time::timeout(timeout, output).await.ok()?.ok()?;
+    first_line(&output.stdout)
+}
+
+fn first_line(stdout: &[u8]) -> Option<String> {
+    String::from_utf8_lossy(stdout)
+        .lines()
+        .map(str::trim)
+        .find(|line| !line.is_empty())
+        .map(String::from)
+}
+
+fn check_executable(path: &Path) -> Result<PathBuf, SpawnError> {
+    let metadata = std::fs::metadata(path).map_err(|_| SpawnError::NotFound {
+        path: path.to_path_buf(),
+    })?;
+    #[cfg(unix)]
+    {
+        use std::os::unix::fs::PermissionsExt;
+        if !metadata.is_file() || metadata.permissions().mode() & 0o111 == 0 {
+            return Err(SpawnError::NotExecutable {
+                path: path.to_path_buf(),
+            });
+        }
+    }
+    #[cfg(not(unix))]
+    if !metadata.is_file() {
+        return Err(SpawnError::NotExecutable {
+            path: path.to_path_buf(),
+        });
+    }
+    Ok(path.to_path_buf())
 }
 
 impl ClaudeProcessBuilder {
@@ -99,12 +246,69 @@ impl ClaudeProcessBuilder {
         self
     }
 
+    /// Give Claude access to an additional directory outside the working directory.
+    #[must_use]
+    pub fn add_dir(mut self, dir: impl Into<PathBuf>) -> Self {
+        self.add_dirs.push(dir.into());
+        self
+    }
+
+    /// Set an environment variable for the Claude process and its hooks.
+    #[must_use]
+    pub fn env(mut self, key: impl Into<String>, value: impl Into<String>) -> Self {
+        self.env.push((key.into(), value.into()));
+        self
+    }
+
+    /// Have a scripted stand-in for Claude, such as `fake-claude`, play the
+    /// scenario file at `path`.
+    ///
+    /// The path is passed in [`SCENARIO_ENV`](super::SCENARIO_ENV), which
+    /// the real binary ignores.
+    #[must_use]
+    pub fn scenario(self, path: impl AsRef<Path>) -> Self {
+        let path = path.as_ref().display().to_string();
+        self.env(super::SCENARIO_ENV, path)
+    }
+
+    /// Get the environment variables set for the Claude process.
+    #[must_use]
+    pub fn get_env(&self) -> &[(String, String)] {
+        &self.env
+    }
+
     /// Get the working directory, if set.
     #[must_use]
     pub fn get_working_dir(&self) -> Option<&PathBuf> {
         self.working_dir.as_ref()
     }
 
+    /// Run this binary instead of `claude`.
+    #[must_use]
+    pub fn claude_binary(mut self, binary: impl Into<PathBuf>) -> Self {
+        self.claude_binary = Some(binary.into());
+        self
+    }
+
+    /// The binary that will be run.
+    ///
+    /// The one set with [`claude_binary`](Self::claude_binary), else
+    /// [`CLAUDE_BIN_ENV`], else `claude`.
+    #[must_use]
+    pub fn get_claude_binary(&self) -> PathBuf {
+        self.claude_binary
+            .clone()
+            .or_else(claude_binary_from_env)
+            .unwrap_or_else(|| PathBuf::from(DEFAULT_CLAUDE_BINARY))
+    }
+
+    /// Replace the prompt, keeping every other setting.
+    #[must_use]
+    pub fn with_prompt(mut self, prompt: impl Into<String>) -> Self {
+        self.prompt = prompt.into();
+        self
+    }
+
     /// Get the prompt.
     #[must_use]
     pub fn prompt(&self) -> &str {
@@ -147,8 +351,41 @@ impl ClaudeProcessBuilder {
             args.push(prompt.clone());
         }
 
+        for dir in &self.add_dirs {
+            args.push("--add-dir".to_string());
+            args.push(dir.display().to_string());
+        }
+
         args
     }
+
+    /// Build the command that runs `binary` with this configuration.
+    ///
+    /// Claude runs under `script`, which provides the PTY stream-json needs.
+    #[must_use]
+    pub fn build_command(&self, binary: &str) -> Command {
+        let claude_cmd = format!(
+            "{} {}",
+            shell_escape::escape(binary.into()),
+            self.build_args()
+                .iter()
+                .map(|a| shell_escape::escape(a.into()))
+                .collect::<Vec<_>>()
+                .join(" ")
+        );
+
+        let mut cmd = Command::new("script");
+        cmd.args(["-q", "-c", &claude_cmd, "/dev/null"])
+            .stdout(Stdio::piped())
+            .stderr(Stdio::piped());
+
+        // Apply working directory if set
+        if let Some(ref dir) = self.working_dir {
+            cmd.current_dir(dir);
+        }
+        cmd.envs(self.env.iter().map(|(k, v)| (k, v)));
+        cmd
+    }
 }
 
 /// A running Claude Code process.
@@ -160,11 +397,16 @@ pub struct ClaudeProcess {
 impl ClaudeProcess {
     /// Spawn a Claude Code process with the given builder configuration.
     ///
+    /// The binary is checked before spawning, so a missing or
+    /// non-executable one is reported with its path.
+    ///
     /// # Errors
     ///
-    /// Returns `SpawnError` if the process fails to spawn.
+    /// Returns `SpawnError` if the binary cannot be found or the process
+    /// fails to spawn.
     pub fn spawn(builder: &ClaudeProcessBuilder) -> Result<Self, SpawnError> {
-        Self::spawn_with_binary("claude", builder)
+        let binary = locate_binary(&builder.get_claude_binary())?;
+        Self::spawn_with_binary(&binary.to_string_lossy(), builder)
     }
 
     /// Spawn a process using a custom binary (for testing).
@@ -176,29 +418,10 @@ impl ClaudeProcess {
         binary: &str,
         builder: &ClaudeProcessBuilder,
     ) -> Result<Self, SpawnError> {
-        let args = builder.build_args();
-
-        // Use 'script' to provide a PTY - required for stream-json output
-        let claude_cmd = format!(
-            "{} {}",
-            binary,
-            args.iter()
-                .map(|a| shell_escape::escape(a.into()))
-                .collect::<Vec<_>>()
-                .join(" ")
-        );
-
-        let mut cmd = Command::new("script");
-        cmd.args(["-q", "-c", &claude_cmd, "/dev/null"])
-            .stdout(Stdio::piped())
-            .stderr(Stdio::piped());
-
-        // Apply working directory if set
-        if let Some(ref dir) = builder.working_dir {
-            cmd.current_dir(dir);
-        }
-
-        let child = cmd.spawn().map_err(SpawnError::from_io)?;
+        let child = builder
+            .build_command(binary)
+            .spawn()
+            .map_err(SpawnError::from_io)?;
 
         Ok(Self { child })
     }
@@ -0,0 +1,354 @@
+//! Scripted stand-in for the Claude Code process.
+//!
+//! A scenario is a JSONL file played by the `fake-claude` binary in place
+//! of `claude`, so sessions can be supervised end to end without the real
+//! binary or network access. Each line is one step:
+//!
+//! - a stream-json event, printed to stdout as is
+//! - `{"fake": "stderr", "line": "..."}`, printed to stderr
+//! - `{"fake": "sleep", "ms": 500}`
+//! - `{"fake": "expect_stdin", "contains": "..."}`, which reads a line from
+//!   stdin and fails the scenario unless it contains the text
+//! - `{"fake": "expect_arg", "arg": "--resume"}`, which fails the scenario
+//!   unless the process was given the argument
+//! - `{"fake": "exit", "code": 1}`, which ends the scenario
+//!
+//! Any step may also carry `"delay_ms"`, waited before the step; it is not
+//! printed. Blank lines are skipped.
+//!
+//! The scenario is passed to the stand-in in [`SCENARIO_ENV`], set with
+//! [`ClaudeProcessBuilder::scenario`](super::ClaudeProcessBuilder::scenario).
+
+use std::io::{BufRead, Write};
+use std::path::{Path, PathBuf};
+use std::time::Duration;
+
+use serde_json::Value;
+
+/// Environment variable holding the scenario file a stand-in plays.
+pub const SCENARIO_ENV: &str = "CLAUDE_SUPERVISOR_SCENARIO";
+
+/// Exit code of a scenario whose expectation was not met.
+pub const SCENARIO_FAILED_EXIT_CODE: i32 = 97;
+
+/// Error type for loading and playing scenarios.
+#[derive(thiserror::Error, Debug)]
+pub enum ScenarioError {
+    /// The scenario file could not be read.
+    #[error("Failed to read scenario {}: {source}", path.display())]
+    Read {
+        /// Scenario file.
+        path: PathBuf,
+        /// Underlying error.
+        source: std::io::Error,
+    },
+    /// A line is not a valid step.
+    #[error("Invalid scenario line {line}: {message}")]
+    Invalid {
+        /// 1-based line number.
+        line: usize,
+        /// What is wrong with it.
+        message: String,
+    },
+    /// Stdin did not contain the expected text.
+    #[error("Expected {expected:?} on stdin, got {found:?}")]
+    StdinMismatch {
+        /// Text the step expected.
+        expected: String,
+        /// Line read, empty at end of input.
+        found: String,
+    },
+    /// The process was not given an expected argument.
+    #[error("Expected argument {0:?}")]
+    MissingArg(String),
+    /// Writing output or reading input failed.
+    #[error("Failed to play scenario: {0}")]
+    Io(#[from] std::io::Error),
+}
+
+/// What one scenario line does.
+#[derive(Debug, Clone, PartialEq)]
+pub enum ScenarioAction {
+    /// Print a stream-json event to stdout.
+    Emit(Value),
+    /// Print a line to stderr.
+    Stderr(String),
+    /// Wait.
+    Sleep(Duration),
+    /// Read a line from stdin that must contain the text.
+    ExpectStdin(String),
+    /// Require a command-line argument.
+    ExpectArg(String),
+    /// End the scenario with an exit code.
+    Exit(i32),
+}
+
+/// One scenario line.
+#[derive(Debug, Clone, PartialEq)]
+pub struct ScenarioStep {
+    /// Wait before the step.
+    pub delay: Duration,
+    /// What the step does.
+    pub action: ScenarioAction,
+}
+
+/// A parsed scenario.
+#[derive(Debug, Clone, Default, PartialEq)]
+pub struct Scenario {
+    steps: Vec<ScenarioStep>,
+}
+
+impl Scenario {
+    /// Parse a scenario from JSONL.
+    ///
+    /// # Errors
+    ///
+    /// Returns `ScenarioError::Invalid` if a line is not a JSON object or an
+    /// unknown or incomplete `fake` step.
+    pub fn parse(content: &str) -> Result<Self, ScenarioError> {
+        let steps = content
+            .lines()
+            .enumerate()
+            .filter(|(_, line)| !line.trim().is_empty())
+            .map(|(index, line)| parse_step(line).map_err(|message| invalid(index + 1, message)))
+            .collect::<Result<_, _>>()?;
+        Ok(Self { steps })
+    }
+
+    /// Read and parse a scenario file.
+    ///
+    /// # Errors
+    ///
+    /// Returns `ScenarioError::Read` if the file cannot be read, or any error
+    /// of [`parse`](Self::parse).
+    pub fn load(path: &Path) -> Result<Self, ScenarioError> {
+        let content = std::fs::read_to_string(path).map_err(|source| ScenarioError::Read {
+            path: path.to_path_buf(),
+            source,
+        })?;
+        Self::parse(&content)
+    }
+
+    /// The steps, in order.
+    #[must_use]
+    pub fn steps(&self) -> &[ScenarioStep] {
+        &self.steps
+    }
+
+    /// Play the scenario as a process started with `args`, returning its
+    /// exit code.
+    ///
+    /// Output is flushed after every step, so a supervisor sees each event
+    /// when it is played.
+    ///
+    /// # Errors
+    ///
+    /// Returns `ScenarioError::StdinMismatch` or `ScenarioError::MissingArg`
+    /// if an expectation is not met, or `ScenarioError::Io` if reading or
+    /// writing fails.
+    pub fn play(
+        &self,
+        args: &[String],
+        stdin: &mut impl BufRead,
+        stdout: &mut impl Write,
+        stderr: &mut impl Write,
+    ) -> Result<i32, ScenarioError> {
+        for step in &self.steps {
+            if !step.delay.is_zero() {
+                std::thread::sleep(step.delay);
+            }
+            match &step.action {
+                ScenarioAction::Emit(event) => {
+                    writeln!(stdout, "{event}")?;
+                    stdout.flush()?;
+                }
+                ScenarioAction::Stderr(line) => {
+                    writeln!(stderr, "{line}")?;
+                    stderr.flush()?;
+                }
+                ScenarioAction::Sleep(duration) => std::thread::sleep(*duration),
+                ScenarioAction::ExpectStdin(expected) => {
+                    let mut found = String::new();
+                    stdin.read_line(&mut found)?;
+                    if !found.contains(expected.as_str()) {
+                        return Err(ScenarioError::StdinMismatch {
+                            expected: expected.clone(),
+                            found: found.trim_end().to_string(),
+                        });
+                    }
+                }
+                ScenarioAction::ExpectArg(arg) => {
+                    if !args.contains(arg) {
+                        return Err(ScenarioError::MissingArg(arg.clone()));
+                    }
+                }
+                ScenarioAction::Exit(code) => return Ok(*code),
+            }
+        }
+        Ok(0)
+    }
+}
+
+fn invalid(line: usize, message: impl Into<String>) -> ScenarioError {
+    ScenarioError::Invalid {
+        line,
+        message: message.into(),
+    }
+}
+
+fn parse_step(line: &str) -> Result<ScenarioStep, String> {
+    let mut value: Value = serde_json::from_str(line).map_err(|e| e.to_string())?;
+    let object = value
+        .as_object_mut()
+        .ok_or_else(|| "expected a JSON object".to_string())?;
+    let delay = match object.remove("delay_ms") {
+        Some(ms) => Duration::from_millis(ms.as_u64().ok_or("delay_ms must be an integer")?),
+        None => Duration::ZERO,
+    };
+    let Some(kind) = object.get("fake") else {
+        return Ok(ScenarioStep {
+            delay,
+            action: ScenarioAction::Emit(value),
+        });
+    };
+    let string = |key: &str| {
+        object
+            .get(key)
+            .and_then(Value::as_str)
+            .map(String::from)
+            .ok_or_else(|| format!("missing string {key:?}"))
+    };
+    let integer = |key: &str| {
+        object
+            .get(key)
+            .and_then(Value::as_i64)
+            .ok_or_else(|| format!("missing integer {key:?}"))
+    };
+    let action = match kind.as_str() {
+        Some("stderr") => ScenarioAction::Stderr(string("line")?),
+        Some("sleep") => ScenarioAction::Sleep(Duration::from_millis(
+            u64::try_from(integer("ms")?).map_err(|e| e.to_string())?,
+        )),
+        Some("expect_stdin") => ScenarioAction::ExpectStdin(string("contains")?),
+        Some("expect_arg") => ScenarioAction::ExpectArg(string("arg")?),
+        Some("exit") => {
+            ScenarioAction::Exit(i32::try_from(integer("code")?).map_err(|e| e.to_string())?)
+        }
+        _ => return Err(format!("unknown fake step {kind}")),
+    };
+    Ok(ScenarioStep { delay, action })
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn play(
+        scenario: &Scenario,
+        args: &[&str],
+        stdin: &str,
+    ) -> (Result<i32, ScenarioError>, String, String) {
+        let args: Vec<String> = args.iter().map(ToString::to_string).collect();
+        let mut stdout = Vec::new();
+        let mut stderr = Vec::new();
+        let result = scenario.play(&args, &mut stdin.as_bytes(), &mut stdout, &mut stderr);
+        (
+            result,
+            String::from_utf8(stdout).unwrap(),
+            String::from_utf8(stderr).unwrap(),
+        )
+    }
+
+    #[test]
+    fn test_parse_steps() {
+        let scenario = Scenario::parse(concat!(
+            r#"{"type": "system", "subtype": "init", "delay_ms": 20}"#,
+            "\n\n",
+            r#"{"fake": "stderr", "line": "warming up"}"#,
+            "\n",
+            r#"{"fake": "sleep", "ms": 5}"#,
+            "\n",
+            r#"{"fake": "exit", "code": 2}"#,
+        ))
+        .unwrap();
+        assert_eq!(
+            scenario.steps(),
+            [
+                ScenarioStep {
+                    delay: Duration::from_millis(20),
+                    action: ScenarioAction::Emit(
+                        serde_json::json!({"type": "system", "subtype": "init"})
+                    ),
+                },
+                ScenarioStep {
+                    delay: Duration::ZERO,
+                    action: ScenarioAction::Stderr("warming up".to_string()),
+                },
+                ScenarioStep {
+                    delay: Duration::ZERO,
+                    action: ScenarioAction::Sleep(Duration::from_millis(5)),
+                },
+                ScenarioStep {
+                    delay: Duration::ZERO,
+                    action: ScenarioAction::Exit(2),
+                },
+            ]
+        );
+    }
+
+    #[test]
+    fn test_parse_rejects_bad_lines() {
+        for (content, line) in [
+            ("[1, 2]", 1),
+            ("{}\n{\"fake\": \"teleport\"}", 2),
+            ("\n{\"fake\": \"exit\"}", 2),
+            ("not json", 1),
+        ] {
+            match Scenario::parse(content) {
+                Err(ScenarioError::Invalid { line: found, .. }) => {
+                    assert_eq!(found, line, "{content}");
+                }
+                other => panic!("expected invalid line for {content:?}, got {other:?}"),
+            }
+        }
+    }
+
+    #[test]
+    fn test_play_emits_events_and_exits() {
+        let scenario = Scenario::parse(concat!(
+            r#"{"type": "result", "result": "done"}"#,
+            "\n",
+            r#"{"fake": "stderr", "line": "bye"}"#,
+            "\n",
+            r#"{"fake": "exit", "code": 3}"#,
+            "\n",
+            r#"{"type": "never"}"#,
+        ))
+        .unwrap();
+        let (result, stdout, stderr) = play(&scenario, &[], "");
+        assert_eq!(result.unwrap(), 3);
+        assert_eq!(stdout, "{\"result\":\"done\",\"type\":\"result\"}\n");
+        assert_eq!(stderr, "bye\n");
+    }
+
+    #[test]
+    fn test_play_checks_expectations() {
+        let scenario = Scenario::parse(concat!(
+            r#"{"fake": "expect_arg", "arg": "--resume"}"#,
+            "\n",
+            r#"{"fake": "expect_stdin", "contains": "continue"}"#,
+        ))
+        .unwrap();
+        let (result, _, _) = play(&scenario, &["-p", "--resume", "s-1"], "please continue\n");
+        assert_eq!(result.unwrap(), 0);
+
+        let (result, _, _) = play(&scenario, &["-p"], "please continue\n");
+        assert!(matches!(result, Err(ScenarioError::MissingArg(arg)) if arg == "--resume"));
+
+        let (result, _, _) = play(&scenario, &["--resume"], "");
+        assert!(matches!(
+            result,
+            Err(ScenarioError::StdinMismatch { found, .. }) if found.is_empty()
+        ));
+    }
+}
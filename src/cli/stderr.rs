@@ -0,0 +1,89 @@
+//! Capture of Claude Code's diagnostic output.
+//!
+//! Claude Code runs under `script` for a PTY, so its stderr reaches us
+//! interleaved with stdout; lines that are not stream-json are what it
+//! printed there. [`StderrCapture`] keeps those lines, plus anything the
+//! wrapper itself writes to the real stderr pipe.
+
+use std::sync::{Arc, Mutex, PoisonError};
+
+use tokio::io::{AsyncBufReadExt, AsyncRead, BufReader};
+
+/// Most bytes kept; later output is dropped.
+const MAX_CAPTURED_STDERR: usize = 16 * 1024;
+
+/// Non-JSON output of a Claude Code process, shared between readers.
+#[derive(Debug, Clone, Default)]
+pub struct StderrCapture {
+    buffer: Arc<Mutex<String>>,
+}
+
+impl StderrCapture {
+    /// Create an empty capture.
+    #[must_use]
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Collect every line of `reader` on a background task.
+    pub fn collect<R>(&self, reader: R)
+    where
+        R: AsyncRead + Unpin + Send + 'static,
+    {
+        let capture = self.clone();
+        tokio::spawn(async move {
+            let mut lines = BufReader::new(reader).lines();
+            while let Ok(Some(line)) = lines.next_line().await {
+                tracing::debug!(line = %line, "Claude stderr");
+                capture.push_line(&line);
+            }
+        });
+    }
+
+    /// Append one line.
+    pub fn push_line(&self, line: &str) {
+        let mut buffer = self.buffer.lock().unwrap_or_else(PoisonError::into_inner);
+        if buffer.len() + line.len() < MAX_CAPTURED_STDERR {
+            buffer.push_str(line);
+            buffer.push('\n');
+        }
+    }
+
+    /// Output collected so far.
+    #[must_use]
+    pub fn contents(&self) -> String {
+        self.buffer
+            .lock()
+            .unwrap_or_else(PoisonError::into_inner)
+            .clone()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[tokio::test]
+    async fn test_collect_reads_lines() {
+        let capture = StderrCapture::new();
+        capture.push_line("first");
+        capture.collect(&b"second\nthird\n"[..]);
+        for _ in 0..50 {
+            if capture.contents().contains("third") {
+                break;
+            }
+            tokio::time::sleep(std::time::Duration::from_millis(10)).await;
+        }
+        assert_eq!(capture.contents(), "first\nsecond\nthird\n");
+    }
+
+    #[test]
+    fn test_capture_is_bounded() {
+        let capture = StderrCapture::new();
+        let line = "x".repeat(1024);
+        for _ in 0..64 {
+            capture.push_line(&line);
+        }
+        assert!(capture.contents().len() < MAX_CAPTURED_STDERR);
+    }
+}
@@ -9,7 +9,8 @@ use tokio::io::{AsyncBufReadExt, AsyncRead, BufReader};
 use tokio::sync::mpsc::{self, Receiver, Sender};
 
 use crate::cli::events::RawClaudeEvent;
-use crate::cli::ClaudeEvent;
+use crate::cli::{ClaudeEvent, StderrCapture};
+use crate::telemetry::SPAN_STREAM_PARSE;
 
 /// Default buffer size for event channels.
 pub const DEFAULT_CHANNEL_BUFFER: usize = 64;
@@ -59,6 +60,7 @@ impl StreamParser {
     ///
     /// Returns `StreamError::ParseError` if the JSON is invalid.
     pub fn parse_line(line: &str) -> Result<ClaudeEvent, StreamError> {
+        let _span = tracing::trace_span!(SPAN_STREAM_PARSE, bytes = line.len()).entered();
         serde_json::from_str(line).map_err(|e| StreamError::ParseError {
             input: line.to_string(),
             reason: e.to_string(),
@@ -88,6 +90,18 @@ impl StreamParser {
     /// Returns `StreamError::ReadError` if reading fails.
     /// Returns `StreamError::ChannelClosed` if the receiver is dropped.
     pub async fn parse_stdout<R>(stdout: R, tx: Sender<ClaudeEvent>) -> Result<(), StreamError>
+    where
+        R: AsyncRead + Unpin,
+    {
+        Self::parse_stdout_capturing(stdout, tx, None).await
+    }
+
+    /// Like [`parse_stdout`](Self::parse_stdout), keeping invalid lines in `stderr`.
+    async fn parse_stdout_capturing<R>(
+        stdout: R,
+        tx: Sender<ClaudeEvent>,
+        stderr: Option<StderrCapture>,
+    ) -> Result<(), StreamError>
     where
         R: AsyncRead + Unpin,
     {
@@ -112,6 +126,9 @@ impl StreamParser {
                 }
                 Err(e) => {
                     tracing::warn!(error = %e, line = %line, "Failed to parse stream line");
+                    if let Some(ref stderr) = stderr {
+                        stderr.push_line(&line);
+                    }
                 }
             }
         }
@@ -187,6 +204,29 @@ impl StreamParser {
 
         rx
     }
+
+    /// Create a channel like [`into_channel`](Self::into_channel), keeping
+    /// lines that are not stream-json in `stderr`.
+    ///
+    /// Claude Code runs under a PTY, so what it prints to stderr arrives here.
+    pub fn into_channel_with_stderr<R>(
+        stdout: R,
+        buffer_size: usize,
+        stderr: StderrCapture,
+    ) -> Receiver<ClaudeEvent>
+    where
+        R: AsyncRead + Unpin + Send + 'static,
+    {
+        let (tx, rx) = mpsc::channel(buffer_size);
+
+        tokio::spawn(async move {
+            if let Err(e) = Self::parse_stdout_capturing(stdout, tx, Some(stderr)).await {
+                tracing::error!(error = %e, "Stream parsing failed");
+            }
+        });
+
+        rx
+    }
 }
 
 #[cfg(test)]
@@ -34,6 +34,31 @@ pub struct UninstallResult {
     pub stop_removed: bool,
 }
 
+/// Supervisor hook commands found in Claude settings.
+#[derive(Debug, Default)]
+pub struct HookReport {
+    /// Path to the settings file that was inspected.
+    pub settings_path: PathBuf,
+    /// Supervisor commands registered for `PreToolUse`.
+    pub pre_tool_use: Vec<String>,
+    /// Supervisor commands registered for Stop.
+    pub stop: Vec<String>,
+}
+
+impl HookReport {
+    /// Hook events with more than one supervisor entry, and their counts.
+    ///
+    /// Each duplicate makes the hook run again for the same event.
+    #[must_use]
+    pub fn duplicates(&self) -> Vec<(&'static str, usize)> {
+        [("PreToolUse", &self.pre_tool_use), ("Stop", &self.stop)]
+            .into_iter()
+            .filter(|(_, commands)| commands.len() > 1)
+            .map(|(event, commands)| (event, commands.len()))
+            .collect()
+    }
+}
+
 /// Errors that can occur during hook installation.
 #[derive(Debug, thiserror::Error)]
 pub enum InstallError {
@@ -157,7 +182,10 @@ impl HookInstaller {
         })
     }
 
-    /// Installs a hook entry, replacing any existing supervisor hook.
+    /// Installs a hook entry, replacing every existing supervisor hook.
+    ///
+    /// Duplicates left by earlier installs collapse into the new entry, which
+    /// takes the place of the last one found.
     fn install_hook_entry(
         hooks: &mut Option<Vec<HookEntry>>,
         entry: HookEntry,
@@ -165,18 +193,34 @@ impl HookInstaller {
     ) -> bool {
         let hook_list = hooks.get_or_insert_with(Vec::new);
 
-        // Check if we already have a supervisor hook
-        if let Some(idx) = hook_list.iter().position(HookEntry::is_supervisor_hook) {
-            if hook_list[idx] != entry {
-                hook_list[idx] = entry;
-                *replaced = true;
-            }
-            true
-        } else {
-            // Add new hook
-            hook_list.push(entry);
-            true
+        let (removed, slot) = take_supervisor_hooks(hook_list);
+        if removed.len() > 1 || removed.first().is_some_and(|old| *old != entry) {
+            *replaced = true;
         }
+        hook_list.insert(slot.unwrap_or(hook_list.len()), entry);
+        true
+    }
+
+    /// Lists the supervisor hooks currently in Claude settings.
+    ///
+    /// # Errors
+    ///
+    /// Returns an error if settings cannot be read.
+    pub fn report(&self) -> Result<HookReport, InstallError> {
+        let settings = ClaudeSettings::load_from(&self.settings_path)?;
+        let commands = |list: Option<&Vec<HookEntry>>| {
+            let mut commands = Vec::new();
+            for entry in list.into_iter().flatten() {
+                collect_supervisor_commands(entry, &mut commands);
+            }
+            commands
+        };
+        let hooks = settings.hooks.as_ref();
+        Ok(HookReport {
+            settings_path: self.settings_path.clone(),
+            pre_tool_use: commands(hooks.and_then(|h| h.pre_tool_use.as_ref())),
+            stop: commands(hooks.and_then(|h| h.stop.as_ref())),
+        })
     }
 
     /// Uninstalls claude-supervisor hooks from Claude settings.
@@ -193,9 +237,7 @@ impl HookInstaller {
         if let Some(ref mut hooks) = settings.hooks {
             // Remove PreToolUse supervisor hooks
             if let Some(ref mut list) = hooks.pre_tool_use {
-                let before_len = list.len();
-                list.retain(|h| !h.is_supervisor_hook());
-                pre_tool_use_removed = list.len() < before_len;
+                pre_tool_use_removed = !take_supervisor_hooks(list).0.is_empty();
 
                 // Clean up empty list
                 if list.is_empty() {
@@ -205,9 +247,7 @@ impl HookInstaller {
 
             // Remove Stop supervisor hooks
             if let Some(ref mut list) = hooks.stop {
-                let before_len = list.len();
-                list.retain(|h| !h.is_supervisor_hook());
-                stop_removed = list.len() < before_len;
+                stop_removed = !take_supervisor_hooks(list).0.is_empty();
 
                 // Clean up empty list
                 if list.is_empty() {
@@ -236,6 +276,48 @@ impl HookInstaller {
     }
 }
 
+/// Removes supervisor hook commands from a list, including inside matcher groups.
+///
+/// Groups left empty are removed too. Returns the removed commands and the
+/// index in the remaining list where the last removed top-level entry stood.
+fn take_supervisor_hooks(list: &mut Vec<HookEntry>) -> (Vec<HookEntry>, Option<usize>) {
+    let mut removed = Vec::new();
+    let mut slot = None;
+    let mut kept = Vec::with_capacity(list.len());
+    for mut entry in list.drain(..) {
+        if let Some(inner) = entry.hooks.as_mut() {
+            let (mut nested, _) = take_supervisor_hooks(inner);
+            if !nested.is_empty() && inner.is_empty() {
+                slot = Some(kept.len());
+                removed.append(&mut nested);
+                continue;
+            }
+            removed.append(&mut nested);
+            kept.push(entry);
+        } else if entry.is_supervisor_hook() {
+            slot = Some(kept.len());
+            removed.push(entry);
+        } else {
+            kept.push(entry);
+        }
+    }
+    *list = kept;
+    (removed, slot)
+}
+
+/// Appends the supervisor commands of an entry or matcher group.
+fn collect_supervisor_commands(entry: &HookEntry, commands: &mut Vec<String>) {
+    match entry.hooks {
+        Some(ref inner) => {
+            for entry in inner {
+                collect_supervisor_commands(entry, commands);
+            }
+        }
+        None if entry.is_supervisor_hook() => commands.push(entry.command.clone()),
+        None => {}
+    }
+}
+
 #[cfg(test)]
 mod tests {
     use super::*;
@@ -349,6 +431,72 @@ mod tests {
             .contains("/new/path/claude-supervisor"));
     }
 
+    #[test]
+    fn install_collapses_duplicate_supervisor_hooks() {
+        let (_temp_dir, settings_path) = create_temp_settings(
+            r#"{
+            "hooks": {
+                "PreToolUse": [
+                    {"type": "command", "command": "/old/bin/claude-supervisor hook pre-tool-use", "timeout": 5000},
+                    {"type": "command", "command": "other-tool", "timeout": 3000},
+                    {"matcher": "*", "hooks": [
+                        {"type": "command", "command": "/usr/local/bin/sv hook pre-tool-use --quiet"}
+                    ]}
+                ]
+            }
+        }"#,
+        );
+
+        let installer = HookInstaller::new(PathBuf::from("/new/bin/claude-supervisor"))
+            .unwrap()
+            .with_settings_path(settings_path.clone());
+        assert_eq!(
+            installer.report().unwrap().duplicates(),
+            vec![("PreToolUse", 2)]
+        );
+
+        let result = installer.install().unwrap();
+        assert!(result.replaced_existing);
+
+        let settings = ClaudeSettings::load_from(&settings_path).unwrap();
+        let pre_tool_use = settings.hooks.unwrap().pre_tool_use.unwrap();
+        assert_eq!(pre_tool_use.len(), 2);
+        assert_eq!(pre_tool_use[0].command, "other-tool");
+        assert_eq!(
+            pre_tool_use[1].command,
+            "/new/bin/claude-supervisor hook pre-tool-use"
+        );
+        assert!(installer.report().unwrap().duplicates().is_empty());
+    }
+
+    #[test]
+    fn uninstall_keeps_other_hooks_in_matcher_group() {
+        let (_temp_dir, settings_path) = create_temp_settings(
+            r#"{
+            "hooks": {
+                "Stop": [
+                    {"matcher": "", "hooks": [
+                        {"type": "command", "command": "notify-send done"},
+                        {"type": "command", "command": "/usr/bin/claude-supervisor hook stop"}
+                    ]}
+                ]
+            }
+        }"#,
+        );
+
+        let installer = HookInstaller::new(PathBuf::from("/usr/bin/claude-supervisor"))
+            .unwrap()
+            .with_settings_path(settings_path.clone());
+
+        assert!(installer.uninstall().unwrap().stop_removed);
+
+        let settings = ClaudeSettings::load_from(&settings_path).unwrap();
+        let stop = settings.hooks.unwrap().stop.unwrap();
+        let group = stop[0].hooks.as_ref().unwrap();
+        assert_eq!(group.len(), 1);
+        assert_eq!(group[0].command, "notify-send done");
+    }
+
     #[test]
     fn uninstall_removes_supervisor_hooks() {
         let (_temp_dir, settings_path) = create_temp_settings(
@@ -0,0 +1,129 @@
+//! Audit sink configuration.
+
+use std::path::PathBuf;
+
+use serde::{Deserialize, Serialize};
+
+/// Where audit records are written.
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, Serialize, Deserialize)]
+#[cfg_attr(test, derive(schemars::JsonSchema))]
+#[serde(rename_all = "lowercase")]
+pub enum AuditSinkKind {
+    /// The `SQLite` audit database, which the query commands read.
+    #[default]
+    Sqlite,
+    /// JSON lines files, rotated by size and day.
+    Jsonl,
+    /// RFC 5424 messages with structured data, sent to the local syslog
+    /// socket (journald reads it too).
+    Syslog,
+}
+
+impl AuditSinkKind {
+    /// Name used in the config file and in warnings.
+    #[must_use]
+    pub fn as_str(&self) -> &'static str {
+        match self {
+            Self::Sqlite => "sqlite",
+            Self::Jsonl => "jsonl",
+            Self::Syslog => "syslog",
+        }
+    }
+}
+
+/// One audit sink.
+///
+/// ```toml
+/// [[audit.sinks]]
+/// kind = "jsonl"
+/// path = "/var/log/claude-supervisor"
+/// max_bytes = 10485760
+/// ```
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+#[cfg_attr(test, derive(schemars::JsonSchema))]
+#[serde(default)]
+pub struct AuditSinkConfig {
+    /// Kind of sink.
+    pub kind: AuditSinkKind,
+    /// Directory of the JSONL files, or the syslog socket; unset uses the
+    /// sink's default. The `SQLite` database always lives in the data
+    /// directory.
+    pub path: Option<PathBuf>,
+    /// Size at which a JSONL file is rotated; 0 rotates by day only.
+    pub max_bytes: u64,
+}
+
+impl Default for AuditSinkConfig {
+    fn default() -> Self {
+        Self {
+            kind: AuditSinkKind::default(),
+            path: None,
+            max_bytes: 10 * 1024 * 1024,
+        }
+    }
+}
+
+impl AuditSinkConfig {
+    /// A sink of `kind` with default settings.
+    #[must_use]
+    pub fn new(kind: AuditSinkKind) -> Self {
+        Self {
+            kind,
+            ..Self::default()
+        }
+    }
+}
+
+/// Sinks receiving every audit record of a session (global config only).
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+#[cfg_attr(test, derive(schemars::JsonSchema))]
+#[serde(default)]
+pub struct AuditConfig {
+    /// Sinks written to, in order. Without a `sqlite` sink the query
+    /// commands and the dashboard history have nothing to read.
+    pub sinks: Vec<AuditSinkConfig>,
+}
+
+impl Default for AuditConfig {
+    fn default() -> Self {
+        Self {
+            sinks: vec![AuditSinkConfig::new(AuditSinkKind::Sqlite)],
+        }
+    }
+}
+
+impl AuditConfig {
+    /// Whether records go to the `SQLite` database.
+    #[must_use]
+    pub fn sqlite_enabled(&self) -> bool {
+        self.sinks
+            .iter()
+            .any(|sink| sink.kind == AuditSinkKind::Sqlite)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_audit_config_deserialize() {
+        let config: AuditConfig = toml::from_str(
+            r#"
+            [[sinks]]
+            kind = "jsonl"
+            path = "/var/log/supervisor"
+
+            [[sinks]]
+            kind = "syslog"
+            "#,
+        )
+        .unwrap();
+        assert_eq!(config.sinks.len(), 2);
+        assert_eq!(config.sinks[0].kind, AuditSinkKind::Jsonl);
+        assert_eq!(config.sinks[0].max_bytes, 10 * 1024 * 1024);
+        assert_eq!(config.sinks[1].path, None);
+        assert!(!config.sqlite_enabled());
+        assert!(AuditConfig::default().sqlite_enabled());
+    }
+}
@@ -0,0 +1,134 @@
+//! Cumulative blast radius limits.
+
+use serde::{Deserialize, Serialize};
+
+/// Score added per mutating operation, by kind.
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+#[cfg_attr(test, derive(schemars::JsonSchema))]
+pub struct MutationWeights {
+    /// File deletion, by a tool or `rm`-like shell command.
+    #[serde(default = "default_delete_weight")]
+    pub delete: u32,
+    /// File write or edit by a tool.
+    #[serde(default = "default_write_weight")]
+    pub write: u32,
+    /// Shell command writing files (redirects, `cp`, `sed -i`).
+    #[serde(default = "default_write_weight")]
+    pub shell_write: u32,
+    /// Shell command moving or renaming files.
+    #[serde(default = "default_move_weight")]
+    pub shell_move: u32,
+    /// Shell command changing permissions or ownership.
+    #[serde(default = "default_shell_weight")]
+    pub shell_permissions: u32,
+    /// Git command rewriting history or the remote.
+    #[serde(default = "default_shell_weight")]
+    pub shell_vcs: u32,
+    /// Package install or removal.
+    #[serde(default = "default_shell_weight")]
+    pub shell_install: u32,
+}
+
+fn default_delete_weight() -> u32 {
+    5
+}
+
+fn default_write_weight() -> u32 {
+    1
+}
+
+fn default_move_weight() -> u32 {
+    2
+}
+
+fn default_shell_weight() -> u32 {
+    3
+}
+
+impl Default for MutationWeights {
+    fn default() -> Self {
+        Self {
+            delete: default_delete_weight(),
+            write: default_write_weight(),
+            shell_write: default_write_weight(),
+            shell_move: default_move_weight(),
+            shell_permissions: default_shell_weight(),
+            shell_vcs: default_shell_weight(),
+            shell_install: default_shell_weight(),
+        }
+    }
+}
+
+/// Limits on the weighted sum of a session's mutating operations.
+///
+/// Each operation adds its weight to the score, which halves every
+/// `half_life_secs`. Crossing `escalate_at` escalates the call that crossed
+/// it; at `deny_at` every mutating call is denied.
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+#[cfg_attr(test, derive(schemars::JsonSchema))]
+pub struct BlastRadiusConfig {
+    /// Whether the score is checked against the thresholds at all.
+    #[serde(default = "default_enabled")]
+    pub enabled: bool,
+    /// Seconds after which an operation counts half as much.
+    #[serde(default = "default_half_life_secs")]
+    pub half_life_secs: u64,
+    /// Score at which the session is escalated.
+    #[serde(default = "default_escalate_at")]
+    pub escalate_at: u32,
+    /// Score at which mutating calls are denied.
+    #[serde(default = "default_deny_at")]
+    pub deny_at: u32,
+    /// Score per kind of operation.
+    #[serde(default)]
+    pub weights: MutationWeights,
+}
+
+fn default_enabled() -> bool {
+    true
+}
+
+fn default_half_life_secs() -> u64 {
+    300
+}
+
+fn default_escalate_at() -> u32 {
+    100
+}
+
+fn default_deny_at() -> u32 {
+    250
+}
+
+impl Default for BlastRadiusConfig {
+    fn default() -> Self {
+        Self {
+            enabled: default_enabled(),
+            half_life_secs: default_half_life_secs(),
+            escalate_at: default_escalate_at(),
+            deny_at: default_deny_at(),
+            weights: MutationWeights::default(),
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_blast_radius_config_deserialize() {
+        let toml_str = r"
+            escalate_at = 20
+
+            [weights]
+            delete = 10
+        ";
+        let config: BlastRadiusConfig = toml::from_str(toml_str).unwrap();
+        assert!(config.enabled);
+        assert_eq!(config.escalate_at, 20);
+        assert_eq!(config.deny_at, 250);
+        assert_eq!(config.weights.delete, 10);
+        assert_eq!(config.weights.write, 1);
+    }
+}
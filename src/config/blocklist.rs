@@ -0,0 +1,158 @@
+//! User-defined blocklist rule configuration.
+
+use serde::{Deserialize, Serialize};
+
+use crate::supervisor::{PolicyLevel, RuleCategory};
+
+/// A command rule added to the built-in blocklist.
+///
+/// ```toml
+/// [[blocklist]]
+/// pattern = 'kubectl\s+delete\s+(ns|namespace)\b'
+/// category = "destructive"
+/// reason = "deletes a Kubernetes namespace"
+/// ```
+///
+/// Commands in which the regex `pattern` is found are denied. An unknown
+/// `category` fails the config load.
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+#[cfg_attr(test, derive(schemars::JsonSchema))]
+pub struct BlocklistEntry {
+    /// Regex searched for in each Bash command.
+    pub pattern: String,
+    /// Category of the rule in denial reasons.
+    pub category: RuleCategory,
+    /// Why matching commands are denied; defaults to naming the pattern.
+    #[serde(default, skip_serializing_if = "String::is_empty")]
+    pub reason: String,
+}
+
+impl BlocklistEntry {
+    /// Description of the rule in denial reasons.
+    #[must_use]
+    pub fn description(&self) -> String {
+        if self.reason.is_empty() {
+            format!("matches `{}`", self.pattern)
+        } else {
+            self.reason.clone()
+        }
+    }
+}
+
+/// What happens to a Bash command matching a blocklist rule.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+#[cfg_attr(test, derive(schemars::JsonSchema))]
+#[serde(rename_all = "lowercase")]
+pub enum BlocklistAction {
+    /// Leave the command to the other rules.
+    Allow,
+    /// Ask the supervisor.
+    Escalate,
+    /// Deny the command.
+    Deny,
+}
+
+/// Per-level actions for the commands of one blocklist category.
+///
+/// ```toml
+/// [blocklist_levels.privilege_escalation]
+/// permissive = "deny"
+/// ```
+///
+/// Levels left out keep the built-in action: privilege escalation is
+/// escalated at the permissive level, and every other category is denied
+/// at every level.
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, Serialize, Deserialize)]
+#[cfg_attr(test, derive(schemars::JsonSchema))]
+#[serde(default)]
+pub struct BlocklistLevels {
+    /// Action at the permissive level.
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub permissive: Option<BlocklistAction>,
+    /// Action at the moderate level.
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub moderate: Option<BlocklistAction>,
+    /// Action at the strict level.
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub strict: Option<BlocklistAction>,
+}
+
+impl BlocklistLevels {
+    /// The built-in actions for `category`.
+    #[must_use]
+    pub fn builtin(category: RuleCategory) -> Self {
+        let permissive = match category {
+            RuleCategory::PrivilegeEscalation => BlocklistAction::Escalate,
+            _ => BlocklistAction::Deny,
+        };
+        Self {
+            permissive: Some(permissive),
+            moderate: Some(BlocklistAction::Deny),
+            strict: Some(BlocklistAction::Deny),
+        }
+    }
+
+    /// The configured action at `level`, if any.
+    #[must_use]
+    pub fn at(&self, level: PolicyLevel) -> Option<BlocklistAction> {
+        match level {
+            PolicyLevel::Permissive => self.permissive,
+            PolicyLevel::Moderate => self.moderate,
+            PolicyLevel::Strict => self.strict,
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_blocklist_entry_deserialize() {
+        let entry: BlocklistEntry = toml::from_str(
+            r#"
+            pattern = 'terraform\s+destroy'
+            category = "system_modification"
+            "#,
+        )
+        .unwrap();
+        assert_eq!(entry.category, RuleCategory::SystemModification);
+        assert_eq!(entry.description(), r"matches `terraform\s+destroy`");
+
+        let error = toml::from_str::<BlocklistEntry>(
+            r#"
+            pattern = "kubectl delete ns"
+            category = "infra"
+            "#,
+        )
+        .unwrap_err();
+        assert!(
+            error.to_string().contains("unknown variant `infra`"),
+            "{error}"
+        );
+    }
+
+    #[test]
+    fn test_blocklist_levels() {
+        let levels: BlocklistLevels = toml::from_str(r#"permissive = "deny""#).unwrap();
+        assert_eq!(
+            levels.at(PolicyLevel::Permissive),
+            Some(BlocklistAction::Deny)
+        );
+        assert_eq!(levels.at(PolicyLevel::Strict), None);
+
+        let builtin = BlocklistLevels::builtin(RuleCategory::PrivilegeEscalation);
+        assert_eq!(
+            builtin.at(PolicyLevel::Permissive),
+            Some(BlocklistAction::Escalate)
+        );
+        assert_eq!(
+            builtin.at(PolicyLevel::Moderate),
+            Some(BlocklistAction::Deny)
+        );
+        assert_eq!(
+            BlocklistLevels::builtin(RuleCategory::Destructive).at(PolicyLevel::Permissive),
+            Some(BlocklistAction::Deny)
+        );
+    }
+}
@@ -0,0 +1,47 @@
+//! Cost budget configuration.
+
+use serde::{Deserialize, Serialize};
+
+use super::WebhookConfig;
+
+/// Cost limit of a session, with alerts before it is reached.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[cfg_attr(test, derive(schemars::JsonSchema))]
+#[serde(default)]
+pub struct BudgetConfig {
+    /// Session cost in USD at which the session is killed; no budget when
+    /// unset.
+    pub max_cost_usd: Option<f64>,
+    /// Shares of the budget, in percent, at which an alert is raised once.
+    pub alert_percent: Vec<u32>,
+    /// Tell Claude at each alert that it should prioritize finishing.
+    pub notify_claude: bool,
+    /// Endpoint alerts are sent to as JSON; disabled while `url` is empty.
+    pub webhook: WebhookConfig,
+}
+
+impl Default for BudgetConfig {
+    fn default() -> Self {
+        Self {
+            max_cost_usd: None,
+            alert_percent: vec![50, 80],
+            notify_claude: false,
+            webhook: WebhookConfig::default(),
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_budget_config_deserialize() {
+        let config: BudgetConfig =
+            toml::from_str("max_cost_usd = 2.5\nnotify_claude = true\n").unwrap();
+        assert_eq!(config.max_cost_usd, Some(2.5));
+        assert_eq!(config.alert_percent, vec![50, 80]);
+        assert!(config.notify_claude);
+        assert!(config.webhook.url.is_empty());
+    }
+}
@@ -36,17 +36,29 @@ pub struct HooksConfig {
     pub other: HashMap<String, serde_json::Value>,
 }
 
+/// Hook events the supervisor installs, as named on its `hook` subcommand.
+pub const SUPERVISOR_HOOK_EVENTS: &[&str] = &["pre-tool-use", "stop"];
+
 /// A single hook entry.
+///
+/// Either a command, or a matcher group wrapping further entries in `hooks`.
 #[derive(Debug, Clone, Serialize, Deserialize, PartialEq)]
 pub struct HookEntry {
-    /// Hook type (always "command" for our hooks).
-    #[serde(rename = "type")]
+    /// Hook type (always "command" for our hooks; absent on matcher groups).
+    #[serde(rename = "type", default, skip_serializing_if = "String::is_empty")]
     pub hook_type: String,
     /// Command to execute.
+    #[serde(default, skip_serializing_if = "String::is_empty")]
     pub command: String,
     /// Timeout in milliseconds.
     #[serde(skip_serializing_if = "Option::is_none")]
     pub timeout: Option<u32>,
+    /// Tool name pattern of a matcher group.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub matcher: Option<String>,
+    /// Entries of a matcher group.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub hooks: Option<Vec<HookEntry>>,
 }
 
 impl HookEntry {
@@ -57,16 +69,46 @@ impl HookEntry {
             hook_type: "command".to_string(),
             command: cmd.into(),
             timeout: Some(timeout),
+            matcher: None,
+            hooks: None,
         }
     }
 
     /// Checks if this hook entry was created by claude-supervisor.
+    ///
+    /// Matches on the `hook <event>` arguments rather than the binary path,
+    /// so entries from older installs at other paths are recognized. For a
+    /// matcher group, true if any entry inside it is a supervisor hook.
     #[must_use]
     pub fn is_supervisor_hook(&self) -> bool {
         self.command.contains("claude-supervisor")
+            || supervisor_hook_event(&self.command).is_some()
+            || self
+                .hooks
+                .iter()
+                .flatten()
+                .any(HookEntry::is_supervisor_hook)
     }
 }
 
+/// The supervisor hook event a command runs, if it is a supervisor hook.
+///
+/// Looks for `<binary> hook <event>` anywhere in the command, so wrappers
+/// (`env X=1 ...`) and trailing arguments do not hide it.
+#[must_use]
+pub fn supervisor_hook_event(command: &str) -> Option<&'static str> {
+    let words: Vec<&str> = command.split_whitespace().collect();
+    words.windows(3).find_map(|window| {
+        if window[1] != "hook" {
+            return None;
+        }
+        SUPERVISOR_HOOK_EVENTS
+            .iter()
+            .find(|event| **event == window[2])
+            .copied()
+    })
+}
+
 impl ClaudeSettings {
     /// Returns the default path for Claude settings.json.
     #[must_use]
@@ -248,6 +290,39 @@ mod tests {
         assert!(!other.is_supervisor_hook());
     }
 
+    #[test]
+    fn hook_entry_is_supervisor_hook_regardless_of_path_and_args() {
+        for command in [
+            "/home/me/.cargo/bin/cs hook pre-tool-use",
+            "env RUST_LOG=debug /opt/supervisor hook stop --quiet",
+            "\"/Applications/Supervisor Tools/sv\" hook stop",
+        ] {
+            assert!(
+                HookEntry::command(command, 5000).is_supervisor_hook(),
+                "{command}"
+            );
+        }
+        assert!(!HookEntry::command("hook stop", 5000).is_supervisor_hook());
+        assert!(!HookEntry::command("lint hook commit", 5000).is_supervisor_hook());
+    }
+
+    #[test]
+    fn hook_entry_matcher_group_is_supervisor_hook() {
+        let group: HookEntry = serde_json::from_value(serde_json::json!({
+            "matcher": "*",
+            "hooks": [{"type": "command", "command": "/usr/bin/sv hook pre-tool-use"}]
+        }))
+        .unwrap();
+        assert!(group.is_supervisor_hook());
+        assert_eq!(
+            serde_json::to_value(&group).unwrap(),
+            serde_json::json!({
+                "matcher": "*",
+                "hooks": [{"type": "command", "command": "/usr/bin/sv hook pre-tool-use"}]
+            })
+        );
+    }
+
     #[test]
     fn load_from_nonexistent_returns_default() {
         let path = PathBuf::from("/nonexistent/path/settings.json");
@@ -0,0 +1,59 @@
+//! Write containment configuration.
+
+use std::path::PathBuf;
+
+use serde::{Deserialize, Serialize};
+
+/// What happens to a write whose real target is outside the allowed roots.
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, Serialize, Deserialize)]
+#[cfg_attr(test, derive(schemars::JsonSchema))]
+#[serde(rename_all = "lowercase")]
+pub enum ContainmentAction {
+    /// Deny the call.
+    #[default]
+    Deny,
+    /// Ask the supervisor.
+    Escalate,
+}
+
+/// Configuration for keeping writes inside the project.
+///
+/// Only honoured from the global config file, like
+/// [`SelfProtectionConfig`](super::SelfProtectionConfig): a project-level
+/// config could otherwise allow every path.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[cfg_attr(test, derive(schemars::JsonSchema))]
+#[serde(default)]
+pub struct ContainmentConfig {
+    /// Check the real target of Write, Edit and `NotebookEdit` calls and of
+    /// Bash redirects against the session cwd and repository roots.
+    pub enabled: bool,
+    /// What to do with a write outside the allowed roots.
+    pub action: ContainmentAction,
+    /// Directories writes may also target, such as `/tmp`.
+    pub extra_roots: Vec<PathBuf>,
+}
+
+impl Default for ContainmentConfig {
+    fn default() -> Self {
+        Self {
+            enabled: true,
+            action: ContainmentAction::default(),
+            extra_roots: vec![std::env::temp_dir()],
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_containment_config_deserialize() {
+        let config: ContainmentConfig =
+            toml::from_str("action = \"escalate\"\nextra_roots = [\"/scratch\"]\n").unwrap();
+        assert!(config.enabled);
+        assert_eq!(config.action, ContainmentAction::Escalate);
+        assert_eq!(config.extra_roots, vec![PathBuf::from("/scratch")]);
+    }
+}
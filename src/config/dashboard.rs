@@ -0,0 +1,87 @@
+//! Dashboard event publishing configuration.
+
+use std::collections::BTreeMap;
+
+use serde::{Deserialize, Serialize};
+
+/// Where the dashboard is served and what its clients are sent of each
+/// event.
+///
+/// `listen` is the `host:port` the dashboard server listens on during
+/// `run`; the dashboard is not served without it. `fields` lists the payload fields published per event type. An entry
+/// named `<type>.<tool>` applies to events of that type about that tool
+/// and takes precedence over the entry of the type. Event types without an
+/// entry are published whole; an empty list keeps the event from clients
+/// altogether.
+///
+/// ```toml
+/// [dashboard]
+/// listen = "127.0.0.1:3000"
+///
+/// [dashboard.fields]
+/// "tool_call.Write" = ["tool_use_id", "tool", "decision"]
+/// todos = []
+/// ```
+#[derive(Debug, Clone, Default, PartialEq, Eq, Serialize, Deserialize)]
+#[cfg_attr(test, derive(schemars::JsonSchema))]
+pub struct DashboardEventConfig {
+    /// Address the dashboard server listens on, as `host:port`.
+    #[serde(default)]
+    pub listen: Option<String>,
+    /// Published fields by event type, or `<type>.<tool>`.
+    #[serde(default)]
+    pub fields: BTreeMap<String, Vec<String>>,
+}
+
+impl DashboardEventConfig {
+    /// Host and port of `listen`, or `None` if unset or not `host:port`.
+    #[must_use]
+    pub fn listen_address(&self) -> Option<(&str, u16)> {
+        let (host, port) = self.listen.as_deref()?.rsplit_once(':')?;
+        let port = port.parse().ok()?;
+        (!host.is_empty()).then_some((host, port))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_dashboard_event_config_deserialize() {
+        let config: DashboardEventConfig = toml::from_str(
+            r#"
+            [fields]
+            "tool_call.Write" = ["tool", "decision"]
+            todos = []
+            "#,
+        )
+        .unwrap();
+        assert_eq!(config.fields["tool_call.Write"], ["tool", "decision"]);
+        assert!(config.fields["todos"].is_empty());
+        assert_eq!(config.listen_address(), None);
+        assert_eq!(
+            toml::from_str::<DashboardEventConfig>("").unwrap(),
+            DashboardEventConfig::default()
+        );
+    }
+
+    #[test]
+    fn test_listen_address() {
+        let listen = |address: &str| DashboardEventConfig {
+            listen: Some(address.to_string()),
+            ..Default::default()
+        };
+        assert_eq!(
+            listen("127.0.0.1:3000").listen_address(),
+            Some(("127.0.0.1", 3000))
+        );
+        assert_eq!(
+            listen("0.0.0.0:8080").listen_address(),
+            Some(("0.0.0.0", 8080))
+        );
+        assert_eq!(listen("127.0.0.1").listen_address(), None);
+        assert_eq!(listen(":3000").listen_address(), None);
+        assert_eq!(listen("localhost:http").listen_address(), None);
+    }
+}
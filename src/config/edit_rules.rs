@@ -0,0 +1,64 @@
+//! Edit-aware policy rule configuration.
+
+use serde::{Deserialize, Serialize};
+
+/// What happens to an Edit or `MultiEdit` call a rule matches.
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, Serialize, Deserialize)]
+#[cfg_attr(test, derive(schemars::JsonSchema))]
+#[serde(rename_all = "lowercase")]
+pub enum EditRuleAction {
+    /// Deny the call.
+    Deny,
+    /// Ask the supervisor.
+    #[default]
+    Escalate,
+}
+
+/// A rule on what an Edit or `MultiEdit` call changes.
+///
+/// ```toml
+/// [[edit_rules]]
+/// name = "keep unsafe forbidden"
+/// file_glob = "**/*.rs"
+/// content_removed_matches = '#\[forbid\(unsafe_code\)\]'
+/// action = "deny"
+/// ```
+///
+/// A rule matches a hunk when every condition it sets holds; a `MultiEdit`
+/// call matches when any of its hunks does.
+#[derive(Debug, Clone, Default, PartialEq, Eq, Serialize, Deserialize)]
+#[cfg_attr(test, derive(schemars::JsonSchema))]
+#[serde(default)]
+pub struct EditRuleConfig {
+    /// Name of the rule in decision reasons.
+    pub name: String,
+    /// Glob the edited file must match, either its absolute path or its
+    /// path inside a session root.
+    pub file_glob: Option<String>,
+    /// Regex matched against each line a hunk removes.
+    pub content_removed_matches: Option<String>,
+    /// Regex matched against each line a hunk adds.
+    pub content_added_matches: Option<String>,
+    /// What to do with a matching call.
+    pub action: EditRuleAction,
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_edit_rule_config_deserialize() {
+        let rule: EditRuleConfig = toml::from_str(
+            r#"
+            name = "version pins"
+            file_glob = "**/Cargo.toml"
+            content_added_matches = '^\w+ = "[^=]'
+            "#,
+        )
+        .unwrap();
+        assert_eq!(rule.file_glob.as_deref(), Some("**/Cargo.toml"));
+        assert_eq!(rule.content_removed_matches, None);
+        assert_eq!(rule.action, EditRuleAction::Escalate);
+    }
+}
@@ -0,0 +1,214 @@
+//! Escalation backend configuration.
+
+use serde::{Deserialize, Serialize};
+
+/// Which backend decides escalated tool calls.
+#[derive(Debug, Clone, Copy, Serialize, Deserialize, Default, PartialEq, Eq)]
+#[cfg_attr(test, derive(schemars::JsonSchema))]
+#[serde(rename_all = "lowercase")]
+pub enum DecisionBackendKind {
+    /// Ask the configured AI provider.
+    #[default]
+    Ai,
+    /// POST the escalation to an HTTP service.
+    Webhook,
+}
+
+/// What to do when the decision backend fails or times out.
+#[derive(Debug, Clone, Copy, Serialize, Deserialize, Default, PartialEq, Eq)]
+#[cfg_attr(test, derive(schemars::JsonSchema))]
+#[serde(rename_all = "lowercase")]
+pub enum OnAiFailure {
+    /// Deny the tool call and kill the session.
+    #[default]
+    Deny,
+    /// Allow the tool call and keep going.
+    Allow,
+}
+
+/// Which engine's decision stands when hook and runner both see a tool call.
+///
+/// With hooks installed, the hook decides a call before it runs and the
+/// runner evaluates it again from the event stream. The two can disagree,
+/// e.g. when they were started with different policies.
+#[derive(Debug, Clone, Copy, Serialize, Deserialize, Default, PartialEq, Eq)]
+#[cfg_attr(test, derive(schemars::JsonSchema))]
+#[serde(rename_all = "lowercase")]
+pub enum DecisionAuthority {
+    /// The runner evaluates every call itself and ignores hook decisions.
+    #[default]
+    Runner,
+    /// A decision reported by the hook stands; the runner only evaluates
+    /// calls the hook did not report.
+    Hook,
+    /// The stricter of the two decisions stands.
+    Strictest,
+}
+
+/// Configuration for the webhook decision backend.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[cfg_attr(test, derive(schemars::JsonSchema))]
+pub struct WebhookConfig {
+    /// Endpoint that receives escalations.
+    #[serde(default)]
+    pub url: String,
+    /// Environment variable holding the `Authorization` header value.
+    #[serde(default)]
+    pub auth_header_env: Option<String>,
+    /// Request timeout in seconds.
+    #[serde(default = "default_timeout_secs")]
+    pub timeout_secs: u64,
+}
+
+fn default_timeout_secs() -> u64 {
+    10
+}
+
+impl Default for WebhookConfig {
+    fn default() -> Self {
+        Self {
+            url: String::new(),
+            auth_header_env: None,
+            timeout_secs: default_timeout_secs(),
+        }
+    }
+}
+
+/// Decision taken when an interactive approval prompt is not answered.
+#[derive(Debug, Clone, Copy, Serialize, Deserialize, Default, PartialEq, Eq)]
+#[cfg_attr(test, derive(schemars::JsonSchema))]
+#[serde(rename_all = "lowercase")]
+pub enum ApprovalDefault {
+    /// Deny the tool call.
+    #[default]
+    Deny,
+    /// Allow the tool call.
+    Allow,
+}
+
+/// Configuration for terminal approval prompts (`run --interactive-approvals`).
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[cfg_attr(test, derive(schemars::JsonSchema))]
+pub struct InteractiveConfig {
+    /// Prompt in the terminal for escalations instead of asking the backend.
+    /// Ignored when stdin is not a terminal.
+    #[serde(default)]
+    pub enabled: bool,
+    /// Seconds to wait for an answer.
+    #[serde(default = "default_interactive_timeout_secs")]
+    pub timeout_secs: u64,
+    /// Decision taken when no answer arrives in time.
+    #[serde(default)]
+    pub default: ApprovalDefault,
+    /// Most bytes of the tool input shown in a prompt.
+    #[serde(default = "default_interactive_max_input_bytes")]
+    pub max_input_bytes: usize,
+}
+
+fn default_interactive_timeout_secs() -> u64 {
+    120
+}
+
+fn default_interactive_max_input_bytes() -> usize {
+    2048
+}
+
+impl Default for InteractiveConfig {
+    fn default() -> Self {
+        Self {
+            enabled: false,
+            timeout_secs: default_interactive_timeout_secs(),
+            default: ApprovalDefault::default(),
+            max_input_bytes: default_interactive_max_input_bytes(),
+        }
+    }
+}
+
+/// Configuration for how escalated tool calls are decided.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[cfg_attr(test, derive(schemars::JsonSchema))]
+pub struct EscalationConfig {
+    /// Backend that decides escalations.
+    #[serde(default)]
+    pub backend: DecisionBackendKind,
+    /// Webhook settings, used when `backend = "webhook"`.
+    #[serde(default)]
+    pub webhook: WebhookConfig,
+    /// Fallback when the backend fails to answer.
+    #[serde(default)]
+    pub on_ai_failure: OnAiFailure,
+    /// Which decision stands when hook and runner disagree.
+    #[serde(default)]
+    pub decision_authority: DecisionAuthority,
+    /// Milliseconds the runner waits for a hook to report its decision
+    /// before evaluating the call itself.
+    #[serde(default = "default_hook_decision_wait_ms")]
+    pub hook_decision_wait_ms: u64,
+    /// Terminal prompt settings, used with `--interactive-approvals`.
+    #[serde(default)]
+    pub interactive: InteractiveConfig,
+}
+
+#[allow(clippy::cast_possible_truncation)]
+fn default_hook_decision_wait_ms() -> u64 {
+    crate::ipc::DEFAULT_HOOK_DECISION_WAIT.as_millis() as u64
+}
+
+impl Default for EscalationConfig {
+    fn default() -> Self {
+        Self {
+            backend: DecisionBackendKind::default(),
+            webhook: WebhookConfig::default(),
+            on_ai_failure: OnAiFailure::default(),
+            decision_authority: DecisionAuthority::default(),
+            hook_decision_wait_ms: default_hook_decision_wait_ms(),
+            interactive: InteractiveConfig::default(),
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_escalation_config_default() {
+        let config = EscalationConfig::default();
+        assert_eq!(config.backend, DecisionBackendKind::Ai);
+        assert_eq!(config.on_ai_failure, OnAiFailure::Deny);
+        assert_eq!(config.webhook.timeout_secs, 10);
+        assert_eq!(config.decision_authority, DecisionAuthority::Runner);
+        assert_eq!(config.hook_decision_wait_ms, 500);
+        assert_eq!(config.interactive.timeout_secs, 120);
+        assert_eq!(config.interactive.default, ApprovalDefault::Deny);
+    }
+
+    #[test]
+    fn test_escalation_config_deserialize_authority() {
+        let config: EscalationConfig =
+            toml::from_str(r#"decision_authority = "strictest""#).unwrap();
+        assert_eq!(config.decision_authority, DecisionAuthority::Strictest);
+        assert_eq!(config.hook_decision_wait_ms, 500);
+    }
+
+    #[test]
+    fn test_escalation_config_deserialize_webhook() {
+        let toml_str = r#"
+            backend = "webhook"
+            on_ai_failure = "allow"
+
+            [webhook]
+            url = "https://policy.example.com/escalate"
+            auth_header_env = "POLICY_TOKEN"
+        "#;
+        let config: EscalationConfig = toml::from_str(toml_str).unwrap();
+        assert_eq!(config.backend, DecisionBackendKind::Webhook);
+        assert_eq!(config.on_ai_failure, OnAiFailure::Allow);
+        assert_eq!(config.webhook.url, "https://policy.example.com/escalate");
+        assert_eq!(
+            config.webhook.auth_header_env.as_deref(),
+            Some("POLICY_TOKEN")
+        );
+        assert_eq!(config.webhook.timeout_secs, 10);
+    }
+}
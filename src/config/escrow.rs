@@ -0,0 +1,39 @@
+//! Deletion escrow configuration.
+
+use std::path::PathBuf;
+
+use serde::{Deserialize, Serialize};
+
+/// Configuration for moving deleted files to a trash directory.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[cfg_attr(test, derive(schemars::JsonSchema))]
+#[serde(default)]
+pub struct EscrowConfig {
+    /// Whether deletions inside the project are moved to the trash instead.
+    pub enabled: bool,
+    /// Trash directory; a relative path is resolved against the session
+    /// working directory.
+    pub dir: PathBuf,
+}
+
+impl Default for EscrowConfig {
+    fn default() -> Self {
+        Self {
+            enabled: false,
+            dir: PathBuf::from(".claude-supervisor/trash"),
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_escrow_config_deserialize() {
+        let config: EscrowConfig = toml::from_str("enabled = true").unwrap();
+        assert!(config.enabled);
+        assert_eq!(config.dir, PathBuf::from(".claude-supervisor/trash"));
+        assert!(!EscrowConfig::default().enabled);
+    }
+}
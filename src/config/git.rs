@@ -0,0 +1,94 @@
+//! Git operation gate configuration.
+
+use serde::{Deserialize, Serialize};
+
+/// What happens to a Bash command that runs a git operation.
+///
+/// Variants are ordered by strictness; when a command runs several
+/// operations, the strictest decides.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, PartialOrd, Ord, Serialize, Deserialize)]
+#[cfg_attr(test, derive(schemars::JsonSchema))]
+#[serde(rename_all = "lowercase")]
+pub enum GitAction {
+    /// Leave the command to the other rules.
+    Allow,
+    /// Ask the supervisor.
+    Escalate,
+    /// Deny the command.
+    Deny,
+}
+
+/// Configuration for gating git commits, pushes, tags and history rewrites.
+///
+/// ```toml
+/// [git]
+/// allow_commit = true
+/// allow_push = "escalate"
+/// protected_branches = ["main", "release/*"]
+/// ```
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[cfg_attr(test, derive(schemars::JsonSchema))]
+#[serde(default)]
+pub struct GitConfig {
+    /// Whether Bash commands are checked for git operations.
+    pub enabled: bool,
+    /// Whether commits to unprotected branches are left to the other rules;
+    /// otherwise they are escalated.
+    pub allow_commit: bool,
+    /// What to do with pushes.
+    pub allow_push: GitAction,
+    /// What to do with tags that are created or deleted.
+    pub allow_tag: GitAction,
+    /// What to do with deleted branches, local or remote.
+    pub allow_branch_delete: GitAction,
+    /// What to do with force pushes.
+    pub force_push: GitAction,
+    /// What to do with history rewrites: `commit --amend`, `rebase -i`,
+    /// `filter-branch`, `filter-repo` and pushes after `reset --hard`.
+    pub history_rewrite: GitAction,
+    /// Branch globs that commits and pushes escalate for, and that force
+    /// pushes, rebases, rewrites and deletions are denied for.
+    pub protected_branches: Vec<String>,
+    /// Whether force pushes, rebases, rewrites and deletions of protected
+    /// branches are denied; otherwise they are escalated at least.
+    pub protect_history: bool,
+}
+
+impl Default for GitConfig {
+    fn default() -> Self {
+        Self {
+            enabled: true,
+            allow_commit: true,
+            allow_push: GitAction::Escalate,
+            allow_tag: GitAction::Allow,
+            allow_branch_delete: GitAction::Escalate,
+            force_push: GitAction::Deny,
+            history_rewrite: GitAction::Escalate,
+            protected_branches: vec!["main".to_string(), "master".to_string()],
+            protect_history: true,
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_git_config_deserialize() {
+        let config: GitConfig = toml::from_str(
+            r#"
+            allow_commit = false
+            allow_push = "deny"
+            protected_branches = ["main", "release/*"]
+            "#,
+        )
+        .unwrap();
+        assert!(config.enabled && !config.allow_commit);
+        assert_eq!(config.allow_push, GitAction::Deny);
+        assert_eq!(config.force_push, GitAction::Deny);
+        assert_eq!(config.protected_branches, ["main", "release/*"]);
+        assert!(config.protect_history);
+        assert!(GitAction::Deny > GitAction::Escalate);
+    }
+}
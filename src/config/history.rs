@@ -0,0 +1,59 @@
+//! Event history configuration.
+
+use serde::{Deserialize, Serialize};
+
+/// Limits for the event history kept as escalation context.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[cfg_attr(test, derive(schemars::JsonSchema))]
+#[serde(default)]
+pub struct HistoryConfig {
+    /// Events kept in memory; the oldest are evicted first.
+    pub max_events: usize,
+    /// Total serialized size of the events kept in memory.
+    pub max_bytes: usize,
+    /// Tool results longer than this are truncated before being kept.
+    pub max_result_bytes: usize,
+    /// Write evicted events to a temporary file ring for later readback.
+    pub spill: bool,
+    /// Files in the spill ring; the oldest is deleted when a new one starts.
+    pub spill_files: usize,
+    /// Size at which the spill ring starts a new file.
+    pub spill_file_bytes: u64,
+    /// Mirror the session to a Claude Code compatible transcript for
+    /// `export transcript`.
+    pub mirror: bool,
+    /// Write events of unknown types whole to a dead-letter JSONL file.
+    pub dead_letters: bool,
+}
+
+impl Default for HistoryConfig {
+    fn default() -> Self {
+        Self {
+            max_events: 50,
+            max_bytes: 1024 * 1024,
+            max_result_bytes: 16 * 1024,
+            spill: false,
+            spill_files: 4,
+            spill_file_bytes: 4 * 1024 * 1024,
+            mirror: false,
+            dead_letters: false,
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_history_config_deserialize() {
+        let toml_str = r"
+            max_events = 200
+            spill = true
+        ";
+        let config: HistoryConfig = toml::from_str(toml_str).unwrap();
+        assert_eq!(config.max_events, 200);
+        assert!(config.spill);
+        assert_eq!(config.max_bytes, 1024 * 1024);
+    }
+}
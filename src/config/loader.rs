@@ -1,20 +1,31 @@
 //! Configuration file loader.
 
-use std::collections::HashSet;
+use std::collections::{BTreeMap, HashSet};
 use std::path::PathBuf;
 
 use serde::{Deserialize, Serialize};
 
-use crate::supervisor::PolicyLevel;
+use crate::supervisor::{McpDefault, PolicyLevel, RuleCategory};
 
-use super::AiConfig;
+use super::schema::ConfigSchema;
+use super::{
+    check_schema, format_issues, AiConfig, AuditConfig, BlocklistEntry, BlocklistLevels,
+    ConfigIssue, ContainmentConfig, EditRuleConfig, EscrowConfig, GitConfig, McpServerPolicy,
+    NetworkConfig, NovelBinaryConfig, PathRuleAction, RateLimitConfig, RewriteRuleConfig,
+    SamplingConfig, SandboxConfig, SecretScanConfig, SnapshotConfig, SuggestionConfig,
+};
 
 /// Policy configuration loaded from TOML file.
 #[derive(Debug, Clone, Serialize, Deserialize)]
+#[cfg_attr(test, derive(schemars::JsonSchema))]
 #[serde(default)]
 pub struct PolicyConfig {
     /// Global policy level.
     pub level: PolicyLevel,
+    /// Record the calls the policy would deny or escalate and allow them.
+    ///
+    /// Only honoured from the global config file, like `[self_protection]`.
+    pub dry_run: bool,
     /// Auto-continue without user prompts.
     pub auto_continue: bool,
     /// AI provider configuration.
@@ -25,23 +36,105 @@ pub struct PolicyConfig {
     pub files: FilesPolicy,
     /// Tool-specific policies.
     pub tools: ToolsPolicy,
+    /// Rules for MCP tools keyed by server name.
+    pub mcp: BTreeMap<String, McpServerPolicy>,
+    /// Rules on what Edit and `MultiEdit` calls change.
+    pub edit_rules: Vec<EditRuleConfig>,
+    /// Rules rewriting Bash commands before they run.
+    pub rewrites: Vec<RewriteRuleConfig>,
+    /// Decisions for file writes keyed by path glob, matched against the
+    /// path relative to the session cwd and the absolute path.
+    pub paths: BTreeMap<String, PathRuleAction>,
+    /// Tool names mapped to the canonical names rules are written for, on
+    /// top of the built-in aliases.
+    pub tool_aliases: BTreeMap<String, String>,
+    /// Sliding-window limits on calls keyed by tool name; calls over a
+    /// limit are escalated.
+    pub rate_limits: BTreeMap<String, RateLimitConfig>,
+    /// Escalation sample rates keyed by tool or rule name; escalations left
+    /// out of the sample are allowed.
+    pub escalation_sampling: BTreeMap<String, SamplingConfig>,
+    /// Command rules added to the built-in blocklist.
+    pub blocklist: Vec<BlocklistEntry>,
+    /// Per-level actions for blocklist categories, overriding the built-in
+    /// ones.
+    pub blocklist_levels: BTreeMap<RuleCategory, BlocklistLevels>,
+    /// Protection of the supervisor's own files (global config only).
+    pub self_protection: SelfProtectionConfig,
+    /// Keeping writes inside the project (global config only).
+    pub containment: ContainmentConfig,
+    /// Sandbox wrapper for Bash commands.
+    pub sandbox: SandboxConfig,
+    /// Snapshots of files before approved writes.
+    pub snapshots: SnapshotConfig,
+    /// Moving deleted files to a trash directory instead of deleting them.
+    pub escrow: EscrowConfig,
+    /// Gating of git commits, pushes, tags and history rewrites.
+    pub git: GitConfig,
+    /// Gating of Bash commands that send data to other hosts.
+    pub network: NetworkConfig,
+    /// Scanning of written content and commands for credentials.
+    pub secrets: SecretScanConfig,
+    /// Escalation of binaries the project has not run before.
+    pub novel_binaries: NovelBinaryConfig,
+    /// Policy level overrides keyed by Claude Code permission mode.
+    pub by_permission_mode: BTreeMap<String, PolicyLevel>,
+    /// Pass supervisor guidance to Claude as the hook's `additionalContext`;
+    /// disable for Claude Code versions that do not read the field.
+    pub hook_additional_context: bool,
+    /// Directory for the audit log, state, snapshots and sockets (global
+    /// config only); see [`data_dir`](super::data_dir).
+    pub data_dir: Option<PathBuf>,
+    /// Kill switch file halting every session while it exists (global
+    /// config only); see [`kill_switch_path`](super::kill_switch_path).
+    pub kill_switch: Option<PathBuf>,
+    /// Sinks receiving every audit record (global config only).
+    pub audit: AuditConfig,
+    /// CLAUDE.md additions suggested after each session.
+    pub suggestions: SuggestionConfig,
 }
 
 impl Default for PolicyConfig {
     fn default() -> Self {
         Self {
             level: PolicyLevel::Permissive,
+            dry_run: false,
             auto_continue: false,
             ai: AiConfig::default(),
             bash: BashPolicy::default(),
             files: FilesPolicy::default(),
             tools: ToolsPolicy::default(),
+            mcp: BTreeMap::new(),
+            edit_rules: Vec::new(),
+            rewrites: Vec::new(),
+            paths: BTreeMap::new(),
+            tool_aliases: BTreeMap::new(),
+            rate_limits: BTreeMap::new(),
+            escalation_sampling: BTreeMap::new(),
+            blocklist: Vec::new(),
+            blocklist_levels: BTreeMap::new(),
+            self_protection: SelfProtectionConfig::default(),
+            containment: ContainmentConfig::default(),
+            sandbox: SandboxConfig::default(),
+            snapshots: SnapshotConfig::default(),
+            escrow: EscrowConfig::default(),
+            git: GitConfig::default(),
+            network: NetworkConfig::default(),
+            secrets: SecretScanConfig::default(),
+            novel_binaries: NovelBinaryConfig::default(),
+            by_permission_mode: BTreeMap::new(),
+            hook_additional_context: true,
+            data_dir: None,
+            kill_switch: None,
+            audit: AuditConfig::default(),
+            suggestions: SuggestionConfig::default(),
         }
     }
 }
 
 /// Bash command policy configuration.
 #[derive(Debug, Clone, Serialize, Deserialize)]
+#[cfg_attr(test, derive(schemars::JsonSchema))]
 #[serde(default)]
 pub struct BashPolicy {
     /// Block destructive commands (rm -rf, etc.).
@@ -50,8 +143,14 @@ pub struct BashPolicy {
     pub block_network_exfil: bool,
     /// Block privilege escalation (sudo, su).
     pub block_privilege_escalation: bool,
-    /// Additional blocked command patterns.
+    /// Additional blocked command patterns, matched as plain text anywhere
+    /// in the command.
     pub blocked_patterns: Vec<String>,
+    /// Additional blocked command regexes, such as `rm\s+-\w*r`.
+    pub blocked_regexes: Vec<String>,
+    /// Command prefixes allowed at every level, such as `cargo test`; when
+    /// set, other commands escalate, or are denied by the Strict level.
+    pub allowed_prefixes: Vec<String>,
 }
 
 impl Default for BashPolicy {
@@ -61,12 +160,15 @@ impl Default for BashPolicy {
             block_network_exfil: true,
             block_privilege_escalation: true,
             blocked_patterns: Vec::new(),
+            blocked_regexes: Vec::new(),
+            allowed_prefixes: Vec::new(),
         }
     }
 }
 
 /// File operation policy configuration.
 #[derive(Debug, Clone, Serialize, Deserialize)]
+#[cfg_attr(test, derive(schemars::JsonSchema))]
 #[serde(default)]
 pub struct FilesPolicy {
     /// Sensitive paths to block writes to.
@@ -97,14 +199,19 @@ impl Default for FilesPolicy {
 
 /// Tool-specific policy configuration.
 #[derive(Debug, Clone, Serialize, Deserialize)]
+#[cfg_attr(test, derive(schemars::JsonSchema))]
 #[serde(default)]
 pub struct ToolsPolicy {
-    /// Tools to always allow.
+    /// Tools to always allow, by name or glob such as `mcp__github__*`.
     pub allowed: HashSet<String>,
-    /// Tools to always deny.
+    /// Tools to always deny, by name or glob; the most specific match wins.
     pub denied: HashSet<String>,
     /// Tools that require escalation.
     pub escalate: HashSet<String>,
+    /// What Strict mode does with `mcp__*` tools that are not listed.
+    pub mcp_default: McpDefault,
+    /// Patterns matched against full MCP tool names to block.
+    pub blocked_mcp_patterns: Vec<String>,
 }
 
 impl Default for ToolsPolicy {
@@ -116,6 +223,32 @@ impl Default for ToolsPolicy {
                 .collect(),
             denied: HashSet::new(),
             escalate: HashSet::new(),
+            mcp_default: McpDefault::default(),
+            blocked_mcp_patterns: Vec::new(),
+        }
+    }
+}
+
+/// Self-protection configuration.
+///
+/// Only honoured from the global config file: a project-level
+/// `.claude-supervisor.toml` is writable by the supervised session, so its
+/// `[self_protection]` section is ignored.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[cfg_attr(test, derive(schemars::JsonSchema))]
+#[serde(default)]
+pub struct SelfProtectionConfig {
+    /// Deny modifications to the supervisor's own files.
+    pub enabled: bool,
+    /// Additional paths to protect.
+    pub extra_paths: Vec<PathBuf>,
+}
+
+impl Default for SelfProtectionConfig {
+    fn default() -> Self {
+        Self {
+            enabled: true,
+            extra_paths: Vec::new(),
         }
     }
 }
@@ -125,6 +258,8 @@ impl Default for ToolsPolicy {
 pub struct ConfigLoader {
     /// Search paths in order of priority.
     search_paths: Vec<PathBuf>,
+    /// The global config file, trusted for self-protection settings.
+    global_path: Option<PathBuf>,
 }
 
 impl ConfigLoader {
@@ -137,18 +272,24 @@ impl ConfigLoader {
         search_paths.push(PathBuf::from(".claude-supervisor.toml"));
 
         // 2. User config directory: ~/.config/claude-supervisor/config.toml
-        if let Some(config_dir) = dirs::config_dir() {
-            search_paths.push(config_dir.join("claude-supervisor").join("config.toml"));
-        }
+        let global_path =
+            dirs::config_dir().map(|dir| dir.join("claude-supervisor").join("config.toml"));
+        search_paths.extend(global_path.clone());
 
-        Self { search_paths }
+        Self {
+            search_paths,
+            global_path,
+        }
     }
 
     /// Create a config loader with a specific config file path.
+    ///
+    /// An explicitly chosen file is trusted like the global config.
     #[must_use]
     pub fn with_path(path: PathBuf) -> Self {
         Self {
-            search_paths: vec![path],
+            search_paths: vec![path.clone()],
+            global_path: Some(path),
         }
     }
 
@@ -161,7 +302,17 @@ impl ConfigLoader {
         for path in &self.search_paths {
             if path.exists() {
                 tracing::debug!(path = %path.display(), "Loading config file");
-                return Self::load_from_path(path);
+                let mut config = Self::load_from_path(path)?;
+                if self.global_path.as_ref() != Some(path) {
+                    let global = self.global_config()?;
+                    config.self_protection = global.self_protection;
+                    config.dry_run = global.dry_run;
+                    config.containment = global.containment;
+                    config.data_dir = global.data_dir;
+                    config.kill_switch = global.kill_switch;
+                    config.audit = global.audit;
+                }
+                return Ok(config);
             }
         }
 
@@ -169,16 +320,39 @@ impl ConfigLoader {
         Ok(PolicyConfig::default())
     }
 
+    /// Load the global config, whose self-protection, dry run, data
+    /// directory, kill switch and audit sink settings are the only ones
+    /// trusted.
+    fn global_config(&self) -> Result<PolicyConfig, ConfigError> {
+        match self.global_path {
+            Some(ref path) if path.exists() => Self::load_from_path(path),
+            _ => Ok(PolicyConfig::default()),
+        }
+    }
+
     /// Load configuration from a specific path.
+    ///
+    /// Values that do not deserialize are checked against the schema, so
+    /// that every one of them is reported.
     fn load_from_path(path: &PathBuf) -> Result<PolicyConfig, ConfigError> {
         let content = std::fs::read_to_string(path).map_err(|e| ConfigError::ReadError {
             path: path.clone(),
             source: e,
         })?;
 
-        toml::from_str(&content).map_err(|e| ConfigError::ParseError {
+        let table: toml::Table = toml::from_str(&content).map_err(|e| ConfigError::ParseError {
             path: path.clone(),
             source: e,
+        })?;
+        PolicyConfig::deserialize(table.clone()).map_err(|e| {
+            let mut issues = check_schema(&PolicyConfig::schema(), &table);
+            if issues.is_empty() {
+                issues.push(ConfigIssue::new("", e.message().trim_end()));
+            }
+            ConfigError::Invalid {
+                path: path.clone(),
+                issues,
+            }
         })
     }
 
@@ -215,11 +389,19 @@ pub enum ConfigError {
         path: PathBuf,
         source: toml::de::Error,
     },
+
+    #[error("Invalid config file {path}:\n{}", format_issues(.issues))]
+    Invalid {
+        path: PathBuf,
+        issues: Vec<ConfigIssue>,
+    },
 }
 
 #[cfg(test)]
 mod tests {
     use super::*;
+    use crate::config::ContainmentAction;
+    use crate::supervisor::{PolicyDecision, PolicyEngine, RuleCategory, SELF_PROTECTION_REASON};
 
     #[test]
     fn test_default_policy_config() {
@@ -244,15 +426,111 @@ mod tests {
         assert_eq!(config.level, PolicyLevel::Permissive);
     }
 
+    #[test]
+    fn test_project_config_cannot_disable_self_protection() {
+        let dir = tempfile::tempdir().unwrap();
+        let project = dir.path().join(".claude-supervisor.toml");
+        std::fs::write(
+            &project,
+            "level = \"strict\"\ndry_run = true\ndata_dir = \"audit-here\"\nkill_switch = \"/dev/null/KILL\"\n[self_protection]\nenabled = false\n[[audit.sinks]]\nkind = \"jsonl\"\n",
+        )
+        .unwrap();
+        let loader = ConfigLoader {
+            search_paths: vec![project],
+            global_path: Some(dir.path().join("missing-global.toml")),
+        };
+
+        let config = loader.load().unwrap();
+        assert_eq!(config.level, PolicyLevel::Strict);
+        assert!(config.self_protection.enabled);
+        assert!(!config.dry_run);
+        assert_eq!(config.data_dir, None);
+        assert_eq!(config.kill_switch, None);
+        assert_eq!(config.audit, AuditConfig::default());
+    }
+
+    #[test]
+    fn test_project_dry_run_does_not_lift_self_protection() {
+        let dir = tempfile::tempdir().unwrap();
+        let project = dir.path().join(".claude-supervisor.toml");
+        std::fs::write(&project, "dry_run = true\n").unwrap();
+        let loader = ConfigLoader {
+            search_paths: vec![project],
+            global_path: Some(dir.path().join("missing-global.toml")),
+        };
+
+        let config = loader.load().unwrap();
+        let mut engine = PolicyEngine::new(config.level);
+        engine.allow_tool("Write");
+        engine.set_dry_run(config.dry_run);
+        let decision = engine.evaluate_with_cwd(
+            "Write",
+            &serde_json::json!({ "file_path": ".claude-supervisor.toml" }),
+            Some(dir.path()),
+        );
+        assert!(
+            matches!(&decision, PolicyDecision::Deny(reason) if reason.starts_with(SELF_PROTECTION_REASON)),
+            "{decision:?}"
+        );
+        assert!(engine.dry_run_log().records().is_empty());
+    }
+
+    #[test]
+    fn test_global_config_controls_self_protection() {
+        let dir = tempfile::tempdir().unwrap();
+        let project = dir.path().join(".claude-supervisor.toml");
+        let global = dir.path().join("config.toml");
+        std::fs::write(&project, "level = \"strict\"\n").unwrap();
+        std::fs::write(
+            &global,
+            "data_dir = \"/srv/supervisor\"\n[self_protection]\nenabled = false\nextra_paths = [\"/opt/policy.toml\"]\n[containment]\naction = \"escalate\"\n",
+        )
+        .unwrap();
+        let loader = ConfigLoader {
+            search_paths: vec![project, global.clone()],
+            global_path: Some(global),
+        };
+
+        let config = loader.load().unwrap();
+        assert!(!config.self_protection.enabled);
+        assert_eq!(
+            config.self_protection.extra_paths,
+            vec![PathBuf::from("/opt/policy.toml")]
+        );
+        assert_eq!(config.data_dir, Some(PathBuf::from("/srv/supervisor")));
+        assert_eq!(config.containment.action, ContainmentAction::Escalate);
+    }
+
+    #[test]
+    fn test_unknown_blocklist_category_fails_the_load() {
+        let dir = tempfile::tempdir().unwrap();
+        let path = dir.path().join(".claude-supervisor.toml");
+        let entry = "[[blocklist]]\npattern = 'terraform\\s+destroy'\ncategory = \"destructive\"\n";
+        std::fs::write(&path, entry).unwrap();
+        let config = ConfigLoader::with_path(path.clone()).load().unwrap();
+        assert_eq!(config.blocklist[0].category, RuleCategory::Destructive);
+
+        std::fs::write(&path, entry.replace("destructive", "infra")).unwrap();
+        let error = ConfigLoader::with_path(path).load().unwrap_err();
+        assert!(matches!(error, ConfigError::Invalid { .. }));
+        assert!(
+            error.to_string().contains("blocklist[0].category: "),
+            "{error}"
+        );
+    }
+
     #[test]
     fn test_parse_toml_config() {
         let toml_str = r#"
             level = "strict"
+            dry_run = true
             auto_continue = true
 
             [bash]
             block_destructive = true
             block_network_exfil = false
+            allowed_prefixes = ["cargo test", "git status"]
+            blocked_regexes = ['git\s+push\s+.*--force']
 
             [files]
             allow_env_files = true
@@ -260,15 +538,35 @@ mod tests {
             [tools]
             allowed = ["Read", "Write"]
             denied = ["Bash"]
+            mcp_default = "deny"
+            blocked_mcp_patterns = ["^mcp__.*__delete_"]
+
+            [mcp.github]
+            default = "escalate"
+            denied = ["delete_repository"]
+
+            [by_permission_mode]
+            bypassPermissions = "strict"
         "#;
 
         let config: PolicyConfig = toml::from_str(toml_str).unwrap();
         assert_eq!(config.level, PolicyLevel::Strict);
+        assert!(config.dry_run);
         assert!(config.auto_continue);
         assert!(config.bash.block_destructive);
         assert!(!config.bash.block_network_exfil);
+        assert_eq!(config.bash.allowed_prefixes, ["cargo test", "git status"]);
+        assert_eq!(config.bash.blocked_regexes, [r"git\s+push\s+.*--force"]);
         assert!(config.files.allow_env_files);
         assert!(config.tools.allowed.contains("Read"));
         assert!(config.tools.denied.contains("Bash"));
+        assert_eq!(config.tools.mcp_default, McpDefault::Deny);
+        assert_eq!(config.tools.blocked_mcp_patterns, ["^mcp__.*__delete_"]);
+        assert_eq!(config.mcp["github"].default, Some(McpDefault::Escalate));
+        assert_eq!(config.mcp["github"].denied, ["delete_repository"]);
+        assert_eq!(
+            config.by_permission_mode["bypassPermissions"],
+            PolicyLevel::Strict
+        );
     }
 }
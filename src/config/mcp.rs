@@ -0,0 +1,48 @@
+//! Policy rules scoped to one MCP server.
+
+use serde::{Deserialize, Serialize};
+
+use crate::supervisor::McpDefault;
+
+/// Rules for the tools of one MCP server, keyed by server name under `[mcp]`.
+///
+/// ```toml
+/// [mcp.github]
+/// default = "escalate"
+/// allowed = ["get_issue", "list_issues"]
+/// denied = ["delete_repository"]
+/// ```
+///
+/// Tool names are given without the `mcp__<server>__` prefix.
+#[derive(Debug, Clone, Default, PartialEq, Eq, Serialize, Deserialize)]
+#[cfg_attr(test, derive(schemars::JsonSchema))]
+#[serde(default)]
+pub struct McpServerPolicy {
+    /// What to do with the server's tools that no rule decided, at every
+    /// policy level; unset falls back to the level.
+    pub default: Option<McpDefault>,
+    /// Tools of the server to always allow.
+    pub allowed: Vec<String>,
+    /// Tools of the server to always deny.
+    pub denied: Vec<String>,
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_mcp_server_policy_deserialize() {
+        let policy: McpServerPolicy = toml::from_str(
+            r#"
+            default = "deny"
+            allowed = ["get_issue"]
+            "#,
+        )
+        .unwrap();
+        assert_eq!(policy.default, Some(McpDefault::Deny));
+        assert_eq!(policy.allowed, ["get_issue"]);
+        assert!(policy.denied.is_empty());
+        assert_eq!(McpServerPolicy::default().default, None);
+    }
+}
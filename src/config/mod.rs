@@ -1,13 +1,72 @@
 //! Configuration module.
 
+mod audit;
+mod blast_radius;
+mod blocklist;
+mod budget;
 mod claude_settings;
+mod containment;
+mod dashboard;
+mod edit_rules;
+mod escalation;
+mod escrow;
+mod git;
+mod history;
 mod loader;
+mod mcp;
+mod network;
+mod novel_binaries;
+mod path_rules;
+mod paths;
+mod rate_limits;
+mod recovery;
+mod redaction;
+mod rewrites;
+mod risk;
+mod sampling;
+mod sandbox;
+pub mod schema;
+mod secrets;
+mod snapshot;
 mod stop;
+mod suggestions;
+mod timeouts;
+mod tool_errors;
 mod types;
+mod validate;
 mod worktree;
 
+pub use audit::*;
+pub use blast_radius::*;
+pub use blocklist::*;
+pub use budget::*;
 pub use claude_settings::*;
+pub use containment::*;
+pub use dashboard::*;
+pub use edit_rules::*;
+pub use escalation::*;
+pub use escrow::*;
+pub use git::*;
+pub use history::*;
 pub use loader::*;
+pub use mcp::*;
+pub use network::*;
+pub use novel_binaries::*;
+pub use path_rules::*;
+pub use paths::*;
+pub use rate_limits::*;
+pub use recovery::*;
+pub use redaction::*;
+pub use rewrites::*;
+pub use risk::*;
+pub use sampling::*;
+pub use sandbox::*;
+pub use secrets::*;
+pub use snapshot::*;
 pub use stop::*;
+pub use suggestions::*;
+pub use timeouts::*;
+pub use tool_errors::*;
 pub use types::*;
+pub use validate::*;
 pub use worktree::*;
@@ -0,0 +1,48 @@
+//! Network exfiltration guard configuration.
+
+use serde::{Deserialize, Serialize};
+
+/// Configuration for gating Bash commands that send data to other hosts.
+///
+/// Uploads with `curl` or `wget`, `nc` connections and `scp` or `rsync`
+/// copies to a host other than this machine are escalated, or denied at the
+/// strict level, unless the host is allowed.
+///
+/// ```toml
+/// [network]
+/// allowed_hosts = ["github.com", "crates.io"]
+/// ```
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+#[cfg_attr(test, derive(schemars::JsonSchema))]
+#[serde(default)]
+pub struct NetworkConfig {
+    /// Whether Bash commands are checked for uploads to other hosts.
+    pub enabled: bool,
+    /// Hosts data may be sent to, along with their subdomains.
+    pub allowed_hosts: Vec<String>,
+}
+
+impl Default for NetworkConfig {
+    fn default() -> Self {
+        Self {
+            enabled: true,
+            allowed_hosts: Vec::new(),
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_network_config_deserialize() {
+        let config: NetworkConfig =
+            toml::from_str(r#"allowed_hosts = ["github.com", "crates.io"]"#).unwrap();
+        assert!(config.enabled);
+        assert_eq!(config.allowed_hosts, ["github.com", "crates.io"]);
+
+        let defaults: NetworkConfig = toml::from_str("").unwrap();
+        assert_eq!(defaults, NetworkConfig::default());
+    }
+}
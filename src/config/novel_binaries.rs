@@ -0,0 +1,50 @@
+//! Detection of binaries a project has not run before.
+
+use serde::{Deserialize, Serialize};
+
+/// What happens when a Bash command runs a binary new to the project.
+#[derive(Debug, Clone, Copy, Serialize, Deserialize, Default, PartialEq, Eq)]
+#[cfg_attr(test, derive(schemars::JsonSchema))]
+#[serde(rename_all = "lowercase")]
+pub enum NovelBinaryMode {
+    /// Do not look for new binaries.
+    Off,
+    /// Log the first use, but leave the decision to the policy.
+    Log,
+    /// Escalate the first use, even under the permissive policy.
+    #[default]
+    Escalate,
+}
+
+/// Configuration for flagging the first use of a binary in a project.
+///
+/// A binary is known if earlier sessions in the project ran it, it is on the
+/// supervisor's known-safe list, or it is listed in `allowed`.
+#[derive(Debug, Clone, Default, PartialEq, Eq, Serialize, Deserialize)]
+#[cfg_attr(test, derive(schemars::JsonSchema))]
+#[serde(default)]
+pub struct NovelBinaryConfig {
+    /// What to do on the first use of an unknown binary.
+    pub mode: NovelBinaryMode,
+    /// Binaries treated as known in every project.
+    pub allowed: Vec<String>,
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_novel_binary_config_deserialize() {
+        let config: NovelBinaryConfig = toml::from_str(
+            r#"
+            mode = "log"
+            allowed = ["terraform"]
+            "#,
+        )
+        .unwrap();
+        assert_eq!(config.mode, NovelBinaryMode::Log);
+        assert_eq!(config.allowed, ["terraform"]);
+        assert_eq!(NovelBinaryConfig::default().mode, NovelBinaryMode::Escalate);
+    }
+}
@@ -0,0 +1,41 @@
+//! Path-scoped policy rule configuration.
+
+use serde::{Deserialize, Serialize};
+
+/// What happens to a file write whose path a rule matches.
+///
+/// Variants are ordered by strictness; when several rules match, the
+/// strictest decides.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, PartialOrd, Ord, Serialize, Deserialize)]
+#[cfg_attr(test, derive(schemars::JsonSchema))]
+#[serde(rename_all = "lowercase")]
+pub enum PathRuleAction {
+    /// Allow the call.
+    Allow,
+    /// Ask the supervisor.
+    Escalate,
+    /// Deny the call.
+    Deny,
+}
+
+#[cfg(test)]
+mod tests {
+    use std::collections::BTreeMap;
+
+    use super::*;
+
+    #[test]
+    fn test_path_rules_deserialize() {
+        let rules: BTreeMap<String, PathRuleAction> = toml::from_str(
+            r#"
+            "src/**" = "allow"
+            "Cargo.lock" = "deny"
+            ".github/**" = "escalate"
+            "#,
+        )
+        .unwrap();
+        assert_eq!(rules["src/**"], PathRuleAction::Allow);
+        assert_eq!(rules["Cargo.lock"], PathRuleAction::Deny);
+        assert!(PathRuleAction::Deny > PathRuleAction::Escalate);
+    }
+}
@@ -0,0 +1,191 @@
+//! Where the supervisor keeps its data.
+//!
+//! Everything the supervisor writes outside a repository lives under one
+//! data directory: [`DATA_DIR_ENV`] if set, else the `data_dir` of the
+//! global configuration (see [`set_data_dir`]), else the XDG data directory
+//! (`~/.local/share/claude-supervisor` on Unix systems).
+//!
+//! ```text
+//! <data_dir>/audit.db          audit log
+//! <data_dir>/detached/         pidfiles and logs of detached sessions
+//! <data_dir>/state/resume/     supervisor contexts of resumable sessions
+//! <data_dir>/state/runs/       run manifests, one directory per session
+//! <data_dir>/snapshots/        file snapshots taken before writes
+//! <data_dir>/sockets/          IPC sockets
+//! <data_dir>/KILL              kill switch; halts every session while present
+//! ```
+
+use std::path::{Path, PathBuf};
+use std::sync::OnceLock;
+
+/// Environment variable overriding the data directory.
+pub const DATA_DIR_ENV: &str = "CLAUDE_SUPERVISOR_DATA_DIR";
+
+/// File name of the audit database.
+pub const AUDIT_DB_FILE: &str = "audit.db";
+
+/// File name of the kill switch.
+pub const KILL_SWITCH_FILE: &str = "KILL";
+
+/// Data directory chosen by configuration, set once at startup.
+static CONFIGURED_DATA_DIR: OnceLock<PathBuf> = OnceLock::new();
+
+/// Kill switch file chosen by configuration, set once at startup.
+static CONFIGURED_KILL_SWITCH: OnceLock<PathBuf> = OnceLock::new();
+
+/// Use `dir` as the data directory unless [`DATA_DIR_ENV`] is set.
+///
+/// Only the first call has an effect.
+pub fn set_data_dir(dir: impl Into<PathBuf>) {
+    let _ = CONFIGURED_DATA_DIR.set(dir.into());
+}
+
+/// The data directory default when neither the environment nor the
+/// configuration name one.
+#[must_use]
+pub fn default_data_dir() -> PathBuf {
+    dirs::data_local_dir()
+        .unwrap_or_else(|| PathBuf::from("."))
+        .join("claude-supervisor")
+}
+
+/// The directory everything else in this module lives under.
+#[must_use]
+pub fn data_dir() -> PathBuf {
+    std::env::var_os(DATA_DIR_ENV)
+        .filter(|dir| !dir.is_empty())
+        .map(PathBuf::from)
+        .or_else(|| CONFIGURED_DATA_DIR.get().cloned())
+        .unwrap_or_else(default_data_dir)
+}
+
+/// Path of the audit database.
+#[must_use]
+pub fn audit_db_path() -> PathBuf {
+    data_dir().join(AUDIT_DB_FILE)
+}
+
+/// Directory for state kept between runs, such as resume contexts.
+#[must_use]
+pub fn state_dir() -> PathBuf {
+    data_dir().join("state")
+}
+
+/// Default directory for file snapshots.
+#[must_use]
+pub fn snapshots_dir() -> PathBuf {
+    data_dir().join("snapshots")
+}
+
+/// Default directory for the artifact bundles of finished runs.
+#[must_use]
+pub fn artifacts_dir() -> PathBuf {
+    data_dir().join("artifacts")
+}
+
+/// Directory for pidfiles and logs of sessions run with `run --detach`.
+#[must_use]
+pub fn detached_dir() -> PathBuf {
+    data_dir().join("detached")
+}
+
+/// Directory for IPC sockets.
+#[must_use]
+pub fn sockets_dir() -> PathBuf {
+    data_dir().join("sockets")
+}
+
+/// Use `path` as the kill switch file instead of [`KILL_SWITCH_FILE`] in
+/// the data directory.
+///
+/// Only the first call has an effect.
+pub fn set_kill_switch_path(path: impl Into<PathBuf>) {
+    let _ = CONFIGURED_KILL_SWITCH.set(path.into());
+}
+
+/// Path of the kill switch file.
+#[must_use]
+pub fn kill_switch_path() -> PathBuf {
+    CONFIGURED_KILL_SWITCH
+        .get()
+        .cloned()
+        .unwrap_or_else(|| data_dir().join(KILL_SWITCH_FILE))
+}
+
+/// Move an audit database from `legacy_dir`, the old default location
+/// ([`default_data_dir`]), into `data_dir`.
+///
+/// Does nothing when the locations coincide, nothing is at the old
+/// location, or a database already exists at the new one. Returns the new
+/// path when a database was moved.
+///
+/// # Errors
+///
+/// Returns an error if the database cannot be moved.
+pub fn migrate_audit_db(legacy_dir: &Path, data_dir: &Path) -> std::io::Result<Option<PathBuf>> {
+    let legacy = legacy_dir.join(AUDIT_DB_FILE);
+    let target = data_dir.join(AUDIT_DB_FILE);
+    if legacy == target || !legacy.exists() || target.exists() {
+        return Ok(None);
+    }
+
+    std::fs::create_dir_all(data_dir)?;
+    // SQLite keeps uncheckpointed writes next to the database
+    for suffix in ["", "-wal", "-shm"] {
+        let from = PathBuf::from(format!("{}{suffix}", legacy.display()));
+        if from.exists() {
+            let to = PathBuf::from(format!("{}{suffix}", target.display()));
+            move_file(&from, &to)?;
+        }
+    }
+    Ok(Some(target))
+}
+
+/// Rename `from` to `to`, copying across filesystems.
+fn move_file(from: &Path, to: &Path) -> std::io::Result<()> {
+    if std::fs::rename(from, to).is_ok() {
+        return Ok(());
+    }
+    std::fs::copy(from, to)?;
+    std::fs::remove_file(from)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_migrate_audit_db_moves_legacy_db_once() {
+        let legacy = tempfile::tempdir().unwrap();
+        let data = tempfile::tempdir().unwrap();
+        let target = data.path().join("nested");
+        std::fs::write(legacy.path().join(AUDIT_DB_FILE), b"sqlite").unwrap();
+        std::fs::write(legacy.path().join("audit.db-wal"), b"wal").unwrap();
+
+        let moved = migrate_audit_db(legacy.path(), &target).unwrap();
+        assert_eq!(moved, Some(target.join(AUDIT_DB_FILE)));
+        assert_eq!(
+            std::fs::read(target.join(AUDIT_DB_FILE)).unwrap(),
+            b"sqlite"
+        );
+        assert!(target.join("audit.db-wal").exists());
+        assert!(!legacy.path().join(AUDIT_DB_FILE).exists());
+
+        assert_eq!(migrate_audit_db(legacy.path(), &target).unwrap(), None);
+    }
+
+    #[test]
+    fn test_migrate_audit_db_keeps_existing_db() {
+        let legacy = tempfile::tempdir().unwrap();
+        let data = tempfile::tempdir().unwrap();
+        std::fs::write(legacy.path().join(AUDIT_DB_FILE), b"old").unwrap();
+        std::fs::write(data.path().join(AUDIT_DB_FILE), b"new").unwrap();
+
+        assert_eq!(migrate_audit_db(legacy.path(), data.path()).unwrap(), None);
+        assert_eq!(
+            std::fs::read(data.path().join(AUDIT_DB_FILE)).unwrap(),
+            b"new"
+        );
+        assert_eq!(migrate_audit_db(data.path(), data.path()).unwrap(), None);
+    }
+}
@@ -0,0 +1,45 @@
+//! Tool call rate limit configuration.
+
+use serde::{Deserialize, Serialize};
+
+/// How often calls to one tool may be made before they are escalated.
+///
+/// ```toml
+/// [rate_limits]
+/// Bash = { max = 30, per_seconds = 60 }
+/// ```
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+#[cfg_attr(test, derive(schemars::JsonSchema))]
+pub struct RateLimitConfig {
+    /// Calls allowed within the window.
+    pub max: u32,
+    /// Length of the sliding window in seconds.
+    pub per_seconds: u64,
+}
+
+#[cfg(test)]
+mod tests {
+    use std::collections::BTreeMap;
+
+    use super::*;
+
+    #[test]
+    fn test_rate_limits_deserialize() {
+        let limits: BTreeMap<String, RateLimitConfig> = toml::from_str(
+            r"
+            Bash = { max = 30, per_seconds = 60 }
+            mcp__github__create_issue = { max = 5, per_seconds = 3600 }
+            ",
+        )
+        .unwrap();
+        assert_eq!(
+            limits["Bash"],
+            RateLimitConfig {
+                max: 30,
+                per_seconds: 60
+            }
+        );
+        assert_eq!(limits["mcp__github__create_issue"].per_seconds, 3600);
+        assert!(toml::from_str::<RateLimitConfig>("max = 3").is_err());
+    }
+}
@@ -0,0 +1,107 @@
+//! Recovery of sessions that exhaust Claude's context window or go idle.
+
+use serde::{Deserialize, Serialize};
+
+/// How a session that ran out of context is continued.
+#[derive(Debug, Clone, Copy, Serialize, Deserialize, Default, PartialEq, Eq)]
+#[cfg_attr(test, derive(schemars::JsonSchema))]
+#[serde(rename_all = "lowercase")]
+pub enum ContextRecoveryMode {
+    /// Report the session as exhausted and stop.
+    #[default]
+    Off,
+    /// Resume the Claude session, which compacts its context.
+    Resume,
+    /// Start a fresh session seeded with a summary of the progress so far.
+    Summary,
+}
+
+/// Configuration for recovering sessions that exhaust their context.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[cfg_attr(test, derive(schemars::JsonSchema))]
+pub struct ContextRecoveryConfig {
+    /// How to continue the session.
+    #[serde(default)]
+    pub mode: ContextRecoveryMode,
+    /// Most recovery attempts per run.
+    #[serde(default = "default_max_attempts")]
+    pub max_attempts: u32,
+}
+
+fn default_max_attempts() -> u32 {
+    1
+}
+
+impl Default for ContextRecoveryConfig {
+    fn default() -> Self {
+        Self {
+            mode: ContextRecoveryMode::default(),
+            max_attempts: default_max_attempts(),
+        }
+    }
+}
+
+/// Configuration for nudging sessions that stop producing events.
+///
+/// ```toml
+/// [idle_nudge]
+/// after_secs = 300
+/// max_nudges = 2
+/// ```
+#[derive(Debug, Clone, Serialize, Deserialize, PartialEq, Eq)]
+#[cfg_attr(test, derive(schemars::JsonSchema))]
+pub struct IdleNudgeConfig {
+    /// Seconds without events, while no tool call runs, before the session
+    /// is nudged; unset disables nudging.
+    #[serde(default)]
+    pub after_secs: Option<u64>,
+    /// Most nudges per run.
+    #[serde(default = "default_max_nudges")]
+    pub max_nudges: u32,
+    /// Prompt the idle session is resumed with.
+    #[serde(default = "default_nudge_message")]
+    pub message: String,
+}
+
+fn default_max_nudges() -> u32 {
+    2
+}
+
+fn default_nudge_message() -> String {
+    crate::supervisor::DEFAULT_NUDGE_MESSAGE.to_string()
+}
+
+impl Default for IdleNudgeConfig {
+    fn default() -> Self {
+        Self {
+            after_secs: None,
+            max_nudges: default_max_nudges(),
+            message: default_nudge_message(),
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_context_recovery_config_deserialize() {
+        let config: ContextRecoveryConfig = toml::from_str(r#"mode = "summary""#).unwrap();
+        assert_eq!(config.mode, ContextRecoveryMode::Summary);
+        assert_eq!(config.max_attempts, 1);
+        assert_eq!(
+            ContextRecoveryConfig::default().mode,
+            ContextRecoveryMode::Off
+        );
+    }
+
+    #[test]
+    fn test_idle_nudge_config_deserialize() {
+        let config: IdleNudgeConfig = toml::from_str("after_secs = 120").unwrap();
+        assert_eq!(config.after_secs, Some(120));
+        assert_eq!(config.max_nudges, 2);
+        assert_eq!(config.message, crate::supervisor::DEFAULT_NUDGE_MESSAGE);
+        assert_eq!(IdleNudgeConfig::default().after_secs, None);
+    }
+}
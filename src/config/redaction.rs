@@ -0,0 +1,115 @@
+//! Secret redaction configuration.
+
+use serde::{Deserialize, Serialize};
+
+/// A user-defined redaction pattern.
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+#[cfg_attr(test, derive(schemars::JsonSchema))]
+pub struct RedactionPattern {
+    /// Label used in the replacement marker, e.g. `[REDACTED:<kind>]`.
+    pub kind: String,
+    /// Regex to match. If it has a `secret` capture group, only that group is replaced.
+    pub pattern: String,
+}
+
+/// Configuration for redacting secrets before they leave the supervisor.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[cfg_attr(test, derive(schemars::JsonSchema))]
+pub struct RedactionConfig {
+    /// Whether redaction is applied to AI supervisor context.
+    #[serde(default = "default_enabled")]
+    pub enabled: bool,
+
+    /// Additional patterns on top of the built-in secret shapes.
+    #[serde(default)]
+    pub patterns: Vec<RedactionPattern>,
+
+    /// Environment variables whose values are redacted wherever they appear literally.
+    #[serde(default = "default_env_denylist")]
+    pub env_denylist: Vec<String>,
+
+    /// Whether tool inputs stored in the audit log are redacted too.
+    #[serde(default = "default_enabled")]
+    pub redact_audit: bool,
+
+    /// Keep secrets in dashboard events and audit tool inputs, for a
+    /// supervisor whose data stays on this machine. Dashboard events are
+    /// still redacted while the dashboard listens beyond loopback.
+    #[serde(default)]
+    pub full_fidelity: bool,
+}
+
+fn default_enabled() -> bool {
+    true
+}
+
+fn default_env_denylist() -> Vec<String> {
+    [
+        "ANTHROPIC_API_KEY",
+        "GEMINI_API_KEY",
+        "OPENAI_API_KEY",
+        "AWS_SECRET_ACCESS_KEY",
+        "AWS_SESSION_TOKEN",
+        "GITHUB_TOKEN",
+        "GH_TOKEN",
+        "NPM_TOKEN",
+        "DATABASE_URL",
+    ]
+    .into_iter()
+    .map(String::from)
+    .collect()
+}
+
+impl Default for RedactionConfig {
+    fn default() -> Self {
+        Self {
+            enabled: default_enabled(),
+            patterns: Vec::new(),
+            env_denylist: default_env_denylist(),
+            redact_audit: default_enabled(),
+            full_fidelity: false,
+        }
+    }
+}
+
+impl RedactionConfig {
+    /// Whether tool inputs stored in the audit log are redacted.
+    #[must_use]
+    pub fn redacts_audit(&self) -> bool {
+        self.redact_audit && !self.full_fidelity
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_redaction_config_default() {
+        let config = RedactionConfig::default();
+        assert!(config.enabled);
+        assert!(config.patterns.is_empty());
+        assert!(config.env_denylist.contains(&"GITHUB_TOKEN".to_string()));
+        assert!(config.redact_audit);
+        assert!(config.redacts_audit());
+        assert!(!config.full_fidelity);
+    }
+
+    #[test]
+    fn test_redaction_config_deserialize_custom() {
+        let toml_str = r#"
+            full_fidelity = true
+            env_denylist = ["MY_TOKEN"]
+
+            [[patterns]]
+            kind = "internal_id"
+            pattern = "ACME-[0-9]{6}"
+        "#;
+        let config: RedactionConfig = toml::from_str(toml_str).unwrap();
+        assert!(config.enabled);
+        assert!(config.redact_audit);
+        assert!(!config.redacts_audit());
+        assert_eq!(config.env_denylist, vec!["MY_TOKEN".to_string()]);
+        assert_eq!(config.patterns[0].kind, "internal_id");
+    }
+}
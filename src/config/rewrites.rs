@@ -0,0 +1,60 @@
+//! Bash command rewrite rule configuration.
+
+use serde::{Deserialize, Serialize};
+
+/// A rule rewriting the Bash commands that run a program.
+///
+/// ```toml
+/// [[rewrites]]
+/// name = "plan before applying"
+/// program = "terraform"
+/// subcommand = "apply"
+/// add_args = ["--dry-run"]
+///
+/// [[rewrites]]
+/// name = "keep commit hooks"
+/// program = "git"
+/// subcommand = "commit"
+/// remove_args = ["--no-verify", "-n"]
+/// ```
+///
+/// The rewritten call is decided by the policy in place of the original and
+/// allowed with its input modified.
+#[derive(Debug, Clone, Default, PartialEq, Eq, Serialize, Deserialize)]
+#[cfg_attr(test, derive(schemars::JsonSchema))]
+#[serde(default)]
+pub struct RewriteRuleConfig {
+    /// Name of the rule in logs and rule firings.
+    pub name: String,
+    /// Program the rule applies to, by file name, such as `rm`.
+    pub program: String,
+    /// First argument that is not an option the command must have, such as
+    /// `apply`.
+    pub subcommand: Option<String>,
+    /// Program run instead, such as `trash-put`.
+    pub replace_program: Option<String>,
+    /// Arguments added after the program and subcommand unless present.
+    pub add_args: Vec<String>,
+    /// Arguments removed before any `--`.
+    pub remove_args: Vec<String>,
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_rewrite_rule_config_deserialize() {
+        let rule: RewriteRuleConfig = toml::from_str(
+            r#"
+            name = "trash instead of rm"
+            program = "rm"
+            replace_program = "trash-put"
+            "#,
+        )
+        .unwrap();
+        assert_eq!(rule.replace_program.as_deref(), Some("trash-put"));
+        assert_eq!(rule.subcommand, None);
+        assert!(rule.add_args.is_empty() && rule.remove_args.is_empty());
+    }
+}
@@ -0,0 +1,142 @@
+//! Cumulative risk score configuration.
+
+use serde::{Deserialize, Serialize};
+
+/// Risk added per tool call, by category.
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+#[cfg_attr(test, derive(schemars::JsonSchema))]
+pub struct RiskWeights {
+    /// Bash command running a network client such as `curl` or `ssh`.
+    #[serde(default = "default_network_weight")]
+    pub network: u32,
+    /// Any other Bash command.
+    #[serde(default = "default_low_weight")]
+    pub shell: u32,
+    /// File write or edit outside the source directories.
+    #[serde(default = "default_write_outside_source_weight")]
+    pub write_outside_source: u32,
+    /// File write or edit inside the source directories.
+    #[serde(default = "default_low_weight")]
+    pub write: u32,
+    /// MCP tool call.
+    #[serde(default = "default_low_weight")]
+    pub mcp: u32,
+    /// Read-only tool call, such as `Read` or `Grep`.
+    #[serde(default)]
+    pub read: u32,
+    /// Call to any other tool.
+    #[serde(default = "default_low_weight")]
+    pub other: u32,
+}
+
+fn default_network_weight() -> u32 {
+    3
+}
+
+fn default_write_outside_source_weight() -> u32 {
+    2
+}
+
+fn default_low_weight() -> u32 {
+    1
+}
+
+impl Default for RiskWeights {
+    fn default() -> Self {
+        Self {
+            network: default_network_weight(),
+            shell: default_low_weight(),
+            write_outside_source: default_write_outside_source_weight(),
+            write: default_low_weight(),
+            mcp: default_low_weight(),
+            read: 0,
+            other: default_low_weight(),
+        }
+    }
+}
+
+/// Escalation on the summed risk of a session's recent tool calls.
+///
+/// Each call adds the weight of its category to the score of the last
+/// `window_secs`; the call that takes the score over `threshold` is
+/// escalated even when the policy allows it.
+///
+/// ```toml
+/// [risk]
+/// enabled = true
+/// threshold = 20
+/// weights = { network = 5 }
+/// ```
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+#[cfg_attr(test, derive(schemars::JsonSchema))]
+pub struct RiskConfig {
+    /// Whether the score escalates calls at all.
+    #[serde(default)]
+    pub enabled: bool,
+    /// Length of the rolling window in seconds.
+    #[serde(default = "default_window_secs")]
+    pub window_secs: u64,
+    /// Score above which the session is escalated.
+    #[serde(default = "default_threshold")]
+    pub threshold: u32,
+    /// Directories, relative to the working directory, whose files count
+    /// as source.
+    #[serde(default = "default_source_dirs")]
+    pub source_dirs: Vec<String>,
+    /// Risk per category of tool call.
+    #[serde(default)]
+    pub weights: RiskWeights,
+}
+
+fn default_window_secs() -> u64 {
+    300
+}
+
+fn default_threshold() -> u32 {
+    20
+}
+
+fn default_source_dirs() -> Vec<String> {
+    vec!["src".to_string()]
+}
+
+impl Default for RiskConfig {
+    fn default() -> Self {
+        Self {
+            enabled: false,
+            window_secs: default_window_secs(),
+            threshold: default_threshold(),
+            source_dirs: default_source_dirs(),
+            weights: RiskWeights::default(),
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_risk_config_deserialize() {
+        let config: RiskConfig = toml::from_str(
+            r#"
+            enabled = true
+            threshold = 12
+            source_dirs = ["src", "tests"]
+            weights = { network = 5, read = 1 }
+            "#,
+        )
+        .unwrap();
+        assert!(config.enabled);
+        assert_eq!(config.threshold, 12);
+        assert_eq!(config.window_secs, 300);
+        assert_eq!(config.source_dirs, ["src", "tests"]);
+        assert_eq!(config.weights.network, 5);
+        assert_eq!(config.weights.read, 1);
+        assert_eq!(config.weights.write_outside_source, 2);
+
+        let defaults: RiskConfig = toml::from_str("").unwrap();
+        assert_eq!(defaults, RiskConfig::default());
+        assert!(!defaults.enabled);
+    }
+}
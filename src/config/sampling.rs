@@ -0,0 +1,38 @@
+//! Escalation sampling configuration.
+
+use serde::{Deserialize, Serialize};
+
+/// Share of a tool's or rule's escalations that go to the supervisor.
+///
+/// ```toml
+/// [escalation_sampling]
+/// WebSearch = { escalate_sample_rate = 0.1 }
+/// ```
+#[derive(Debug, Clone, Copy, PartialEq, Serialize, Deserialize)]
+#[cfg_attr(test, derive(schemars::JsonSchema))]
+pub struct SamplingConfig {
+    /// Share of matching escalations sent to the supervisor, from 0 to 1;
+    /// the rest are allowed.
+    pub escalate_sample_rate: f64,
+}
+
+#[cfg(test)]
+mod tests {
+    use std::collections::BTreeMap;
+
+    use super::*;
+
+    #[test]
+    fn test_escalation_sampling_deserialize() {
+        let sampling: BTreeMap<String, SamplingConfig> = toml::from_str(
+            r#"
+            WebSearch = { escalate_sample_rate = 0.1 }
+            "moderate level" = { escalate_sample_rate = 0.5 }
+            "#,
+        )
+        .unwrap();
+        assert!((sampling["WebSearch"].escalate_sample_rate - 0.1).abs() < f64::EPSILON);
+        assert!((sampling["moderate level"].escalate_sample_rate - 0.5).abs() < f64::EPSILON);
+        assert!(toml::from_str::<SamplingConfig>("rate = 0.1").is_err());
+    }
+}
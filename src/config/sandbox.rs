@@ -0,0 +1,53 @@
+//! Sandbox wrapper configuration for Bash commands.
+
+use serde::{Deserialize, Serialize};
+
+/// Configuration for running approved Bash commands inside a sandbox.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[cfg_attr(test, derive(schemars::JsonSchema))]
+#[serde(default)]
+pub struct SandboxConfig {
+    /// Wrapper binary (`bwrap`, `firejail` or a path). Sandboxing is off when unset.
+    pub wrapper: Option<String>,
+    /// Wrapper arguments placed before `sh -c '<command>'`; `{cwd}` is replaced
+    /// with the session working directory. Defaults depend on the wrapper.
+    pub args: Option<Vec<String>>,
+    /// Also sandbox commands whose binary is not on the known-safe list.
+    pub isolate_unknown: bool,
+}
+
+impl Default for SandboxConfig {
+    fn default() -> Self {
+        Self {
+            wrapper: None,
+            args: None,
+            isolate_unknown: true,
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_sandbox_config_default_disabled() {
+        let config = SandboxConfig::default();
+        assert!(config.wrapper.is_none());
+        assert!(config.args.is_none());
+        assert!(config.isolate_unknown);
+    }
+
+    #[test]
+    fn test_sandbox_config_deserialize() {
+        let toml_str = r#"
+            wrapper = "bwrap"
+            args = ["--ro-bind", "/", "/", "--bind", "{cwd}", "{cwd}", "--"]
+            isolate_unknown = false
+        "#;
+        let config: SandboxConfig = toml::from_str(toml_str).unwrap();
+        assert_eq!(config.wrapper.as_deref(), Some("bwrap"));
+        assert_eq!(config.args.unwrap()[4], "{cwd}");
+        assert!(!config.isolate_unknown);
+    }
+}
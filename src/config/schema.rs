@@ -0,0 +1,1936 @@
+//! Configuration schema for documentation and editor validation.
+//!
+//! Each config type describes its fields through [`ConfigSchema`]. Defaults
+//! are never written down here: they are taken from the type's `Default`
+//! impl when the schema is rendered, and the tests check the fields against
+//! a schema `schemars` derives from the structs, including tables in lists
+//! and maps, so the generated docs cannot drift from them.
+
+use std::fmt::Write as _;
+
+use serde::Serialize;
+use serde_json::{json, Map, Value};
+
+use super::{
+    AiConfig, AuditConfig, AuditSinkConfig, BashPolicy, BlastRadiusConfig, BlocklistEntry,
+    BlocklistLevels, BudgetConfig, ContainmentConfig, ContextRecoveryConfig, DashboardEventConfig,
+    EditRuleConfig, EscalationConfig, EscrowConfig, FilesPolicy, GitConfig, HistoryConfig,
+    IdleNudgeConfig, InteractiveConfig, McpServerPolicy, MutationWeights, NetworkConfig,
+    NovelBinaryConfig, PolicyConfig, RateLimitConfig, RedactionConfig, RedactionPattern,
+    RewriteRuleConfig, RiskConfig, RiskWeights, SamplingConfig, SandboxConfig, SecretScanConfig,
+    SelfProtectionConfig, SnapshotConfig, StopConfig, SuggestionConfig, SupervisorConfig,
+    ToolErrorConfig, ToolTimeoutConfig, ToolsPolicy, WebhookConfig, WorktreeConfig,
+};
+
+/// JSON Schema dialect of the generated schema.
+const JSON_SCHEMA_DIALECT: &str = "http://json-schema.org/draft-07/schema#";
+
+/// Type of a config field.
+#[derive(Debug, Clone)]
+pub enum FieldType {
+    /// `true` or `false`.
+    Boolean,
+    /// Non-negative integer.
+    Integer,
+    /// Non-negative number.
+    Number,
+    /// Free-form string.
+    String,
+    /// Filesystem path.
+    Path,
+    /// One of a fixed set of strings.
+    Enum(&'static [&'static str]),
+    /// Ordered list of values.
+    List(Box<FieldType>),
+    /// Unordered set of values.
+    Set(Box<FieldType>),
+    /// Table with free-form keys.
+    Map(Box<FieldType>),
+    /// Value that may be left unset.
+    Optional(Box<FieldType>),
+    /// Nested table.
+    Table(Schema),
+}
+
+impl FieldType {
+    /// List of `item` values.
+    #[must_use]
+    pub fn list(item: Self) -> Self {
+        Self::List(Box::new(item))
+    }
+
+    /// Set of `item` values.
+    #[must_use]
+    pub fn set(item: Self) -> Self {
+        Self::Set(Box::new(item))
+    }
+
+    /// Table mapping keys to `value`.
+    #[must_use]
+    pub fn map(value: Self) -> Self {
+        Self::Map(Box::new(value))
+    }
+
+    /// Optional `inner` value.
+    #[must_use]
+    pub fn optional(inner: Self) -> Self {
+        Self::Optional(Box::new(inner))
+    }
+
+    /// Nested table described by `T`.
+    #[must_use]
+    pub fn table<T: ConfigSchema>() -> Self {
+        Self::Table(T::schema())
+    }
+
+    /// Short type name for Markdown output.
+    fn name(&self) -> String {
+        match self {
+            Self::Boolean => "boolean".to_string(),
+            Self::Integer => "integer".to_string(),
+            Self::Number => "number".to_string(),
+            Self::String => "string".to_string(),
+            Self::Path => "path".to_string(),
+            Self::Enum(values) => values
+                .iter()
+                .map(|value| format!("`\"{value}\"`"))
+                .collect::<Vec<_>>()
+                .join(" \\| "),
+            Self::List(item) => format!("list of {}", item.name()),
+            Self::Set(item) => format!("set of {}", item.name()),
+            Self::Map(value) => format!("table of {}", value.name()),
+            Self::Optional(inner) => format!("{} (optional)", inner.name()),
+            Self::Table(_) => "table".to_string(),
+        }
+    }
+
+    /// JSON Schema for this type, with `default` as the field's default value.
+    fn json_schema(&self, default: &Value) -> Value {
+        match self {
+            Self::Boolean => json!({ "type": "boolean" }),
+            Self::Integer => json!({ "type": "integer", "minimum": 0 }),
+            Self::Number => json!({ "type": "number", "minimum": 0 }),
+            Self::String | Self::Path => json!({ "type": "string" }),
+            Self::Enum(values) => json!({ "type": "string", "enum": values }),
+            Self::List(item) => json!({ "type": "array", "items": item.json_schema(&Value::Null) }),
+            Self::Set(item) => json!({
+                "type": "array",
+                "items": item.json_schema(&Value::Null),
+                "uniqueItems": true,
+            }),
+            Self::Map(value) => json!({
+                "type": "object",
+                "additionalProperties": value.json_schema(&Value::Null),
+            }),
+            // TOML has no null: an unset option is simply left out
+            Self::Optional(inner) => inner.json_schema(default),
+            Self::Table(schema) => schema.json_schema(default),
+        }
+    }
+}
+
+/// Documentation of one config field.
+#[derive(Debug, Clone)]
+pub struct Field {
+    /// Key as written in the config file.
+    pub name: &'static str,
+    /// One-line description.
+    pub doc: &'static str,
+    /// Field type.
+    pub ty: FieldType,
+}
+
+impl Field {
+    /// Describe a field.
+    #[must_use]
+    pub fn new(name: &'static str, ty: FieldType, doc: &'static str) -> Self {
+        Self { name, doc, ty }
+    }
+}
+
+/// Documentation of a config table.
+#[derive(Debug, Clone)]
+pub struct Schema {
+    /// Name of the Rust type.
+    pub title: &'static str,
+    /// One-line description.
+    pub doc: &'static str,
+    /// Fields in declaration order.
+    pub fields: Vec<Field>,
+}
+
+impl Schema {
+    /// JSON Schema object for this table.
+    fn json_schema(&self, default: &Value) -> Value {
+        let properties: Map<String, Value> = self
+            .fields
+            .iter()
+            .map(|field| {
+                let default = field_default(default, field);
+                let mut property = field.ty.json_schema(&default);
+                if let Value::Object(ref mut property) = property {
+                    property.insert("description".to_string(), json!(field.doc));
+                    if !default.is_null() {
+                        property.insert("default".to_string(), default);
+                    }
+                }
+                (field.name.to_string(), property)
+            })
+            .collect();
+
+        json!({
+            "title": self.title,
+            "description": self.doc,
+            "type": "object",
+            "properties": properties,
+        })
+    }
+
+    /// Append a Markdown section for this table and its nested tables.
+    fn write_markdown(&self, out: &mut String, section: &str, default: &Value) {
+        let heading = if section.is_empty() {
+            format!("## {}", self.title)
+        } else {
+            format!("## `[{section}]`")
+        };
+        let _ = writeln!(out, "{heading}\n\n{}\n", self.doc);
+        let _ = writeln!(out, "| Field | Type | Default | Description |");
+        let _ = writeln!(out, "|-------|------|---------|-------------|");
+        for field in &self.fields {
+            let _ = writeln!(
+                out,
+                "| `{}` | {} | {} | {} |",
+                field.name,
+                field.ty.name(),
+                markdown_default(&field.ty, &field_default(default, field)),
+                field.doc.replace('|', "\\|")
+            );
+        }
+        let _ = writeln!(out);
+
+        for field in &self.fields {
+            if let FieldType::Table(ref schema) = field.ty {
+                let nested = if section.is_empty() {
+                    field.name.to_string()
+                } else {
+                    format!("{section}.{}", field.name)
+                };
+                schema.write_markdown(out, &nested, &field_default(default, field));
+            }
+        }
+    }
+}
+
+/// A config type that can describe its fields.
+pub trait ConfigSchema {
+    /// Describe the type's fields.
+    fn schema() -> Schema;
+}
+
+/// JSON Schema for a config type, with defaults taken from its `Default` impl.
+///
+/// # Errors
+///
+/// Returns an error if the default value cannot be serialized.
+pub fn json_schema<T: ConfigSchema + Default + Serialize>() -> Result<Value, serde_json::Error> {
+    let default = serde_json::to_value(T::default())?;
+    let mut schema = T::schema().json_schema(&default);
+    if let Value::Object(ref mut schema) = schema {
+        schema.insert("$schema".to_string(), json!(JSON_SCHEMA_DIALECT));
+    }
+    Ok(schema)
+}
+
+/// Markdown tables of every field of a config type and its nested tables.
+///
+/// # Errors
+///
+/// Returns an error if the default value cannot be serialized.
+pub fn markdown<T: ConfigSchema + Default + Serialize>() -> Result<String, serde_json::Error> {
+    let default = serde_json::to_value(T::default())?;
+    let mut out = String::new();
+    T::schema().write_markdown(&mut out, "", &default);
+    Ok(out)
+}
+
+/// Default of `field` within its table's default, with sets in sorted order.
+fn field_default(table_default: &Value, field: &Field) -> Value {
+    let mut default = table_default
+        .get(field.name)
+        .cloned()
+        .unwrap_or(Value::Null);
+    if let (FieldType::Set(_), Value::Array(ref mut items)) = (&field.ty, &mut default) {
+        items.sort_by_key(ToString::to_string);
+    }
+    default
+}
+
+/// Default value as shown in a Markdown table cell.
+fn markdown_default(ty: &FieldType, default: &Value) -> String {
+    match (ty, default) {
+        (FieldType::Table(_), _) => "see below".to_string(),
+        (_, Value::Null) => "unset".to_string(),
+        (_, Value::String(s)) if s.is_empty() => "`\"\"`".to_string(),
+        (_, value) => format!("`{}`", value.to_string().replace('|', "\\|")),
+    }
+}
+
+impl ConfigSchema for PolicyConfig {
+    #[allow(clippy::too_many_lines)]
+    fn schema() -> Schema {
+        Schema {
+            title: "PolicyConfig",
+            doc: "Policy configuration loaded from `.claude-supervisor.toml` or the global config file.",
+            fields: vec![
+                Field::new("level", policy_level(), "Global policy level."),
+                Field::new(
+                    "dry_run",
+                    FieldType::Boolean,
+                    "Record the calls the policy would deny or escalate and allow them.",
+                ),
+                Field::new("auto_continue", FieldType::Boolean, "Auto-continue without user prompts."),
+                Field::new("ai", FieldType::table::<AiConfig>(), "AI provider configuration."),
+                Field::new("bash", FieldType::table::<BashPolicy>(), "Bash command policies."),
+                Field::new("files", FieldType::table::<FilesPolicy>(), "File operation policies."),
+                Field::new("tools", FieldType::table::<ToolsPolicy>(), "Tool-specific policies."),
+                Field::new(
+                    "mcp",
+                    FieldType::map(FieldType::table::<McpServerPolicy>()),
+                    "Rules for MCP tools keyed by server name.",
+                ),
+                Field::new(
+                    "edit_rules",
+                    FieldType::list(FieldType::table::<EditRuleConfig>()),
+                    "Rules on what Edit and `MultiEdit` calls change.",
+                ),
+                Field::new(
+                    "rewrites",
+                    FieldType::list(FieldType::table::<RewriteRuleConfig>()),
+                    "Rules rewriting Bash commands before they run.",
+                ),
+                Field::new(
+                    "paths",
+                    FieldType::map(FieldType::Enum(&["allow", "escalate", "deny"])),
+                    "Decisions for file writes keyed by path glob, matched against the path relative to the session cwd and the absolute path.",
+                ),
+                Field::new(
+                    "tool_aliases",
+                    FieldType::map(FieldType::String),
+                    "Tool names mapped to the canonical names rules are written for, on top of the built-in aliases.",
+                ),
+                Field::new(
+                    "rate_limits",
+                    FieldType::map(FieldType::table::<RateLimitConfig>()),
+                    "Sliding-window limits on calls keyed by tool name; calls over a limit are escalated.",
+                ),
+                Field::new(
+                    "escalation_sampling",
+                    FieldType::map(FieldType::table::<SamplingConfig>()),
+                    "Escalation sample rates keyed by tool or rule name; escalations left out of the sample are allowed.",
+                ),
+                Field::new(
+                    "blocklist",
+                    FieldType::list(FieldType::table::<BlocklistEntry>()),
+                    "Command rules added to the built-in blocklist.",
+                ),
+                Field::new(
+                    "blocklist_levels",
+                    FieldType::map(FieldType::table::<BlocklistLevels>()),
+                    "Per-level actions for blocklist categories, overriding the built-in ones.",
+                ),
+                Field::new(
+                    "self_protection",
+                    FieldType::table::<SelfProtectionConfig>(),
+                    "Protection of the supervisor's own files (global config only).",
+                ),
+                Field::new(
+                    "containment",
+                    FieldType::table::<ContainmentConfig>(),
+                    "Keeping writes inside the project (global config only).",
+                ),
+                Field::new("sandbox", FieldType::table::<SandboxConfig>(), "Sandbox wrapper for Bash commands."),
+                Field::new(
+                    "snapshots",
+                    FieldType::table::<SnapshotConfig>(),
+                    "Snapshots of files before approved writes.",
+                ),
+                Field::new(
+                    "escrow",
+                    FieldType::table::<EscrowConfig>(),
+                    "Moving deleted files to a trash directory instead of deleting them.",
+                ),
+                Field::new(
+                    "git",
+                    FieldType::table::<GitConfig>(),
+                    "Gating of git commits, pushes, tags and history rewrites.",
+                ),
+                Field::new(
+                    "network",
+                    FieldType::table::<NetworkConfig>(),
+                    "Gating of Bash commands that send data to other hosts.",
+                ),
+                Field::new(
+                    "secrets",
+                    FieldType::table::<SecretScanConfig>(),
+                    "Scanning of written content and commands for credentials.",
+                ),
+                Field::new(
+                    "novel_binaries",
+                    FieldType::table::<NovelBinaryConfig>(),
+                    "Escalation of binaries the project has not run before.",
+                ),
+                Field::new(
+                    "by_permission_mode",
+                    FieldType::map(policy_level()),
+                    "Policy level overrides keyed by Claude Code permission mode.",
+                ),
+                Field::new(
+                    "hook_additional_context",
+                    FieldType::Boolean,
+                    "Pass supervisor guidance to Claude as the hook's `additionalContext`; disable for Claude Code versions that do not read the field.",
+                ),
+                Field::new(
+                    "data_dir",
+                    FieldType::Path,
+                    "Directory for the audit log, state, snapshots and sockets (global config only).",
+                ),
+                Field::new(
+                    "kill_switch",
+                    FieldType::Path,
+                    "Kill switch file halting every session while it exists (global config only).",
+                ),
+                Field::new(
+                    "audit",
+                    FieldType::table::<AuditConfig>(),
+                    "Sinks receiving every audit record (global config only).",
+                ),
+                Field::new(
+                    "suggestions",
+                    FieldType::table::<SuggestionConfig>(),
+                    "CLAUDE.md additions suggested after each session.",
+                ),
+            ],
+        }
+    }
+}
+
+impl ConfigSchema for SupervisorConfig {
+    #[allow(clippy::too_many_lines)]
+    fn schema() -> Schema {
+        Schema {
+            title: "SupervisorConfig",
+            doc: "Configuration for a supervised `run` session.",
+            fields: vec![
+                Field::new("policy", policy_level(), "Policy level."),
+                Field::new(
+                    "auto_continue",
+                    FieldType::Boolean,
+                    "Auto-continue without user prompts.",
+                ),
+                Field::new(
+                    "allowed_tools",
+                    FieldType::set(FieldType::String),
+                    "Tools to auto-approve.",
+                ),
+                Field::new(
+                    "denied_tools",
+                    FieldType::set(FieldType::String),
+                    "Tools to always deny.",
+                ),
+                Field::new(
+                    "ai_supervisor",
+                    FieldType::Boolean,
+                    "Decide escalations with the AI supervisor.",
+                ),
+                Field::new(
+                    "escalation",
+                    FieldType::table::<EscalationConfig>(),
+                    "How escalated tool calls are decided.",
+                ),
+                Field::new(
+                    "tool_timeouts",
+                    FieldType::table::<ToolTimeoutConfig>(),
+                    "Timeouts for approved tool calls that never produce a result.",
+                ),
+                Field::new(
+                    "tool_errors",
+                    FieldType::table::<ToolErrorConfig>(),
+                    "How tool results are recognized as errors.",
+                ),
+                Field::new(
+                    "blast_radius",
+                    FieldType::table::<BlastRadiusConfig>(),
+                    "Limits on the session's cumulative mutating operations.",
+                ),
+                Field::new(
+                    "risk",
+                    FieldType::table::<RiskConfig>(),
+                    "Escalation on the summed risk of the session's recent tool calls.",
+                ),
+                Field::new(
+                    "stop",
+                    FieldType::table::<StopConfig>(),
+                    "Stop hook behaviour.",
+                ),
+                Field::new(
+                    "worktree",
+                    FieldType::table::<WorktreeConfig>(),
+                    "Git worktree isolation.",
+                ),
+                Field::new(
+                    "show_activity",
+                    FieldType::Boolean,
+                    "Show detailed activity output.",
+                ),
+                Field::new(
+                    "raw_mode",
+                    FieldType::Boolean,
+                    "Output raw untruncated events (verbose mode).",
+                ),
+                Field::new(
+                    "redaction",
+                    FieldType::table::<RedactionConfig>(),
+                    "Secret redaction for AI context and audit storage.",
+                ),
+                Field::new(
+                    "no_sandbox",
+                    FieldType::Boolean,
+                    "Disable the Bash sandbox for this session's hooks.",
+                ),
+                Field::new(
+                    "policy_dry_run",
+                    FieldType::Boolean,
+                    "Record the calls the policy would deny or escalate and allow them, in the runner and the session's hooks.",
+                ),
+                Field::new(
+                    "snapshots",
+                    FieldType::table::<SnapshotConfig>(),
+                    "Snapshots of files before approved writes.",
+                ),
+                Field::new(
+                    "repos",
+                    FieldType::list(FieldType::Path),
+                    "Repositories the session spans; the first is the working directory.",
+                ),
+                Field::new(
+                    "history",
+                    FieldType::table::<HistoryConfig>(),
+                    "Event history kept as escalation context.",
+                ),
+                Field::new(
+                    "startup_timeout_secs",
+                    FieldType::Integer,
+                    "Seconds to wait for Claude Code's init event before checking its stderr for login errors.",
+                ),
+                Field::new(
+                    "knowledge_timeout_secs",
+                    FieldType::Integer,
+                    "Seconds each knowledge source may take to load before supervision starts without it.",
+                ),
+                Field::new(
+                    "max_output_bytes",
+                    FieldType::Integer,
+                    "Most bytes printed for one event, tool input or tool result.",
+                ),
+                Field::new(
+                    "show_thinking",
+                    FieldType::Boolean,
+                    "Print Claude's thinking blocks.",
+                ),
+                Field::new(
+                    "claude_binary",
+                    FieldType::Path,
+                    "Claude Code binary to run: a name looked up in PATH, or a path.",
+                ),
+                Field::new(
+                    "max_duration_mins",
+                    FieldType::optional(FieldType::Integer),
+                    "Wall-clock limit for the session in minutes, counted from the init event.",
+                ),
+                Field::new(
+                    "pause_stops_clock",
+                    FieldType::Boolean,
+                    "Stop the session clock while the session is paused.",
+                ),
+                Field::new(
+                    "context_recovery",
+                    FieldType::table::<ContextRecoveryConfig>(),
+                    "Recovery of sessions that exhaust Claude's context window.",
+                ),
+                Field::new(
+                    "idle_nudge",
+                    FieldType::table::<IdleNudgeConfig>(),
+                    "Nudging of sessions that stop producing events mid-task.",
+                ),
+                Field::new(
+                    "budget",
+                    FieldType::table::<BudgetConfig>(),
+                    "Cost limit of the session and alerts before it is reached.",
+                ),
+                Field::new(
+                    "strict_startup",
+                    FieldType::Boolean,
+                    "Abort the run when a startup check, such as validating the AI model, fails instead of warning.",
+                ),
+                Field::new(
+                    "merge_transcript",
+                    FieldType::Boolean,
+                    "Merge the session transcript Claude Code writes to disk with its stdout.",
+                ),
+                Field::new(
+                    "artifacts_dir",
+                    FieldType::optional(FieldType::Path),
+                    "Directory each finished run's artifact bundle is written to.",
+                ),
+                Field::new(
+                    "dashboard",
+                    FieldType::table::<DashboardEventConfig>(),
+                    "What dashboard clients are sent of each event.",
+                ),
+            ],
+        }
+    }
+}
+
+impl ConfigSchema for AiConfig {
+    fn schema() -> Schema {
+        Schema {
+            title: "AiConfig",
+            doc: "Configuration for the AI supervisor client.",
+            fields: vec![
+                Field::new(
+                    "provider",
+                    FieldType::Enum(&["gemini", "claude"]),
+                    "Provider to use.",
+                ),
+                Field::new("model", FieldType::String, "Model to use for supervision."),
+                Field::new(
+                    "max_tokens",
+                    FieldType::Integer,
+                    "Maximum tokens in response.",
+                ),
+                Field::new("base_url", FieldType::String, "Base URL for the API."),
+                Field::new(
+                    "api_key_env",
+                    FieldType::String,
+                    "Environment variable name for the API key.",
+                ),
+                Field::new(
+                    "max_concurrent_requests",
+                    FieldType::optional(FieldType::Integer),
+                    "Limit on in-flight AI requests across every session sharing the client.",
+                ),
+            ],
+        }
+    }
+}
+
+impl ConfigSchema for BashPolicy {
+    fn schema() -> Schema {
+        Schema {
+            title: "BashPolicy",
+            doc: "Bash command policy configuration.",
+            fields: vec![
+                Field::new(
+                    "block_destructive",
+                    FieldType::Boolean,
+                    "Block destructive commands (rm -rf, etc.).",
+                ),
+                Field::new(
+                    "block_network_exfil",
+                    FieldType::Boolean,
+                    "Block network exfiltration (curl | sh, etc.).",
+                ),
+                Field::new(
+                    "block_privilege_escalation",
+                    FieldType::Boolean,
+                    "Block privilege escalation (sudo, su).",
+                ),
+                Field::new(
+                    "blocked_patterns",
+                    FieldType::list(FieldType::String),
+                    "Additional blocked command patterns, matched as plain text anywhere in the command.",
+                ),
+                Field::new(
+                    "blocked_regexes",
+                    FieldType::list(FieldType::String),
+                    "Additional blocked command regexes, such as `rm\\s+-\\w*r`.",
+                ),
+                Field::new(
+                    "allowed_prefixes",
+                    FieldType::list(FieldType::String),
+                    "Command prefixes allowed at every level, such as `cargo test`; when set, other commands escalate, or are denied by the Strict level.",
+                ),
+            ],
+        }
+    }
+}
+
+impl ConfigSchema for FilesPolicy {
+    fn schema() -> Schema {
+        Schema {
+            title: "FilesPolicy",
+            doc: "File operation policy configuration.",
+            fields: vec![
+                Field::new(
+                    "sensitive_paths",
+                    FieldType::list(FieldType::String),
+                    "Sensitive paths to block writes to.",
+                ),
+                Field::new(
+                    "allow_env_files",
+                    FieldType::Boolean,
+                    "Allow writes to .env files.",
+                ),
+                Field::new(
+                    "allow_ssh_dir",
+                    FieldType::Boolean,
+                    "Allow writes to SSH directory.",
+                ),
+            ],
+        }
+    }
+}
+
+impl ConfigSchema for ToolsPolicy {
+    fn schema() -> Schema {
+        Schema {
+            title: "ToolsPolicy",
+            doc: "Tool-specific policy configuration.",
+            fields: vec![
+                Field::new(
+                    "allowed",
+                    FieldType::set(FieldType::String),
+                    "Tools to always allow, by name or glob such as `mcp__github__*`.",
+                ),
+                Field::new(
+                    "denied",
+                    FieldType::set(FieldType::String),
+                    "Tools to always deny, by name or glob; the most specific match wins.",
+                ),
+                Field::new(
+                    "escalate",
+                    FieldType::set(FieldType::String),
+                    "Tools that require escalation.",
+                ),
+                Field::new(
+                    "mcp_default",
+                    FieldType::Enum(&["allow", "escalate", "deny"]),
+                    "What Strict mode does with `mcp__*` tools that are not listed.",
+                ),
+                Field::new(
+                    "blocked_mcp_patterns",
+                    FieldType::list(FieldType::String),
+                    "Patterns matched against full MCP tool names to block.",
+                ),
+            ],
+        }
+    }
+}
+
+impl ConfigSchema for McpServerPolicy {
+    fn schema() -> Schema {
+        Schema {
+            title: "McpServerPolicy",
+            doc: "Rules for the tools of one MCP server; tool names omit the `mcp__<server>__` prefix.",
+            fields: vec![
+                Field::new(
+                    "default",
+                    FieldType::optional(FieldType::Enum(&["allow", "escalate", "deny"])),
+                    "What to do with the server's tools that no rule decided, at every policy level; unset falls back to the level.",
+                ),
+                Field::new(
+                    "allowed",
+                    FieldType::list(FieldType::String),
+                    "Tools of the server to always allow.",
+                ),
+                Field::new(
+                    "denied",
+                    FieldType::list(FieldType::String),
+                    "Tools of the server to always deny.",
+                ),
+            ],
+        }
+    }
+}
+
+impl ConfigSchema for SelfProtectionConfig {
+    fn schema() -> Schema {
+        Schema {
+            title: "SelfProtectionConfig",
+            doc: "Self-protection configuration, only honoured from the global config file.",
+            fields: vec![
+                Field::new(
+                    "enabled",
+                    FieldType::Boolean,
+                    "Deny modifications to the supervisor's own files.",
+                ),
+                Field::new(
+                    "extra_paths",
+                    FieldType::list(FieldType::Path),
+                    "Additional paths to protect.",
+                ),
+            ],
+        }
+    }
+}
+
+impl ConfigSchema for BudgetConfig {
+    fn schema() -> Schema {
+        Schema {
+            title: "BudgetConfig",
+            doc: "Cost budget configuration.",
+            fields: vec![
+                Field::new(
+                    "max_cost_usd",
+                    FieldType::optional(FieldType::Number),
+                    "Session cost in USD at which the session is killed; no budget when unset.",
+                ),
+                Field::new(
+                    "alert_percent",
+                    FieldType::list(FieldType::Integer),
+                    "Shares of the budget, in percent, at which an alert is raised once.",
+                ),
+                Field::new(
+                    "notify_claude",
+                    FieldType::Boolean,
+                    "Tell Claude at each alert that it should prioritize finishing.",
+                ),
+                Field::new(
+                    "webhook",
+                    FieldType::table::<WebhookConfig>(),
+                    "Endpoint alerts are sent to as JSON; disabled while `url` is empty.",
+                ),
+            ],
+        }
+    }
+}
+
+impl ConfigSchema for ContainmentConfig {
+    fn schema() -> Schema {
+        Schema {
+            title: "ContainmentConfig",
+            doc: "Write containment configuration, only honoured from the global config file.",
+            fields: vec![
+                Field::new(
+                    "enabled",
+                    FieldType::Boolean,
+                    "Check the real target of file writes and Bash redirects against the session cwd and repository roots.",
+                ),
+                Field::new(
+                    "action",
+                    FieldType::Enum(&["deny", "escalate"]),
+                    "What to do with a write outside the allowed roots.",
+                ),
+                Field::new(
+                    "extra_roots",
+                    FieldType::list(FieldType::Path),
+                    "Directories writes may also target, such as `/tmp`.",
+                ),
+            ],
+        }
+    }
+}
+
+impl ConfigSchema for SandboxConfig {
+    fn schema() -> Schema {
+        Schema {
+            title: "SandboxConfig",
+            doc: "Configuration for running approved Bash commands inside a sandbox.",
+            fields: vec![
+                Field::new(
+                    "wrapper",
+                    FieldType::optional(FieldType::String),
+                    "Wrapper binary (`bwrap`, `firejail` or a path). Sandboxing is off when unset.",
+                ),
+                Field::new(
+                    "args",
+                    FieldType::optional(FieldType::list(FieldType::String)),
+                    "Wrapper arguments placed before `sh -c '<command>'`; `{cwd}` is the session working directory.",
+                ),
+                Field::new(
+                    "isolate_unknown",
+                    FieldType::Boolean,
+                    "Also sandbox commands whose binary is not on the known-safe list.",
+                ),
+            ],
+        }
+    }
+}
+
+impl ConfigSchema for NovelBinaryConfig {
+    fn schema() -> Schema {
+        Schema {
+            title: "NovelBinaryConfig",
+            doc: "Configuration for flagging the first use of a binary in a project.",
+            fields: vec![
+                Field::new(
+                    "mode",
+                    FieldType::Enum(&["off", "log", "escalate"]),
+                    "What to do on the first use of an unknown binary.",
+                ),
+                Field::new(
+                    "allowed",
+                    FieldType::list(FieldType::String),
+                    "Binaries treated as known in every project.",
+                ),
+            ],
+        }
+    }
+}
+
+impl ConfigSchema for HistoryConfig {
+    fn schema() -> Schema {
+        Schema {
+            title: "HistoryConfig",
+            doc: "Limits for the event history kept as escalation context.",
+            fields: vec![
+                Field::new(
+                    "max_events",
+                    FieldType::Integer,
+                    "Events kept in memory; the oldest are evicted first.",
+                ),
+                Field::new(
+                    "max_bytes",
+                    FieldType::Integer,
+                    "Total serialized size of the events kept in memory.",
+                ),
+                Field::new(
+                    "max_result_bytes",
+                    FieldType::Integer,
+                    "Tool results longer than this are truncated before being kept.",
+                ),
+                Field::new(
+                    "spill",
+                    FieldType::Boolean,
+                    "Write evicted events to a temporary file ring for later readback.",
+                ),
+                Field::new(
+                    "spill_files",
+                    FieldType::Integer,
+                    "Files in the spill ring; the oldest is deleted when a new one starts.",
+                ),
+                Field::new(
+                    "spill_file_bytes",
+                    FieldType::Integer,
+                    "Size at which the spill ring starts a new file.",
+                ),
+                Field::new(
+                    "mirror",
+                    FieldType::Boolean,
+                    "Mirror the session to a Claude Code compatible transcript for `export transcript`.",
+                ),
+                Field::new(
+                    "dead_letters",
+                    FieldType::Boolean,
+                    "Write events of unknown types whole to a dead-letter JSONL file.",
+                ),
+            ],
+        }
+    }
+}
+
+impl ConfigSchema for SnapshotConfig {
+    fn schema() -> Schema {
+        Schema {
+            title: "SnapshotConfig",
+            doc: "Configuration for snapshotting files before approved writes.",
+            fields: vec![
+                Field::new(
+                    "enabled",
+                    FieldType::Boolean,
+                    "Whether Write/Edit targets are snapshotted before approval.",
+                ),
+                Field::new(
+                    "dir",
+                    FieldType::Path,
+                    "Snapshot directory; a relative path is resolved against the session working directory.",
+                ),
+                Field::new(
+                    "max_file_bytes",
+                    FieldType::Integer,
+                    "Files larger than this are not snapshotted.",
+                ),
+                Field::new(
+                    "max_per_file",
+                    FieldType::Integer,
+                    "Snapshots kept per file and session; the oldest are evicted first.",
+                ),
+                Field::new(
+                    "timeout_ms",
+                    FieldType::Integer,
+                    "Longest a snapshot may delay an approval, in milliseconds.",
+                ),
+            ],
+        }
+    }
+}
+
+impl ConfigSchema for EscrowConfig {
+    fn schema() -> Schema {
+        Schema {
+            title: "EscrowConfig",
+            doc: "Configuration for moving deleted files to a trash directory.",
+            fields: vec![
+                Field::new(
+                    "enabled",
+                    FieldType::Boolean,
+                    "Whether deletions inside the project are moved to the trash instead.",
+                ),
+                Field::new(
+                    "dir",
+                    FieldType::Path,
+                    "Trash directory; a relative path is resolved against the session working directory.",
+                ),
+            ],
+        }
+    }
+}
+
+impl ConfigSchema for GitConfig {
+    fn schema() -> Schema {
+        let action = || FieldType::Enum(&["allow", "escalate", "deny"]);
+        Schema {
+            title: "GitConfig",
+            doc: "Configuration for gating git commits, pushes, tags and history rewrites.",
+            fields: vec![
+                Field::new(
+                    "enabled",
+                    FieldType::Boolean,
+                    "Whether Bash commands are checked for git operations.",
+                ),
+                Field::new(
+                    "allow_commit",
+                    FieldType::Boolean,
+                    "Whether commits to unprotected branches are left to the other rules; otherwise they are escalated.",
+                ),
+                Field::new("allow_push", action(), "What to do with pushes."),
+                Field::new(
+                    "allow_tag",
+                    action(),
+                    "What to do with tags that are created or deleted.",
+                ),
+                Field::new(
+                    "allow_branch_delete",
+                    action(),
+                    "What to do with deleted branches, local or remote.",
+                ),
+                Field::new("force_push", action(), "What to do with force pushes."),
+                Field::new(
+                    "history_rewrite",
+                    action(),
+                    "What to do with history rewrites: `commit --amend`, `rebase -i`, `filter-branch`, `filter-repo` and pushes after `reset --hard`.",
+                ),
+                Field::new(
+                    "protected_branches",
+                    FieldType::list(FieldType::String),
+                    "Branch globs that commits and pushes escalate for, and that force pushes, rebases, rewrites and deletions are denied for.",
+                ),
+                Field::new(
+                    "protect_history",
+                    FieldType::Boolean,
+                    "Whether force pushes, rebases, rewrites and deletions of protected branches are denied; otherwise they are escalated at least.",
+                ),
+            ],
+        }
+    }
+}
+
+impl ConfigSchema for RateLimitConfig {
+    fn schema() -> Schema {
+        Schema {
+            title: "RateLimitConfig",
+            doc: "How often calls to one tool may be made before they are escalated.",
+            fields: vec![
+                Field::new(
+                    "max",
+                    FieldType::Integer,
+                    "Calls allowed within the window.",
+                ),
+                Field::new(
+                    "per_seconds",
+                    FieldType::Integer,
+                    "Length of the sliding window in seconds.",
+                ),
+            ],
+        }
+    }
+}
+
+impl ConfigSchema for SamplingConfig {
+    fn schema() -> Schema {
+        Schema {
+            title: "SamplingConfig",
+            doc: "Share of a tool's or rule's escalations that go to the supervisor.",
+            fields: vec![Field::new(
+                "escalate_sample_rate",
+                FieldType::Number,
+                "Share of matching escalations sent to the supervisor, from 0 to 1; the rest are allowed.",
+            )],
+        }
+    }
+}
+
+impl ConfigSchema for BlocklistLevels {
+    fn schema() -> Schema {
+        let action = || FieldType::optional(FieldType::Enum(&["allow", "escalate", "deny"]));
+        Schema {
+            title: "BlocklistLevels",
+            doc: "Per-level actions for the commands of one blocklist category; levels left out keep the built-in action.",
+            fields: vec![
+                Field::new("permissive", action(), "Action at the permissive level."),
+                Field::new("moderate", action(), "Action at the moderate level."),
+                Field::new("strict", action(), "Action at the strict level."),
+            ],
+        }
+    }
+}
+
+impl ConfigSchema for NetworkConfig {
+    fn schema() -> Schema {
+        Schema {
+            title: "NetworkConfig",
+            doc: "Configuration for gating Bash commands that send data to other hosts.",
+            fields: vec![
+                Field::new(
+                    "enabled",
+                    FieldType::Boolean,
+                    "Whether Bash commands are checked for uploads to other hosts.",
+                ),
+                Field::new(
+                    "allowed_hosts",
+                    FieldType::list(FieldType::String),
+                    "Hosts data may be sent to, along with their subdomains.",
+                ),
+            ],
+        }
+    }
+}
+
+impl ConfigSchema for SecretScanConfig {
+    fn schema() -> Schema {
+        Schema {
+            title: "SecretScanConfig",
+            doc: "Configuration for scanning Write, Edit and Bash inputs for credentials.",
+            fields: vec![
+                Field::new(
+                    "enabled",
+                    FieldType::Boolean,
+                    "Whether written content and commands are scanned for secrets.",
+                ),
+                Field::new(
+                    "action",
+                    FieldType::Enum(&["escalate", "deny"]),
+                    "What to do with a call that contains a secret.",
+                ),
+            ],
+        }
+    }
+}
+
+impl ConfigSchema for EscalationConfig {
+    fn schema() -> Schema {
+        Schema {
+            title: "EscalationConfig",
+            doc: "Configuration for how escalated tool calls are decided.",
+            fields: vec![
+                Field::new(
+                    "backend",
+                    FieldType::Enum(&["ai", "webhook"]),
+                    "Backend that decides escalations.",
+                ),
+                Field::new(
+                    "webhook",
+                    FieldType::table::<WebhookConfig>(),
+                    "Webhook settings, used when `backend = \"webhook\"`.",
+                ),
+                Field::new(
+                    "on_ai_failure",
+                    FieldType::Enum(&["deny", "allow"]),
+                    "Fallback when the backend fails to answer.",
+                ),
+                Field::new(
+                    "decision_authority",
+                    FieldType::Enum(&["runner", "hook", "strictest"]),
+                    "Which decision stands when hook and runner disagree.",
+                ),
+                Field::new(
+                    "hook_decision_wait_ms",
+                    FieldType::Integer,
+                    "Milliseconds the runner waits for a hook to report its decision before evaluating the call itself.",
+                ),
+                Field::new(
+                    "interactive",
+                    FieldType::table::<InteractiveConfig>(),
+                    "Terminal prompt settings, used with `--interactive-approvals`.",
+                ),
+            ],
+        }
+    }
+}
+
+impl ConfigSchema for IdleNudgeConfig {
+    fn schema() -> Schema {
+        Schema {
+            title: "IdleNudgeConfig",
+            doc: "Configuration for nudging sessions that stop producing events.",
+            fields: vec![
+                Field::new(
+                    "after_secs",
+                    FieldType::optional(FieldType::Integer),
+                    "Seconds without events, while no tool call runs, before the session is nudged; unset disables nudging.",
+                ),
+                Field::new("max_nudges", FieldType::Integer, "Most nudges per run."),
+                Field::new(
+                    "message",
+                    FieldType::String,
+                    "Prompt the idle session is resumed with.",
+                ),
+            ],
+        }
+    }
+}
+
+impl ConfigSchema for ContextRecoveryConfig {
+    fn schema() -> Schema {
+        Schema {
+            title: "ContextRecoveryConfig",
+            doc: "Configuration for recovering sessions that exhaust their context.",
+            fields: vec![
+                Field::new(
+                    "mode",
+                    FieldType::Enum(&["off", "resume", "summary"]),
+                    "How to continue the session.",
+                ),
+                Field::new(
+                    "max_attempts",
+                    FieldType::Integer,
+                    "Most recovery attempts per run.",
+                ),
+            ],
+        }
+    }
+}
+
+impl ConfigSchema for InteractiveConfig {
+    fn schema() -> Schema {
+        Schema {
+            title: "InteractiveConfig",
+            doc: "Configuration for terminal approval prompts (`run --interactive-approvals`).",
+            fields: vec![
+                Field::new(
+                    "enabled",
+                    FieldType::Boolean,
+                    "Prompt in the terminal for escalations instead of asking the backend. Ignored when stdin is not a terminal.",
+                ),
+                Field::new(
+                    "timeout_secs",
+                    FieldType::Integer,
+                    "Seconds to wait for an answer.",
+                ),
+                Field::new(
+                    "default",
+                    FieldType::Enum(&["deny", "allow"]),
+                    "Decision taken when no answer arrives in time.",
+                ),
+                Field::new(
+                    "max_input_bytes",
+                    FieldType::Integer,
+                    "Most bytes of the tool input shown in a prompt.",
+                ),
+            ],
+        }
+    }
+}
+
+impl ConfigSchema for WebhookConfig {
+    fn schema() -> Schema {
+        Schema {
+            title: "WebhookConfig",
+            doc: "Configuration for the webhook decision backend.",
+            fields: vec![
+                Field::new(
+                    "url",
+                    FieldType::String,
+                    "Endpoint that receives escalations.",
+                ),
+                Field::new(
+                    "auth_header_env",
+                    FieldType::optional(FieldType::String),
+                    "Environment variable holding the `Authorization` header value.",
+                ),
+                Field::new(
+                    "timeout_secs",
+                    FieldType::Integer,
+                    "Request timeout in seconds.",
+                ),
+            ],
+        }
+    }
+}
+
+impl ConfigSchema for ToolTimeoutConfig {
+    fn schema() -> Schema {
+        Schema {
+            title: "ToolTimeoutConfig",
+            doc: "Timeouts between an approved tool call and its result.",
+            fields: vec![
+                Field::new(
+                    "enabled",
+                    FieldType::Boolean,
+                    "Whether hung tool calls are detected at all.",
+                ),
+                Field::new(
+                    "default_secs",
+                    FieldType::Integer,
+                    "Timeout in seconds for tools without an entry in `per_tool`.",
+                ),
+                Field::new(
+                    "per_tool",
+                    FieldType::map(FieldType::Integer),
+                    "Timeouts in seconds keyed by tool name.",
+                ),
+                Field::new(
+                    "on_timeout",
+                    FieldType::Enum(&["warn", "terminate", "escalate"]),
+                    "What to do when a tool call times out.",
+                ),
+            ],
+        }
+    }
+}
+
+impl ConfigSchema for ToolErrorConfig {
+    fn schema() -> Schema {
+        Schema {
+            title: "ToolErrorConfig",
+            doc: "How tool results are recognized as errors.",
+            fields: vec![Field::new(
+                "patterns",
+                FieldType::list(FieldType::String),
+                "Regular expressions matched against the start of results that do not say whether they failed.",
+            )],
+        }
+    }
+}
+
+impl ConfigSchema for BlastRadiusConfig {
+    fn schema() -> Schema {
+        Schema {
+            title: "BlastRadiusConfig",
+            doc: "Limits on the weighted sum of a session's mutating operations.",
+            fields: vec![
+                Field::new(
+                    "enabled",
+                    FieldType::Boolean,
+                    "Whether the score is checked against the thresholds at all.",
+                ),
+                Field::new(
+                    "half_life_secs",
+                    FieldType::Integer,
+                    "Seconds after which an operation counts half as much.",
+                ),
+                Field::new(
+                    "escalate_at",
+                    FieldType::Integer,
+                    "Score at which the session is escalated.",
+                ),
+                Field::new(
+                    "deny_at",
+                    FieldType::Integer,
+                    "Score at which mutating calls are denied.",
+                ),
+                Field::new(
+                    "weights",
+                    FieldType::table::<MutationWeights>(),
+                    "Score per kind of operation.",
+                ),
+            ],
+        }
+    }
+}
+
+impl ConfigSchema for DashboardEventConfig {
+    fn schema() -> Schema {
+        Schema {
+            title: "DashboardEventConfig",
+            doc: "Where the dashboard is served and what its clients are sent of each event.",
+            fields: vec![
+                Field::new(
+                    "listen",
+                    FieldType::optional(FieldType::String),
+                    "Address the dashboard server listens on during `run`, as `host:port`.",
+                ),
+                Field::new(
+                    "fields",
+                    FieldType::map(FieldType::list(FieldType::String)),
+                    "Published fields by event type, or `<type>.<tool>`; an empty list keeps the event from clients.",
+                ),
+            ],
+        }
+    }
+}
+
+impl ConfigSchema for RiskConfig {
+    fn schema() -> Schema {
+        Schema {
+            title: "RiskConfig",
+            doc: "Escalation on the summed risk of a session's recent tool calls.",
+            fields: vec![
+                Field::new(
+                    "enabled",
+                    FieldType::Boolean,
+                    "Whether the score escalates calls at all.",
+                ),
+                Field::new(
+                    "window_secs",
+                    FieldType::Integer,
+                    "Length of the rolling window in seconds.",
+                ),
+                Field::new(
+                    "threshold",
+                    FieldType::Integer,
+                    "Score above which the session is escalated.",
+                ),
+                Field::new(
+                    "source_dirs",
+                    FieldType::list(FieldType::String),
+                    "Directories, relative to the working directory, whose files count as source.",
+                ),
+                Field::new(
+                    "weights",
+                    FieldType::table::<RiskWeights>(),
+                    "Risk per category of tool call.",
+                ),
+            ],
+        }
+    }
+}
+
+impl ConfigSchema for RiskWeights {
+    fn schema() -> Schema {
+        Schema {
+            title: "RiskWeights",
+            doc: "Risk added per tool call, by category.",
+            fields: vec![
+                Field::new(
+                    "network",
+                    FieldType::Integer,
+                    "Bash command running a network client such as `curl` or `ssh`.",
+                ),
+                Field::new("shell", FieldType::Integer, "Any other Bash command."),
+                Field::new(
+                    "write_outside_source",
+                    FieldType::Integer,
+                    "File write or edit outside the source directories.",
+                ),
+                Field::new(
+                    "write",
+                    FieldType::Integer,
+                    "File write or edit inside the source directories.",
+                ),
+                Field::new("mcp", FieldType::Integer, "MCP tool call."),
+                Field::new(
+                    "read",
+                    FieldType::Integer,
+                    "Read-only tool call, such as `Read` or `Grep`.",
+                ),
+                Field::new("other", FieldType::Integer, "Call to any other tool."),
+            ],
+        }
+    }
+}
+
+impl ConfigSchema for MutationWeights {
+    fn schema() -> Schema {
+        Schema {
+            title: "MutationWeights",
+            doc: "Score added per mutating operation, by kind.",
+            fields: vec![
+                Field::new(
+                    "delete",
+                    FieldType::Integer,
+                    "File deletion, by a tool or `rm`-like shell command.",
+                ),
+                Field::new("write", FieldType::Integer, "File write or edit by a tool."),
+                Field::new(
+                    "shell_write",
+                    FieldType::Integer,
+                    "Shell command writing files (redirects, `cp`, `sed -i`).",
+                ),
+                Field::new(
+                    "shell_move",
+                    FieldType::Integer,
+                    "Shell command moving or renaming files.",
+                ),
+                Field::new(
+                    "shell_permissions",
+                    FieldType::Integer,
+                    "Shell command changing permissions or ownership.",
+                ),
+                Field::new(
+                    "shell_vcs",
+                    FieldType::Integer,
+                    "Git command rewriting history or the remote.",
+                ),
+                Field::new(
+                    "shell_install",
+                    FieldType::Integer,
+                    "Package install or removal.",
+                ),
+            ],
+        }
+    }
+}
+
+impl ConfigSchema for StopConfig {
+    fn schema() -> Schema {
+        Schema {
+            title: "StopConfig",
+            doc: "Configuration for the Stop hook handler.",
+            fields: vec![
+                Field::new(
+                    "max_iterations",
+                    FieldType::Integer,
+                    "Maximum number of iterations before allowing stop.",
+                ),
+                Field::new(
+                    "max_subagent_iterations",
+                    FieldType::Integer,
+                    "Maximum number of iterations of each subagent before allowing its stop; counted apart from the main conversation.",
+                ),
+                Field::new(
+                    "force_continue",
+                    FieldType::Boolean,
+                    "Whether to force continue on stop events.",
+                ),
+                Field::new(
+                    "completion_phrases",
+                    FieldType::list(FieldType::String),
+                    "Phrases that indicate the task is complete.",
+                ),
+                Field::new(
+                    "incomplete_phrases",
+                    FieldType::list(FieldType::String),
+                    "Phrases that indicate the task is incomplete.",
+                ),
+                Field::new(
+                    "min_completion_confidence",
+                    FieldType::Number,
+                    "Block a stop when the completion confidence of Claude's final message is below this, from 0 to 1.",
+                ),
+                Field::new(
+                    "allow_stop_with_open_todos",
+                    FieldType::Boolean,
+                    "Allow a stop while items of Claude's TodoWrite plan are still pending or in progress.",
+                ),
+            ],
+        }
+    }
+}
+
+impl ConfigSchema for WorktreeConfig {
+    fn schema() -> Schema {
+        Schema {
+            title: "WorktreeConfig",
+            doc: "Configuration for git worktree isolation.",
+            fields: vec![
+                Field::new(
+                    "enabled",
+                    FieldType::Boolean,
+                    "Whether worktree isolation is enabled.",
+                ),
+                Field::new(
+                    "worktree_dir",
+                    FieldType::Path,
+                    "Directory where worktrees are created (relative to repo root).",
+                ),
+                Field::new(
+                    "auto_cleanup",
+                    FieldType::Boolean,
+                    "Whether to automatically clean up worktrees on session end.",
+                ),
+                Field::new(
+                    "branch_pattern",
+                    FieldType::String,
+                    "Branch name pattern for worktrees. Use {name} as placeholder.",
+                ),
+            ],
+        }
+    }
+}
+
+impl ConfigSchema for RedactionConfig {
+    fn schema() -> Schema {
+        Schema {
+            title: "RedactionConfig",
+            doc: "Configuration for redacting secrets before they leave the supervisor.",
+            fields: vec![
+                Field::new(
+                    "enabled",
+                    FieldType::Boolean,
+                    "Whether redaction is applied to AI supervisor context.",
+                ),
+                Field::new(
+                    "patterns",
+                    FieldType::list(FieldType::table::<RedactionPattern>()),
+                    "Additional patterns on top of the built-in secret shapes.",
+                ),
+                Field::new(
+                    "env_denylist",
+                    FieldType::list(FieldType::String),
+                    "Environment variables whose values are redacted wherever they appear literally.",
+                ),
+                Field::new(
+                    "redact_audit",
+                    FieldType::Boolean,
+                    "Whether tool inputs stored in the audit log are redacted too.",
+                ),
+                Field::new(
+                    "full_fidelity",
+                    FieldType::Boolean,
+                    "Keep secrets in dashboard events and audit tool inputs; dashboard events are still redacted beyond loopback.",
+                ),
+            ],
+        }
+    }
+}
+
+impl ConfigSchema for RedactionPattern {
+    fn schema() -> Schema {
+        Schema {
+            title: "RedactionPattern",
+            doc: "A user-defined redaction pattern.",
+            fields: vec![
+                Field::new(
+                    "kind",
+                    FieldType::String,
+                    "Label used in the replacement marker, e.g. `[REDACTED:<kind>]`.",
+                ),
+                Field::new(
+                    "pattern",
+                    FieldType::String,
+                    "Regex to match. If it has a `secret` capture group, only that group is replaced.",
+                ),
+            ],
+        }
+    }
+}
+
+impl ConfigSchema for BlocklistEntry {
+    fn schema() -> Schema {
+        Schema {
+            title: "BlocklistEntry",
+            doc: "A command rule added to the built-in blocklist; an unknown category fails the config load.",
+            fields: vec![
+                Field::new(
+                    "pattern",
+                    FieldType::String,
+                    "Regex searched for in each Bash command.",
+                ),
+                Field::new(
+                    "category",
+                    FieldType::Enum(&[
+                        "destructive",
+                        "privilege",
+                        "privilege_escalation",
+                        "network_exfil",
+                        "secret_access",
+                        "system_modification",
+                        "project",
+                        "custom",
+                        "mcp",
+                    ]),
+                    "Category of the rule in denial reasons.",
+                ),
+                Field::new(
+                    "reason",
+                    FieldType::String,
+                    "Why matching commands are denied; defaults to naming the pattern.",
+                ),
+            ],
+        }
+    }
+}
+
+impl ConfigSchema for SuggestionConfig {
+    fn schema() -> Schema {
+        Schema {
+            title: "SuggestionConfig",
+            doc: "Configuration for the CLAUDE.md additions suggested after each session.",
+            fields: vec![
+                Field::new(
+                    "enabled",
+                    FieldType::Boolean,
+                    "Whether suggestions are written to the session's run directory.",
+                ),
+                Field::new(
+                    "max_suggestions",
+                    FieldType::Integer,
+                    "Most suggestions kept for a session.",
+                ),
+                Field::new(
+                    "ai_polish",
+                    FieldType::Boolean,
+                    "Have the AI client reword the suggestions before they are written.",
+                ),
+            ],
+        }
+    }
+}
+
+impl ConfigSchema for AuditConfig {
+    fn schema() -> Schema {
+        Schema {
+            title: "AuditConfig",
+            doc: "Sinks receiving every audit record of a session (global config only).",
+            fields: vec![Field::new(
+                "sinks",
+                FieldType::list(FieldType::table::<AuditSinkConfig>()),
+                "Sinks written to, in order. Without a `sqlite` sink the query commands and the dashboard history have nothing to read.",
+            )],
+        }
+    }
+}
+
+impl ConfigSchema for AuditSinkConfig {
+    fn schema() -> Schema {
+        Schema {
+            title: "AuditSinkConfig",
+            doc: "One audit sink.",
+            fields: vec![
+                Field::new(
+                    "kind",
+                    FieldType::Enum(&["sqlite", "jsonl", "syslog"]),
+                    "Kind of sink: the `SQLite` database, rotated JSON lines files, or the local syslog socket.",
+                ),
+                Field::new(
+                    "path",
+                    FieldType::optional(FieldType::Path),
+                    "Directory of the JSONL files, or the syslog socket; unset uses the sink's default.",
+                ),
+                Field::new(
+                    "max_bytes",
+                    FieldType::Integer,
+                    "Size at which a JSONL file is rotated; 0 rotates by day only.",
+                ),
+            ],
+        }
+    }
+}
+
+impl ConfigSchema for EditRuleConfig {
+    fn schema() -> Schema {
+        Schema {
+            title: "EditRuleConfig",
+            doc: "A rule on what an Edit or `MultiEdit` call changes; it matches when every condition it sets holds for one hunk.",
+            fields: vec![
+                Field::new("name", FieldType::String, "Name of the rule in decision reasons."),
+                Field::new(
+                    "file_glob",
+                    FieldType::optional(FieldType::String),
+                    "Glob the edited file must match, either its absolute path or its path inside a session root.",
+                ),
+                Field::new(
+                    "content_removed_matches",
+                    FieldType::optional(FieldType::String),
+                    "Regex matched against each line a hunk removes.",
+                ),
+                Field::new(
+                    "content_added_matches",
+                    FieldType::optional(FieldType::String),
+                    "Regex matched against each line a hunk adds.",
+                ),
+                Field::new(
+                    "action",
+                    FieldType::Enum(&["deny", "escalate"]),
+                    "What to do with a matching call.",
+                ),
+            ],
+        }
+    }
+}
+
+impl ConfigSchema for RewriteRuleConfig {
+    fn schema() -> Schema {
+        Schema {
+            title: "RewriteRuleConfig",
+            doc: "A rule rewriting the Bash commands that run a program; the rewritten call is decided in place of the original.",
+            fields: vec![
+                Field::new("name", FieldType::String, "Name of the rule in logs and rule firings."),
+                Field::new(
+                    "program",
+                    FieldType::String,
+                    "Program the rule applies to, by file name, such as `rm`.",
+                ),
+                Field::new(
+                    "subcommand",
+                    FieldType::optional(FieldType::String),
+                    "First argument that is not an option the command must have, such as `apply`.",
+                ),
+                Field::new(
+                    "replace_program",
+                    FieldType::optional(FieldType::String),
+                    "Program run instead, such as `trash-put`.",
+                ),
+                Field::new(
+                    "add_args",
+                    FieldType::list(FieldType::String),
+                    "Arguments added after the program and subcommand unless present.",
+                ),
+                Field::new(
+                    "remove_args",
+                    FieldType::list(FieldType::String),
+                    "Arguments removed before any `--`.",
+                ),
+            ],
+        }
+    }
+}
+
+/// Type of a policy level field.
+fn policy_level() -> FieldType {
+    FieldType::Enum(&["permissive", "moderate", "strict"])
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    /// Assert that `schema` describes exactly the keys of `default`, recursively.
+    fn assert_covers(schema: &Schema, default: &Value) {
+        let Value::Object(default) = default else {
+            panic!("{} default is not a table", schema.title);
+        };
+        let mut described: Vec<_> = schema.fields.iter().map(|f| f.name).collect();
+        let mut serialized: Vec<_> = default.keys().map(String::as_str).collect();
+        described.sort_unstable();
+        serialized.sort_unstable();
+        assert_eq!(described, serialized, "fields of {}", schema.title);
+
+        for field in &schema.fields {
+            if let FieldType::Table(ref nested) = field.ty {
+                assert_covers(nested, &default[field.name]);
+            }
+        }
+    }
+
+    #[test]
+    fn test_schemas_cover_every_field() {
+        let default = serde_json::to_value(PolicyConfig::default()).unwrap();
+        assert_covers(&PolicyConfig::schema(), &default);
+        let default = serde_json::to_value(SupervisorConfig::default()).unwrap();
+        assert_covers(&SupervisorConfig::schema(), &default);
+    }
+
+    /// `schema` with references and optional wrappers resolved against `root`.
+    fn resolve<'a>(root: &'a Value, schema: &'a Value) -> &'a Value {
+        if let Some(reference) = schema["$ref"].as_str() {
+            let name = reference.rsplit('/').next().unwrap_or(reference);
+            return resolve(root, &root["$defs"][name]);
+        }
+        let variants = ["anyOf", "oneOf"]
+            .iter()
+            .find_map(|key| schema[key].as_array());
+        match variants.and_then(|variants| variants.iter().find(|v| v["type"] != "null")) {
+            Some(variant) => resolve(root, variant),
+            None => schema,
+        }
+    }
+
+    /// Assert that `schema` describes exactly the fields of the struct that
+    /// `derived` was derived from, recursing into tables in lists and maps.
+    fn assert_describes(schema: &Schema, derived: &Value, root: &Value) {
+        let derived = resolve(root, derived);
+        let mut described: Vec<_> = schema.fields.iter().map(|f| f.name).collect();
+        let mut fields: Vec<_> = derived["properties"]
+            .as_object()
+            .unwrap_or_else(|| panic!("{} is not a struct", schema.title))
+            .keys()
+            .map(String::as_str)
+            .collect();
+        described.sort_unstable();
+        fields.sort_unstable();
+        assert_eq!(described, fields, "fields of {}", schema.title);
+
+        for field in &schema.fields {
+            let mut ty = &field.ty;
+            let mut derived = resolve(root, &derived["properties"][field.name]);
+            loop {
+                match ty {
+                    FieldType::Optional(inner) => ty = inner,
+                    FieldType::List(item) | FieldType::Set(item) => {
+                        ty = item;
+                        derived = resolve(root, &derived["items"]);
+                    }
+                    FieldType::Map(value) => {
+                        ty = value;
+                        derived = resolve(root, &derived["additionalProperties"]);
+                    }
+                    FieldType::Table(nested) => {
+                        assert_describes(nested, derived, root);
+                        break;
+                    }
+                    _ => break,
+                }
+            }
+        }
+    }
+
+    #[test]
+    fn test_schemas_describe_every_struct_field() {
+        let derived = schemars::schema_for!(PolicyConfig).to_value();
+        assert_describes(&PolicyConfig::schema(), &derived, &derived);
+        let derived = schemars::schema_for!(SupervisorConfig).to_value();
+        assert_describes(&SupervisorConfig::schema(), &derived, &derived);
+    }
+
+    #[test]
+    fn test_json_schema_defaults_match_default_impls() {
+        let schema = json_schema::<PolicyConfig>().unwrap();
+        assert_eq!(schema["$schema"], JSON_SCHEMA_DIALECT);
+
+        let level = &schema["properties"]["level"];
+        assert_eq!(level["default"], "permissive");
+        assert_eq!(level["enum"], json!(["permissive", "moderate", "strict"]));
+
+        let ai = &schema["properties"]["ai"]["properties"];
+        assert_eq!(ai["model"]["default"], AiConfig::default().model);
+        assert_eq!(ai["max_tokens"]["default"], AiConfig::default().max_tokens);
+        assert_eq!(ai["max_tokens"]["type"], "integer");
+
+        let allowed = &schema["properties"]["tools"]["properties"]["allowed"];
+        assert_eq!(allowed["default"], json!(["Glob", "Grep", "Read"]));
+        assert_eq!(allowed["uniqueItems"], true);
+
+        // Unset options have no default rather than a null one
+        let wrapper = &schema["properties"]["sandbox"]["properties"]["wrapper"];
+        assert_eq!(wrapper["type"], "string");
+        assert!(wrapper.get("default").is_none());
+
+        let schema = json_schema::<SupervisorConfig>().unwrap();
+        let stop = &schema["properties"]["stop"]["properties"];
+        assert_eq!(
+            stop["max_iterations"]["default"],
+            StopConfig::default().max_iterations
+        );
+        let worktree = &schema["properties"]["worktree"]["properties"];
+        assert_eq!(worktree["worktree_dir"]["default"], ".worktrees");
+    }
+
+    #[test]
+    fn test_markdown_lists_nested_tables() {
+        let output = markdown::<PolicyConfig>().unwrap();
+        assert!(output.starts_with("## PolicyConfig"));
+        assert!(output.contains("## `[ai]`"));
+        assert!(output.contains("(curl \\| sh, etc.)"));
+        assert!(output.contains("| `model` | string | `\"gemini-3-flash\"` |"));
+        assert!(output.contains("| `wrapper` | string (optional) | unset |"));
+        assert!(
+            output.contains("| `level` | `\"permissive\"` \\| `\"moderate\"` \\| `\"strict\"` |")
+        );
+
+        let output = markdown::<SupervisorConfig>().unwrap();
+        assert!(output.contains("## `[escalation.webhook]`"));
+    }
+}
@@ -0,0 +1,53 @@
+//! Secret scanning configuration.
+
+use serde::{Deserialize, Serialize};
+
+/// What happens to a tool call whose input contains a secret.
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, Serialize, Deserialize)]
+#[cfg_attr(test, derive(schemars::JsonSchema))]
+#[serde(rename_all = "lowercase")]
+pub enum SecretAction {
+    /// Ask the supervisor.
+    #[default]
+    Escalate,
+    /// Deny the call.
+    Deny,
+}
+
+/// Configuration for scanning Write, Edit and Bash inputs for credentials.
+///
+/// ```toml
+/// [secrets]
+/// action = "deny"
+/// ```
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[cfg_attr(test, derive(schemars::JsonSchema))]
+#[serde(default)]
+pub struct SecretScanConfig {
+    /// Whether written content and commands are scanned for secrets.
+    pub enabled: bool,
+    /// What to do with a call that contains a secret.
+    pub action: SecretAction,
+}
+
+impl Default for SecretScanConfig {
+    fn default() -> Self {
+        Self {
+            enabled: true,
+            action: SecretAction::Escalate,
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_secret_scan_config_deserialize() {
+        let config: SecretScanConfig = toml::from_str(r#"action = "deny""#).unwrap();
+        assert!(config.enabled);
+        assert_eq!(config.action, SecretAction::Deny);
+        assert_eq!(SecretScanConfig::default().action, SecretAction::Escalate);
+    }
+}
@@ -0,0 +1,69 @@
+//! File snapshot configuration.
+
+use std::path::PathBuf;
+use std::time::Duration;
+
+use serde::{Deserialize, Serialize};
+
+/// Configuration for snapshotting files before approved writes.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[cfg_attr(test, derive(schemars::JsonSchema))]
+#[serde(default)]
+pub struct SnapshotConfig {
+    /// Whether Write/Edit targets are snapshotted before approval.
+    pub enabled: bool,
+    /// Snapshot directory; a relative path is resolved against the session
+    /// working directory.
+    pub dir: PathBuf,
+    /// Files larger than this are not snapshotted.
+    pub max_file_bytes: u64,
+    /// Snapshots kept per file and session; the oldest are evicted first.
+    pub max_per_file: usize,
+    /// Longest a snapshot may delay an approval, in milliseconds.
+    pub timeout_ms: u64,
+}
+
+impl Default for SnapshotConfig {
+    fn default() -> Self {
+        Self {
+            enabled: false,
+            dir: super::snapshots_dir(),
+            max_file_bytes: 10 * 1024 * 1024,
+            max_per_file: 20,
+            timeout_ms: 500,
+        }
+    }
+}
+
+impl SnapshotConfig {
+    /// Longest a snapshot may delay an approval.
+    #[must_use]
+    pub fn timeout(&self) -> Duration {
+        Duration::from_millis(self.timeout_ms)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_snapshot_config_default() {
+        let config = SnapshotConfig::default();
+        assert!(!config.enabled);
+        assert_eq!(config.dir, crate::config::snapshots_dir());
+        assert_eq!(config.timeout(), Duration::from_millis(500));
+    }
+
+    #[test]
+    fn test_snapshot_config_deserialize() {
+        let toml_str = r"
+            enabled = true
+            max_per_file = 3
+        ";
+        let config: SnapshotConfig = toml::from_str(toml_str).unwrap();
+        assert!(config.enabled);
+        assert_eq!(config.max_per_file, 3);
+        assert_eq!(config.max_file_bytes, 10 * 1024 * 1024);
+    }
+}
@@ -4,11 +4,17 @@ use serde::{Deserialize, Serialize};
 
 /// Configuration for the Stop hook handler.
 #[derive(Debug, Clone, Serialize, Deserialize)]
+#[cfg_attr(test, derive(schemars::JsonSchema))]
 pub struct StopConfig {
     /// Maximum number of iterations before allowing stop.
     #[serde(default = "default_max_iterations")]
     pub max_iterations: u32,
 
+    /// Maximum number of iterations of each subagent before allowing its
+    /// stop; counted apart from the main conversation.
+    #[serde(default = "default_max_subagent_iterations")]
+    pub max_subagent_iterations: u32,
+
     /// Whether to force continue on stop events.
     #[serde(default)]
     pub force_continue: bool,
@@ -20,12 +26,31 @@ pub struct StopConfig {
     /// Phrases that indicate the task is incomplete.
     #[serde(default = "default_incomplete_phrases")]
     pub incomplete_phrases: Vec<String>,
+
+    /// Block a stop when the completion confidence of Claude's final
+    /// message is below this, from 0 to 1; the default blocks a tie between
+    /// completion and incomplete phrases.
+    #[serde(default = "default_min_completion_confidence")]
+    pub min_completion_confidence: f32,
+
+    /// Allow a stop while items of Claude's `TodoWrite` plan are still
+    /// pending or in progress.
+    #[serde(default)]
+    pub allow_stop_with_open_todos: bool,
 }
 
 fn default_max_iterations() -> u32 {
     50
 }
 
+fn default_max_subagent_iterations() -> u32 {
+    10
+}
+
+fn default_min_completion_confidence() -> f32 {
+    0.6
+}
+
 fn default_completion_phrases() -> Vec<String> {
     vec![
         "task is complete".to_string(),
@@ -51,9 +76,23 @@ impl Default for StopConfig {
     fn default() -> Self {
         Self {
             max_iterations: default_max_iterations(),
+            max_subagent_iterations: default_max_subagent_iterations(),
             force_continue: false,
             completion_phrases: default_completion_phrases(),
             incomplete_phrases: default_incomplete_phrases(),
+            min_completion_confidence: default_min_completion_confidence(),
+            allow_stop_with_open_todos: false,
+        }
+    }
+}
+
+impl StopConfig {
+    /// Maximum iterations of the main conversation (`None`) or a subagent.
+    #[must_use]
+    pub fn max_iterations_for(&self, agent_id: Option<&str>) -> u32 {
+        match agent_id {
+            Some(_) => self.max_subagent_iterations,
+            None => self.max_iterations,
         }
     }
 }
@@ -66,6 +105,7 @@ mod tests {
     fn test_stop_config_default() {
         let config = StopConfig::default();
         assert_eq!(config.max_iterations, 50);
+        assert_eq!(config.max_iterations_for(Some("agent-1")), 10);
         assert!(!config.force_continue);
         assert!(!config.completion_phrases.is_empty());
         assert!(!config.incomplete_phrases.is_empty());
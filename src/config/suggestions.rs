@@ -0,0 +1,40 @@
+//! CLAUDE.md suggestion configuration.
+
+use serde::{Deserialize, Serialize};
+
+use crate::audit::DEFAULT_MAX_SUGGESTIONS;
+
+/// Configuration for the CLAUDE.md additions suggested after each session.
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+#[cfg_attr(test, derive(schemars::JsonSchema))]
+#[serde(default)]
+pub struct SuggestionConfig {
+    /// Whether suggestions are written to the session's run directory.
+    pub enabled: bool,
+    /// Most suggestions kept for a session.
+    pub max_suggestions: usize,
+    /// Have the AI client reword the suggestions before they are written.
+    pub ai_polish: bool,
+}
+
+impl Default for SuggestionConfig {
+    fn default() -> Self {
+        Self {
+            enabled: true,
+            max_suggestions: DEFAULT_MAX_SUGGESTIONS,
+            ai_polish: false,
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_suggestion_config_deserialize() {
+        let config: SuggestionConfig = toml::from_str("ai_polish = true").unwrap();
+        assert!(config.enabled && config.ai_polish);
+        assert_eq!(config.max_suggestions, DEFAULT_MAX_SUGGESTIONS);
+    }
+}
@@ -0,0 +1,103 @@
+//! Per-tool timeout configuration.
+
+use std::collections::HashMap;
+use std::time::Duration;
+
+use serde::{Deserialize, Serialize};
+
+/// What to do when an approved tool call produces no result in time.
+#[derive(Debug, Clone, Copy, Serialize, Deserialize, Default, PartialEq, Eq)]
+#[cfg_attr(test, derive(schemars::JsonSchema))]
+#[serde(rename_all = "lowercase")]
+pub enum HungToolAction {
+    /// Warn and keep waiting.
+    #[default]
+    Warn,
+    /// Kill the session.
+    Terminate,
+    /// Ask the decision backend whether to keep waiting.
+    Escalate,
+}
+
+/// Timeouts between an approved tool call and its result.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[cfg_attr(test, derive(schemars::JsonSchema))]
+pub struct ToolTimeoutConfig {
+    /// Whether hung tool calls are detected at all.
+    #[serde(default = "default_enabled")]
+    pub enabled: bool,
+    /// Timeout in seconds for tools without an entry in `per_tool`.
+    #[serde(default = "default_timeout_secs")]
+    pub default_secs: u64,
+    /// Timeouts in seconds keyed by tool name.
+    #[serde(default = "default_per_tool")]
+    pub per_tool: HashMap<String, u64>,
+    /// What to do when a tool call times out.
+    #[serde(default)]
+    pub on_timeout: HungToolAction,
+}
+
+fn default_enabled() -> bool {
+    true
+}
+
+fn default_timeout_secs() -> u64 {
+    120
+}
+
+fn default_per_tool() -> HashMap<String, u64> {
+    HashMap::from([("Bash".to_string(), 600)])
+}
+
+impl Default for ToolTimeoutConfig {
+    fn default() -> Self {
+        Self {
+            enabled: default_enabled(),
+            default_secs: default_timeout_secs(),
+            per_tool: default_per_tool(),
+            on_timeout: HungToolAction::default(),
+        }
+    }
+}
+
+impl ToolTimeoutConfig {
+    /// Timeout for the given tool.
+    #[must_use]
+    pub fn timeout_for(&self, tool_name: &str) -> Duration {
+        let secs = self
+            .per_tool
+            .get(tool_name)
+            .copied()
+            .unwrap_or(self.default_secs);
+        Duration::from_secs(secs)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_tool_timeout_config_default() {
+        let config = ToolTimeoutConfig::default();
+        assert!(config.enabled);
+        assert_eq!(config.timeout_for("Bash"), Duration::from_mins(10));
+        assert_eq!(config.timeout_for("Read"), Duration::from_mins(2));
+        assert_eq!(config.on_timeout, HungToolAction::Warn);
+    }
+
+    #[test]
+    fn test_tool_timeout_config_deserialize() {
+        let toml_str = r#"
+            default_secs = 30
+            on_timeout = "escalate"
+
+            [per_tool]
+            WebFetch = 90
+        "#;
+        let config: ToolTimeoutConfig = toml::from_str(toml_str).unwrap();
+        assert_eq!(config.timeout_for("WebFetch"), Duration::from_secs(90));
+        assert_eq!(config.timeout_for("Bash"), Duration::from_secs(30));
+        assert_eq!(config.on_timeout, HungToolAction::Escalate);
+    }
+}
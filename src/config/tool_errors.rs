@@ -0,0 +1,42 @@
+//! Detection of failed tool calls in tool results.
+
+use serde::{Deserialize, Serialize};
+
+/// How tool results are recognized as errors.
+///
+/// A result that says whether it failed, through an `is_error` flag or an
+/// error `type`, is taken at its word. Only results that say neither are
+/// matched against `patterns`.
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+#[cfg_attr(test, derive(schemars::JsonSchema))]
+pub struct ToolErrorConfig {
+    /// Regular expressions matched against the start of results that do not
+    /// say whether they failed.
+    #[serde(default = "default_patterns")]
+    pub patterns: Vec<String>,
+}
+
+fn default_patterns() -> Vec<String> {
+    vec![r"^Error:".to_string(), r"^<tool_use_error>".to_string()]
+}
+
+impl Default for ToolErrorConfig {
+    fn default() -> Self {
+        Self {
+            patterns: default_patterns(),
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_tool_error_config_deserialize() {
+        let config: ToolErrorConfig = toml::from_str(r#"patterns = ["^fatal:"]"#).unwrap();
+        assert_eq!(config.patterns, ["^fatal:"]);
+        let config: ToolErrorConfig = toml::from_str("").unwrap();
+        assert_eq!(config, ToolErrorConfig::default());
+    }
+}
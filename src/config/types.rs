@@ -1,15 +1,21 @@
 //! Configuration types.
 
 use std::collections::HashSet;
+use std::path::PathBuf;
 
 use serde::{Deserialize, Serialize};
 
 use crate::supervisor::PolicyLevel;
 
-use super::{StopConfig, WorktreeConfig};
+use super::{
+    BlastRadiusConfig, BudgetConfig, ContextRecoveryConfig, DashboardEventConfig, EscalationConfig,
+    HistoryConfig, IdleNudgeConfig, RedactionConfig, RiskConfig, SnapshotConfig, StopConfig,
+    ToolErrorConfig, ToolTimeoutConfig, WorktreeConfig,
+};
 
 /// AI provider kind.
 #[derive(Debug, Clone, Serialize, Deserialize, Default, PartialEq)]
+#[cfg_attr(test, derive(schemars::JsonSchema))]
 #[serde(rename_all = "lowercase")]
 pub enum ProviderKind {
     #[default]
@@ -19,6 +25,7 @@ pub enum ProviderKind {
 
 /// Configuration for the AI supervisor client.
 #[derive(Debug, Clone, Serialize, Deserialize)]
+#[cfg_attr(test, derive(schemars::JsonSchema))]
 pub struct AiConfig {
     /// Provider to use (gemini or claude).
     #[serde(default)]
@@ -35,6 +42,9 @@ pub struct AiConfig {
     /// Environment variable name for the API key.
     #[serde(default = "default_api_key_env")]
     pub api_key_env: String,
+    /// Limit on in-flight AI requests across every session sharing the client.
+    #[serde(default)]
+    pub max_concurrent_requests: Option<usize>,
 }
 
 fn default_model() -> String {
@@ -61,12 +71,14 @@ impl Default for AiConfig {
             max_tokens: default_max_tokens(),
             base_url: default_base_url(),
             api_key_env: default_api_key_env(),
+            max_concurrent_requests: None,
         }
     }
 }
 
 /// Configuration for the supervisor.
 #[derive(Debug, Clone, Serialize, Deserialize)]
+#[cfg_attr(test, derive(schemars::JsonSchema))]
 #[allow(clippy::struct_excessive_bools)]
 pub struct SupervisorConfig {
     #[serde(default)]
@@ -79,6 +91,21 @@ pub struct SupervisorConfig {
     pub denied_tools: HashSet<String>,
     #[serde(default)]
     pub ai_supervisor: bool,
+    /// How escalated tool calls are decided.
+    #[serde(default)]
+    pub escalation: EscalationConfig,
+    /// Timeouts for approved tool calls that never produce a result.
+    #[serde(default)]
+    pub tool_timeouts: ToolTimeoutConfig,
+    /// How tool results are recognized as errors.
+    #[serde(default)]
+    pub tool_errors: ToolErrorConfig,
+    /// Limits on the session's cumulative mutating operations.
+    #[serde(default)]
+    pub blast_radius: BlastRadiusConfig,
+    /// Escalation on the summed risk of the session's recent tool calls.
+    #[serde(default)]
+    pub risk: RiskConfig,
     #[serde(default)]
     pub stop: StopConfig,
     #[serde(default)]
@@ -89,6 +116,97 @@ pub struct SupervisorConfig {
     /// Output raw untruncated events (verbose mode).
     #[serde(default)]
     pub raw_mode: bool,
+    /// Secret redaction for AI context and audit storage.
+    #[serde(default)]
+    pub redaction: RedactionConfig,
+    /// Disable the Bash sandbox for this session's hooks.
+    #[serde(default)]
+    pub no_sandbox: bool,
+    /// Record the calls the policy would deny or escalate and allow them,
+    /// in the runner and the session's hooks.
+    #[serde(default)]
+    pub policy_dry_run: bool,
+    /// Snapshots of files before approved writes.
+    #[serde(default)]
+    pub snapshots: SnapshotConfig,
+    /// Repositories the session spans; the first is the working directory.
+    #[serde(default)]
+    pub repos: Vec<PathBuf>,
+    /// Event history kept as escalation context.
+    #[serde(default)]
+    pub history: HistoryConfig,
+    /// Seconds to wait for Claude Code's init event before checking its
+    /// stderr for login errors.
+    #[serde(default = "default_startup_timeout_secs")]
+    pub startup_timeout_secs: u64,
+    /// Seconds each knowledge source may take to load before supervision
+    /// starts without it.
+    #[serde(default = "default_knowledge_timeout_secs")]
+    pub knowledge_timeout_secs: u64,
+    /// Most bytes printed for one event, tool input or tool result.
+    #[serde(default = "default_max_output_bytes")]
+    pub max_output_bytes: usize,
+    /// Print Claude's thinking blocks.
+    #[serde(default = "default_show_thinking")]
+    pub show_thinking: bool,
+    /// Claude Code binary to run: a name looked up in `PATH`, or a path.
+    #[serde(default = "default_claude_binary")]
+    pub claude_binary: PathBuf,
+    /// Wall-clock limit for the session in minutes, counted from the init
+    /// event (none by default).
+    #[serde(default)]
+    pub max_duration_mins: Option<u64>,
+    /// Stop the session clock while the session is paused.
+    #[serde(default)]
+    pub pause_stops_clock: bool,
+    /// Recovery of sessions that exhaust Claude's context window.
+    #[serde(default)]
+    pub context_recovery: ContextRecoveryConfig,
+    /// Nudging of sessions that stop producing events mid-task.
+    #[serde(default)]
+    pub idle_nudge: IdleNudgeConfig,
+    /// Cost limit of the session and alerts before it is reached.
+    #[serde(default)]
+    pub budget: BudgetConfig,
+    /// Abort the run when a startup check, such as validating the AI model,
+    /// fails instead of warning.
+    #[serde(default)]
+    pub strict_startup: bool,
+    /// Merge the session transcript Claude Code writes to disk with its
+    /// stdout, so events only one of them carries are supervised.
+    #[serde(default = "default_merge_transcript")]
+    pub merge_transcript: bool,
+    /// Directory each finished run's artifact bundle is written to; no
+    /// bundle is written when unset.
+    #[serde(default)]
+    pub artifacts_dir: Option<PathBuf>,
+    /// What dashboard clients are sent of each event.
+    #[serde(default)]
+    pub dashboard: DashboardEventConfig,
+}
+
+fn default_startup_timeout_secs() -> u64 {
+    crate::supervisor::DEFAULT_STARTUP_TIMEOUT_SECS
+}
+
+fn default_knowledge_timeout_secs() -> u64 {
+    crate::supervisor::DEFAULT_KNOWLEDGE_TIMEOUT.as_secs()
+}
+
+fn default_max_output_bytes() -> usize {
+    crate::display::DEFAULT_MAX_OUTPUT_BYTES
+}
+
+fn default_merge_transcript() -> bool {
+    true
+}
+
+fn default_show_thinking() -> bool {
+    true
+}
+
+fn default_claude_binary() -> PathBuf {
+    PathBuf::from(crate::cli::DEFAULT_CLAUDE_BINARY)
 }
 
 impl Default for SupervisorConfig {
@@ -102,10 +220,35 @@ impl Default for SupervisorConfig {
                 .collect(),
             denied_tools: HashSet::new(),
             ai_supervisor: true,
+            escalation: EscalationConfig::default(),
+            tool_timeouts: ToolTimeoutConfig::default(),
+            tool_errors: ToolErrorConfig::default(),
+            blast_radius: BlastRadiusConfig::default(),
+            risk: RiskConfig::default(),
             stop: StopConfig::default(),
             worktree: WorktreeConfig::default(),
             show_activity: false,
             raw_mode: true,
+            redaction: RedactionConfig::default(),
+            no_sandbox: false,
+            policy_dry_run: false,
+            snapshots: SnapshotConfig::default(),
+            repos: Vec::new(),
+            history: HistoryConfig::default(),
+            startup_timeout_secs: default_startup_timeout_secs(),
+            knowledge_timeout_secs: default_knowledge_timeout_secs(),
+            max_output_bytes: default_max_output_bytes(),
+            show_thinking: default_show_thinking(),
+            claude_binary: default_claude_binary(),
+            max_duration_mins: None,
+            pause_stops_clock: false,
+            context_recovery: ContextRecoveryConfig::default(),
+            idle_nudge: IdleNudgeConfig::default(),
+            budget: BudgetConfig::default(),
+            strict_startup: false,
+            merge_transcript: default_merge_transcript(),
+            artifacts_dir: None,
+            dashboard: DashboardEventConfig::default(),
         }
     }
 }
@@ -0,0 +1,533 @@
+//! Validation of config files and run settings.
+//!
+//! A config file whose values do not deserialize is checked against its
+//! [schema](super::schema), so that every wrong type and unknown value is
+//! reported with its key path at once, rather than serde's first error at a
+//! byte offset. Settings that deserialize are then checked for values out of
+//! range and options that contradict each other. Each problem is a
+//! [`ConfigIssue`]; `config validate` lists them, and `run` refuses to start
+//! while there are any.
+
+use std::collections::BTreeSet;
+use std::fmt;
+use std::path::Path;
+
+use super::schema::{FieldType, Schema};
+use super::{AiConfig, DecisionBackendKind, PolicyConfig, SupervisorConfig};
+use crate::ai::edit_distance;
+
+/// A problem with one config value.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct ConfigIssue {
+    /// Dotted key path of the value, such as `rate_limits.Bash.max`.
+    pub path: String,
+    /// What is wrong with the value.
+    pub message: String,
+    /// How to fix it, if there is more to say than the message.
+    pub hint: Option<String>,
+}
+
+impl ConfigIssue {
+    /// Create an issue with the value at `path`.
+    #[must_use]
+    pub fn new(path: impl Into<String>, message: impl Into<String>) -> Self {
+        Self {
+            path: path.into(),
+            message: message.into(),
+            hint: None,
+        }
+    }
+
+    /// Add a hint on how to fix the issue.
+    #[must_use]
+    pub fn with_hint(mut self, hint: impl Into<String>) -> Self {
+        self.hint = Some(hint.into());
+        self
+    }
+}
+
+impl fmt::Display for ConfigIssue {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        if self.path.is_empty() {
+            f.write_str(&self.message)?;
+        } else {
+            write!(f, "{}: {}", self.path, self.message)?;
+        }
+        if let Some(ref hint) = self.hint {
+            write!(f, "\n  hint: {hint}")?;
+        }
+        Ok(())
+    }
+}
+
+/// One issue per line, each hint on the line below its issue.
+#[must_use]
+pub fn format_issues(issues: &[ConfigIssue]) -> String {
+    issues
+        .iter()
+        .map(|issue| format!("  {}", issue.to_string().replace('\n', "\n  ")))
+        .collect::<Vec<_>>()
+        .join("\n")
+}
+
+/// Issues with the values of `table` against `schema`.
+///
+/// Keys the schema does not describe are left alone.
+#[must_use]
+pub fn check_schema(schema: &Schema, table: &toml::Table) -> Vec<ConfigIssue> {
+    let mut issues = Vec::new();
+    check_table(schema, table, "", &mut issues);
+    issues
+}
+
+fn check_table(schema: &Schema, table: &toml::Table, path: &str, issues: &mut Vec<ConfigIssue>) {
+    for field in &schema.fields {
+        if let Some(value) = table.get(field.name) {
+            check_value(&field.ty, value, &join(path, field.name), issues);
+        }
+    }
+}
+
+fn check_value(ty: &FieldType, value: &toml::Value, path: &str, issues: &mut Vec<ConfigIssue>) {
+    let mismatch = |expected: &str| {
+        ConfigIssue::new(
+            path,
+            format!("expected {expected}, found {}", describe(value)),
+        )
+    };
+    match (ty, value) {
+        (FieldType::Optional(inner), _) => check_value(inner, value, path, issues),
+        (FieldType::Integer, toml::Value::Integer(number)) if *number < 0 => {
+            issues.push(ConfigIssue::new(
+                path,
+                format!("must not be negative, found {number}"),
+            ));
+        }
+        (FieldType::Number, toml::Value::Float(number)) if *number < 0.0 => {
+            issues.push(ConfigIssue::new(
+                path,
+                format!("must not be negative, found {number}"),
+            ));
+        }
+        (FieldType::Boolean, toml::Value::Boolean(_))
+        | (FieldType::String | FieldType::Path, toml::Value::String(_))
+        | (FieldType::Integer | FieldType::Number, toml::Value::Integer(_))
+        | (FieldType::Number, toml::Value::Float(_)) => {}
+        (FieldType::Enum(values), toml::Value::String(text)) => {
+            if !values.contains(&text.as_str()) {
+                issues.push(unknown_value(path, text, values));
+            }
+        }
+        (FieldType::List(item) | FieldType::Set(item), toml::Value::Array(items)) => {
+            for (index, value) in items.iter().enumerate() {
+                check_value(item, value, &format!("{path}[{index}]"), issues);
+            }
+        }
+        (FieldType::Map(inner), toml::Value::Table(table)) => {
+            for (key, value) in table {
+                check_value(inner, value, &join(path, key), issues);
+            }
+        }
+        (FieldType::Table(schema), toml::Value::Table(table)) => {
+            check_table(schema, table, path, issues);
+        }
+        (FieldType::Boolean, _) => issues.push(mismatch("true or false")),
+        (FieldType::Integer, _) => issues.push(mismatch("a whole number")),
+        (FieldType::Number, _) => issues.push(mismatch("a number")),
+        (FieldType::String, _) => issues.push(mismatch("a string")),
+        (FieldType::Path, _) => issues.push(mismatch("a path string")),
+        (FieldType::Enum(values), _) => {
+            issues.push(mismatch(&format!("one of {}", quoted(values))));
+        }
+        (FieldType::List(_) | FieldType::Set(_), _) => issues.push(mismatch("a list")),
+        (FieldType::Map(_) | FieldType::Table(_), _) => issues.push(mismatch("a table")),
+    }
+}
+
+/// An enum value that is not one of `values`, suggesting the closest one.
+fn unknown_value(path: &str, text: &str, values: &[&str]) -> ConfigIssue {
+    let expected = format!("expected one of {}", quoted(values));
+    let closest = values
+        .iter()
+        .map(|value| (edit_distance(text, value), value))
+        .filter(|(distance, _)| *distance <= text.len().max(6) / 3)
+        .min_by_key(|(distance, _)| *distance);
+    let hint = match closest {
+        Some((_, value)) => format!("did you mean \"{value}\"? {expected}"),
+        None => expected,
+    };
+    ConfigIssue::new(path, format!("unknown value \"{text}\"")).with_hint(hint)
+}
+
+/// `values` quoted and separated by commas.
+fn quoted(values: &[&str]) -> String {
+    values
+        .iter()
+        .map(|value| format!("\"{value}\""))
+        .collect::<Vec<_>>()
+        .join(", ")
+}
+
+/// What kind of TOML value `value` is, for messages.
+fn describe(value: &toml::Value) -> String {
+    match value {
+        toml::Value::String(text) => format!("\"{text}\""),
+        toml::Value::Integer(number) => number.to_string(),
+        toml::Value::Float(number) => number.to_string(),
+        toml::Value::Boolean(flag) => flag.to_string(),
+        toml::Value::Datetime(_) => "a date".to_string(),
+        toml::Value::Array(_) => "a list".to_string(),
+        toml::Value::Table(_) => "a table".to_string(),
+    }
+}
+
+fn join(path: &str, key: &str) -> String {
+    if path.is_empty() {
+        key.to_string()
+    } else {
+        format!("{path}.{key}")
+    }
+}
+
+impl PolicyConfig {
+    /// Values out of range and options contradicting each other.
+    #[must_use]
+    pub fn validate(&self) -> Vec<ConfigIssue> {
+        let mut issues = Vec::new();
+        if self.ai.max_tokens == 0 {
+            issues.push(ConfigIssue::new("ai.max_tokens", "must be at least 1"));
+        }
+        for (tool, limit) in &self.rate_limits {
+            if limit.max == 0 {
+                issues.push(
+                    ConfigIssue::new(format!("rate_limits.{tool}.max"), "must be at least 1")
+                        .with_hint(format!(
+                            "to stop every call, add \"{tool}\" to tools.denied"
+                        )),
+                );
+            }
+            if limit.per_seconds == 0 {
+                issues.push(ConfigIssue::new(
+                    format!("rate_limits.{tool}.per_seconds"),
+                    "must be at least 1",
+                ));
+            }
+        }
+        for (key, sampling) in &self.escalation_sampling {
+            let rate = sampling.escalate_sample_rate;
+            if !(0.0..=1.0).contains(&rate) {
+                issues.push(ConfigIssue::new(
+                    format!("escalation_sampling.{key}.escalate_sample_rate"),
+                    format!("must be between 0 and 1, found {rate}"),
+                ));
+            }
+        }
+        for (index, pattern) in self.bash.blocked_regexes.iter().enumerate() {
+            check_regex(
+                &format!("bash.blocked_regexes[{index}]"),
+                pattern,
+                &mut issues,
+            );
+        }
+        for (index, entry) in self.blocklist.iter().enumerate() {
+            check_regex(
+                &format!("blocklist[{index}].pattern"),
+                &entry.pattern,
+                &mut issues,
+            );
+        }
+        let both: BTreeSet<_> = self
+            .tools
+            .allowed
+            .intersection(&self.tools.denied)
+            .collect();
+        for tool in both {
+            issues.push(
+                ConfigIssue::new(
+                    "tools.allowed",
+                    format!("\"{tool}\" is also in tools.denied"),
+                )
+                .with_hint("list each tool in only one of tools.allowed and tools.denied"),
+            );
+        }
+        issues
+    }
+}
+
+/// Add an issue if `pattern` is not a valid regex.
+fn check_regex(path: &str, pattern: &str, issues: &mut Vec<ConfigIssue>) {
+    if let Err(e) = regex::Regex::new(pattern) {
+        let reason = match e {
+            regex::Error::Syntax(ref text) => text.lines().last().unwrap_or_default().to_string(),
+            ref e => e.to_string(),
+        };
+        issues.push(ConfigIssue::new(
+            path,
+            format!(
+                "is not a valid regex: {}",
+                reason.trim_start_matches("error: ")
+            ),
+        ));
+    }
+}
+
+impl SupervisorConfig {
+    /// Values out of range and options contradicting each other, for a
+    /// session run in `cwd`.
+    #[must_use]
+    pub fn validate(&self, cwd: &Path) -> Vec<ConfigIssue> {
+        let mut issues = Vec::new();
+        if self.worktree.enabled && !cwd.ancestors().any(|dir| dir.join(".git").exists()) {
+            issues.push(
+                ConfigIssue::new(
+                    "worktree.enabled",
+                    format!(
+                        "worktrees need a git repository, but {} is not in one",
+                        cwd.display()
+                    ),
+                )
+                .with_hint("run from inside a git repository, or drop --worktree"),
+            );
+        }
+        let decided_by_ai = self.ai_supervisor
+            && !self.escalation.interactive.enabled
+            && self.escalation.backend == DecisionBackendKind::Ai;
+        if decided_by_ai {
+            check_api_key(&AiConfig::default().api_key_env, &mut issues);
+        }
+        if self.stop.max_iterations == 0 {
+            issues.push(ConfigIssue::new(
+                "stop.max_iterations",
+                "must be at least 1",
+            ));
+        }
+        if self.max_output_bytes == 0 {
+            issues.push(
+                ConfigIssue::new("max_output_bytes", "must be at least 1")
+                    .with_hint("leave --max-output-bytes out to keep the default"),
+            );
+        }
+        if let Some(ref listen) = self.dashboard.listen {
+            if self.dashboard.listen_address().is_none() {
+                issues.push(
+                    ConfigIssue::new(
+                        "dashboard.listen",
+                        format!("\"{listen}\" is not a host:port address"),
+                    )
+                    .with_hint("for example --dashboard 127.0.0.1:3000"),
+                );
+            }
+        }
+        issues
+    }
+}
+
+/// Add an issue if the environment variable `name` holding the AI API key
+/// is not set.
+fn check_api_key(name: &str, issues: &mut Vec<ConfigIssue>) {
+    if std::env::var_os(name).is_none_or(|key| key.is_empty()) {
+        issues.push(
+            ConfigIssue::new(
+                "ai.api_key_env",
+                format!("AI supervision is on, but {name} is not set"),
+            )
+            .with_hint(format!("export {name}, or pass --no-ai")),
+        );
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::config::schema::ConfigSchema;
+    use crate::config::{RateLimitConfig, SamplingConfig};
+
+    fn schema_issues(content: &str) -> Vec<String> {
+        let table: toml::Table = toml::from_str(content).unwrap();
+        check_schema(&PolicyConfig::schema(), &table)
+            .iter()
+            .map(ToString::to_string)
+            .collect()
+    }
+
+    fn policy_issues(config: &PolicyConfig) -> Vec<String> {
+        config.validate().iter().map(ToString::to_string).collect()
+    }
+
+    #[test]
+    fn test_unknown_enum_value() {
+        assert_eq!(
+            schema_issues(r#"level = "agressive""#),
+            ["level: unknown value \"agressive\"\n  \
+              hint: expected one of \"permissive\", \"moderate\", \"strict\""]
+        );
+        assert_eq!(
+            schema_issues("[git]\nallow_push = \"escalat\""),
+            ["git.allow_push: unknown value \"escalat\"\n  \
+              hint: did you mean \"escalate\"? expected one of \"allow\", \"escalate\", \"deny\""]
+        );
+    }
+
+    #[test]
+    fn test_negative_integer() {
+        assert_eq!(
+            schema_issues("[rate_limits]\nBash = { max = -1, per_seconds = 60 }"),
+            ["rate_limits.Bash.max: must not be negative, found -1"]
+        );
+    }
+
+    #[test]
+    fn test_wrong_types_are_all_reported() {
+        assert_eq!(
+            schema_issues(
+                r#"
+                dry_run = "yes"
+                [bash]
+                blocked_patterns = ["ok", 3]
+                [tools]
+                allowed = "Read"
+                "#
+            ),
+            [
+                "dry_run: expected true or false, found \"yes\"",
+                "bash.blocked_patterns[1]: expected a string, found 3",
+                "tools.allowed: expected a list, found \"Read\"",
+            ]
+        );
+        assert!(schema_issues("level = \"strict\"\nunknown_key = 1").is_empty());
+    }
+
+    #[test]
+    fn test_zero_max_tokens() {
+        let mut config = PolicyConfig::default();
+        config.ai.max_tokens = 0;
+        assert_eq!(
+            policy_issues(&config),
+            ["ai.max_tokens: must be at least 1"]
+        );
+    }
+
+    #[test]
+    fn test_zero_rate_limit() {
+        let mut config = PolicyConfig::default();
+        config.rate_limits.insert(
+            "Bash".to_string(),
+            RateLimitConfig {
+                max: 0,
+                per_seconds: 0,
+            },
+        );
+        assert_eq!(
+            policy_issues(&config),
+            [
+                "rate_limits.Bash.max: must be at least 1\n  \
+                 hint: to stop every call, add \"Bash\" to tools.denied",
+                "rate_limits.Bash.per_seconds: must be at least 1",
+            ]
+        );
+    }
+
+    #[test]
+    fn test_sample_rate_out_of_range() {
+        let mut config = PolicyConfig::default();
+        config.escalation_sampling.insert(
+            "WebSearch".to_string(),
+            SamplingConfig {
+                escalate_sample_rate: 1.5,
+            },
+        );
+        assert_eq!(
+            policy_issues(&config),
+            ["escalation_sampling.WebSearch.escalate_sample_rate: must be between 0 and 1, found 1.5"]
+        );
+    }
+
+    #[test]
+    fn test_invalid_regexes() {
+        let mut config = PolicyConfig::default();
+        config.bash.blocked_regexes = vec![r"rm\s+-rf".to_string(), "rm (".to_string()];
+        assert_eq!(
+            policy_issues(&config),
+            ["bash.blocked_regexes[1]: is not a valid regex: unclosed group"]
+        );
+    }
+
+    #[test]
+    fn test_tool_both_allowed_and_denied() {
+        let mut config = PolicyConfig::default();
+        config.tools.allowed.insert("WebFetch".to_string());
+        config.tools.denied.insert("WebFetch".to_string());
+        assert_eq!(
+            policy_issues(&config),
+            ["tools.allowed: \"WebFetch\" is also in tools.denied\n  \
+              hint: list each tool in only one of tools.allowed and tools.denied"]
+        );
+    }
+
+    fn run_issues(config: &SupervisorConfig, cwd: &Path) -> Vec<String> {
+        config
+            .validate(cwd)
+            .iter()
+            .map(ToString::to_string)
+            .collect()
+    }
+
+    fn without_ai() -> SupervisorConfig {
+        SupervisorConfig {
+            ai_supervisor: false,
+            ..SupervisorConfig::default()
+        }
+    }
+
+    #[test]
+    fn test_worktree_outside_git() {
+        let dir = tempfile::tempdir().unwrap();
+        let mut config = without_ai();
+        config.worktree.enabled = true;
+        assert_eq!(
+            run_issues(&config, dir.path()),
+            [format!(
+                "worktree.enabled: worktrees need a git repository, but {} is not in one\n  \
+                 hint: run from inside a git repository, or drop --worktree",
+                dir.path().display()
+            )]
+        );
+
+        std::fs::create_dir(dir.path().join(".git")).unwrap();
+        std::fs::create_dir(dir.path().join("src")).unwrap();
+        assert!(run_issues(&config, &dir.path().join("src")).is_empty());
+    }
+
+    #[test]
+    fn test_missing_api_key() {
+        let mut issues = Vec::new();
+        check_api_key("CLAUDE_SUPERVISOR_TEST_UNSET_KEY", &mut issues);
+        assert_eq!(
+            issues.iter().map(ToString::to_string).collect::<Vec<_>>(),
+            ["ai.api_key_env: AI supervision is on, but CLAUDE_SUPERVISOR_TEST_UNSET_KEY is not set\n  \
+              hint: export CLAUDE_SUPERVISOR_TEST_UNSET_KEY, or pass --no-ai"]
+        );
+        check_api_key("PATH", &mut issues);
+        assert_eq!(issues.len(), 1);
+    }
+
+    #[test]
+    fn test_zero_run_limits() {
+        let dir = tempfile::tempdir().unwrap();
+        let mut config = without_ai();
+        config.stop.max_iterations = 0;
+        config.max_output_bytes = 0;
+        config.dashboard.listen = Some("localhost".to_string());
+        assert_eq!(
+            run_issues(&config, dir.path()),
+            [
+                "stop.max_iterations: must be at least 1",
+                "max_output_bytes: must be at least 1\n  \
+                 hint: leave --max-output-bytes out to keep the default",
+                "dashboard.listen: \"localhost\" is not a host:port address\n  \
+                 hint: for example --dashboard 127.0.0.1:3000",
+            ]
+        );
+        assert!(run_issues(&without_ai(), dir.path()).is_empty());
+    }
+}
@@ -6,6 +6,7 @@ use serde::{Deserialize, Serialize};
 
 /// Configuration for git worktree isolation.
 #[derive(Debug, Clone, Serialize, Deserialize)]
+#[cfg_attr(test, derive(schemars::JsonSchema))]
 pub struct WorktreeConfig {
     /// Whether worktree isolation is enabled.
     #[serde(default)]
@@ -2,10 +2,14 @@
 
 use serde::{Deserialize, Serialize};
 
+use uuid::Uuid;
+
 use super::SupervisorStatus;
-use crate::audit::SessionMetrics;
+use crate::audit::{CostShare, SessionMetrics};
+use crate::display::Verbosity;
+use crate::supervisor::{DecisionBreakdown, TaskLedger, TodoCounts, TodoItem};
 
-/// Response for GET /api/status endpoint.
+/// Response for GET /api/v1/status endpoint.
 #[derive(Debug, Clone, Serialize, Deserialize)]
 pub struct StatusResponse {
     /// Whether a client is connected to the SSE stream.
@@ -23,7 +27,8 @@ impl StatusResponse {
     }
 }
 
-/// Response for command endpoints (POST /api/stop, /api/continue, /api/kill).
+/// Response for command endpoints (POST /api/v1/stop, /api/v1/continue, /api/v1/kill,
+/// /api/v1/display).
 #[derive(Debug, Clone, Serialize, Deserialize)]
 pub struct CommandResponse {
     /// Whether the command was successful.
@@ -57,7 +62,18 @@ impl CommandResponse {
     }
 }
 
-/// Query parameters for GET /api/events endpoint.
+/// Request body for POST /api/v1/display; omitted options are left as they are.
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, Serialize, Deserialize)]
+pub struct DisplayRequest {
+    /// Print Claude's thinking, streamed or in whole messages.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub show_thinking: Option<bool>,
+    /// How much of tool inputs and results is printed.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub verbosity: Option<Verbosity>,
+}
+
+/// Query parameters for GET /api/v1/events endpoint.
 #[derive(Debug, Clone, Deserialize)]
 pub struct EventsQuery {
     /// Maximum number of events to return.
@@ -92,7 +108,7 @@ const fn default_limit() -> usize {
     100
 }
 
-/// Response for GET /api/metrics endpoint.
+/// Response for GET /api/v1/metrics endpoint.
 #[derive(Debug, Clone, Serialize, Deserialize)]
 pub struct MetricsResponse {
     /// Total number of events recorded.
@@ -101,6 +117,9 @@ pub struct MetricsResponse {
     pub allowed: u64,
     /// Number of denied actions.
     pub denied: u64,
+    /// Approvals and denials by who made them.
+    #[serde(default)]
+    pub by_source: DecisionBreakdown,
     /// Session-specific metrics, if available.
     #[serde(skip_serializing_if = "Option::is_none")]
     pub session: Option<SessionMetricsResponse>,
@@ -114,10 +133,18 @@ impl MetricsResponse {
             total_events,
             allowed,
             denied,
+            by_source: DecisionBreakdown::default(),
             session: None,
         }
     }
 
+    /// Break the decisions down by source (builder pattern).
+    #[must_use]
+    pub fn with_sources(mut self, by_source: DecisionBreakdown) -> Self {
+        self.by_source = by_source;
+        self
+    }
+
     /// Create a metrics response with session data.
     #[must_use]
     pub fn with_session(
@@ -130,6 +157,7 @@ impl MetricsResponse {
             total_events,
             allowed,
             denied,
+            by_source: DecisionBreakdown::default(),
             session: Some(session),
         }
     }
@@ -150,6 +178,10 @@ pub struct SessionMetricsResponse {
     pub cache_hits: u64,
     /// Estimated cost in USD cents.
     pub estimated_cost_cents: u64,
+    /// Highest blast radius score the session reached.
+    pub blast_radius_peak: u64,
+    /// Blast radius score at which the session escalates.
+    pub blast_radius_threshold: u64,
 }
 
 impl From<SessionMetrics> for SessionMetricsResponse {
@@ -161,6 +193,60 @@ impl From<SessionMetrics> for SessionMetricsResponse {
             api_calls: metrics.api_calls,
             cache_hits: metrics.cache_hits,
             estimated_cost_cents: metrics.estimated_cost_cents,
+            blast_radius_peak: metrics.blast_radius_peak,
+            blast_radius_threshold: metrics.blast_radius_threshold,
+        }
+    }
+}
+
+/// Query parameters for GET /api/v1/costs endpoint.
+#[derive(Debug, Clone, Default, Deserialize)]
+pub struct CostsQuery {
+    /// Restrict the breakdown to one audit session (all sessions if omitted).
+    #[serde(default)]
+    pub session: Option<Uuid>,
+}
+
+/// Response for GET /api/v1/costs endpoint (cost attribution panel).
+#[derive(Debug, Clone, Default, Serialize, Deserialize)]
+pub struct CostsResponse {
+    /// Sum of attributed cost in micro-dollars.
+    pub total_cost_micros: u64,
+    /// Approximate cost by tool name, most expensive first.
+    pub by_tool: Vec<CostShare>,
+    /// Approximate cost by touched file path, most expensive first.
+    pub by_file: Vec<CostShare>,
+}
+
+impl CostsResponse {
+    /// Create a costs response from per-tool and per-file shares.
+    #[must_use]
+    pub fn new(by_tool: Vec<CostShare>, by_file: Vec<CostShare>) -> Self {
+        Self {
+            total_cost_micros: by_tool.iter().map(|s| s.cost_micros).sum(),
+            by_tool,
+            by_file,
+        }
+    }
+}
+
+/// Response for GET /api/v1/todos endpoint.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct TodosResponse {
+    /// Claude's plan, in order.
+    pub items: Vec<TodoItem>,
+    /// Item counts by state.
+    #[serde(flatten)]
+    pub counts: TodoCounts,
+}
+
+impl TodosResponse {
+    /// Create a todos response from the ledger.
+    #[must_use]
+    pub fn new(ledger: &TaskLedger) -> Self {
+        Self {
+            items: ledger.items().to_vec(),
+            counts: ledger.counts(),
         }
     }
 }
@@ -230,7 +316,19 @@ mod tests {
             tool_calls: 5,
             approvals: 4,
             denials: 1,
+            by_source: DecisionBreakdown::default(),
             task: Some("Fix bug".to_string()),
+            kill_cause: None,
+            retry_hint: None,
+            permission_mode: None,
+            name: None,
+            claude_code_version: None,
+            remaining_secs: None,
+            blast_radius: 0.0,
+            blast_radius_threshold: None,
+            risk_score: 0,
+            risk_threshold: None,
+            todos: TaskLedger::default(),
         };
         let response = StatusResponse::new(status, true);
 
@@ -266,6 +364,8 @@ mod tests {
             api_calls: 10,
             cache_hits: 3,
             estimated_cost_cents: 5,
+            blast_radius_peak: 0,
+            blast_radius_threshold: 100,
         };
         let response = MetricsResponse::with_session(100, 80, 20, session);
 
@@ -275,6 +375,31 @@ mod tests {
         assert_eq!(session.input_tokens, 5000);
     }
 
+    #[test]
+    fn test_costs_response_total_matches_tools() {
+        let share = |key: &str, cost_micros| CostShare {
+            key: key.to_string(),
+            calls: 1,
+            cost_micros,
+            ..CostShare::default()
+        };
+        let response = CostsResponse::new(
+            vec![share("Bash", 300), share("Read", 200)],
+            vec![share("src/lib.rs", 200)],
+        );
+
+        assert_eq!(response.total_cost_micros, 500);
+        let json = serde_json::to_string(&response).unwrap();
+        assert!(json.contains("\"by_tool\""));
+        assert!(json.contains("\"key\":\"src/lib.rs\""));
+    }
+
+    #[test]
+    fn test_costs_query_deserialize() {
+        let query: CostsQuery = serde_json::from_str("{}").unwrap();
+        assert!(query.session.is_none());
+    }
+
     #[test]
     fn test_session_metrics_response_from() {
         use uuid::Uuid;
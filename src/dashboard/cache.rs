@@ -0,0 +1,218 @@
+//! Cached JSON bodies of polled endpoints, revalidated with `ETag`s.
+//!
+//! Pollers hit the status and metrics endpoints several times a second.
+//! Each endpoint keeps the body it last serialized with its own watch
+//! receiver: the body is rebuilt only when the supervisor published a new
+//! status, and a client that sends the current `ETag` back in
+//! `If-None-Match` gets an empty `304 Not Modified`.
+
+use std::hash::{DefaultHasher, Hash, Hasher};
+use std::sync::atomic::{AtomicU64, Ordering};
+use std::sync::Mutex;
+
+use axum::body::{Body, Bytes};
+use axum::http::header::{CACHE_CONTROL, CONTENT_TYPE, ETAG, IF_NONE_MATCH};
+use axum::http::{HeaderMap, HeaderValue, StatusCode};
+use axum::response::{IntoResponse, Response};
+use serde::Serialize;
+use tokio::sync::watch;
+
+use super::state::SupervisorStatus;
+
+/// `Cache-Control` of cached endpoints: clients may store the body, but
+/// must revalidate it on every request.
+pub const REVALIDATE: &str = "no-cache";
+
+/// A serialized JSON body and its entity tag.
+#[derive(Debug, Clone)]
+pub struct CachedBody {
+    /// JSON body, shared between responses.
+    body: Bytes,
+    /// Quoted entity tag of the body.
+    etag: HeaderValue,
+}
+
+impl CachedBody {
+    /// Serialize `value` and tag the body with its hash.
+    ///
+    /// # Errors
+    ///
+    /// Returns an error if `value` cannot be serialized.
+    ///
+    /// # Panics
+    ///
+    /// Never in practice: the tag is only quoted hex digits.
+    pub fn new<T: Serialize>(value: &T) -> Result<Self, serde_json::Error> {
+        let body = serde_json::to_vec(value)?;
+        let mut hasher = DefaultHasher::new();
+        body.hash(&mut hasher);
+        let etag = HeaderValue::from_str(&format!("\"{:016x}\"", hasher.finish()))
+            .expect("hex digits are a valid header value");
+        Ok(Self {
+            body: Bytes::from(body),
+            etag,
+        })
+    }
+
+    /// The JSON body.
+    #[must_use]
+    pub fn body(&self) -> &Bytes {
+        &self.body
+    }
+
+    /// The quoted entity tag.
+    #[must_use]
+    pub fn etag(&self) -> &HeaderValue {
+        &self.etag
+    }
+
+    /// Whether an `If-None-Match` header names this body.
+    ///
+    /// Weak tags match by their opaque part, and `*` matches any body.
+    #[must_use]
+    pub fn matches(&self, if_none_match: &HeaderValue) -> bool {
+        let Ok(tags) = if_none_match.to_str() else {
+            return false;
+        };
+        tags.split(',').map(str::trim).any(|tag| {
+            tag == "*" || tag.trim_start_matches("W/").as_bytes() == self.etag.as_bytes()
+        })
+    }
+
+    /// Respond with the body, or with `304 Not Modified` when the request's
+    /// `If-None-Match` names it.
+    #[must_use]
+    pub fn respond(&self, headers: &HeaderMap) -> Response {
+        let not_modified = headers
+            .get_all(IF_NONE_MATCH)
+            .iter()
+            .any(|value| self.matches(value));
+        let mut response = if not_modified {
+            StatusCode::NOT_MODIFIED.into_response()
+        } else {
+            let mut response = Response::new(Body::from(self.body.clone()));
+            response
+                .headers_mut()
+                .insert(CONTENT_TYPE, HeaderValue::from_static("application/json"));
+            response
+        };
+        let headers = response.headers_mut();
+        headers.insert(ETAG, self.etag.clone());
+        headers.insert(CACHE_CONTROL, HeaderValue::from_static(REVALIDATE));
+        response
+    }
+}
+
+/// Body built from the latest supervisor status, rebuilt only on change.
+#[derive(Debug)]
+pub struct StatusCache {
+    inner: Mutex<CacheInner>,
+    /// Number of times a body was serialized.
+    builds: AtomicU64,
+}
+
+#[derive(Debug)]
+struct CacheInner {
+    status_rx: watch::Receiver<SupervisorStatus>,
+    /// Extra key the body was built with, and the body.
+    entry: Option<(u64, CachedBody)>,
+}
+
+impl StatusCache {
+    /// Create a cache that watches `status_rx` for changes.
+    #[must_use]
+    pub fn new(status_rx: watch::Receiver<SupervisorStatus>) -> Self {
+        Self {
+            inner: Mutex::new(CacheInner {
+                status_rx,
+                entry: None,
+            }),
+            builds: AtomicU64::new(0),
+        }
+    }
+
+    /// Get the body for the current status.
+    ///
+    /// `key` covers inputs of `build` other than the status, such as the
+    /// number of connected clients; the body is rebuilt when the status
+    /// changed or `key` differs from the last build.
+    ///
+    /// # Errors
+    ///
+    /// Returns an error if the built value cannot be serialized.
+    ///
+    /// # Panics
+    ///
+    /// Panics if the internal `Mutex` is poisoned.
+    pub fn get<T: Serialize>(
+        &self,
+        key: u64,
+        build: impl FnOnce(&SupervisorStatus) -> T,
+    ) -> Result<CachedBody, serde_json::Error> {
+        let mut inner = self.inner.lock().expect("Mutex poisoned");
+        // A closed channel keeps its last status
+        let changed = inner.status_rx.has_changed().unwrap_or(false);
+        if let Some((built_key, ref body)) = inner.entry {
+            if !changed && built_key == key {
+                return Ok(body.clone());
+            }
+        }
+
+        let body = CachedBody::new(&build(&inner.status_rx.borrow_and_update()))?;
+        self.builds.fetch_add(1, Ordering::Relaxed);
+        inner.entry = Some((key, body.clone()));
+        Ok(body)
+    }
+
+    /// Number of times a body was serialized.
+    #[must_use]
+    pub fn builds(&self) -> u64 {
+        self.builds.load(Ordering::Relaxed)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_if_none_match_forms() {
+        let body = CachedBody::new(&serde_json::json!({"state": "idle"})).unwrap();
+        let etag = body.etag().to_str().unwrap().to_string();
+
+        assert!(body.matches(&HeaderValue::from_str(&etag).unwrap()));
+        assert!(body.matches(&HeaderValue::from_str(&format!("W/{etag}")).unwrap()));
+        assert!(body.matches(&HeaderValue::from_str(&format!("\"other\", {etag}")).unwrap()));
+        assert!(body.matches(&HeaderValue::from_static("*")));
+        assert!(!body.matches(&HeaderValue::from_static("\"other\"")));
+    }
+
+    #[test]
+    fn test_body_is_rebuilt_only_on_change() {
+        let (status_tx, status_rx) = watch::channel(SupervisorStatus::default());
+        let cache = StatusCache::new(status_rx);
+        let state = |status: &SupervisorStatus| status.state.clone();
+
+        let first = cache.get(0, state).unwrap();
+        for _ in 0..1000 {
+            let again = cache.get(0, state).unwrap();
+            assert_eq!(again.etag(), first.etag());
+        }
+        assert_eq!(cache.builds(), 1);
+
+        // A different key rebuilds, to the same tag for the same body
+        assert_eq!(cache.get(1, state).unwrap().etag(), first.etag());
+        assert_eq!(cache.builds(), 2);
+
+        status_tx.send_modify(|status| status.state = "running".to_string());
+        let changed = cache.get(1, state).unwrap();
+        assert_ne!(changed.etag(), first.etag());
+        assert_eq!(changed.body().as_ref(), b"\"running\"");
+        assert_eq!(cache.builds(), 3);
+
+        // The last status stays cached after the supervisor goes away
+        drop(status_tx);
+        assert_eq!(cache.get(1, state).unwrap().etag(), changed.etag());
+        assert_eq!(cache.builds(), 3);
+    }
+}
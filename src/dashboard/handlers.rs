@@ -3,15 +3,24 @@
 use std::convert::Infallible;
 use std::sync::Arc;
 
-use axum::extract::State;
+use axum::extract::{Query, State};
+use axum::http::{HeaderMap, StatusCode};
 use axum::response::sse::{Event, KeepAlive, Sse};
+use axum::response::{IntoResponse, Response};
 use axum::Json;
 use futures_util::stream::StreamExt;
 use tokio_stream::wrappers::BroadcastStream;
 
-use super::api::{CommandResponse, MetricsResponse, StatusResponse};
+use super::api::{
+    CommandResponse, CostsQuery, CostsResponse, DisplayRequest, MetricsResponse, StatusResponse,
+    TodosResponse,
+};
+use super::cache::{CachedBody, StatusCache};
+use super::openapi;
+use super::scrub::EventScrubber;
 use super::state::{DashboardCommand, DashboardState};
-use crate::audit::AuditLog;
+use crate::audit::{AuditLog, CostDimension};
+use crate::supervisor::{HealthMonitor, HealthReport};
 
 /// Application state shared across all handlers.
 #[derive(Clone)]
@@ -20,6 +29,14 @@ pub struct AppState {
     pub dashboard: Arc<DashboardState>,
     /// Optional audit log for metrics.
     pub audit: Option<Arc<AuditLog>>,
+    /// Last serialized status response.
+    pub status_cache: Arc<StatusCache>,
+    /// Last serialized metrics response.
+    pub metrics_cache: Arc<StatusCache>,
+    /// Health of the supervisor, for `/healthz`.
+    pub health: Option<HealthMonitor>,
+    /// Filters and redacts events before they are streamed.
+    pub scrubber: Arc<EventScrubber>,
 }
 
 impl AppState {
@@ -27,8 +44,12 @@ impl AppState {
     #[must_use]
     pub fn new(dashboard: Arc<DashboardState>) -> Self {
         Self {
+            status_cache: Arc::new(StatusCache::new(dashboard.status_rx.clone())),
+            metrics_cache: Arc::new(StatusCache::new(dashboard.status_rx.clone())),
             dashboard,
             audit: None,
+            health: None,
+            scrubber: Arc::new(EventScrubber::default()),
         }
     }
 
@@ -36,35 +57,76 @@ impl AppState {
     #[must_use]
     pub fn with_audit(dashboard: Arc<DashboardState>, audit: Arc<AuditLog>) -> Self {
         Self {
-            dashboard,
             audit: Some(audit),
+            ..Self::new(dashboard)
+        }
+    }
+}
+
+/// Respond with a cached body, or 500 if it could not be serialized.
+fn cached_response(body: Result<CachedBody, serde_json::Error>, headers: &HeaderMap) -> Response {
+    match body {
+        Ok(body) => body.respond(headers),
+        Err(e) => {
+            tracing::warn!(error = %e, "Failed to serialize dashboard response");
+            StatusCode::INTERNAL_SERVER_ERROR.into_response()
         }
     }
 }
 
-/// GET /api/status - Get current supervisor status.
-pub async fn get_status(State(state): State<AppState>) -> Json<StatusResponse> {
-    let status = state.dashboard.status_rx.borrow().clone();
+/// GET /api/v1/status - Get current supervisor status.
+///
+/// The body is only serialized again when the status or the SSE connection
+/// changed, and a matching `If-None-Match` gets `304 Not Modified`.
+pub async fn get_status(State(state): State<AppState>, headers: HeaderMap) -> Response {
     // Check if there are any SSE subscribers
     let connected = state.dashboard.event_tx.receiver_count() > 0;
+    let body = state.status_cache.get(u64::from(connected), |status| {
+        StatusResponse::new(status.clone(), connected)
+    });
+    cached_response(body, &headers)
+}
 
-    Json(StatusResponse::new(status, connected))
+/// GET /healthz - Health of the supervisor.
+///
+/// Answers 200 when every check passed and 503 otherwise, with the
+/// [`HealthReport`] naming the failed checks. Without a health monitor only
+/// the dashboard itself is known to be up, and it answers 200.
+pub async fn get_healthz(State(state): State<AppState>) -> Response {
+    let report = match state.health {
+        Some(ref monitor) => monitor.report().await,
+        None => HealthReport::new(Vec::new()),
+    };
+    let status = if report.healthy {
+        StatusCode::OK
+    } else {
+        StatusCode::SERVICE_UNAVAILABLE
+    };
+    (status, Json(report)).into_response()
 }
 
-/// GET /api/events - SSE stream of dashboard events.
+/// GET /api/v1/events - SSE stream of dashboard events.
+///
+/// Events pass through the [`EventScrubber`] first; those it withholds are
+/// not streamed.
 pub async fn get_events_sse(
     State(state): State<AppState>,
 ) -> Sse<impl futures_core::Stream<Item = Result<Event, Infallible>>> {
     let rx = state.dashboard.event_tx.subscribe();
-    let stream = BroadcastStream::new(rx).filter_map(|result| async move {
-        match result {
-            Ok(event) => {
-                let data = serde_json::to_string(&event).ok()?;
-                Some(Ok(Event::default().event(&event.event_type).data(data)))
-            }
-            Err(e) => {
-                tracing::debug!(error = ?e, "SSE broadcast lagged, dropping message");
-                None
+    let scrubber = state.scrubber;
+    let stream = BroadcastStream::new(rx).filter_map(move |result| {
+        let scrubber = Arc::clone(&scrubber);
+        async move {
+            match result {
+                Ok(event) => {
+                    let event = scrubber.scrub(&event)?;
+                    let data = serde_json::to_string(&event).ok()?;
+                    Some(Ok(Event::default().event(&event.event_type).data(data)))
+                }
+                Err(e) => {
+                    tracing::debug!(error = ?e, "SSE broadcast lagged, dropping message");
+                    None
+                }
             }
         }
     });
@@ -72,19 +134,54 @@ pub async fn get_events_sse(
     Sse::new(stream).keep_alive(KeepAlive::default())
 }
 
-/// GET /api/metrics - Get aggregated metrics.
-pub async fn get_metrics(State(state): State<AppState>) -> Json<MetricsResponse> {
-    let status = state.dashboard.status_rx.borrow();
-
+/// GET /api/v1/metrics - Get aggregated metrics.
+///
+/// Cached and revalidated like [`get_status`].
+pub async fn get_metrics(State(state): State<AppState>, headers: HeaderMap) -> Response {
     // Use status counters as base metrics
-    let response = MetricsResponse::new(status.tool_calls, status.approvals, status.denials);
+    let body = state.metrics_cache.get(0, |status| {
+        MetricsResponse::new(status.tool_calls, status.approvals, status.denials)
+            .with_sources(status.by_source)
+    });
 
     // TODO: Integrate with audit log for historical metrics when session tracking is added
 
-    Json(response)
+    cached_response(body, &headers)
+}
+
+/// GET /api/v1/costs - Get approximate cost attribution by tool and by file.
+///
+/// Returns an empty breakdown when no audit log is attached.
+pub async fn get_costs(
+    State(state): State<AppState>,
+    Query(query): Query<CostsQuery>,
+) -> Json<CostsResponse> {
+    let Some(audit) = state.audit else {
+        return Json(CostsResponse::default());
+    };
+
+    let by_tool = audit
+        .get_cost_shares(CostDimension::Tool, query.session)
+        .await;
+    let by_file = audit
+        .get_cost_shares(CostDimension::File, query.session)
+        .await;
+    match (by_tool, by_file) {
+        (Ok(by_tool), Ok(by_file)) => Json(CostsResponse::new(by_tool, by_file)),
+        (Err(e), _) | (_, Err(e)) => {
+            tracing::warn!(error = %e, "Failed to query cost attribution");
+            Json(CostsResponse::default())
+        }
+    }
+}
+
+/// GET /api/v1/todos - Get Claude's plan from its latest `TodoWrite` call.
+pub async fn get_todos(State(state): State<AppState>) -> Json<TodosResponse> {
+    let status = state.dashboard.status_rx.borrow();
+    Json(TodosResponse::new(&status.todos))
 }
 
-/// POST /api/stop - Stop the current session gracefully.
+/// POST /api/v1/stop - Stop the current session gracefully.
 pub async fn post_stop(State(state): State<AppState>) -> Json<CommandResponse> {
     match state
         .dashboard
@@ -100,7 +197,7 @@ pub async fn post_stop(State(state): State<AppState>) -> Json<CommandResponse> {
     }
 }
 
-/// POST /api/continue - Continue execution (approve pending action).
+/// POST /api/v1/continue - Continue execution (approve pending action).
 pub async fn post_continue(State(state): State<AppState>) -> Json<CommandResponse> {
     match state
         .dashboard
@@ -116,7 +213,25 @@ pub async fn post_continue(State(state): State<AppState>) -> Json<CommandRespons
     }
 }
 
-/// POST /api/kill - Force kill the Claude process.
+/// POST /api/v1/display - Change what the terminal display prints.
+pub async fn post_display(
+    State(state): State<AppState>,
+    Json(request): Json<DisplayRequest>,
+) -> Json<CommandResponse> {
+    let command = DashboardCommand::SetDisplayOptions {
+        show_thinking: request.show_thinking,
+        verbosity: request.verbosity,
+    };
+    match state.dashboard.command_tx.send(command).await {
+        Ok(()) => Json(CommandResponse::success("Display options sent")),
+        Err(e) => Json(CommandResponse::error(
+            "Failed to send display options",
+            e.to_string(),
+        )),
+    }
+}
+
+/// POST /api/v1/kill - Force kill the Claude process.
 pub async fn post_kill(State(state): State<AppState>) -> Json<CommandResponse> {
     match state
         .dashboard
@@ -132,10 +247,16 @@ pub async fn post_kill(State(state): State<AppState>) -> Json<CommandResponse> {
     }
 }
 
+/// GET /api/v1/openapi.json - `OpenAPI` document of the version 1 API.
+pub async fn get_openapi() -> Json<serde_json::Value> {
+    Json(openapi::document())
+}
+
 #[cfg(test)]
 mod tests {
     use super::*;
     use crate::dashboard::{create_dashboard_channels, SupervisorStatus};
+    use crate::supervisor::{DecisionBreakdown, DecisionSource, TaskLedger};
 
     #[tokio::test]
     async fn test_get_status() {
@@ -150,12 +271,25 @@ mod tests {
                 tool_calls: 10,
                 approvals: 8,
                 denials: 2,
+                by_source: DecisionBreakdown::default(),
                 task: Some("Test task".to_string()),
+                kill_cause: None,
+                retry_hint: None,
+                permission_mode: None,
+                name: None,
+                claude_code_version: None,
+                remaining_secs: None,
+                blast_radius: 0.0,
+                blast_radius_threshold: None,
+                risk_score: 0,
+                risk_threshold: None,
+                todos: TaskLedger::default(),
             })
             .unwrap();
 
         let state = AppState::new(Arc::new(dashboard_state));
-        let Json(response) = get_status(State(state)).await;
+        let response: StatusResponse =
+            json_body(get_status(State(state), HeaderMap::new()).await).await;
 
         assert!(!response.connected); // No SSE subscribers
         assert_eq!(response.status.session_id, Some("test-session".to_string()));
@@ -163,9 +297,101 @@ mod tests {
         assert_eq!(response.status.tool_calls, 10);
     }
 
+    async fn json_body<T: serde::de::DeserializeOwned>(response: Response) -> T {
+        assert_eq!(response.status(), StatusCode::OK);
+        let body = axum::body::to_bytes(response.into_body(), usize::MAX)
+            .await
+            .unwrap();
+        serde_json::from_slice(&body).unwrap()
+    }
+
+    #[tokio::test]
+    async fn test_polling_status_short_circuits() {
+        use axum::http::header::{CACHE_CONTROL, ETAG, IF_NONE_MATCH};
+
+        let (dashboard_state, handles) = create_dashboard_channels();
+        let state = AppState::new(Arc::new(dashboard_state));
+
+        let first = get_status(State(state.clone()), HeaderMap::new()).await;
+        let etag = first.headers()[ETAG].clone();
+        assert_eq!(first.headers()[CACHE_CONTROL], "no-cache");
+
+        // Unchanged status: served from the cache, 304 for the current tag
+        let mut conditional = HeaderMap::new();
+        conditional.insert(IF_NONE_MATCH, etag.clone());
+        for _ in 0..1000 {
+            let response = get_status(State(state.clone()), conditional.clone()).await;
+            assert_eq!(response.status(), StatusCode::NOT_MODIFIED);
+            assert_eq!(response.headers()[ETAG], etag);
+        }
+        let response = get_metrics(State(state.clone()), HeaderMap::new()).await;
+        let metrics_etag = response.headers()[ETAG].clone();
+        let response = get_metrics(State(state.clone()), HeaderMap::new()).await;
+        assert_eq!(response.headers()[ETAG], metrics_etag);
+        assert_eq!(state.status_cache.builds(), 1);
+        assert_eq!(state.metrics_cache.builds(), 1);
+
+        // A new status is serialized once and no longer matches
+        handles
+            .status_tx
+            .send_modify(|status| status.tool_calls += 1);
+        let response = get_status(State(state.clone()), conditional).await;
+        assert_ne!(response.headers()[ETAG], etag);
+        let response: StatusResponse = json_body(response).await;
+        assert_eq!(response.status.tool_calls, 1);
+        assert_eq!(state.status_cache.builds(), 2);
+
+        // An SSE client connecting changes the body too
+        let _subscriber = state.dashboard.event_tx.subscribe();
+        let response: StatusResponse =
+            json_body(get_status(State(state.clone()), HeaderMap::new()).await).await;
+        assert!(response.connected);
+        assert_eq!(state.status_cache.builds(), 3);
+    }
+
+    #[tokio::test]
+    async fn test_get_healthz() {
+        let (dashboard_state, _handles) = create_dashboard_channels();
+        let mut state = AppState::new(Arc::new(dashboard_state));
+
+        // Without a monitor only the dashboard is known to be up
+        let report: HealthReport = json_body(get_healthz(State(state.clone())).await).await;
+        assert!(report.healthy && report.checks.is_empty());
+
+        // A loop that never started is unavailable
+        state.health = Some(HealthMonitor::new());
+        let response = get_healthz(State(state)).await;
+        assert_eq!(response.status(), StatusCode::SERVICE_UNAVAILABLE);
+    }
+
+    #[tokio::test]
+    async fn test_get_todos() {
+        let (dashboard_state, handles) = create_dashboard_channels();
+        handles.status_tx.send_modify(|status| {
+            status.todos.update(
+                &serde_json::json!({"todos": [
+                    {"content": "Plan", "status": "completed"},
+                    {"content": "Build", "status": "in_progress"}
+                ]}),
+                chrono::Utc::now(),
+            );
+        });
+
+        let state = AppState::new(Arc::new(dashboard_state));
+        let Json(response) = get_todos(State(state)).await;
+
+        assert_eq!(response.items.len(), 2);
+        assert_eq!(response.items[1].content, "Build");
+        assert_eq!(response.counts.completed, 1);
+        assert_eq!(response.counts.in_progress, 1);
+    }
+
     #[tokio::test]
     async fn test_get_metrics_no_audit() {
         let (dashboard_state, handles) = create_dashboard_channels();
+        let mut by_source = DecisionBreakdown::default();
+        by_source.record_approval(DecisionSource::Ai);
+        by_source.record_denial(DecisionSource::Policy);
 
         handles
             .status_tx
@@ -175,16 +401,30 @@ mod tests {
                 tool_calls: 100,
                 approvals: 80,
                 denials: 20,
+                by_source,
                 task: None,
+                kill_cause: None,
+                retry_hint: None,
+                permission_mode: None,
+                name: None,
+                claude_code_version: None,
+                remaining_secs: None,
+                blast_radius: 0.0,
+                blast_radius_threshold: None,
+                risk_score: 0,
+                risk_threshold: None,
+                todos: TaskLedger::default(),
             })
             .unwrap();
 
         let state = AppState::new(Arc::new(dashboard_state));
-        let Json(response) = get_metrics(State(state)).await;
+        let response: MetricsResponse =
+            json_body(get_metrics(State(state), HeaderMap::new()).await).await;
 
         assert_eq!(response.total_events, 100);
         assert_eq!(response.allowed, 80);
         assert_eq!(response.denied, 20);
+        assert_eq!(response.by_source, by_source);
         assert!(response.session.is_none());
     }
 
@@ -233,6 +473,25 @@ mod tests {
         assert_eq!(cmd, DashboardCommand::ForceKill);
     }
 
+    #[tokio::test]
+    async fn test_post_display() {
+        let (dashboard_state, mut handles) = create_dashboard_channels();
+        let state = AppState::new(Arc::new(dashboard_state));
+        let request: DisplayRequest = serde_json::from_str(r#"{"show_thinking": true}"#).unwrap();
+
+        let Json(response) = post_display(State(state), Json(request)).await;
+
+        assert!(response.success);
+        let cmd = handles.command_rx.recv().await.unwrap();
+        assert_eq!(
+            cmd,
+            DashboardCommand::SetDisplayOptions {
+                show_thinking: Some(true),
+                verbosity: None,
+            }
+        );
+    }
+
     #[tokio::test]
     async fn test_command_error_on_closed_channel() {
         let (dashboard_state, handles) = create_dashboard_channels();
@@ -248,6 +507,52 @@ mod tests {
         assert!(response.error.is_some());
     }
 
+    #[tokio::test]
+    async fn test_get_costs_no_audit() {
+        let (dashboard_state, _handles) = create_dashboard_channels();
+        let state = AppState::new(Arc::new(dashboard_state));
+
+        let Json(response) = get_costs(State(state), Query(CostsQuery::default())).await;
+
+        assert_eq!(response.total_cost_micros, 0);
+        assert!(response.by_tool.is_empty());
+    }
+
+    #[tokio::test]
+    async fn test_get_costs_from_audit() {
+        use crate::audit::{AuditSession, CostAttributor};
+        use crate::cli::{ClaudeEvent, ToolUse};
+
+        let (dashboard_state, _handles) = create_dashboard_channels();
+        let audit = AuditLog::open_in_memory().await.unwrap();
+        let session = AuditSession::new("Test task");
+        audit.log_session_start(&session).await.unwrap();
+
+        let mut attributor = CostAttributor::new();
+        attributor.observe(&ClaudeEvent::ToolUse(ToolUse {
+            id: "t1".to_string(),
+            name: "Edit".to_string(),
+            input: serde_json::json!({"file_path": "a.rs"}),
+        }));
+        attributor.observe(&ClaudeEvent::MessageStart {
+            message: serde_json::json!({"id": "m2", "usage": {"input_tokens": 10, "output_tokens": 0}}),
+        });
+        audit
+            .log_cost_breakdown(session.id, &attributor.breakdown())
+            .await
+            .unwrap();
+
+        let state = AppState::with_audit(Arc::new(dashboard_state), Arc::new(audit));
+        let query = CostsQuery {
+            session: Some(session.id),
+        };
+        let Json(response) = get_costs(State(state), Query(query)).await;
+
+        assert_eq!(response.total_cost_micros, 30);
+        assert_eq!(response.by_tool.len(), 2);
+        assert_eq!(response.by_file.len(), 1);
+    }
+
     #[tokio::test]
     async fn test_app_state_with_audit() {
         let (dashboard_state, _handles) = create_dashboard_channels();
@@ -1,19 +1,26 @@
 //! Web dashboard module for monitoring and controlling the supervisor.
 
 mod api;
+mod cache;
 mod error;
 mod handlers;
+pub mod openapi;
+mod scrub;
 mod server;
 mod state;
 
 pub use api::{
-    CommandResponse, EventsQuery, MetricsResponse, SessionMetricsResponse, StatusResponse,
+    CommandResponse, CostsQuery, CostsResponse, DisplayRequest, EventsQuery, MetricsResponse,
+    SessionMetricsResponse, StatusResponse, TodosResponse,
 };
+pub use cache::{CachedBody, StatusCache, REVALIDATE};
 pub use error::DashboardError;
 pub use handlers::{
-    get_events_sse, get_metrics, get_status, post_continue, post_kill, post_stop, AppState,
+    get_costs, get_events_sse, get_healthz, get_metrics, get_openapi, get_status, get_todos,
+    post_continue, post_display, post_kill, post_stop, AppState,
 };
-pub use server::{DashboardConfig, DashboardServer, DEFAULT_PORT};
+pub use scrub::EventScrubber;
+pub use server::{DashboardConfig, DashboardServer, DEFAULT_PORT, HEALTHZ_PATH};
 pub use state::{
     create_dashboard_channels, DashboardCommand, DashboardEvent, DashboardHandles, DashboardState,
     SupervisorStatus,
@@ -0,0 +1,588 @@
+//! `OpenAPI` 3 document for the versioned dashboard API.
+//!
+//! Routes under [`API_V1`] keep their response shapes; breaking changes go
+//! to a new version prefix. The unversioned `/api/*` aliases are deprecated
+//! and will be removed in the next release.
+//!
+//! The document is written by hand from [`OPERATIONS`]; the tests check it
+//! against the router's routes and against serialized responses, so it
+//! cannot drift from the handlers.
+
+use axum::http::Method;
+use serde_json::{json, Map, Value};
+
+/// Prefix of the stable version 1 API.
+pub const API_V1: &str = "/api/v1";
+
+/// Prefix of the deprecated unversioned aliases.
+pub const API_LEGACY: &str = "/api";
+
+/// Path of the `OpenAPI` document, relative to [`API_V1`].
+pub const OPENAPI_PATH: &str = "/openapi.json";
+
+/// Body of a successful response.
+#[derive(Debug, Clone, Copy)]
+pub enum ResponseBody {
+    /// JSON body described by a component schema.
+    Json(&'static str),
+    /// Server-sent events whose data is described by a component schema.
+    EventStream(&'static str),
+    /// Any JSON document.
+    AnyJson,
+}
+
+/// A query parameter of an operation.
+#[derive(Debug, Clone, Copy)]
+pub struct QueryParam {
+    /// Parameter name.
+    pub name: &'static str,
+    /// JSON Schema string format (e.g. `uuid`).
+    pub format: &'static str,
+    /// Description.
+    pub doc: &'static str,
+}
+
+/// A documented API operation.
+#[derive(Debug, Clone)]
+pub struct Operation {
+    /// HTTP method.
+    pub method: Method,
+    /// Path relative to the version prefix.
+    pub path: &'static str,
+    /// Operation ID.
+    pub id: &'static str,
+    /// One-line summary.
+    pub summary: &'static str,
+    /// Optional query parameters.
+    pub query: &'static [QueryParam],
+    /// Component schema of the JSON request body, if the operation takes one.
+    pub request: Option<&'static str>,
+    /// Body of the 200 response.
+    pub response: ResponseBody,
+    /// Whether the response carries an `ETag` that `If-None-Match` can
+    /// revalidate with a `304 Not Modified`.
+    pub conditional: bool,
+}
+
+/// Every operation of the version 1 API.
+pub const OPERATIONS: &[Operation] = &[
+    Operation {
+        method: Method::GET,
+        path: "/status",
+        id: "getStatus",
+        summary: "Get current supervisor status.",
+        query: &[],
+        request: None,
+        response: ResponseBody::Json("StatusResponse"),
+        conditional: true,
+    },
+    Operation {
+        method: Method::GET,
+        path: "/events",
+        id: "getEvents",
+        summary: "Stream dashboard events.",
+        query: &[],
+        request: None,
+        response: ResponseBody::EventStream("DashboardEvent"),
+        conditional: false,
+    },
+    Operation {
+        method: Method::GET,
+        path: "/metrics",
+        id: "getMetrics",
+        summary: "Get aggregated metrics.",
+        query: &[],
+        request: None,
+        response: ResponseBody::Json("MetricsResponse"),
+        conditional: true,
+    },
+    Operation {
+        method: Method::GET,
+        path: "/costs",
+        id: "getCosts",
+        summary: "Get approximate cost attribution by tool and by file.",
+        query: &[QueryParam {
+            name: "session",
+            format: "uuid",
+            doc: "Restrict the breakdown to one audit session (all sessions if omitted).",
+        }],
+        request: None,
+        response: ResponseBody::Json("CostsResponse"),
+        conditional: false,
+    },
+    Operation {
+        method: Method::GET,
+        path: "/todos",
+        id: "getTodos",
+        summary: "Get Claude's plan from its latest TodoWrite call.",
+        query: &[],
+        request: None,
+        response: ResponseBody::Json("TodosResponse"),
+        conditional: false,
+    },
+    Operation {
+        method: Method::POST,
+        path: "/stop",
+        id: "postStop",
+        summary: "Stop the current session gracefully.",
+        query: &[],
+        request: None,
+        response: ResponseBody::Json("CommandResponse"),
+        conditional: false,
+    },
+    Operation {
+        method: Method::POST,
+        path: "/continue",
+        id: "postContinue",
+        summary: "Continue execution (approve pending action).",
+        query: &[],
+        request: None,
+        response: ResponseBody::Json("CommandResponse"),
+        conditional: false,
+    },
+    Operation {
+        method: Method::POST,
+        path: "/kill",
+        id: "postKill",
+        summary: "Force kill the Claude process.",
+        query: &[],
+        request: None,
+        response: ResponseBody::Json("CommandResponse"),
+        conditional: false,
+    },
+    Operation {
+        method: Method::POST,
+        path: "/display",
+        id: "postDisplay",
+        summary: "Change what the terminal display prints.",
+        query: &[],
+        request: Some("DisplayRequest"),
+        response: ResponseBody::Json("CommandResponse"),
+        conditional: false,
+    },
+    Operation {
+        method: Method::GET,
+        path: OPENAPI_PATH,
+        id: "getOpenApi",
+        summary: "Get this OpenAPI document.",
+        query: &[],
+        request: None,
+        response: ResponseBody::AnyJson,
+        conditional: false,
+    },
+];
+
+/// Build the `OpenAPI` document for the version 1 API.
+#[must_use]
+pub fn document() -> Value {
+    let mut paths = Map::new();
+    for operation in OPERATIONS {
+        let path = paths
+            .entry(format!("{API_V1}{}", operation.path))
+            .or_insert_with(|| json!({}));
+        path[operation.method.as_str().to_lowercase()] = operation_object(operation);
+    }
+
+    json!({
+        "openapi": "3.0.3",
+        "info": {
+            "title": "claude-supervisor dashboard API",
+            "version": "1",
+            "description": "Monitoring and control API of the supervisor dashboard.",
+        },
+        "paths": paths,
+        "components": { "schemas": schemas() },
+    })
+}
+
+fn operation_object(operation: &Operation) -> Value {
+    let content = match operation.response {
+        ResponseBody::Json(schema) => json!({
+            "application/json": { "schema": schema_ref(schema) },
+        }),
+        ResponseBody::EventStream(schema) => json!({
+            "text/event-stream": {
+                "schema": { "type": "string" },
+                "x-event-data": schema_ref(schema),
+            },
+        }),
+        ResponseBody::AnyJson => json!({
+            "application/json": { "schema": { "type": "object" } },
+        }),
+    };
+    let mut object = json!({
+        "operationId": operation.id,
+        "summary": operation.summary,
+        "responses": { "200": { "description": "OK", "content": content } },
+    });
+    if operation.conditional {
+        object["responses"]["304"] = json!({ "description": "Not modified" });
+    }
+    if let Some(schema) = operation.request {
+        object["requestBody"] = json!({
+            "required": true,
+            "content": { "application/json": { "schema": schema_ref(schema) } },
+        });
+    }
+    let mut parameters: Vec<Value> = operation
+        .query
+        .iter()
+        .map(|param| {
+            json!({
+                "name": param.name,
+                "in": "query",
+                "required": false,
+                "description": param.doc,
+                "schema": { "type": "string", "format": param.format },
+            })
+        })
+        .collect();
+    if operation.conditional {
+        parameters.push(json!({
+            "name": "If-None-Match",
+            "in": "header",
+            "required": false,
+            "description": "ETag of a previous response; answered with 304 when unchanged.",
+            "schema": { "type": "string" },
+        }));
+    }
+    if !parameters.is_empty() {
+        object["parameters"] = Value::Array(parameters);
+    }
+    object
+}
+
+fn schema_ref(name: &str) -> Value {
+    json!({ "$ref": format!("#/components/schemas/{name}") })
+}
+
+/// Component schemas of the response types.
+#[allow(clippy::too_many_lines)]
+fn schemas() -> Value {
+    let integer = || json!({ "type": "integer", "minimum": 0 });
+    let string = || json!({ "type": "string" });
+    let nullable_string = || json!({ "type": "string", "nullable": true });
+
+    json!({
+        "StatusResponse": {
+            "type": "object",
+            "description": "Current supervisor status.",
+            "required": [
+                "connected", "state", "tool_calls", "approvals", "denials", "blast_radius",
+                "risk_score",
+            ],
+            "properties": {
+                "connected": { "type": "boolean", "description": "Whether a client is connected to the SSE stream." },
+                "session_id": nullable_string(),
+                "state": { "type": "string", "description": "Current state (e.g. running, waiting, stopped)." },
+                "tool_calls": integer(),
+                "approvals": integer(),
+                "denials": integer(),
+                "by_source": schema_ref("DecisionBreakdown"),
+                "task": nullable_string(),
+                "kill_cause": schema_ref("KillCause"),
+                "retry_hint": schema_ref("RetryHint"),
+                "permission_mode": { "type": "string", "description": "Claude Code permission mode reported at session start." },
+                "name": { "type": "string", "description": "Supervisor-assigned session name." },
+                "claude_code_version": { "type": "string", "description": "Claude Code version the session runs under." },
+                "remaining_secs": { "type": "integer", "description": "Seconds left before the session's time limit, if it has one." },
+                "blast_radius": { "type": "number", "description": "Current blast radius score of the session's mutating operations." },
+                "blast_radius_threshold": { "type": "integer", "description": "Blast radius score at which the session escalates." },
+                "risk_score": { "type": "integer", "minimum": 0, "description": "Risk score of the session's recent tool calls." },
+                "risk_threshold": { "type": "integer", "description": "Risk score above which the session escalates, if the score escalates." },
+                "todos": {
+                    "type": "object",
+                    "description": "Claude's plan, as last recorded with TodoWrite.",
+                    "properties": { "items": { "type": "array", "items": schema_ref("TodoItem") } },
+                },
+            },
+        },
+        "KillCause": {
+            "type": "string",
+            "description": "Why the session was killed.",
+            "enum": [
+                "policy_deny", "escalation_unavailable", "ai_denial", "ai_error",
+                "stuck_pattern", "tool_timeout", "budget", "operator",
+            ],
+        },
+        "RetryHint": {
+            "type": "object",
+            "description": "Whether and how a killed session can be retried.",
+            "required": ["action"],
+            "properties": {
+                "action": { "type": "string", "enum": ["do_not_retry", "retry_as_is", "allow_tool"] },
+                "tool": { "type": "string", "description": "Tool to pre-approve, for allow_tool." },
+            },
+        },
+        "CommandResponse": {
+            "type": "object",
+            "description": "Result of a control command.",
+            "required": ["success", "message"],
+            "properties": {
+                "success": { "type": "boolean" },
+                "message": string(),
+                "error": string(),
+            },
+        },
+        "DisplayRequest": {
+            "type": "object",
+            "description": "Display options to change; omitted options are left as they are.",
+            "required": [],
+            "properties": {
+                "show_thinking": { "type": "boolean" },
+                "verbosity": schema_ref("Verbosity"),
+            },
+        },
+        "Verbosity": {
+            "type": "string",
+            "description": "How much of tool inputs and results is printed.",
+            "enum": ["normal", "raw"],
+        },
+        "MetricsResponse": {
+            "type": "object",
+            "description": "Aggregated metrics.",
+            "required": ["total_events", "allowed", "denied"],
+            "properties": {
+                "total_events": integer(),
+                "allowed": integer(),
+                "denied": integer(),
+                "by_source": schema_ref("DecisionBreakdown"),
+                "session": schema_ref("SessionMetricsResponse"),
+            },
+        },
+        "DecisionBreakdown": {
+            "type": "object",
+            "description": "Approvals and denials by who made them.",
+            "properties": {
+                "policy": schema_ref("DecisionCounts"),
+                "hook": schema_ref("DecisionCounts"),
+                "ai": schema_ref("DecisionCounts"),
+                "human": schema_ref("DecisionCounts"),
+                "fallback": schema_ref("DecisionCounts"),
+                "sampled": schema_ref("DecisionCounts"),
+            },
+        },
+        "DecisionCounts": {
+            "type": "object",
+            "description": "Approvals and denials made by one source.",
+            "required": ["approvals", "denials"],
+            "properties": {
+                "approvals": integer(),
+                "denials": integer(),
+            },
+        },
+        "SessionMetricsResponse": {
+            "type": "object",
+            "description": "Session-specific metrics.",
+            "required": [
+                "session_id", "input_tokens", "output_tokens", "api_calls",
+                "cache_hits", "estimated_cost_cents", "blast_radius_peak",
+                "blast_radius_threshold",
+            ],
+            "properties": {
+                "session_id": string(),
+                "input_tokens": integer(),
+                "output_tokens": integer(),
+                "api_calls": integer(),
+                "cache_hits": integer(),
+                "estimated_cost_cents": integer(),
+                "blast_radius_peak": integer(),
+                "blast_radius_threshold": integer(),
+            },
+        },
+        "CostsResponse": {
+            "type": "object",
+            "description": "Approximate cost attribution.",
+            "required": ["total_cost_micros", "by_tool", "by_file"],
+            "properties": {
+                "total_cost_micros": integer(),
+                "by_tool": { "type": "array", "items": schema_ref("CostShare") },
+                "by_file": { "type": "array", "items": schema_ref("CostShare") },
+            },
+        },
+        "CostShare": {
+            "type": "object",
+            "description": "Attributed usage and cost for one tool name or file path.",
+            "required": ["key", "calls", "input_tokens", "output_tokens", "cost_micros"],
+            "properties": {
+                "key": string(),
+                "calls": integer(),
+                "input_tokens": integer(),
+                "output_tokens": integer(),
+                "cost_micros": integer(),
+            },
+        },
+        "TodosResponse": {
+            "type": "object",
+            "description": "Claude's plan and its progress.",
+            "required": ["items", "pending", "in_progress", "completed"],
+            "properties": {
+                "items": { "type": "array", "items": schema_ref("TodoItem") },
+                "pending": integer(),
+                "in_progress": integer(),
+                "completed": integer(),
+            },
+        },
+        "TodoItem": {
+            "type": "object",
+            "description": "An item of Claude's plan.",
+            "required": ["content", "state", "first_seen", "updated_at"],
+            "properties": {
+                "content": string(),
+                "state": { "type": "string", "enum": ["pending", "in_progress", "completed"] },
+                "active_form": string(),
+                "first_seen": { "type": "string", "format": "date-time" },
+                "updated_at": { "type": "string", "format": "date-time" },
+            },
+        },
+        "DenyReason": {
+            "type": "object",
+            "description": "Why a tool call was denied, as sent in denial events.",
+            "required": ["message"],
+            "properties": {
+                "message": string(),
+                "rule_category": string(),
+                "rule_id": string(),
+                "source": {
+                    "type": "string",
+                    "enum": ["policy", "hook", "ai", "human", "fallback", "sampled"],
+                },
+            },
+        },
+        "DashboardEvent": {
+            "type": "object",
+            "description": "Event sent to dashboard clients; the SSE event name is event_type.",
+            "required": ["event_type", "timestamp", "data"],
+            "properties": {
+                "event_type": string(),
+                "timestamp": { "type": "string", "format": "date-time" },
+                "data": {},
+            },
+        },
+    })
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::audit::CostShare;
+    use crate::dashboard::{
+        CommandResponse, CostsResponse, DashboardEvent, DisplayRequest, MetricsResponse,
+        SessionMetricsResponse, StatusResponse, SupervisorStatus, TodosResponse,
+    };
+    use crate::display::Verbosity;
+    use crate::supervisor::{KillCause, RetryHint, TaskLedger};
+
+    /// Assert a serialized value only uses described properties and has the required ones.
+    fn assert_matches_schema(name: &str, value: &impl serde::Serialize) {
+        let schemas = schemas();
+        let schema = &schemas[name];
+        let value = serde_json::to_value(value).unwrap();
+        let object = value.as_object().unwrap();
+        for key in object.keys() {
+            assert!(
+                schema["properties"].get(key).is_some(),
+                "{name}.{key} is not described"
+            );
+        }
+        for required in schema["required"].as_array().unwrap() {
+            assert!(
+                object.contains_key(required.as_str().unwrap()),
+                "{name}.{required} is required but not serialized"
+            );
+        }
+    }
+
+    #[test]
+    fn test_schemas_match_responses() {
+        let status = SupervisorStatus {
+            session_id: Some("s".to_string()),
+            task: Some("t".to_string()),
+            kill_cause: Some(KillCause::Budget),
+            retry_hint: Some(RetryHint::AllowTool {
+                tool: "Bash".to_string(),
+            }),
+            permission_mode: Some("plan".to_string()),
+            name: Some("brisk-otter".to_string()),
+            claude_code_version: Some("2.0.14".to_string()),
+            remaining_secs: Some(600),
+            blast_radius: 37.5,
+            blast_radius_threshold: Some(100),
+            risk_score: 9,
+            risk_threshold: Some(20),
+            todos: TaskLedger::replay(
+                [&json!({"todos": [{"content": "c", "status": "pending", "activeForm": "a"}]})],
+                chrono::Utc::now(),
+            ),
+            ..SupervisorStatus::default()
+        };
+        let todos = TodosResponse::new(&status.todos);
+        assert_matches_schema("TodosResponse", &todos);
+        assert_matches_schema("TodoItem", &todos.items[0]);
+        assert_matches_schema("StatusResponse", &StatusResponse::new(status, true));
+        assert_matches_schema(
+            "RetryHint",
+            &RetryHint::AllowTool {
+                tool: "Bash".to_string(),
+            },
+        );
+        assert_matches_schema("CommandResponse", &CommandResponse::error("a", "b"));
+        assert_matches_schema(
+            "DisplayRequest",
+            &DisplayRequest {
+                show_thinking: Some(true),
+                verbosity: Some(Verbosity::Raw),
+            },
+        );
+        let session = SessionMetricsResponse {
+            session_id: "s".to_string(),
+            input_tokens: 1,
+            output_tokens: 1,
+            api_calls: 1,
+            cache_hits: 1,
+            estimated_cost_cents: 1,
+            blast_radius_peak: 37,
+            blast_radius_threshold: 100,
+        };
+        assert_matches_schema("SessionMetricsResponse", &session);
+        assert_matches_schema(
+            "MetricsResponse",
+            &MetricsResponse::with_session(1, 1, 0, session),
+        );
+        let share = CostShare::default();
+        assert_matches_schema("CostShare", &share);
+        assert_matches_schema("CostsResponse", &CostsResponse::new(vec![share], vec![]));
+        assert_matches_schema("DashboardEvent", &DashboardEvent::new("output", json!({})));
+
+        let causes = schemas()["KillCause"]["enum"].clone();
+        for cause in [KillCause::PolicyDeny, KillCause::Operator] {
+            assert!(causes.as_array().unwrap().contains(&json!(cause.as_str())));
+        }
+    }
+
+    #[test]
+    fn test_document_references_resolve() {
+        let document = document();
+        let text = document.to_string();
+        for (start, _) in text.match_indices("#/components/schemas/") {
+            let rest = &text[start + "#/components/schemas/".len()..];
+            let name = &rest[..rest.find('"').unwrap()];
+            assert!(
+                document["components"]["schemas"].get(name).is_some(),
+                "unresolved reference to {name}"
+            );
+        }
+        assert_eq!(document["openapi"], "3.0.3");
+    }
+
+    #[test]
+    fn test_polled_endpoints_document_revalidation() {
+        let document = document();
+        let status = &document["paths"]["/api/v1/status"]["get"];
+        assert!(status["responses"].get("304").is_some());
+        assert_eq!(status["parameters"][0]["name"], "If-None-Match");
+        assert_eq!(status["parameters"][0]["in"], "header");
+        let costs = &document["paths"]["/api/v1/costs"]["get"];
+        assert!(costs["responses"].get("304").is_none());
+    }
+}
@@ -5,6 +5,9 @@ use serde::{Deserialize, Serialize};
 use tokio::sync::{broadcast, mpsc, watch};
 use tokio_util::sync::CancellationToken;
 
+use crate::display::Verbosity;
+use crate::supervisor::{DecisionBreakdown, KillCause, RetryHint, SupervisorResult, TaskLedger};
+
 /// Commands that can be sent from the dashboard to the supervisor.
 #[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
 pub enum DashboardCommand {
@@ -14,6 +17,14 @@ pub enum DashboardCommand {
     Continue,
     /// Force kill the Claude process.
     ForceKill,
+    /// Change what the terminal display prints; `None` leaves an option as
+    /// it is.
+    SetDisplayOptions {
+        /// Print Claude's thinking, streamed or in whole messages.
+        show_thinking: Option<bool>,
+        /// How much of tool inputs and results is printed.
+        verbosity: Option<Verbosity>,
+    },
 }
 
 /// Current status of the supervisor session.
@@ -29,8 +40,44 @@ pub struct SupervisorStatus {
     pub approvals: u64,
     /// Number of denied tool calls.
     pub denials: u64,
+    /// Approvals and denials by who made them.
+    #[serde(default)]
+    pub by_source: DecisionBreakdown,
     /// Current task description.
     pub task: Option<String>,
+    /// Why the session was killed, if it was.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub kill_cause: Option<KillCause>,
+    /// Retry advice for a killed session.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub retry_hint: Option<RetryHint>,
+    /// Claude Code permission mode reported at session start.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub permission_mode: Option<String>,
+    /// Supervisor-assigned session name.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub name: Option<String>,
+    /// Claude Code version the session runs under.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub claude_code_version: Option<String>,
+    /// Seconds left before the session's time limit, if it has one.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub remaining_secs: Option<u64>,
+    /// Current blast radius score of the session's mutating operations.
+    #[serde(default)]
+    pub blast_radius: f64,
+    /// Blast radius score at which the session escalates.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub blast_radius_threshold: Option<u32>,
+    /// Risk score of the session's recent tool calls.
+    #[serde(default)]
+    pub risk_score: u32,
+    /// Risk score above which the session escalates, if the score escalates.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub risk_threshold: Option<u32>,
+    /// Claude's plan, as last recorded with `TodoWrite`.
+    #[serde(default, skip_serializing_if = "TaskLedger::is_empty")]
+    pub todos: TaskLedger,
 }
 
 impl Default for SupervisorStatus {
@@ -41,8 +88,37 @@ impl Default for SupervisorStatus {
             tool_calls: 0,
             approvals: 0,
             denials: 0,
+            by_source: DecisionBreakdown::default(),
             task: None,
+            kill_cause: None,
+            retry_hint: None,
+            permission_mode: None,
+            name: None,
+            claude_code_version: None,
+            remaining_secs: None,
+            blast_radius: 0.0,
+            blast_radius_threshold: None,
+            risk_score: 0,
+            risk_threshold: None,
+            todos: TaskLedger::default(),
+        }
+    }
+}
+
+impl SupervisorStatus {
+    /// Update the state and kill details from a finished session's result.
+    pub fn record_result(&mut self, result: &SupervisorResult) {
+        match result {
+            SupervisorResult::Killed {
+                cause, retry_hint, ..
+            } => {
+                self.kill_cause = Some(*cause);
+                self.retry_hint = Some(retry_hint.clone());
+            }
+            SupervisorResult::TimedOut { .. } => self.remaining_secs = Some(0),
+            _ => {}
         }
+        self.state = result.outcome().to_string();
     }
 }
 
@@ -155,6 +231,26 @@ mod tests {
         assert!(status.task.is_none());
     }
 
+    #[test]
+    fn test_supervisor_status_record_killed() {
+        let mut status = SupervisorStatus::default();
+        status.record_result(&SupervisorResult::Killed {
+            reason: "Blocked".to_string(),
+            cause: KillCause::PolicyDeny,
+            retry_hint: RetryHint::DoNotRetry,
+        });
+
+        assert_eq!(status.state, "killed");
+        assert_eq!(status.kill_cause, Some(KillCause::PolicyDeny));
+        assert_eq!(status.retry_hint, Some(RetryHint::DoNotRetry));
+
+        let json = serde_json::to_string(&status).unwrap();
+        assert!(json.contains("\"kill_cause\":\"policy_deny\""));
+        assert!(!serde_json::to_string(&SupervisorStatus::default())
+            .unwrap()
+            .contains("kill_cause"));
+    }
+
     #[test]
     fn test_dashboard_event_creation() {
         let event = DashboardEvent::new("tool_call", serde_json::json!({"tool": "Read"}));
@@ -208,7 +304,19 @@ mod tests {
                 tool_calls: 5,
                 approvals: 4,
                 denials: 1,
+                by_source: DecisionBreakdown::default(),
                 task: Some("Fix bug".to_string()),
+                kill_cause: None,
+                retry_hint: None,
+                permission_mode: None,
+                name: None,
+                claude_code_version: None,
+                remaining_secs: None,
+                blast_radius: 0.0,
+                blast_radius_threshold: None,
+                risk_score: 0,
+                risk_threshold: None,
+                todos: TaskLedger::default(),
             })
             .unwrap();
 
@@ -4,9 +4,15 @@
 //! to the terminal during Claude Code supervision.
 
 use std::io::{self, Write};
+use std::sync::{Arc, PoisonError, RwLock};
+use std::time::{Duration, Instant};
 
 use chrono::Utc;
 use owo_colors::OwoColorize;
+use serde::{Deserialize, Serialize};
+
+use crate::cli::{AssistantMessage, ContentBlock};
+use crate::supervisor::TaskLedger;
 
 /// Get current timestamp in the same format as tracing.
 fn timestamp() -> String {
@@ -16,6 +22,131 @@ fn timestamp() -> String {
 /// Maximum length for truncated display strings.
 const DEFAULT_MAX_LEN: usize = 80;
 
+/// Default cap on the bytes printed for one tool input or result.
+pub const DEFAULT_MAX_OUTPUT_BYTES: usize = 2048;
+
+/// Time without events before the spinner appears.
+pub const SPINNER_DELAY: Duration = Duration::from_secs(1);
+
+/// How often the spinner is redrawn.
+pub const SPINNER_INTERVAL: Duration = Duration::from_millis(100);
+
+const SPINNER_FRAMES: &[char] = &['⠋', '⠙', '⠹', '⠸', '⠼', '⠴', '⠦', '⠧', '⠇', '⠏'];
+
+/// What the display prints and how much of it.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct DisplayOptions {
+    /// Print values untruncated, up to `max_output_bytes`.
+    pub raw_mode: bool,
+    /// Most bytes printed for one tool input or result.
+    pub max_output_bytes: usize,
+    /// Print Claude's thinking blocks.
+    pub show_thinking: bool,
+    /// Show a spinner on stderr while no events arrive.
+    ///
+    /// Only set this when stderr is a terminal.
+    pub spinner: bool,
+}
+
+impl Default for DisplayOptions {
+    fn default() -> Self {
+        Self {
+            raw_mode: true,
+            max_output_bytes: DEFAULT_MAX_OUTPUT_BYTES,
+            show_thinking: true,
+            spinner: false,
+        }
+    }
+}
+
+impl DisplayOptions {
+    /// How much of tool inputs and results is printed.
+    #[must_use]
+    pub fn verbosity(&self) -> Verbosity {
+        if self.raw_mode {
+            Verbosity::Raw
+        } else {
+            Verbosity::Normal
+        }
+    }
+
+    /// Apply a runtime change; `None` leaves the option as it is.
+    pub fn update(&mut self, show_thinking: Option<bool>, verbosity: Option<Verbosity>) {
+        if let Some(show_thinking) = show_thinking {
+            self.show_thinking = show_thinking;
+        }
+        if let Some(verbosity) = verbosity {
+            self.raw_mode = verbosity == Verbosity::Raw;
+        }
+    }
+}
+
+/// How much of tool inputs and results is printed.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "lowercase")]
+pub enum Verbosity {
+    /// Truncate values to one line.
+    Normal,
+    /// Print values untruncated, up to `max_output_bytes`.
+    Raw,
+}
+
+/// Display options that can change while a session runs.
+///
+/// Clones share the options: the dashboard or a terminal key handler keeps
+/// a clone and the event loop reads the current options for every event.
+/// Reads copy the options out, so a writer never holds up the loop for
+/// longer than a copy.
+#[derive(Debug, Clone, Default)]
+pub struct SharedDisplayOptions(Arc<RwLock<DisplayOptions>>);
+
+impl SharedDisplayOptions {
+    /// Share `options`.
+    #[must_use]
+    pub fn new(options: DisplayOptions) -> Self {
+        Self(Arc::new(RwLock::new(options)))
+    }
+
+    /// The current options.
+    #[must_use]
+    pub fn get(&self) -> DisplayOptions {
+        *self.0.read().unwrap_or_else(PoisonError::into_inner)
+    }
+
+    /// Replace the options.
+    pub fn set(&self, options: DisplayOptions) {
+        *self.0.write().unwrap_or_else(PoisonError::into_inner) = options;
+    }
+
+    /// Apply a runtime change and return the resulting options.
+    ///
+    /// See [`DisplayOptions::update`].
+    pub fn update(
+        &self,
+        show_thinking: Option<bool>,
+        verbosity: Option<Verbosity>,
+    ) -> DisplayOptions {
+        let mut options = self.0.write().unwrap_or_else(PoisonError::into_inner);
+        options.update(show_thinking, verbosity);
+        *options
+    }
+}
+
+/// Cap a string at `max_bytes`, noting how many bytes were cut.
+///
+/// Cuts on a character boundary, so slightly less than `max_bytes` may be kept.
+#[must_use]
+pub fn truncate_bytes(s: &str, max_bytes: usize) -> String {
+    if s.len() <= max_bytes {
+        return s.to_string();
+    }
+    let mut end = max_bytes;
+    while !s.is_char_boundary(end) {
+        end -= 1;
+    }
+    format!("{}… {} more bytes", &s[..end], s.len() - end)
+}
+
 /// Truncate a string to a maximum length, adding ellipsis if truncated.
 #[must_use]
 pub fn truncate(s: &str, max_len: usize, raw_mode: bool) -> String {
@@ -125,15 +256,34 @@ pub fn print_session_end(
     let _ = io::stdout().flush();
 }
 
-/// Print a tool request.
-pub fn print_tool_request(name: &str, input: &serde_json::Value, raw_mode: bool) {
-    println!(
+/// Write a tool request, capped at `options.max_output_bytes`.
+///
+/// # Errors
+///
+/// Returns an error if writing to `out` fails.
+pub fn write_tool_request<W: Write>(
+    out: &mut W,
+    name: &str,
+    input: &serde_json::Value,
+    options: &DisplayOptions,
+) -> io::Result<()> {
+    let input = truncate_bytes(
+        &format_tool_input(input, options.raw_mode),
+        options.max_output_bytes,
+    );
+    writeln!(
+        out,
         "{} {} ({})",
         "[TOOL]".cyan().bold(),
         name.bold(),
-        format_tool_input(input, raw_mode).dimmed()
-    );
-    let _ = io::stdout().flush();
+        input.dimmed()
+    )?;
+    out.flush()
+}
+
+/// Print a tool request.
+pub fn print_tool_request(name: &str, input: &serde_json::Value, options: &DisplayOptions) {
+    let _ = write_tool_request(&mut io::stdout().lock(), name, input, options);
 }
 
 /// Print tool allow decision.
@@ -181,34 +331,142 @@ pub fn print_thinking(text: &str) {
     let _ = io::stdout().flush();
 }
 
+/// Write a streamed thinking fragment (dimmed) if `options.show_thinking`
+/// is set.
+///
+/// Returns whether the fragment was written.
+///
+/// # Errors
+///
+/// Returns an error if writing to `out` fails.
+pub fn write_thinking_delta<W: Write>(
+    out: &mut W,
+    thinking: &str,
+    options: &DisplayOptions,
+) -> io::Result<bool> {
+    if !options.show_thinking {
+        return Ok(false);
+    }
+    write!(out, "{}", thinking.dimmed())?;
+    out.flush()?;
+    Ok(true)
+}
+
+/// Print a streamed thinking fragment if `options.show_thinking` is set.
+///
+/// Returns whether the fragment was printed.
+#[must_use]
+pub fn print_thinking_delta(thinking: &str, options: &DisplayOptions) -> bool {
+    write_thinking_delta(&mut io::stdout(), thinking, options).unwrap_or(false)
+}
+
 /// Print text content.
 pub fn print_text(text: &str) {
     print!("{text}");
     let _ = io::stdout().flush();
 }
 
-/// Print tool result output.
-pub fn print_tool_result(tool_use_id: &str, content: &str, is_error: bool, raw_mode: bool) {
-    let id_short = truncate(tool_use_id, 12, raw_mode);
-    let content_short = truncate(content, 150, raw_mode);
+/// Print the thinking and text blocks of an assistant message.
+///
+/// Thinking blocks are skipped unless `options.show_thinking` is set.
+pub fn print_assistant_message(message: &AssistantMessage, options: &DisplayOptions) {
+    for block in &message.content {
+        match block {
+            ContentBlock::Thinking { thinking, .. } => {
+                if options.show_thinking {
+                    print_thinking(thinking);
+                    println!();
+                }
+            }
+            ContentBlock::Text { text } => {
+                print_text(text);
+                println!();
+            }
+            ContentBlock::ToolUse(_) | ContentBlock::Other(_) => {}
+        }
+    }
+}
+
+/// Write tool result output, capped at `options.max_output_bytes`.
+///
+/// # Errors
+///
+/// Returns an error if writing to `out` fails.
+pub fn write_tool_result<W: Write>(
+    out: &mut W,
+    tool_use_id: &str,
+    content: &str,
+    is_error: bool,
+    options: &DisplayOptions,
+) -> io::Result<()> {
+    let id_short = truncate(tool_use_id, 12, options.raw_mode);
+    let content_short = truncate_bytes(
+        &truncate(content, 150, options.raw_mode),
+        options.max_output_bytes,
+    );
     if is_error {
-        println!(
+        writeln!(
+            out,
             "{} {} {}",
             "[RESULT]".red().bold(),
             id_short.dimmed(),
             content_short
-        );
+        )?;
     } else {
-        println!(
+        writeln!(
+            out,
             "{} {} {}",
             "[RESULT]".green().bold(),
             id_short.dimmed(),
             content_short
-        );
+        )?;
+    }
+    out.flush()
+}
+
+/// Print tool result output.
+pub fn print_tool_result(
+    tool_use_id: &str,
+    content: &str,
+    is_error: bool,
+    options: &DisplayOptions,
+) {
+    let _ = write_tool_result(
+        &mut io::stdout().lock(),
+        tool_use_id,
+        content,
+        is_error,
+        options,
+    );
+}
+
+/// Print Claude's plan after a `TodoWrite` call.
+pub fn print_todos(ledger: &TaskLedger) {
+    println!("{} {}", "[PLAN]".cyan().bold(), ledger.summary());
+    for line in ledger.render().lines() {
+        println!("       {line}");
     }
     let _ = io::stdout().flush();
 }
 
+/// Print a time limit notice.
+pub fn print_time_limit(message: &str) {
+    println!("{} {}", "[TIME]".yellow().bold(), message);
+    let _ = io::stdout().flush();
+}
+
+/// Print a budget notice.
+pub fn print_budget(message: &str) {
+    println!("{} {}", "[BUDGET]".yellow().bold(), message);
+    let _ = io::stdout().flush();
+}
+
+/// Print a warning.
+pub fn print_warning(message: &str) {
+    println!("{} {}", "[WARN]".yellow().bold(), message);
+    let _ = io::stdout().flush();
+}
+
 /// Print an error message.
 pub fn print_error(message: &str) {
     println!("{} {}", "[ERROR]".red().bold(), message);
@@ -240,6 +498,28 @@ pub fn print_connection_test(provider: &str, model: &str, success: bool) {
     let _ = io::stdout().flush();
 }
 
+/// Print the settings a run starts with, one labelled line each.
+///
+/// Values are truncated unless `raw_mode` is set.
+pub fn print_run_banner(lines: &[(&str, String)], raw_mode: bool) {
+    let width = lines
+        .iter()
+        .map(|(label, _)| label.len())
+        .max()
+        .unwrap_or(0);
+    let mut out = io::stdout().lock();
+    for (label, value) in lines {
+        let _ = writeln!(
+            out,
+            "{} {:<width$} {}",
+            "[RUN]".blue().bold(),
+            label.dimmed(),
+            truncate(value, DEFAULT_MAX_LEN, raw_mode)
+        );
+    }
+    let _ = out.flush();
+}
+
 /// Print raw event output (for verbose/raw mode).
 pub fn print_raw_event(event_type: &str, event_json: &str) {
     println!(
@@ -251,10 +531,112 @@ pub fn print_raw_event(event_type: &str, event_json: &str) {
     let _ = io::stdout().flush();
 }
 
+/// Elapsed-time spinner shown on stderr while Claude is silent.
+///
+/// Draws nothing until [`SPINNER_DELAY`] has passed since the last
+/// [`reset`](Self::reset), and nothing at all when disabled.
+#[derive(Debug)]
+pub struct Spinner {
+    enabled: bool,
+    idle_since: Instant,
+    frame: usize,
+    visible: bool,
+}
+
+impl Default for Spinner {
+    fn default() -> Self {
+        Self::new(false)
+    }
+}
+
+impl Spinner {
+    /// Create a spinner; a disabled one never draws.
+    #[must_use]
+    pub fn new(enabled: bool) -> Self {
+        Self {
+            enabled,
+            idle_since: Instant::now(),
+            frame: 0,
+            visible: false,
+        }
+    }
+
+    /// Whether the spinner draws at all.
+    #[must_use]
+    pub fn is_enabled(&self) -> bool {
+        self.enabled
+    }
+
+    /// Redraw the spinner if the session has been idle long enough.
+    pub fn tick(&mut self) {
+        if self.enabled {
+            let _ = self.render(&mut io::stderr().lock(), self.idle_since.elapsed());
+        }
+    }
+
+    /// Erase the spinner and restart the idle clock.
+    pub fn reset(&mut self) {
+        self.clear();
+        self.idle_since = Instant::now();
+    }
+
+    /// Erase the spinner line, if drawn.
+    pub fn clear(&mut self) {
+        if self.visible {
+            let mut stderr = io::stderr().lock();
+            let _ = write!(stderr, "\r\x1b[K");
+            let _ = stderr.flush();
+            self.visible = false;
+        }
+    }
+
+    fn render<W: Write>(&mut self, out: &mut W, idle: Duration) -> io::Result<()> {
+        if idle < SPINNER_DELAY {
+            return Ok(());
+        }
+        let frame = SPINNER_FRAMES[self.frame % SPINNER_FRAMES.len()];
+        self.frame = self.frame.wrapping_add(1);
+        write!(
+            out,
+            "\r{} {}\x1b[K",
+            frame.cyan(),
+            format!("Waiting for Claude… {}s", idle.as_secs()).dimmed()
+        )?;
+        self.visible = true;
+        out.flush()
+    }
+}
+
 #[cfg(test)]
 mod tests {
     use super::*;
 
+    #[test]
+    fn test_thinking_deltas_follow_shared_options() {
+        let shared = SharedDisplayOptions::new(DisplayOptions {
+            show_thinking: false,
+            ..DisplayOptions::default()
+        });
+        let handle = shared.clone();
+        let mut out = Vec::new();
+
+        let mut stream = |fragment: &str| {
+            write_thinking_delta(&mut out, fragment, &shared.get()).unwrap();
+        };
+        stream("hidden ");
+        handle.update(Some(true), None);
+        stream("shown ");
+        stream("still shown ");
+        handle.update(Some(false), Some(Verbosity::Normal));
+        stream("hidden again");
+
+        let text = String::from_utf8(out).unwrap();
+        assert!(!text.contains("hidden"));
+        assert!(text.contains("shown "));
+        assert!(text.contains("still shown "));
+        assert_eq!(shared.get().verbosity(), Verbosity::Normal);
+    }
+
     #[test]
     fn test_truncate_short_string() {
         assert_eq!(truncate("hello", 10, false), "hello");
@@ -327,6 +709,78 @@ mod tests {
         assert!(truncated.ends_with("..."));
     }
 
+    #[test]
+    fn test_truncate_bytes() {
+        assert_eq!(truncate_bytes("hello", 5), "hello");
+        assert_eq!(truncate_bytes("hello world", 5), "hello… 6 more bytes");
+        // Never splits a multi-byte character
+        assert_eq!(truncate_bytes("héllo", 2), "h… 5 more bytes");
+    }
+
+    #[test]
+    fn test_write_tool_request_caps_oversized_input() {
+        let content = "x".repeat(1_000_000);
+        let input = serde_json::json!({ "file_path": "big.txt", "content": content });
+        let options = DisplayOptions {
+            max_output_bytes: 100,
+            ..DisplayOptions::default()
+        };
+
+        let mut out = Vec::new();
+        write_tool_request(&mut out, "Write", &input, &options).unwrap();
+        let rendered = String::from_utf8(out).unwrap();
+
+        assert!(rendered.len() < 200, "{} bytes rendered", rendered.len());
+        assert!(rendered.contains("Write"));
+        assert!(rendered.contains("more bytes"));
+    }
+
+    #[test]
+    fn test_write_tool_result_caps_oversized_content() {
+        let content = "y".repeat(10_000);
+        let options = DisplayOptions {
+            max_output_bytes: 64,
+            ..DisplayOptions::default()
+        };
+
+        let mut out = Vec::new();
+        write_tool_result(&mut out, "toolu_01", &content, false, &options).unwrap();
+        let rendered = String::from_utf8(out).unwrap();
+
+        assert!(rendered.contains(&format!("{}… 9936 more bytes", "y".repeat(64))));
+    }
+
+    #[test]
+    fn test_write_tool_result_small_content_untouched() {
+        let mut out = Vec::new();
+        write_tool_result(
+            &mut out,
+            "toolu_01",
+            "ok",
+            false,
+            &DisplayOptions::default(),
+        )
+        .unwrap();
+        let rendered = String::from_utf8(out).unwrap();
+        assert!(rendered.contains("ok"));
+        assert!(!rendered.contains("more bytes"));
+    }
+
+    #[test]
+    fn test_spinner_waits_for_delay() {
+        let mut spinner = Spinner::new(true);
+        let mut out = Vec::new();
+        spinner
+            .render(&mut out, Duration::from_millis(200))
+            .unwrap();
+        assert!(out.is_empty());
+
+        spinner.render(&mut out, Duration::from_secs(12)).unwrap();
+        let rendered = String::from_utf8(out).unwrap();
+        assert!(rendered.starts_with('\r'));
+        assert!(rendered.contains("12s"));
+    }
+
     #[test]
     fn test_format_tool_input_raw_mode_no_truncation() {
         let long_content = "a".repeat(100);
@@ -1,7 +1,13 @@
 //! Completion detection for stop hook handling.
 
+use serde::{Deserialize, Serialize};
+
+use crate::cli::AssistantMessage;
+use crate::config::StopConfig;
+
 /// Result of analyzing text for completion status.
-#[derive(Debug, Clone, PartialEq, Eq)]
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(tag = "status", content = "reason", rename_all = "snake_case")]
 pub enum CompletionStatus {
     /// Task appears to be complete.
     Complete,
@@ -11,6 +17,39 @@ pub enum CompletionStatus {
     Unknown,
 }
 
+/// Completion status of a message and the evidence behind it.
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct CompletionAssessment {
+    /// Status the phrases point to; incomplete phrases take priority.
+    pub verdict: CompletionStatus,
+    /// Completion phrases found in the message.
+    pub matched_completion_phrases: Vec<String>,
+    /// Incomplete phrases found in the message.
+    pub matched_incomplete_phrases: Vec<String>,
+    /// Share of the matched phrases that signal completion, from 0 to 1;
+    /// 0.5 when no phrase matched.
+    pub confidence: f32,
+}
+
+impl CompletionAssessment {
+    /// Whether any phrase matched.
+    #[must_use]
+    pub fn has_evidence(&self) -> bool {
+        !self.matched_completion_phrases.is_empty() || !self.matched_incomplete_phrases.is_empty()
+    }
+
+    /// Human-readable summary used in logs and the audit log.
+    #[must_use]
+    pub fn describe(&self) -> String {
+        format!(
+            "Completion confidence {:.2} (completion phrases: [{}]; incomplete phrases: [{}])",
+            self.confidence,
+            self.matched_completion_phrases.join(", "),
+            self.matched_incomplete_phrases.join(", ")
+        )
+    }
+}
+
 /// Detects whether Claude's response indicates task completion.
 #[derive(Debug, Clone)]
 pub struct CompletionDetector {
@@ -28,6 +67,15 @@ impl CompletionDetector {
         }
     }
 
+    /// Create a completion detector with the phrases of `config`.
+    #[must_use]
+    pub fn from_config(config: &StopConfig) -> Self {
+        Self::new(
+            config.completion_phrases.clone(),
+            config.incomplete_phrases.clone(),
+        )
+    }
+
     /// Check if the text indicates task completion.
     /// Incomplete phrases take priority over complete phrases.
     #[must_use]
@@ -41,24 +89,60 @@ impl CompletionDetector {
     /// Returns `Unknown` if no phrases match.
     #[must_use]
     pub fn analyze(&self, text: &str) -> CompletionStatus {
+        self.assess(text).verdict
+    }
+
+    /// Assess text: which phrases it contains, the status they point to and
+    /// how confident that it is complete.
+    #[must_use]
+    pub fn assess(&self, text: &str) -> CompletionAssessment {
         let text_lower = text.to_lowercase();
+        let matching = |phrases: &[String]| -> Vec<String> {
+            phrases
+                .iter()
+                .filter(|phrase| text_lower.contains(&phrase.to_lowercase()))
+                .cloned()
+                .collect()
+        };
+        let complete = matching(&self.complete_phrases);
+        let incomplete = matching(&self.incomplete_phrases);
 
         // Incomplete phrases take priority
-        for phrase in &self.incomplete_phrases {
-            if text_lower.contains(&phrase.to_lowercase()) {
-                return CompletionStatus::Incomplete(format!("Found incomplete phrase: {phrase}"));
+        let verdict = match (incomplete.first(), complete.is_empty()) {
+            (Some(phrase), _) => {
+                CompletionStatus::Incomplete(format!("Found incomplete phrase: {phrase}"))
             }
-        }
+            (None, false) => CompletionStatus::Complete,
+            (None, true) => CompletionStatus::Unknown,
+        };
+        let matched = complete.len() + incomplete.len();
+        #[allow(clippy::cast_precision_loss)]
+        let confidence = if matched == 0 {
+            0.5
+        } else {
+            complete.len() as f32 / matched as f32
+        };
 
-        // Check for completion phrases
-        for phrase in &self.complete_phrases {
-            if text_lower.contains(&phrase.to_lowercase()) {
-                return CompletionStatus::Complete;
-            }
+        CompletionAssessment {
+            verdict,
+            matched_completion_phrases: complete,
+            matched_incomplete_phrases: incomplete,
+            confidence,
         }
+    }
 
-        // Default: unknown
-        CompletionStatus::Unknown
+    /// Analyze the text blocks of an assistant message.
+    ///
+    /// Thinking and tool calls are ignored; only what Claude said counts.
+    #[must_use]
+    pub fn analyze_message(&self, message: &AssistantMessage) -> CompletionStatus {
+        self.analyze(&message.text())
+    }
+
+    /// Assess the text blocks of an assistant message.
+    #[must_use]
+    pub fn assess_message(&self, message: &AssistantMessage) -> CompletionAssessment {
+        self.assess(&message.text())
     }
 }
 
@@ -180,6 +264,54 @@ mod tests {
         assert_eq!(status, CompletionStatus::Unknown);
     }
 
+    #[test]
+    fn test_analyze_message_ignores_thinking() {
+        let detector = CompletionDetector::default();
+        let message: AssistantMessage = serde_json::from_value(serde_json::json!({
+            "role": "assistant",
+            "content": [
+                {"type": "thinking", "thinking": "Next step is the tests."},
+                {"type": "text", "text": "The task is complete."},
+            ],
+        }))
+        .unwrap();
+        assert_eq!(
+            detector.analyze_message(&message),
+            CompletionStatus::Complete
+        );
+    }
+
+    #[test]
+    fn test_assess_mixed_signals() {
+        let detector = CompletionDetector::default();
+        let assessment = detector
+            .assess("All done and the implementation is complete, but the next step is the docs.");
+        assert!(matches!(
+            assessment.verdict,
+            CompletionStatus::Incomplete(_)
+        ));
+        assert_eq!(
+            assessment.matched_completion_phrases,
+            vec!["all done", "implementation is complete"]
+        );
+        assert_eq!(assessment.matched_incomplete_phrases, vec!["next step"]);
+        assert!((assessment.confidence - 2.0 / 3.0).abs() < f32::EPSILON);
+        assert!(assessment.describe().contains("0.67"));
+    }
+
+    #[test]
+    fn test_assess_without_evidence() {
+        let detector = CompletionDetector::default();
+        let assessment = detector.assess("Here is some random text.");
+        assert_eq!(assessment.verdict, CompletionStatus::Unknown);
+        assert!(!assessment.has_evidence());
+        assert!((assessment.confidence - 0.5).abs() < f32::EPSILON);
+
+        let assessment = detector.assess("The task is complete.");
+        assert!(assessment.has_evidence());
+        assert!((assessment.confidence - 1.0).abs() < f32::EPSILON);
+    }
+
     #[test]
     fn test_analyze_incomplete_priority() {
         let detector = CompletionDetector::default();
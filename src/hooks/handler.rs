@@ -1,11 +1,26 @@
 //! Hook handler that processes Claude Code hook events.
 
-use crate::config::StopConfig;
-use crate::ipc::{EscalationRequest, EscalationResponse, IpcClient};
-use crate::supervisor::{PolicyDecision, PolicyEngine};
-use crate::watcher::{PatternDetector, StuckPattern, ToolCallRecord};
-
-use super::completion::{CompletionDetector, CompletionStatus};
+use std::borrow::Cow;
+use std::path::{Path, PathBuf};
+use std::time::SystemTime;
+
+use tracing::Instrument;
+
+use crate::config::{SnapshotConfig, StopConfig};
+use crate::ipc::{EscalationRequest, EscalationResponse, HookDecisionReport, IpcClient};
+use crate::snapshot::{write_target, SnapshotStore};
+use crate::supervisor::{
+    take_appeal, take_budget_note, KillSwitch, PolicyDecision, PolicyEngine, TaskLedger,
+    KILL_SWITCH_REASON, TODO_WRITE_TOOL, WRAP_UP_MESSAGE,
+};
+use crate::telemetry::SPAN_HOOK_HANDLE;
+use crate::trash::TrashError;
+use crate::watcher::{
+    last_assistant_text, parse_jsonl_content, tool_use_inputs, JournalEntry, PatternDetector,
+    StuckPattern, ToolCallRecord,
+};
+
+use super::completion::{CompletionAssessment, CompletionDetector, CompletionStatus};
 use super::input::HookInput;
 use super::iteration::IterationTracker;
 use super::pre_tool_use::PreToolUseResponse;
@@ -31,6 +46,24 @@ pub struct HookResult {
     pub response: String,
     /// Whether the hook should deny/block (exit code 2).
     pub should_deny: bool,
+    /// Final decision for a `PreToolUse` event; `None` when the user is
+    /// asked and for other events.
+    pub decision: Option<EscalationResponse>,
+}
+
+impl HookResult {
+    /// Report of this result's decision for `input`, to send to the supervisor.
+    ///
+    /// `None` when there is no final decision or the input has no tool use ID.
+    #[must_use]
+    pub fn decision_report(&self, input: &HookInput) -> Option<HookDecisionReport> {
+        Some(HookDecisionReport {
+            session_id: input.session_id.clone(),
+            tool_use_id: input.tool_use_id.clone()?,
+            tool_name: input.tool_name.clone()?,
+            decision: self.decision.clone()?,
+        })
+    }
 }
 
 /// Handler for Claude Code hook events.
@@ -42,6 +75,11 @@ pub struct HookHandler {
     completion: CompletionDetector,
     pattern_detector: PatternDetector,
     ipc_client: Option<IpcClient>,
+    snapshots: Option<SnapshotConfig>,
+    wrap_up_at: Option<SystemTime>,
+    additional_context: bool,
+    kill_switch: Option<KillSwitch>,
+    budget_note: Option<PathBuf>,
 }
 
 impl HookHandler {
@@ -55,6 +93,11 @@ impl HookHandler {
             completion: CompletionDetector::default(),
             pattern_detector: PatternDetector::new(),
             ipc_client: None,
+            snapshots: None,
+            wrap_up_at: None,
+            additional_context: true,
+            kill_switch: None,
+            budget_note: None,
         }
     }
 
@@ -72,6 +115,11 @@ impl HookHandler {
             completion,
             pattern_detector: PatternDetector::new(),
             ipc_client: None,
+            snapshots: None,
+            wrap_up_at: None,
+            additional_context: true,
+            kill_switch: None,
+            budget_note: None,
         }
     }
 
@@ -82,6 +130,53 @@ impl HookHandler {
         self
     }
 
+    /// Snapshot Write/Edit targets before approving them.
+    ///
+    /// Has no effect unless `config.enabled` is set.
+    #[must_use]
+    pub fn with_snapshots(mut self, config: SnapshotConfig) -> Self {
+        self.snapshots = config.enabled.then_some(config);
+        self
+    }
+
+    /// Tell Claude to wrap up at the first stop after `at`.
+    #[must_use]
+    pub fn with_wrap_up_at(mut self, at: SystemTime) -> Self {
+        self.wrap_up_at = Some(at);
+        self
+    }
+
+    /// Pass supervisor guidance to Claude as `additionalContext`.
+    ///
+    /// Claude Code versions that predate the field ignore it; with this
+    /// disabled the guidance is sent as the decision reason instead.
+    #[must_use]
+    pub fn with_additional_context(mut self, enabled: bool) -> Self {
+        self.additional_context = enabled;
+        self
+    }
+
+    /// Deny every tool call while `kill_switch` is engaged.
+    #[must_use]
+    pub fn with_kill_switch(mut self, kill_switch: KillSwitch) -> Self {
+        self.kill_switch = Some(kill_switch);
+        self
+    }
+
+    /// Pass the budget note left at `path` on to Claude with the next
+    /// approved tool call.
+    #[must_use]
+    pub fn with_budget_note(mut self, path: impl Into<PathBuf>) -> Self {
+        self.budget_note = Some(path.into());
+        self
+    }
+
+    /// Whether the session's wrap-up warning is due.
+    #[must_use]
+    pub fn wrap_up_due(&self) -> bool {
+        self.wrap_up_at.is_some_and(|at| SystemTime::now() >= at)
+    }
+
     /// Returns whether an IPC client is configured.
     #[must_use]
     pub fn has_ipc_client(&self) -> bool {
@@ -116,13 +211,43 @@ impl HookHandler {
     ///
     /// Returns an error if the hook event is unknown or required fields are missing.
     pub fn handle(&self, input: &HookInput) -> Result<HookResult, HookError> {
+        let _span = hook_span(input).entered();
         match input.hook_event_name.as_str() {
             "PreToolUse" => self.handle_pre_tool_use(input),
-            "Stop" => self.handle_stop(input),
+            "Stop" | "SubagentStop" => self.handle_stop(input),
             other => Err(HookError::UnknownEvent(other.to_string())),
         }
     }
 
+    /// The policy engine with the level for the hook's permission mode,
+    /// escrowing deletions to the hook session's trash.
+    fn policy_for(&self, input: &HookInput) -> Cow<'_, PolicyEngine> {
+        self.policy
+            .for_session(&input.session_id, input.permission_mode.as_deref())
+    }
+
+    /// Policy decision for a tool call; a denial while the kill switch is
+    /// engaged.
+    fn evaluate(
+        &self,
+        input: &HookInput,
+        tool_name: &str,
+        tool_input: &serde_json::Value,
+    ) -> PolicyDecision {
+        if self
+            .kill_switch
+            .as_ref()
+            .is_some_and(KillSwitch::is_engaged)
+        {
+            return PolicyDecision::deny(KILL_SWITCH_REASON.to_string());
+        }
+        self.policy_for(input).evaluate_with_cwd(
+            tool_name,
+            tool_input,
+            input.cwd.as_deref().map(Path::new),
+        )
+    }
+
     /// Handle a `PreToolUse` event.
     fn handle_pre_tool_use(&self, input: &HookInput) -> Result<HookResult, HookError> {
         let tool_name = input
@@ -135,28 +260,210 @@ impl HookHandler {
             .clone()
             .unwrap_or(serde_json::Value::Object(serde_json::Map::new()));
 
-        let decision = self.policy.evaluate(tool_name, &tool_input);
+        let decision = self.evaluate(input, tool_name, &tool_input);
+        self.pre_tool_use_result(input, tool_name, decision, None)
+    }
+
+    /// Handle a `PreToolUse` event whose policy decision was made elsewhere,
+    /// such as by the supervisor's engine (see
+    /// [`IpcClient::evaluate`](crate::ipc::IpcClient::evaluate)).
+    ///
+    /// The kill switch still outranks the decision.
+    ///
+    /// # Errors
+    ///
+    /// Returns an error if `tool_name` is missing or the response cannot be
+    /// serialized.
+    pub fn handle_decided(
+        &self,
+        input: &HookInput,
+        decision: PolicyDecision,
+    ) -> Result<HookResult, HookError> {
+        let _span = hook_span(input).entered();
+        let tool_name = input
+            .tool_name
+            .as_deref()
+            .ok_or_else(|| HookError::MissingField("tool_name".to_string()))?;
+        let decision = if self
+            .kill_switch
+            .as_ref()
+            .is_some_and(KillSwitch::is_engaged)
+        {
+            PolicyDecision::deny(KILL_SWITCH_REASON.to_string())
+        } else {
+            decision
+        };
+        self.pre_tool_use_result(input, tool_name, decision, None)
+    }
+
+    /// Handle a `PreToolUse` event, escalating to the supervisor over IPC.
+    ///
+    /// Escalated calls are decided by the supervisor when one is running,
+    /// instead of asking the user. Appeal fields (see
+    /// [`AppealGate`](crate::supervisor::AppealGate)) are stripped from the
+    /// input before the policy sees it and forwarded with the escalation.
+    ///
+    /// The final decision is reported to the supervisor (see
+    /// [`HookDecisionLog`](crate::ipc::HookDecisionLog)) so the runner does
+    /// not re-decide the call blind.
+    ///
+    /// # Errors
+    ///
+    /// Returns an error if `tool_name` is missing or the response cannot be
+    /// serialized.
+    pub async fn handle_pre_tool_use_async(
+        &self,
+        input: &HookInput,
+    ) -> Result<HookResult, HookError> {
+        async {
+            let result = self.decide_pre_tool_use_async(input).await?;
+            self.report_decision(input, &result).await;
+            Ok(result)
+        }
+        .instrument(hook_span(input))
+        .await
+    }
 
-        let (response, should_deny) = match decision {
+    /// Report a `PreToolUse` result's decision to the supervisor via IPC.
+    ///
+    /// Does nothing if no IPC client is configured, the supervisor is not
+    /// running, or there is no final decision to report.
+    pub async fn report_decision(&self, input: &HookInput, result: &HookResult) {
+        let Some(client) = self.ipc_client.as_ref() else {
+            return;
+        };
+        let Some(report) = result.decision_report(input) else {
+            return;
+        };
+        if !client.is_supervisor_running() {
+            return;
+        }
+        if let Err(e) = client.report_decision(&report).await {
+            tracing::warn!(
+                tool_use_id = %report.tool_use_id,
+                error = %e,
+                "Failed to report decision to supervisor"
+            );
+        }
+    }
+
+    async fn decide_pre_tool_use_async(&self, input: &HookInput) -> Result<HookResult, HookError> {
+        let tool_name = input
+            .tool_name
+            .as_deref()
+            .ok_or_else(|| HookError::MissingField("tool_name".to_string()))?;
+
+        let mut tool_input = input
+            .tool_input
+            .clone()
+            .unwrap_or(serde_json::Value::Object(serde_json::Map::new()));
+        let original = tool_input.clone();
+        let appeal = take_appeal(&mut tool_input);
+        let stripped = tool_input != original;
+
+        let mut decision = self.evaluate(input, tool_name, &tool_input);
+        if stripped && decision == PolicyDecision::Allow {
+            // The appeal fields must not reach the tool
+            decision = PolicyDecision::AllowWithModification(tool_input.clone());
+        }
+
+        let PolicyDecision::Escalate(ref reason) = decision else {
+            return self.pre_tool_use_result(input, tool_name, decision, None);
+        };
+        let request = EscalationRequest {
+            session_id: input.session_id.clone(),
+            tool_name: tool_name.to_string(),
+            tool_input: tool_input.clone(),
+            reason: reason.clone(),
+            appeal,
+        };
+        let Some(response) = self.try_escalate_request(request).await else {
+            return self.pre_tool_use_result(input, tool_name, decision, None);
+        };
+
+        let (decision, guidance) = match response {
+            EscalationResponse::Allow if stripped => {
+                (PolicyDecision::AllowWithModification(tool_input), None)
+            }
+            EscalationResponse::Allow => (PolicyDecision::Allow, None),
+            EscalationResponse::Deny { reason } => (PolicyDecision::deny(reason), None),
+            EscalationResponse::Modify { updated_input } => {
+                (PolicyDecision::AllowWithModification(updated_input), None)
+            }
+            EscalationResponse::Guide { guidance } if stripped => (
+                PolicyDecision::AllowWithModification(tool_input),
+                Some(guidance),
+            ),
+            EscalationResponse::Guide { guidance } => (PolicyDecision::Allow, Some(guidance)),
+        };
+        self.pre_tool_use_result(input, tool_name, decision, guidance)
+    }
+
+    /// Turn a policy decision into the `PreToolUse` hook result.
+    ///
+    /// `guidance` from the supervisor accompanies an allow; it is reported
+    /// back as a [`EscalationResponse::Guide`] decision.
+    fn pre_tool_use_result(
+        &self,
+        input: &HookInput,
+        tool_name: &str,
+        decision: PolicyDecision,
+        guidance: Option<String>,
+    ) -> Result<HookResult, HookError> {
+        let (response, should_deny, decision) = match decision {
             PolicyDecision::Allow => {
+                let tool_input = input.tool_input.clone().unwrap_or_default();
+                self.snapshot_before_write(input, tool_name, &tool_input);
+                self.escrow_before_write(input, tool_name, &tool_input);
                 tracing::info!(tool = %tool_name, decision = "allow", "Tool call approved");
-                (PreToolUseResponse::allow(), false)
+                (
+                    PreToolUseResponse::allow(),
+                    false,
+                    Some(EscalationResponse::Allow),
+                )
             }
             PolicyDecision::AllowWithModification(updated_input) => {
+                self.snapshot_before_write(input, tool_name, &updated_input);
+                self.escrow_before_write(input, tool_name, &updated_input);
                 tracing::info!(tool = %tool_name, decision = "allow_modified", "Tool call approved with modified input");
                 (
-                    PreToolUseResponse::allow_with_modification(updated_input),
+                    PreToolUseResponse::allow_with_modification(updated_input.clone()),
                     false,
+                    Some(EscalationResponse::Modify { updated_input }),
                 )
             }
             PolicyDecision::Deny(reason) => {
                 tracing::warn!(tool = %tool_name, reason = %reason, "Tool call denied");
-                (PreToolUseResponse::deny(&reason), true)
+                (
+                    PreToolUseResponse::deny(reason.as_str()),
+                    true,
+                    Some(EscalationResponse::Deny {
+                        reason: reason.into(),
+                    }),
+                )
             }
             PolicyDecision::Escalate(reason) => {
                 tracing::info!(tool = %tool_name, reason = %reason, "Tool call escalated");
-                (PreToolUseResponse::ask(&reason), false)
+                (PreToolUseResponse::ask(&reason), false, None)
+            }
+        };
+        // A budget note waits for a call Claude is told about
+        let guidance = if should_deny {
+            None
+        } else {
+            guidance.or_else(|| self.budget_note.as_deref().and_then(take_budget_note))
+        };
+        let (response, decision) = match guidance {
+            Some(guidance) => {
+                tracing::info!(tool = %tool_name, guidance = %guidance, "Passing supervisor guidance to Claude");
+                let response = if self.additional_context {
+                    response.with_additional_context(&guidance)
+                } else {
+                    response.with_reason(&guidance)
+                };
+                (response, Some(EscalationResponse::Guide { guidance }))
             }
+            _ => (response, decision),
         };
 
         let response_json = serde_json::to_string(&response)?;
@@ -164,9 +471,86 @@ impl HookHandler {
         Ok(HookResult {
             response: response_json,
             should_deny,
+            decision,
         })
     }
 
+    /// Snapshot the target of an approved write before the approval is returned.
+    ///
+    /// Bounded by the configured timeout; a slow or failed snapshot is
+    /// skipped with a warning rather than delaying the tool call.
+    fn snapshot_before_write(
+        &self,
+        input: &HookInput,
+        tool_name: &str,
+        tool_input: &serde_json::Value,
+    ) {
+        let Some(config) = self.snapshots.as_ref() else {
+            return;
+        };
+        let Some(target) = write_target(tool_name, tool_input) else {
+            return;
+        };
+        let cwd = input.cwd.as_deref().map_or_else(
+            || std::env::current_dir().unwrap_or_else(|_| PathBuf::from("/")),
+            PathBuf::from,
+        );
+
+        let store = SnapshotStore::from_config(config, &cwd);
+        let path = cwd.join(target);
+        if let Some(entry) = store.snapshot_within(&input.session_id, &path, config.timeout()) {
+            tracing::info!(
+                tool = %tool_name,
+                path = %path.display(),
+                snapshot_id = %entry.id,
+                "Snapshot taken before write"
+            );
+        }
+    }
+
+    /// Copy a file into the session's trash before a Write empties it.
+    ///
+    /// Does nothing unless escrow is enabled.
+    fn escrow_before_write(
+        &self,
+        input: &HookInput,
+        tool_name: &str,
+        tool_input: &serde_json::Value,
+    ) {
+        let Some(escrow) = self.policy.escrow() else {
+            return;
+        };
+        let empties = matches!(tool_name, "Write" | "write")
+            && tool_input
+                .get("content")
+                .and_then(serde_json::Value::as_str)
+                == Some("");
+        let Some(target) = write_target(tool_name, tool_input).filter(|_| empties) else {
+            return;
+        };
+        let cwd = input.cwd.as_deref().map_or_else(
+            || std::env::current_dir().unwrap_or_else(|_| PathBuf::from("/")),
+            PathBuf::from,
+        );
+
+        let path = cwd.join(target);
+        if !std::fs::metadata(&path).is_ok_and(|metadata| metadata.is_file() && metadata.len() > 0)
+        {
+            return;
+        }
+        match escrow.store(&cwd).copy_in(&input.session_id, &path) {
+            Ok(entry) => tracing::info!(
+                tool = %tool_name,
+                path = %entry.path.display(),
+                "File copied to the trash before being emptied"
+            ),
+            Err(TrashError::OutsideProject(_)) => {}
+            Err(e) => {
+                tracing::warn!(path = %path.display(), error = %e, "Failed to copy file to the trash");
+            }
+        }
+    }
+
     /// Handle a `Stop` event.
     fn handle_stop(&self, input: &HookInput) -> Result<HookResult, HookError> {
         // If stop_hook_active is true, allow to prevent infinite loops
@@ -177,19 +561,40 @@ impl HookHandler {
             return Ok(HookResult {
                 response: response_json,
                 should_deny: false,
+                decision: None,
+            });
+        }
+
+        // Near the time limit, the stop is blocked once to deliver the warning
+        if self.wrap_up_due() {
+            tracing::info!(session = %input.session_id, "Time limit near, asking Claude to wrap up");
+            let response = StopResponse::block(WRAP_UP_MESSAGE);
+            let response_json = serde_json::to_string(&response)?;
+            return Ok(HookResult {
+                response: response_json,
+                should_deny: false,
+                decision: None,
             });
         }
 
-        // Increment iteration count
-        let iteration = self.iterations.increment(&input.session_id);
-        tracing::debug!(session = %input.session_id, iteration = iteration, "Stop event iteration");
+        // Increment iteration count, kept apart for each subagent
+        let agent_id = input.agent_id();
+        let iteration = self.iterations.increment(&input.session_id, agent_id);
+        tracing::debug!(
+            session = %input.session_id,
+            agent = ?agent_id,
+            iteration = iteration,
+            "Stop event iteration"
+        );
 
         // If we've exceeded max iterations, allow stop
-        if iteration > self.stop_config.max_iterations {
+        let max = self.stop_config.max_iterations_for(agent_id);
+        if iteration > max {
             tracing::info!(
                 session = %input.session_id,
+                agent = ?agent_id,
                 iteration = iteration,
-                max = self.stop_config.max_iterations,
+                max = max,
                 "Max iterations exceeded, allowing stop"
             );
             let response = StopResponse::allow();
@@ -197,6 +602,21 @@ impl HookHandler {
             return Ok(HookResult {
                 response: response_json,
                 should_deny: false,
+                decision: None,
+            });
+        }
+
+        // Decide from Claude's plan and final message when there is a transcript
+        let entries = read_transcript(input);
+        if let Some(response) = self.stop_by_todos(&input.session_id, &entries).or_else(|| {
+            self.assess_entries(&entries)
+                .and_then(|assessment| self.stop_by_completion(&input.session_id, &assessment))
+        }) {
+            let response_json = serde_json::to_string(&response)?;
+            return Ok(HookResult {
+                response: response_json,
+                should_deny: false,
+                decision: None,
             });
         }
 
@@ -208,6 +628,7 @@ impl HookHandler {
             return Ok(HookResult {
                 response: response_json,
                 should_deny: false,
+                decision: None,
             });
         }
 
@@ -219,6 +640,7 @@ impl HookHandler {
         Ok(HookResult {
             response: response_json,
             should_deny: false,
+            decision: None,
         })
     }
 
@@ -257,8 +679,9 @@ impl HookHandler {
         }
 
         // Check iteration count - if exceeded, allow stop
-        let iteration = self.iterations.get(&input.session_id);
-        if iteration >= self.stop_config.max_iterations {
+        let agent_id = input.agent_id();
+        let iteration = self.iterations.get(&input.session_id, agent_id);
+        if iteration >= self.stop_config.max_iterations_for(agent_id) {
             return None;
         }
 
@@ -290,12 +713,15 @@ impl HookHandler {
         }
 
         // Check iteration count
-        let iteration = self.iterations.get(&input.session_id);
-        if iteration >= self.stop_config.max_iterations {
+        let agent_id = input.agent_id();
+        let iteration = self.iterations.get(&input.session_id, agent_id);
+        let max = self.stop_config.max_iterations_for(agent_id);
+        if iteration >= max {
             tracing::info!(
                 session = %input.session_id,
+                agent = ?agent_id,
                 iteration = iteration,
-                max = self.stop_config.max_iterations,
+                max = max,
                 "Max iterations reached, allowing stop"
             );
             return StopResponse::allow();
@@ -311,24 +737,17 @@ impl HookHandler {
             return StopResponse::allow();
         }
 
+        // Block while Claude's plan has open items
+        let entries = read_transcript(input);
+        if let Some(response) = self.stop_by_todos(&input.session_id, &entries) {
+            return response;
+        }
+
         // Analyze final message for completion status
         if let Some(message) = final_message {
-            match self.completion.analyze(message) {
-                CompletionStatus::Complete => {
-                    tracing::info!(session = %input.session_id, "Task appears complete");
-                    return StopResponse::allow();
-                }
-                CompletionStatus::Incomplete(reason) => {
-                    tracing::info!(
-                        session = %input.session_id,
-                        reason = %reason,
-                        "Task appears incomplete, blocking stop"
-                    );
-                    return StopResponse::block(format!("Task incomplete: {reason}"));
-                }
-                CompletionStatus::Unknown => {
-                    // Fall through to default behavior
-                }
+            let assessment = self.completion.assess(message);
+            if let Some(response) = self.stop_by_completion(&input.session_id, &assessment) {
+                return response;
             }
         }
 
@@ -342,6 +761,67 @@ impl HookHandler {
         StopResponse::allow()
     }
 
+    /// Decide a stop from the completion assessment of Claude's final
+    /// message: block it when the confidence is below
+    /// [`min_completion_confidence`](StopConfig::min_completion_confidence).
+    ///
+    /// `None` when no phrase matched.
+    fn stop_by_completion(
+        &self,
+        session_id: &str,
+        assessment: &CompletionAssessment,
+    ) -> Option<StopResponse> {
+        if !assessment.has_evidence() {
+            return None;
+        }
+        if assessment.confidence >= self.stop_config.min_completion_confidence {
+            tracing::info!(
+                session = %session_id,
+                assessment = %assessment.describe(),
+                "Task appears complete"
+            );
+            return Some(StopResponse::allow());
+        }
+        tracing::info!(
+            session = %session_id,
+            assessment = %assessment.describe(),
+            "Task appears incomplete, blocking stop"
+        );
+        let reason = match assessment.verdict {
+            CompletionStatus::Incomplete(ref reason) => reason.clone(),
+            _ => assessment.describe(),
+        };
+        Some(StopResponse::block(format!("Task incomplete: {reason}")))
+    }
+
+    /// Block a stop while Claude's `TodoWrite` plan in the transcript has
+    /// pending or in-progress items, unless
+    /// [`allow_stop_with_open_todos`](StopConfig::allow_stop_with_open_todos).
+    fn stop_by_todos(&self, session_id: &str, entries: &[JournalEntry]) -> Option<StopResponse> {
+        if self.stop_config.allow_stop_with_open_todos {
+            return None;
+        }
+        let ledger = TaskLedger::replay(
+            tool_use_inputs(entries, TODO_WRITE_TOOL),
+            chrono::Utc::now(),
+        );
+        let reason = ledger.open_reason()?;
+        tracing::info!(
+            session = %session_id,
+            todos = %ledger.summary(),
+            "Plan has open items, blocking stop"
+        );
+        Some(StopResponse::block(format!(
+            "{reason}. Finish them, or update the plan with TodoWrite, before stopping."
+        )))
+    }
+
+    /// Completion assessment of Claude's last message in `entries`.
+    fn assess_entries(&self, entries: &[JournalEntry]) -> Option<CompletionAssessment> {
+        let text = last_assistant_text(entries)?;
+        Some(self.completion.assess(&text))
+    }
+
     /// Attempt to escalate a tool call to the supervisor via IPC.
     ///
     /// Returns `None` if no IPC client is configured or the supervisor is not running.
@@ -356,6 +836,21 @@ impl HookHandler {
         tool_input: &serde_json::Value,
         reason: &str,
     ) -> Option<EscalationResponse> {
+        self.try_escalate_request(EscalationRequest {
+            session_id: session_id.to_string(),
+            tool_name: tool_name.to_string(),
+            tool_input: tool_input.clone(),
+            reason: reason.to_string(),
+            appeal: None,
+        })
+        .await
+    }
+
+    /// Send an escalation request to the supervisor via IPC.
+    ///
+    /// Returns `None` if no IPC client is configured, the supervisor is not
+    /// running, or the request fails.
+    async fn try_escalate_request(&self, request: EscalationRequest) -> Option<EscalationResponse> {
         let client = self.ipc_client.as_ref()?;
 
         if !client.is_supervisor_running() {
@@ -363,25 +858,19 @@ impl HookHandler {
             return None;
         }
 
-        let request = EscalationRequest {
-            session_id: session_id.to_string(),
-            tool_name: tool_name.to_string(),
-            tool_input: tool_input.clone(),
-            reason: reason.to_string(),
-        };
-
         tracing::debug!(
-            session_id = %session_id,
-            tool_name = %tool_name,
-            reason = %reason,
+            session_id = %request.session_id,
+            tool_name = %request.tool_name,
+            reason = %request.reason,
+            appeal = request.appeal.is_some(),
             "Escalating to supervisor"
         );
 
         match client.escalate(&request).await {
             Ok(response) => {
                 tracing::info!(
-                    session_id = %session_id,
-                    tool_name = %tool_name,
+                    session_id = %request.session_id,
+                    tool_name = %request.tool_name,
                     response = ?response,
                     "Received supervisor response"
                 );
@@ -389,8 +878,8 @@ impl HookHandler {
             }
             Err(e) => {
                 tracing::warn!(
-                    session_id = %session_id,
-                    tool_name = %tool_name,
+                    session_id = %request.session_id,
+                    tool_name = %request.tool_name,
                     error = %e,
                     "Failed to escalate to supervisor"
                 );
@@ -401,15 +890,17 @@ impl HookHandler {
 
     /// Attempt to escalate a Stop event to the supervisor via IPC.
     ///
-    /// The `transcript_path` parameter allows the supervisor to read the full
-    /// conversation context and final message directly from the transcript file.
+    /// The input's transcript path allows the supervisor to read the full
+    /// conversation context and final message directly from the transcript file;
+    /// `iteration` counts the stops of the input's agent, and `completion` is
+    /// the hook's own assessment of the final message.
     pub async fn try_escalate_stop(
         &self,
-        session_id: &str,
+        input: &HookInput,
         final_message: &str,
-        transcript_path: Option<&str>,
         task: Option<&str>,
         iteration: u32,
+        completion: Option<&CompletionAssessment>,
     ) -> Option<crate::ipc::StopEscalationResponse> {
         let client = self.ipc_client.as_ref()?;
 
@@ -418,16 +909,20 @@ impl HookHandler {
             return None;
         }
 
+        let session_id = &input.session_id;
         let request = crate::ipc::StopEscalationRequest {
-            session_id: session_id.to_string(),
+            session_id: session_id.clone(),
+            agent_id: input.agent_id().map(String::from),
             final_message: final_message.to_string(),
-            transcript_path: transcript_path.map(String::from),
+            transcript_path: input.transcript_path.clone(),
             task: task.map(String::from),
             iteration,
+            completion: completion.cloned(),
         };
 
         tracing::debug!(
             session_id = %session_id,
+            agent_id = ?request.agent_id,
             iteration = iteration,
             "Escalating stop to supervisor"
         );
@@ -457,6 +952,7 @@ impl HookHandler {
     /// # Errors
     ///
     /// Returns an error if the response cannot be serialized.
+    #[allow(clippy::too_many_lines)]
     pub async fn handle_stop_async(
         &self,
         input: &super::input::HookInput,
@@ -470,19 +966,40 @@ impl HookHandler {
             return Ok(HookResult {
                 response: response_json,
                 should_deny: false,
+                decision: None,
             });
         }
 
-        // Increment iteration count
-        let iteration = self.iterations.increment(&input.session_id);
-        tracing::debug!(session = %input.session_id, iteration = iteration, "Stop event iteration");
+        // Near the time limit, the stop is blocked once to deliver the warning
+        if self.wrap_up_due() {
+            tracing::info!(session = %input.session_id, "Time limit near, asking Claude to wrap up");
+            let response = StopResponse::block(WRAP_UP_MESSAGE);
+            let response_json = serde_json::to_string(&response)?;
+            return Ok(HookResult {
+                response: response_json,
+                should_deny: false,
+                decision: None,
+            });
+        }
+
+        // Increment iteration count, kept apart for each subagent
+        let agent_id = input.agent_id();
+        let iteration = self.iterations.increment(&input.session_id, agent_id);
+        tracing::debug!(
+            session = %input.session_id,
+            agent = ?agent_id,
+            iteration = iteration,
+            "Stop event iteration"
+        );
 
         // If we've exceeded max iterations, allow stop
-        if iteration > self.stop_config.max_iterations {
+        let max = self.stop_config.max_iterations_for(agent_id);
+        if iteration > max {
             tracing::info!(
                 session = %input.session_id,
+                agent = ?agent_id,
                 iteration = iteration,
-                max = self.stop_config.max_iterations,
+                max = max,
                 "Max iterations exceeded, allowing stop"
             );
             let response = StopResponse::allow();
@@ -490,21 +1007,27 @@ impl HookHandler {
             return Ok(HookResult {
                 response: response_json,
                 should_deny: false,
+                decision: None,
+            });
+        }
+
+        let entries = read_transcript(input);
+        if let Some(response) = self.stop_by_todos(&input.session_id, &entries) {
+            let response_json = serde_json::to_string(&response)?;
+            return Ok(HookResult {
+                response: response_json,
+                should_deny: false,
+                decision: None,
             });
         }
+        let assessment = self.assess_entries(&entries);
 
         // Try escalation to supervisor if available
         if self.ipc_client.is_some() {
             // Pass empty final_message - supervisor reads the transcript for actual content
             let final_message = "";
             if let Some(escalation_response) = self
-                .try_escalate_stop(
-                    &input.session_id,
-                    final_message,
-                    input.transcript_path.as_deref(),
-                    task,
-                    iteration,
-                )
+                .try_escalate_stop(input, final_message, task, iteration, assessment.as_ref())
                 .await
             {
                 let response = match escalation_response {
@@ -517,11 +1040,23 @@ impl HookHandler {
                 return Ok(HookResult {
                     response: response_json,
                     should_deny: false,
+                    decision: None,
                 });
             }
         }
 
-        // Fallback: If force_continue is enabled, block the stop
+        // Fallback: decide from the final message, then force_continue
+        if let Some(response) = assessment
+            .as_ref()
+            .and_then(|assessment| self.stop_by_completion(&input.session_id, assessment))
+        {
+            let response_json = serde_json::to_string(&response)?;
+            return Ok(HookResult {
+                response: response_json,
+                should_deny: false,
+                decision: None,
+            });
+        }
         if self.stop_config.force_continue {
             tracing::info!(session = %input.session_id, "Force continue enabled, blocking stop");
             let response = StopResponse::block("Continue working on the task.");
@@ -529,6 +1064,7 @@ impl HookHandler {
             return Ok(HookResult {
                 response: response_json,
                 should_deny: false,
+                decision: None,
             });
         }
 
@@ -540,13 +1076,41 @@ impl HookHandler {
         Ok(HookResult {
             response: response_json,
             should_deny: false,
+            decision: None,
         })
     }
 }
 
+/// Span timing the answer to a hook.
+fn hook_span(input: &HookInput) -> tracing::Span {
+    tracing::debug_span!(
+        SPAN_HOOK_HANDLE,
+        hook = %input.hook_event_name,
+        session_id = %input.session_id,
+        tool_use_id = input.tool_use_id.as_deref(),
+    )
+}
+
+/// Journal entries of the hook's transcript; none without one to read.
+fn read_transcript(input: &HookInput) -> Vec<JournalEntry> {
+    let Some(path) = input.transcript_path.as_deref() else {
+        return Vec::new();
+    };
+    match std::fs::read_to_string(path) {
+        Ok(content) => parse_jsonl_content(&content),
+        Err(e) => {
+            tracing::debug!(path = %path, error = %e, "Failed to read transcript");
+            Vec::new()
+        }
+    }
+}
+
 #[cfg(test)]
 mod tests {
+    use std::collections::HashMap;
+
     use super::*;
+    use crate::hooks::StopDecision;
     use crate::supervisor::PolicyLevel;
 
     fn create_handler(level: PolicyLevel) -> HookHandler {
@@ -568,6 +1132,108 @@ mod tests {
         assert!(result.response.contains("\"permissionDecision\":\"allow\""));
     }
 
+    #[test]
+    fn test_handle_pre_tool_use_passes_budget_note_once() {
+        let dir = tempfile::tempdir().unwrap();
+        let note = dir.path().join("session.note");
+        let handler = create_handler(PolicyLevel::Permissive).with_budget_note(&note);
+        let input = r#"{
+            "hook_event_name": "PreToolUse",
+            "session_id": "test",
+            "tool_name": "Read",
+            "tool_input": {"file_path": "/tmp/test.txt"}
+        }"#;
+        crate::supervisor::write_budget_note(&note, "80% of the budget used").unwrap();
+
+        let result = handler.handle_json(input).unwrap();
+        assert!(!result.should_deny);
+        assert!(result.response.contains("\"permissionDecision\":\"allow\""));
+        assert!(result.response.contains("80% of the budget used"));
+        assert!(!handler
+            .handle_json(input)
+            .unwrap()
+            .response
+            .contains("budget"));
+    }
+
+    #[test]
+    fn test_handle_pre_tool_use_denies_while_kill_switch_engaged() {
+        let dir = tempfile::tempdir().unwrap();
+        let kill_switch = KillSwitch::new(dir.path().join("KILL"));
+        let handler = create_handler(PolicyLevel::Permissive).with_kill_switch(kill_switch.clone());
+        let input = r#"{
+            "hook_event_name": "PreToolUse",
+            "session_id": "test",
+            "tool_name": "Read",
+            "tool_input": {"file_path": "/tmp/test.txt"}
+        }"#;
+        assert!(!handler.handle_json(input).unwrap().should_deny);
+
+        std::fs::write(kill_switch.path(), b"").unwrap();
+        let result = handler.handle_json(input).unwrap();
+        assert!(result.should_deny);
+        assert!(result.response.contains(KILL_SWITCH_REASON));
+    }
+
+    #[test]
+    fn test_handle_pre_tool_use_applies_permission_mode_level() {
+        let mut policy = PolicyEngine::new(PolicyLevel::Permissive);
+        policy.set_permission_mode_levels(std::collections::BTreeMap::from([(
+            "bypassPermissions".to_string(),
+            PolicyLevel::Strict,
+        )]));
+        let handler = HookHandler::new(policy);
+        let input = |mode: &str| {
+            format!(
+                r#"{{
+                    "hook_event_name": "PreToolUse",
+                    "session_id": "test",
+                    "permission_mode": "{mode}",
+                    "tool_name": "WebFetch",
+                    "tool_input": {{"url": "https://example.com"}}
+                }}"#
+            )
+        };
+
+        let result = handler.handle_json(&input("default")).unwrap();
+        assert!(result.response.contains("\"permissionDecision\":\"allow\""));
+
+        let result = handler.handle_json(&input("bypassPermissions")).unwrap();
+        assert!(result.response.contains("\"permissionDecision\":\"ask\""));
+        assert_eq!(handler.policy().level(), PolicyLevel::Permissive);
+    }
+
+    #[test]
+    fn test_handle_pre_tool_use_snapshots_write_target() {
+        let dir = tempfile::tempdir().unwrap();
+        std::fs::write(dir.path().join("notes.txt"), "before").unwrap();
+        let handler = create_handler(PolicyLevel::Permissive).with_snapshots(SnapshotConfig {
+            enabled: true,
+            dir: PathBuf::from(".claude-supervisor/snapshots"),
+            ..Default::default()
+        });
+        let input = HookInput {
+            hook_event_name: "PreToolUse".to_string(),
+            session_id: "session-1".to_string(),
+            cwd: Some(dir.path().to_string_lossy().into_owned()),
+            transcript_path: None,
+            permission_mode: None,
+            tool_name: Some("Write".to_string()),
+            tool_use_id: None,
+            tool_input: Some(serde_json::json!({"file_path": "notes.txt", "content": "after"})),
+            tool_result: None,
+            stop_hook_active: None,
+            extras: HashMap::new(),
+        };
+
+        let result = handler.handle(&input).unwrap();
+        assert!(!result.should_deny);
+        let store = SnapshotStore::new(dir.path().join(".claude-supervisor/snapshots"));
+        let entries = store.list("session-1", None).unwrap();
+        assert_eq!(entries.len(), 1);
+        assert_eq!(entries[0].path, dir.path().join("notes.txt"));
+    }
+
     #[test]
     fn test_handle_pre_tool_use_deny_dangerous() {
         let handler = create_handler(PolicyLevel::Permissive);
@@ -583,6 +1249,46 @@ mod tests {
         assert!(result.response.contains("\"permissionDecision\":\"deny\""));
     }
 
+    #[test]
+    fn test_handle_pre_tool_use_dry_run_allows_and_records() {
+        let mut policy = PolicyEngine::new(PolicyLevel::Strict);
+        policy.set_dry_run(true);
+        let log = policy.dry_run_log().clone();
+        let handler = HookHandler::new(policy);
+        for command in ["rm -rf /", "curl example.com"] {
+            let input = serde_json::json!({
+                "hook_event_name": "PreToolUse",
+                "session_id": "test",
+                "tool_name": "Bash",
+                "tool_input": {"command": command}
+            });
+            let result = handler.handle_json(&input.to_string()).unwrap();
+            assert!(!result.should_deny, "{command}");
+            assert!(result.response.contains("\"permissionDecision\":\"allow\""));
+        }
+
+        let records = log.records();
+        assert_eq!(records.len(), 2);
+        assert!(matches!(records[0].decision, PolicyDecision::Deny(_)));
+        assert!(records[0].rule.starts_with("blocklist: "));
+        assert_eq!(log.totals().denied + log.totals().escalated, 2);
+    }
+
+    #[test]
+    fn test_handle_pre_tool_use_denies_reshaped_command() {
+        let handler = create_handler(PolicyLevel::Permissive);
+        let input = r#"{
+            "hook_event_name": "PreToolUse",
+            "session_id": "test",
+            "tool_name": "Bash",
+            "tool_input": {"command": ["\u0433\u043c", "-rf", "/"]}
+        }"#;
+
+        let result = handler.handle_json(input).unwrap();
+        assert!(result.should_deny);
+        assert!(result.response.contains("Recursive forced delete"));
+    }
+
     #[test]
     fn test_handle_pre_tool_use_escalate_moderate() {
         let handler = create_handler(PolicyLevel::Moderate);
@@ -656,6 +1362,33 @@ mod tests {
         assert!(result.response.contains("Continue working on the task."));
     }
 
+    #[test]
+    fn test_handle_stop_wrap_up_warning() {
+        let stop =
+            r#"{"hook_event_name": "Stop", "session_id": "test", "stop_hook_active": false}"#;
+        let later = create_handler(PolicyLevel::Permissive)
+            .with_wrap_up_at(SystemTime::now() + std::time::Duration::from_hours(1));
+        assert!(later
+            .handle_json(stop)
+            .unwrap()
+            .response
+            .contains("\"decision\":\"allow\""));
+
+        let due = create_handler(PolicyLevel::Permissive)
+            .with_wrap_up_at(SystemTime::now() - std::time::Duration::from_secs(1));
+        let result = due.handle_json(stop).unwrap();
+        assert!(result.response.contains("\"decision\":\"block\""));
+        assert!(result.response.contains("Wrap up now"));
+
+        // The continuation it starts is allowed to stop
+        let active = stop.replace("false", "true");
+        assert!(due
+            .handle_json(&active)
+            .unwrap()
+            .response
+            .contains("\"decision\":\"allow\""));
+    }
+
     #[test]
     fn test_handle_stop_max_iterations_exceeded() {
         let stop_config = StopConfig {
@@ -706,6 +1439,44 @@ mod tests {
         assert!(result.response.contains("\"decision\":\"allow\""));
     }
 
+    #[test]
+    fn test_subagent_stops_count_apart_from_main() {
+        let stop_config = StopConfig {
+            max_iterations: 3,
+            max_subagent_iterations: 1,
+            force_continue: true,
+            ..StopConfig::default()
+        };
+        let handler =
+            HookHandler::with_config(PolicyEngine::new(PolicyLevel::Permissive), stop_config);
+        let stop = |event: &str, agent_id: Option<&str>| {
+            let mut input = serde_json::json!({
+                "hook_event_name": event,
+                "session_id": "shared",
+                "stop_hook_active": false
+            });
+            if let Some(agent_id) = agent_id {
+                input["agent_id"] = agent_id.into();
+            }
+            let result = handler.handle_json(&input.to_string()).unwrap();
+            result.response.contains("\"decision\":\"block\"")
+        };
+
+        // Interleaved main and subagent stops share the session id
+        assert!(stop("Stop", None));
+        assert!(stop("SubagentStop", Some("agent-a")));
+        assert!(!stop("SubagentStop", Some("agent-a")));
+        assert!(stop("Stop", None));
+        assert!(stop("Stop", Some("agent-b")));
+        assert!(stop("Stop", None));
+        assert!(!stop("Stop", None));
+
+        let iterations = handler.iterations();
+        assert_eq!(iterations.get("shared", None), 4);
+        assert_eq!(iterations.get("shared", Some("agent-a")), 2);
+        assert_eq!(iterations.get("shared", Some("agent-b")), 1);
+    }
+
     #[test]
     fn test_with_config_constructor() {
         let stop_config = StopConfig {
@@ -713,6 +1484,9 @@ mod tests {
             force_continue: true,
             completion_phrases: vec!["done".to_string()],
             incomplete_phrases: vec!["pending".to_string()],
+            min_completion_confidence: 0.5,
+            allow_stop_with_open_todos: false,
+            max_subagent_iterations: 10,
         };
         let handler = HookHandler::with_config(PolicyEngine::new(PolicyLevel::Strict), stop_config);
 
@@ -720,6 +1494,168 @@ mod tests {
         assert!(handler.stop_config().force_continue);
     }
 
+    fn stop_input(transcript_path: Option<&Path>) -> HookInput {
+        serde_json::from_value(serde_json::json!({
+            "hook_event_name": "Stop",
+            "session_id": "test",
+            "transcript_path": transcript_path,
+        }))
+        .unwrap()
+    }
+
+    /// Handler whose stops are blocked below `threshold` completion confidence.
+    fn threshold_handler(threshold: f32) -> HookHandler {
+        let stop_config = StopConfig {
+            min_completion_confidence: threshold,
+            ..StopConfig::default()
+        };
+        HookHandler::with_config(PolicyEngine::new(PolicyLevel::Permissive), stop_config)
+    }
+
+    #[test]
+    fn test_decide_stop_uses_completion_confidence() {
+        let input = stop_input(None);
+        // Two completion phrases against one incomplete phrase: 0.67
+        let mixed = "All done, the task is complete. The next step is the changelog.";
+
+        let response = threshold_handler(0.6).decide_stop(&input, &[], Some(mixed));
+        assert_eq!(response.decision(), StopDecision::Allow);
+
+        let response = threshold_handler(0.7).decide_stop(&input, &[], Some(mixed));
+        assert_eq!(response.decision(), StopDecision::Block);
+        let reason = response.hook_specific_output.reason.unwrap();
+        assert!(reason.contains("next step"), "{reason}");
+
+        // A tie is blocked at the default threshold
+        let tie = "All done. The next step is the changelog.";
+        let response = threshold_handler(StopConfig::default().min_completion_confidence)
+            .decide_stop(&input, &[], Some(tie));
+        assert_eq!(response.decision(), StopDecision::Block);
+
+        // Without evidence the stop is allowed as before
+        let response = threshold_handler(0.9).decide_stop(&input, &[], Some("Here you go."));
+        assert_eq!(response.decision(), StopDecision::Allow);
+    }
+
+    #[test]
+    fn test_handle_stop_assesses_transcript() {
+        let dir = tempfile::tempdir().unwrap();
+        let transcript = dir.path().join("transcript.jsonl");
+        let line = |text: &str| {
+            serde_json::json!({
+                "type": "assistant",
+                "uuid": "a-1",
+                "parentUuid": null,
+                "sessionId": "test",
+                "timestamp": "2026-01-29T10:00:01Z",
+                "message": {"role": "assistant", "content": [{"type": "text", "text": text}]},
+                "cwd": "/tmp",
+                "version": "2.1.25",
+            })
+            .to_string()
+        };
+        std::fs::write(
+            &transcript,
+            format!(
+                "{}\n{}\n",
+                line("The task is complete."),
+                line("Now I'll add the remaining tests.")
+            ),
+        )
+        .unwrap();
+
+        let handler = create_handler(PolicyLevel::Permissive);
+        let result = handler.handle(&stop_input(Some(&transcript))).unwrap();
+        assert!(result.response.contains("\"decision\":\"block\""));
+        assert!(result.response.contains("now i'll"), "{}", result.response);
+
+        let entries = read_transcript(&stop_input(Some(&transcript)));
+        assert_eq!(
+            handler
+                .assess_entries(&entries)
+                .unwrap()
+                .matched_incomplete_phrases,
+            vec!["now i'll"]
+        );
+        assert!(handler
+            .assess_entries(&read_transcript(&stop_input(None)))
+            .is_none());
+    }
+
+    /// Transcript whose assistant records `plans` with `TodoWrite`, then
+    /// says it is done.
+    fn todo_transcript(dir: &Path, plans: &[serde_json::Value]) -> PathBuf {
+        let entry = |content: serde_json::Value| {
+            serde_json::json!({
+                "type": "assistant",
+                "uuid": "a-1",
+                "parentUuid": null,
+                "sessionId": "test",
+                "timestamp": "2026-01-29T10:00:01Z",
+                "message": {"role": "assistant", "content": [content]},
+                "cwd": "/tmp",
+                "version": "2.1.25",
+            })
+            .to_string()
+        };
+        let mut lines: Vec<String> = plans
+            .iter()
+            .enumerate()
+            .map(|(i, plan)| {
+                entry(serde_json::json!({
+                    "type": "tool_use", "id": format!("t-{i}"), "name": "TodoWrite", "input": plan,
+                }))
+            })
+            .collect();
+        lines.push(entry(
+            serde_json::json!({"type": "text", "text": "The task is complete."}),
+        ));
+        let path = dir.join("transcript.jsonl");
+        std::fs::write(&path, lines.join("\n")).unwrap();
+        path
+    }
+
+    #[test]
+    fn test_stop_blocked_while_todos_open() {
+        let dir = tempfile::tempdir().unwrap();
+        let open = todo_transcript(
+            dir.path(),
+            &[
+                serde_json::json!({"todos": [
+                    {"content": "Write parser", "status": "completed", "activeForm": "Writing parser"},
+                    {"content": "Add tests", "status": "in_progress", "activeForm": "Adding tests"}
+                ]}),
+                serde_json::json!({"todos": "malformed"}),
+            ],
+        );
+        let handler = create_handler(PolicyLevel::Permissive);
+        let result = handler.handle(&stop_input(Some(&open))).unwrap();
+        assert!(result.response.contains("\"decision\":\"block\""));
+        assert!(result.response.contains("Add tests"), "{}", result.response);
+
+        let response = handler.decide_stop(&stop_input(Some(&open)), &[], None);
+        assert_eq!(response.decision(), StopDecision::Block);
+
+        // The override lets the completion check decide
+        let stop_config = StopConfig {
+            allow_stop_with_open_todos: true,
+            ..StopConfig::default()
+        };
+        let handler =
+            HookHandler::with_config(PolicyEngine::new(PolicyLevel::Permissive), stop_config);
+        let result = handler.handle(&stop_input(Some(&open))).unwrap();
+        assert!(result.response.contains("\"decision\":\"allow\""));
+
+        // A finished plan does not block
+        let done = todo_transcript(
+            dir.path(),
+            &[serde_json::json!([{"content": "Write parser", "status": "completed"}])],
+        );
+        let handler = create_handler(PolicyLevel::Permissive);
+        let result = handler.handle(&stop_input(Some(&done))).unwrap();
+        assert!(result.response.contains("\"decision\":\"allow\""));
+    }
+
     #[test]
     fn test_iteration_tracking() {
         let handler = create_handler(PolicyLevel::Permissive);
@@ -731,10 +1667,10 @@ mod tests {
         }"#;
 
         handler.handle_json(input).unwrap();
-        assert_eq!(handler.iterations().get("track_test"), 1);
+        assert_eq!(handler.iterations().get("track_test", None), 1);
 
         handler.handle_json(input).unwrap();
-        assert_eq!(handler.iterations().get("track_test"), 2);
+        assert_eq!(handler.iterations().get("track_test", None), 2);
     }
 
     #[test]
@@ -787,11 +1723,11 @@ mod tests {
         let handler = create_handler(PolicyLevel::Permissive);
         let result = handler
             .try_escalate_stop(
-                "session-1",
+                &stop_input(Some(Path::new("/path/to/transcript.jsonl"))),
                 "Task done",
-                Some("/path/to/transcript.jsonl"),
                 Some("Fix bug"),
                 1,
+                None,
             )
             .await;
         assert!(result.is_none());
@@ -803,11 +1739,11 @@ mod tests {
         let handler = create_handler(PolicyLevel::Permissive).with_ipc_client(client);
         let result = handler
             .try_escalate_stop(
-                "session-1",
+                &stop_input(Some(Path::new("/path/to/transcript.jsonl"))),
                 "Task done",
-                Some("/path/to/transcript.jsonl"),
                 Some("Fix bug"),
                 1,
+                None,
             )
             .await;
         assert!(result.is_none());
@@ -827,6 +1763,7 @@ mod tests {
             tool_input: None,
             tool_result: None,
             stop_hook_active: Some(false),
+            extras: HashMap::new(),
         };
         let result = handler.handle_stop_async(&input, None).await.unwrap();
         assert!(result.response.contains("\"decision\":\"allow\""));
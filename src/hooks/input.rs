@@ -1,5 +1,7 @@
 //! Hook input types for Claude Code events.
 
+use std::collections::HashMap;
+
 use serde::{Deserialize, Serialize};
 
 /// Input received from Claude Code hook events.
@@ -42,6 +44,10 @@ pub struct HookInput {
     /// Whether the stop hook is active (for Stop events).
     #[serde(default)]
     pub stop_hook_active: Option<bool>,
+
+    /// Extra fields not explicitly defined (forward compatibility).
+    #[serde(flatten, default)]
+    pub extras: HashMap<String, serde_json::Value>,
 }
 
 impl HookInput {
@@ -57,6 +63,17 @@ impl HookInput {
         self.hook_event_name == "Stop"
     }
 
+    /// Get the subagent the event comes from, when the hook names one.
+    ///
+    /// `None` is the main conversation.
+    #[must_use]
+    pub fn agent_id(&self) -> Option<&str> {
+        self.extras
+            .get("agent_id")
+            .and_then(serde_json::Value::as_str)
+            .filter(|agent_id| !agent_id.is_empty())
+    }
+
     /// Get the tool name if available.
     #[must_use]
     pub fn get_tool_name(&self) -> Option<&str> {
@@ -102,6 +119,21 @@ mod tests {
         let input: HookInput = serde_json::from_str(json).unwrap();
         assert!(input.is_stop());
         assert_eq!(input.stop_hook_active, Some(true));
+        assert_eq!(input.agent_id(), None);
+    }
+
+    #[test]
+    fn test_deserialize_subagent_stop_keeps_agent_id() {
+        let json = r#"{
+            "hook_event_name": "SubagentStop",
+            "session_id": "abc123",
+            "agent_id": "agent-7",
+            "agent_type": "Explore"
+        }"#;
+
+        let input: HookInput = serde_json::from_str(json).unwrap();
+        assert_eq!(input.agent_id(), Some("agent-7"));
+        assert_eq!(input.extras["agent_type"], "Explore");
     }
 
     #[test]
@@ -3,10 +3,17 @@
 use std::collections::HashMap;
 use std::sync::RwLock;
 
-/// Tracks iteration counts per session.
+/// Agent key of the main conversation, used when a hook names no agent.
+pub const MAIN_AGENT: &str = "main";
+
+/// Tracks iteration counts per session and agent.
+///
+/// Some Claude Code versions send a subagent's stop events with its parent's
+/// session id, so counts are kept per agent: a chatty subagent cannot use up
+/// the main conversation's iterations.
 #[derive(Debug, Default)]
 pub struct IterationTracker {
-    counts: RwLock<HashMap<String, u32>>,
+    counts: RwLock<HashMap<(String, String), u32>>,
 }
 
 impl IterationTracker {
@@ -18,41 +25,50 @@ impl IterationTracker {
         }
     }
 
-    /// Get the current iteration count for a session.
+    /// Key of a session's agent; `None` is the main conversation.
+    fn key(session_id: &str, agent_id: Option<&str>) -> (String, String) {
+        (
+            session_id.to_string(),
+            agent_id.unwrap_or(MAIN_AGENT).to_string(),
+        )
+    }
+
+    /// Get the current iteration count for an agent of a session.
     ///
     /// # Panics
     ///
     /// Panics if the internal `RwLock` is poisoned.
     #[must_use]
-    pub fn get(&self, session_id: &str) -> u32 {
+    pub fn get(&self, session_id: &str, agent_id: Option<&str>) -> u32 {
         self.counts
             .read()
             .expect("RwLock poisoned")
-            .get(session_id)
+            .get(&Self::key(session_id, agent_id))
             .copied()
             .unwrap_or(0)
     }
 
-    /// Increment the iteration count for a session and return the new count.
+    /// Increment the iteration count for an agent of a session and return
+    /// the new count.
     ///
     /// # Panics
     ///
     /// Panics if the internal `RwLock` is poisoned.
-    pub fn increment(&self, session_id: &str) -> u32 {
+    pub fn increment(&self, session_id: &str, agent_id: Option<&str>) -> u32 {
         let mut counts = self.counts.write().expect("RwLock poisoned");
-        let count = counts.entry(session_id.to_string()).or_insert(0);
+        let count = counts.entry(Self::key(session_id, agent_id)).or_insert(0);
         *count += 1;
         *count
     }
 
-    /// Reset the iteration count for a session.
+    /// Reset the iteration count for an agent of a session.
     ///
     /// # Panics
     ///
     /// Panics if the internal `RwLock` is poisoned.
-    pub fn reset(&self, session_id: &str) {
+    pub fn reset(&self, session_id: &str, agent_id: Option<&str>) {
         let mut counts = self.counts.write().expect("RwLock poisoned");
-        counts.remove(session_id);
+        counts.remove(&Self::key(session_id, agent_id));
     }
 }
 
@@ -63,35 +79,51 @@ mod tests {
     #[test]
     fn test_iteration_tracker_new() {
         let tracker = IterationTracker::new();
-        assert_eq!(tracker.get("session1"), 0);
+        assert_eq!(tracker.get("session1", None), 0);
     }
 
     #[test]
     fn test_iteration_tracker_increment() {
         let tracker = IterationTracker::new();
-        assert_eq!(tracker.increment("session1"), 1);
-        assert_eq!(tracker.increment("session1"), 2);
-        assert_eq!(tracker.increment("session1"), 3);
-        assert_eq!(tracker.get("session1"), 3);
+        assert_eq!(tracker.increment("session1", None), 1);
+        assert_eq!(tracker.increment("session1", None), 2);
+        assert_eq!(tracker.increment("session1", None), 3);
+        assert_eq!(tracker.get("session1", None), 3);
     }
 
     #[test]
     fn test_iteration_tracker_multiple_sessions() {
         let tracker = IterationTracker::new();
-        assert_eq!(tracker.increment("session1"), 1);
-        assert_eq!(tracker.increment("session2"), 1);
-        assert_eq!(tracker.increment("session1"), 2);
-        assert_eq!(tracker.get("session1"), 2);
-        assert_eq!(tracker.get("session2"), 1);
+        assert_eq!(tracker.increment("session1", None), 1);
+        assert_eq!(tracker.increment("session2", None), 1);
+        assert_eq!(tracker.increment("session1", None), 2);
+        assert_eq!(tracker.get("session1", None), 2);
+        assert_eq!(tracker.get("session2", None), 1);
+    }
+
+    #[test]
+    fn test_iteration_tracker_agents_count_separately() {
+        let tracker = IterationTracker::new();
+        assert_eq!(tracker.increment("session1", None), 1);
+        assert_eq!(tracker.increment("session1", Some("agent-a")), 1);
+        assert_eq!(tracker.increment("session1", Some("agent-a")), 2);
+        assert_eq!(tracker.increment("session1", Some("agent-b")), 1);
+        assert_eq!(tracker.increment("session1", None), 2);
+        assert_eq!(tracker.get("session1", Some(MAIN_AGENT)), 2);
+        assert_eq!(tracker.get("session1", Some("agent-a")), 2);
+
+        tracker.reset("session1", Some("agent-a"));
+        assert_eq!(tracker.get("session1", Some("agent-a")), 0);
+        assert_eq!(tracker.get("session1", None), 2);
     }
 
     #[test]
     fn test_iteration_tracker_reset() {
         let tracker = IterationTracker::new();
-        tracker.increment("session1");
-        tracker.increment("session1");
-        assert_eq!(tracker.get("session1"), 2);
-        tracker.reset("session1");
-        assert_eq!(tracker.get("session1"), 0);
+        tracker.increment("session1", None);
+        tracker.increment("session1", None);
+        assert_eq!(tracker.get("session1", None), 2);
+        tracker.reset("session1", None);
+        assert_eq!(tracker.get("session1", None), 0);
     }
 }
@@ -7,7 +7,7 @@
 //! # Components
 //!
 //! - [`HookHandler`]: Main handler that processes hook events
-//! - [`IterationTracker`]: Tracks iteration counts per session
+//! - [`IterationTracker`]: Tracks iteration counts per session and agent
 //! - [`CompletionDetector`]: Detects task completion from Claude's responses
 
 mod completion;
@@ -2,6 +2,8 @@
 
 use serde::{Deserialize, Serialize};
 
+use crate::supervisor::DenyReason;
+
 /// Decision for a `PreToolUse` hook.
 #[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
 #[serde(rename_all = "lowercase")]
@@ -21,6 +23,10 @@ pub struct PreToolUseOutput {
     pub permission_decision_reason: Option<String>,
     #[serde(skip_serializing_if = "Option::is_none")]
     pub updated_input: Option<serde_json::Value>,
+    /// Context added to Claude's conversation, read by recent Claude Code
+    /// versions only.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub additional_context: Option<String>,
 }
 
 /// Response from a `PreToolUse` hook wrapped in hookSpecificOutput.
@@ -39,6 +45,7 @@ impl PreToolUseResponse {
                 permission_decision: PermissionDecision::Allow,
                 permission_decision_reason: None,
                 updated_input: None,
+                additional_context: None,
             },
         }
     }
@@ -51,18 +58,20 @@ impl PreToolUseResponse {
                 permission_decision: PermissionDecision::Allow,
                 permission_decision_reason: Some(reason.into()),
                 updated_input: None,
+                additional_context: None,
             },
         }
     }
 
     #[must_use]
-    pub fn deny(reason: impl Into<String>) -> Self {
+    pub fn deny(reason: impl Into<DenyReason>) -> Self {
         Self {
             hook_specific_output: PreToolUseOutput {
                 hook_event_name: "PreToolUse".to_string(),
                 permission_decision: PermissionDecision::Deny,
-                permission_decision_reason: Some(reason.into()),
+                permission_decision_reason: Some(reason.into().message),
                 updated_input: None,
+                additional_context: None,
             },
         }
     }
@@ -75,6 +84,7 @@ impl PreToolUseResponse {
                 permission_decision: PermissionDecision::Ask,
                 permission_decision_reason: Some(reason.into()),
                 updated_input: None,
+                additional_context: None,
             },
         }
     }
@@ -88,10 +98,25 @@ impl PreToolUseResponse {
                 permission_decision: PermissionDecision::Allow,
                 permission_decision_reason: Some("Input modified by supervisor".to_string()),
                 updated_input: Some(updated_input),
+                additional_context: None,
             },
         }
     }
 
+    /// Pass `context` on to Claude through the `additionalContext` field.
+    #[must_use]
+    pub fn with_additional_context(mut self, context: impl Into<String>) -> Self {
+        self.hook_specific_output.additional_context = Some(context.into());
+        self
+    }
+
+    /// Replace the reason given for the decision.
+    #[must_use]
+    pub fn with_reason(mut self, reason: impl Into<String>) -> Self {
+        self.hook_specific_output.permission_decision_reason = Some(reason.into());
+        self
+    }
+
     /// Get the permission decision.
     #[must_use]
     pub fn decision(&self) -> PermissionDecision {
@@ -140,4 +165,18 @@ mod tests {
         assert!(json.contains("\"updatedInput\""));
         assert!(json.contains("\"command\":\"ls -la\""));
     }
+
+    #[test]
+    fn test_additional_context_matches_hook_output_schema() {
+        let response =
+            PreToolUseResponse::allow().with_additional_context("Run the tests after this edit.");
+        let expected: serde_json::Value = serde_json::from_str(include_str!(
+            "../../tests/fixtures/hooks/pre_tool_use_guide.json"
+        ))
+        .unwrap();
+        assert_eq!(serde_json::to_value(&response).unwrap(), expected);
+
+        let json = serde_json::to_string(&PreToolUseResponse::allow()).unwrap();
+        assert!(!json.contains("additionalContext"));
+    }
 }
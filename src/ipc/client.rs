@@ -6,7 +6,7 @@
 use std::path::{Path, PathBuf};
 use std::time::Duration;
 
-use crate::ipc::{EscalationRequest, EscalationResponse, IpcError, DEFAULT_SOCKET_PATH};
+use crate::ipc::{default_socket_path, EscalationRequest, EscalationResponse, IpcError};
 
 /// Default timeout for IPC operations (4 seconds).
 ///
@@ -29,7 +29,7 @@ impl IpcClient {
     #[must_use]
     pub fn new() -> Self {
         Self {
-            socket_path: PathBuf::from(DEFAULT_SOCKET_PATH),
+            socket_path: default_socket_path(),
             timeout: DEFAULT_TIMEOUT,
         }
     }
@@ -182,6 +182,233 @@ impl IpcClient {
             Err(_) => Err(IpcError::Timeout(timeout_ms)),
         }
     }
+
+    /// Reports a hook's final decision for a tool call to the supervisor.
+    ///
+    /// The supervisor does not reply; the report only has to be delivered.
+    ///
+    /// # Errors
+    ///
+    /// Returns an error if:
+    /// - The supervisor is not running ([`IpcError::SupervisorNotRunning`])
+    /// - The connection fails ([`IpcError::ConnectionFailed`])
+    /// - The operation times out ([`IpcError::Timeout`])
+    /// - Message serialization fails ([`IpcError::SerializationError`])
+    pub async fn report_decision(
+        &self,
+        report: &crate::ipc::HookDecisionReport,
+    ) -> Result<(), IpcError> {
+        use tokio::io::AsyncWriteExt;
+        use tokio::net::UnixStream;
+
+        if !self.is_supervisor_running() {
+            return Err(IpcError::SupervisorNotRunning);
+        }
+
+        #[allow(clippy::cast_possible_truncation)]
+        let timeout_ms = self.timeout.as_millis() as u64;
+
+        let result = tokio::time::timeout(self.timeout, async {
+            let mut stream = UnixStream::connect(&self.socket_path).await?;
+
+            // Wrap report with type tag for server routing
+            let wrapper = serde_json::json!({
+                "type": "decision",
+                "payload": report
+            });
+            let mut report_json = serde_json::to_string(&wrapper)?;
+            report_json.push('\n');
+            stream.write_all(report_json.as_bytes()).await?;
+            stream.flush().await?;
+            Ok(())
+        })
+        .await;
+
+        match result {
+            Ok(inner) => inner,
+            Err(_) => Err(IpcError::Timeout(timeout_ms)),
+        }
+    }
+
+    /// Asks the supervisor to evaluate a tool call against its policy engine.
+    ///
+    /// # Errors
+    ///
+    /// Returns an error if:
+    /// - The supervisor is not running ([`IpcError::SupervisorNotRunning`])
+    /// - The connection fails ([`IpcError::ConnectionFailed`])
+    /// - The operation times out ([`IpcError::Timeout`])
+    /// - Message serialization fails ([`IpcError::SerializationError`])
+    /// - The supervisor does not serve its policy and closes the connection
+    ///   without answering ([`IpcError::InvalidResponse`])
+    pub async fn evaluate(
+        &self,
+        request: &crate::ipc::EvaluateRequest,
+    ) -> Result<crate::ipc::PolicyVerdict, IpcError> {
+        use tokio::io::{AsyncBufReadExt, AsyncWriteExt, BufReader};
+        use tokio::net::UnixStream;
+
+        if !self.is_supervisor_running() {
+            return Err(IpcError::SupervisorNotRunning);
+        }
+
+        #[allow(clippy::cast_possible_truncation)]
+        let timeout_ms = self.timeout.as_millis() as u64;
+
+        let result = tokio::time::timeout(self.timeout, async {
+            let stream = UnixStream::connect(&self.socket_path).await?;
+            let (reader, mut writer) = stream.into_split();
+
+            // Wrap request with type tag for server routing
+            let wrapper = serde_json::json!({
+                "type": "evaluate",
+                "payload": request
+            });
+            let mut request_json = serde_json::to_string(&wrapper)?;
+            request_json.push('\n');
+            writer.write_all(request_json.as_bytes()).await?;
+            writer.flush().await?;
+
+            let mut reader = BufReader::new(reader);
+            let mut response_line = String::new();
+            let bytes_read = reader.read_line(&mut response_line).await?;
+
+            if bytes_read == 0 {
+                return Err(IpcError::InvalidResponse);
+            }
+
+            let response: crate::ipc::PolicyVerdict = serde_json::from_str(response_line.trim())?;
+            Ok(response)
+        })
+        .await;
+
+        match result {
+            Ok(inner) => inner,
+            Err(_) => Err(IpcError::Timeout(timeout_ms)),
+        }
+    }
+
+    /// Sends a control request to a detached session and returns its answer.
+    ///
+    /// # Errors
+    ///
+    /// Returns an error if:
+    /// - The session is not running ([`IpcError::SupervisorNotRunning`])
+    /// - The connection fails ([`IpcError::ConnectionFailed`])
+    /// - The operation times out ([`IpcError::Timeout`])
+    /// - Message serialization fails ([`IpcError::SerializationError`])
+    /// - The session closes the connection without answering
+    ///   ([`IpcError::InvalidResponse`])
+    pub async fn control(
+        &self,
+        request: crate::ipc::ControlRequest,
+    ) -> Result<crate::ipc::ControlResponse, IpcError> {
+        use tokio::io::{AsyncBufReadExt, AsyncWriteExt, BufReader};
+        use tokio::net::UnixStream;
+
+        if !self.is_supervisor_running() {
+            return Err(IpcError::SupervisorNotRunning);
+        }
+
+        #[allow(clippy::cast_possible_truncation)]
+        let timeout_ms = self.timeout.as_millis() as u64;
+
+        let result = tokio::time::timeout(self.timeout, async {
+            let stream = UnixStream::connect(&self.socket_path).await?;
+            let (reader, mut writer) = stream.into_split();
+
+            // Wrap request with type tag for server routing
+            let wrapper = serde_json::json!({
+                "type": "control",
+                "payload": request
+            });
+            let mut request_json = serde_json::to_string(&wrapper)?;
+            request_json.push('\n');
+            writer.write_all(request_json.as_bytes()).await?;
+            writer.flush().await?;
+
+            let mut reader = BufReader::new(reader);
+            let mut response_line = String::new();
+            let bytes_read = reader.read_line(&mut response_line).await?;
+
+            if bytes_read == 0 {
+                return Err(IpcError::InvalidResponse);
+            }
+
+            let response: crate::ipc::ControlResponse = serde_json::from_str(response_line.trim())?;
+            Ok(response)
+        })
+        .await;
+
+        match result {
+            Ok(inner) => inner,
+            Err(_) => Err(IpcError::Timeout(timeout_ms)),
+        }
+    }
+
+    /// Asks the supervisor to hand its session over to this process.
+    ///
+    /// The supervisor answers with a snapshot of its state, then stops
+    /// accepting connections and exits once it answered the ones in flight.
+    ///
+    /// # Errors
+    ///
+    /// Returns an error if:
+    /// - The supervisor is not running ([`IpcError::SupervisorNotRunning`])
+    /// - The connection fails ([`IpcError::ConnectionFailed`])
+    /// - The operation times out ([`IpcError::Timeout`])
+    /// - Message serialization fails ([`IpcError::SerializationError`])
+    /// - The supervisor cannot hand off and closes the connection without
+    ///   answering ([`IpcError::InvalidResponse`])
+    /// - The supervisor speaks another handoff protocol
+    ///   ([`IpcError::ProtocolMismatch`]); it still hands off, so the
+    ///   session is left unsupervised
+    pub async fn request_handoff(&self) -> Result<crate::ipc::HandoffSnapshot, IpcError> {
+        use tokio::io::{AsyncBufReadExt, AsyncWriteExt, BufReader};
+        use tokio::net::UnixStream;
+
+        if !self.is_supervisor_running() {
+            return Err(IpcError::SupervisorNotRunning);
+        }
+
+        #[allow(clippy::cast_possible_truncation)]
+        let timeout_ms = self.timeout.as_millis() as u64;
+
+        let result = tokio::time::timeout(self.timeout, async {
+            let stream = UnixStream::connect(&self.socket_path).await?;
+            let (reader, mut writer) = stream.into_split();
+
+            // The request has no payload; the tag alone routes it
+            let wrapper = serde_json::json!({ "type": "handoff" });
+            let mut request_json = serde_json::to_string(&wrapper)?;
+            request_json.push('\n');
+            writer.write_all(request_json.as_bytes()).await?;
+            writer.flush().await?;
+
+            let mut reader = BufReader::new(reader);
+            let mut response_line = String::new();
+            let bytes_read = reader.read_line(&mut response_line).await?;
+
+            if bytes_read == 0 {
+                return Err(IpcError::InvalidResponse);
+            }
+
+            let snapshot: crate::ipc::HandoffSnapshot = serde_json::from_str(response_line.trim())?;
+            if !snapshot.is_compatible() {
+                return Err(IpcError::ProtocolMismatch {
+                    expected: crate::ipc::HANDOFF_PROTOCOL,
+                    found: snapshot.protocol,
+                });
+            }
+            Ok(snapshot)
+        })
+        .await;
+
+        match result {
+            Ok(inner) => inner,
+            Err(_) => Err(IpcError::Timeout(timeout_ms)),
+        }
+    }
 }
 
 impl Default for IpcClient {
@@ -197,7 +424,7 @@ mod tests {
     #[test]
     fn client_new_uses_default_path() {
         let client = IpcClient::new();
-        assert_eq!(client.socket_path(), Path::new(DEFAULT_SOCKET_PATH));
+        assert_eq!(client.socket_path(), default_socket_path());
     }
 
     #[test]
@@ -227,7 +454,7 @@ mod tests {
     #[test]
     fn client_default_impl() {
         let client = IpcClient::default();
-        assert_eq!(client.socket_path(), Path::new(DEFAULT_SOCKET_PATH));
+        assert_eq!(client.socket_path(), default_socket_path());
     }
 
     #[tokio::test]
@@ -235,10 +462,12 @@ mod tests {
         let client = IpcClient::with_path("/nonexistent/socket.sock");
         let request = crate::ipc::StopEscalationRequest {
             session_id: "test".to_string(),
+            agent_id: None,
             final_message: "Done".to_string(),
             transcript_path: Some("/path/to/transcript.jsonl".to_string()),
             task: Some("Test task".to_string()),
             iteration: 1,
+            completion: None,
         };
         let result = client.escalate_stop(&request).await;
         assert!(matches!(result, Err(IpcError::SupervisorNotRunning)));
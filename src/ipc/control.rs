@@ -0,0 +1,123 @@
+//! Control of a running session from another process.
+//!
+//! `claude-supervisor attach` reaches a detached session over its socket:
+//! it reads the session's status and health and asks it to stop or
+//! continue. Requests go through the same channels as the dashboard's.
+
+use tokio::sync::{mpsc, watch};
+
+use crate::dashboard::{DashboardCommand, SupervisorStatus};
+use crate::ipc::{ControlRequest, ControlResponse};
+use crate::supervisor::HealthMonitor;
+
+/// The session end of [`ControlRequest`]s.
+#[derive(Debug, Clone)]
+pub struct SessionControl {
+    status: watch::Receiver<SupervisorStatus>,
+    commands: mpsc::Sender<DashboardCommand>,
+    health: Option<HealthMonitor>,
+}
+
+impl SessionControl {
+    /// Answer status requests from `status` and pass commands to `commands`.
+    #[must_use]
+    pub fn new(
+        status: watch::Receiver<SupervisorStatus>,
+        commands: mpsc::Sender<DashboardCommand>,
+    ) -> Self {
+        Self {
+            status,
+            commands,
+            health: None,
+        }
+    }
+
+    /// Include the health `monitor` reports in status answers.
+    #[must_use]
+    pub fn with_health(mut self, monitor: HealthMonitor) -> Self {
+        self.health = Some(monitor);
+        self
+    }
+
+    /// Answer `request`.
+    pub async fn handle(&self, request: ControlRequest) -> ControlResponse {
+        let command = match request {
+            ControlRequest::Status => {
+                let status = Box::new(self.status.borrow().clone());
+                let health = match self.health {
+                    Some(ref monitor) => Some(monitor.report().await),
+                    None => None,
+                };
+                return ControlResponse::Status { status, health };
+            }
+            ControlRequest::Stop => DashboardCommand::Stop,
+            ControlRequest::Continue => DashboardCommand::Continue,
+        };
+        match self.commands.send(command).await {
+            Ok(()) => ControlResponse::Accepted,
+            Err(_) => ControlResponse::Rejected {
+                reason: "The session has ended".to_string(),
+            },
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::dashboard::create_dashboard_channels;
+
+    #[tokio::test]
+    async fn test_control_uses_dashboard_channels() {
+        let (state, mut handles) = create_dashboard_channels();
+        let control = SessionControl::new(state.status_rx, state.command_tx);
+
+        handles.status_tx.send_modify(|status| {
+            status.state = "paused".to_string();
+        });
+        match control.handle(ControlRequest::Status).await {
+            ControlResponse::Status { status, health } => {
+                assert_eq!(status.state, "paused");
+                assert!(health.is_none());
+            }
+            other => panic!("Expected status, got {other:?}"),
+        }
+
+        assert!(matches!(
+            control.handle(ControlRequest::Continue).await,
+            ControlResponse::Accepted
+        ));
+        assert_eq!(
+            handles.command_rx.recv().await,
+            Some(DashboardCommand::Continue)
+        );
+
+        drop(handles);
+        assert!(matches!(
+            control.handle(ControlRequest::Stop).await,
+            ControlResponse::Rejected { .. }
+        ));
+    }
+
+    #[tokio::test(start_paused = true)]
+    async fn test_status_includes_health() {
+        let (state, _handles) = create_dashboard_channels();
+        let monitor = HealthMonitor::new();
+        let control =
+            SessionControl::new(state.status_rx, state.command_tx).with_health(monitor.clone());
+
+        monitor.beat();
+        tokio::time::advance(crate::supervisor::DEFAULT_MAX_LOOP_AGE * 2).await;
+        match control.handle(ControlRequest::Status).await {
+            ControlResponse::Status {
+                health: Some(health),
+                ..
+            } => {
+                assert!(!health.healthy);
+                let failing: Vec<_> = health.failing().map(|check| check.name.as_str()).collect();
+                assert_eq!(failing, [crate::supervisor::EVENT_LOOP_CHECK]);
+            }
+            other => panic!("Expected status with health, got {other:?}"),
+        }
+    }
+}
@@ -0,0 +1,171 @@
+//! Decisions reported by hooks, for the runner to reconcile with its own.
+//!
+//! With hooks installed, a tool call is decided by the hook before it runs
+//! and seen again by the runner in the event stream. The hook reports its
+//! decision over IPC keyed by the tool use ID; the runner looks it up in a
+//! shared [`HookDecisionLog`] instead of deciding blind.
+
+use std::collections::VecDeque;
+use std::sync::{Arc, Mutex, PoisonError};
+use std::time::Duration;
+
+use tokio::sync::Notify;
+
+use crate::ipc::{EscalationResponse, HookDecisionReport};
+
+/// Most reported decisions kept; the oldest are dropped first.
+pub const MAX_HOOK_DECISIONS: usize = 256;
+
+/// Default time the runner waits for a hook to report a decision.
+pub const DEFAULT_HOOK_DECISION_WAIT: Duration = Duration::from_millis(500);
+
+/// Hook decisions keyed by tool use ID.
+///
+/// Cheap to clone; clones share the same decisions, so the IPC server can
+/// record into the log the runner reads from.
+#[derive(Debug, Clone, Default)]
+pub struct HookDecisionLog {
+    inner: Arc<Inner>,
+}
+
+#[derive(Debug, Default)]
+struct Inner {
+    decisions: Mutex<VecDeque<(String, EscalationResponse)>>,
+    recorded: Notify,
+}
+
+impl HookDecisionLog {
+    /// Create an empty log.
+    #[must_use]
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Record a reported decision, replacing an earlier one for the same call.
+    pub fn record(&self, report: HookDecisionReport) {
+        {
+            let mut decisions = self.lock();
+            decisions.retain(|(id, _)| *id != report.tool_use_id);
+            if decisions.len() >= MAX_HOOK_DECISIONS {
+                decisions.pop_front();
+            }
+            decisions.push_back((report.tool_use_id, report.decision));
+        }
+        self.inner.recorded.notify_waiters();
+    }
+
+    /// Whether a decision was reported for `tool_use_id`.
+    #[must_use]
+    pub fn contains(&self, tool_use_id: &str) -> bool {
+        self.lock().iter().any(|(id, _)| id == tool_use_id)
+    }
+
+    /// Remove and return the decision reported for `tool_use_id`.
+    #[must_use]
+    pub fn take(&self, tool_use_id: &str) -> Option<EscalationResponse> {
+        let mut decisions = self.lock();
+        let index = decisions.iter().position(|(id, _)| id == tool_use_id)?;
+        decisions.remove(index).map(|(_, decision)| decision)
+    }
+
+    /// Wait up to `timeout` for a decision on `tool_use_id` to be reported.
+    ///
+    /// Returns whether one was reported in time. Does not remove it.
+    pub async fn wait_for(&self, tool_use_id: &str, timeout: Duration) -> bool {
+        let deadline = tokio::time::Instant::now() + timeout;
+        loop {
+            let recorded = self.inner.recorded.notified();
+            tokio::pin!(recorded);
+            // Register before checking so a report in between is not missed
+            recorded.as_mut().enable();
+            if self.contains(tool_use_id) {
+                return true;
+            }
+            if tokio::time::timeout_at(deadline, recorded).await.is_err() {
+                return self.contains(tool_use_id);
+            }
+        }
+    }
+
+    /// Number of decisions held.
+    #[must_use]
+    pub fn len(&self) -> usize {
+        self.lock().len()
+    }
+
+    /// Whether no decisions are held.
+    #[must_use]
+    pub fn is_empty(&self) -> bool {
+        self.len() == 0
+    }
+
+    fn lock(&self) -> std::sync::MutexGuard<'_, VecDeque<(String, EscalationResponse)>> {
+        self.inner
+            .decisions
+            .lock()
+            .unwrap_or_else(PoisonError::into_inner)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn report(id: &str, decision: EscalationResponse) -> HookDecisionReport {
+        HookDecisionReport {
+            session_id: "s1".to_string(),
+            tool_use_id: id.to_string(),
+            tool_name: "Bash".to_string(),
+            decision,
+        }
+    }
+
+    #[test]
+    fn test_record_and_take() {
+        let log = HookDecisionLog::new();
+        log.record(report("toolu_1", EscalationResponse::Allow));
+        log.record(report(
+            "toolu_1",
+            EscalationResponse::Deny {
+                reason: "no".to_string(),
+            },
+        ));
+
+        assert_eq!(log.len(), 1);
+        assert!(matches!(
+            log.take("toolu_1"),
+            Some(EscalationResponse::Deny { .. })
+        ));
+        assert!(log.take("toolu_1").is_none());
+    }
+
+    #[test]
+    fn test_log_is_bounded() {
+        let log = HookDecisionLog::new();
+        for i in 0..=MAX_HOOK_DECISIONS {
+            log.record(report(&format!("toolu_{i}"), EscalationResponse::Allow));
+        }
+        assert_eq!(log.len(), MAX_HOOK_DECISIONS);
+        assert!(!log.contains("toolu_0"));
+        assert!(log.contains(&format!("toolu_{MAX_HOOK_DECISIONS}")));
+    }
+
+    #[tokio::test]
+    async fn test_wait_for_late_report() {
+        let log = HookDecisionLog::new();
+        let writer = log.clone();
+        tokio::spawn(async move {
+            tokio::time::sleep(Duration::from_millis(20)).await;
+            writer.record(report("toolu_1", EscalationResponse::Allow));
+        });
+
+        assert!(log.wait_for("toolu_1", Duration::from_secs(2)).await);
+    }
+
+    #[tokio::test]
+    async fn test_wait_for_times_out() {
+        let log = HookDecisionLog::new();
+        log.record(report("toolu_other", EscalationResponse::Allow));
+        assert!(!log.wait_for("toolu_1", Duration::from_millis(20)).await);
+    }
+}
@@ -0,0 +1,371 @@
+//! Handing a running session to a newer supervisor binary.
+//!
+//! Installing a new version mid-session leaves hooks running the new binary
+//! against an old supervisor. `claude-supervisor upgrade-handoff` asks the
+//! old supervisor for a [`HandoffSnapshot`] over its socket; the old
+//! supervisor answers, stops accepting connections and finishes the ones in
+//! flight. The new binary then serves the socket itself and follows the
+//! Claude process by its PID and transcript. The old supervisor holds
+//! Claude's output pipes, so it keeps reading them, without deciding
+//! anything, until Claude exits.
+//!
+//! ```text
+//! upgrade-handoff               old supervisor
+//!     |                              |
+//!     |-- handoff ------------------>|
+//!     |<-- HandoffSnapshot ----------| (stops accepting, finishes requests)
+//!     |                              |
+//!  serves the socket,          reads Claude's output
+//!  follows the Claude process  until Claude exits
+//! ```
+
+use std::collections::BTreeMap;
+use std::path::PathBuf;
+use std::sync::{Arc, Mutex};
+
+use chrono::{DateTime, Utc};
+use serde::{Deserialize, Serialize};
+use tokio_util::sync::CancellationToken;
+
+use crate::ipc::EscalationRequest;
+use crate::supervisor::SessionOverride;
+
+/// Version of the handoff snapshot format.
+///
+/// A binary only adopts sessions from supervisors speaking the same version.
+pub const HANDOFF_PROTOCOL: u32 = 1;
+
+/// A Claude session the supervisor was following.
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct HandoffSession {
+    /// Session ID from Claude Code.
+    pub session_id: String,
+    /// The session's JSONL transcript, if it was found.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub transcript_path: Option<PathBuf>,
+}
+
+/// Cost budget counters carried over to the new supervisor.
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct BudgetCounters {
+    /// The budget, in USD.
+    pub limit_usd: f64,
+    /// Spent so far, in USD.
+    pub spent_usd: f64,
+    /// Shares of the budget, in percent, that raise an alert.
+    #[serde(default)]
+    pub alert_percent: Vec<u32>,
+}
+
+/// Session time limit carried over to the new supervisor.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+pub struct TimeBoxCounters {
+    /// The limit, in seconds.
+    pub limit_secs: u64,
+    /// When the limit is reached at the current pace.
+    pub expires_at: DateTime<Utc>,
+}
+
+/// State of a running supervisor, handed to the binary taking it over.
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct HandoffSnapshot {
+    /// Snapshot format version; see [`HANDOFF_PROTOCOL`].
+    pub protocol: u32,
+    /// Version of the supervisor binary that took the snapshot.
+    pub version: String,
+    /// Process ID of the supervisor that took the snapshot.
+    pub supervisor_pid: u32,
+    /// Process ID of the Claude process it supervised, while it runs.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub child_pid: Option<u32>,
+    /// Claude sessions it followed, oldest first.
+    #[serde(default)]
+    pub sessions: Vec<HandoffSession>,
+    /// Escalations still waiting for a decision; the old supervisor decides
+    /// them before it exits.
+    #[serde(default)]
+    pub pending_approvals: Vec<EscalationRequest>,
+    /// Cost budget counters, if the session has a budget.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub budget: Option<BudgetCounters>,
+    /// Session cost so far, in micro-dollars.
+    #[serde(default)]
+    pub cost_micros: u64,
+    /// Time limit, if the session has one.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub time_box: Option<TimeBoxCounters>,
+    /// Session overrides given to the session's `run`.
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
+    pub overrides: Vec<SessionOverride>,
+    /// When the snapshot was taken.
+    pub taken_at: DateTime<Utc>,
+}
+
+impl HandoffSnapshot {
+    /// Whether this binary can adopt the session the snapshot describes.
+    #[must_use]
+    pub fn is_compatible(&self) -> bool {
+        self.protocol == HANDOFF_PROTOCOL
+    }
+
+    /// The most recent session, whose process is the one adopted.
+    #[must_use]
+    pub fn current_session(&self) -> Option<&HandoffSession> {
+        self.sessions.last()
+    }
+}
+
+#[derive(Debug, Default)]
+struct HandoffInner {
+    child_pid: Option<u32>,
+    sessions: Vec<HandoffSession>,
+    pending: BTreeMap<u64, EscalationRequest>,
+    next_approval: u64,
+    budget: Option<BudgetCounters>,
+    cost_micros: u64,
+    time_box: Option<TimeBoxCounters>,
+    overrides: Vec<SessionOverride>,
+}
+
+/// State a supervisor keeps ready for a handoff, shared by the runner that
+/// updates it and the IPC server that hands it off.
+#[derive(Debug, Clone, Default)]
+pub struct HandoffState {
+    inner: Arc<Mutex<HandoffInner>>,
+    handed_off: CancellationToken,
+}
+
+impl HandoffState {
+    /// Create an empty state.
+    #[must_use]
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Continue the state of an adopted supervisor, so the session can be
+    /// handed off again. Its pending approvals stay with the old supervisor.
+    #[must_use]
+    pub fn from_snapshot(snapshot: &HandoffSnapshot) -> Self {
+        let state = Self::new();
+        {
+            let mut inner = state.lock();
+            inner.child_pid = snapshot.child_pid;
+            inner.sessions.clone_from(&snapshot.sessions);
+            inner.budget.clone_from(&snapshot.budget);
+            inner.cost_micros = snapshot.cost_micros;
+            inner.time_box = snapshot.time_box;
+            inner.overrides.clone_from(&snapshot.overrides);
+        }
+        state
+    }
+
+    fn lock(&self) -> std::sync::MutexGuard<'_, HandoffInner> {
+        self.inner.lock().expect("Mutex poisoned")
+    }
+
+    /// Set the Claude process being supervised, or `None` once it exited.
+    ///
+    /// # Panics
+    ///
+    /// Panics if the internal `Mutex` is poisoned.
+    pub fn set_child(&self, pid: Option<u32>) {
+        self.lock().child_pid = pid;
+    }
+
+    /// Record a Claude session, or update the transcript of a known one.
+    ///
+    /// # Panics
+    ///
+    /// Panics if the internal `Mutex` is poisoned.
+    pub fn add_session(&self, session_id: &str, transcript_path: Option<PathBuf>) {
+        let mut inner = self.lock();
+        match inner
+            .sessions
+            .iter_mut()
+            .find(|session| session.session_id == session_id)
+        {
+            Some(session) => {
+                if transcript_path.is_some() {
+                    session.transcript_path = transcript_path;
+                }
+            }
+            None => inner.sessions.push(HandoffSession {
+                session_id: session_id.to_string(),
+                transcript_path,
+            }),
+        }
+    }
+
+    /// Record the session cost so far and the budget it counts against.
+    ///
+    /// # Panics
+    ///
+    /// Panics if the internal `Mutex` is poisoned.
+    pub fn set_cost(&self, cost_micros: u64, budget: Option<BudgetCounters>) {
+        let mut inner = self.lock();
+        inner.cost_micros = cost_micros;
+        inner.budget = budget;
+    }
+
+    /// Record the session's time limit.
+    ///
+    /// # Panics
+    ///
+    /// Panics if the internal `Mutex` is poisoned.
+    pub fn set_time_box(&self, time_box: Option<TimeBoxCounters>) {
+        self.lock().time_box = time_box;
+    }
+
+    /// Record the session overrides the session runs with.
+    ///
+    /// # Panics
+    ///
+    /// Panics if the internal `Mutex` is poisoned.
+    pub fn set_overrides(&self, overrides: Vec<SessionOverride>) {
+        self.lock().overrides = overrides;
+    }
+
+    /// Record an escalation waiting for a decision; returns the key to
+    /// [`end_approval`](Self::end_approval) it with.
+    ///
+    /// # Panics
+    ///
+    /// Panics if the internal `Mutex` is poisoned.
+    #[must_use]
+    pub fn begin_approval(&self, request: EscalationRequest) -> u64 {
+        let mut inner = self.lock();
+        let key = inner.next_approval;
+        inner.next_approval += 1;
+        inner.pending.insert(key, request);
+        key
+    }
+
+    /// Forget an escalation once it is decided.
+    ///
+    /// # Panics
+    ///
+    /// Panics if the internal `Mutex` is poisoned.
+    pub fn end_approval(&self, key: u64) {
+        self.lock().pending.remove(&key);
+    }
+
+    /// The state as it is now.
+    ///
+    /// # Panics
+    ///
+    /// Panics if the internal `Mutex` is poisoned.
+    #[must_use]
+    pub fn snapshot(&self) -> HandoffSnapshot {
+        let inner = self.lock();
+        HandoffSnapshot {
+            protocol: HANDOFF_PROTOCOL,
+            version: env!("CARGO_PKG_VERSION").to_string(),
+            supervisor_pid: std::process::id(),
+            child_pid: inner.child_pid,
+            sessions: inner.sessions.clone(),
+            pending_approvals: inner.pending.values().cloned().collect(),
+            budget: inner.budget.clone(),
+            cost_micros: inner.cost_micros,
+            time_box: inner.time_box,
+            overrides: inner.overrides.clone(),
+            taken_at: Utc::now(),
+        }
+    }
+
+    /// Take the snapshot for a new supervisor and mark the session as
+    /// handed off.
+    #[must_use]
+    pub fn hand_off(&self) -> HandoffSnapshot {
+        let snapshot = self.snapshot();
+        tracing::info!(
+            child_pid = ?snapshot.child_pid,
+            pending_approvals = snapshot.pending_approvals.len(),
+            "Handing the session off to a new supervisor"
+        );
+        self.handed_off.cancel();
+        snapshot
+    }
+
+    /// Whether the session was handed off.
+    #[must_use]
+    pub fn is_handed_off(&self) -> bool {
+        self.handed_off.is_cancelled()
+    }
+
+    /// Wait until the session is handed off.
+    pub async fn handed_off(&self) {
+        self.handed_off.cancelled().await;
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::supervisor::OverrideEffect;
+    use serde_json::json;
+
+    fn escalation(tool_name: &str) -> EscalationRequest {
+        EscalationRequest {
+            session_id: "session-1".to_string(),
+            tool_name: tool_name.to_string(),
+            tool_input: json!({}),
+            reason: "Needs approval".to_string(),
+            appeal: None,
+        }
+    }
+
+    #[test]
+    fn test_handoff_state_snapshot() {
+        let state = HandoffState::new();
+        state.set_child(Some(4242));
+        state.add_session("session-1", None);
+        state.add_session("session-1", Some(PathBuf::from("/tmp/session-1.jsonl")));
+        state.set_cost(
+            250_000,
+            Some(BudgetCounters {
+                limit_usd: 5.0,
+                spent_usd: 0.25,
+                alert_percent: vec![50, 80],
+            }),
+        );
+        let time_box = TimeBoxCounters {
+            limit_secs: 600,
+            expires_at: Utc::now(),
+        };
+        state.set_time_box(Some(time_box));
+        let allow = SessionOverride::parse(OverrideEffect::Allow, "Bash:docker *").unwrap();
+        state.set_overrides(vec![allow.clone()]);
+        let bash = state.begin_approval(escalation("Bash"));
+        let _fetch = state.begin_approval(escalation("WebFetch"));
+        state.end_approval(bash);
+
+        let clone = state.clone();
+        assert!(!clone.is_handed_off());
+        let snapshot = clone.hand_off();
+        assert!(state.is_handed_off());
+        assert!(snapshot.is_compatible());
+        assert_eq!(snapshot.child_pid, Some(4242));
+        assert_eq!(snapshot.supervisor_pid, std::process::id());
+        assert_eq!(
+            snapshot.current_session().unwrap().transcript_path,
+            Some(PathBuf::from("/tmp/session-1.jsonl"))
+        );
+        assert_eq!(snapshot.sessions.len(), 1);
+        assert_eq!(snapshot.pending_approvals, [escalation("WebFetch")]);
+        assert_eq!(snapshot.cost_micros, 250_000);
+
+        let json = serde_json::to_string(&snapshot).unwrap();
+        assert_eq!(
+            serde_json::from_str::<HandoffSnapshot>(&json).unwrap(),
+            snapshot
+        );
+
+        // The adopter continues everything but the old pending approvals
+        let adopted = HandoffState::from_snapshot(&snapshot).snapshot();
+        assert_eq!(adopted.sessions, snapshot.sessions);
+        assert_eq!(adopted.budget, snapshot.budget);
+        assert_eq!(adopted.time_box, Some(time_box));
+        assert_eq!(adopted.overrides, [allow]);
+        assert!(adopted.pending_approvals.is_empty());
+    }
+}
@@ -3,6 +3,13 @@
 //! This module provides inter-process communication for the supervisor to receive
 //! escalation requests from hook binaries and respond with decisions.
 //!
+//! A running supervisor can also serve its policy engine: hooks send an
+//! [`EvaluateRequest`] and get a [`PolicyVerdict`] from the one engine the
+//! supervisor keeps compiled, instead of building their own on every call.
+//!
+//! A newer binary can take a running session over from the supervisor; see
+//! [`handoff`].
+//!
 //! # Architecture
 //!
 //! ```text
@@ -35,6 +42,7 @@
 //!         tool_name: "Bash".to_string(),
 //!         tool_input: json!({"command": "rm -rf /"}),
 //!         reason: "Destructive command detected".to_string(),
+//!         appeal: None,
 //!     };
 //!
 //!     let response = client.escalate(&request).await?;
@@ -45,14 +53,48 @@
 //! ```
 
 pub mod client;
+pub mod control;
+pub mod decisions;
+pub mod handoff;
 pub mod server;
 pub mod types;
 
 pub use client::IpcClient;
+pub use control::SessionControl;
+pub use decisions::{HookDecisionLog, DEFAULT_HOOK_DECISION_WAIT, MAX_HOOK_DECISIONS};
+pub use handoff::{
+    BudgetCounters, HandoffSession, HandoffSnapshot, HandoffState, TimeBoxCounters,
+    HANDOFF_PROTOCOL,
+};
 pub use server::{IpcServer, ServerHandle};
 pub use types::{
-    EscalationRequest, EscalationResponse, IpcError, StopEscalationRequest, StopEscalationResponse,
+    Appeal, ControlRequest, ControlResponse, EscalationRequest, EscalationResponse,
+    EvaluateRequest, HookDecisionReport, IpcError, PolicyVerdict, StopEscalationRequest,
+    StopEscalationResponse,
 };
 
-/// Default socket path for supervisor IPC.
-pub const DEFAULT_SOCKET_PATH: &str = "/tmp/claude-supervisor.sock";
+/// File name of the supervisor IPC socket.
+pub const SOCKET_FILE: &str = "supervisor.sock";
+
+/// Environment variable naming the socket a session's hooks reach its
+/// supervisor on.
+pub const SOCKET_ENV: &str = "CLAUDE_SUPERVISOR_SOCKET";
+
+/// Default socket path for supervisor IPC: the one named by [`SOCKET_ENV`],
+/// or [`SOCKET_FILE`] in the [sockets directory](crate::config::sockets_dir).
+#[must_use]
+pub fn default_socket_path() -> std::path::PathBuf {
+    std::env::var_os(SOCKET_ENV)
+        .filter(|path| !path.is_empty())
+        .map_or_else(
+            || crate::config::sockets_dir().join(SOCKET_FILE),
+            std::path::PathBuf::from,
+        )
+}
+
+/// Socket the supervisor of session `name` serves the session's hooks on,
+/// in the [sockets directory](crate::config::sockets_dir).
+#[must_use]
+pub fn session_socket_path(name: &str) -> std::path::PathBuf {
+    crate::config::sockets_dir().join(format!("{name}.hooks.sock"))
+}
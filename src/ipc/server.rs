@@ -5,13 +5,23 @@
 
 use std::future::Future;
 use std::path::{Path, PathBuf};
-use std::sync::Arc;
+use std::sync::{Arc, Mutex, PoisonError};
 
 use tokio::io::{AsyncBufReadExt, AsyncWriteExt, BufReader};
 use tokio::net::UnixListener;
 use tokio::sync::watch;
-
-use crate::ipc::{EscalationRequest, EscalationResponse, IpcError, DEFAULT_SOCKET_PATH};
+use tokio_util::sync::CancellationToken;
+use tokio_util::task::TaskTracker;
+use tracing::field::Empty;
+use tracing::Instrument;
+
+use crate::ipc::{
+    default_socket_path, ControlRequest, ControlResponse, EscalationRequest, EscalationResponse,
+    EvaluateRequest, HandoffState, HookDecisionLog, HookDecisionReport, IpcError, PolicyVerdict,
+    SessionControl,
+};
+use crate::supervisor::{DryRunLog, PolicyDecision, PolicyEngine, RateLimiter};
+use crate::telemetry::SPAN_IPC_REQUEST;
 
 /// IPC server for receiving escalation requests from hook binaries.
 ///
@@ -20,6 +30,10 @@ use crate::ipc::{EscalationRequest, EscalationResponse, IpcError, DEFAULT_SOCKET
 #[derive(Debug)]
 pub struct IpcServer {
     socket_path: PathBuf,
+    decisions: Option<HookDecisionLog>,
+    control: Option<SessionControl>,
+    policy: Option<ServedPolicy>,
+    handoff: Option<HandoffState>,
 }
 
 impl IpcServer {
@@ -28,13 +42,60 @@ impl IpcServer {
     pub fn new<P: AsRef<Path>>(socket_path: P) -> Self {
         Self {
             socket_path: socket_path.as_ref().to_path_buf(),
+            decisions: None,
+            control: None,
+            policy: None,
+            handoff: None,
         }
     }
 
     /// Creates a new IPC server with the default socket path.
     #[must_use]
     pub fn with_default_path() -> Self {
-        Self::new(DEFAULT_SOCKET_PATH)
+        Self::new(default_socket_path())
+    }
+
+    /// Records decisions reported by hooks into `log`.
+    ///
+    /// Without a log, reports are read and dropped.
+    #[must_use]
+    pub fn with_decision_log(mut self, log: HookDecisionLog) -> Self {
+        self.decisions = Some(log);
+        self
+    }
+
+    /// Answers [`ControlRequest`]s with `control`.
+    ///
+    /// Without it, control requests are rejected.
+    #[must_use]
+    pub fn with_control(mut self, control: SessionControl) -> Self {
+        self.control = Some(control);
+        self
+    }
+
+    /// Answers [`EvaluateRequest`]s with `policy`, shared by all connections
+    /// and with whoever else holds it, such as the runner.
+    ///
+    /// Changes to the engine apply to the next evaluation. Hook calls count
+    /// towards rate limits and dry run records kept apart from the engine's,
+    /// since the runner evaluates the same calls again. Without a policy,
+    /// evaluation requests are closed unanswered and hooks fall back to their
+    /// own engine.
+    #[must_use]
+    pub fn with_policy(mut self, policy: Arc<Mutex<PolicyEngine>>) -> Self {
+        self.policy = Some(ServedPolicy::new(policy));
+        self
+    }
+
+    /// Answers handoff requests with a snapshot of `handoff`.
+    ///
+    /// Once it has answered one, the server stops accepting connections and
+    /// leaves the socket file to the new supervisor. Without it, handoff
+    /// requests are closed unanswered.
+    #[must_use]
+    pub fn with_handoff(mut self, handoff: HandoffState) -> Self {
+        self.handoff = Some(handoff);
+        self
     }
 
     /// Returns the socket path.
@@ -56,6 +117,10 @@ impl IpcServer {
         F: Fn(EscalationRequest) -> Fut + Send + Sync + 'static,
         Fut: Future<Output = EscalationResponse> + Send,
     {
+        if let Some(dir) = self.socket_path.parent() {
+            std::fs::create_dir_all(dir)?;
+        }
+
         // Remove existing socket file if it exists
         if self.socket_path.exists() {
             std::fs::remove_file(&self.socket_path)?;
@@ -71,8 +136,16 @@ impl IpcServer {
         let (shutdown_tx, mut shutdown_rx) = watch::channel(false);
 
         let handler = Arc::new(handler);
+        let decisions = self.decisions.clone();
+        let control = self.control.clone();
+        let policy = self.policy.clone();
+        let handoff = self.handoff.clone();
+        let drained = CancellationToken::new();
+        let connections = TaskTracker::new();
 
         // Spawn the accept loop
+        let loop_handoff = handoff.clone();
+        let loop_drained = drained.clone();
         tokio::spawn(async move {
             loop {
                 tokio::select! {
@@ -85,15 +158,36 @@ impl IpcServer {
                         }
                     }
 
+                    () = async {
+                        match loop_handoff {
+                            Some(ref handoff) => handoff.handed_off().await,
+                            None => std::future::pending().await,
+                        }
+                    } => {
+                        tracing::info!("IPC server handed off; no longer accepting connections");
+                        break;
+                    }
+
                     accept_result = listener.accept() => {
                         match accept_result {
                             Ok((stream, _addr)) => {
                                 let handler = Arc::clone(&handler);
-                                tokio::spawn(async move {
-                                    if let Err(e) = handle_connection(stream, handler).await {
+                                let decisions = decisions.clone();
+                                let control = control.clone();
+                                let policy = policy.clone();
+                                let handoff = handoff.clone();
+                                let span = tracing::debug_span!(
+                                    SPAN_IPC_REQUEST,
+                                    request = Empty,
+                                    session_id = Empty,
+                                    tool_use_id = Empty,
+                                );
+                                connections.spawn(async move {
+                                    let services = Services { decisions, control, policy, handoff };
+                                    if let Err(e) = handle_connection(stream, handler, services).await {
                                         tracing::warn!(error = %e, "Connection handler error");
                                     }
-                                });
+                                }.instrument(span));
                             }
                             Err(e) => {
                                 tracing::warn!(error = %e, "Failed to accept connection");
@@ -102,22 +196,84 @@ impl IpcServer {
                     }
                 }
             }
+
+            // Connections accepted before the loop ended are still answered
+            drop(listener);
+            connections.close();
+            connections.wait().await;
+            loop_drained.cancel();
         });
 
         Ok(ServerHandle {
             socket_path: self.socket_path.clone(),
             shutdown_tx,
+            handoff: self.handoff.clone(),
+            drained,
         })
     }
 }
 
+/// What a connection can be answered with besides escalations.
+struct Services {
+    decisions: Option<HookDecisionLog>,
+    control: Option<SessionControl>,
+    policy: Option<ServedPolicy>,
+    handoff: Option<HandoffState>,
+}
+
+/// A policy engine served to hooks, with the hooks' own call counters.
+#[derive(Debug, Clone)]
+struct ServedPolicy {
+    engine: Arc<Mutex<PolicyEngine>>,
+    rate_limiter: Option<RateLimiter>,
+    dry_run_log: DryRunLog,
+}
+
+impl ServedPolicy {
+    fn new(engine: Arc<Mutex<PolicyEngine>>) -> Self {
+        let rate_limiter = engine
+            .lock()
+            .unwrap_or_else(PoisonError::into_inner)
+            .rate_limiter()
+            .map(RateLimiter::with_own_calls);
+        Self {
+            engine,
+            rate_limiter,
+            dry_run_log: DryRunLog::default(),
+        }
+    }
+
+    /// Evaluate a hook's tool call the way the hook would with its own
+    /// engine.
+    ///
+    /// The engine is only locked while copying it, not while evaluating.
+    fn evaluate(&self, request: &EvaluateRequest) -> PolicyDecision {
+        let mut policy = self
+            .engine
+            .lock()
+            .unwrap_or_else(PoisonError::into_inner)
+            .for_session(&request.session_id, request.permission_mode.as_deref())
+            .into_owned();
+        policy.set_rate_limiter(self.rate_limiter.clone());
+        policy.set_dry_run_log(self.dry_run_log.clone());
+        policy.evaluate_with_cwd(
+            &request.tool_name,
+            &request.tool_input,
+            request.cwd.as_deref().map(Path::new),
+        )
+    }
+}
+
 /// Handle for a running IPC server.
 ///
-/// When dropped, the socket file is cleaned up.
+/// When dropped, the socket file is cleaned up, unless the server was
+/// handed off and the file belongs to the new supervisor.
 #[derive(Debug)]
 pub struct ServerHandle {
     socket_path: PathBuf,
     shutdown_tx: watch::Sender<bool>,
+    handoff: Option<HandoffState>,
+    drained: CancellationToken,
 }
 
 impl ServerHandle {
@@ -131,6 +287,20 @@ impl ServerHandle {
     pub fn socket_path(&self) -> &Path {
         &self.socket_path
     }
+
+    /// Whether the server answered a handoff request.
+    #[must_use]
+    pub fn is_handed_off(&self) -> bool {
+        self.handoff
+            .as_ref()
+            .is_some_and(HandoffState::is_handed_off)
+    }
+
+    /// Wait until the server stopped accepting connections and answered
+    /// the ones it had accepted.
+    pub async fn drained(&self) {
+        self.drained.cancelled().await;
+    }
 }
 
 impl Drop for ServerHandle {
@@ -139,7 +309,12 @@ impl Drop for ServerHandle {
         let _ = self.shutdown_tx.send(true);
 
         // Clean up socket file
-        if self.socket_path.exists() {
+        if self.is_handed_off() {
+            tracing::debug!(
+                path = %self.socket_path.display(),
+                "Leaving the socket file to the new supervisor"
+            );
+        } else if self.socket_path.exists() {
             if let Err(e) = std::fs::remove_file(&self.socket_path) {
                 tracing::warn!(
                     path = %self.socket_path.display(),
@@ -155,6 +330,7 @@ impl Drop for ServerHandle {
 async fn handle_connection<F, Fut>(
     stream: tokio::net::UnixStream,
     handler: Arc<F>,
+    services: Services,
 ) -> Result<(), IpcError>
 where
     F: Fn(EscalationRequest) -> Fut + Send + Sync,
@@ -170,8 +346,86 @@ where
         return Ok(());
     }
 
+    // Control requests and decision reports are tagged; only control
+    // requests get a reply
+    let Services {
+        decisions,
+        control,
+        policy,
+        handoff,
+    } = services;
+    let message: serde_json::Value = serde_json::from_str(line.trim())?;
+    let message_type = message.get("type").and_then(serde_json::Value::as_str);
+    let span = tracing::Span::current();
+    span.record("request", message_type.unwrap_or("escalation"));
+    if message_type == Some("handoff") {
+        let Some(handoff) = handoff else {
+            tracing::debug!("Closing handoff request; this supervisor cannot hand off");
+            return Ok(());
+        };
+        let mut response_json = serde_json::to_string(&handoff.hand_off())?;
+        response_json.push('\n');
+        writer.write_all(response_json.as_bytes()).await?;
+        writer.flush().await?;
+        return Ok(());
+    }
+    if message_type == Some("control") {
+        let request: ControlRequest = serde_json::from_value(message["payload"].clone())?;
+        tracing::debug!(?request, "Received control request");
+        let response = match control {
+            Some(control) => control.handle(request).await,
+            None => ControlResponse::Rejected {
+                reason: "This supervisor does not accept control requests".to_string(),
+            },
+        };
+        let mut response_json = serde_json::to_string(&response)?;
+        response_json.push('\n');
+        writer.write_all(response_json.as_bytes()).await?;
+        writer.flush().await?;
+        return Ok(());
+    }
+    if message_type == Some("evaluate") {
+        let request: EvaluateRequest = serde_json::from_value(message["payload"].clone())?;
+        span.record("session_id", request.session_id.as_str());
+        let Some(policy) = policy else {
+            tracing::debug!(
+                "Closing evaluation request; this supervisor does not serve its policy"
+            );
+            return Ok(());
+        };
+        let verdict = PolicyVerdict::from(policy.evaluate(&request));
+        tracing::debug!(
+            session_id = %request.session_id,
+            tool_name = %request.tool_name,
+            verdict = ?verdict,
+            "Evaluated tool call for hook"
+        );
+        let mut response_json = serde_json::to_string(&verdict)?;
+        response_json.push('\n');
+        writer.write_all(response_json.as_bytes()).await?;
+        writer.flush().await?;
+        return Ok(());
+    }
+    if message_type == Some("decision") {
+        let report: HookDecisionReport = serde_json::from_value(message["payload"].clone())?;
+        span.record("session_id", report.session_id.as_str());
+        span.record("tool_use_id", report.tool_use_id.as_str());
+        tracing::debug!(
+            session_id = %report.session_id,
+            tool_use_id = %report.tool_use_id,
+            tool_name = %report.tool_name,
+            decision = ?report.decision,
+            "Received hook decision report"
+        );
+        if let Some(decisions) = decisions {
+            decisions.record(report);
+        }
+        return Ok(());
+    }
+
     // Parse the request
-    let request: EscalationRequest = serde_json::from_str(line.trim())?;
+    let request: EscalationRequest = serde_json::from_value(message)?;
+    span.record("session_id", request.session_id.as_str());
 
     tracing::debug!(
         session_id = %request.session_id,
@@ -207,7 +461,7 @@ mod tests {
     #[test]
     fn server_with_default_path_uses_default() {
         let server = IpcServer::with_default_path();
-        assert_eq!(server.socket_path(), Path::new(DEFAULT_SOCKET_PATH));
+        assert_eq!(server.socket_path(), default_socket_path());
     }
 
     #[tokio::test]
@@ -245,6 +499,7 @@ mod tests {
             tool_name: "Bash".to_string(),
             tool_input: json!({"command": "ls"}),
             reason: "Test escalation".to_string(),
+            appeal: None,
         };
 
         let response = client.escalate(&request).await.expect("Escalation failed");
@@ -256,6 +511,7 @@ mod tests {
             tool_name: "Read".to_string(),
             tool_input: json!({"path": "/tmp/test"}),
             reason: "Test escalation".to_string(),
+            appeal: None,
         };
 
         let response = client.escalate(&request).await.expect("Escalation failed");
@@ -268,6 +524,79 @@ mod tests {
         tokio::time::sleep(Duration::from_millis(10)).await;
     }
 
+    #[tokio::test]
+    async fn server_records_decision_reports() {
+        use crate::ipc::IpcClient;
+
+        let socket_path =
+            std::env::temp_dir().join(format!("test-report-{}.sock", std::process::id()));
+        let log = HookDecisionLog::new();
+        let server = IpcServer::new(&socket_path).with_decision_log(log.clone());
+        let _handle = server
+            .start(|_| async { EscalationResponse::Allow })
+            .expect("Failed to start server");
+
+        let report = HookDecisionReport {
+            session_id: "test-session".to_string(),
+            tool_use_id: "toolu_1".to_string(),
+            tool_name: "Bash".to_string(),
+            decision: EscalationResponse::Deny {
+                reason: "Bash not allowed".to_string(),
+            },
+        };
+        IpcClient::with_path(&socket_path)
+            .report_decision(&report)
+            .await
+            .expect("Report failed");
+
+        assert!(log.wait_for("toolu_1", Duration::from_secs(2)).await);
+        assert_eq!(log.take("toolu_1"), Some(report.decision));
+    }
+
+    #[test]
+    fn served_policy_follows_the_shared_engine() {
+        use crate::config::RateLimitConfig;
+        use crate::supervisor::PolicyLevel;
+
+        let limit = RateLimitConfig {
+            max: 1,
+            per_seconds: 60,
+        };
+        let mut engine = PolicyEngine::new(PolicyLevel::Permissive);
+        engine.set_rate_limiter(RateLimiter::from_config(
+            &[("Bash".to_string(), limit)].into_iter().collect(),
+        ));
+        let engine = Arc::new(Mutex::new(engine));
+        let served = ServedPolicy::new(Arc::clone(&engine));
+        let request = |tool_name: &str| EvaluateRequest {
+            session_id: "test-session".to_string(),
+            tool_name: tool_name.to_string(),
+            tool_input: json!({"command": "ls"}),
+            cwd: None,
+            permission_mode: None,
+        };
+
+        // The runner evaluating the same call does not use up the hook's limit
+        assert_eq!(served.evaluate(&request("Bash")), PolicyDecision::Allow);
+        let runner = engine
+            .lock()
+            .unwrap()
+            .evaluate("Bash", &json!({"command": "ls"}));
+        assert_eq!(runner, PolicyDecision::Allow);
+        assert!(matches!(
+            served.evaluate(&request("Bash")),
+            PolicyDecision::Escalate(_)
+        ));
+
+        // What the runner's engine learns applies to the next hook call
+        assert_eq!(served.evaluate(&request("WebFetch")), PolicyDecision::Allow);
+        engine.lock().unwrap().deny_tool("WebFetch");
+        assert!(matches!(
+            served.evaluate(&request("WebFetch")),
+            PolicyDecision::Deny(_)
+        ));
+    }
+
     #[tokio::test]
     async fn server_handle_drop_cleans_up_socket() {
         let temp_dir = std::env::temp_dir();
@@ -5,6 +5,10 @@
 
 use serde::{Deserialize, Serialize};
 
+use crate::dashboard::SupervisorStatus;
+use crate::hooks::CompletionAssessment;
+use crate::supervisor::{DenyReason, HealthReport, PolicyDecision};
+
 /// Request from hook to supervisor for escalation.
 ///
 /// When a hook binary encounters a tool call that requires supervisor
@@ -19,6 +23,20 @@ pub struct EscalationRequest {
     pub tool_input: serde_json::Value,
     /// Reason for escalation.
     pub reason: String,
+    /// Appeal of an earlier denial of the same call.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub appeal: Option<Appeal>,
+}
+
+/// Claude's appeal of a denied escalation.
+///
+/// See [`AppealGate`](crate::supervisor::AppealGate).
+#[derive(Debug, Clone, Serialize, Deserialize, PartialEq, Eq)]
+pub struct Appeal {
+    /// Token from the deny reason.
+    pub token: String,
+    /// Context Claude says the supervisor lacked.
+    pub justification: String,
 }
 
 /// Response from supervisor to hook.
@@ -40,6 +58,95 @@ pub enum EscalationResponse {
         /// The modified input parameters to use instead.
         updated_input: serde_json::Value,
     },
+    /// Allow the tool call and pass guidance on to Claude.
+    Guide {
+        /// Advice for Claude on how to proceed.
+        guidance: String,
+    },
+}
+
+/// Final decision a hook made for a tool call, reported to the supervisor.
+///
+/// Sent without waiting for a reply; see
+/// [`HookDecisionLog`](crate::ipc::HookDecisionLog).
+#[derive(Debug, Clone, Serialize, Deserialize, PartialEq)]
+pub struct HookDecisionReport {
+    /// Session ID from Claude Code.
+    pub session_id: String,
+    /// Tool use ID the decision applies to.
+    pub tool_use_id: String,
+    /// Name of the tool being called.
+    pub tool_name: String,
+    /// The decision.
+    #[serde(flatten)]
+    pub decision: EscalationResponse,
+}
+
+/// Request from hook to supervisor to evaluate a tool call against the
+/// supervisor's policy engine instead of building its own.
+#[derive(Debug, Clone, Serialize, Deserialize, PartialEq)]
+pub struct EvaluateRequest {
+    /// Session ID from Claude Code.
+    pub session_id: String,
+    /// Name of the tool being called.
+    pub tool_name: String,
+    /// Tool input parameters.
+    pub tool_input: serde_json::Value,
+    /// Working directory relative paths are resolved against.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub cwd: Option<String>,
+    /// Claude Code's permission mode.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub permission_mode: Option<String>,
+}
+
+/// Policy decision for an [`EvaluateRequest`].
+#[derive(Debug, Clone, Serialize, Deserialize, PartialEq)]
+#[serde(tag = "decision", rename_all = "snake_case")]
+pub enum PolicyVerdict {
+    /// Allow the tool call.
+    Allow,
+    /// Allow the tool call with modified input parameters.
+    AllowWithModification {
+        /// The modified input parameters to use instead.
+        updated_input: serde_json::Value,
+    },
+    /// Deny the tool call.
+    Deny {
+        /// Why the policy denied it, and by which rule.
+        reason: DenyReason,
+    },
+    /// Escalate the tool call.
+    Escalate {
+        /// Why the policy escalated it.
+        reason: String,
+    },
+}
+
+impl From<PolicyDecision> for PolicyVerdict {
+    fn from(decision: PolicyDecision) -> Self {
+        match decision {
+            PolicyDecision::Allow => Self::Allow,
+            PolicyDecision::AllowWithModification(updated_input) => {
+                Self::AllowWithModification { updated_input }
+            }
+            PolicyDecision::Deny(reason) => Self::Deny { reason },
+            PolicyDecision::Escalate(reason) => Self::Escalate { reason },
+        }
+    }
+}
+
+impl From<PolicyVerdict> for PolicyDecision {
+    fn from(verdict: PolicyVerdict) -> Self {
+        match verdict {
+            PolicyVerdict::Allow => Self::Allow,
+            PolicyVerdict::AllowWithModification { updated_input } => {
+                Self::AllowWithModification(updated_input)
+            }
+            PolicyVerdict::Deny { reason } => Self::Deny(reason),
+            PolicyVerdict::Escalate { reason } => Self::Escalate(reason),
+        }
+    }
 }
 
 /// Request from Stop hook to supervisor for Q&A escalation.
@@ -47,6 +154,9 @@ pub enum EscalationResponse {
 pub struct StopEscalationRequest {
     /// Session ID from Claude Code.
     pub session_id: String,
+    /// Subagent that is stopping; `None` is the main conversation.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub agent_id: Option<String>,
     /// The final message/summary from Claude before stopping.
     /// Note: This may be empty - the supervisor should read the transcript
     /// for the actual final message when `transcript_path` is provided.
@@ -56,8 +166,12 @@ pub struct StopEscalationRequest {
     pub transcript_path: Option<String>,
     /// The original task being worked on.
     pub task: Option<String>,
-    /// Current iteration count for this session.
+    /// Current iteration count for this session's agent.
     pub iteration: u32,
+    /// Completion assessment of Claude's final message, when the hook could
+    /// read it from the transcript.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub completion: Option<CompletionAssessment>,
 }
 
 /// Response from supervisor to Stop hook.
@@ -73,6 +187,39 @@ pub enum StopEscalationResponse {
     },
 }
 
+/// Request from `attach` to a detached session.
+#[derive(Debug, Clone, Copy, Serialize, Deserialize, PartialEq, Eq)]
+#[serde(tag = "command", rename_all = "snake_case")]
+pub enum ControlRequest {
+    /// Report the session's status.
+    Status,
+    /// Stop the session.
+    Stop,
+    /// Continue a paused session.
+    Continue,
+}
+
+/// Response from a detached session to a [`ControlRequest`].
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(tag = "result", rename_all = "snake_case")]
+pub enum ControlResponse {
+    /// Current status of the session.
+    Status {
+        /// The status, as shown on the dashboard.
+        status: Box<SupervisorStatus>,
+        /// The session's health, if it reports it.
+        #[serde(default, skip_serializing_if = "Option::is_none")]
+        health: Option<HealthReport>,
+    },
+    /// The command was passed on to the session.
+    Accepted,
+    /// The command could not be passed on.
+    Rejected {
+        /// Why not.
+        reason: String,
+    },
+}
+
 /// Errors that can occur during IPC.
 #[derive(Debug, thiserror::Error)]
 pub enum IpcError {
@@ -95,11 +242,21 @@ pub enum IpcError {
     /// The response from the supervisor was invalid.
     #[error("Invalid response from supervisor")]
     InvalidResponse,
+
+    /// The supervisor speaks a different handoff protocol.
+    #[error("Supervisor speaks handoff protocol {found}, this binary speaks {expected}")]
+    ProtocolMismatch {
+        /// Protocol of this binary.
+        expected: u32,
+        /// Protocol of the running supervisor.
+        found: u32,
+    },
 }
 
 #[cfg(test)]
 mod tests {
     use super::*;
+    use crate::hooks::CompletionDetector;
     use serde_json::json;
 
     #[test]
@@ -109,6 +266,7 @@ mod tests {
             tool_name: "Bash".to_string(),
             tool_input: json!({"command": "ls -la"}),
             reason: "Potentially dangerous command".to_string(),
+            appeal: None,
         };
 
         let serialized = serde_json::to_string(&request).unwrap();
@@ -117,6 +275,24 @@ mod tests {
         assert_eq!(request, deserialized);
     }
 
+    #[test]
+    fn hook_decision_report_flattens_decision() {
+        let report = HookDecisionReport {
+            session_id: "session-123".to_string(),
+            tool_use_id: "toolu_1".to_string(),
+            tool_name: "Bash".to_string(),
+            decision: EscalationResponse::Deny {
+                reason: "no".to_string(),
+            },
+        };
+
+        let serialized = serde_json::to_value(&report).unwrap();
+        assert_eq!(serialized["decision"], "deny");
+        assert_eq!(serialized["reason"], "no");
+        let deserialized: HookDecisionReport = serde_json::from_value(serialized).unwrap();
+        assert_eq!(report, deserialized);
+    }
+
     #[test]
     fn escalation_response_allow_serialization() {
         let response = EscalationResponse::Allow;
@@ -153,6 +329,37 @@ mod tests {
         assert_eq!(response, deserialized);
     }
 
+    #[test]
+    fn escalation_response_guide_serialization() {
+        let response = EscalationResponse::Guide {
+            guidance: "Run the tests first".to_string(),
+        };
+        let serialized = serde_json::to_string(&response).unwrap();
+        assert_eq!(
+            serialized,
+            r#"{"decision":"guide","guidance":"Run the tests first"}"#
+        );
+
+        let deserialized: EscalationResponse = serde_json::from_str(&serialized).unwrap();
+        assert_eq!(response, deserialized);
+    }
+
+    #[test]
+    fn policy_verdict_roundtrips_policy_decisions() {
+        let verdict = PolicyVerdict::from(PolicyDecision::Escalate("Needs review".to_string()));
+        assert_eq!(
+            serde_json::to_string(&verdict).unwrap(),
+            r#"{"decision":"escalate","reason":"Needs review"}"#
+        );
+
+        let decision = PolicyDecision::AllowWithModification(json!({"command": "ls"}));
+        let verdict: PolicyVerdict = serde_json::from_str(
+            &serde_json::to_string(&PolicyVerdict::from(decision.clone())).unwrap(),
+        )
+        .unwrap();
+        assert_eq!(PolicyDecision::from(verdict), decision);
+    }
+
     #[test]
     fn escalation_request_json_line_format() {
         let request = EscalationRequest {
@@ -160,6 +367,7 @@ mod tests {
             tool_name: "Read".to_string(),
             tool_input: json!({"path": "/etc/passwd"}),
             reason: "Sensitive file access".to_string(),
+            appeal: None,
         };
 
         // Verify it can be serialized to a single line (no embedded newlines)
@@ -180,10 +388,14 @@ mod tests {
     fn stop_escalation_request_serialization_roundtrip() {
         let request = StopEscalationRequest {
             session_id: "session-123".to_string(),
+            agent_id: Some("agent-1".to_string()),
             final_message: "I've completed the task".to_string(),
             transcript_path: Some("/home/user/.claude/projects/abc/conversation.jsonl".to_string()),
             task: Some("Fix the auth bug".to_string()),
             iteration: 3,
+            completion: Some(
+                CompletionDetector::default().assess("All done, but the next step is the docs"),
+            ),
         };
         let serialized = serde_json::to_string(&request).unwrap();
         let deserialized: StopEscalationRequest = serde_json::from_str(&serialized).unwrap();
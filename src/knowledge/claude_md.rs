@@ -4,7 +4,7 @@
 //! AST-based section extraction.
 
 use std::collections::HashMap;
-use std::path::Path;
+use std::path::{Path, PathBuf};
 
 use comrak::nodes::{AstNode, NodeValue};
 use comrak::{parse_document, Arena, Options};
@@ -56,28 +56,62 @@ impl ClaudeMdSource {
     /// Merges both sources, with project-level sections taking precedence
     /// over global sections with the same name.
     pub async fn load_with_global(project_dir: &Path) -> Self {
+        Self::merge(Self::load_global().await, &[(None, project_dir)]).await
+    }
+
+    /// Load CLAUDE.md from several repository roots plus the global file.
+    ///
+    /// Each root is given as `(label, path)`. With more than one root, the
+    /// sections of each repository are prefixed with `[label]` so facts can
+    /// be traced back to the repository they came from.
+    pub async fn load_with_global_roots(roots: &[(String, PathBuf)]) -> Self {
+        let labelled = roots.len() > 1;
+        let roots: Vec<_> = roots
+            .iter()
+            .map(|(label, path)| (labelled.then_some(label.as_str()), path.as_path()))
+            .collect();
+        Self::merge(Self::load_global().await, &roots).await
+    }
+
+    /// Read the global ~/.claude/CLAUDE.md, if present.
+    async fn load_global() -> Option<String> {
+        let global_path = dirs::home_dir()?.join(".claude").join("CLAUDE.md");
+        let content = tokio::fs::read_to_string(&global_path).await.ok()?;
+        tracing::debug!("Loaded global CLAUDE.md from {:?}", global_path);
+        Some(content)
+    }
+
+    /// Merge the global content with each root's CLAUDE.md, later roots
+    /// taking precedence for unlabelled sections with the same name.
+    async fn merge(global: Option<String>, roots: &[(Option<&str>, &Path)]) -> Self {
         let mut combined_sections = HashMap::new();
-        let mut combined_content = String::new();
+        let mut parts = Vec::new();
 
         // Load global CLAUDE.md first (lower priority)
-        if let Some(home) = dirs::home_dir() {
-            let global_path = home.join(".claude").join("CLAUDE.md");
-            if let Ok(content) = tokio::fs::read_to_string(&global_path).await {
-                let global = Self::from_content(&content);
-                combined_sections.extend(global.sections);
-                combined_content.push_str(&content);
-                combined_content.push_str("\n\n---\n\n");
-                tracing::debug!("Loaded global CLAUDE.md from {:?}", global_path);
-            }
+        if let Some(content) = global {
+            combined_sections.extend(Self::parse_sections(&content));
+            parts.push(content);
         }
 
-        // Load project CLAUDE.md (higher priority, overwrites global sections)
-        let project_path = project_dir.join("CLAUDE.md");
-        if let Ok(content) = tokio::fs::read_to_string(&project_path).await {
-            let project = Self::from_content(&content);
-            combined_sections.extend(project.sections);
-            combined_content.push_str(&content);
+        // Load project CLAUDE.md files (higher priority, overwrite global sections)
+        for (label, dir) in roots {
+            let project_path = dir.join("CLAUDE.md");
+            let Ok(content) = tokio::fs::read_to_string(&project_path).await else {
+                continue;
+            };
             tracing::debug!("Loaded project CLAUDE.md from {:?}", project_path);
+            let sections = Self::parse_sections(&content);
+            if let Some(label) = label {
+                combined_sections.extend(
+                    sections
+                        .into_iter()
+                        .map(|(header, body)| (format!("[{label}] {header}"), body)),
+                );
+                parts.push(format!("<!-- CLAUDE.md from {label} -->\n{content}"));
+            } else {
+                combined_sections.extend(sections);
+                parts.push(content);
+            }
         }
 
         if combined_sections.is_empty() {
@@ -86,7 +120,7 @@ impl ClaudeMdSource {
         } else {
             Self {
                 sections: combined_sections,
-                raw_content: combined_content,
+                raw_content: parts.join("\n\n---\n\n"),
             }
         }
     }
@@ -236,7 +270,7 @@ impl ClaudeMdSource {
             .collect();
 
         // Sort by score descending
-        matches.sort_by(|a, b| b.0.cmp(&a.0));
+        matches.sort_by_key(|b| std::cmp::Reverse(b.0));
 
         matches.into_iter().map(|(_, h, c)| (h, c)).collect()
     }
@@ -246,6 +280,22 @@ impl ClaudeMdSource {
     pub fn raw_content(&self) -> &str {
         &self.raw_content
     }
+
+    /// Contents of the fenced code blocks whose info string is `info`.
+    #[must_use]
+    pub fn code_blocks(&self, info: &str) -> Vec<String> {
+        let arena = Arena::new();
+        let root = parse_document(&arena, &self.raw_content, &Options::default());
+
+        root.descendants()
+            .filter_map(|node| match &node.data.borrow().value {
+                NodeValue::CodeBlock(block) if block.fenced && block.info.trim() == info => {
+                    Some(block.literal.clone())
+                }
+                _ => None,
+            })
+            .collect()
+    }
 }
 
 impl KnowledgeSource for ClaudeMdSource {
@@ -322,6 +372,26 @@ Some links here.
         assert!(source.sections["Conventions"].contains("snake_case"));
     }
 
+    #[test]
+    fn test_code_blocks_by_info_string() {
+        let markdown = "# Project
+
+```supervisor-policy
+deny = [\"WebFetch\"]
+```
+
+```toml
+deny = []
+```
+";
+        let source = ClaudeMdSource::from_content(markdown);
+        assert_eq!(
+            source.code_blocks("supervisor-policy"),
+            ["deny = [\"WebFetch\"]\n"]
+        );
+        assert!(source.code_blocks("json").is_empty());
+    }
+
     #[test]
     fn test_empty_file() {
         let source = ClaudeMdSource::from_content("");
@@ -19,20 +19,27 @@ pub struct QAPair {
 /// Knowledge source backed by session history.
 pub struct SessionHistorySource {
     pub pairs: Vec<QAPair>,
+    /// Bash commands run in past sessions, oldest first.
+    pub commands: Vec<String>,
 }
 
 impl SessionHistorySource {
     /// Create from a list of journal entries.
     #[must_use]
     pub fn from_entries(entries: &[JournalEntry]) -> Self {
-        let pairs = extract_qa_pairs(entries);
-        Self { pairs }
+        Self {
+            pairs: extract_qa_pairs(entries),
+            commands: extract_bash_commands(entries),
+        }
     }
 
     /// Create an empty source.
     #[must_use]
     pub fn empty() -> Self {
-        Self { pairs: Vec::new() }
+        Self {
+            pairs: Vec::new(),
+            commands: Vec::new(),
+        }
     }
 
     /// Maximum number of JSONL files to load.
@@ -128,7 +135,7 @@ impl SessionHistorySource {
             })
             .collect();
 
-        matches.sort_by(|a, b| b.0.cmp(&a.0));
+        matches.sort_by_key(|b| std::cmp::Reverse(b.0));
         matches.into_iter().map(|(_, p)| p).collect()
     }
 }
@@ -177,11 +184,9 @@ pub fn extract_qa_pairs(entries: &[JournalEntry]) -> Vec<QAPair> {
 
     for entry in entries {
         match entry {
-            JournalEntry::User(u) => {
-                // Skip tool results (they have source_tool_use_id)
-                if u.source_tool_use_id.is_none() {
-                    user_messages.insert(u.uuid.clone(), u);
-                }
+            // Skip tool results (they have source_tool_use_id)
+            JournalEntry::User(u) if u.source_tool_use_id.is_none() => {
+                user_messages.insert(u.uuid.clone(), u);
             }
             JournalEntry::Assistant(a) => {
                 assistant_messages.push(a);
@@ -212,6 +217,26 @@ pub fn extract_qa_pairs(entries: &[JournalEntry]) -> Vec<QAPair> {
     pairs
 }
 
+/// Extract the commands of Bash tool calls from journal entries.
+#[must_use]
+pub fn extract_bash_commands(entries: &[JournalEntry]) -> Vec<String> {
+    entries
+        .iter()
+        .filter_map(|entry| match entry {
+            JournalEntry::Assistant(a) => Some(&a.message.content),
+            _ => None,
+        })
+        .flatten()
+        .filter_map(|block| match block {
+            ContentBlock::ToolUse { name, input, .. } if name == "Bash" => input
+                .get("command")
+                .and_then(|c| c.as_str())
+                .map(String::from),
+            _ => None,
+        })
+        .collect()
+}
+
 /// Extract text content from an assistant message.
 fn extract_assistant_text(message: &AssistantMessage) -> String {
     message
@@ -262,6 +287,8 @@ mod tests {
                     text: content.to_string(),
                 }],
                 model: Some("claude-3".to_string()),
+                id: None,
+                usage: None,
             },
             cwd: "/tmp".to_string(),
             version: "2.1.25".to_string(),
@@ -284,6 +311,32 @@ mod tests {
         assert!(pairs[0].answer.contains("nextest"));
     }
 
+    #[test]
+    fn test_extract_bash_commands() {
+        let mut entry = make_assistant_entry("a1", "q1", "Checking");
+        if let JournalEntry::Assistant(ref mut a) = entry {
+            a.message.content.extend([
+                ContentBlock::ToolUse {
+                    id: "t1".to_string(),
+                    name: "Bash".to_string(),
+                    input: serde_json::json!({"command": "terraform plan"}),
+                },
+                ContentBlock::ToolUse {
+                    id: "t2".to_string(),
+                    name: "Read".to_string(),
+                    input: serde_json::json!({"file_path": "main.tf"}),
+                },
+            ]);
+        }
+        let entries = vec![make_user_entry("q1", None, "Plan it"), entry];
+
+        assert_eq!(extract_bash_commands(&entries), ["terraform plan"]);
+        assert_eq!(
+            SessionHistorySource::from_entries(&entries).commands,
+            ["terraform plan"]
+        );
+    }
+
     #[test]
     fn test_history_source_query() {
         let pairs = vec![
@@ -299,7 +352,10 @@ mod tests {
             },
         ];
 
-        let source = SessionHistorySource { pairs };
+        let source = SessionHistorySource {
+            pairs,
+            commands: Vec::new(),
+        };
         let fact = source.query("test framework");
 
         assert!(fact.is_some());
@@ -341,7 +397,10 @@ mod tests {
             })
             .collect();
 
-        let source = SessionHistorySource { pairs };
+        let source = SessionHistorySource {
+            pairs,
+            commands: Vec::new(),
+        };
         let summary = source.context_summary().unwrap();
 
         // Should only include recent 10
@@ -11,6 +11,11 @@ pub mod hooks;
 pub mod integration;
 pub mod ipc;
 pub mod knowledge;
+#[cfg(feature = "otel")]
+pub mod otel;
+pub mod snapshot;
 pub mod supervisor;
+pub mod telemetry;
+pub mod trash;
 pub mod watcher;
 pub mod worktree;
@@ -1,21 +1,71 @@
 //! Claude Supervisor - Automated Claude Code with AI oversight.
 
-use std::io::{self, BufRead, Write};
-use std::path::PathBuf;
+use std::borrow::Cow;
+use std::collections::{BTreeMap, HashSet};
+use std::io::{self, BufRead, IsTerminal, Write};
+use std::path::{Path, PathBuf};
 
 use clap::{Parser, Subcommand, ValueEnum};
+use tokio_util::sync::CancellationToken;
+use tracing::Level;
+use tracing_subscriber::filter::Targets;
 use tracing_subscriber::{fmt, prelude::*, EnvFilter};
 
-use claude_supervisor::ai::AiClient;
-use claude_supervisor::cli::{ClaudeProcess, ClaudeProcessBuilder, SpawnError};
+use claude_supervisor::ai::{
+    transcript_events, transcript_task, AiClient, InteractiveApprover, Redactor, SessionReviewer,
+    WebhookBackend, DEFAULT_SESSION_WEIGHT,
+};
+use claude_supervisor::audit::{
+    config_hash, default_audit_path, default_dead_letter_dir, default_manifest_dir,
+    default_transcript_dir, reconstruct, redact_config, suggest_claude_md_additions,
+    suggestions_markdown, write_transcript, ArtifactBundler, AuditEvent, AuditLog, AuditRecord,
+    AuditSession, AuditSinks, CostDimension, CostShare, DeadLetterLog, Decision, EventType,
+    RuleStats, RunLimits, RunManifest, SessionMetrics, TranscriptMirror, SUGGESTIONS_FILE,
+};
+use claude_supervisor::cli::{
+    binary_version, claude_binary_from_env, is_older_than_minimum, locate_binary,
+    probe_binary_version, ClaudeProcess, ClaudeProcessBuilder, SpawnError, MIN_CLAUDE_CODE_VERSION,
+};
 use claude_supervisor::commands::HookInstaller;
-use claude_supervisor::config::{ConfigLoader, PolicyConfig, SupervisorConfig, WorktreeConfig};
-use claude_supervisor::display;
-use claude_supervisor::hooks::HookHandler;
+use claude_supervisor::config::{
+    artifacts_dir, data_dir, default_data_dir, detached_dir, format_issues, kill_switch_path,
+    migrate_audit_db, schema, set_data_dir, set_kill_switch_path, sockets_dir, AiConfig,
+    AuditConfig, ConfigError, ConfigIssue, ConfigLoader, ContextRecoveryMode, DecisionAuthority,
+    DecisionBackendKind, GitConfig, NetworkConfig, NovelBinaryConfig, PolicyConfig,
+    SuggestionConfig, SupervisorConfig, WorktreeConfig,
+};
+use claude_supervisor::dashboard::{
+    create_dashboard_channels, DashboardConfig, DashboardServer, EventScrubber, SupervisorStatus,
+};
+use claude_supervisor::display::{self, DisplayOptions};
+use claude_supervisor::hooks::{CompletionDetector, HookHandler, HookInput};
+use claude_supervisor::ipc::{
+    default_socket_path, session_socket_path, ControlRequest, ControlResponse, EscalationResponse,
+    EvaluateRequest, HandoffState, HookDecisionLog, IpcClient, IpcServer, SessionControl,
+    SOCKET_ENV,
+};
+use claude_supervisor::knowledge::MemorySource;
+use claude_supervisor::snapshot::SnapshotStore;
 use claude_supervisor::supervisor::{
-    MultiSessionSupervisor, PolicyEngine, PolicyLevel, Supervisor, SupervisorResult,
+    budget_note_path, generate_session_name, run_policy_cases, simulate, unique_session_name,
+    validate_session_name, AdoptedSession, AdoptionEnd, BashAllowlist, Blocklist, BlocklistRule,
+    BudgetAlerts, CommandRewriter, Containment, CostBudget, DecisionBreakdown, DetachedSession,
+    DryRunLog, DryRunRecord, EditRule, EscalationSampler, Escrow, ExfiltrationGuard, GitGate,
+    HealthMonitor, HealthReport, KillSwitch, LogTail, MultiSessionSupervisor, OverrideEffect,
+    OverrideError, PathRule, PolicyCaseFile, PolicyCaseReport, PolicyDecision, PolicyEngine,
+    PolicyLevel, RateLimiter, RecoveryPlan, ResumeContext, RewriteRule, RuleCategory, Sandbox,
+    SecretScanner, SelfProtection, SessionOverride, SessionStats, SimulatedCall, SimulationReport,
+    Supervisor, SupervisorResult, TimeBox, ToolAliases, ADOPT_POLL_INTERVAL, BUDGET_NOTE_ENV,
+    CONTEXT_EXHAUSTED_EXIT_CODE, DETACH_STARTUP_TIMEOUT, HALTED_EXIT_CODE, KILL_SWITCH_REASON,
+    NO_SANDBOX_ENV, POLICY_DRY_RUN_ENV, SESSION_OVERRIDES_ENV, SESSION_ROOTS_ENV,
+    TIMED_OUT_EXIT_CODE, WRAP_UP_AT_ENV,
 };
-use claude_supervisor::worktree::{WorktreeManager, WorktreeRegistry};
+use claude_supervisor::telemetry::{ChromeTraceLayer, TraceFileGuard};
+use claude_supervisor::trash::TrashStore;
+use claude_supervisor::watcher::{
+    find_project_sessions_dir, find_session_by_id, parse_jsonl_file, SessionReconstructor,
+};
+use claude_supervisor::worktree::{self, WorktreeGroup, WorktreeManager, WorktreeRegistry};
 
 #[derive(Debug, Clone, Copy, ValueEnum)]
 enum PolicyArg {
@@ -24,6 +74,40 @@ enum PolicyArg {
     Strict,
 }
 
+#[derive(Debug, Clone, Copy, ValueEnum)]
+enum RecoveryArg {
+    Off,
+    Resume,
+    Summary,
+}
+
+impl From<RecoveryArg> for ContextRecoveryMode {
+    fn from(arg: RecoveryArg) -> Self {
+        match arg {
+            RecoveryArg::Off => ContextRecoveryMode::Off,
+            RecoveryArg::Resume => ContextRecoveryMode::Resume,
+            RecoveryArg::Summary => ContextRecoveryMode::Summary,
+        }
+    }
+}
+
+#[derive(Debug, Clone, Copy, ValueEnum)]
+enum AuthorityArg {
+    Runner,
+    Hook,
+    Strictest,
+}
+
+impl From<AuthorityArg> for DecisionAuthority {
+    fn from(arg: AuthorityArg) -> Self {
+        match arg {
+            AuthorityArg::Runner => DecisionAuthority::Runner,
+            AuthorityArg::Hook => DecisionAuthority::Hook,
+            AuthorityArg::Strictest => DecisionAuthority::Strictest,
+        }
+    }
+}
+
 impl From<PolicyArg> for PolicyLevel {
     fn from(arg: PolicyArg) -> Self {
         match arg {
@@ -45,10 +129,16 @@ struct Cli {
     #[arg(short = 'v', long, action = clap::ArgAction::Count)]
     verbose: u8,
 
+    /// Write spans to this file in Chrome trace format, for Perfetto.
+    #[arg(long, global = true, value_name = "PATH")]
+    trace_file: Option<PathBuf>,
+
     #[command(subcommand)]
     command: Commands,
 }
 
+// Parsed once per process, so the size of `Run` does not matter
+#[allow(clippy::large_enum_variant)]
 #[derive(Subcommand)]
 enum Commands {
     /// Run Claude Code with supervision.
@@ -64,9 +154,12 @@ enum Commands {
         /// Tools to auto-approve (comma-separated).
         #[arg(long, value_delimiter = ',')]
         allowed_tools: Option<Vec<String>>,
-        /// Resume a previous session by ID.
+        /// Resume a previous session by Claude session ID or session name.
         #[arg(long, conflicts_with = "task")]
         resume: Option<String>,
+        /// Session name (default: a generated adjective-noun slug).
+        #[arg(long)]
+        name: Option<String>,
         /// Run in an isolated git worktree.
         #[arg(long)]
         worktree: bool,
@@ -76,11 +169,104 @@ enum Commands {
         /// Cleanup worktree after session ends.
         #[arg(long)]
         worktree_cleanup: bool,
+        /// Run Bash commands without the configured sandbox wrapper.
+        #[arg(long)]
+        no_sandbox: bool,
+        /// Allow every call, recording the calls the policy would deny or escalate.
+        #[arg(long)]
+        policy_dry_run: bool,
+        /// Snapshot files before approved Write/Edit calls.
+        #[arg(long)]
+        snapshot: bool,
+        /// Repository the session spans (repeatable; the first is the working directory).
+        #[arg(long = "repo", action = clap::ArgAction::Append)]
+        repos: Vec<PathBuf>,
+        /// Most bytes printed for one event, tool input or tool result.
+        #[arg(long)]
+        max_output_bytes: Option<usize>,
+        /// Print Claude's thinking blocks (default).
+        #[arg(long, overrides_with = "hide_thinking")]
+        show_thinking: bool,
+        /// Do not print Claude's thinking blocks.
+        #[arg(long, overrides_with = "show_thinking")]
+        hide_thinking: bool,
+        /// Claude Code binary to run (default: `CLAUDE_SUPERVISOR_CLAUDE_BIN`, then `claude`).
+        #[arg(long)]
+        claude_bin: Option<PathBuf>,
+        /// Scenario file for a scripted stand-in for Claude, such as `fake-claude`.
+        #[arg(long, value_name = "FILE")]
+        scenario: Option<PathBuf>,
+        /// Supervise with the policy alone, without the AI supervisor.
+        #[arg(long)]
+        no_ai: bool,
+        /// Which decision stands when installed hooks and the runner disagree.
+        #[arg(long, value_enum)]
+        decision_authority: Option<AuthorityArg>,
+        /// Allow matching tool calls for this session only (repeatable; `Tool` or `Tool:pattern`).
+        #[arg(long = "allow-once", value_parser = parse_allow_once, action = clap::ArgAction::Append)]
+        allow_once: Vec<SessionOverride>,
+        /// Deny matching tool calls for this session only (repeatable; `Tool` or `Tool:pattern`).
+        #[arg(long = "deny-once", value_parser = parse_deny_once, action = clap::ArgAction::Append)]
+        deny_once: Vec<SessionOverride>,
+        /// Wall-clock limit for the session in minutes; Claude is told to wrap up at 80%.
+        #[arg(long = "max-duration", value_name = "MINS")]
+        max_duration: Option<u64>,
+        /// Stop the session clock while the session is paused.
+        #[arg(long)]
+        pause_stops_clock: bool,
+        /// Decide escalations at a terminal prompt instead of the AI or webhook.
+        #[arg(long)]
+        interactive_approvals: bool,
+        /// Mirror the session to a Claude Code compatible transcript for `export transcript`.
+        #[arg(long)]
+        mirror_transcript: bool,
+        /// Continue a session that runs out of context by resuming it or from a progress summary.
+        #[arg(long, value_enum)]
+        context_recovery: Option<RecoveryArg>,
+        /// Resume a session that produces no events for this long with a "continue" nudge.
+        #[arg(long, value_name = "SECS")]
+        nudge_after: Option<u64>,
+        /// Abort instead of warning when the AI model cannot be validated at startup.
+        #[arg(long)]
+        strict_startup: bool,
+        /// Supervise stdout alone, without merging the session transcript Claude writes to disk.
+        #[arg(long)]
+        no_merge_transcript: bool,
+        /// Run the session in the background, logging to a file, once it has started.
+        #[arg(long, conflicts_with = "interactive_approvals")]
+        detach: bool,
+        /// Bundle the manifest, audit export, transcript, diff and summary of the run when it ends.
+        #[arg(long)]
+        bundle: bool,
+        /// Serve the dashboard during the session (default address: 127.0.0.1:3000).
+        #[arg(
+            long,
+            value_name = "HOST:PORT",
+            num_args = 0..=1,
+            default_missing_value = "127.0.0.1:3000"
+        )]
+        dashboard: Option<String>,
+    },
+    /// Follow the output of a detached session and stop or continue it.
+    Attach {
+        /// Session name (default: the only detached session running).
+        #[arg(long)]
+        session: Option<String>,
+    },
+    /// Take the running session over from the supervisor serving the socket,
+    /// as after installing a new version mid-session.
+    UpgradeHandoff {
+        /// Session name (default: the session whose hooks run in this
+        /// environment).
+        #[arg(long)]
+        session: Option<String>,
     },
     /// Install hooks into Claude Code settings.
     InstallHooks,
     /// Uninstall hooks from Claude Code settings.
     UninstallHooks,
+    /// Check the hook installation for missing or duplicate entries.
+    Doctor,
     /// Handle Claude Code hook events (reads JSON from stdin).
     Hook {
         #[command(subcommand)]
@@ -110,6 +296,59 @@ enum Commands {
         /// Auto-continue without user prompts.
         #[arg(long)]
         auto_continue: bool,
+        /// Share of the AI request limit of each task, in `--task` order (default: 1 each).
+        #[arg(
+            long,
+            action = clap::ArgAction::Append,
+            value_parser = clap::value_parser!(u32).range(1..)
+        )]
+        weight: Vec<u32>,
+        /// Most AI requests in flight across all sessions (default: the configured
+        /// `ai.max_concurrent_requests`, else `--max-parallel`).
+        #[arg(long, value_name = "N")]
+        max_ai_requests: Option<usize>,
+    },
+    /// Inspect the audit log.
+    Audit {
+        #[command(subcommand)]
+        action: AuditAction,
+    },
+    /// List and restore file snapshots taken before approved writes.
+    Snapshots {
+        #[command(subcommand)]
+        action: SnapshotAction,
+    },
+    /// List, restore and empty files moved to the trash by escrow.
+    Trash {
+        #[command(subcommand)]
+        action: TrashAction,
+    },
+    /// Evaluate policy changes before applying them.
+    Policy {
+        #[command(subcommand)]
+        action: PolicyAction,
+    },
+    /// Ask the AI for a post-mortem critique of a finished session.
+    Review {
+        /// Session ID or name, or a path to a transcript (.jsonl).
+        #[arg(long)]
+        session: String,
+        /// Write the review to this file instead of stdout.
+        #[arg(long)]
+        out: Option<PathBuf>,
+        /// Append the suggested CLAUDE.md additions to project memory.
+        #[arg(long)]
+        learn: bool,
+    },
+    /// Export a supervised session for use in other tools.
+    Export {
+        #[command(subcommand)]
+        action: ExportAction,
+    },
+    /// Inspect the AI provider.
+    Ai {
+        #[command(subcommand)]
+        action: AiAction,
     },
 }
 
@@ -125,6 +364,34 @@ enum HookEvent {
 enum ConfigAction {
     /// Show current configuration.
     Show,
+    /// Check the config file, listing every problem found.
+    Validate,
+    /// Print the configuration schema.
+    #[command(hide = true)]
+    Schema {
+        /// Config type to describe.
+        #[arg(long, value_enum, default_value_t = SchemaTarget::File)]
+        target: SchemaTarget,
+        /// Output format.
+        #[arg(long, value_enum, default_value_t = SchemaFormat::Json)]
+        format: SchemaFormat,
+    },
+}
+
+#[derive(Debug, Clone, Copy, ValueEnum)]
+enum SchemaTarget {
+    /// The config file (`.claude-supervisor.toml` or the global config.toml).
+    File,
+    /// Settings of a supervised `run` session.
+    Supervisor,
+}
+
+#[derive(Debug, Clone, Copy, ValueEnum)]
+enum SchemaFormat {
+    /// JSON Schema, e.g. for editor validation with taplo.
+    Json,
+    /// Markdown tables for the documentation.
+    Markdown,
 }
 
 #[derive(Subcommand, Clone)]
@@ -134,7 +401,11 @@ enum WorktreeAction {
     /// Remove a worktree.
     Remove {
         /// Name of the worktree to remove.
-        name: String,
+        #[arg(required_unless_present = "group")]
+        name: Option<String>,
+        /// Remove every worktree created for a multi-repo session.
+        #[arg(long, conflicts_with = "name")]
+        group: Option<String>,
         /// Force removal even with uncommitted changes.
         #[arg(short, long)]
         force: bool,
@@ -150,23 +421,275 @@ enum WorktreeAction {
         /// Force removal even with uncommitted changes.
         #[arg(short, long)]
         force: bool,
+        /// Print the worktrees that would be removed without removing them.
+        #[arg(long)]
+        dry_run: bool,
+    },
+    /// Run a command inside a managed worktree, e.g. `exec feature-x -- cargo test`.
+    Exec {
+        /// Name of the worktree to run in.
+        #[arg(required_unless_present = "each")]
+        name: Option<String>,
+        /// Run in every managed worktree in turn.
+        #[arg(long, conflicts_with = "name")]
+        each: bool,
+        /// Command and arguments, after `--`.
+        #[arg(last = true, required = true)]
+        command: Vec<String>,
+    },
+}
+
+#[derive(Subcommand, Clone)]
+enum AuditAction {
+    /// Show decision counts and approximate cost attribution.
+    Stats {
+        /// Break down attributed cost by tool name.
+        #[arg(long)]
+        by_tool: bool,
+        /// Break down attributed cost by touched file path.
+        #[arg(long)]
+        by_file: bool,
+        /// Restrict the cost breakdown to one audit session ID.
+        #[arg(long)]
+        session: Option<uuid::Uuid>,
+    },
+    /// List recent sessions with their result and Claude Code version.
+    Sessions {
+        /// Number of sessions to show.
+        #[arg(short, long, default_value = "20")]
+        limit: usize,
+    },
+    /// Show how often each policy rule fired and how its calls were decided.
+    RuleStats {
+        /// Count rule firings since this time (e.g. 30d, 12h, 2024-06-01).
+        #[arg(long, default_value = "30d", value_parser = parse_since)]
+        since: chrono::DateTime<chrono::Utc>,
+        /// Output the report as JSON.
+        #[arg(long)]
+        json: bool,
+    },
+}
+
+#[derive(Subcommand, Clone)]
+enum ExportAction {
+    /// Write a session as a Claude Code compatible JSONL transcript.
+    ///
+    /// Sessions run with `history.mirror` are exported as mirrored; others
+    /// are reconstructed from the audit log and the saved supervisor
+    /// context, which loses assistant text and tool output.
+    Transcript {
+        /// Audit session ID, session name or Claude session ID.
+        #[arg(long)]
+        session: String,
+        /// File to write.
+        #[arg(long)]
+        out: PathBuf,
+    },
+}
+
+#[derive(Subcommand, Clone, Copy)]
+enum AiAction {
+    /// List the models available from the configured provider.
+    Models,
+}
+
+#[derive(Subcommand, Clone)]
+enum SnapshotAction {
+    /// List snapshots for a session.
+    List {
+        /// Claude session ID.
+        session: String,
+        /// Only show snapshots of this file.
+        path: Option<PathBuf>,
+    },
+    /// Restore files from a session's snapshots.
+    Restore {
+        /// Claude session ID.
+        session: String,
+        /// File to restore (default: every snapshotted file).
+        path: Option<PathBuf>,
+        /// Snapshot ID to restore (default: the oldest, undoing the session).
+        #[arg(long, requires = "path")]
+        id: Option<String>,
+    },
+}
+
+#[derive(Subcommand, Clone)]
+enum TrashAction {
+    /// List trashed files.
+    List {
+        /// Only show files trashed by this Claude session.
+        session: Option<String>,
+    },
+    /// Move a session's trashed files back into the project.
+    Restore {
+        /// Claude session ID.
+        session: String,
+        /// File or directory to restore (default: everything the session trashed).
+        path: Option<PathBuf>,
+    },
+    /// Delete trashed files for good.
+    Empty {
+        /// Only delete files trashed by this Claude session.
+        session: Option<String>,
     },
 }
 
-fn init_tracing(verbosity: u8) {
+#[derive(Subcommand, Clone)]
+enum PolicyAction {
+    /// Replay past tool calls through a candidate config and report changed decisions.
+    Simulate {
+        /// Candidate config file.
+        #[arg(long)]
+        config: PathBuf,
+        /// Replay audit events since this time (e.g. 7d, 12h, 2024-06-01).
+        #[arg(long, default_value = "7d", value_parser = parse_since)]
+        since: chrono::DateTime<chrono::Utc>,
+        /// Replay tool calls from a session transcript instead of the audit log.
+        #[arg(long)]
+        transcript: Option<PathBuf>,
+        /// Output the report as JSON.
+        #[arg(long)]
+        json: bool,
+    },
+    /// Check policy test cases against the active config.
+    Test {
+        /// TOML file of cases (`cases = [{ tool, input, expect }, ...]`).
+        file: PathBuf,
+        /// Config file to test (default: the active config).
+        #[arg(long)]
+        config: Option<PathBuf>,
+        /// Output the report as JSON.
+        #[arg(long)]
+        json: bool,
+    },
+}
+
+/// Parse a relative age (`30m`, `12h`, `7d`, `2w`) or an absolute date/time.
+fn parse_since(value: &str) -> Result<chrono::DateTime<chrono::Utc>, String> {
+    let value = value.trim();
+    if let Some(unit) = value.chars().last().filter(char::is_ascii_alphabetic) {
+        let amount: i64 = value[..value.len() - 1]
+            .parse()
+            .map_err(|_| format!("invalid duration: {value}"))?;
+        let age = match unit {
+            'm' => chrono::Duration::minutes(amount),
+            'h' => chrono::Duration::hours(amount),
+            'd' => chrono::Duration::days(amount),
+            'w' => chrono::Duration::weeks(amount),
+            _ => return Err(format!("unknown duration unit '{unit}' (use m, h, d or w)")),
+        };
+        return Ok(chrono::Utc::now() - age);
+    }
+    if let Ok(time) = chrono::DateTime::parse_from_rfc3339(value) {
+        return Ok(time.with_timezone(&chrono::Utc));
+    }
+    chrono::NaiveDate::parse_from_str(value, "%Y-%m-%d")
+        .map(|date| date.and_time(chrono::NaiveTime::MIN).and_utc())
+        .map_err(|_| format!("invalid time: {value} (use e.g. 7d or 2024-06-01)"))
+}
+
+fn parse_allow_once(value: &str) -> Result<SessionOverride, OverrideError> {
+    SessionOverride::parse(OverrideEffect::Allow, value)
+}
+
+fn parse_deny_once(value: &str) -> Result<SessionOverride, OverrideError> {
+    SessionOverride::parse(OverrideEffect::Deny, value)
+}
+
+/// Finishes the trace file and flushes exported spans when dropped.
+struct TracingGuard {
+    _trace_file: Option<TraceFileGuard>,
+    #[cfg(feature = "otel")]
+    _otel: Option<claude_supervisor::otel::OtelGuard>,
+}
+
+/// Install the log subscriber, and the trace file layer if asked for.
+///
+/// The trace file gets every span of this crate whatever the log filter;
+/// it is finished when the returned guard is dropped. With the `otel`
+/// feature, spans are also exported to the OTLP endpoint set in
+/// `OTEL_EXPORTER_OTLP_ENDPOINT`.
+fn init_tracing(verbosity: u8, trace_file: Option<&Path>) -> TracingGuard {
     let level = match verbosity {
         0 => "debug",
         _ => "trace",
     };
     let filter = EnvFilter::try_from_default_env().unwrap_or_else(|_| EnvFilter::new(level));
-    tracing_subscriber::registry()
-        .with(fmt::layer().with_writer(io::stderr))
-        .with(filter)
-        .init();
+    let (trace_layer, guard) = match trace_file.map(ChromeTraceLayer::create) {
+        Some(Ok((layer, guard))) => (Some(layer), Some(guard)),
+        Some(Err(e)) => {
+            eprintln!("Failed to create trace file: {e}");
+            (None, None)
+        }
+        None => (None, None),
+    };
+    let spans = Targets::new().with_target(env!("CARGO_CRATE_NAME"), Level::TRACE);
+    let registry = tracing_subscriber::registry()
+        .with(fmt::layer().with_writer(io::stderr).with_filter(filter))
+        .with(trace_layer.map(|layer| layer.with_filter(spans)));
+    #[cfg(feature = "otel")]
+    let (registry, otel) = {
+        let (layer, otel) = match claude_supervisor::otel::init() {
+            Ok(Some((layer, otel))) => (Some(layer), Some(otel)),
+            Ok(None) => (None, None),
+            Err(e) => {
+                eprintln!("Failed to create OpenTelemetry exporter: {e}");
+                (None, None)
+            }
+        };
+        (registry.with(layer), otel)
+    };
+    registry.init();
+    TracingGuard {
+        _trace_file: guard,
+        #[cfg(feature = "otel")]
+        _otel: otel,
+    }
+}
+
+/// Apply the configured data directory and kill switch, and move an audit
+/// log left at the old default location into it.
+fn init_data_dir() {
+    match ConfigLoader::new().load() {
+        Ok(config) => {
+            if let Some(dir) = config.data_dir {
+                set_data_dir(dir);
+            }
+            if let Some(path) = config.kill_switch {
+                set_kill_switch_path(path);
+            }
+        }
+        // Reported by the commands that need the configuration
+        Err(e) => tracing::debug!(error = %e, "Failed to load config for the data directory"),
+    }
+    match migrate_audit_db(&default_data_dir(), &data_dir()) {
+        Ok(Some(path)) => {
+            tracing::info!(path = %path.display(), "Moved audit log to the data directory");
+        }
+        Ok(None) => {}
+        Err(e) => tracing::warn!(error = %e, "Failed to move audit log to the data directory"),
+    }
 }
 
+/// Issues with the config file and the settings of a run, all at once.
+fn run_config_issues(config: &SupervisorConfig) -> Vec<ConfigIssue> {
+    let mut issues = match ConfigLoader::new().load() {
+        Ok(policy) => policy.validate(),
+        Err(ConfigError::Invalid { issues, .. }) => issues,
+        Err(e) => vec![ConfigIssue::new("", e.to_string())],
+    };
+    let cwd = std::env::current_dir().unwrap_or_else(|_| PathBuf::from("."));
+    issues.extend(config.validate(&cwd));
+    issues
+}
+
+#[allow(clippy::too_many_lines)]
 fn build_policy_engine(config: &PolicyConfig) -> PolicyEngine {
     let mut engine = PolicyEngine::new(config.level);
+    engine.set_permission_mode_levels(config.by_permission_mode.clone());
+    engine.set_mcp_default(config.tools.mcp_default);
+    engine.set_mcp_servers(config.mcp.clone());
 
     for tool in &config.tools.allowed {
         engine.allow_tool(tool);
@@ -176,10 +699,178 @@ fn build_policy_engine(config: &PolicyConfig) -> PolicyEngine {
         engine.deny_tool(tool);
     }
 
+    engine.set_bash_allowlist(BashAllowlist::new(&config.bash.allowed_prefixes));
+    engine.set_escrow(Escrow::from_config(&config.escrow));
+    match GitGate::from_config(&config.git) {
+        Ok(gate) => engine.set_git_gate(gate),
+        Err(e) => tracing::warn!(error = %e, "Ignoring git gate with invalid protected branches"),
+    }
+    engine.set_exfiltration_guard(ExfiltrationGuard::from_config(&config.network));
+    engine.set_blocklist_levels(config.blocklist_levels.clone());
+    engine.set_secret_scanner(SecretScanner::from_config(&config.secrets));
+    engine.set_rate_limiter(RateLimiter::from_config(&config.rate_limits));
+
+    let substrings = config.bash.blocked_patterns.iter().map(|pattern| {
+        let description = format!("contains `{pattern}`");
+        let rule = BlocklistRule::substring(RuleCategory::Custom, pattern, description);
+        (pattern, rule)
+    });
+    let regexes = config.bash.blocked_regexes.iter().map(|pattern| {
+        let description = format!("matches `{pattern}`");
+        (
+            pattern,
+            BlocklistRule::regex(RuleCategory::Custom, pattern, description),
+        )
+    });
+    let entries = config.blocklist.iter().map(|entry| {
+        (
+            &entry.pattern,
+            BlocklistRule::regex(entry.category, &entry.pattern, entry.description()),
+        )
+    });
+    for (pattern, rule) in substrings.chain(regexes).chain(entries) {
+        match rule {
+            Ok(rule) => engine.block_commands(rule),
+            Err(e) => {
+                tracing::warn!(pattern = %pattern, error = %e, "Ignoring invalid blocked pattern");
+            }
+        }
+    }
+
+    for pattern in &config.tools.blocked_mcp_patterns {
+        match BlocklistRule::new(RuleCategory::Mcp, pattern, format!("matches `{pattern}`")) {
+            Ok(rule) => engine.block_mcp_tools(rule),
+            Err(e) => {
+                tracing::warn!(pattern = %pattern, error = %e, "Ignoring invalid MCP pattern");
+            }
+        }
+    }
+
+    for (pattern, action) in &config.paths {
+        match PathRule::new(pattern, *action) {
+            Ok(rule) => engine.add_path_rule(rule),
+            Err(e) => tracing::warn!(pattern = %pattern, error = %e, "Ignoring invalid path rule"),
+        }
+    }
+
+    for rule in &config.edit_rules {
+        match EditRule::from_config(rule) {
+            Ok(rule) => engine.add_edit_rule(rule),
+            Err(e) => tracing::warn!(error = %e, "Ignoring invalid edit rule"),
+        }
+    }
+
+    let mut rewriter = CommandRewriter::new();
+    for rule in &config.rewrites {
+        match RewriteRule::from_config(rule) {
+            Ok(rule) => rewriter.add_rule(rule),
+            Err(e) => tracing::warn!(error = %e, "Ignoring invalid rewrite rule"),
+        }
+    }
+    engine.set_command_rewriter(rewriter);
+
+    let mut aliases = ToolAliases::with_defaults();
+    aliases.extend(&config.tool_aliases);
+    engine.set_tool_aliases(aliases);
+
+    if config.self_protection.enabled {
+        let mut guard = engine.self_protection().clone();
+        for path in &config.self_protection.extra_paths {
+            guard.protect(path);
+        }
+        engine.set_self_protection(guard);
+    } else {
+        tracing::warn!("Self-protection disabled by global config");
+        engine.set_self_protection(SelfProtection::disabled());
+    }
+
+    engine.set_containment(Containment::from_config(&config.containment));
+
+    if std::env::var_os(NO_SANDBOX_ENV).is_some_and(|value| !value.is_empty()) {
+        if config.sandbox.wrapper.is_some() {
+            tracing::warn!("Sandbox disabled by {NO_SANDBOX_ENV}");
+        }
+    } else {
+        engine.set_sandbox(Sandbox::from_config(&config.sandbox));
+    }
+
+    if let Some(roots) = std::env::var_os(SESSION_ROOTS_ENV) {
+        engine.set_roots(std::env::split_paths(&roots).collect());
+    }
+
+    engine.set_dry_run(
+        config.dry_run
+            || std::env::var_os(POLICY_DRY_RUN_ENV).is_some_and(|value| !value.is_empty()),
+    );
+
+    if let Ok(overrides) = std::env::var(SESSION_OVERRIDES_ENV) {
+        match serde_json::from_str::<Vec<SessionOverride>>(&overrides) {
+            Ok(overrides) => {
+                for rule in overrides {
+                    engine.add_session_override(rule);
+                }
+            }
+            Err(e) => tracing::warn!(error = %e, "Ignoring invalid {SESSION_OVERRIDES_ENV}"),
+        }
+    }
+
+    engine
+}
+
+/// Engine for replayed calls, which are not made at their original pace:
+/// without rate limits.
+fn replay_policy_engine(config: &PolicyConfig) -> PolicyEngine {
+    let mut engine = build_policy_engine(config);
+    engine.set_rate_limiter(None);
+    engine
+}
+
+/// Engine for a hook whose call the supervisor evaluated: only the parts
+/// the hook acts on after the decision, without compiling any rules.
+fn delegated_policy_engine(config: &PolicyConfig) -> PolicyEngine {
+    let mut engine = PolicyEngine::with_blocklist(config.level, Blocklist::new());
+    engine.set_escrow(Escrow::from_config(&config.escrow));
     engine
 }
 
-fn handle_hook(_event: HookEvent) {
+/// How long the hook command spends reporting its decision to the supervisor.
+const HOOK_REPORT_TIMEOUT: std::time::Duration = std::time::Duration::from_secs(1);
+
+/// How long the hook command waits for the supervisor to evaluate a call
+/// before building its own policy engine.
+const HOOK_EVALUATE_TIMEOUT: std::time::Duration = std::time::Duration::from_millis(500);
+
+/// Have a running supervisor evaluate a `PreToolUse` call with its engine.
+///
+/// `None` if no supervisor answers in time; the hook then decides itself.
+async fn evaluate_with_supervisor(input: &HookInput) -> Option<PolicyDecision> {
+    if input.hook_event_name != "PreToolUse" {
+        return None;
+    }
+    let client = IpcClient::new().with_timeout(HOOK_EVALUATE_TIMEOUT);
+    if !client.is_supervisor_running() {
+        return None;
+    }
+    let request = EvaluateRequest {
+        session_id: input.session_id.clone(),
+        tool_name: input.tool_name.clone()?,
+        tool_input: input
+            .tool_input
+            .clone()
+            .unwrap_or(serde_json::Value::Object(serde_json::Map::new())),
+        cwd: input.cwd.clone(),
+        permission_mode: input.permission_mode.clone(),
+    };
+    match client.evaluate(&request).await {
+        Ok(verdict) => Some(verdict.into()),
+        Err(e) => {
+            tracing::debug!(error = %e, "Supervisor did not evaluate the call; deciding locally");
+            None
+        }
+    }
+}
+
+async fn handle_hook(_event: HookEvent) {
     // Load configuration
     let loader = ConfigLoader::new();
     let config = match loader.load() {
@@ -190,10 +881,6 @@ fn handle_hook(_event: HookEvent) {
         }
     };
 
-    // Build policy engine from config
-    let policy = build_policy_engine(&config);
-    let handler = HookHandler::new(policy);
-
     // Read JSON from stdin
     let stdin = io::stdin();
     let mut input = String::new();
@@ -207,9 +894,58 @@ fn handle_hook(_event: HookEvent) {
         }
     }
 
+    // A running supervisor's engine decides the call; otherwise build one
+    // from config
+    let hook_input = serde_json::from_str::<HookInput>(&input).ok();
+    let delegated = match hook_input {
+        Some(ref hook_input) => evaluate_with_supervisor(hook_input).await,
+        None => None,
+    };
+    let policy = match (&hook_input, &delegated) {
+        (_, Some(_)) => delegated_policy_engine(&config),
+        (Some(hook_input), None) => {
+            // Each hook runs once, so call counts are kept per session on disk
+            let mut policy = build_policy_engine(&config);
+            let path = RateLimiter::path_in(&RateLimiter::default_dir(), &hook_input.session_id);
+            let limiter = policy.rate_limiter().cloned();
+            policy.set_rate_limiter(limiter.map(|limiter| limiter.with_state_file(path)));
+            policy
+        }
+        (None, None) => build_policy_engine(&config),
+    };
+    let dry_run = policy.dry_run_log().clone();
+    let mut handler = HookHandler::new(policy)
+        .with_snapshots(config.snapshots.clone())
+        .with_additional_context(config.hook_additional_context)
+        .with_kill_switch(KillSwitch::new(kill_switch_path()))
+        .with_ipc_client(IpcClient::new().with_timeout(HOOK_REPORT_TIMEOUT));
+    if let Some(secs) = std::env::var(WRAP_UP_AT_ENV)
+        .ok()
+        .and_then(|value| value.parse::<u64>().ok())
+    {
+        handler =
+            handler.with_wrap_up_at(std::time::UNIX_EPOCH + std::time::Duration::from_secs(secs));
+    }
+    if let Some(path) = std::env::var_os(BUDGET_NOTE_ENV) {
+        handler = handler.with_budget_note(PathBuf::from(path));
+    }
+
     // Handle the hook event
-    match handler.handle_json(&input) {
+    let result = match (&hook_input, delegated) {
+        (Some(hook_input), Some(decision)) => handler.handle_decided(hook_input, decision),
+        _ => handler.handle_json(&input),
+    };
+    match result {
         Ok(result) => {
+            // Let a supervising runner know what was decided
+            if let Some(ref hook_input) = hook_input {
+                handler.report_decision(hook_input, &result).await;
+                let records = dry_run.records();
+                if !records.is_empty() {
+                    record_hook_dry_run(&config, &hook_input.session_id, &records).await;
+                }
+            }
+
             // Write response to stdout
             if let Err(e) = io::stdout().write_all(result.response.as_bytes()) {
                 eprintln!("Failed to write response: {e}");
@@ -229,6 +965,55 @@ fn handle_hook(_event: HookEvent) {
     }
 }
 
+/// Record the calls a hook allowed in a policy dry run in every configured
+/// audit sink.
+///
+/// The records of a Claude session go to one audit session, started by the
+/// first of its hooks to record a call; its ID is kept in a state file.
+async fn record_hook_dry_run(config: &PolicyConfig, session_id: &str, records: &[DryRunRecord]) {
+    let path = RateLimiter::path_in(&DryRunLog::default_dir(), session_id);
+    let started = std::fs::read(&path)
+        .ok()
+        .and_then(|bytes| serde_json::from_slice::<uuid::Uuid>(&bytes).ok());
+    let sinks = AuditSinks::open(&config.audit, None).await;
+    let audit_id = if let Some(id) = started {
+        id
+    } else {
+        let session =
+            AuditSession::new("Policy dry run in hooks").with_claude_session_id(session_id);
+        let _ = sinks
+            .write(&AuditRecord::SessionStart(session.clone()))
+            .await;
+        let saved = path
+            .parent()
+            .map_or(Ok(()), std::fs::create_dir_all)
+            .and_then(|()| std::fs::write(&path, serde_json::to_vec(&session.id)?));
+        if let Err(e) = saved {
+            tracing::warn!(path = %path.display(), error = %e, "Failed to save dry run audit session");
+        }
+        session.id
+    };
+    let mut aliases = ToolAliases::with_defaults();
+    aliases.extend(&config.tool_aliases);
+    for record in records {
+        let event = record.audit_event(audit_id, &aliases);
+        let _ = sinks.write(&AuditRecord::Event(event)).await;
+    }
+}
+
+/// Print the built-in and configured blocklist rules as TOML comments.
+fn print_effective_blocklist(blocklist: &Blocklist) {
+    println!("# Effective blocklist:");
+    for rule in blocklist.rules().iter().chain(blocklist.mcp_rules()) {
+        println!(
+            "#   [{}] {} ({})",
+            rule.category(),
+            rule.pattern(),
+            rule.description()
+        );
+    }
+}
+
 fn handle_config(action: ConfigAction) {
     match action {
         ConfigAction::Show => {
@@ -253,6 +1038,7 @@ fn handle_config(action: ConfigAction) {
                             std::process::exit(1);
                         }
                     }
+                    print_effective_blocklist(build_policy_engine(&config).blocklist());
                 }
                 Err(e) => {
                     eprintln!("Failed to load config: {e}");
@@ -260,35 +1046,196 @@ fn handle_config(action: ConfigAction) {
                 }
             }
         }
+        ConfigAction::Validate => {
+            let loader = ConfigLoader::new();
+            let Some(path) = loader.find_config_file() else {
+                println!("No config file found; the defaults are used");
+                return;
+            };
+            let (path, issues) = match loader.load() {
+                Ok(config) => (path, config.validate()),
+                Err(ConfigError::Invalid { path, issues }) => (path, issues),
+                Err(e) => {
+                    eprintln!("error: {e}");
+                    std::process::exit(1);
+                }
+            };
+            if issues.is_empty() {
+                println!("{}: valid", path.display());
+            } else {
+                eprintln!(
+                    "error: {} has {} issue(s):\n{}",
+                    path.display(),
+                    issues.len(),
+                    format_issues(&issues)
+                );
+                std::process::exit(1);
+            }
+        }
+        ConfigAction::Schema { target, format } => {
+            let output = match (target, format) {
+                (SchemaTarget::File, SchemaFormat::Json) => schema::json_schema::<PolicyConfig>()
+                    .and_then(|schema| serde_json::to_string_pretty(&schema)),
+                (SchemaTarget::Supervisor, SchemaFormat::Json) => {
+                    schema::json_schema::<SupervisorConfig>()
+                        .and_then(|schema| serde_json::to_string_pretty(&schema))
+                }
+                (SchemaTarget::File, SchemaFormat::Markdown) => schema::markdown::<PolicyConfig>(),
+                (SchemaTarget::Supervisor, SchemaFormat::Markdown) => {
+                    schema::markdown::<SupervisorConfig>()
+                }
+            };
+            match output {
+                Ok(output) => println!("{output}"),
+                Err(e) => {
+                    eprintln!("Failed to generate schema: {e}");
+                    std::process::exit(1);
+                }
+            }
+        }
     }
 }
 
-fn handle_install_hooks() {
-    let installer = match HookInstaller::from_current_exe() {
-        Ok(i) => i,
+fn handle_snapshots(action: SnapshotAction) {
+    let config = match ConfigLoader::new().load() {
+        Ok(c) => c,
         Err(e) => {
-            eprintln!("Failed to create hook installer: {e}");
+            eprintln!("Failed to load config: {e}");
             std::process::exit(1);
         }
     };
+    let cwd = std::env::current_dir().unwrap_or_else(|_| PathBuf::from("."));
+    let store = SnapshotStore::from_config(&config.snapshots, &cwd);
+    let absolute = |path: PathBuf| cwd.join(path);
 
-    match installer.install() {
-        Ok(result) => {
-            println!("Hooks installed successfully!");
-            println!("  Settings file: {}", result.settings_path.display());
-            println!(
-                "  PreToolUse: {}",
-                if result.pre_tool_use_installed {
-                    "installed"
-                } else {
-                    "skipped"
+    match action {
+        SnapshotAction::List { session, path } => {
+            match store.list(&session, path.map(absolute).as_deref()) {
+                Ok(entries) if entries.is_empty() => {
+                    println!("No snapshots for session {session}");
                 }
-            );
-            println!(
-                "  Stop: {}",
-                if result.stop_installed {
-                    "installed"
-                } else {
+                Ok(entries) => {
+                    println!("{:<34} {:<20} {:>10}  PATH", "ID", "TAKEN", "SIZE");
+                    for entry in entries {
+                        println!(
+                            "{:<34} {:<20} {:>10}  {}",
+                            entry.id,
+                            entry.created_at.format("%Y-%m-%d %H:%M:%S"),
+                            entry.size,
+                            entry.path.display()
+                        );
+                    }
+                }
+                Err(e) => {
+                    eprintln!("error: Failed to list snapshots: {e}");
+                    std::process::exit(1);
+                }
+            }
+        }
+        SnapshotAction::Restore { session, path, id } => {
+            let restored = match path.map(absolute) {
+                Some(path) => store
+                    .restore(&session, &path, id.as_deref())
+                    .map(|entry| vec![entry]),
+                None => store.restore_session(&session),
+            };
+            match restored {
+                Ok(entries) if entries.is_empty() => {
+                    println!("No snapshots for session {session}");
+                }
+                Ok(entries) => {
+                    for entry in entries {
+                        println!("Restored {} ({})", entry.path.display(), entry.id);
+                    }
+                }
+                Err(e) => {
+                    eprintln!("error: Failed to restore snapshot: {e}");
+                    std::process::exit(1);
+                }
+            }
+        }
+    }
+}
+
+fn handle_trash(action: TrashAction) {
+    let config = match ConfigLoader::new().load() {
+        Ok(c) => c,
+        Err(e) => {
+            eprintln!("Failed to load config: {e}");
+            std::process::exit(1);
+        }
+    };
+    let cwd = std::env::current_dir().unwrap_or_else(|_| PathBuf::from("."));
+    let store = TrashStore::from_config(&config.escrow, &cwd);
+
+    match action {
+        TrashAction::List { session } => match store.list(session.as_deref()) {
+            Ok(entries) if entries.is_empty() => println!("Trash is empty"),
+            Ok(entries) => {
+                println!("{:<38} {:>10}  PATH", "SESSION", "SIZE");
+                for entry in entries {
+                    println!(
+                        "{:<38} {:>10}  {}",
+                        entry.session,
+                        entry.size,
+                        entry.path.display()
+                    );
+                }
+            }
+            Err(e) => {
+                eprintln!("error: Failed to list trash: {e}");
+                std::process::exit(1);
+            }
+        },
+        TrashAction::Restore { session, path } => {
+            match store.restore(&session, path.map(|path| cwd.join(path)).as_deref()) {
+                Ok(restored) => {
+                    for path in restored {
+                        println!("Restored {}", path.display());
+                    }
+                }
+                Err(e) => {
+                    eprintln!("error: Failed to restore from trash: {e}");
+                    std::process::exit(1);
+                }
+            }
+        }
+        TrashAction::Empty { session } => match store.empty(session.as_deref()) {
+            Ok(count) => println!("Deleted {count} trashed file(s)"),
+            Err(e) => {
+                eprintln!("error: Failed to empty trash: {e}");
+                std::process::exit(1);
+            }
+        },
+    }
+}
+
+fn handle_install_hooks() {
+    let installer = match HookInstaller::from_current_exe() {
+        Ok(i) => i,
+        Err(e) => {
+            eprintln!("Failed to create hook installer: {e}");
+            std::process::exit(1);
+        }
+    };
+
+    match installer.install() {
+        Ok(result) => {
+            println!("Hooks installed successfully!");
+            println!("  Settings file: {}", result.settings_path.display());
+            println!(
+                "  PreToolUse: {}",
+                if result.pre_tool_use_installed {
+                    "installed"
+                } else {
+                    "skipped"
+                }
+            );
+            println!(
+                "  Stop: {}",
+                if result.stop_installed {
+                    "installed"
+                } else {
                     "skipped"
                 }
             );
@@ -340,6 +1287,82 @@ fn handle_uninstall_hooks() {
     }
 }
 
+fn handle_doctor() {
+    let installer = match HookInstaller::from_current_exe() {
+        Ok(i) => i,
+        Err(e) => {
+            eprintln!("Failed to create hook installer: {e}");
+            std::process::exit(1);
+        }
+    };
+    let report = match installer.report() {
+        Ok(report) => report,
+        Err(e) => {
+            eprintln!("Failed to read hooks: {e}");
+            std::process::exit(1);
+        }
+    };
+
+    println!("Settings file: {}", report.settings_path.display());
+    let mut healthy = true;
+    for (event, commands) in [("PreToolUse", &report.pre_tool_use), ("Stop", &report.stop)] {
+        match commands.len() {
+            0 => {
+                healthy = false;
+                println!("  {event}: missing");
+            }
+            1 => println!("  {event}: ok ({})", commands[0]),
+            n => {
+                healthy = false;
+                println!("  {event}: {n} supervisor entries, the hook runs {n} times per event");
+                for command in commands {
+                    println!("    {command}");
+                }
+            }
+        }
+    }
+
+    if !healthy {
+        println!("Run `claude-supervisor install-hooks` to repair.");
+    }
+
+    let kill_switch = kill_switch_path();
+    if kill_switch.exists() {
+        println!(
+            "Kill switch: engaged ({}); every session is halted until it is removed",
+            kill_switch.display()
+        );
+    }
+
+    let binary =
+        claude_binary_from_env().unwrap_or_else(|| SupervisorConfig::default().claude_binary);
+    let binary_ok = match locate_binary(&binary) {
+        Ok(path) => {
+            let version = binary_version(&path);
+            println!(
+                "Claude binary: {} ({})",
+                path.display(),
+                version.as_deref().unwrap_or("version unknown")
+            );
+            if version.as_deref().is_some_and(is_older_than_minimum) {
+                println!(
+                    "  warning: Claude Code older than {MIN_CLAUDE_CODE_VERSION} is not supported; \
+                     upgrade with `claude update`"
+                );
+            }
+            true
+        }
+        Err(e) => {
+            println!("Claude binary: {e}");
+            false
+        }
+    };
+
+    if !healthy || !binary_ok {
+        std::process::exit(1);
+    }
+}
+
 #[allow(clippy::too_many_lines)]
 async fn handle_worktree(action: WorktreeAction) {
     // Get current directory as repo root
@@ -393,9 +1416,46 @@ async fn handle_worktree(action: WorktreeAction) {
             }
         }
         WorktreeAction::Remove {
-            name,
+            group: Some(group),
+            force,
+            delete_branch,
+            ..
+        } => {
+            let registry_path = WorktreeRegistry::default_path(&manager.worktree_dir());
+            let registry = match WorktreeRegistry::load(&registry_path) {
+                Ok(r) => r,
+                Err(e) => {
+                    eprintln!("Failed to load registry: {e}");
+                    std::process::exit(1);
+                }
+            };
+
+            let Some(group) = WorktreeGroup::from_registry(
+                &registry,
+                &group,
+                manager.repo_root(),
+                manager.config(),
+            ) else {
+                eprintln!("No worktrees found in group '{group}'");
+                std::process::exit(1);
+            };
+
+            let removal = group.remove(force, delete_branch).await;
+            for wt in &removal.removed {
+                println!("Worktree '{}' removed ({}).", wt.name, wt.path.display());
+            }
+            for (wt, e) in &removal.failed {
+                eprintln!("Failed to remove worktree '{}': {e}", wt.name);
+            }
+            if !removal.failed.is_empty() {
+                std::process::exit(1);
+            }
+        }
+        WorktreeAction::Remove {
+            name: Some(name),
             force,
             delete_branch,
+            ..
         } => {
             // Load registry
             let registry_path = WorktreeRegistry::default_path(&manager.worktree_dir());
@@ -437,7 +1497,50 @@ async fn handle_worktree(action: WorktreeAction) {
                 }
             }
         }
-        WorktreeAction::Prune { hours, force } => {
+        WorktreeAction::Exec {
+            name,
+            each,
+            command,
+        } => {
+            let registry_path = WorktreeRegistry::default_path(&manager.worktree_dir());
+            let registry = match WorktreeRegistry::load(&registry_path) {
+                Ok(r) => r,
+                Err(e) => {
+                    eprintln!("Failed to load registry: {e}");
+                    std::process::exit(1);
+                }
+            };
+
+            let code = if each {
+                if registry.list().is_empty() {
+                    println!("No managed worktrees found.");
+                    return;
+                }
+                worktree::exec_each(registry.list(), &command).await
+            } else {
+                let name = name.unwrap_or_default();
+                let wt = match worktree::find_worktree(&registry, &name) {
+                    Ok(wt) => wt,
+                    Err(e) => {
+                        eprintln!("{e}");
+                        std::process::exit(1);
+                    }
+                };
+                match worktree::exec_in(wt, &command).await {
+                    Ok(code) => code,
+                    Err(e) => {
+                        eprintln!("Failed to run command in worktree '{name}': {e}");
+                        std::process::exit(worktree::SPAWN_FAILED_EXIT_CODE);
+                    }
+                }
+            };
+            std::process::exit(code);
+        }
+        WorktreeAction::Prune {
+            hours,
+            force,
+            dry_run,
+        } => {
             let registry_path = WorktreeRegistry::default_path(&manager.worktree_dir());
             let mut registry = match WorktreeRegistry::load(&registry_path) {
                 Ok(r) => r,
@@ -449,42 +1552,56 @@ async fn handle_worktree(action: WorktreeAction) {
 
             #[allow(clippy::cast_possible_wrap)]
             let max_age = chrono::Duration::hours(hours as i64);
-            let stale: Vec<_> = registry
-                .find_stale(max_age)
-                .iter()
-                .map(|wt| wt.name.clone())
-                .collect();
+            let plan = worktree::prune_plan(&registry, max_age);
 
-            if stale.is_empty() {
+            if plan.is_empty() {
                 println!("No stale worktrees found (older than {hours} hours).");
                 return;
             }
-
-            println!("Pruning {} stale worktree(s)...", stale.len());
-            for name in stale {
-                match manager.remove(&name, force).await {
-                    Ok(()) => {
-                        registry.remove(&name);
-                        println!("  Removed: {name}");
-                    }
-                    Err(e) => {
-                        eprintln!("  Failed to remove '{name}': {e}");
-                    }
+            if dry_run {
+                println!("Would prune {} stale worktree(s):", plan.len());
+                for wt in &plan {
+                    println!("  {} ({})", wt.name, wt.path.display());
                 }
+                return;
+            }
+
+            println!("Pruning {} stale worktree(s)...", plan.len());
+            let report = worktree::prune(&manager, &mut registry, &plan, force).await;
+            for name in &report.removed {
+                println!("  Removed: {name}");
+            }
+            for name in &report.skipped_dirty {
+                println!("  Skipped '{name}': uncommitted changes (use --force to remove)");
+            }
+            for (name, e) in &report.failed {
+                eprintln!("  Failed to remove '{name}': {e}");
             }
 
-            if let Err(e) = registry.save(&registry_path) {
-                eprintln!("Warning: Failed to update registry: {e}");
+            let saved = registry.save(&registry_path);
+            if let Err(ref e) = saved {
+                eprintln!("Failed to update registry: {e}");
+            }
+            if !report.is_success() || saved.is_err() {
+                std::process::exit(1);
             }
         }
+        WorktreeAction::Remove { .. } => unreachable!("clap requires a name or --group"),
     }
 }
 
+/// Handle the multi command - supervise `tasks` in parallel.
+///
+/// The sessions share one AI client whose request limit is split between
+/// them by `weights`, given in task order; tasks without one get the default
+/// weight.
 async fn handle_multi(
     tasks: Vec<String>,
     max_parallel: usize,
     policy: PolicyArg,
     _auto_continue: bool,
+    weights: Vec<u32>,
+    max_ai_requests: Option<usize>,
 ) {
     tracing::info!(
         tasks = tasks.len(),
@@ -494,13 +1611,42 @@ async fn handle_multi(
     );
 
     let policy_engine = PolicyEngine::new(policy.into());
-    let mut supervisor = MultiSessionSupervisor::new(max_parallel, policy_engine);
+    let mut supervisor = MultiSessionSupervisor::new(max_parallel, policy_engine)
+        .with_kill_switch(KillSwitch::new(kill_switch_path()));
+
+    // One client for every session, so they take fair shares of one limit
+    let ai_config = match ConfigLoader::new().load() {
+        Ok(config) => config.ai,
+        Err(e) => {
+            tracing::warn!(error = %e, "Failed to load config; using the default AI settings");
+            AiConfig::default()
+        }
+    };
+    let limit = max_ai_requests
+        .or(ai_config.max_concurrent_requests)
+        .unwrap_or(max_parallel);
+    match AiClient::from_config(ai_config) {
+        Ok(client) => {
+            println!("AI requests: at most {limit} in flight, shared by session weight");
+            supervisor =
+                supervisor.with_ai_client(client.with_max_concurrency(limit).with_fair_share());
+        }
+        Err(e) => {
+            tracing::warn!(error = %e, "AI supervision disabled for all sessions");
+        }
+    }
 
     // Spawn all sessions
-    for task in &tasks {
-        match supervisor.spawn_session(task.clone()).await {
+    let weights = weights
+        .into_iter()
+        .chain(std::iter::repeat(DEFAULT_SESSION_WEIGHT));
+    for (task, weight) in tasks.iter().zip(weights) {
+        match supervisor
+            .spawn_weighted_session(task.clone(), weight)
+            .await
+        {
             Ok(id) => {
-                tracing::info!(session_id = %id, task = %task, "Session spawned");
+                tracing::info!(session_id = %id, task = %task, weight, "Session spawned");
             }
             Err(e) => {
                 tracing::error!(task = %task, error = %e, "Failed to spawn session");
@@ -517,10 +1663,18 @@ async fn handle_multi(
 
     for result in &results {
         let status = match &result.result {
+            Ok(SupervisorResult::Killed {
+                reason,
+                cause,
+                retry_hint,
+            }) => format!("Killed [{cause}]: {reason} ({retry_hint})"),
             Ok(r) => format!("{r:?}"),
             Err(e) => format!("Error: {e}"),
         };
         println!("  [{}] {} - {}", result.id, result.task, status);
+        if let Some(ref version) = result.claude_code_version {
+            println!("      Claude Code {version}");
+        }
     }
 
     let stats = supervisor.stats();
@@ -530,135 +1684,1928 @@ async fn handle_multi(
     println!("  Tool calls: {}", stats.total_tool_calls);
     println!("  Approvals: {}", stats.total_approvals);
     println!("  Denials: {}", stats.total_denials);
+    print_decision_sources(&stats.by_source, "  ");
+    let mut kills: Vec<_> = stats.kills_by_cause.iter().collect();
+    kills.sort_by_key(|(cause, _)| cause.as_str());
+    for (cause, count) in kills {
+        println!("  Killed ({cause}): {count}");
+    }
+    if let Some(ai) = supervisor.ai_stats() {
+        println!("  AI requests: {} ({} failed)", ai.requests, ai.errors);
+    }
 }
 
-/// Handle the run command - spawn and supervise Claude Code.
-async fn handle_run(
-    task: Option<String>,
-    resume: Option<String>,
-    config: SupervisorConfig,
-) -> Result<(), Box<dyn std::error::Error>> {
-    // Handle worktree isolation if enabled
-    let (working_dir, worktree_cleanup_info) = if config.worktree.enabled {
-        tracing::info!("Creating isolated worktree for task");
-        let repo_root = std::env::current_dir()?;
-        let manager = WorktreeManager::new(repo_root, config.worktree.clone())?;
-        let task_name = task.as_deref().unwrap_or("supervised-task").to_string();
-        let worktree = manager.create(&task_name).await?;
-        let path = worktree.path.clone();
-        tracing::info!(path = %path.display(), "Running in worktree");
-        (Some(path), Some((manager, task_name)))
-    } else {
-        (None, None)
-    };
-
-    // Get prompt (task or "continue" for resume)
-    let prompt = task.unwrap_or_else(|| "continue".to_string());
-
-    // Build process
-    let mut builder = ClaudeProcessBuilder::new(&prompt);
-
-    // Add resume if provided
-    if let Some(ref session_id) = resume {
-        builder = builder.resume(session_id);
+/// Print who made the decisions, if any were made, indented by `indent`.
+fn print_decision_sources(by_source: &DecisionBreakdown, indent: &str) {
+    let lines = by_source.summary_lines();
+    if lines.is_empty() {
+        return;
     }
+    println!("{indent}Decisions by source:");
+    for line in lines {
+        println!("{indent}  {line}");
+    }
+}
 
-    // Add allowed tools if configured
-    if !config.allowed_tools.is_empty() {
-        let tools: Vec<&str> = config.allowed_tools.iter().map(String::as_str).collect();
-        builder = builder.allowed_tools(&tools);
+/// Final summary of a run, as Markdown for its artifact bundle.
+fn run_summary(
+    session_name: &str,
+    result: &SupervisorResult,
+    exit_code: i32,
+    stats: &SessionStats,
+) -> String {
+    use std::fmt::Write as _;
+
+    let mut out = format!("# Run summary: {session_name}\n\n");
+    let _ = writeln!(out, "- Outcome: {}", result.outcome());
+    if let SupervisorResult::Killed { reason, cause, .. } = result {
+        let _ = writeln!(out, "- Reason: [{cause}] {reason}");
+    }
+    if let SupervisorResult::Completed {
+        cost_usd: Some(cost),
+        ..
+    }
+    | SupervisorResult::ContextExhausted {
+        cost_usd: Some(cost),
+        ..
+    } = result
+    {
+        let _ = writeln!(out, "- Cost: ${cost:.4}");
+    }
+    let _ = writeln!(out, "- Exit code: {exit_code}");
+    let _ = writeln!(
+        out,
+        "- Tool calls: {} ({} approved, {} denied, {} escalated)",
+        stats.tool_calls, stats.approvals, stats.denials, stats.escalations
+    );
+    let _ = writeln!(out, "- Turns: {}", stats.turns);
+    if stats.tool_errors > 0 {
+        let _ = writeln!(out, "- Tool errors: {}", stats.tool_errors);
+    }
+    let lines = stats.by_source.summary_lines();
+    if !lines.is_empty() {
+        out.push_str("\n## Decisions by source\n\n");
+        for line in lines {
+            let _ = writeln!(out, "- {line}");
+        }
     }
+    out
+}
 
-    // Set working directory if using worktree
-    if let Some(ref dir) = working_dir {
-        builder = builder.working_dir(dir);
+/// Print how many calls a policy dry run allowed that the policy would
+/// have denied or escalated, by rule.
+fn print_dry_run_summary(log: &DryRunLog) {
+    let totals = log.totals();
+    println!(
+        "Policy dry run: {} calls would have been denied, {} escalated",
+        totals.denied, totals.escalated
+    );
+    for (rule, counts) in log.by_rule() {
+        println!(
+            "  {rule}: {} denied, {} escalated",
+            counts.denied, counts.escalated
+        );
     }
+}
 
-    tracing::info!("Spawning Claude Code process");
-    let process = ClaudeProcess::spawn(&builder)?;
+/// Print a cost attribution table for one dimension.
+#[allow(clippy::cast_precision_loss)]
+fn print_cost_shares(title: &str, shares: &[CostShare]) {
+    println!("\n=== {title} ===");
+    if shares.is_empty() {
+        println!("  (no attributed cost recorded)");
+        return;
+    }
+    let total: u64 = shares.iter().map(|s| s.cost_micros).sum();
+    for share in shares {
+        let percent = if total == 0 {
+            0.0
+        } else {
+            share.cost_micros as f64 * 100.0 / total as f64
+        };
+        println!(
+            "  {:<40} {:>5} calls  ${:>9.4}  {:>5.1}%",
+            share.key,
+            share.calls,
+            share.cost_usd(),
+            percent
+        );
+    }
+}
 
-    // Build policy engine
-    let mut policy = PolicyEngine::new(config.policy);
-    for tool in &config.allowed_tools {
-        policy.allow_tool(tool);
+/// Load tool call decisions recorded in the audit log since `since`.
+async fn audit_calls(
+    since: chrono::DateTime<chrono::Utc>,
+) -> Result<Vec<SimulatedCall>, Box<dyn std::error::Error>> {
+    let path = default_audit_path();
+    if !path.exists() {
+        return Ok(Vec::new());
     }
+    let events = AuditLog::open(&path)
+        .await?
+        .get_tool_decisions_since(since)
+        .await?;
+    Ok(events
+        .into_iter()
+        .filter_map(|event| {
+            Some(SimulatedCall {
+                tool_name: event.tool_name?,
+                tool_input: event.tool_input.unwrap_or_else(|| serde_json::json!({})),
+                original: event.decision?,
+            })
+        })
+        .collect())
+}
 
-    // Create supervisor (with or without AI)
-    let mut supervisor = if config.ai_supervisor {
-        tracing::info!("AI supervision enabled");
-        let ai_client = AiClient::from_env()?;
+/// Load tool calls from a session transcript.
+///
+/// Every call in a transcript was executed, so each counts as allowed.
+async fn transcript_calls(
+    path: &std::path::Path,
+) -> Result<Vec<SimulatedCall>, Box<dyn std::error::Error>> {
+    let entries = parse_jsonl_file(path).await?;
+    let mut reconstructor = SessionReconstructor::new();
+    reconstructor.process_entries(&entries);
+    let pending = reconstructor.pending_tool_calls();
+    Ok(reconstructor
+        .tool_calls()
+        .iter()
+        .chain(pending)
+        .map(|record| SimulatedCall {
+            tool_name: record.tool_name.clone(),
+            tool_input: record.input.clone(),
+            original: Decision::Allow,
+        })
+        .collect())
+}
 
-        // Test AI provider connectivity before starting
-        let provider_name = format!("{:?}", ai_client.provider_kind());
-        let model = ai_client.model().to_string();
-        match ai_client.test_connection().await {
-            Ok(()) => {
-                display::print_connection_test(&provider_name, &model, true);
-            }
-            Err(e) => {
-                display::print_connection_test(&provider_name, &model, false);
-                return Err(format!("AI provider connection failed: {e}").into());
+fn print_simulation_report(source: &str, config: &std::path::Path, report: &SimulationReport) {
+    println!(
+        "Simulated {} tool calls from {source} against {}",
+        report.total,
+        config.display()
+    );
+    println!("  Unchanged: {}", report.unchanged);
+    println!("  Changed: {}", report.changed());
+
+    if !report.changes.is_empty() {
+        println!("\n=== Changed Decisions ===");
+        println!("  {:<22} {:>5}  RULE", "CHANGE", "COUNT");
+        for change in &report.changes {
+            let label = format!("{} -> {}", change.from.as_str(), change.to.as_str());
+            println!("  {label:<22} {:>5}  {}", change.count, change.rule);
+            for example in &change.examples {
+                println!("      {} {}", example.tool_name, example.tool_input);
             }
         }
+    }
 
-        Supervisor::from_process_with_ai(process, policy, ai_client)?
-    } else {
-        Supervisor::from_process(process, policy)?
-    };
+    println!("\n=== Decisions by Rule ===");
+    for (rule, count) in &report.rule_counts {
+        println!("  {rule:<40} {count:>5}");
+    }
+}
 
-    // Set task context
-    supervisor.set_task(&prompt);
+/// Handle policy subcommands.
+async fn handle_policy(action: PolicyAction) {
+    match action {
+        PolicyAction::Simulate {
+            config,
+            since,
+            transcript,
+            json,
+        } => {
+            if !config.exists() {
+                eprintln!("error: Config file not found: {}", config.display());
+                std::process::exit(1);
+            }
+            let candidate = match ConfigLoader::with_path(config.clone()).load() {
+                Ok(c) => c,
+                Err(e) => {
+                    eprintln!("error: Failed to load config: {e}");
+                    std::process::exit(1);
+                }
+            };
+            let engine = replay_policy_engine(&candidate);
 
-    // Initialize knowledge from working directory (worktree or current)
-    let cwd = std::env::current_dir()?;
-    let knowledge_dir = working_dir.clone().unwrap_or_else(|| cwd.clone());
-    supervisor.init_knowledge(&knowledge_dir).await;
+            let (source, calls) = match transcript {
+                Some(ref path) => (path.display().to_string(), transcript_calls(path).await),
+                None => ("the audit log".to_string(), audit_calls(since).await),
+            };
+            let calls = match calls {
+                Ok(calls) => calls,
+                Err(e) => {
+                    eprintln!("error: Failed to load tool calls: {e}");
+                    std::process::exit(1);
+                }
+            };
 
-    // Run supervision loop
+            let report = simulate(&calls, &engine);
+            if json {
+                match serde_json::to_string_pretty(&report) {
+                    Ok(json) => println!("{json}"),
+                    Err(e) => {
+                        eprintln!("error: Failed to serialize report: {e}");
+                        std::process::exit(1);
+                    }
+                }
+            } else {
+                print_simulation_report(&source, &config, &report);
+            }
+        }
+        PolicyAction::Test { file, config, json } => {
+            let loader = match config {
+                Some(path) if !path.exists() => {
+                    eprintln!("error: Config file not found: {}", path.display());
+                    std::process::exit(1);
+                }
+                Some(path) => ConfigLoader::with_path(path),
+                None => ConfigLoader::new(),
+            };
+            let config = match loader.load() {
+                Ok(c) => c,
+                Err(e) => {
+                    eprintln!("error: Failed to load config: {e}");
+                    std::process::exit(1);
+                }
+            };
+            let cases = match PolicyCaseFile::load(&file) {
+                Ok(cases) => cases.cases,
+                Err(e) => {
+                    eprintln!("error: {}: {e}", file.display());
+                    std::process::exit(1);
+                }
+            };
+
+            let report = run_policy_cases(&cases, &replay_policy_engine(&config));
+            if json {
+                match serde_json::to_string_pretty(&report) {
+                    Ok(json) => println!("{json}"),
+                    Err(e) => {
+                        eprintln!("error: Failed to serialize report: {e}");
+                        std::process::exit(1);
+                    }
+                }
+            } else {
+                print_policy_case_report(&report);
+            }
+            if !report.all_passed() {
+                std::process::exit(1);
+            }
+        }
+    }
+}
+
+fn print_policy_case_report(report: &PolicyCaseReport) {
+    println!(
+        "  {:<6} {:<15} {:<15} {:<24} RULE",
+        "RESULT", "EXPECTED", "ACTUAL", "CASE"
+    );
+    for result in &report.results {
+        let status = if result.passed() { "PASS" } else { "FAIL" };
+        println!(
+            "  {status:<6} {:<15} {:<15} {:<24} {}",
+            result.case.expect.as_str(),
+            result.actual.as_str(),
+            result.case.label(),
+            result.rule
+        );
+    }
+    println!(
+        "\n{} passed, {} failed",
+        report.results.len() - report.failures(),
+        report.failures()
+    );
+}
+
+/// Handle audit subcommands.
+/// Find the transcript of a session given its ID, name or path.
+async fn resolve_review_transcript(target: &str, cwd: &Path) -> Option<PathBuf> {
+    let path = Path::new(target);
+    if path.is_file() {
+        return Some(path.to_path_buf());
+    }
+    let session_id = match AuditLog::open(default_audit_path()).await {
+        Ok(audit) => audit
+            .resolve_resume_target(target)
+            .await
+            .unwrap_or_else(|_| target.to_string()),
+        Err(_) => target.to_string(),
+    };
+    find_session_by_id(&find_project_sessions_dir(cwd)?, &session_id)
+}
+
+async fn handle_ai(action: AiAction) {
+    let AiAction::Models = action;
+    let client = match AiClient::from_env() {
+        Ok(client) => client,
+        Err(e) => {
+            eprintln!("error: AI client unavailable: {e}");
+            std::process::exit(1);
+        }
+    };
+    let models = match client.list_models().await {
+        Ok(models) => models,
+        Err(e) => {
+            eprintln!("error: Failed to list models: {e}");
+            std::process::exit(1);
+        }
+    };
+
+    println!("{:?} models:", client.provider_kind());
+    for model in &models {
+        let marker = if client.model_matches(model) {
+            "*"
+        } else {
+            " "
+        };
+        println!("{marker} {model}");
+    }
+    if !models.iter().any(|model| client.model_matches(model)) {
+        eprintln!(
+            "warning: Configured model '{}' is not in the list",
+            client.model()
+        );
+    }
+}
+
+async fn handle_export(action: ExportAction) {
+    let ExportAction::Transcript { session, out } = action;
+    let path = default_audit_path();
+    let audit = match AuditLog::open(&path).await {
+        Ok(audit) => audit,
+        Err(e) => {
+            eprintln!("error: Failed to open audit log: {e}");
+            std::process::exit(1);
+        }
+    };
+    let audit_session = match audit.find_session(&session).await {
+        Ok(Some(audit_session)) => audit_session,
+        Ok(None) => {
+            eprintln!("error: No session found for {session}");
+            std::process::exit(1);
+        }
+        Err(e) => {
+            eprintln!("error: Failed to read audit log: {e}");
+            std::process::exit(1);
+        }
+    };
+
+    let mirrored = default_transcript_dir().join(format!("{}.jsonl", audit_session.id));
+    if mirrored.exists() {
+        if let Err(e) = std::fs::copy(&mirrored, &out) {
+            eprintln!("error: Failed to write {}: {e}", out.display());
+            std::process::exit(1);
+        }
+        println!("Exported transcript to {}", out.display());
+        return;
+    }
+
+    let mut events = match audit.get_events(audit_session.id, usize::MAX).await {
+        Ok(events) => events,
+        Err(e) => {
+            eprintln!("error: Failed to read audit log: {e}");
+            std::process::exit(1);
+        }
+    };
+    events.reverse();
+    let history = audit_session
+        .claude_session_id
+        .as_deref()
+        .and_then(|id| {
+            ResumeContext::load(&ResumeContext::default_dir(), id)
+                .ok()
+                .flatten()
+        })
+        .map(|context| context.events)
+        .unwrap_or_default();
+    let entries = reconstruct(&audit_session, &events, &history);
+    if let Err(e) = write_transcript(&out, &entries) {
+        eprintln!("error: {e}");
+        std::process::exit(1);
+    }
+    println!(
+        "Exported transcript to {} (reconstructed: the session was not mirrored, so only {})",
+        out.display(),
+        if history.is_empty() {
+            "the task and audited tool calls are included"
+        } else {
+            "the most recent events of the saved context are included"
+        }
+    );
+}
+
+async fn handle_review(target: &str, out: Option<PathBuf>, learn: bool) {
+    let cwd = std::env::current_dir().unwrap_or_else(|_| PathBuf::from("."));
+    let Some(path) = resolve_review_transcript(target, &cwd).await else {
+        eprintln!("error: No transcript found for session '{target}'");
+        std::process::exit(1);
+    };
+    let entries = match parse_jsonl_file(&path).await {
+        Ok(entries) => entries,
+        Err(e) => {
+            eprintln!("error: Failed to read {}: {e}", path.display());
+            std::process::exit(1);
+        }
+    };
+    let events = transcript_events(&entries);
+    if events.is_empty() {
+        eprintln!("error: Transcript {} has no activity", path.display());
+        std::process::exit(1);
+    }
+    let task = transcript_task(&entries).unwrap_or_else(|| "(unknown task)".to_string());
+
+    let client = match AiClient::from_env() {
+        Ok(client) => client,
+        Err(e) => {
+            eprintln!("error: AI client unavailable: {e}");
+            std::process::exit(1);
+        }
+    };
+    let critique = match SessionReviewer::new(client).review(&task, &events).await {
+        Ok(critique) => critique,
+        Err(e) => {
+            eprintln!("error: Review failed: {e}");
+            std::process::exit(1);
+        }
+    };
+
+    let markdown = critique.to_markdown(target);
+    match out {
+        Some(out) => {
+            if let Err(e) = std::fs::write(&out, &markdown) {
+                eprintln!("error: Failed to write {}: {e}", out.display());
+                std::process::exit(1);
+            }
+            eprintln!("Wrote review to {}", out.display());
+        }
+        None => print!("{markdown}"),
+    }
+
+    if learn && !critique.claude_md_additions.is_empty() {
+        let mut memory = MemorySource::load(&cwd).await;
+        for addition in &critique.claude_md_additions {
+            memory.add_fact(
+                format!("What does the project expect about {}?", addition.topic),
+                addition.text.clone(),
+            );
+        }
+        if let Err(e) = memory.save().await {
+            eprintln!("error: Failed to save memory: {e}");
+            std::process::exit(1);
+        }
+        eprintln!(
+            "Added {} suggestion(s) to project memory",
+            critique.claude_md_additions.len()
+        );
+    }
+}
+
+#[allow(clippy::too_many_lines)]
+async fn handle_audit(action: AuditAction) {
+    match action {
+        AuditAction::Stats {
+            by_tool,
+            by_file,
+            session,
+        } => {
+            let path = default_audit_path();
+            if !path.exists() {
+                println!("No audit log found at {}", path.display());
+                return;
+            }
+            let audit = match AuditLog::open(&path).await {
+                Ok(audit) => audit,
+                Err(e) => {
+                    eprintln!("error: Failed to open audit log: {e}");
+                    std::process::exit(1);
+                }
+            };
+
+            let counts = async {
+                Ok::<_, claude_supervisor::audit::AuditError>((
+                    audit.count_events().await?,
+                    audit.count_by_decision(Decision::Allow).await?,
+                    audit.count_by_decision(Decision::Deny).await?,
+                    audit.count_by_decision(Decision::Escalate).await?,
+                    audit.get_decision_sources(session).await?,
+                ))
+            }
+            .await;
+            match counts {
+                Ok((total, allowed, denied, escalated, by_source)) => {
+                    println!("Audit log: {}", path.display());
+                    println!("  Events: {total}");
+                    println!("  Allowed: {allowed}");
+                    println!("  Denied: {denied}");
+                    println!("  Escalated: {escalated}");
+                    print_decision_sources(&by_source, "  ");
+                }
+                Err(e) => {
+                    eprintln!("error: Failed to query audit log: {e}");
+                    std::process::exit(1);
+                }
+            }
+
+            let dimensions = [
+                (by_tool, CostDimension::Tool, "Approximate Cost by Tool"),
+                (by_file, CostDimension::File, "Approximate Cost by File"),
+            ];
+            for (enabled, dimension, title) in dimensions {
+                if !enabled {
+                    continue;
+                }
+                match audit.get_cost_shares(dimension, session).await {
+                    Ok(shares) => print_cost_shares(title, &shares),
+                    Err(e) => {
+                        eprintln!("error: Failed to query cost attribution: {e}");
+                        std::process::exit(1);
+                    }
+                }
+            }
+        }
+        AuditAction::Sessions { limit } => {
+            let path = default_audit_path();
+            if !path.exists() {
+                println!("No audit log found at {}", path.display());
+                return;
+            }
+            let sessions = match AuditLog::open(&path).await {
+                Ok(audit) => audit.recent_sessions(limit).await,
+                Err(e) => Err(e),
+            };
+            let sessions = match sessions {
+                Ok(sessions) => sessions,
+                Err(e) => {
+                    eprintln!("error: Failed to query audit log: {e}");
+                    std::process::exit(1);
+                }
+            };
+            if sessions.is_empty() {
+                println!("No sessions recorded.");
+                return;
+            }
+            println!(
+                "{:<20} {:<24} {:<12} CLAUDE CODE",
+                "STARTED", "NAME", "RESULT"
+            );
+            for session in sessions {
+                println!(
+                    "{:<20} {:<24} {:<12} {}",
+                    session.started_at.format("%Y-%m-%d %H:%M:%S"),
+                    session.name.as_deref().unwrap_or("-"),
+                    session.result.as_deref().unwrap_or("running"),
+                    session.claude_code_version.as_deref().unwrap_or("-"),
+                );
+            }
+        }
+        AuditAction::RuleStats { since, json } => {
+            let path = default_audit_path();
+            if !path.exists() {
+                println!("No audit log found at {}", path.display());
+                return;
+            }
+            let report = match AuditLog::open(&path).await {
+                Ok(audit) => audit.rule_stats(since).await,
+                Err(e) => Err(e),
+            };
+            let report = match report {
+                Ok(report) => report,
+                Err(e) => {
+                    eprintln!("error: Failed to query audit log: {e}");
+                    std::process::exit(1);
+                }
+            };
+            if json {
+                match serde_json::to_string_pretty(&report) {
+                    Ok(json) => println!("{json}"),
+                    Err(e) => {
+                        eprintln!("error: Failed to serialize report: {e}");
+                        std::process::exit(1);
+                    }
+                }
+                return;
+            }
+            if report.rules.is_empty() {
+                println!(
+                    "No rule firings recorded since {}.",
+                    report.since.format("%Y-%m-%d %H:%M")
+                );
+                return;
+            }
+            print_rule_stats("RULE", &report.rules);
+            println!();
+            print_rule_stats("TOOL", &report.tools);
+        }
+    }
+}
+
+/// Print rule firing outcomes as a table keyed by `heading`.
+fn print_rule_stats(heading: &str, stats: &[RuleStats]) {
+    println!(
+        "{heading:<32} {:>6} {:>6} {:>9} {:>13} {:>16} {:>8}  SUGGESTION",
+        "FIRED", "DENIED", "ESCALATED", "AI ALLOW/DENY", "HUMAN ALLOW/DENY", "SESSIONS"
+    );
+    for rule in stats {
+        println!(
+            "{:<32} {:>6} {:>6} {:>9} {:>13} {:>16} {:>8}  {}",
+            rule.key,
+            rule.fired,
+            rule.denied,
+            rule.escalated,
+            format!("{}/{}", rule.ai_allowed, rule.ai_denied),
+            format!("{}/{}", rule.human_allowed, rule.human_denied),
+            rule.sessions,
+            rule.suggestion.as_deref().unwrap_or("-"),
+        );
+    }
+}
+
+/// Record a finished session, its rule firings, its hung and mismatched
+/// tool calls and its cost attribution in every configured audit sink.
+///
+/// Audit failures are logged as warnings and never fail the run.
+#[allow(clippy::too_many_lines)]
+async fn record_session_audit(
+    session: &AuditSession,
+    result: &SupervisorResult,
+    supervisor: &Supervisor,
+    audit: &AuditConfig,
+    redactor: Option<&Redactor>,
+) -> Vec<AuditEvent> {
+    let breakdown = supervisor.cost_breakdown();
+    let blast_radius = supervisor.blast_radius();
+    let outcome = match result {
+        SupervisorResult::Completed { .. } => "completed".to_string(),
+        SupervisorResult::Killed { reason, cause, .. } => format!("killed [{cause}]: {reason}"),
+        SupervisorResult::ProcessExited => "process_exited".to_string(),
+        SupervisorResult::Cancelled => "cancelled".to_string(),
+        SupervisorResult::StartupFailed { hint } => format!("startup_failed: {hint}"),
+        SupervisorResult::TimedOut { limit, .. } => {
+            format!("timed_out after {}m", limit.as_secs() / 60)
+        }
+        SupervisorResult::ContextExhausted { .. } => "context_exhausted".to_string(),
+        SupervisorResult::Halted { .. } => KILL_SWITCH_REASON.to_string(),
+        SupervisorResult::HandedOff { .. } => "handed_off".to_string(),
+    };
+
+    let mut metrics = SessionMetrics::new(session.id);
+    metrics.add_tokens(breakdown.input_tokens, breakdown.output_tokens);
+    metrics.estimated_cost_cents = breakdown.total_cost_micros.div_ceil(10_000);
+    metrics.record_blast_radius(blast_radius.peak(), blast_radius.config().escalate_at);
+    metrics.decision_sources = supervisor.stats().by_source;
+
+    let aliases = &supervisor.policy().tool_aliases().clone();
+    let fired = supervisor.rule_firings().iter().map(|firing| {
+        let mut event = AuditEvent::builder(session.id, EventType::PolicyDecision)
+            .timestamp(firing.fired_at)
+            .called_tool(&firing.tool_name, aliases)
+            .tool_input(firing.tool_input.clone())
+            .decision(firing.decision)
+            .reason(&firing.reason)
+            .rule(&firing.rule);
+        if let Some(source) = firing.decided_by {
+            event = event.decided_by(source);
+        }
+        event.build()
+    });
+    let hung = supervisor.hung_tools().iter().map(|hung| {
+        AuditEvent::builder(session.id, EventType::Error)
+            .called_tool(&hung.tool_name, aliases)
+            .tool_input(hung.input.clone())
+            .reason(hung.describe())
+            .build()
+    });
+    let mismatched = supervisor.tool_mismatches().iter().map(|mismatch| {
+        AuditEvent::builder(session.id, EventType::Error)
+            .called_tool(&mismatch.tool_name, aliases)
+            .tool_input(mismatch.input.clone())
+            .reason(mismatch.describe())
+            .build()
+    });
+    let snapshotted = supervisor.snapshots().iter().map(|(tool_use, entry)| {
+        AuditEvent::builder(session.id, EventType::PolicyDecision)
+            .timestamp(entry.created_at)
+            .called_tool(&tool_use.name, aliases)
+            .tool_input(tool_use.input.clone())
+            .decision(Decision::Allow)
+            .reason(format!("Snapshot taken of {}", entry.path.display()))
+            .snapshot_id(&entry.id)
+            .build()
+    });
+    let recoveries = supervisor.context_recoveries().iter().map(|recovery| {
+        AuditEvent::builder(session.id, EventType::Error)
+            .timestamp(recovery.started_at)
+            .reason(recovery.describe())
+            .build()
+    });
+    let nudges = supervisor.idle_nudges().iter().map(|nudge| {
+        AuditEvent::builder(session.id, EventType::Error)
+            .timestamp(nudge.sent_at)
+            .reason(nudge.describe())
+            .build()
+    });
+    let dry_run = supervisor.policy().dry_run_log().records();
+    let dry_run = dry_run
+        .iter()
+        .map(|record| record.audit_event(session.id, aliases));
+    let completion = supervisor.completion_assessment().map(|assessment| {
+        let mut event =
+            AuditEvent::builder(session.id, EventType::SessionEnd).reason(assessment.describe());
+        if let Ok(value) = serde_json::to_value(assessment) {
+            event = event.tool_input(value);
+        }
+        event.build()
+    });
+    let events = fired
+        .chain(hung)
+        .chain(mismatched)
+        .chain(snapshotted)
+        .chain(recoveries)
+        .chain(nudges)
+        .chain(dry_run)
+        .chain(completion)
+        .collect::<Vec<_>>();
+    let records = std::iter::once(AuditRecord::SessionStart(session.clone()))
+        .chain(events.iter().cloned().map(AuditRecord::Event))
+        .chain([
+            AuditRecord::session_end(session.id, outcome),
+            AuditRecord::Metrics(metrics),
+            AuditRecord::CostBreakdown {
+                session_id: session.id,
+                breakdown,
+            },
+        ]);
+
+    // Each failing sink is reported as it fails; keep writing to the others
+    let sinks = AuditSinks::open(audit, redactor).await;
+    for record in records {
+        let _ = sinks.write(&record).await;
+    }
+    events
+}
+
+/// Write suggested CLAUDE.md additions for the session's friction next to
+/// its run manifest.
+async fn write_claude_md_suggestions(
+    session_name: &str,
+    events: &[AuditEvent],
+    config: &SuggestionConfig,
+    redactor: Option<&Redactor>,
+) {
+    let mut suggestions = suggest_claude_md_additions(events, config.max_suggestions);
+    if suggestions.is_empty() {
+        return;
+    }
+    if config.ai_polish {
+        let polished = match AiClient::from_env() {
+            Ok(client) => SessionReviewer::new(client).polish(&suggestions).await,
+            Err(e) => Err(e),
+        };
+        match polished {
+            Ok(polished) => suggestions = polished,
+            Err(e) => tracing::warn!(error = %e, "Failed to polish CLAUDE.md suggestions"),
+        }
+    }
+
+    let markdown = suggestions_markdown(session_name, &suggestions);
+    let markdown = redactor.map_or(Cow::Borrowed(markdown.as_str()), |redactor| {
+        redactor.redact(&markdown)
+    });
+    let dir = default_manifest_dir().join(session_name);
+    let path = dir.join(SUGGESTIONS_FILE);
+    match std::fs::create_dir_all(&dir).and_then(|()| std::fs::write(&path, markdown.as_bytes())) {
+        Ok(()) => tracing::info!(
+            path = %path.display(),
+            count = suggestions.len(),
+            "Suggested CLAUDE.md additions"
+        ),
+        Err(e) => tracing::warn!(error = %e, "Failed to write CLAUDE.md suggestions"),
+    }
+}
+
+/// Pick the session name: the requested one, or a generated slug, made
+/// unique among the names already used today.
+async fn assign_session_name(audit: Option<&AuditLog>, requested: Option<String>) -> String {
+    let base = requested.unwrap_or_else(generate_session_name);
+    let Some(audit) = audit else {
+        return base;
+    };
+    match audit
+        .session_names_on(chrono::Utc::now().date_naive())
+        .await
+    {
+        Ok(taken) => unique_session_name(&base, &taken),
+        Err(e) => {
+            tracing::warn!(error = %e, "Failed to look up today's session names");
+            base
+        }
+    }
+}
+
+/// Label for a repository in a multi-repo session: its directory name.
+fn repo_label(repo: &Path) -> String {
+    repo.file_name().map_or_else(
+        || repo.display().to_string(),
+        |name| name.to_string_lossy().into_owned(),
+    )
+}
+
+/// Context preamble telling Claude where the other repositories of the session are.
+fn multi_repo_preamble(roots: &[(String, PathBuf)]) -> String {
+    use std::fmt::Write as _;
+
+    let mut preamble = String::from(
+        "This session spans several repositories. \
+         Work may involve any of them; the first is the working directory.\n",
+    );
+    for (index, (label, root)) in roots.iter().enumerate() {
+        let role = if index == 0 {
+            " (working directory)"
+        } else {
+            ""
+        };
+        let _ = writeln!(preamble, "- {label}: {}{role}", root.display());
+    }
+    preamble
+}
+
+/// What a run was set up with, beyond its configuration.
+struct RunSetup<'a> {
+    session_name: &'a str,
+    prompt: &'a str,
+    config_snapshot: &'a serde_json::Value,
+    ai: Option<(String, String)>,
+    worktree: Option<PathBuf>,
+}
+
+/// Describe the setup a run starts with, with secrets redacted.
+fn run_manifest(
+    config: &SupervisorConfig,
+    supervisor: &Supervisor,
+    setup: RunSetup<'_>,
+    redactor: &Redactor,
+) -> RunManifest {
+    let sorted = |tools: &HashSet<String>| {
+        let mut tools: Vec<_> = tools.iter().cloned().collect();
+        tools.sort_unstable();
+        tools
+    };
+    let escalation = supervisor.decision_backend_name();
+    let blast_radius = config.blast_radius.enabled.then_some(&config.blast_radius);
+
+    let mut config_hashes = BTreeMap::new();
+    if let Ok(json) = serde_json::to_vec(config) {
+        config_hashes.insert("supervisor".to_string(), config_hash(&json));
+    }
+    if let Some(content) = ConfigLoader::new()
+        .find_config_file()
+        .and_then(|path| std::fs::read(path).ok())
+    {
+        config_hashes.insert("policy_file".to_string(), config_hash(&content));
+    }
+    if let Some(Ok(json)) = supervisor.project_policy().map(serde_json::to_vec) {
+        config_hashes.insert("project_policy".to_string(), config_hash(&json));
+    }
+
+    let (ai_provider, ai_model) = setup.ai.unzip();
+    let policy = supervisor.policy();
+    RunManifest {
+        session_name: setup.session_name.to_string(),
+        started_at: chrono::Utc::now(),
+        task: redactor.redact(setup.prompt).into_owned(),
+        policy: policy.level(),
+        allowed_tools: sorted(policy.allowed_tools()),
+        denied_tools: sorted(policy.denied_tools()),
+        escalation: escalation.to_string(),
+        ai_provider,
+        ai_model,
+        worktree: setup.worktree,
+        repos: config.repos.clone(),
+        audit_db: default_audit_path(),
+        limits: RunLimits {
+            max_duration_mins: config.max_duration_mins,
+            startup_timeout_secs: config.startup_timeout_secs,
+            blast_radius_escalate_at: blast_radius.map(|config| config.escalate_at),
+            blast_radius_deny_at: blast_radius.map(|config| config.deny_at),
+            max_output_bytes: config.max_output_bytes,
+        },
+        config: redact_config(setup.config_snapshot, Some(redactor)),
+        config_hashes,
+    }
+}
+
+/// Write the run manifest to the data dir and, if any, the worktree.
+fn write_run_manifest(manifest: &RunManifest, worktree: Option<&Path>) {
+    let dirs = std::iter::once(default_manifest_dir().join(&manifest.session_name))
+        .chain(worktree.map(Path::to_path_buf));
+    for dir in dirs {
+        match manifest.write(&dir) {
+            Ok(path) => tracing::debug!(path = %path.display(), "Wrote run manifest"),
+            Err(e) => tracing::warn!(error = %e, "Failed to write run manifest"),
+        }
+    }
+}
+
+/// Audit sinks of the global configuration.
+fn load_audit_config() -> AuditConfig {
+    match ConfigLoader::new().load() {
+        Ok(global) => global.audit,
+        Err(e) => {
+            tracing::warn!(error = %e, "Failed to load audit sinks");
+            AuditConfig::default()
+        }
+    }
+}
+
+/// Audit log to name sessions with, if the `SQLite` sink is enabled.
+async fn open_naming_audit(audit_config: &AuditConfig) -> Option<AuditLog> {
+    if !audit_config.sqlite_enabled() {
+        return None;
+    }
+    match AuditLog::open(default_audit_path()).await {
+        Ok(audit) => Some(audit),
+        Err(e) => {
+            tracing::warn!(error = %e, "Failed to open audit log for session naming");
+            None
+        }
+    }
+}
+
+/// Start the session in the background and report where to find it.
+async fn handle_detach(name: Option<String>) -> Result<(), Box<dyn std::error::Error>> {
+    let audit = open_naming_audit(&load_audit_config()).await;
+    let base = assign_session_name(audit.as_ref(), name).await;
+    drop(audit);
+    // A running detached session is only in the audit log once it ends
+    let running: Vec<String> = DetachedSession::list_in(&detached_dir(), &sockets_dir())
+        .into_iter()
+        .filter(DetachedSession::is_running)
+        .map(|session| session.name().to_string())
+        .collect();
+    let session = DetachedSession::new(unique_session_name(&base, &running));
+
+    let mut child = session.spawn(std::env::args_os().skip(1))?;
+    session
+        .wait_for_startup(&mut child, DETACH_STARTUP_TIMEOUT)
+        .await?;
+    println!("Session {} detached (pid {})", session.name(), child.id());
+    println!("Log: {}", session.log_path().display());
+    println!(
+        "Attach with: claude-supervisor attach --session {}",
+        session.name()
+    );
+    Ok(())
+}
+
+/// Bytes of earlier output `attach` shows before following the log.
+const ATTACH_BACKLOG_BYTES: u64 = 4096;
+
+/// How often `attach` checks the log for new output.
+const ATTACH_POLL_INTERVAL: std::time::Duration = std::time::Duration::from_millis(250);
+
+/// Print a detached session's status as reported over its socket.
+fn print_session_status(status: &SupervisorStatus, health: Option<&HealthReport>) {
+    println!(
+        "Session {}: {} ({} tool calls, {} approved, {} denied)",
+        status.name.as_deref().unwrap_or("(unnamed)"),
+        status.state,
+        status.tool_calls,
+        status.approvals,
+        status.denials
+    );
+    if let Some(ref task) = status.task {
+        println!("  Task: {task}");
+    }
+    if let Some(health) = health {
+        if health.healthy {
+            println!("  Health: ok");
+        }
+        for check in health.failing() {
+            println!(
+                "  Health: {} failing: {}",
+                check.name,
+                check.detail.as_deref().unwrap_or("no detail")
+            );
+        }
+    }
+}
+
+/// Send `request` to a detached session and report its answer.
+async fn send_control(client: &IpcClient, request: ControlRequest) {
+    match client.control(request).await {
+        Ok(ControlResponse::Status { status, health }) => {
+            print_session_status(&status, health.as_ref());
+        }
+        Ok(ControlResponse::Accepted) => println!("{request:?} requested"),
+        Ok(ControlResponse::Rejected { reason }) => eprintln!("error: {reason}"),
+        Err(e) => eprintln!("error: Failed to reach the session: {e}"),
+    }
+}
+
+/// Follow a detached session's log and pass `stop`, `continue` and `status`
+/// typed on stdin to it, until the session ends or stdin closes.
+async fn handle_attach(session: Option<&str>) {
+    use tokio::io::{AsyncBufReadExt, BufReader};
+
+    let session = match DetachedSession::find_running(session) {
+        Ok(session) => session,
+        Err(e) => {
+            eprintln!("error: {e}");
+            std::process::exit(1);
+        }
+    };
+    let client = IpcClient::with_path(session.socket_path());
+    send_control(&client, ControlRequest::Status).await;
+    println!("Type stop, continue or status; end input (Ctrl-D) to detach.");
+
+    let mut log = LogTail::new(session.log_path(), ATTACH_BACKLOG_BYTES);
+    let mut input = BufReader::new(tokio::io::stdin()).lines();
+    let mut poll = tokio::time::interval(ATTACH_POLL_INTERVAL);
+    loop {
+        tokio::select! {
+            _ = poll.tick() => {
+                // Checked first so that the last output is read after the end
+                let running = session.is_running();
+                match log.read_new() {
+                    Ok(output) if !output.is_empty() => {
+                        print!("{output}");
+                        let _ = io::stdout().flush();
+                    }
+                    Ok(_) => {}
+                    Err(e) => tracing::warn!(error = %e, "Failed to read session log"),
+                }
+                if !running {
+                    println!("Session {} has ended", session.name());
+                    return;
+                }
+            }
+            line = input.next_line() => {
+                let Ok(Some(line)) = line else {
+                    println!("Detached from session {}; it keeps running", session.name());
+                    return;
+                };
+                let request = match line.trim() {
+                    "" => continue,
+                    "stop" => ControlRequest::Stop,
+                    "continue" => ControlRequest::Continue,
+                    "status" => ControlRequest::Status,
+                    other => {
+                        eprintln!("Unknown command '{other}'; use stop, continue or status");
+                        continue;
+                    }
+                };
+                send_control(&client, request).await;
+            }
+        }
+    }
+}
+
+/// How long a handed-off supervisor waits for hook connections to finish.
+const HANDOFF_DRAIN_TIMEOUT: std::time::Duration = std::time::Duration::from_secs(30);
+
+/// Take the session over from the supervisor serving the hooks of `session`,
+/// or the socket in the environment, and follow it until Claude exits.
+///
+/// Hooks are served the engine they would build from the configuration with
+/// the session overrides given to the old supervisor's `run`. The carried
+/// budget, the time limit and the kill switch stay enforced; returns the exit
+/// code `run` would exit with for how the session ended.
+async fn handle_upgrade_handoff(
+    session: Option<String>,
+) -> Result<i32, Box<dyn std::error::Error>> {
+    let socket = session.map_or_else(default_socket_path, |name| session_socket_path(&name));
+    let snapshot = IpcClient::with_path(&socket).request_handoff().await?;
+    println!(
+        "Adopting the session of supervisor {} (pid {})",
+        snapshot.version, snapshot.supervisor_pid
+    );
+    for session in &snapshot.sessions {
+        println!("  Session: {}", session.session_id);
+    }
+    if !snapshot.pending_approvals.is_empty() {
+        println!(
+            "  Escalations the old supervisor decides before exiting: {}",
+            snapshot.pending_approvals.len()
+        );
+    }
+    if let Some(ref budget) = snapshot.budget {
+        println!(
+            "  Budget: ${:.2} of ${:.2} spent",
+            budget.spent_usd, budget.limit_usd
+        );
+    }
+    if let Some(time_box) = snapshot.time_box {
+        println!(
+            "  Time limit: {}s, until {}",
+            time_box.limit_secs, time_box.expires_at
+        );
+    }
+    for rule in &snapshot.overrides {
+        println!("  Session override: {rule}");
+    }
+
+    // The old supervisor no longer accepts connections; serve them at once
+    let handoff = HandoffState::from_snapshot(&snapshot);
+    let mut server = IpcServer::new(&socket).with_handoff(handoff.clone());
+    match ConfigLoader::new().load() {
+        Ok(config) => {
+            let mut engine = build_policy_engine(&config);
+            for rule in &snapshot.overrides {
+                engine.add_session_override(rule.clone());
+            }
+            server = server.with_policy(std::sync::Arc::new(std::sync::Mutex::new(engine)));
+        }
+        Err(e) => {
+            tracing::warn!(error = %e, "Failed to load config; hooks will decide on their own");
+        }
+    }
+    let _server = server.start(|_| async {
+        EscalationResponse::Deny {
+            reason: "This supervisor only accepts evaluations and decision reports".to_string(),
+        }
+    })?;
+
+    let mut adopted = AdoptedSession::from_snapshot(&snapshot)
+        .with_kill_switch(KillSwitch::new(kill_switch_path()))
+        .with_handoff(handoff.clone());
+    let Some(child_pid) = adopted.child_pid() else {
+        println!("The session has no running Claude process to follow");
+        return Ok(0);
+    };
+    println!("Following Claude (pid {child_pid})");
+
+    // Stop following on Ctrl-C, or once the session is handed off again
+    let cancel = CancellationToken::new();
+    let stop = cancel.clone();
+    tokio::spawn(async move {
+        tokio::select! {
+            _ = tokio::signal::ctrl_c() => {}
+            () = handoff.handed_off() => {}
+        }
+        stop.cancel();
+    });
+    let end = adopted
+        .follow(ADOPT_POLL_INTERVAL, &cancel, |call| {
+            tracing::info!(tool = %call.tool_name, id = %call.tool_use_id, "Tool call completed");
+        })
+        .await;
+    let outcome = match end {
+        AdoptionEnd::ChildExited => "Claude exited".to_string(),
+        AdoptionEnd::Cancelled => "Stopped following".to_string(),
+        AdoptionEnd::Killed { ref reason, .. } => format!("Session killed: {reason}"),
+        AdoptionEnd::TimedOut { limit } => {
+            format!("Session timed out after {}s", limit.as_secs())
+        }
+        AdoptionEnd::Halted => "Kill switch engaged; session halted".to_string(),
+    };
+    println!(
+        "{outcome}; {} tool calls since the handoff",
+        adopted.tool_calls().len()
+    );
+    Ok(end.exit_code())
+}
+
+/// End the session as a cancellation does when the terminal hangs up.
+///
+/// A detached session has no terminal and ignores the signal.
+#[cfg(unix)]
+fn watch_hangup(cancel: CancellationToken, detached: bool) {
+    use tokio::signal::unix::{signal, SignalKind};
+
+    let mut hangups = match signal(SignalKind::hangup()) {
+        Ok(hangups) => hangups,
+        Err(e) => {
+            tracing::warn!(error = %e, "Failed to watch for SIGHUP");
+            return;
+        }
+    };
+    tokio::spawn(async move {
+        while hangups.recv().await.is_some() {
+            if detached {
+                tracing::info!("Ignoring SIGHUP in detached session");
+                continue;
+            }
+            tracing::warn!("Terminal hung up; stopping the session");
+            // Printing to the closed terminal would fail from here on
+            if let Err(e) = claude_supervisor::supervisor::silence_terminal() {
+                tracing::warn!(error = %e, "Failed to detach output from the terminal");
+            }
+            cancel.cancel();
+            break;
+        }
+    });
+}
+
+/// Handle the run command - spawn and supervise Claude Code.
+///
+/// Returns the process exit code: 0 unless the session was killed, in which
+/// case the code identifies the kill cause. A detached session runs as
+/// `detached`, answering control requests on its socket.
+#[allow(clippy::too_many_lines)]
+async fn handle_run(
+    task: Option<String>,
+    resume: Option<String>,
+    name: Option<String>,
+    config: SupervisorConfig,
+    overrides: Vec<SessionOverride>,
+    scenario: Option<PathBuf>,
+    detached: Option<DetachedSession>,
+) -> Result<i32, Box<dyn std::error::Error>> {
+    let audit_config = load_audit_config();
+
+    // Name the session and resolve a resumed session name via the audit log;
+    // a detached session was named before it started
+    let audit = open_naming_audit(&audit_config).await;
+    let session_name = match detached {
+        Some(ref session) => session.name().to_string(),
+        None => assign_session_name(audit.as_ref(), name).await,
+    };
+    let resume = match (resume, audit.as_ref()) {
+        (Some(target), Some(audit)) => match audit.resolve_resume_target(&target).await {
+            Ok(session_id) => Some(session_id),
+            Err(e) => {
+                tracing::warn!(error = %e, "Failed to resolve resumed session name");
+                Some(target)
+            }
+        },
+        (resume, _) => resume,
+    };
+    drop(audit);
+    tracing::info!(name = %session_name, "Session named");
+
+    // Handle worktree isolation if enabled
+    let mut worktree_group = None;
+    let (working_dir, worktree_cleanup_info) = if config.worktree.enabled && config.repos.len() > 1
+    {
+        tracing::info!(
+            repos = config.repos.len(),
+            "Creating linked worktrees for task"
+        );
+        let task_name = task.as_deref().unwrap_or("supervised-task").to_string();
+        let mut group = WorktreeGroup::create(&config.repos, &task_name, &config.worktree).await?;
+        if let Err(e) = group.set_session_name(&session_name) {
+            tracing::warn!(error = %e, "Failed to record session name in worktree registry");
+        }
+        tracing::info!(group = %group.id(), "Running in worktree group");
+        let path = group.paths().into_iter().next();
+        worktree_group = Some(group);
+        (path, None)
+    } else if config.worktree.enabled {
+        tracing::info!("Creating isolated worktree for task");
+        let repo_root = match config.repos.first() {
+            Some(repo) => repo.clone(),
+            None => std::env::current_dir()?,
+        };
+        let manager = WorktreeManager::new(repo_root, config.worktree.clone())?;
+        let task_name = task.as_deref().unwrap_or("supervised-task").to_string();
+        let worktree = manager
+            .create(&task_name)
+            .await?
+            .with_session_name(&session_name);
+        let path = worktree.path.clone();
+        let registry_path = WorktreeRegistry::default_path(&manager.worktree_dir());
+        let registered = WorktreeRegistry::load(&registry_path).and_then(|mut registry| {
+            registry.upsert(worktree);
+            registry.save(&registry_path)
+        });
+        if let Err(e) = registered {
+            tracing::warn!(error = %e, "Failed to record session name in worktree registry");
+        }
+        tracing::info!(path = %path.display(), "Running in worktree");
+        (Some(path), Some((manager, task_name)))
+    } else {
+        (config.repos.first().cloned(), None)
+    };
+
+    // Roots of a multi-repo session, labelled by repository name
+    let session_roots: Vec<(String, PathBuf)> = match worktree_group {
+        Some(ref group) => config
+            .repos
+            .iter()
+            .zip(group.paths())
+            .map(|(repo, path)| (repo_label(repo), path))
+            .collect(),
+        None => config
+            .repos
+            .iter()
+            .map(|repo| (repo_label(repo), repo.clone()))
+            .collect(),
+    };
+
+    // Get prompt (task or "continue" for resume)
+    let prompt = task.unwrap_or_else(|| "continue".to_string());
+
+    // Build process
+    let mut builder = ClaudeProcessBuilder::new(&prompt).claude_binary(&config.claude_binary);
+
+    // Add allowed tools if configured
+    if !config.allowed_tools.is_empty() {
+        let tools: Vec<&str> = config.allowed_tools.iter().map(String::as_str).collect();
+        builder = builder.allowed_tools(&tools);
+    }
+
+    // Set working directory if using worktree
+    if let Some(ref dir) = working_dir {
+        builder = builder.working_dir(dir);
+    }
+
+    // Expose the other repositories of a multi-repo session
+    if session_roots.len() > 1 {
+        for (_, root) in session_roots.iter().skip(1) {
+            builder = builder.add_dir(root);
+        }
+        builder = builder.append_system_prompt(multi_repo_preamble(&session_roots));
+        let roots = std::env::join_paths(session_roots.iter().map(|(_, root)| root))?;
+        builder = builder.env(SESSION_ROOTS_ENV, roots.to_string_lossy());
+    }
+
+    if let Some(ref scenario) = scenario {
+        builder = builder.scenario(scenario);
+    }
+
+    // Hooks inherit the environment, so this disables their sandbox too
+    if config.no_sandbox {
+        builder = builder.env(NO_SANDBOX_ENV, "1");
+    }
+    if config.policy_dry_run {
+        builder = builder.env(POLICY_DRY_RUN_ENV, "1");
+    }
+
+    // Hooks reach this session's supervisor on a socket of its own
+    let hook_socket = session_socket_path(&session_name);
+    builder = builder.env(SOCKET_ENV, hook_socket.to_string_lossy());
+
+    // Session overrides reach hooks the same way and are never saved
+    if !overrides.is_empty() {
+        builder = builder.env(SESSION_OVERRIDES_ENV, serde_json::to_string(&overrides)?);
+    }
+
+    // The wrap-up warning reaches Claude through the Stop hook, which only
+    // knows when it is due; the clock is estimated from spawn time
+    let time_box = config.max_duration_mins.map(|mins| {
+        TimeBox::new(std::time::Duration::from_secs(mins * 60))
+            .with_pause_stops_clock(config.pause_stops_clock)
+    });
+    if let Some(ref time_box) = time_box {
+        let wrap_up_at = std::time::SystemTime::now() + time_box.warning_after();
+        let secs = wrap_up_at
+            .duration_since(std::time::UNIX_EPOCH)
+            .unwrap_or_default()
+            .as_secs();
+        builder = builder.env(WRAP_UP_AT_ENV, secs.to_string());
+    }
+
+    // Budget notes reach Claude through the PreToolUse hook, which picks
+    // them up from a file
+    let budget = CostBudget::from_config(&config.budget).map(|budget| {
+        let alerts = BudgetAlerts::from_config(&config.budget, budget_note_path(&session_name));
+        (budget, alerts)
+    });
+    if let Some(path) = budget.as_ref().and_then(|(_, alerts)| alerts.note_path()) {
+        // A note left by an earlier run is stale
+        let _ = std::fs::remove_file(path);
+        builder = builder.env(BUDGET_NOTE_ENV, path.to_string_lossy());
+    }
+
+    // Sessions out of context are continued with the same settings
+    let respawn_builder = builder.clone();
+    if let Some(ref session_id) = resume {
+        builder = builder.resume(session_id);
+    }
+
+    tracing::info!("Spawning Claude Code process");
+    let process = ClaudeProcess::spawn(&builder)?;
+    // Fallback for when the stream never reports a version in its init event
+    let version_probe = locate_binary(&config.claude_binary).ok().map(|path| {
+        tokio::spawn(probe_binary_version(
+            path,
+            std::time::Duration::from_secs(2),
+        ))
+    });
+
+    // Build policy engine: the configured rules at the level given to `run`.
+    // The session's hooks are served this engine, so they decide as the
+    // runner does
+    let global = ConfigLoader::new().load();
+    let mut policy = match global {
+        Ok(ref global) => build_policy_engine(&PolicyConfig {
+            level: config.policy,
+            ..global.clone()
+        }),
+        Err(_) => PolicyEngine::new(config.policy),
+    };
+    policy.set_dry_run(policy.is_dry_run() || config.policy_dry_run);
+    if config.no_sandbox {
+        policy.set_sandbox(None);
+    }
+    for tool in &config.allowed_tools {
+        policy.allow_tool(tool);
+    }
+    if session_roots.len() > 1 {
+        policy.set_roots(session_roots.iter().map(|(_, root)| root.clone()).collect());
+    }
+    for rule in &overrides {
+        tracing::warn!(rule = %rule, "Session override active for this session only");
+        policy.add_session_override(rule.clone());
+    }
+    let (novel_binaries, suggestion_config) = match global {
+        Ok(global) => {
+            policy.set_escalation_sampler(EscalationSampler::from_config(
+                &global.escalation_sampling,
+            ));
+            (global.novel_binaries, global.suggestions)
+        }
+        Err(e) => {
+            tracing::warn!(error = %e, "Failed to load the policy configuration");
+            // History stays protected without a configuration
+            policy.set_git_gate(GitGate::from_config(&GitConfig::default()).ok().flatten());
+            policy
+                .set_exfiltration_guard(ExfiltrationGuard::from_config(&NetworkConfig::default()));
+            (NovelBinaryConfig::default(), SuggestionConfig::default())
+        }
+    };
+
+    if policy.is_dry_run() {
+        display::print_warning(
+            "Policy dry run: calls the policy would deny or escalate are allowed and recorded",
+        );
+    }
+
+    // Create supervisor (webhook, AI or none)
+    let mut ai_summary = None;
+    let mut health = HealthMonitor::new();
+    if audit_config.sqlite_enabled() {
+        health = health.with_audit_path(default_audit_path());
+    }
+    let interactive = config.escalation.interactive.enabled && io::stdin().is_terminal();
+    if config.escalation.interactive.enabled && !interactive {
+        tracing::warn!("stdin is not a terminal; ignoring --interactive-approvals");
+    }
+    let supervisor = if interactive {
+        tracing::info!("Interactive approvals enabled");
+        let mut supervisor = Supervisor::from_process(process, policy)?;
+        supervisor.set_decision_backend(InteractiveApprover::stdio(&config.escalation.interactive));
+        supervisor
+    } else if config.escalation.backend == DecisionBackendKind::Webhook {
+        tracing::info!(url = %config.escalation.webhook.url, "Webhook escalation enabled");
+        let webhook = WebhookBackend::from_config(&config.escalation.webhook)?;
+        let mut supervisor = Supervisor::from_process(process, policy)?;
+        supervisor.set_decision_backend(webhook);
+        supervisor
+    } else if config.ai_supervisor {
+        tracing::info!("AI supervision enabled");
+        let ai_client = AiClient::from_env()?;
+
+        // Catch a mistyped model now rather than at the first escalation
+        let provider_name = format!("{:?}", ai_client.provider_kind());
+        let model = ai_client.model().to_string();
+        match ai_client.validate_model().await {
+            Ok(()) => {
+                display::print_connection_test(&provider_name, &model, true);
+            }
+            Err(e) => {
+                display::print_connection_test(&provider_name, &model, false);
+                if config.strict_startup {
+                    return Err(format!("AI model validation failed: {e}").into());
+                }
+                tracing::warn!(error = %e, "AI model validation failed");
+                display::print_warning(&format!(
+                    "AI model validation failed, escalations may fail: {e}"
+                ));
+            }
+        }
+        ai_summary = Some((provider_name, model));
+        health = health.with_ai_client(ai_client.clone());
+
+        Supervisor::from_process_with_ai(process, policy, ai_client)?
+    } else {
+        Supervisor::from_process(process, policy)?
+    };
+
+    // Closing the terminal stops the session the way a cancellation does
+    let cancel = CancellationToken::new();
+    #[cfg(unix)]
+    watch_hangup(cancel.clone(), detached.is_some());
+    let mut supervisor = supervisor.with_cancellation(cancel);
+
+    supervisor.set_on_ai_failure(config.escalation.on_ai_failure);
+    supervisor.set_health_monitor(health);
+    // Installed hooks have their calls evaluated by the runner's engine,
+    // served here, and report their decisions over IPC for the runner to
+    // honor. A newer binary can take the session over through the same socket
+    let handoff = HandoffState::new();
+    handoff.set_overrides(overrides.clone());
+    supervisor.set_handoff(handoff.clone());
+    let mut hook_server = IpcServer::new(&hook_socket)
+        .with_handoff(handoff)
+        .with_policy(supervisor.shared_policy());
+    if config.escalation.decision_authority != DecisionAuthority::Runner {
+        let log = HookDecisionLog::new();
+        hook_server = hook_server.with_decision_log(log.clone());
+        supervisor.set_decision_authority(config.escalation.decision_authority);
+        supervisor.set_hook_decisions(
+            log,
+            std::time::Duration::from_millis(config.escalation.hook_decision_wait_ms),
+        );
+    }
+    let hook_server = hook_server.start(|_| async {
+        EscalationResponse::Deny {
+            reason: "This supervisor only accepts evaluations and decision reports".to_string(),
+        }
+    })?;
+    supervisor.set_tool_timeouts(config.tool_timeouts.clone());
+    supervisor.set_tool_errors(&config.tool_errors);
+    supervisor.set_idle_nudge(&config.idle_nudge);
+    supervisor.set_blast_radius(config.blast_radius.clone());
+    supervisor.set_risk(config.risk.clone());
+    if let Some(time_box) = time_box {
+        supervisor.set_time_box(time_box);
+    }
+    if let Some((budget, alerts)) = budget {
+        supervisor.set_budget(budget, alerts);
+    }
+    supervisor.set_kill_switch(KillSwitch::new(kill_switch_path()));
+    supervisor.set_completion_detector(CompletionDetector::from_config(&config.stop));
+    supervisor.set_startup_timeout(std::time::Duration::from_secs(config.startup_timeout_secs));
+    supervisor.set_knowledge_timeout(std::time::Duration::from_secs(
+        config.knowledge_timeout_secs,
+    ));
+    supervisor.set_history(config.history.clone());
+    supervisor.set_snapshots(config.snapshots.clone());
+    supervisor.set_novel_binaries(&novel_binaries);
+    supervisor.set_transcript_merge(config.merge_transcript);
+    supervisor.set_display_options(DisplayOptions {
+        raw_mode: config.raw_mode,
+        max_output_bytes: config.max_output_bytes,
+        show_thinking: config.show_thinking,
+        spinner: io::stderr().is_terminal(),
+    });
+
+    // Dashboard clients get the event fields the configuration publishes,
+    // redacted unless full fidelity is allowed on this host
+    let dashboard_server = match config.dashboard.listen_address() {
+        Some((host, port)) => {
+            let scrubber = EventScrubber::from_config(&config.redaction, &config.dashboard, host)?;
+            Some((
+                DashboardConfig {
+                    host: host.to_string(),
+                    port,
+                    ..Default::default()
+                },
+                scrubber,
+            ))
+        }
+        None => None,
+    };
+
+    // A detached session is controlled over its own socket, through the
+    // channels the dashboard uses
+    let dashboard = (detached.is_some() || dashboard_server.is_some()).then(|| {
+        let (dashboard, handles) = create_dashboard_channels();
+        supervisor.set_dashboard_commands(handles.command_rx);
+        supervisor.set_dashboard_status(handles.status_tx);
+        supervisor.set_dashboard_events(handles.event_tx);
+        dashboard
+    });
+    let _control_server = match (&detached, &dashboard) {
+        (Some(session), Some(dashboard)) => {
+            let control =
+                SessionControl::new(dashboard.status_rx.clone(), dashboard.command_tx.clone())
+                    .with_health(supervisor.health_monitor().clone());
+            let server = IpcServer::new(session.socket_path())
+                .with_control(control)
+                .start(|_| async {
+                    EscalationResponse::Deny {
+                        reason: "This socket only accepts control requests".to_string(),
+                    }
+                })?;
+            Some(server)
+        }
+        _ => None,
+    };
+    let dashboard_cancel = match (dashboard, dashboard_server) {
+        (Some(dashboard), Some((server_config, scrubber))) => {
+            let cancel = dashboard.cancel.clone();
+            let server = DashboardServer::new(dashboard, None)
+                .with_health(supervisor.health_monitor().clone())
+                .with_scrubber(scrubber)
+                .with_config(server_config);
+            println!("Dashboard: http://{}", server.address());
+            tokio::spawn(async move {
+                if let Err(e) = server.run().await {
+                    tracing::warn!(error = %e, "Dashboard server failed");
+                }
+            });
+            Some(cancel)
+        }
+        _ => None,
+    };
+
+    // Set task context, continuing a resumed session's supervisor context
+    supervisor.set_task(&prompt);
+    supervisor.set_name(&session_name);
+    if let Some(ref session_id) = resume {
+        match ResumeContext::load(&ResumeContext::default_dir(), session_id) {
+            Ok(Some(context)) => supervisor.restore_context(context),
+            Ok(None) => tracing::debug!(session_id = %session_id, "No saved supervisor context"),
+            Err(e) => tracing::warn!(error = %e, "Failed to load supervisor context"),
+        }
+    }
+    let redactor = Redactor::from_config(&config.redaction)?;
+    supervisor.set_redactor(redactor.clone());
+
+    // Initialize knowledge from every root, or the working directory (worktree or current)
+    if session_roots.len() > 1 {
+        supervisor.init_knowledge_roots(&session_roots).await;
+    } else {
+        let cwd = std::env::current_dir()?;
+        let knowledge_dir = working_dir.clone().unwrap_or(cwd);
+        supervisor.init_knowledge(&knowledge_dir).await;
+    }
+
+    // Run supervision loop
     tracing::info!("Starting supervision loop");
-    let result = supervisor.run().await?;
+    let mut allowed_tools: Vec<_> = config.allowed_tools.iter().collect();
+    allowed_tools.sort_unstable();
+    let config_snapshot = serde_json::json!({
+        "policy": config.policy,
+        "allowed_tools": allowed_tools,
+        "project_policy": supervisor.project_policy(),
+        "session_overrides": overrides,
+    });
+    let mut audit_session = AuditSession::new(&prompt)
+        .with_name(&session_name)
+        .with_config(config_snapshot.clone());
+    let worktree_path = working_dir.clone().filter(|_| config.worktree.enabled);
+    let manifest = run_manifest(
+        &config,
+        &supervisor,
+        RunSetup {
+            session_name: &session_name,
+            prompt: &prompt,
+            config_snapshot: &config_snapshot,
+            ai: ai_summary,
+            worktree: worktree_path.clone(),
+        },
+        &redactor,
+    );
+    display::print_run_banner(&manifest.banner(), config.raw_mode);
+    write_run_manifest(&manifest, worktree_path.as_deref());
+    if config.history.mirror {
+        let path = default_transcript_dir().join(format!("{}.jsonl", audit_session.id));
+        match TranscriptMirror::create(&path, audit_session.id.to_string()) {
+            Ok(mut mirror) => {
+                if let Err(e) = mirror.write_prompt(&prompt) {
+                    tracing::warn!(error = %e, "Failed to write transcript prompt");
+                }
+                supervisor.set_transcript_mirror(mirror);
+            }
+            Err(e) => tracing::warn!(error = %e, "Failed to create transcript mirror"),
+        }
+    }
+    if config.history.dead_letters {
+        let path = default_dead_letter_dir().join(format!("{}.jsonl", audit_session.id));
+        match DeadLetterLog::create(&path) {
+            Ok(log) => supervisor.set_dead_letter_log(log),
+            Err(e) => tracing::warn!(error = %e, "Failed to create dead-letter log"),
+        }
+    }
+    // The pidfile tells `run --detach` that the session has started
+    if let Some(ref session) = detached {
+        session.write_pid(std::process::id())?;
+    }
+    let result = supervisor
+        .run_with_context_recovery(&config.context_recovery, |plan| {
+            let builder = match plan {
+                RecoveryPlan::Resume { session_id } => respawn_builder
+                    .clone()
+                    .with_prompt("continue")
+                    .resume(session_id),
+                RecoveryPlan::Fresh { prompt } => respawn_builder.clone().with_prompt(prompt),
+                RecoveryPlan::Nudge {
+                    session_id,
+                    message,
+                } => respawn_builder
+                    .clone()
+                    .with_prompt(message)
+                    .resume(session_id),
+            };
+            ClaudeProcess::spawn(&builder)
+        })
+        .await;
+    if let Some(ref session) = detached {
+        session.remove_pid();
+    }
+    if let Some(cancel) = dashboard_cancel {
+        cancel.cancel();
+    }
+    let result = result?;
+    if let Some(probe) = version_probe {
+        if supervisor.claude_code_version().is_none() {
+            if let Ok(Some(version)) = probe.await {
+                supervisor.set_claude_code_version(version);
+            }
+        } else {
+            probe.abort();
+        }
+    }
+    if let Some(version) = supervisor.claude_code_version() {
+        audit_session = audit_session.with_claude_code_version(version);
+    }
+    if let Some(mode) = supervisor.permission_mode() {
+        audit_session = audit_session.with_permission_mode(mode);
+    }
+    if let Some(session_id) = supervisor.session_id().or(resume.as_deref()) {
+        audit_session = audit_session.with_claude_session_id(session_id);
+    }
+    if let Some(context) = supervisor.resume_context() {
+        if let Err(e) = context.save(&ResumeContext::default_dir()) {
+            tracing::warn!(error = %e, "Failed to save supervisor context");
+        }
+    }
+    let audit_redactor = config.redaction.redacts_audit().then_some(&redactor);
+    let events = record_session_audit(
+        &audit_session,
+        &result,
+        &supervisor,
+        &audit_config,
+        audit_redactor,
+    )
+    .await;
+    if suggestion_config.enabled {
+        write_claude_md_suggestions(&session_name, &events, &suggestion_config, audit_redactor)
+            .await;
+    }
 
     // Report result
-    match result {
+    let mut exit_code = 0;
+    let handed_off = matches!(result, SupervisorResult::HandedOff { .. });
+    match &result {
         SupervisorResult::Completed {
             session_id,
             cost_usd,
         } => {
             tracing::info!(
+                name = %session_name,
                 session_id = ?session_id,
                 cost_usd = ?cost_usd,
                 "Session completed successfully"
             );
         }
-        SupervisorResult::Killed { reason } => {
-            tracing::warn!(reason = %reason, "Session killed by supervisor");
+        SupervisorResult::Killed {
+            reason,
+            cause,
+            retry_hint,
+        } => {
+            tracing::warn!(
+                name = %session_name,
+                reason = %reason,
+                cause = %cause,
+                retry_hint = %retry_hint,
+                "Session killed by supervisor"
+            );
+            exit_code = cause.exit_code();
         }
         SupervisorResult::ProcessExited => {
-            tracing::info!("Claude process exited");
+            tracing::info!(name = %session_name, "Claude process exited");
         }
         SupervisorResult::Cancelled => {
-            tracing::info!("Session cancelled");
+            tracing::info!(name = %session_name, "Session cancelled");
+        }
+        SupervisorResult::StartupFailed { hint } => {
+            eprintln!("error: {hint}");
+            exit_code = 1;
+        }
+        SupervisorResult::TimedOut { session_id, limit } => {
+            tracing::warn!(
+                name = %session_name,
+                session_id = ?session_id,
+                limit_mins = limit.as_secs() / 60,
+                "Session reached its time limit"
+            );
+            exit_code = TIMED_OUT_EXIT_CODE;
+        }
+        SupervisorResult::ContextExhausted { session_id, .. } => {
+            tracing::warn!(
+                name = %session_name,
+                session_id = ?session_id,
+                recoveries = supervisor.context_recoveries().len(),
+                "Session ran out of context"
+            );
+            eprintln!(
+                "error: Claude ran out of context; continue with `run --resume {session_name}` \
+                 or pass --context-recovery"
+            );
+            exit_code = CONTEXT_EXHAUSTED_EXIT_CODE;
+        }
+        SupervisorResult::Halted { session_id } => {
+            tracing::warn!(
+                name = %session_name,
+                session_id = ?session_id,
+                "Session halted by the kill switch"
+            );
+            eprintln!(
+                "error: {KILL_SWITCH_REASON}; remove {} to run sessions again",
+                kill_switch_path().display()
+            );
+            exit_code = HALTED_EXIT_CODE;
+        }
+        SupervisorResult::HandedOff { session_id } => {
+            tracing::info!(
+                name = %session_name,
+                session_id = ?session_id,
+                "Session handed off to a new supervisor"
+            );
+            // Hooks connected before the handoff still get their answers
+            if tokio::time::timeout(HANDOFF_DRAIN_TIMEOUT, hook_server.drained())
+                .await
+                .is_err()
+            {
+                tracing::warn!("Hook connections still open after the handoff; exiting anyway");
+            }
+            println!(
+                "Session handed off; Claude keeps running under the new supervisor, \
+                 this process reads its output until it exits"
+            );
+            let events = supervisor.drain_handed_off().await;
+            println!("Claude exited after the handoff ({events} more events)");
+        }
+    }
+    println!("Session: {session_name}");
+    let stats = supervisor.stats();
+    print_decision_sources(&stats.by_source, "");
+    if stats.tool_errors > 0 {
+        println!(
+            "Tool errors: {} ({} recognized by pattern only)",
+            stats.tool_errors, stats.pattern_tool_errors
+        );
+    }
+    if supervisor.policy().is_dry_run() {
+        print_dry_run_summary(supervisor.policy().dry_run_log());
+    }
+
+    // Bundle before the worktrees are cleaned up; a failure only warns
+    if let Some(ref dir) = config.artifacts_dir {
+        let worktrees = if worktree_group.is_some() {
+            session_roots.clone()
+        } else {
+            worktree_path
+                .iter()
+                .map(|path| (repo_label(path), path.clone()))
+                .collect()
+        };
+        let transcript = config
+            .history
+            .mirror
+            .then(|| default_transcript_dir().join(format!("{}.jsonl", audit_session.id)));
+        let mut bundler =
+            ArtifactBundler::new(dir, &session_name, audit_session.started_at.date_naive())
+                .with_manifest_dir(default_manifest_dir().join(&session_name))
+                .with_audit(audit_session.clone(), events)
+                .with_summary(run_summary(&session_name, &result, exit_code, &stats));
+        if let Some(path) = transcript {
+            bundler = bundler.with_transcript(path);
+        }
+        for (label, path) in worktrees {
+            bundler = bundler.with_worktree(label, path);
+        }
+        if let Some(redactor) = audit_redactor {
+            bundler = bundler.with_redactor(redactor.clone());
+        }
+        match bundler.write().await {
+            Ok(bundle) => println!("Artifacts: {}", bundle.path.display()),
+            Err(e) => tracing::warn!(error = %e, "Failed to bundle run artifacts"),
         }
     }
 
-    // Cleanup worktree if configured
+    // Cleanup worktree if configured; a handed-off session still works in it
     if let Some((manager, task_name)) = worktree_cleanup_info {
-        if config.worktree.auto_cleanup {
+        if config.worktree.auto_cleanup && !handed_off {
             tracing::info!(worktree = %task_name, "Cleaning up worktree");
             if let Err(e) = manager.remove(&task_name, false).await {
                 tracing::warn!(error = %e, "Failed to cleanup worktree");
             }
         }
     }
+    if let Some(group) = worktree_group {
+        if config.worktree.auto_cleanup && !handed_off {
+            tracing::info!(group = %group.id(), "Cleaning up worktree group");
+            for (worktree, e) in group.remove(false, false).await.failed {
+                tracing::warn!(worktree = %worktree.name, error = %e, "Failed to cleanup worktree");
+            }
+        }
+    }
 
-    Ok(())
+    Ok(exit_code)
 }
 
 #[tokio::main]
 #[allow(clippy::too_many_lines)]
 async fn main() {
     let cli = Cli::parse();
-    init_tracing(cli.verbose);
+    let _tracing = init_tracing(cli.verbose, cli.trace_file.as_deref());
+    init_data_dir();
 
     match cli.command {
         Commands::Run {
@@ -667,15 +3614,44 @@ async fn main() {
             auto_continue,
             allowed_tools,
             resume,
+            name,
             worktree,
             worktree_dir,
             worktree_cleanup,
+            no_sandbox,
+            policy_dry_run,
+            snapshot,
+            repos,
+            max_output_bytes,
+            show_thinking,
+            hide_thinking,
+            claude_bin,
+            scenario,
+            no_ai,
+            decision_authority,
+            allow_once,
+            deny_once,
+            max_duration,
+            pause_stops_clock,
+            interactive_approvals,
+            mirror_transcript,
+            context_recovery,
+            nudge_after,
+            strict_startup,
+            no_merge_transcript,
+            detach,
+            bundle,
+            dashboard,
         } => {
             // Validate: either task or resume must be provided
             if task.is_none() && resume.is_none() {
                 eprintln!("error: either <TASK> or --resume <SESSION_ID> is required");
                 std::process::exit(1);
             }
+            if let Some(Err(e)) = name.as_deref().map(validate_session_name) {
+                eprintln!("error: {e}");
+                std::process::exit(1);
+            }
 
             let mut config = SupervisorConfig {
                 policy: policy.into(),
@@ -698,6 +3674,57 @@ async fn main() {
             if worktree_cleanup {
                 config.worktree.auto_cleanup = true;
             }
+            config.no_sandbox = no_sandbox;
+            config.policy_dry_run = policy_dry_run;
+            config.ai_supervisor = !no_ai;
+            config.snapshots.enabled = snapshot;
+            if let Some(max) = max_output_bytes {
+                config.max_output_bytes = max;
+            }
+            config.show_thinking = show_thinking || !hide_thinking;
+            if let Some(binary) = claude_bin.or_else(claude_binary_from_env) {
+                config.claude_binary = binary;
+            }
+            if let Some(authority) = decision_authority {
+                config.escalation.decision_authority = authority.into();
+            }
+            if max_duration.is_some() {
+                config.max_duration_mins = max_duration;
+            }
+            config.pause_stops_clock = pause_stops_clock;
+            if interactive_approvals {
+                config.escalation.interactive.enabled = true;
+            }
+            config.history.mirror = mirror_transcript;
+            if let Some(mode) = context_recovery {
+                config.context_recovery.mode = mode.into();
+            }
+            if nudge_after.is_some() {
+                config.idle_nudge.after_secs = nudge_after;
+            }
+            config.strict_startup = strict_startup;
+            config.merge_transcript = !no_merge_transcript;
+            if bundle && config.artifacts_dir.is_none() {
+                config.artifacts_dir = Some(artifacts_dir());
+            }
+            if dashboard.is_some() {
+                config.dashboard.listen = dashboard;
+            }
+            for repo in repos {
+                match repo.canonicalize() {
+                    Ok(path) => config.repos.push(path),
+                    Err(e) => {
+                        eprintln!("error: invalid --repo {}: {e}", repo.display());
+                        std::process::exit(1);
+                    }
+                }
+            }
+
+            let issues = run_config_issues(&config);
+            if !issues.is_empty() {
+                eprintln!("error: invalid configuration:\n{}", format_issues(&issues));
+                std::process::exit(1);
+            }
 
             // Log based on task or resume mode
             if let Some(ref task_str) = task {
@@ -720,37 +3747,90 @@ async fn main() {
                 );
             }
 
-            if let Err(e) = handle_run(task, resume, config).await {
-                // Provide user-friendly error messages for common failures
-                if let Some(spawn_err) = e.downcast_ref::<SpawnError>() {
-                    match spawn_err {
-                        SpawnError::NotFound => {
-                            eprintln!(
-                                "error: Claude CLI not found. Is 'claude' installed and in PATH?"
-                            );
-                        }
-                        SpawnError::PermissionDenied => {
-                            eprintln!("error: Permission denied when spawning Claude CLI");
-                        }
-                        SpawnError::Io(_) => {
-                            eprintln!("error: {e}");
+            // The stand-in may run in a worktree, so it gets an absolute path
+            let scenario = match scenario.map(|path| path.canonicalize().map_err(|e| (path, e))) {
+                Some(Ok(path)) => Some(path),
+                Some(Err((path, e))) => {
+                    eprintln!("error: invalid --scenario {}: {e}", path.display());
+                    std::process::exit(1);
+                }
+                None => None,
+            };
+
+            // The detached child runs this command again, so everything above
+            // is checked before it starts
+            let detached = DetachedSession::current();
+            if detach && detached.is_none() {
+                if let Err(e) = handle_detach(name).await {
+                    eprintln!("error: {e}");
+                    std::process::exit(1);
+                }
+                return;
+            }
+            #[cfg(unix)]
+            if detached.is_some() {
+                if let Err(e) = claude_supervisor::supervisor::enter_new_session() {
+                    tracing::warn!(error = %e, "Failed to leave the terminal's session");
+                }
+            }
+
+            let overrides = deny_once.into_iter().chain(allow_once).collect();
+            match handle_run(task, resume, name, config, overrides, scenario, detached).await {
+                Ok(0) => {}
+                Ok(code) => std::process::exit(code),
+                Err(e) => {
+                    // Provide user-friendly error messages for common failures
+                    if let Some(spawn_err) = e.downcast_ref::<SpawnError>() {
+                        match spawn_err {
+                            SpawnError::NotFound { path } => {
+                                eprintln!(
+                                    "error: Claude CLI not found at '{}'. Install it, or pass --claude-bin.",
+                                    path.display()
+                                );
+                            }
+                            SpawnError::NotExecutable { path } => {
+                                eprintln!(
+                                    "error: Claude CLI at '{}' is not executable",
+                                    path.display()
+                                );
+                            }
+                            SpawnError::PermissionDenied => {
+                                eprintln!("error: Permission denied when spawning Claude CLI");
+                            }
+                            SpawnError::Io(_) => {
+                                eprintln!("error: {e}");
+                            }
                         }
+                    } else {
+                        eprintln!("error: {e}");
                     }
-                } else {
-                    eprintln!("error: {e}");
+                    tracing::error!(error = %e, "Supervisor failed");
+                    std::process::exit(1);
                 }
-                tracing::error!(error = %e, "Supervisor failed");
-                std::process::exit(1);
             }
         }
+        Commands::Attach { session } => {
+            handle_attach(session.as_deref()).await;
+        }
+        Commands::UpgradeHandoff { session } => match handle_upgrade_handoff(session).await {
+            Ok(0) => {}
+            Ok(code) => std::process::exit(code),
+            Err(e) => {
+                eprintln!("error: {e}");
+                std::process::exit(1);
+            }
+        },
         Commands::InstallHooks => {
             handle_install_hooks();
         }
         Commands::UninstallHooks => {
             handle_uninstall_hooks();
         }
+        Commands::Doctor => {
+            handle_doctor();
+        }
         Commands::Hook { event } => {
-            handle_hook(event);
+            handle_hook(event).await;
         }
         Commands::Config { action } => {
             handle_config(action);
@@ -763,8 +3843,51 @@ async fn main() {
             max_parallel,
             policy,
             auto_continue,
+            weight,
+            max_ai_requests,
+        } => {
+            if weight.len() > task.len() {
+                eprintln!(
+                    "error: {} --weight values given for {} tasks",
+                    weight.len(),
+                    task.len()
+                );
+                std::process::exit(1);
+            }
+            handle_multi(
+                task,
+                max_parallel,
+                policy,
+                auto_continue,
+                weight,
+                max_ai_requests,
+            )
+            .await;
+        }
+        Commands::Audit { action } => {
+            handle_audit(action).await;
+        }
+        Commands::Snapshots { action } => {
+            handle_snapshots(action);
+        }
+        Commands::Trash { action } => {
+            handle_trash(action);
+        }
+        Commands::Policy { action } => {
+            handle_policy(action).await;
+        }
+        Commands::Review {
+            session,
+            out,
+            learn,
         } => {
-            handle_multi(task, max_parallel, policy, auto_continue).await;
+            handle_review(&session, out, learn).await;
+        }
+        Commands::Export { action } => {
+            handle_export(action).await;
+        }
+        Commands::Ai { action } => {
+            handle_ai(action).await;
         }
     }
 }
@@ -0,0 +1,157 @@
+//! OpenTelemetry export of supervision spans, behind the `otel` feature.
+//!
+//! The `session`, `tool_call` and `escalation` spans recorded by
+//! [`SessionTrace`](crate::supervisor::SessionTrace) are sent to an OTLP
+//! collector over HTTP. The exporter is configured through the standard
+//! `OTEL_EXPORTER_OTLP_*` environment variables and only installed when an
+//! endpoint is set, so a build with the feature exports nothing by default.
+
+use opentelemetry::trace::TracerProvider as _;
+use opentelemetry_otlp::{ExporterBuildError, SpanExporter};
+use opentelemetry_sdk::trace::{SdkTracer, SdkTracerProvider};
+use opentelemetry_sdk::Resource;
+use tracing::Subscriber;
+use tracing_opentelemetry::OpenTelemetryLayer;
+use tracing_subscriber::filter::{Filtered, Targets};
+use tracing_subscriber::layer::Layer;
+use tracing_subscriber::registry::LookupSpan;
+
+/// Variables naming the collector; without one the exporter is not installed.
+const ENDPOINT_VARS: [&str; 2] = [
+    "OTEL_EXPORTER_OTLP_TRACES_ENDPOINT",
+    "OTEL_EXPORTER_OTLP_ENDPOINT",
+];
+
+/// Service name reported with every span.
+const SERVICE_NAME: &str = "claude-supervisor";
+
+/// The OpenTelemetry layer for a subscriber, exporting this crate's spans.
+pub type OtelLayer<S> = Filtered<OpenTelemetryLayer<S, SdkTracer>, Targets, S>;
+
+/// Flushes and shuts down the exporter when dropped.
+#[derive(Debug)]
+pub struct OtelGuard {
+    provider: SdkTracerProvider,
+}
+
+impl Drop for OtelGuard {
+    fn drop(&mut self) {
+        if let Err(e) = self.provider.shutdown() {
+            eprintln!("Failed to flush OpenTelemetry spans: {e}");
+        }
+    }
+}
+
+/// Whether an OTLP endpoint is configured in the environment.
+#[must_use]
+pub fn is_configured() -> bool {
+    ENDPOINT_VARS
+        .iter()
+        .any(|name| std::env::var_os(name).is_some_and(|value| !value.is_empty()))
+}
+
+/// Create the OTLP exporting layer if an endpoint is configured.
+///
+/// # Errors
+///
+/// Returns an error if the exporter cannot be built from the environment.
+pub fn init<S>() -> Result<Option<(OtelLayer<S>, OtelGuard)>, ExporterBuildError>
+where
+    S: Subscriber + for<'a> LookupSpan<'a>,
+{
+    if !is_configured() {
+        return Ok(None);
+    }
+    let exporter = SpanExporter::builder().with_http().build()?;
+    let provider = SdkTracerProvider::builder()
+        .with_resource(Resource::builder().with_service_name(SERVICE_NAME).build())
+        .with_batch_exporter(exporter)
+        .build();
+    Ok(Some((layer(&provider), OtelGuard { provider })))
+}
+
+/// A layer exporting this crate's spans through `provider`.
+#[must_use]
+pub fn layer<S>(provider: &SdkTracerProvider) -> OtelLayer<S>
+where
+    S: Subscriber + for<'a> LookupSpan<'a>,
+{
+    let spans = Targets::new().with_target(env!("CARGO_CRATE_NAME"), tracing::Level::INFO);
+    tracing_opentelemetry::layer()
+        .with_tracer(provider.tracer(SERVICE_NAME))
+        .with_filter(spans)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::cli::ToolUse;
+    use crate::supervisor::SessionTrace;
+    use crate::telemetry::{SPAN_ESCALATION, SPAN_SESSION, SPAN_TOOL_CALL};
+    use opentelemetry::Value;
+    use opentelemetry_sdk::trace::{InMemorySpanExporter, SpanData};
+    use std::time::Duration;
+    use tracing_subscriber::layer::SubscriberExt;
+
+    fn attribute<'a>(span: &'a SpanData, key: &str) -> Option<&'a Value> {
+        span.attributes
+            .iter()
+            .find(|kv| kv.key.as_str() == key)
+            .map(|kv| &kv.value)
+    }
+
+    #[test]
+    fn test_exports_session_spans() {
+        let exporter = InMemorySpanExporter::default();
+        let provider = SdkTracerProvider::builder()
+            .with_simple_exporter(exporter.clone())
+            .build();
+        let subscriber = tracing_subscriber::registry().with(layer(&provider));
+        tracing::subscriber::with_default(subscriber, || {
+            let mut trace = SessionTrace::default();
+            trace.start_session("s1", "claude");
+            trace.start_tool_call(&ToolUse {
+                id: "toolu_1".to_string(),
+                name: "Bash".to_string(),
+                input: serde_json::json!({"command": "ls"}),
+            });
+            trace.record_decision("toolu_1", "escalate");
+            let escalation = trace.escalation("toolu_1", "ai");
+            SessionTrace::finish_escalation(&escalation, Duration::from_millis(5), "allow");
+            drop(escalation);
+            trace.finish_tool_call("toolu_1", Some(Duration::from_millis(9)));
+            trace.finish("completed");
+        });
+        provider.force_flush().unwrap();
+
+        let spans = exporter.get_finished_spans().unwrap();
+        let find = |name: &str| spans.iter().find(|span| span.name == name).unwrap();
+        let (session, tool_call, escalation) = (
+            find(SPAN_SESSION),
+            find(SPAN_TOOL_CALL),
+            find(SPAN_ESCALATION),
+        );
+        assert_eq!(
+            tool_call.parent_span_id,
+            session.span_context.span_id(),
+            "tool call under the session"
+        );
+        assert_eq!(
+            escalation.parent_span_id,
+            tool_call.span_context.span_id(),
+            "escalation under the tool call"
+        );
+        assert_eq!(
+            attribute(session, "session.outcome"),
+            Some(&Value::from("completed"))
+        );
+        assert_eq!(
+            attribute(tool_call, "tool.decision"),
+            Some(&Value::from("escalate"))
+        );
+        assert_eq!(
+            attribute(escalation, "escalation.verdict"),
+            Some(&Value::from("allow"))
+        );
+    }
+}
@@ -0,0 +1,31 @@
+//! Snapshot error types.
+
+use std::path::PathBuf;
+
+/// Errors that can occur during snapshot operations.
+#[derive(thiserror::Error, Debug)]
+pub enum SnapshotError {
+    /// Session ID cannot be used as a directory name.
+    #[error("Invalid session ID: {0}")]
+    InvalidSession(String),
+
+    /// No snapshot matches the request.
+    #[error("No snapshot of {path} in session {session}")]
+    NotFound { session: String, path: PathBuf },
+
+    /// I/O error on a snapshot or target file.
+    #[error("I/O error on {path}: {source}")]
+    Io {
+        path: PathBuf,
+        #[source]
+        source: std::io::Error,
+    },
+
+    /// Snapshot index could not be read or written.
+    #[error("Invalid snapshot index {path}: {source}")]
+    Index {
+        path: PathBuf,
+        #[source]
+        source: serde_json::Error,
+    },
+}
@@ -0,0 +1,13 @@
+//! File snapshots taken before approved writes.
+//!
+//! Allowed edits sometimes need undoing. Before a Write or Edit is approved
+//! the target file is copied into a content-addressed store under
+//! `snapshots/<session>/` in the [data directory](crate::config::data_dir),
+//! from which it can be listed and restored with `claude-supervisor snapshots`.
+
+mod error;
+mod store;
+
+pub use error::SnapshotError;
+pub(crate) use store::content_id;
+pub use store::{write_target, SnapshotEntry, SnapshotStore};
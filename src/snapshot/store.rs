@@ -0,0 +1,493 @@
+//! Content-addressed snapshot store.
+//!
+//! Layout of a session directory:
+//!
+//! ```text
+//! <root>/<session>/index.json      snapshot entries, oldest first
+//! <root>/<session>/objects/<id>    file contents keyed by content hash
+//! ```
+
+use std::path::{Path, PathBuf};
+use std::sync::mpsc;
+use std::time::Duration;
+
+use chrono::{DateTime, Utc};
+use serde::{Deserialize, Serialize};
+
+use crate::cli::input_paths;
+use crate::config::SnapshotConfig;
+
+use super::SnapshotError;
+
+/// Name of the per-session index file.
+const INDEX_FILE: &str = "index.json";
+
+/// Name of the per-session object directory.
+const OBJECTS_DIR: &str = "objects";
+
+/// A stored copy of a file taken before a write.
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct SnapshotEntry {
+    /// Content hash, also the object file name.
+    pub id: String,
+    /// Absolute path of the snapshotted file.
+    pub path: PathBuf,
+    /// File size in bytes.
+    pub size: u64,
+    /// When the snapshot was taken.
+    pub created_at: DateTime<Utc>,
+}
+
+/// Store of file snapshots grouped by session.
+#[derive(Debug, Clone)]
+pub struct SnapshotStore {
+    root: PathBuf,
+    max_file_bytes: u64,
+    max_per_file: usize,
+}
+
+impl SnapshotStore {
+    /// Create a store rooted at `root` with default caps.
+    #[must_use]
+    pub fn new(root: impl Into<PathBuf>) -> Self {
+        let defaults = SnapshotConfig::default();
+        Self {
+            root: root.into(),
+            max_file_bytes: defaults.max_file_bytes,
+            max_per_file: defaults.max_per_file,
+        }
+    }
+
+    /// Create a store from configuration, resolving its directory against `cwd`.
+    #[must_use]
+    pub fn from_config(config: &SnapshotConfig, cwd: &Path) -> Self {
+        Self::new(cwd.join(&config.dir))
+            .with_max_file_bytes(config.max_file_bytes)
+            .with_max_per_file(config.max_per_file)
+    }
+
+    /// Set the largest file that will be snapshotted (builder pattern).
+    #[must_use]
+    pub fn with_max_file_bytes(mut self, max_file_bytes: u64) -> Self {
+        self.max_file_bytes = max_file_bytes;
+        self
+    }
+
+    /// Set how many snapshots are kept per file (builder pattern).
+    #[must_use]
+    pub fn with_max_per_file(mut self, max_per_file: usize) -> Self {
+        self.max_per_file = max_per_file.max(1);
+        self
+    }
+
+    /// Get the store root.
+    #[must_use]
+    pub fn root(&self) -> &Path {
+        &self.root
+    }
+
+    /// Snapshot a file for a session.
+    ///
+    /// Returns `Ok(None)` if the file does not exist yet or exceeds the size
+    /// cap. Snapshotting unchanged content again returns the existing entry.
+    ///
+    /// # Errors
+    ///
+    /// Returns an error if the session ID is invalid or the file, objects or
+    /// index cannot be read or written.
+    pub fn snapshot(
+        &self,
+        session: &str,
+        path: &Path,
+    ) -> Result<Option<SnapshotEntry>, SnapshotError> {
+        let dir = self.session_dir(session)?;
+        let metadata = match std::fs::metadata(path) {
+            Ok(metadata) if metadata.is_file() => metadata,
+            Ok(_) => return Ok(None),
+            Err(e) if e.kind() == std::io::ErrorKind::NotFound => return Ok(None),
+            Err(source) => return Err(io_error(path, source)),
+        };
+        if metadata.len() > self.max_file_bytes {
+            tracing::warn!(
+                path = %path.display(),
+                size = metadata.len(),
+                max = self.max_file_bytes,
+                "File too large to snapshot"
+            );
+            return Ok(None);
+        }
+
+        let content = std::fs::read(path).map_err(|e| io_error(path, e))?;
+        let id = content_id(&content);
+        let objects = dir.join(OBJECTS_DIR);
+        std::fs::create_dir_all(&objects).map_err(|e| io_error(&objects, e))?;
+        let object = objects.join(&id);
+        if !object.exists() {
+            write_atomic(&object, &content)?;
+        }
+
+        let mut index = read_index(&dir)?;
+        if let Some(latest) = index.iter().rev().find(|entry| entry.path == path) {
+            if latest.id == id {
+                return Ok(Some(latest.clone()));
+            }
+        }
+
+        let entry = SnapshotEntry {
+            id,
+            path: path.to_path_buf(),
+            size: content.len() as u64,
+            created_at: Utc::now(),
+        };
+        index.push(entry.clone());
+        self.evict(&mut index, path, &objects)?;
+        write_index(&dir, &index)?;
+
+        tracing::debug!(path = %path.display(), id = %entry.id, "File snapshotted");
+        Ok(Some(entry))
+    }
+
+    /// Snapshot a file, giving up after `timeout`.
+    ///
+    /// Errors and timeouts are logged and yield `None` so an approval is
+    /// never blocked for longer than `timeout`. A snapshot that times out
+    /// keeps running in the background.
+    #[must_use]
+    pub fn snapshot_within(
+        &self,
+        session: &str,
+        path: &Path,
+        timeout: Duration,
+    ) -> Option<SnapshotEntry> {
+        let (tx, rx) = mpsc::channel();
+        let store = self.clone();
+        let session_owned = session.to_string();
+        let path_owned = path.to_path_buf();
+        std::thread::spawn(move || {
+            let _ = tx.send(store.snapshot(&session_owned, &path_owned));
+        });
+
+        match rx.recv_timeout(timeout) {
+            Ok(Ok(entry)) => entry,
+            Ok(Err(e)) => {
+                tracing::warn!(path = %path.display(), error = %e, "Failed to snapshot file");
+                None
+            }
+            Err(_) => {
+                tracing::warn!(
+                    path = %path.display(),
+                    timeout_ms = timeout.as_millis(),
+                    "Snapshot timed out; approving without it"
+                );
+                None
+            }
+        }
+    }
+
+    /// List a session's snapshots, oldest first, optionally for one file.
+    ///
+    /// # Errors
+    ///
+    /// Returns an error if the session ID is invalid or the index cannot be read.
+    pub fn list(
+        &self,
+        session: &str,
+        path: Option<&Path>,
+    ) -> Result<Vec<SnapshotEntry>, SnapshotError> {
+        let index = read_index(&self.session_dir(session)?)?;
+        Ok(index
+            .into_iter()
+            .filter(|entry| path.is_none_or(|path| entry.path == path))
+            .collect())
+    }
+
+    /// Restore a file from a snapshot and return the entry used.
+    ///
+    /// With no `id` the file's oldest snapshot is used, undoing every
+    /// write the session made to it.
+    ///
+    /// # Errors
+    ///
+    /// Returns `SnapshotError::NotFound` if no snapshot matches, or an error if
+    /// the object cannot be read or the file cannot be written.
+    pub fn restore(
+        &self,
+        session: &str,
+        path: &Path,
+        id: Option<&str>,
+    ) -> Result<SnapshotEntry, SnapshotError> {
+        let entry = self
+            .list(session, Some(path))?
+            .into_iter()
+            .find(|entry| id.is_none_or(|id| entry.id == id))
+            .ok_or_else(|| SnapshotError::NotFound {
+                session: session.to_string(),
+                path: path.to_path_buf(),
+            })?;
+        self.write_back(session, &entry)?;
+        Ok(entry)
+    }
+
+    /// Restore every file snapshotted in a session to its oldest snapshot.
+    ///
+    /// # Errors
+    ///
+    /// Returns an error if the index or an object cannot be read or a file
+    /// cannot be written. Files restored before the error stay restored.
+    pub fn restore_session(&self, session: &str) -> Result<Vec<SnapshotEntry>, SnapshotError> {
+        let mut restored: Vec<SnapshotEntry> = Vec::new();
+        for entry in self.list(session, None)? {
+            if restored.iter().any(|done| done.path == entry.path) {
+                continue;
+            }
+            self.write_back(session, &entry)?;
+            restored.push(entry);
+        }
+        Ok(restored)
+    }
+
+    /// Directory holding a session's snapshots.
+    fn session_dir(&self, session: &str) -> Result<PathBuf, SnapshotError> {
+        let valid = !session.is_empty()
+            && session
+                .chars()
+                .all(|c| c.is_ascii_alphanumeric() || c == '-' || c == '_');
+        if !valid {
+            return Err(SnapshotError::InvalidSession(session.to_string()));
+        }
+        Ok(self.root.join(session))
+    }
+
+    /// Write a snapshot's content back to its file.
+    fn write_back(&self, session: &str, entry: &SnapshotEntry) -> Result<(), SnapshotError> {
+        let object = self.session_dir(session)?.join(OBJECTS_DIR).join(&entry.id);
+        let content = std::fs::read(&object).map_err(|e| io_error(&object, e))?;
+        if let Some(parent) = entry.path.parent() {
+            std::fs::create_dir_all(parent).map_err(|e| io_error(parent, e))?;
+        }
+        write_atomic(&entry.path, &content)
+    }
+
+    /// Drop the oldest snapshots of `path` beyond the per-file cap, deleting
+    /// objects no remaining entry refers to.
+    fn evict(
+        &self,
+        index: &mut Vec<SnapshotEntry>,
+        path: &Path,
+        objects: &Path,
+    ) -> Result<(), SnapshotError> {
+        let count = index.iter().filter(|entry| entry.path == path).count();
+        let mut excess = count.saturating_sub(self.max_per_file);
+        let mut evicted = Vec::new();
+        index.retain(|entry| {
+            if excess > 0 && entry.path == path {
+                excess -= 1;
+                evicted.push(entry.id.clone());
+                false
+            } else {
+                true
+            }
+        });
+
+        for id in evicted {
+            if !index.iter().any(|entry| entry.id == id) {
+                let object = objects.join(&id);
+                std::fs::remove_file(&object).map_err(|e| io_error(&object, e))?;
+            }
+        }
+        Ok(())
+    }
+}
+
+/// File path a write tool call targets, if any.
+#[must_use]
+pub fn write_target<'a>(tool_name: &str, tool_input: &'a serde_json::Value) -> Option<&'a str> {
+    if !matches!(
+        tool_name,
+        "Write" | "Edit" | "MultiEdit" | "NotebookEdit" | "write" | "edit"
+    ) {
+        return None;
+    }
+    input_paths(tool_input).next()
+}
+
+/// Hex FNV-1a 128-bit hash of the content.
+pub(crate) fn content_id(content: &[u8]) -> String {
+    const OFFSET: u128 = 0x6c62_272e_07bb_0142_62b8_2175_6295_c58d;
+    const PRIME: u128 = 0x0000_0000_0100_0000_0000_0000_0000_013b;
+    let hash = content.iter().fold(OFFSET, |hash, byte| {
+        (hash ^ u128::from(*byte)).wrapping_mul(PRIME)
+    });
+    format!("{hash:032x}")
+}
+
+fn read_index(dir: &Path) -> Result<Vec<SnapshotEntry>, SnapshotError> {
+    let path = dir.join(INDEX_FILE);
+    match std::fs::read(&path) {
+        Ok(bytes) => {
+            serde_json::from_slice(&bytes).map_err(|source| SnapshotError::Index { path, source })
+        }
+        Err(e) if e.kind() == std::io::ErrorKind::NotFound => Ok(Vec::new()),
+        Err(source) => Err(io_error(&path, source)),
+    }
+}
+
+fn write_index(dir: &Path, index: &[SnapshotEntry]) -> Result<(), SnapshotError> {
+    let path = dir.join(INDEX_FILE);
+    let bytes = serde_json::to_vec_pretty(index).map_err(|source| SnapshotError::Index {
+        path: path.clone(),
+        source,
+    })?;
+    write_atomic(&path, &bytes)
+}
+
+/// Write via a temporary file and rename so readers never see partial content.
+fn write_atomic(path: &Path, content: &[u8]) -> Result<(), SnapshotError> {
+    let mut tmp = path.as_os_str().to_owned();
+    tmp.push(format!(".tmp-{}", uuid::Uuid::new_v4()));
+    let tmp = PathBuf::from(tmp);
+    std::fs::write(&tmp, content).map_err(|e| io_error(&tmp, e))?;
+    std::fs::rename(&tmp, path).map_err(|e| io_error(path, e))
+}
+
+fn io_error(path: &Path, source: std::io::Error) -> SnapshotError {
+    SnapshotError::Io {
+        path: path.to_path_buf(),
+        source,
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn setup() -> (tempfile::TempDir, SnapshotStore, PathBuf) {
+        let dir = tempfile::tempdir().unwrap();
+        let store = SnapshotStore::new(dir.path().join("snapshots"));
+        let file = dir.path().join("src").join("lib.rs");
+        std::fs::create_dir_all(file.parent().unwrap()).unwrap();
+        std::fs::write(&file, "fn original() {}").unwrap();
+        (dir, store, file)
+    }
+
+    #[test]
+    fn test_snapshot_creates_object_and_index() {
+        let (_dir, store, file) = setup();
+        let entry = store.snapshot("session-1", &file).unwrap().unwrap();
+
+        assert_eq!(entry.path, file);
+        assert_eq!(entry.size, 16);
+        let object = store.root().join("session-1/objects").join(&entry.id);
+        assert_eq!(std::fs::read_to_string(object).unwrap(), "fn original() {}");
+        assert_eq!(store.list("session-1", None).unwrap(), vec![entry]);
+    }
+
+    #[test]
+    fn test_snapshot_dedupes_unchanged_content() {
+        let (_dir, store, file) = setup();
+        let first = store.snapshot("s", &file).unwrap().unwrap();
+        let second = store.snapshot("s", &file).unwrap().unwrap();
+        assert_eq!(first, second);
+        assert_eq!(store.list("s", Some(&file)).unwrap().len(), 1);
+    }
+
+    #[test]
+    fn test_snapshot_skips_missing_and_large_files() {
+        let (dir, store, file) = setup();
+        assert!(store
+            .snapshot("s", &dir.path().join("new.rs"))
+            .unwrap()
+            .is_none());
+
+        let store = store.with_max_file_bytes(4);
+        assert!(store.snapshot("s", &file).unwrap().is_none());
+        assert!(store.list("s", None).unwrap().is_empty());
+    }
+
+    #[test]
+    fn test_count_cap_evicts_oldest() {
+        let (_dir, store, file) = setup();
+        let store = store.with_max_per_file(2);
+        let mut ids = Vec::new();
+        for version in 0..3 {
+            std::fs::write(&file, format!("version {version}")).unwrap();
+            ids.push(store.snapshot("s", &file).unwrap().unwrap().id);
+        }
+
+        let kept: Vec<String> = store
+            .list("s", Some(&file))
+            .unwrap()
+            .into_iter()
+            .map(|entry| entry.id)
+            .collect();
+        assert_eq!(kept, ids[1..]);
+        let objects = store.root().join("s/objects");
+        assert!(!objects.join(&ids[0]).exists());
+        assert!(objects.join(&ids[2]).exists());
+    }
+
+    #[test]
+    fn test_restore_oldest_and_by_id() {
+        let (_dir, store, file) = setup();
+        store.snapshot("s", &file).unwrap();
+        std::fs::write(&file, "fn edited() {}").unwrap();
+        let edited = store.snapshot("s", &file).unwrap().unwrap();
+        std::fs::write(&file, "fn broken(").unwrap();
+
+        let restored = store.restore("s", &file, None).unwrap();
+        assert_eq!(std::fs::read_to_string(&file).unwrap(), "fn original() {}");
+        assert_ne!(restored.id, edited.id);
+
+        store.restore("s", &file, Some(&edited.id)).unwrap();
+        assert_eq!(std::fs::read_to_string(&file).unwrap(), "fn edited() {}");
+
+        assert!(matches!(
+            store.restore("s", &file, Some("missing")),
+            Err(SnapshotError::NotFound { .. })
+        ));
+    }
+
+    #[test]
+    fn test_restore_session_recreates_deleted_files() {
+        let (dir, store, file) = setup();
+        let other = dir.path().join("README.md");
+        std::fs::write(&other, "# readme").unwrap();
+        store.snapshot("s", &file).unwrap();
+        store.snapshot("s", &other).unwrap();
+        std::fs::remove_dir_all(file.parent().unwrap()).unwrap();
+        std::fs::write(&other, "").unwrap();
+
+        let restored = store.restore_session("s").unwrap();
+        assert_eq!(restored.len(), 2);
+        assert_eq!(std::fs::read_to_string(&file).unwrap(), "fn original() {}");
+        assert_eq!(std::fs::read_to_string(&other).unwrap(), "# readme");
+    }
+
+    #[test]
+    fn test_invalid_session_rejected() {
+        let (_dir, store, file) = setup();
+        assert!(matches!(
+            store.snapshot("../escape", &file),
+            Err(SnapshotError::InvalidSession(_))
+        ));
+    }
+
+    #[test]
+    fn test_snapshot_within_returns_entry() {
+        let (_dir, store, file) = setup();
+        let entry = store.snapshot_within("s", &file, Duration::from_secs(5));
+        assert!(entry.is_some());
+        assert!(store
+            .snapshot_within("..", &file, Duration::from_secs(5))
+            .is_none());
+    }
+
+    #[test]
+    fn test_write_target() {
+        let input = serde_json::json!({ "file_path": "/tmp/a.rs", "content": "" });
+        assert_eq!(write_target("Write", &input), Some("/tmp/a.rs"));
+        assert_eq!(write_target("Read", &input), None);
+        let notebook = serde_json::json!({ "notebook_path": "nb.ipynb" });
+        assert_eq!(write_target("NotebookEdit", &notebook), Some("nb.ipynb"));
+    }
+}
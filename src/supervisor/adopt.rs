@@ -0,0 +1,517 @@
+//! Following a session adopted from another supervisor.
+//!
+//! After a [handoff](crate::ipc::handoff) the new supervisor does not own
+//! Claude's output stream; it follows the Claude process by its PID and the
+//! session by its JSONL transcript, from where the transcript was when it
+//! took over. Hooks keep enforcing the policy through the new supervisor's
+//! socket.
+//!
+//! The follower enforces the limits the old supervisor did: the carried-over
+//! cost budget, costed from the transcript's token usage, the time limit and
+//! the kill switch. It also stops a session caught in a stuck pattern, which
+//! no Stop hook ends while the old supervisor's runner no longer watches it.
+//! Any of these terminates the Claude process.
+
+use std::time::Duration;
+
+use serde_json::json;
+use tokio::time::Instant;
+use tokio_util::sync::CancellationToken;
+
+use super::{
+    process_alive, BudgetEvent, CostBudget, KillCause, KillSwitch, DEFAULT_TERMINATE_TIMEOUT,
+    HALTED_EXIT_CODE, TIMED_OUT_EXIT_CODE,
+};
+use crate::audit::CostAttributor;
+use crate::cli::ClaudeEvent;
+use crate::display;
+use crate::ipc::{BudgetCounters, HandoffSnapshot, HandoffState};
+use crate::watcher::{
+    JournalEntry, JsonlTailer, PatternDetector, SessionReconstructor, StuckPattern, ToolCallRecord,
+};
+
+/// How often an adopted session's transcript and process are checked.
+pub const ADOPT_POLL_INTERVAL: Duration = Duration::from_millis(500);
+
+/// Why following an adopted session ended.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum AdoptionEnd {
+    /// The Claude process exited.
+    ChildExited,
+    /// Following was cancelled.
+    Cancelled,
+    /// The session was killed for going over its budget or being stuck.
+    Killed {
+        /// Human-readable reason for killing.
+        reason: String,
+        /// Structured cause of the kill.
+        cause: KillCause,
+    },
+    /// The session reached its time limit and was shut down.
+    TimedOut {
+        /// The limit that was reached.
+        limit: Duration,
+    },
+    /// The kill switch was engaged and the session was shut down.
+    Halted,
+}
+
+impl AdoptionEnd {
+    /// Process exit code for `upgrade-handoff`, as `run` exits with for the
+    /// same outcome.
+    #[must_use]
+    pub fn exit_code(&self) -> i32 {
+        match self {
+            Self::ChildExited | Self::Cancelled => 0,
+            Self::Killed { cause, .. } => cause.exit_code(),
+            Self::TimedOut { .. } => TIMED_OUT_EXIT_CODE,
+            Self::Halted => HALTED_EXIT_CODE,
+        }
+    }
+}
+
+/// A session taken over from another supervisor.
+#[derive(Debug)]
+pub struct AdoptedSession {
+    child_pid: Option<u32>,
+    tailer: Option<JsonlTailer>,
+    reconstructor: SessionReconstructor,
+    detector: PatternDetector,
+    reported: usize,
+    stuck: Option<StuckPattern>,
+    costs: CostAttributor,
+    carried_cost_micros: u64,
+    budget: Option<CostBudget>,
+    time_limit: Option<(Duration, Instant)>,
+    kill_switch: Option<KillSwitch>,
+    handoff: Option<HandoffState>,
+}
+
+impl AdoptedSession {
+    /// Follow the process and most recent session of `snapshot`, reading
+    /// only what the transcript gains from now on, against the budget and
+    /// time limit it carries.
+    #[must_use]
+    pub fn from_snapshot(snapshot: &HandoffSnapshot) -> Self {
+        let tailer = snapshot
+            .current_session()
+            .and_then(|session| session.transcript_path.clone())
+            .map(|path| {
+                let offset = std::fs::metadata(&path).map_or(0, |metadata| metadata.len());
+                JsonlTailer::with_offset(path, offset)
+            });
+        let budget = snapshot.budget.as_ref().map(|counters| {
+            let mut budget = CostBudget::new(counters.limit_usd, &counters.alert_percent);
+            // The old supervisor already raised the alerts reached so far
+            let _ = budget.observe(snapshot.cost_micros);
+            budget
+        });
+        let time_limit = snapshot.time_box.map(|time_box| {
+            let remaining = (time_box.expires_at - chrono::Utc::now())
+                .to_std()
+                .unwrap_or_default();
+            (
+                Duration::from_secs(time_box.limit_secs),
+                Instant::now() + remaining,
+            )
+        });
+        Self {
+            child_pid: snapshot.child_pid,
+            tailer,
+            reconstructor: SessionReconstructor::new(),
+            detector: PatternDetector::new(),
+            reported: 0,
+            stuck: None,
+            costs: CostAttributor::new(),
+            carried_cost_micros: snapshot.cost_micros,
+            budget,
+            time_limit,
+            kill_switch: None,
+            handoff: None,
+        }
+    }
+
+    /// Halt the session once `kill_switch` is engaged.
+    #[must_use]
+    pub fn with_kill_switch(mut self, kill_switch: KillSwitch) -> Self {
+        self.kill_switch = Some(kill_switch);
+        self
+    }
+
+    /// Keep `handoff` up to date with the session cost, so the session can
+    /// be handed off again.
+    #[must_use]
+    pub fn with_handoff(mut self, handoff: HandoffState) -> Self {
+        self.handoff = Some(handoff);
+        self
+    }
+
+    /// Session cost so far, in micro-dollars: the cost carried over plus the
+    /// estimate for the turns since the handoff.
+    #[must_use]
+    pub fn cost_micros(&self) -> u64 {
+        self.carried_cost_micros + self.costs.breakdown().total_cost_micros
+    }
+
+    /// Time left before the session's time limit, if it has one.
+    #[must_use]
+    pub fn time_remaining(&self) -> Option<Duration> {
+        self.time_limit
+            .map(|(_, deadline)| deadline.saturating_duration_since(Instant::now()))
+    }
+
+    /// Process ID of the adopted Claude process.
+    #[must_use]
+    pub fn child_pid(&self) -> Option<u32> {
+        self.child_pid
+    }
+
+    /// Whether the adopted Claude process still runs.
+    #[must_use]
+    pub fn is_child_alive(&self) -> bool {
+        self.child_pid.is_some_and(process_alive)
+    }
+
+    /// Tool calls completed since the session was adopted, oldest first.
+    #[must_use]
+    pub fn tool_calls(&self) -> &[ToolCallRecord] {
+        self.reconstructor.tool_calls()
+    }
+
+    /// The stuck pattern the session was last seen in, if any.
+    #[must_use]
+    pub fn stuck_pattern(&self) -> Option<&StuckPattern> {
+        self.stuck.as_ref()
+    }
+
+    /// Read what the transcript gained and return the tool calls it
+    /// completed.
+    ///
+    /// A transcript that cannot be read is logged and read again next time.
+    pub async fn poll(&mut self) -> Vec<ToolCallRecord> {
+        let Some(ref mut tailer) = self.tailer else {
+            return Vec::new();
+        };
+        match tailer.read_new_entries().await {
+            Ok(entries) => {
+                for entry in &entries {
+                    if let JournalEntry::Assistant(assistant) = entry {
+                        // Costed like the stream's assistant events
+                        self.costs.observe(&ClaudeEvent::Assistant {
+                            message: json!({
+                                "id": assistant.message.id,
+                                "usage": assistant.message.usage,
+                            }),
+                        });
+                    }
+                }
+                self.reconstructor.process_entries(&entries);
+            }
+            Err(e) => tracing::warn!(error = %e, "Failed to read the adopted transcript"),
+        }
+        if let Some(ref handoff) = self.handoff {
+            let budget = self.budget.as_ref().map(|budget| BudgetCounters {
+                limit_usd: budget.limit_usd(),
+                spent_usd: budget.spent_usd(),
+                alert_percent: budget.alert_percent().to_vec(),
+            });
+            handoff.set_cost(self.cost_micros(), budget);
+        }
+
+        let stuck = self.reconstructor.detect_stuck_pattern(&self.detector);
+        if stuck.is_some() && stuck != self.stuck {
+            tracing::warn!(pattern = ?stuck, "Adopted session looks stuck");
+        }
+        self.stuck = stuck;
+
+        let calls = self.reconstructor.tool_calls()[self.reported..].to_vec();
+        self.reported += calls.len();
+        calls
+    }
+
+    /// Check the session against the kill switch, its time limit, its
+    /// budget and stuck patterns; returns why it must end, if it must.
+    ///
+    /// Budget alerts reached since the last check are shown.
+    pub fn check_limits(&mut self) -> Option<AdoptionEnd> {
+        if self
+            .kill_switch
+            .as_ref()
+            .is_some_and(KillSwitch::is_engaged)
+        {
+            return Some(AdoptionEnd::Halted);
+        }
+        if let Some((limit, deadline)) = self.time_limit {
+            if Instant::now() >= deadline {
+                return Some(AdoptionEnd::TimedOut { limit });
+            }
+        }
+        let cost_micros = self.cost_micros();
+        if let Some(ref mut budget) = self.budget {
+            for budget_event in budget.observe(cost_micros) {
+                match budget_event {
+                    BudgetEvent::Alert { .. } => {
+                        tracing::warn!(alert = %budget_event.describe(), "Budget alert");
+                        display::print_budget(&budget_event.describe());
+                    }
+                    BudgetEvent::Exhausted { .. } => {
+                        return Some(AdoptionEnd::Killed {
+                            reason: budget_event.describe(),
+                            cause: KillCause::Budget,
+                        });
+                    }
+                }
+            }
+        }
+        self.stuck.as_ref().map(|pattern| AdoptionEnd::Killed {
+            reason: format!("Stuck pattern detected: {pattern}"),
+            cause: KillCause::StuckPattern,
+        })
+    }
+
+    /// Stop the Claude process: `SIGTERM`, then `SIGKILL` if it still runs
+    /// after `timeout`.
+    pub async fn terminate(&self, timeout: Duration) {
+        #[cfg(unix)]
+        {
+            use nix::sys::signal::{kill, Signal};
+            use nix::unistd::Pid;
+
+            let Some(pid) = self.child_pid.and_then(|pid| i32::try_from(pid).ok()) else {
+                return;
+            };
+            let pid = Pid::from_raw(pid);
+            let _ = kill(pid, Signal::SIGTERM);
+            let deadline = Instant::now() + timeout;
+            while self.is_child_alive() && Instant::now() < deadline {
+                tokio::time::sleep(Duration::from_millis(50)).await;
+            }
+            if self.is_child_alive() {
+                let _ = kill(pid, Signal::SIGKILL);
+            }
+        }
+
+        #[cfg(not(unix))]
+        {
+            let _ = timeout;
+            tracing::warn!("Cannot stop the adopted Claude process on this platform");
+        }
+    }
+
+    /// Poll every `interval`, passing completed tool calls to `on_call`,
+    /// until the Claude process exits, `cancel` fires or
+    /// [`check_limits`](Self::check_limits) ends the session, terminating
+    /// Claude.
+    pub async fn follow(
+        &mut self,
+        interval: Duration,
+        cancel: &CancellationToken,
+        mut on_call: impl FnMut(&ToolCallRecord),
+    ) -> AdoptionEnd {
+        loop {
+            // Checked first so that the last entries are read after the exit
+            let alive = self.is_child_alive();
+            for call in self.poll().await {
+                on_call(&call);
+            }
+            if !alive {
+                return AdoptionEnd::ChildExited;
+            }
+            if let Some(end) = self.check_limits() {
+                tracing::warn!(end = ?end, "Stopping the adopted session");
+                self.terminate(DEFAULT_TERMINATE_TIMEOUT).await;
+                return end;
+            }
+            tokio::select! {
+                () = cancel.cancelled() => return AdoptionEnd::Cancelled,
+                () = tokio::time::sleep(interval) => {}
+            }
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use std::io::Write;
+
+    use super::*;
+    use crate::ipc::{HandoffState, TimeBoxCounters, HANDOFF_PROTOCOL};
+
+    fn tool_use(id: &str) -> String {
+        format!(
+            r#"{{"type":"assistant","uuid":"a-{id}","sessionId":"session-1","timestamp":"2026-10-15T12:00:00Z","message":{{"role":"assistant","content":[{{"type":"tool_use","id":"{id}","name":"Bash","input":{{"command":"ls"}}}}]}},"cwd":"/tmp","version":"2.1.25"}}"#
+        )
+    }
+
+    fn tool_result(id: &str) -> String {
+        format!(
+            r#"{{"type":"user","uuid":"u-{id}","sessionId":"session-1","timestamp":"2026-10-15T12:00:01Z","message":{{"role":"user","content":"Tool result"}},"userType":"tool_result","cwd":"/tmp","version":"2.1.25","sourceToolUseId":"{id}","toolUseResult":{{"stdout":"ok"}}}}"#
+        )
+    }
+
+    fn costly_turn(id: &str, output_tokens: u64) -> String {
+        format!(
+            r#"{{"type":"assistant","uuid":"a-{id}","sessionId":"session-1","timestamp":"2026-10-15T12:00:00Z","message":{{"id":"{id}","role":"assistant","content":[{{"type":"text","text":"Working"}}],"usage":{{"input_tokens":0,"output_tokens":{output_tokens}}}}},"cwd":"/tmp","version":"2.1.25"}}"#
+        )
+    }
+
+    /// A running `sleep` and the thread reaping it, as the old supervisor
+    /// reaps Claude.
+    fn sleeping_child() -> (u32, std::thread::JoinHandle<()>) {
+        let mut child = std::process::Command::new("sleep")
+            .arg("30")
+            .spawn()
+            .unwrap();
+        let pid = child.id();
+        let reaper = std::thread::spawn(move || {
+            child.wait().unwrap();
+        });
+        (pid, reaper)
+    }
+
+    async fn follow_briefly(adopted: &mut AdoptedSession) -> AdoptionEnd {
+        tokio::time::timeout(
+            Duration::from_secs(10),
+            adopted.follow(Duration::from_millis(10), &CancellationToken::new(), |_| {}),
+        )
+        .await
+        .expect("the adopted session was not stopped")
+    }
+
+    #[tokio::test]
+    async fn test_adopted_session_enforces_carried_budget() {
+        let dir = tempfile::tempdir().unwrap();
+        let path = dir.path().join("session-1.jsonl");
+        let mut file = std::fs::File::create(&path).unwrap();
+
+        let state = HandoffState::new();
+        state.add_session("session-1", Some(path.clone()));
+        let (pid, reaper) = sleeping_child();
+        state.set_child(Some(pid));
+        // $0.90 of $1.00 spent before the handoff
+        state.set_cost(
+            900_000,
+            Some(BudgetCounters {
+                limit_usd: 1.0,
+                spent_usd: 0.9,
+                alert_percent: vec![50],
+            }),
+        );
+        let snapshot = state.hand_off();
+
+        let follower = HandoffState::from_snapshot(&snapshot);
+        let mut adopted = AdoptedSession::from_snapshot(&snapshot).with_handoff(follower.clone());
+        assert!(adopted.check_limits().is_none());
+
+        // 10k output tokens cost $0.15
+        writeln!(file, "{}", costly_turn("msg_1", 10_000)).unwrap();
+        let end = follow_briefly(&mut adopted).await;
+        assert!(matches!(
+            end,
+            AdoptionEnd::Killed {
+                cause: KillCause::Budget,
+                ..
+            }
+        ));
+        assert_eq!(end.exit_code(), KillCause::Budget.exit_code());
+        assert_eq!(adopted.cost_micros(), 1_050_000);
+        assert_eq!(follower.snapshot().cost_micros, 1_050_000);
+        reaper.join().unwrap();
+        assert!(!adopted.is_child_alive());
+    }
+
+    #[tokio::test]
+    async fn test_adopted_session_enforces_time_limit() {
+        let state = HandoffState::new();
+        let (pid, reaper) = sleeping_child();
+        state.set_child(Some(pid));
+        state.set_time_box(Some(TimeBoxCounters {
+            limit_secs: 90,
+            expires_at: chrono::Utc::now() - chrono::Duration::seconds(1),
+        }));
+        let snapshot = state.hand_off();
+
+        let mut adopted = AdoptedSession::from_snapshot(&snapshot);
+        assert_eq!(adopted.time_remaining(), Some(Duration::ZERO));
+        let end = follow_briefly(&mut adopted).await;
+        assert_eq!(
+            end,
+            AdoptionEnd::TimedOut {
+                limit: Duration::from_secs(90)
+            }
+        );
+        assert_eq!(end.exit_code(), TIMED_OUT_EXIT_CODE);
+        reaper.join().unwrap();
+    }
+
+    #[tokio::test]
+    async fn test_adopted_session_halts_on_kill_switch() {
+        let dir = tempfile::tempdir().unwrap();
+        let switch = dir.path().join("halt");
+        let state = HandoffState::new();
+        let (pid, reaper) = sleeping_child();
+        state.set_child(Some(pid));
+        let snapshot = state.hand_off();
+
+        let mut adopted =
+            AdoptedSession::from_snapshot(&snapshot).with_kill_switch(KillSwitch::new(&switch));
+        assert!(adopted.check_limits().is_none());
+        std::fs::write(&switch, "").unwrap();
+        let end = follow_briefly(&mut adopted).await;
+        assert_eq!(end, AdoptionEnd::Halted);
+        assert_eq!(end.exit_code(), HALTED_EXIT_CODE);
+        reaper.join().unwrap();
+    }
+
+    #[tokio::test]
+    async fn test_adopted_session_reads_only_new_tool_calls() {
+        let dir = tempfile::tempdir().unwrap();
+        let path = dir.path().join("session-1.jsonl");
+        let mut file = std::fs::File::create(&path).unwrap();
+        writeln!(
+            file,
+            "{}\n{}",
+            tool_use("toolu_old"),
+            tool_result("toolu_old")
+        )
+        .unwrap();
+
+        let state = HandoffState::new();
+        state.add_session("session-1", Some(path.clone()));
+        let mut child = std::process::Command::new("sleep")
+            .arg("30")
+            .spawn()
+            .unwrap();
+        state.set_child(Some(child.id()));
+        let snapshot = state.hand_off();
+        assert_eq!(snapshot.protocol, HANDOFF_PROTOCOL);
+
+        let mut adopted = AdoptedSession::from_snapshot(&snapshot);
+        assert!(adopted.is_child_alive());
+        assert!(adopted.poll().await.is_empty());
+
+        writeln!(
+            file,
+            "{}\n{}",
+            tool_use("toolu_new"),
+            tool_result("toolu_new")
+        )
+        .unwrap();
+        child.kill().unwrap();
+        child.wait().unwrap();
+
+        let mut seen = Vec::new();
+        let end = adopted
+            .follow(
+                Duration::from_millis(10),
+                &CancellationToken::new(),
+                |call| {
+                    seen.push(call.tool_use_id.clone());
+                },
+            )
+            .await;
+        assert_eq!(end, AdoptionEnd::ChildExited);
+        assert_eq!(seen, ["toolu_new"]);
+        assert_eq!(adopted.tool_calls().len(), 1);
+    }
+}
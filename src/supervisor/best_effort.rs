@@ -0,0 +1,290 @@
+//! Degradation policy for optional subsystems.
+//!
+//! Mirrors and logs written while a session runs, such as the transcript
+//! mirror and the dead-letter log, must never stop supervision. Each is
+//! wrapped in a [`BestEffort`] that counts consecutive failures, disables
+//! the subsystem once a threshold is reached, and tries it again after a
+//! backoff that doubles while the subsystem keeps failing.
+
+use std::fmt::Display;
+use std::time::{Duration, Instant};
+
+use serde::Serialize;
+
+/// Consecutive failures after which a subsystem is disabled.
+pub const DEFAULT_FAILURE_THRESHOLD: u32 = 3;
+
+/// Time a disabled subsystem waits before it is tried again.
+pub const DEFAULT_RETRY_BACKOFF: Duration = Duration::from_secs(30);
+
+/// Longest wait between retries of a subsystem that keeps failing.
+const MAX_RETRY_BACKOFF: Duration = Duration::from_mins(10);
+
+/// A subsystem was disabled or enabled again.
+#[derive(Debug, Clone, PartialEq, Eq, Serialize)]
+#[serde(tag = "state", rename_all = "snake_case")]
+pub enum HealthChange {
+    /// Disabled after too many consecutive failures.
+    Disabled {
+        /// Name of the subsystem.
+        subsystem: &'static str,
+        /// The last error.
+        error: String,
+        /// Seconds until the subsystem is tried again.
+        retry_in_secs: u64,
+    },
+    /// Enabled again after a retry succeeded.
+    Enabled {
+        /// Name of the subsystem.
+        subsystem: &'static str,
+    },
+}
+
+impl HealthChange {
+    /// One-line description for the terminal.
+    #[must_use]
+    pub fn describe(&self) -> String {
+        match self {
+            Self::Disabled {
+                subsystem,
+                error,
+                retry_in_secs,
+            } => format!(
+                "{subsystem} disabled after repeated failures ({error}), retrying in {retry_in_secs}s"
+            ),
+            Self::Enabled { subsystem } => format!("{subsystem} recovered and is enabled again"),
+        }
+    }
+}
+
+/// An optional subsystem whose failures are counted instead of propagated.
+#[derive(Debug)]
+pub struct BestEffort<T> {
+    name: &'static str,
+    inner: T,
+    threshold: u32,
+    base_backoff: Duration,
+    backoff: Duration,
+    failures: u32,
+    /// When a disabled subsystem is tried again; `None` while enabled.
+    retry_at: Option<Instant>,
+    change: Option<HealthChange>,
+}
+
+impl<T> BestEffort<T> {
+    /// Wrap `inner`, named `name` in warnings, with the default policy.
+    #[must_use]
+    pub fn new(name: &'static str, inner: T) -> Self {
+        Self {
+            name,
+            inner,
+            threshold: DEFAULT_FAILURE_THRESHOLD,
+            base_backoff: DEFAULT_RETRY_BACKOFF,
+            backoff: DEFAULT_RETRY_BACKOFF,
+            failures: 0,
+            retry_at: None,
+            change: None,
+        }
+    }
+
+    /// Disable the subsystem after `threshold` consecutive failures.
+    #[must_use]
+    pub fn with_threshold(mut self, threshold: u32) -> Self {
+        self.threshold = threshold.max(1);
+        self
+    }
+
+    /// Wait `backoff` before trying a disabled subsystem again.
+    #[must_use]
+    pub fn with_backoff(mut self, backoff: Duration) -> Self {
+        self.base_backoff = backoff;
+        self.backoff = backoff;
+        self
+    }
+
+    /// Name of the subsystem.
+    #[must_use]
+    pub fn name(&self) -> &'static str {
+        self.name
+    }
+
+    /// The wrapped subsystem.
+    #[must_use]
+    pub fn get_ref(&self) -> &T {
+        &self.inner
+    }
+
+    /// Whether the subsystem is enabled.
+    #[must_use]
+    pub fn is_enabled(&self) -> bool {
+        self.retry_at.is_none()
+    }
+
+    /// Failures since the last success.
+    #[must_use]
+    pub fn consecutive_failures(&self) -> u32 {
+        self.failures
+    }
+
+    /// Run `op` on the subsystem, unless it is disabled and not yet due
+    /// for a retry.
+    ///
+    /// Returns the result of `op`, or `None` if it failed or was skipped.
+    pub fn run<R, E: Display>(&mut self, op: impl FnOnce(&mut T) -> Result<R, E>) -> Option<R> {
+        self.run_at(Instant::now(), op)
+    }
+
+    fn run_at<R, E: Display>(
+        &mut self,
+        now: Instant,
+        op: impl FnOnce(&mut T) -> Result<R, E>,
+    ) -> Option<R> {
+        let retrying = match self.retry_at {
+            Some(at) if now < at => return None,
+            Some(_) => true,
+            None => false,
+        };
+
+        match op(&mut self.inner) {
+            Ok(value) => {
+                self.failures = 0;
+                if retrying {
+                    self.retry_at = None;
+                    self.backoff = self.base_backoff;
+                    self.change = Some(HealthChange::Enabled {
+                        subsystem: self.name,
+                    });
+                }
+                Some(value)
+            }
+            Err(e) => {
+                self.failures += 1;
+                if retrying {
+                    // Already reported as disabled; just wait longer
+                    self.backoff = (self.backoff * 2).min(MAX_RETRY_BACKOFF);
+                    self.retry_at = Some(now + self.backoff);
+                    tracing::debug!(subsystem = self.name, error = %e, "Retry of disabled subsystem failed");
+                } else if self.failures >= self.threshold {
+                    self.retry_at = Some(now + self.backoff);
+                    self.change = Some(HealthChange::Disabled {
+                        subsystem: self.name,
+                        error: e.to_string(),
+                        retry_in_secs: self.backoff.as_secs(),
+                    });
+                } else {
+                    tracing::warn!(subsystem = self.name, error = %e, failures = self.failures, "Subsystem failed");
+                }
+                None
+            }
+        }
+    }
+
+    /// Take the change of state of the last call to [`Self::run`], to be
+    /// reported once.
+    pub fn take_change(&mut self) -> Option<HealthChange> {
+        self.change.take()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    /// A sink that fails while `failing` is set.
+    #[derive(Default)]
+    struct Sink {
+        failing: bool,
+        written: u32,
+    }
+
+    fn write(sink: &mut Sink) -> Result<u32, &'static str> {
+        if sink.failing {
+            return Err("No space left on device");
+        }
+        sink.written += 1;
+        Ok(sink.written)
+    }
+
+    #[test]
+    fn test_disabled_after_consecutive_failures() {
+        let mut sink = BestEffort::new("audit", Sink::default()).with_threshold(3);
+        let now = Instant::now();
+
+        sink.inner.failing = true;
+        assert_eq!(sink.run_at(now, write), None);
+        assert_eq!(sink.run_at(now, write), None);
+        assert!(sink.is_enabled());
+        assert_eq!(sink.take_change(), None);
+
+        assert_eq!(sink.run_at(now, write), None);
+        assert!(!sink.is_enabled());
+        assert_eq!(
+            sink.take_change(),
+            Some(HealthChange::Disabled {
+                subsystem: "audit",
+                error: "No space left on device".to_string(),
+                retry_in_secs: 30,
+            })
+        );
+        // Reported once
+        assert_eq!(sink.take_change(), None);
+
+        // Skipped while disabled, even once the sink works again
+        sink.inner.failing = false;
+        assert_eq!(sink.run_at(now + Duration::from_secs(29), write), None);
+        assert_eq!(sink.get_ref().written, 0);
+    }
+
+    #[test]
+    fn test_success_resets_the_count() {
+        let mut sink = BestEffort::new("audit", Sink::default()).with_threshold(2);
+        let now = Instant::now();
+        for _ in 0..5 {
+            sink.inner.failing = true;
+            assert_eq!(sink.run_at(now, write), None);
+            sink.inner.failing = false;
+            assert!(sink.run_at(now, write).is_some());
+        }
+        assert!(sink.is_enabled());
+        assert_eq!(sink.consecutive_failures(), 0);
+    }
+
+    #[test]
+    fn test_reenabled_after_backoff() {
+        let backoff = Duration::from_secs(10);
+        let mut sink = BestEffort::new("mirror", Sink::default())
+            .with_threshold(1)
+            .with_backoff(backoff);
+        let start = Instant::now();
+
+        sink.inner.failing = true;
+        sink.run_at(start, write);
+        assert!(matches!(
+            sink.take_change(),
+            Some(HealthChange::Disabled { .. })
+        ));
+
+        // A failed retry doubles the wait without a second notice
+        let first_retry = start + backoff;
+        assert_eq!(sink.run_at(first_retry, write), None);
+        assert_eq!(sink.take_change(), None);
+        sink.inner.failing = false;
+        assert_eq!(sink.run_at(first_retry + backoff, write), None);
+
+        let second_retry = first_retry + backoff * 2;
+        assert_eq!(sink.run_at(second_retry, write), Some(1));
+        assert!(sink.is_enabled());
+        assert_eq!(
+            sink.take_change(),
+            Some(HealthChange::Enabled {
+                subsystem: "mirror"
+            })
+        );
+
+        // The backoff starts over after a recovery
+        sink.inner.failing = true;
+        sink.run_at(second_retry, write);
+        sink.inner.failing = false;
+        assert_eq!(sink.run_at(second_retry + backoff, write), Some(2));
+    }
+}
@@ -0,0 +1,386 @@
+//! Cumulative blast radius of a session's mutating operations.
+//!
+//! Per-call rules judge each tool call on its own, so forty single-file
+//! deletions never trip them. [`BlastRadius`] keeps a decaying, weighted
+//! score of everything a session has changed and escalates or denies once
+//! the score crosses the thresholds in [`BlastRadiusConfig`].
+
+use std::collections::VecDeque;
+use std::time::Duration;
+
+use tokio::time::Instant;
+
+use crate::config::{BlastRadiusConfig, MutationWeights};
+
+/// Operations older than this many half-lives are dropped from the score.
+const MAX_HALF_LIVES: u32 = 8;
+
+/// Kind of a mutating operation.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum MutationKind {
+    /// A file is deleted.
+    Delete,
+    /// A tool writes or edits a file.
+    Write,
+    /// A shell command writes files.
+    ShellWrite,
+    /// A shell command moves or renames files.
+    ShellMove,
+    /// A shell command changes permissions or ownership.
+    ShellPermissions,
+    /// A git command rewrites history or the remote.
+    ShellVcs,
+    /// A package is installed or removed.
+    ShellInstall,
+}
+
+impl MutationKind {
+    /// Mutating operations a tool call performs; empty for read-only calls.
+    ///
+    /// A Bash command counts once per mutating segment, so `rm a && rm b`
+    /// is two deletions.
+    #[must_use]
+    pub fn classify(tool_name: &str, tool_input: &serde_json::Value) -> Vec<Self> {
+        match tool_name {
+            "Write" | "Edit" | "MultiEdit" | "NotebookEdit" => vec![Self::Write],
+            "Bash" => tool_input
+                .get("command")
+                .and_then(serde_json::Value::as_str)
+                .map(|command| {
+                    command
+                        .split(['\n', ';', '|', '&'])
+                        .filter_map(classify_segment)
+                        .collect()
+                })
+                .unwrap_or_default(),
+            _ => Vec::new(),
+        }
+    }
+
+    /// Score of this kind of operation.
+    #[must_use]
+    pub fn weight(self, weights: &MutationWeights) -> u32 {
+        match self {
+            Self::Delete => weights.delete,
+            Self::Write => weights.write,
+            Self::ShellWrite => weights.shell_write,
+            Self::ShellMove => weights.shell_move,
+            Self::ShellPermissions => weights.shell_permissions,
+            Self::ShellVcs => weights.shell_vcs,
+            Self::ShellInstall => weights.shell_install,
+        }
+    }
+}
+
+/// Kind of the mutation one shell segment performs, if any.
+fn classify_segment(segment: &str) -> Option<MutationKind> {
+    let mut words = segment.split_whitespace().skip_while(|word| {
+        matches!(*word, "sudo" | "env" | "command" | "exec") || word.contains('=')
+    });
+    let program = words.next()?.rsplit('/').next()?;
+    let args: Vec<&str> = words.collect();
+    let subcommand = args.iter().find(|arg| !arg.starts_with('-')).copied();
+
+    let kind = match program {
+        "rm" | "rmdir" | "unlink" | "shred" => Some(MutationKind::Delete),
+        "find" if args.iter().any(|arg| *arg == "-delete" || *arg == "rm") => {
+            Some(MutationKind::Delete)
+        }
+        "mv" => Some(MutationKind::ShellMove),
+        "chmod" | "chown" | "chgrp" => Some(MutationKind::ShellPermissions),
+        "git" => match subcommand? {
+            "rm" | "clean" => Some(MutationKind::Delete),
+            "mv" => Some(MutationKind::ShellMove),
+            "commit" | "push" | "reset" | "rebase" | "merge" | "checkout" | "switch"
+            | "restore" | "cherry-pick" | "revert" | "stash" => Some(MutationKind::ShellVcs),
+            _ => None,
+        },
+        "npm" | "pnpm" | "yarn" | "pip" | "pip3" | "cargo" | "apt" | "apt-get" | "brew" | "gem" => {
+            match subcommand? {
+                "install" | "i" | "add" | "uninstall" | "remove" | "rm" => {
+                    Some(MutationKind::ShellInstall)
+                }
+                _ => None,
+            }
+        }
+        "cp" | "tee" | "touch" | "mkdir" | "truncate" | "dd" | "ln" => {
+            Some(MutationKind::ShellWrite)
+        }
+        "sed" | "perl" if args.iter().any(|arg| arg.starts_with("-i")) => {
+            Some(MutationKind::ShellWrite)
+        }
+        _ => None,
+    };
+    kind.or_else(|| writes_by_redirect(segment).then_some(MutationKind::ShellWrite))
+}
+
+/// Whether a segment redirects output into a file other than `/dev/null`.
+fn writes_by_redirect(segment: &str) -> bool {
+    segment.match_indices('>').any(|(index, _)| {
+        let target = segment[index + 1..].trim_start_matches('>').trim_start();
+        !segment[..index].ends_with(|c: char| c.is_ascii_digit())
+            && !target.is_empty()
+            && !target.starts_with("/dev/null")
+    })
+}
+
+/// What crossing a blast radius threshold calls for.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum BlastRadiusVerdict {
+    /// The escalation threshold was crossed.
+    Escalate(String),
+    /// The hard cap is reached.
+    Deny(String),
+}
+
+/// Decaying, weighted score of a session's mutating operations.
+#[derive(Debug, Clone)]
+pub struct BlastRadius {
+    config: BlastRadiusConfig,
+    operations: VecDeque<(Instant, u32)>,
+    peak: f64,
+    escalated: bool,
+}
+
+impl Default for BlastRadius {
+    fn default() -> Self {
+        Self::new(BlastRadiusConfig::default())
+    }
+}
+
+impl BlastRadius {
+    /// Create an empty score with the given weights and thresholds.
+    #[must_use]
+    pub fn new(config: BlastRadiusConfig) -> Self {
+        Self {
+            config,
+            operations: VecDeque::new(),
+            peak: 0.0,
+            escalated: false,
+        }
+    }
+
+    /// The weights and thresholds in use.
+    #[must_use]
+    pub fn config(&self) -> &BlastRadiusConfig {
+        &self.config
+    }
+
+    /// Replace the weights and thresholds, keeping recorded operations.
+    pub fn set_config(&mut self, config: BlastRadiusConfig) {
+        self.config = config;
+    }
+
+    /// Score at `now`, with each operation halved per half-life since it ran.
+    #[must_use]
+    pub fn score(&self, now: Instant) -> f64 {
+        let half_life = self.half_life().as_secs_f64();
+        self.operations
+            .iter()
+            .map(|(at, weight)| {
+                let age = now.saturating_duration_since(*at).as_secs_f64();
+                f64::from(*weight) * 0.5_f64.powf(age / half_life)
+            })
+            .sum()
+    }
+
+    /// Highest score the session reached.
+    #[must_use]
+    pub fn peak(&self) -> f64 {
+        self.peak
+    }
+
+    /// Operations recorded within the last half-life.
+    #[must_use]
+    pub fn recent_operations(&self, now: Instant) -> usize {
+        let half_life = self.half_life();
+        self.operations
+            .iter()
+            .filter(|(at, _)| now.saturating_duration_since(*at) <= half_life)
+            .count()
+    }
+
+    /// Record the operations of one tool call and check the thresholds.
+    ///
+    /// Escalates once per crossing of `escalate_at`, re-arming when the
+    /// score decays below it again; denies for as long as the score is at
+    /// `deny_at`. Never returns a verdict when disabled.
+    pub fn record(&mut self, kinds: &[MutationKind], now: Instant) -> Option<BlastRadiusVerdict> {
+        let cutoff = self.half_life() * MAX_HALF_LIVES;
+        while self
+            .operations
+            .front()
+            .is_some_and(|(at, _)| now.saturating_duration_since(*at) > cutoff)
+        {
+            self.operations.pop_front();
+        }
+        for kind in kinds {
+            let weight = kind.weight(&self.config.weights);
+            if weight > 0 {
+                self.operations.push_back((now, weight));
+            }
+        }
+
+        let score = self.score(now);
+        self.peak = self.peak.max(score);
+        let escalate_at = f64::from(self.config.escalate_at);
+        if score < escalate_at {
+            self.escalated = false;
+        }
+        if !self.config.enabled || kinds.is_empty() {
+            return None;
+        }
+
+        if score >= f64::from(self.config.deny_at) {
+            Some(BlastRadiusVerdict::Deny(self.describe(
+                now,
+                score,
+                "hard cap",
+                self.config.deny_at,
+            )))
+        } else if score >= escalate_at && !self.escalated {
+            self.escalated = true;
+            Some(BlastRadiusVerdict::Escalate(self.describe(
+                now,
+                score,
+                "escalation threshold",
+                self.config.escalate_at,
+            )))
+        } else {
+            None
+        }
+    }
+
+    fn half_life(&self) -> Duration {
+        Duration::from_secs(self.config.half_life_secs.max(1))
+    }
+
+    fn describe(&self, now: Instant, score: f64, limit: &str, threshold: u32) -> String {
+        let secs = self.half_life().as_secs();
+        let window = if secs.is_multiple_of(60) {
+            format!("{} minutes", secs / 60)
+        } else {
+            format!("{secs} seconds")
+        };
+        format!(
+            "Session has performed {} mutating operations in the last {window} \
+             (blast radius {score:.0}, {limit} {threshold})",
+            self.recent_operations(now)
+        )
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use serde_json::json;
+
+    fn bash(command: &str) -> Vec<MutationKind> {
+        MutationKind::classify("Bash", &json!({ "command": command }))
+    }
+
+    #[test]
+    fn test_classify() {
+        assert_eq!(bash("ls -la && cat a.txt 2>&1"), vec![]);
+        assert_eq!(
+            bash("rm a.txt && sudo rm -rf build; git rm -q b.rs"),
+            vec![MutationKind::Delete; 3]
+        );
+        assert_eq!(
+            bash("git commit -m x && git push origin main"),
+            vec![MutationKind::ShellVcs; 2]
+        );
+        assert_eq!(bash("echo hi > out.txt"), vec![MutationKind::ShellWrite]);
+        assert_eq!(bash("make > /dev/null"), vec![]);
+        assert_eq!(bash("sed -i s/a/b/ f.rs"), vec![MutationKind::ShellWrite]);
+        assert_eq!(
+            bash("npm install left-pad"),
+            vec![MutationKind::ShellInstall]
+        );
+        assert_eq!(bash("cargo build"), vec![]);
+        assert_eq!(
+            MutationKind::classify("Edit", &json!({"file_path": "/a.rs"})),
+            vec![MutationKind::Write]
+        );
+        assert_eq!(MutationKind::classify("Read", &json!({})), vec![]);
+    }
+
+    #[test]
+    fn test_escalates_once_then_denies_at_cap() {
+        let config = BlastRadiusConfig {
+            escalate_at: 20,
+            deny_at: 50,
+            ..BlastRadiusConfig::default()
+        };
+        let mut radius = BlastRadius::new(config);
+        let now = Instant::now();
+
+        // Four deletions score 20 and cross the escalation threshold
+        for _ in 0..3 {
+            assert_eq!(radius.record(&[MutationKind::Delete], now), None);
+        }
+        let Some(BlastRadiusVerdict::Escalate(reason)) =
+            radius.record(&[MutationKind::Delete], now)
+        else {
+            panic!("expected an escalation");
+        };
+        assert_eq!(
+            reason,
+            "Session has performed 4 mutating operations in the last 5 minutes \
+             (blast radius 20, escalation threshold 20)"
+        );
+
+        // Escalated once; the next five pass until the cap at 50
+        for _ in 0..5 {
+            assert_eq!(radius.record(&[MutationKind::Delete], now), None);
+        }
+        assert!(matches!(
+            radius.record(&[MutationKind::Delete], now),
+            Some(BlastRadiusVerdict::Deny(_))
+        ));
+        // Read-only calls are never denied
+        assert_eq!(radius.record(&[], now), None);
+        assert!((radius.peak() - 50.0).abs() < 1e-9);
+    }
+
+    #[test]
+    fn test_score_decays_and_rearms() {
+        let config = BlastRadiusConfig {
+            escalate_at: 10,
+            ..BlastRadiusConfig::default()
+        };
+        let mut radius = BlastRadius::new(config);
+        let start = Instant::now();
+        assert!(radius
+            .record(&[MutationKind::Delete, MutationKind::Delete], start)
+            .is_some());
+
+        // One half-life later the score is 5 and the threshold re-arms
+        let later = start + Duration::from_mins(5);
+        assert!((radius.score(later) - 5.0).abs() < 1e-9);
+        assert_eq!(radius.recent_operations(later), 2);
+        assert_eq!(radius.record(&[MutationKind::Write], later), None);
+        assert!(radius
+            .record(&[MutationKind::Delete], later)
+            .is_some_and(|verdict| matches!(verdict, BlastRadiusVerdict::Escalate(_))));
+
+        // Old operations are eventually dropped
+        let much_later = start + Duration::from_hours(2);
+        radius.record(&[], much_later);
+        assert_eq!(radius.recent_operations(much_later), 0);
+        assert!(radius.score(much_later) < f64::EPSILON);
+    }
+
+    #[test]
+    fn test_disabled_only_tracks() {
+        let config = BlastRadiusConfig {
+            enabled: false,
+            deny_at: 1,
+            ..BlastRadiusConfig::default()
+        };
+        let mut radius = BlastRadius::new(config);
+        let now = Instant::now();
+        assert_eq!(radius.record(&[MutationKind::Delete], now), None);
+        assert!((radius.score(now) - 5.0).abs() < 1e-9);
+    }
+}
@@ -2,24 +2,65 @@
 //!
 //! This module provides pattern-based blocking of dangerous commands,
 //! categorized by type of risk (destructive, privilege escalation, etc.).
+//! Rules match a regex, which catches variations such as `rm  -rf` or
+//! `rm -fr`, or a plain substring of the command. Both kinds are compiled
+//! into one set, so a command is scanned once however many rules there are.
 
-use regex::Regex;
+use std::fmt;
+use std::sync::OnceLock;
+
+use regex::{Regex, RegexSet};
 use serde::{Deserialize, Serialize};
 
+/// Prefix of the reasons given for escalated blocklist matches.
+pub const BLOCKLIST_ESCALATION_REASON: &str = "Blocklist";
+
+/// Regex prefix matching where a command of a command line starts: its
+/// beginning, after an operator or an opening subshell, and past `VAR=value`
+/// assignments, wrappers such as `env` or `command`, and a directory.
+const COMMAND_START: &str = r"(^|[;&|(`\n]|\$\()\s*(((\S*/)?(env|command|exec|nohup|time|nice|builtin)(\s+-\S+)*|\w+=\S*)\s+)*(\S*/)?";
+
 /// Category of blocked command.
-#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+#[derive(Debug, Clone, Copy, PartialEq, Eq, PartialOrd, Ord, Serialize, Deserialize)]
+#[cfg_attr(test, derive(schemars::JsonSchema))]
 #[serde(rename_all = "snake_case")]
 pub enum RuleCategory {
     /// Commands that destroy data (rm -rf, mkfs, dd).
     Destructive,
-    /// Commands that escalate privileges (sudo, chmod 777).
+    /// Commands that loosen permissions or ownership (chmod 777, chown root).
     Privilege,
+    /// Commands that run as another user or grant it (sudo, su, doas,
+    /// pkexec, writes to /etc/sudoers).
+    PrivilegeEscalation,
     /// Commands that could exfiltrate data (curl | sh).
     NetworkExfil,
     /// Commands that access secrets (.ssh, .aws, shadow).
     SecretAccess,
     /// Commands that modify system configuration (/etc).
     SystemModification,
+    /// Commands forbidden by the project's CLAUDE.md.
+    Project,
+    /// Commands blocked by the supervisor config.
+    Custom,
+    /// MCP tools blocked by name.
+    Mcp,
+}
+
+impl fmt::Display for RuleCategory {
+    /// The category's name in config files.
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.write_str(match self {
+            Self::Destructive => "destructive",
+            Self::Privilege => "privilege",
+            Self::PrivilegeEscalation => "privilege_escalation",
+            Self::NetworkExfil => "network_exfil",
+            Self::SecretAccess => "secret_access",
+            Self::SystemModification => "system_modification",
+            Self::Project => "project",
+            Self::Custom => "custom",
+            Self::Mcp => "mcp",
+        })
+    }
 }
 
 /// Error type for blocklist operations.
@@ -28,18 +69,36 @@ pub enum BlocklistError {
     /// Invalid regex pattern.
     #[error("Invalid regex pattern: {0}")]
     InvalidPattern(#[from] regex::Error),
+    /// Empty substring pattern, which would match every command.
+    #[error("Empty blocklist pattern")]
+    EmptyPattern,
+}
+
+/// How a rule's pattern is matched against a command.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum PatternKind {
+    /// The pattern is a regex found anywhere in the command.
+    Regex,
+    /// The pattern is text found as is anywhere in the command.
+    Substring,
 }
 
 /// A single blocklist rule with category and pattern.
 #[derive(Debug, Clone)]
 pub struct BlocklistRule {
     category: RuleCategory,
-    pattern: Regex,
+    kind: PatternKind,
+    /// The pattern as given.
+    pattern: String,
+    /// The pattern as a regex, escaped for substrings.
+    regex: Regex,
     description: String,
 }
 
 impl BlocklistRule {
-    /// Create a new blocklist rule.
+    /// Create a new blocklist rule matching the regex `pattern`, as
+    /// [`regex`](Self::regex) does.
     ///
     /// # Errors
     ///
@@ -48,10 +107,49 @@ impl BlocklistRule {
         category: RuleCategory,
         pattern: &str,
         description: impl Into<String>,
+    ) -> Result<Self, BlocklistError> {
+        Self::regex(category, pattern, description)
+    }
+
+    /// Create a rule matching commands in which the regex `pattern` is
+    /// found, such as `rm\s+-\w*r` for any recursive `rm`.
+    ///
+    /// # Errors
+    ///
+    /// Returns `BlocklistError::InvalidPattern` if the regex is invalid.
+    pub fn regex(
+        category: RuleCategory,
+        pattern: &str,
+        description: impl Into<String>,
     ) -> Result<Self, BlocklistError> {
         Ok(Self {
             category,
-            pattern: Regex::new(pattern)?,
+            kind: PatternKind::Regex,
+            pattern: pattern.to_string(),
+            regex: Regex::new(pattern)?,
+            description: description.into(),
+        })
+    }
+
+    /// Create a rule matching commands that contain `pattern` as is.
+    ///
+    /// # Errors
+    ///
+    /// Returns `BlocklistError::EmptyPattern` if `pattern` is empty, or
+    /// `BlocklistError::InvalidPattern` if it is too long to compile.
+    pub fn substring(
+        category: RuleCategory,
+        pattern: &str,
+        description: impl Into<String>,
+    ) -> Result<Self, BlocklistError> {
+        if pattern.is_empty() {
+            return Err(BlocklistError::EmptyPattern);
+        }
+        Ok(Self {
+            category,
+            kind: PatternKind::Substring,
+            pattern: pattern.to_string(),
+            regex: Regex::new(&regex::escape(pattern))?,
             description: description.into(),
         })
     }
@@ -59,7 +157,13 @@ impl BlocklistRule {
     /// Check if the command matches this rule.
     #[must_use]
     pub fn matches(&self, command: &str) -> bool {
-        self.pattern.is_match(command)
+        self.regex.is_match(command)
+    }
+
+    /// How the pattern is matched.
+    #[must_use]
+    pub fn kind(&self) -> PatternKind {
+        self.kind
     }
 
     /// Get the rule category.
@@ -74,10 +178,10 @@ impl BlocklistRule {
         &self.description
     }
 
-    /// Get the pattern string (for debugging/display).
+    /// Get the pattern string as given (for debugging/display).
     #[must_use]
     pub fn pattern(&self) -> &str {
-        self.pattern.as_str()
+        &self.pattern
     }
 }
 
@@ -85,13 +189,17 @@ impl BlocklistRule {
 #[derive(Debug, Clone, Default)]
 pub struct Blocklist {
     rules: Vec<BlocklistRule>,
+    /// Rules matched against MCP tool names rather than commands.
+    mcp_rules: Vec<BlocklistRule>,
+    /// All rule patterns compiled together, built on first check.
+    set: OnceLock<Option<RegexSet>>,
 }
 
 impl Blocklist {
     /// Create an empty blocklist.
     #[must_use]
     pub fn new() -> Self {
-        Self { rules: Vec::new() }
+        Self::default()
     }
 
     /// Create a blocklist with default security rules.
@@ -107,20 +215,76 @@ impl Blocklist {
                 }
             })
             .collect();
-        Self { rules }
+        Self {
+            rules,
+            mcp_rules: Vec::new(),
+            set: OnceLock::new(),
+        }
     }
 
     /// Add a rule to the blocklist.
     pub fn add_rule(&mut self, rule: BlocklistRule) {
         self.rules.push(rule);
+        self.set = OnceLock::new();
     }
 
-    /// Check a command against all rules.
+    /// Check a command against all rules, regex and substring alike.
     ///
-    /// Returns the first matching rule, if any.
+    /// Returns the first matching rule, if any. The command is scanned once
+    /// for all rules together.
     #[must_use]
     pub fn check(&self, command: &str) -> Option<&BlocklistRule> {
-        self.rules.iter().find(|rule| rule.matches(command))
+        match self.regex_set() {
+            Some(set) => set
+                .matches(command)
+                .iter()
+                .next()
+                .map(|index| &self.rules[index]),
+            None => self.rules.iter().find(|rule| rule.matches(command)),
+        }
+    }
+
+    /// Every rule matching a command, in the order they were added.
+    pub fn check_all<'a>(&'a self, command: &str) -> impl Iterator<Item = &'a BlocklistRule> {
+        let indices: Vec<usize> = match self.regex_set() {
+            Some(set) => set.matches(command).into_iter().collect(),
+            None => (0..self.rules.len())
+                .filter(|&index| self.rules[index].matches(command))
+                .collect(),
+        };
+        indices.into_iter().map(|index| &self.rules[index])
+    }
+
+    /// Add a rule matched against full MCP tool names.
+    pub fn add_mcp_rule(&mut self, rule: BlocklistRule) {
+        self.mcp_rules.push(rule);
+    }
+
+    /// Check an MCP tool name against the MCP rules.
+    ///
+    /// Returns the first matching rule, if any.
+    #[must_use]
+    pub fn check_mcp_tool(&self, tool_name: &str) -> Option<&BlocklistRule> {
+        self.mcp_rules.iter().find(|rule| rule.matches(tool_name))
+    }
+
+    /// Get the rules matched against MCP tool names.
+    #[must_use]
+    pub fn mcp_rules(&self) -> &[BlocklistRule] {
+        &self.mcp_rules
+    }
+
+    /// The combined pattern set, or `None` if it exceeds the regex size limit.
+    fn regex_set(&self) -> Option<&RegexSet> {
+        self.set
+            .get_or_init(|| {
+                RegexSet::new(self.rules.iter().map(|rule| rule.regex.as_str()))
+                    .inspect_err(|e| {
+                        tracing::debug!(error = %e, "Checking blocklist rules one by one");
+                    })
+                    .ok()
+            })
+            .as_ref()
     }
 
     /// Check if the blocklist is empty.
@@ -171,12 +335,34 @@ impl Blocklist {
                 r">\s*/dev/sd[a-z]",
                 "Direct write to block device",
             ),
-            // Privilege escalation
+            // Privilege escalation, wherever a command starts: after an
+            // operator, inside a subshell or behind `env` or `command`
             BlocklistRule::new(
-                RuleCategory::Privilege,
-                r"sudo\s+rm\s",
-                "Privileged deletion",
+                RuleCategory::PrivilegeEscalation,
+                &format!(r"{COMMAND_START}sudo(edit)?(\s|$)"),
+                "Running a command with sudo",
+            ),
+            BlocklistRule::new(
+                RuleCategory::PrivilegeEscalation,
+                &format!(r"{COMMAND_START}su(\s|$)"),
+                "Switching user with su",
+            ),
+            BlocklistRule::new(
+                RuleCategory::PrivilegeEscalation,
+                &format!(r"{COMMAND_START}doas(\s|$)"),
+                "Running a command with doas",
             ),
+            BlocklistRule::new(
+                RuleCategory::PrivilegeEscalation,
+                &format!(r"{COMMAND_START}pkexec(\s|$)"),
+                "Running a command with pkexec",
+            ),
+            BlocklistRule::new(
+                RuleCategory::PrivilegeEscalation,
+                r"(>|\btee\s+(-\S+\s+)*|\bvisudo\b.*)\s*/etc/sudoers",
+                "Writing to sudoers file",
+            ),
+            // Permission changes
             BlocklistRule::new(
                 RuleCategory::Privilege,
                 r"chmod\s+777\s",
@@ -187,11 +373,6 @@ impl Blocklist {
                 r"chown\s+root\s",
                 "Changing ownership to root",
             ),
-            BlocklistRule::new(
-                RuleCategory::Privilege,
-                r"sudo\s+chmod\s",
-                "Privileged permission change",
-            ),
             // Network exfiltration
             BlocklistRule::new(
                 RuleCategory::NetworkExfil,
@@ -245,11 +426,6 @@ impl Blocklist {
                 r">\s*/etc/passwd",
                 "Writing to passwd file",
             ),
-            BlocklistRule::new(
-                RuleCategory::SystemModification,
-                r">\s*/etc/sudoers",
-                "Writing to sudoers file",
-            ),
             BlocklistRule::new(
                 RuleCategory::SystemModification,
                 r":\(\)\s*\{\s*:\|:",
@@ -330,7 +506,7 @@ mod tests {
     fn test_blocklist_check_privilege() {
         let blocklist = Blocklist::with_default_rules();
 
-        let result = blocklist.check("sudo rm /etc/passwd");
+        let result = blocklist.check("chown root /usr/local/bin/tool");
         assert!(result.is_some());
         assert_eq!(result.unwrap().category(), RuleCategory::Privilege);
 
@@ -339,6 +515,41 @@ mod tests {
         assert_eq!(result.unwrap().category(), RuleCategory::Privilege);
     }
 
+    #[test]
+    fn test_blocklist_check_privilege_escalation() {
+        let blocklist = Blocklist::with_default_rules();
+
+        for command in [
+            "sudo rm -rf /tmp/foo",
+            "env sudo apt install jq",
+            "env -i PATH=/usr/bin sudo ls",
+            "command sudo -u postgres psql",
+            "cargo build && sudo make install",
+            "/usr/bin/sudo id",
+            "echo $(doas cat /etc/shadow)",
+            "su -",
+            "make; su root -c 'make install'",
+            "pkexec visudo",
+            "echo 'me ALL=(ALL) NOPASSWD: ALL' | tee -a /etc/sudoers",
+            "echo 'me ALL=(ALL) ALL' > /etc/sudoers.d/me",
+        ] {
+            let rule = blocklist.check(command);
+            assert_eq!(
+                rule.map(BlocklistRule::category),
+                Some(RuleCategory::PrivilegeEscalation),
+                "{command}"
+            );
+        }
+        for command in [
+            "grep sudo README.md",
+            "cargo run --bin sudoku",
+            "git log --grep su",
+            "cat /etc/sudoers",
+        ] {
+            assert!(blocklist.check(command).is_none(), "{command}");
+        }
+    }
+
     #[test]
     fn test_blocklist_check_network_exfil() {
         let blocklist = Blocklist::with_default_rules();
@@ -386,6 +597,40 @@ mod tests {
         assert!(result.is_some());
     }
 
+    #[test]
+    fn test_blocklist_check_returns_first_rule_after_adding() {
+        let mut blocklist = Blocklist::new();
+        blocklist.add_rule(BlocklistRule::new(RuleCategory::Project, r"deploy", "First").unwrap());
+        assert_eq!(
+            blocklist.check("deploy prod").unwrap().description(),
+            "First"
+        );
+
+        // Rules added after a check are still matched
+        blocklist.add_rule(BlocklistRule::new(RuleCategory::Project, r"prod", "Second").unwrap());
+        assert_eq!(
+            blocklist.check("deploy prod").unwrap().description(),
+            "First"
+        );
+        assert_eq!(blocklist.check("prod").unwrap().description(), "Second");
+    }
+
+    #[test]
+    fn test_mcp_rules_match_tool_names_only() {
+        let mut blocklist = Blocklist::new();
+        blocklist.add_mcp_rule(
+            BlocklistRule::new(RuleCategory::Mcp, r"^mcp__[^_]+__delete_", "No deletes").unwrap(),
+        );
+
+        let rule = blocklist
+            .check_mcp_tool("mcp__github__delete_repository")
+            .unwrap();
+        assert_eq!(rule.category(), RuleCategory::Mcp);
+        assert!(blocklist.check_mcp_tool("mcp__github__get_issue").is_none());
+        assert!(blocklist.check("mcp__github__delete_repository").is_none());
+        assert!(blocklist.is_empty());
+    }
+
     #[test]
     fn test_fork_bomb_detection() {
         let blocklist = Blocklist::with_default_rules();
@@ -394,4 +639,80 @@ mod tests {
         assert!(result.is_some());
         assert_eq!(result.unwrap().category(), RuleCategory::SystemModification);
     }
+
+    #[test]
+    fn test_regex_and_substring_rules_both_match() {
+        let mut blocklist = Blocklist::new();
+        blocklist.add_rule(
+            BlocklistRule::substring(RuleCategory::Project, "deploy --prod", "No prod deploys")
+                .unwrap(),
+        );
+        blocklist.add_rule(
+            BlocklistRule::regex(
+                RuleCategory::Destructive,
+                r"rm\s+(-\w+\s+)*-\w*(rf|fr)\w*\s+/",
+                "Recursive delete from root",
+            )
+            .unwrap(),
+        );
+
+        for command in ["rm  -rf /", "rm -fr /", "rm -v -rf /tmp"] {
+            let rule = blocklist.check(command).unwrap();
+            assert_eq!(rule.kind(), PatternKind::Regex, "{command}");
+        }
+        let rule = blocklist.check("make deploy --prod").unwrap();
+        assert_eq!(rule.kind(), PatternKind::Substring);
+        assert_eq!(rule.pattern(), "deploy --prod");
+        assert!(blocklist.check("rm -rf build").is_none());
+
+        // Substrings are not regexes
+        let dots = BlocklistRule::substring(RuleCategory::Project, "a.b", "Dots").unwrap();
+        assert!(dots.matches("cat a.b") && !dots.matches("cat axb"));
+        assert!(matches!(
+            BlocklistRule::substring(RuleCategory::Project, "", "Empty"),
+            Err(BlocklistError::EmptyPattern)
+        ));
+        assert!(matches!(
+            BlocklistRule::regex(RuleCategory::Project, "rm (", "Unclosed"),
+            Err(BlocklistError::InvalidPattern(_))
+        ));
+    }
+
+    #[test]
+    fn test_hundred_regex_rules_check_under_a_millisecond() {
+        let mut blocklist = Blocklist::with_default_rules();
+        for i in 0..100 {
+            blocklist.add_rule(
+                BlocklistRule::regex(
+                    RuleCategory::Project,
+                    &format!(r"tool{i}\s+(--force|-f)\s+\S*prod"),
+                    format!("Rule {i}"),
+                )
+                .unwrap(),
+            );
+        }
+        let commands: [&str; 4] = [
+            "cargo test --workspace -- --nocapture",
+            "git commit -m 'Add the thing' && git push origin main",
+            "tool42 --force eu-prod",
+            "npm run build 2>&1 | tee build.log",
+        ];
+        assert_eq!(
+            blocklist.check(commands[2]).unwrap().description(),
+            "Rule 42"
+        );
+
+        let rounds: u32 = 250;
+        let started = std::time::Instant::now();
+        for _ in 0..rounds {
+            for command in commands {
+                std::hint::black_box(blocklist.check(command));
+            }
+        }
+        let per_command = started.elapsed() / (rounds * 4);
+        assert!(
+            per_command < std::time::Duration::from_millis(1),
+            "{per_command:?} per command"
+        );
+    }
 }
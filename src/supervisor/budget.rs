@@ -0,0 +1,386 @@
+//! Cost budget of a session.
+//!
+//! The session's cost is estimated as it runs (see
+//! [`CostAttributor`](crate::audit::CostAttributor)). Each configured share
+//! of the budget raises one alert, and reaching the budget kills the session
+//! with [`KillCause::Budget`](super::KillCause::Budget).
+//!
+//! Estimates can go down, for example when the cost reported by Claude Code
+//! replaces the token estimate. The budget only looks at the highest cost
+//! seen, so an alert fires exactly once even if the cost dips below its
+//! threshold and crosses it again.
+
+use std::path::{Path, PathBuf};
+use std::time::Duration;
+
+use serde::Serialize;
+
+use crate::config::{BudgetConfig, WebhookConfig};
+
+/// Environment variable telling hook processes where to pick up a budget
+/// note for Claude.
+pub const BUDGET_NOTE_ENV: &str = "CLAUDE_SUPERVISOR_BUDGET_NOTE";
+
+/// Micro-dollars per dollar.
+const MICROS_PER_USD: f64 = 1_000_000.0;
+
+/// Returns the budget note file of the session named `session_name`.
+///
+/// This is `state/budget/<session_name>.note` in the
+/// [data directory](crate::config::data_dir).
+#[must_use]
+pub fn budget_note_path(session_name: &str) -> PathBuf {
+    crate::config::state_dir()
+        .join("budget")
+        .join(format!("{session_name}.note"))
+}
+
+/// What a cost update reached.
+#[derive(Debug, Clone, Copy, PartialEq, Serialize)]
+#[serde(tag = "kind", rename_all = "snake_case")]
+pub enum BudgetEvent {
+    /// A share of the budget was used.
+    Alert {
+        /// The share, in percent.
+        percent: u32,
+        /// Cost so far in USD.
+        spent_usd: f64,
+        /// The budget in USD.
+        limit_usd: f64,
+    },
+    /// The budget was used up.
+    Exhausted {
+        /// Cost so far in USD.
+        spent_usd: f64,
+        /// The budget in USD.
+        limit_usd: f64,
+    },
+}
+
+impl BudgetEvent {
+    /// One-line description for the terminal and logs.
+    #[must_use]
+    pub fn describe(&self) -> String {
+        match self {
+            Self::Alert {
+                percent,
+                spent_usd,
+                limit_usd,
+            } => format!("{percent}% of the budget used (${spent_usd:.2} of ${limit_usd:.2})"),
+            Self::Exhausted {
+                spent_usd,
+                limit_usd,
+            } => format!("Budget exhausted (${spent_usd:.2} of ${limit_usd:.2})"),
+        }
+    }
+
+    /// Note telling Claude how much of the budget is left.
+    #[must_use]
+    pub fn note(&self) -> String {
+        format!(
+            "{}. Prioritize finishing the task: complete the current step, commit your \
+             progress and summarize what is left.",
+            self.describe()
+        )
+    }
+}
+
+/// A cost limit with one-shot alerts at shares of it.
+#[derive(Debug, Clone, PartialEq)]
+pub struct CostBudget {
+    limit_micros: u64,
+    thresholds: Vec<u32>,
+    fired: usize,
+    peak_micros: u64,
+    exhausted: bool,
+}
+
+impl CostBudget {
+    /// Create a budget of `limit_usd` with alerts at `alert_percent`.
+    ///
+    /// Shares of 0% or 100% and more are ignored; the kill at 100% is
+    /// always raised.
+    #[must_use]
+    pub fn new(limit_usd: f64, alert_percent: &[u32]) -> Self {
+        let mut thresholds: Vec<u32> = alert_percent
+            .iter()
+            .copied()
+            .filter(|percent| (1..100).contains(percent))
+            .collect();
+        thresholds.sort_unstable();
+        thresholds.dedup();
+        Self {
+            limit_micros: usd_to_micros(limit_usd),
+            thresholds,
+            fired: 0,
+            peak_micros: 0,
+            exhausted: false,
+        }
+    }
+
+    /// Create a budget from the configuration; `None` without a limit.
+    #[must_use]
+    pub fn from_config(config: &BudgetConfig) -> Option<Self> {
+        config
+            .max_cost_usd
+            .filter(|limit| *limit > 0.0)
+            .map(|limit| Self::new(limit, &config.alert_percent))
+    }
+
+    /// The budget in USD.
+    #[must_use]
+    pub fn limit_usd(&self) -> f64 {
+        micros_to_usd(self.limit_micros)
+    }
+
+    /// Shares of the budget, in percent, that raise an alert.
+    #[must_use]
+    pub fn alert_percent(&self) -> &[u32] {
+        &self.thresholds
+    }
+
+    /// Highest cost seen, in USD.
+    #[must_use]
+    pub fn spent_usd(&self) -> f64 {
+        micros_to_usd(self.peak_micros)
+    }
+
+    /// Whether the budget was used up.
+    #[must_use]
+    pub fn is_exhausted(&self) -> bool {
+        self.exhausted
+    }
+
+    /// Record the session cost, in micro-dollars, and return what it
+    /// reached for the first time: alerts in ascending order, then
+    /// exhaustion.
+    pub fn observe(&mut self, cost_micros: u64) -> Vec<BudgetEvent> {
+        self.peak_micros = self.peak_micros.max(cost_micros);
+        let spent_usd = self.spent_usd();
+        let limit_usd = self.limit_usd();
+        let mut events = Vec::new();
+        while let Some(&percent) = self.thresholds.get(self.fired) {
+            if u128::from(self.peak_micros) * 100
+                < u128::from(self.limit_micros) * u128::from(percent)
+            {
+                break;
+            }
+            self.fired += 1;
+            events.push(BudgetEvent::Alert {
+                percent,
+                spent_usd,
+                limit_usd,
+            });
+        }
+        if !self.exhausted && self.peak_micros >= self.limit_micros {
+            self.exhausted = true;
+            events.push(BudgetEvent::Exhausted {
+                spent_usd,
+                limit_usd,
+            });
+        }
+        events
+    }
+}
+
+/// Where budget alerts are delivered besides the terminal and dashboard.
+#[derive(Debug, Clone, Default)]
+pub struct BudgetAlerts {
+    webhook: Option<BudgetWebhook>,
+    note: Option<PathBuf>,
+}
+
+impl BudgetAlerts {
+    /// Create alerts for the configuration; the note for Claude is left at
+    /// `note_path` when enabled.
+    #[must_use]
+    pub fn from_config(config: &BudgetConfig, note_path: PathBuf) -> Self {
+        Self {
+            webhook: BudgetWebhook::from_config(&config.webhook),
+            note: config.notify_claude.then_some(note_path),
+        }
+    }
+
+    /// POST alerts to `webhook` (builder pattern).
+    #[must_use]
+    pub fn with_webhook(mut self, webhook: BudgetWebhook) -> Self {
+        self.webhook = Some(webhook);
+        self
+    }
+
+    /// Leave alerts for Claude at `path` (builder pattern).
+    #[must_use]
+    pub fn with_note(mut self, path: impl Into<PathBuf>) -> Self {
+        self.note = Some(path.into());
+        self
+    }
+
+    /// Webhook alerts are sent to, if any.
+    #[must_use]
+    pub fn webhook(&self) -> Option<&BudgetWebhook> {
+        self.webhook.as_ref()
+    }
+
+    /// File alerts for Claude are left in, if any.
+    #[must_use]
+    pub fn note_path(&self) -> Option<&Path> {
+        self.note.as_deref()
+    }
+}
+
+/// Endpoint that receives budget alerts.
+#[derive(Debug, Clone)]
+pub struct BudgetWebhook {
+    client: reqwest::Client,
+    url: String,
+    auth_header: Option<String>,
+    timeout: Duration,
+}
+
+impl BudgetWebhook {
+    /// Create a webhook from configuration; `None` while the URL is empty.
+    ///
+    /// A missing `auth_header_env` variable is logged and the alerts are
+    /// sent without the header.
+    #[must_use]
+    pub fn from_config(config: &WebhookConfig) -> Option<Self> {
+        if config.url.is_empty() {
+            return None;
+        }
+        let auth_header = config.auth_header_env.as_ref().and_then(|env| {
+            let value = std::env::var(env).ok();
+            if value.is_none() {
+                tracing::warn!(env = %env, "Budget webhook auth header variable is not set");
+            }
+            value
+        });
+        Some(Self {
+            client: reqwest::Client::new(),
+            url: config.url.clone(),
+            auth_header,
+            timeout: Duration::from_secs(config.timeout_secs),
+        })
+    }
+
+    /// Get the configured endpoint.
+    #[must_use]
+    pub fn url(&self) -> &str {
+        &self.url
+    }
+
+    /// Send `payload` in the background; failures are logged.
+    pub fn send(&self, payload: &serde_json::Value) {
+        let mut builder = self
+            .client
+            .post(&self.url)
+            .timeout(self.timeout)
+            .json(payload);
+        if let Some(ref auth) = self.auth_header {
+            builder = builder.header("Authorization", auth);
+        }
+        let url = self.url.clone();
+        tokio::spawn(async move {
+            match builder.send().await {
+                Ok(response) if !response.status().is_success() => {
+                    tracing::warn!(url = %url, status = %response.status(), "Budget webhook rejected the alert");
+                }
+                Ok(_) => {}
+                Err(e) => tracing::warn!(url = %url, error = %e, "Failed to send budget alert"),
+            }
+        });
+    }
+}
+
+/// Leave `note` for the next hook to pass on to Claude.
+///
+/// # Errors
+///
+/// Returns an error if the file cannot be written.
+pub fn write_budget_note(path: &Path, note: &str) -> std::io::Result<()> {
+    if let Some(dir) = path.parent() {
+        std::fs::create_dir_all(dir)?;
+    }
+    std::fs::write(path, note)
+}
+
+/// Take the note left at `path`, if any, so it is passed on once.
+#[must_use]
+pub fn take_budget_note(path: &Path) -> Option<String> {
+    let note = std::fs::read_to_string(path).ok()?;
+    // Whoever removes the file delivers the note
+    std::fs::remove_file(path).ok()?;
+    let note = note.trim();
+    (!note.is_empty()).then(|| note.to_string())
+}
+
+#[allow(clippy::cast_possible_truncation, clippy::cast_sign_loss)]
+fn usd_to_micros(usd: f64) -> u64 {
+    (usd.max(0.0) * MICROS_PER_USD).round() as u64
+}
+
+#[allow(clippy::cast_precision_loss)]
+fn micros_to_usd(micros: u64) -> f64 {
+    micros as f64 / MICROS_PER_USD
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn percents(events: &[BudgetEvent]) -> Vec<Option<u32>> {
+        events
+            .iter()
+            .map(|event| match event {
+                BudgetEvent::Alert { percent, .. } => Some(*percent),
+                BudgetEvent::Exhausted { .. } => None,
+            })
+            .collect()
+    }
+
+    #[test]
+    fn test_alerts_fire_once_in_order_before_exhaustion() {
+        let mut budget = CostBudget::new(1.0, &[80, 50, 50, 0, 100]);
+        assert!(budget.observe(100_000).is_empty());
+        assert_eq!(percents(&budget.observe(500_000)), vec![Some(50)]);
+        // Noisy estimates around a threshold do not fire it again
+        assert!(budget.observe(490_000).is_empty());
+        assert!(budget.observe(510_000).is_empty());
+        assert_eq!(percents(&budget.observe(850_000)), vec![Some(80)]);
+        assert_eq!(percents(&budget.observe(1_000_000)), vec![None]);
+        assert!(budget.observe(1_200_000).is_empty());
+        assert!(budget.is_exhausted());
+    }
+
+    #[test]
+    fn test_jump_past_several_thresholds() {
+        let mut budget = CostBudget::new(2.0, &[50, 80]);
+        let events = budget.observe(2_500_000);
+        assert_eq!(percents(&events), vec![Some(50), Some(80), None]);
+        assert_eq!(
+            events[0].describe(),
+            "50% of the budget used ($2.50 of $2.00)"
+        );
+        assert!((budget.spent_usd() - 2.5).abs() < f64::EPSILON);
+    }
+
+    #[test]
+    fn test_from_config_needs_a_limit() {
+        assert_eq!(CostBudget::from_config(&BudgetConfig::default()), None);
+        let config = BudgetConfig {
+            max_cost_usd: Some(3.0),
+            ..BudgetConfig::default()
+        };
+        let budget = CostBudget::from_config(&config).unwrap();
+        assert!((budget.limit_usd() - 3.0).abs() < f64::EPSILON);
+    }
+
+    #[test]
+    fn test_budget_note_is_taken_once() {
+        let dir = tempfile::tempdir().unwrap();
+        let path = dir.path().join("budget").join("s.note");
+        assert_eq!(take_budget_note(&path), None);
+        write_budget_note(&path, "80% used").unwrap();
+        assert_eq!(take_budget_note(&path).as_deref(), Some("80% used"));
+        assert_eq!(take_budget_note(&path), None);
+    }
+}
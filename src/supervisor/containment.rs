@@ -0,0 +1,308 @@
+//! Containment of writes to the project.
+//!
+//! Worktree isolation only keeps Claude out of the main checkout if its
+//! writes stay in the worktree, and a path inside the worktree can still
+//! lead out of it through a symlink, as the file itself or any directory on
+//! the way. [`Containment`] resolves the real target of each Write, Edit
+//! and `NotebookEdit` call, and of Bash output redirects, and flags targets
+//! outside the session cwd, the repository roots and the configured extra
+//! roots.
+
+use std::path::{Component, Path, PathBuf};
+
+use crate::config::{ContainmentAction, ContainmentConfig};
+use crate::supervisor::{expand_home, PolicyDecision};
+
+/// Prefix of every containment denial or escalation reason.
+pub const CONTAINMENT_REASON: &str = "containment";
+
+/// Symlinks followed while resolving one path before giving up.
+const MAX_SYMLINK_HOPS: usize = 40;
+
+/// Redirect targets that are not files.
+const NON_FILE_TARGETS: &[&str] = &["/dev/null", "/dev/stdout", "/dev/stderr", "/dev/tty"];
+
+/// Guard that flags writes whose real target is outside the allowed roots.
+#[derive(Debug, Clone, Default)]
+pub struct Containment {
+    action: ContainmentAction,
+    extra_roots: Vec<PathBuf>,
+}
+
+impl Containment {
+    /// Create a guard that allows only the session cwd and repository roots.
+    #[must_use]
+    pub fn new(action: ContainmentAction) -> Self {
+        Self {
+            action,
+            extra_roots: Vec::new(),
+        }
+    }
+
+    /// Create a guard from the configuration; `None` when disabled.
+    #[must_use]
+    pub fn from_config(config: &ContainmentConfig) -> Option<Self> {
+        config.enabled.then(|| {
+            let mut guard = Self::new(config.action);
+            for root in &config.extra_roots {
+                guard.allow_root(root);
+            }
+            guard
+        })
+    }
+
+    /// Also allow writes below `root`.
+    pub fn allow_root(&mut self, root: impl Into<PathBuf>) {
+        self.extra_roots.push(root.into());
+    }
+
+    /// What happens to a write outside the allowed roots.
+    #[must_use]
+    pub fn action(&self) -> ContainmentAction {
+        self.action
+    }
+
+    /// Return the real target of `path` if it is outside `cwd`, `roots` and
+    /// the extra roots.
+    #[must_use]
+    pub fn check_path(&self, path: &str, cwd: &Path, roots: &[PathBuf]) -> Option<PathBuf> {
+        if path.is_empty() {
+            return None;
+        }
+        let target = real_path(Path::new(path), cwd);
+        let contained = std::iter::once(cwd)
+            .chain(roots.iter().map(PathBuf::as_path))
+            .chain(self.extra_roots.iter().map(PathBuf::as_path))
+            .map(|root| real_path(root, cwd))
+            .any(|root| target.starts_with(root));
+        (!contained).then_some(target)
+    }
+
+    /// Return the real target of the first output redirect of a shell
+    /// command that is outside the allowed roots.
+    ///
+    /// Only `>`, `>>`, `&>` and `N>` redirects are detected; what a command
+    /// writes through its arguments is not.
+    #[must_use]
+    pub fn check_command(
+        &self,
+        command: &str,
+        cwd: &Path,
+        roots: &[PathBuf],
+    ) -> Option<(String, PathBuf)> {
+        redirect_targets(command)
+            .filter(|target| !NON_FILE_TARGETS.contains(&target.as_str()))
+            .find_map(|target| {
+                let real = self.check_path(&target, cwd, roots)?;
+                Some((target, real))
+            })
+    }
+
+    /// Decision for a write of `tool_name` to `path`, whose real target is
+    /// `real`.
+    #[must_use]
+    pub fn decision(&self, tool_name: &str, path: &str, real: &Path) -> PolicyDecision {
+        let reason = format!(
+            "{CONTAINMENT_REASON}: {tool_name} target {path} resolves to {}, outside the project",
+            real.display()
+        );
+        match self.action {
+            ContainmentAction::Deny => PolicyDecision::deny(reason),
+            ContainmentAction::Escalate => PolicyDecision::Escalate(reason),
+        }
+    }
+}
+
+/// Resolve `path` the way the filesystem would, like `realpath -m`.
+///
+/// Expands `~` and `$HOME` and anchors relative paths at `cwd`, then walks
+/// the components: symlinks are followed wherever they are, `..` goes to
+/// the parent of the real directory, and components that do not exist are
+/// kept as written.
+#[must_use]
+pub fn real_path(path: &Path, cwd: &Path) -> PathBuf {
+    let expanded = expand_home(path);
+    let absolute = if expanded.is_absolute() {
+        expanded
+    } else {
+        cwd.join(expanded)
+    };
+    let mut hops = 0;
+    resolve_components(&absolute, PathBuf::new(), &mut hops)
+}
+
+fn resolve_components(path: &Path, mut resolved: PathBuf, hops: &mut usize) -> PathBuf {
+    for component in path.components() {
+        match component {
+            Component::Prefix(_) | Component::RootDir => resolved.push(component),
+            Component::CurDir => {}
+            Component::ParentDir => {
+                resolved.pop();
+            }
+            Component::Normal(name) => {
+                let candidate = resolved.join(name);
+                match std::fs::read_link(&candidate) {
+                    Ok(link) if *hops < MAX_SYMLINK_HOPS => {
+                        *hops += 1;
+                        // A relative link is relative to its directory
+                        let base = if link.is_absolute() {
+                            PathBuf::new()
+                        } else {
+                            resolved.clone()
+                        };
+                        resolved = resolve_components(&link, base, hops);
+                    }
+                    _ => resolved = candidate,
+                }
+            }
+        }
+    }
+    resolved
+}
+
+/// Targets of the output redirects of a shell command.
+fn redirect_targets(command: &str) -> impl Iterator<Item = String> + '_ {
+    let mut rest = command;
+    std::iter::from_fn(move || loop {
+        let index = rest.find('>')?;
+        let mut after = &rest[index + 1..];
+        after = after.strip_prefix('>').unwrap_or(after);
+        // `>&2` and `>|` duplicate descriptors or force a clobber
+        if let Some(dup) = after.strip_prefix('&') {
+            rest = dup;
+            continue;
+        }
+        after = after.strip_prefix('|').unwrap_or(after).trim_start();
+        let end = after
+            .find(|c: char| c.is_whitespace() || matches!(c, ';' | '|' | '&' | '<' | '>' | ')'))
+            .unwrap_or(after.len());
+        let target = after[..end].trim_matches(|c| c == '"' || c == '\'');
+        rest = &after[end..];
+        if !target.is_empty() {
+            return Some(target.to_string());
+        }
+    })
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    /// A project directory and a directory outside it, both canonical.
+    fn layout() -> (tempfile::TempDir, PathBuf, PathBuf) {
+        let dir = tempfile::tempdir().unwrap();
+        let base = dir.path().canonicalize().unwrap();
+        let project = base.join("worktree");
+        let outside = base.join("main-checkout");
+        std::fs::create_dir_all(project.join("src")).unwrap();
+        std::fs::create_dir_all(&outside).unwrap();
+        std::fs::write(outside.join("lib.rs"), "").unwrap();
+        (dir, project, outside)
+    }
+
+    #[test]
+    fn test_paths_inside_the_project_are_contained() {
+        let (_dir, project, _) = layout();
+        let guard = Containment::new(ContainmentAction::Deny);
+        assert!(guard.check_path("src/new.rs", &project, &[]).is_none());
+        assert!(guard
+            .check_path("src/missing/dir/../new.rs", &project, &[])
+            .is_none());
+        assert_eq!(
+            guard.check_path("../main-checkout/lib.rs", &project, &[]),
+            Some(project.parent().unwrap().join("main-checkout/lib.rs"))
+        );
+    }
+
+    #[cfg(unix)]
+    #[test]
+    fn test_direct_symlink_escape() {
+        let (_dir, project, outside) = layout();
+        std::os::unix::fs::symlink(outside.join("lib.rs"), project.join("src/lib.rs")).unwrap();
+        let guard = Containment::new(ContainmentAction::Deny);
+
+        assert_eq!(
+            guard.check_path("src/lib.rs", &project, &[]),
+            Some(outside.join("lib.rs"))
+        );
+        // Allowed once the target's directory is a root
+        assert!(guard
+            .check_path("src/lib.rs", &project, std::slice::from_ref(&outside))
+            .is_none());
+    }
+
+    #[cfg(unix)]
+    #[test]
+    fn test_parent_directory_symlink_escape() {
+        let (_dir, project, outside) = layout();
+        std::os::unix::fs::symlink("../main-checkout", project.join("vendor")).unwrap();
+        let guard = Containment::new(ContainmentAction::Deny);
+
+        // Through a relative link to a directory, to a file that may not exist
+        assert_eq!(
+            guard.check_path("vendor/new/file.rs", &project, &[]),
+            Some(outside.join("new/file.rs"))
+        );
+        // `..` after a symlink goes to the parent of the link's target
+        assert_eq!(
+            real_path(Path::new("vendor/.."), &project),
+            project.parent().unwrap()
+        );
+        assert!(guard
+            .check_path("vendor/../worktree/src/a.rs", &project, &[])
+            .is_none());
+    }
+
+    #[cfg(unix)]
+    #[test]
+    fn test_symlink_loop_terminates() {
+        let (_dir, project, _) = layout();
+        std::os::unix::fs::symlink("b", project.join("a")).unwrap();
+        std::os::unix::fs::symlink("a", project.join("b")).unwrap();
+        let guard = Containment::new(ContainmentAction::Deny);
+        let _ = guard.check_path("a/file", &project, &[]);
+    }
+
+    #[cfg(unix)]
+    #[test]
+    fn test_bash_redirects() {
+        let (_dir, project, outside) = layout();
+        std::os::unix::fs::symlink(&outside, project.join("out")).unwrap();
+        let guard = Containment::new(ContainmentAction::Escalate);
+
+        for command in [
+            "echo x > out/lib.rs",
+            "echo x >>out/lib.rs",
+            "make 2> \"out/lib.rs\"",
+            "cargo build &>out/lib.rs; ls",
+        ] {
+            let (target, real) = guard.check_command(command, &project, &[]).unwrap();
+            assert_eq!(target, "out/lib.rs", "{command}");
+            assert_eq!(real, outside.join("lib.rs"));
+        }
+        for command in [
+            "echo x > src/lib.rs",
+            "cargo test 2>&1 | tee log.txt",
+            "ls > /dev/null",
+            "cat out/lib.rs",
+        ] {
+            assert!(
+                guard.check_command(command, &project, &[]).is_none(),
+                "{command}"
+            );
+        }
+    }
+
+    #[test]
+    fn test_decision_reports_resolved_path() {
+        let guard = Containment::new(ContainmentAction::Escalate);
+        let decision = guard.decision("Write", "vendor/lib.rs", Path::new("/srv/main/lib.rs"));
+        assert_eq!(
+            decision,
+            PolicyDecision::Escalate(
+                "containment: Write target vendor/lib.rs resolves to /srv/main/lib.rs, outside the project"
+                    .to_string()
+            )
+        );
+    }
+}
@@ -0,0 +1,116 @@
+//! Sessions that exhaust Claude's context window.
+//!
+//! A session that hits the context limit ends with an error result such as
+//! "Prompt is too long" instead of finishing its task. It is reported as
+//! [`SupervisorResult::ContextExhausted`](super::SupervisorResult) and, if
+//! configured, continued by resuming the Claude session, which compacts its
+//! context, or by a fresh session seeded with a summary of the progress.
+
+use chrono::{DateTime, Utc};
+
+use crate::cli::ResultEvent;
+use crate::config::ContextRecoveryMode;
+
+/// Process exit code of a session that ran out of context, next to
+/// [`TIMED_OUT_EXIT_CODE`](super::TIMED_OUT_EXIT_CODE).
+pub const CONTEXT_EXHAUSTED_EXIT_CODE: i32 = 19;
+
+/// Lowercase fragments of the result text of a session out of context.
+pub const CONTEXT_LIMIT_MARKERS: &[&str] = &[
+    "prompt is too long",
+    "input is too long",
+    "context length",
+    "context window",
+];
+
+/// Whether `event` ends a session that ran out of context.
+#[must_use]
+pub fn is_context_exhausted(event: &ResultEvent) -> bool {
+    if !event.is_error {
+        return false;
+    }
+    let result = event.result.to_lowercase();
+    CONTEXT_LIMIT_MARKERS
+        .iter()
+        .any(|marker| result.contains(marker))
+}
+
+/// How to start the process continuing an exhausted or idle session.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum RecoveryPlan {
+    /// Resume the Claude session with a `continue` prompt.
+    Resume {
+        /// Claude session to resume.
+        session_id: String,
+    },
+    /// Start a new session with this prompt.
+    Fresh {
+        /// The task and a summary of the progress so far.
+        prompt: String,
+    },
+    /// Resume an idle Claude session with a nudge.
+    Nudge {
+        /// Claude session to resume.
+        session_id: String,
+        /// Prompt to resume it with.
+        message: String,
+    },
+}
+
+/// A recovery attempt, recorded in the audit log.
+#[derive(Debug, Clone)]
+pub struct ContextRecoveryAttempt {
+    /// Attempt number, from 1.
+    pub attempt: u32,
+    /// How the session was continued.
+    pub mode: ContextRecoveryMode,
+    /// Claude session that ran out of context, if known.
+    pub session_id: Option<String>,
+    /// When the attempt started.
+    pub started_at: DateTime<Utc>,
+}
+
+impl ContextRecoveryAttempt {
+    /// Human-readable description used in logs and the audit log.
+    #[must_use]
+    pub fn describe(&self) -> String {
+        let how = match self.mode {
+            ContextRecoveryMode::Resume => "resuming the session",
+            _ => "starting a fresh session from a progress summary",
+        };
+        format!(
+            "Context exhausted; recovery attempt {} by {how}",
+            self.attempt
+        )
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn result(text: &str, is_error: bool) -> ResultEvent {
+        ResultEvent {
+            result: text.to_string(),
+            session_id: "s-1".to_string(),
+            is_error,
+            cost_usd: None,
+            duration_ms: None,
+            extras: std::collections::HashMap::new(),
+        }
+    }
+
+    #[test]
+    fn test_context_exhaustion_is_detected() {
+        assert!(is_context_exhausted(&result("Prompt is too long", true)));
+        assert!(is_context_exhausted(&result(
+            "API Error: input length exceeds the context window",
+            true
+        )));
+        assert!(!is_context_exhausted(&result("Prompt is too long", false)));
+        assert!(!is_context_exhausted(&result(
+            "API Error: overloaded",
+            true
+        )));
+    }
+}
@@ -0,0 +1,177 @@
+//! Why a tool call was denied, and by which rule and source.
+
+use std::fmt;
+use std::ops::Deref;
+
+use serde::{Deserialize, Serialize};
+
+use super::DecisionSource;
+
+/// Reason a tool call was denied, with the rule and source behind it.
+///
+/// Displays as its message. Serialized as an object; a plain string also
+/// deserializes, as a message of unknown provenance.
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(from = "DenyReasonRepr")]
+pub struct DenyReason {
+    /// Explanation shown to Claude and the operator.
+    pub message: String,
+    /// Kind of rule that denied the call, like `blocklist`, `path rule` or
+    /// `rate limit`.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub rule_category: Option<String>,
+    /// Name of the rule that denied the call, as in rule firings, like
+    /// `path rule: src/**`.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub rule_id: Option<String>,
+    /// Who denied the call.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub source: Option<DecisionSource>,
+}
+
+impl DenyReason {
+    /// A reason of unknown provenance.
+    #[must_use]
+    pub fn new(message: impl Into<String>) -> Self {
+        Self {
+            message: message.into(),
+            rule_category: None,
+            rule_id: None,
+            source: None,
+        }
+    }
+
+    /// Name the rule that denied the call; its category is the part of the
+    /// name before `: `, or the whole name (builder pattern).
+    #[must_use]
+    pub fn with_rule(mut self, rule_id: impl Into<String>) -> Self {
+        let rule_id = rule_id.into();
+        let category = rule_id
+            .split_once(": ")
+            .map_or(rule_id.as_str(), |(category, _)| category);
+        self.rule_category = Some(category.to_string());
+        self.rule_id = Some(rule_id);
+        self
+    }
+
+    /// Set who denied the call (builder pattern).
+    #[must_use]
+    pub fn with_source(mut self, source: DecisionSource) -> Self {
+        self.source = Some(source);
+        self
+    }
+
+    /// The message.
+    #[must_use]
+    pub fn as_str(&self) -> &str {
+        &self.message
+    }
+}
+
+impl fmt::Display for DenyReason {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.write_str(&self.message)
+    }
+}
+
+impl Deref for DenyReason {
+    type Target = str;
+
+    fn deref(&self) -> &str {
+        &self.message
+    }
+}
+
+impl From<String> for DenyReason {
+    fn from(message: String) -> Self {
+        Self::new(message)
+    }
+}
+
+impl From<&str> for DenyReason {
+    fn from(message: &str) -> Self {
+        Self::new(message)
+    }
+}
+
+impl From<DenyReason> for String {
+    fn from(reason: DenyReason) -> Self {
+        reason.message
+    }
+}
+
+/// Serialized forms of a [`DenyReason`].
+#[derive(Deserialize)]
+#[serde(untagged)]
+enum DenyReasonRepr {
+    Message(String),
+    Full {
+        message: String,
+        #[serde(default)]
+        rule_category: Option<String>,
+        #[serde(default)]
+        rule_id: Option<String>,
+        #[serde(default)]
+        source: Option<DecisionSource>,
+    },
+}
+
+impl From<DenyReasonRepr> for DenyReason {
+    fn from(repr: DenyReasonRepr) -> Self {
+        match repr {
+            DenyReasonRepr::Message(message) => Self::new(message),
+            DenyReasonRepr::Full {
+                message,
+                rule_category,
+                rule_id,
+                source,
+            } => Self {
+                message,
+                rule_category,
+                rule_id,
+                source,
+            },
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_deny_reason_serde() {
+        let reason = DenyReason::new("Blocked destructive command")
+            .with_rule("blocklist: recursive delete of root")
+            .with_source(DecisionSource::Policy);
+        assert_eq!(reason.rule_category.as_deref(), Some("blocklist"));
+        assert_eq!(reason.to_string(), "Blocked destructive command");
+
+        let json = serde_json::to_value(&reason).unwrap();
+        assert_eq!(
+            json,
+            serde_json::json!({
+                "message": "Blocked destructive command",
+                "rule_category": "blocklist",
+                "rule_id": "blocklist: recursive delete of root",
+                "source": "policy",
+            })
+        );
+        assert_eq!(serde_json::from_value::<DenyReason>(json).unwrap(), reason);
+
+        // Reasons serialized as plain text still load
+        let old: DenyReason = serde_json::from_str(r#""Denied by hook: no""#).unwrap();
+        assert_eq!(old, DenyReason::from("Denied by hook: no"));
+        assert_eq!(
+            serde_json::to_string(&old).unwrap(),
+            r#"{"message":"Denied by hook: no"}"#
+        );
+        assert_eq!(
+            DenyReason::new("x")
+                .with_rule("git gate")
+                .rule_category
+                .as_deref(),
+            Some("git gate")
+        );
+    }
+}
@@ -0,0 +1,442 @@
+//! Running sessions in the background.
+//!
+//! `run --detach` hands the session to a child process and returns once the
+//! session is up. The child is the same command run again without
+//! `--detach` and with [`DETACHED_ENV`] naming the session: it leaves the
+//! terminal's session with `setsid`, so closing the terminal does not hang
+//! it up, and prints to a log file instead. Running the binary again rather
+//! than forking keeps the async runtime out of the way.
+//!
+//! A detached session keeps three files, named after the session:
+//!
+//! ```text
+//! <data_dir>/detached/<name>.pid   process id, present while it runs
+//! <data_dir>/detached/<name>.log   everything the session prints
+//! <data_dir>/sockets/<name>.sock   control socket for `attach`
+//! ```
+
+use std::ffi::OsString;
+use std::io::{Read, Seek, SeekFrom};
+use std::path::{Path, PathBuf};
+use std::process::{Child, Command, Stdio};
+use std::time::Duration;
+
+use crate::config::{detached_dir, sockets_dir};
+
+/// Environment variable naming the session in a detached child.
+pub const DETACHED_ENV: &str = "CLAUDE_SUPERVISOR_DETACHED";
+
+/// How long `run --detach` waits for the session to start.
+pub const DETACH_STARTUP_TIMEOUT: Duration = Duration::from_secs(90);
+
+/// How often `run --detach` checks whether the session has started.
+const STARTUP_POLL_INTERVAL: Duration = Duration::from_millis(100);
+
+/// Error type for detached sessions.
+#[derive(thiserror::Error, Debug)]
+pub enum DetachError {
+    /// No detached session runs.
+    #[error("No detached session is running")]
+    NoneRunning,
+    /// The named detached session does not run.
+    #[error("No detached session named '{0}' is running")]
+    NotRunning(String),
+    /// Several detached sessions run and none was named.
+    #[error("Several detached sessions are running ({}); pick one with --session", .0.join(", "))]
+    Ambiguous(Vec<String>),
+    /// The child exited before the session started.
+    #[error("Detached session exited during startup ({status}); see {}", log.display())]
+    StartupFailed { status: String, log: PathBuf },
+    /// The session did not start in time.
+    #[error("Detached session did not start within {0}s")]
+    StartupTimeout(u64),
+    /// Reading or writing the session's files failed.
+    #[error("I/O error: {0}")]
+    Io(#[from] std::io::Error),
+}
+
+/// Files of a detached session.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct DetachedSession {
+    name: String,
+    dir: PathBuf,
+    sockets_dir: PathBuf,
+}
+
+impl DetachedSession {
+    /// The session `name` in the data directory.
+    #[must_use]
+    pub fn new(name: impl Into<String>) -> Self {
+        Self::in_dirs(name, detached_dir(), sockets_dir())
+    }
+
+    /// The session `name` keeping its pidfile and log in `dir` and its
+    /// socket in `sockets_dir`.
+    #[must_use]
+    pub fn in_dirs(
+        name: impl Into<String>,
+        dir: impl Into<PathBuf>,
+        sockets_dir: impl Into<PathBuf>,
+    ) -> Self {
+        Self {
+            name: name.into(),
+            dir: dir.into(),
+            sockets_dir: sockets_dir.into(),
+        }
+    }
+
+    /// The session named by [`DETACHED_ENV`], if this process is one.
+    #[must_use]
+    pub fn current() -> Option<Self> {
+        std::env::var(DETACHED_ENV)
+            .ok()
+            .filter(|name| !name.is_empty())
+            .map(Self::new)
+    }
+
+    /// Session name.
+    #[must_use]
+    pub fn name(&self) -> &str {
+        &self.name
+    }
+
+    /// Path of the pidfile.
+    #[must_use]
+    pub fn pid_path(&self) -> PathBuf {
+        self.dir.join(format!("{}.pid", self.name))
+    }
+
+    /// Path of the log file.
+    #[must_use]
+    pub fn log_path(&self) -> PathBuf {
+        self.dir.join(format!("{}.log", self.name))
+    }
+
+    /// Path of the control socket.
+    #[must_use]
+    pub fn socket_path(&self) -> PathBuf {
+        self.sockets_dir.join(format!("{}.sock", self.name))
+    }
+
+    /// Record `pid` as the session's process.
+    ///
+    /// # Errors
+    ///
+    /// Returns an error if the pidfile cannot be written.
+    pub fn write_pid(&self, pid: u32) -> std::io::Result<()> {
+        std::fs::create_dir_all(&self.dir)?;
+        std::fs::write(self.pid_path(), format!("{pid}\n"))
+    }
+
+    /// Process id from the pidfile, if there is one.
+    #[must_use]
+    pub fn pid(&self) -> Option<u32> {
+        std::fs::read_to_string(self.pid_path())
+            .ok()?
+            .trim()
+            .parse()
+            .ok()
+    }
+
+    /// Whether the process in the pidfile is alive.
+    #[must_use]
+    pub fn is_running(&self) -> bool {
+        self.pid().is_some_and(process_alive)
+    }
+
+    /// Remove the pidfile; the log stays for later reading.
+    pub fn remove_pid(&self) {
+        if let Err(e) = std::fs::remove_file(self.pid_path()) {
+            if e.kind() != std::io::ErrorKind::NotFound {
+                tracing::warn!(error = %e, name = %self.name, "Failed to remove pidfile");
+            }
+        }
+    }
+
+    /// Sessions with a pidfile in `dir`, running or not, ordered by name.
+    #[must_use]
+    pub fn list_in(dir: &Path, sockets_dir: &Path) -> Vec<Self> {
+        let Ok(entries) = std::fs::read_dir(dir) else {
+            return Vec::new();
+        };
+        let mut sessions: Vec<_> = entries
+            .filter_map(Result::ok)
+            .map(|entry| entry.path())
+            .filter(|path| path.extension().is_some_and(|ext| ext == "pid"))
+            .filter_map(|path| Some(path.file_stem()?.to_string_lossy().into_owned()))
+            .map(|name| Self::in_dirs(name, dir, sockets_dir))
+            .collect();
+        sessions.sort_by(|a, b| a.name.cmp(&b.name));
+        sessions
+    }
+
+    /// The running session `name`, or the only running session when no
+    /// name is given, in the data directory.
+    ///
+    /// # Errors
+    ///
+    /// Returns an error if the session does not run, or several run and
+    /// none was named.
+    pub fn find_running(name: Option<&str>) -> Result<Self, DetachError> {
+        Self::find_running_in(&detached_dir(), &sockets_dir(), name)
+    }
+
+    /// [`find_running`](Self::find_running) in `dir` and `sockets_dir`.
+    ///
+    /// # Errors
+    ///
+    /// See [`find_running`](Self::find_running).
+    pub fn find_running_in(
+        dir: &Path,
+        sockets_dir: &Path,
+        name: Option<&str>,
+    ) -> Result<Self, DetachError> {
+        if let Some(name) = name {
+            let session = Self::in_dirs(name, dir, sockets_dir);
+            return if session.is_running() {
+                Ok(session)
+            } else {
+                Err(DetachError::NotRunning(name.to_string()))
+            };
+        }
+        let mut running: Vec<_> = Self::list_in(dir, sockets_dir)
+            .into_iter()
+            .filter(Self::is_running)
+            .collect();
+        match running.len() {
+            0 => Err(DetachError::NoneRunning),
+            1 => Ok(running.remove(0)),
+            _ => Err(DetachError::Ambiguous(
+                running.into_iter().map(|session| session.name).collect(),
+            )),
+        }
+    }
+
+    /// Start the current command again as this session, in the background.
+    ///
+    /// `args` are the arguments of this process, without the program name.
+    ///
+    /// # Errors
+    ///
+    /// Returns an error if the log file cannot be opened or the child
+    /// cannot be started.
+    pub fn spawn(&self, args: impl IntoIterator<Item = OsString>) -> Result<Child, DetachError> {
+        std::fs::create_dir_all(&self.dir)?;
+        let log = std::fs::OpenOptions::new()
+            .create(true)
+            .append(true)
+            .open(self.log_path())?;
+        let child = Command::new(std::env::current_exe()?)
+            .args(detached_args(args))
+            .env(DETACHED_ENV, &self.name)
+            .stdin(Stdio::null())
+            .stdout(log.try_clone()?)
+            .stderr(log)
+            .spawn()?;
+        Ok(child)
+    }
+
+    /// Wait until `child` has recorded itself in the pidfile, which it does
+    /// once the session is set up.
+    ///
+    /// # Errors
+    ///
+    /// Returns an error if the child exits first or `timeout` passes.
+    pub async fn wait_for_startup(
+        &self,
+        child: &mut Child,
+        timeout: Duration,
+    ) -> Result<(), DetachError> {
+        let deadline = tokio::time::Instant::now() + timeout;
+        loop {
+            if self.pid() == Some(child.id()) {
+                return Ok(());
+            }
+            if let Some(status) = child.try_wait()? {
+                return Err(DetachError::StartupFailed {
+                    status: status.to_string(),
+                    log: self.log_path(),
+                });
+            }
+            if tokio::time::Instant::now() >= deadline {
+                return Err(DetachError::StartupTimeout(timeout.as_secs()));
+            }
+            tokio::time::sleep(STARTUP_POLL_INTERVAL).await;
+        }
+    }
+}
+
+/// Arguments for the detached child: `args` without `--detach`.
+#[must_use]
+pub fn detached_args(args: impl IntoIterator<Item = OsString>) -> Vec<OsString> {
+    args.into_iter().filter(|arg| arg != "--detach").collect()
+}
+
+/// Whether process `pid` exists.
+#[cfg(unix)]
+#[must_use]
+pub fn process_alive(pid: u32) -> bool {
+    use nix::errno::Errno;
+    use nix::sys::signal::kill;
+    use nix::unistd::Pid;
+
+    let Ok(pid) = i32::try_from(pid) else {
+        return false;
+    };
+    // Signal 0 checks for the process without signalling it
+    matches!(kill(Pid::from_raw(pid), None), Ok(()) | Err(Errno::EPERM))
+}
+
+/// Whether process `pid` exists; assumed so without a way to check.
+#[cfg(not(unix))]
+#[must_use]
+pub fn process_alive(_pid: u32) -> bool {
+    true
+}
+
+/// Leave the terminal's session so that closing the terminal does not
+/// hang up this process.
+///
+/// # Errors
+///
+/// Returns an error if this process leads a process group.
+#[cfg(unix)]
+pub fn enter_new_session() -> std::io::Result<()> {
+    nix::unistd::setsid()?;
+    Ok(())
+}
+
+/// Point stdout and stderr at `/dev/null` once the terminal is gone, so
+/// that printing does not fail.
+///
+/// # Errors
+///
+/// Returns an error if `/dev/null` cannot be opened or duplicated.
+#[cfg(unix)]
+pub fn silence_terminal() -> std::io::Result<()> {
+    use std::os::fd::AsRawFd;
+
+    let null = std::fs::OpenOptions::new().write(true).open("/dev/null")?;
+    for fd in [std::io::stdout().as_raw_fd(), std::io::stderr().as_raw_fd()] {
+        nix::unistd::dup2(null.as_raw_fd(), fd)?;
+    }
+    Ok(())
+}
+
+/// Reads what is appended to a log file.
+#[derive(Debug, Clone)]
+pub struct LogTail {
+    path: PathBuf,
+    position: u64,
+}
+
+impl LogTail {
+    /// Follow `path`, starting with its last `backlog` bytes.
+    #[must_use]
+    pub fn new(path: impl Into<PathBuf>, backlog: u64) -> Self {
+        let path = path.into();
+        let len = std::fs::metadata(&path).map_or(0, |meta| meta.len());
+        Self {
+            path,
+            position: len.saturating_sub(backlog),
+        }
+    }
+
+    /// Text appended since the last read; from the start again if the file
+    /// was truncated.
+    ///
+    /// # Errors
+    ///
+    /// Returns an error if the file cannot be read.
+    pub fn read_new(&mut self) -> std::io::Result<String> {
+        let mut file = match std::fs::File::open(&self.path) {
+            Ok(file) => file,
+            Err(e) if e.kind() == std::io::ErrorKind::NotFound => return Ok(String::new()),
+            Err(e) => return Err(e),
+        };
+        if file.metadata()?.len() < self.position {
+            self.position = 0;
+        }
+        file.seek(SeekFrom::Start(self.position))?;
+        let mut bytes = Vec::new();
+        file.read_to_end(&mut bytes)?;
+        self.position += bytes.len() as u64;
+        Ok(String::from_utf8_lossy(&bytes).into_owned())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_pidfile_marks_running_sessions() {
+        let dir = tempfile::tempdir().unwrap();
+        let sockets = dir.path().join("sockets");
+        let session = DetachedSession::in_dirs("calm-otter", dir.path(), &sockets);
+        assert!(!session.is_running());
+        assert!(matches!(
+            DetachedSession::find_running_in(dir.path(), &sockets, None),
+            Err(DetachError::NoneRunning)
+        ));
+
+        session.write_pid(std::process::id()).unwrap();
+        assert_eq!(session.pid(), Some(std::process::id()));
+        assert!(session.is_running());
+        assert_eq!(session.socket_path(), sockets.join("calm-otter.sock"));
+        let found = DetachedSession::find_running_in(dir.path(), &sockets, None).unwrap();
+        assert_eq!(found, session);
+
+        // A pidfile left by a dead process does not count
+        let stale = DetachedSession::in_dirs("stale-heron", dir.path(), &sockets);
+        std::fs::write(stale.pid_path(), format!("{}\n", i32::MAX)).unwrap();
+        assert_eq!(DetachedSession::list_in(dir.path(), &sockets).len(), 2);
+        assert!(matches!(
+            DetachedSession::find_running_in(dir.path(), &sockets, Some("stale-heron")),
+            Err(DetachError::NotRunning(_))
+        ));
+
+        let other = DetachedSession::in_dirs("bold-lynx", dir.path(), &sockets);
+        other.write_pid(std::process::id()).unwrap();
+        let err = DetachedSession::find_running_in(dir.path(), &sockets, None).unwrap_err();
+        assert_eq!(
+            err.to_string(),
+            "Several detached sessions are running (bold-lynx, calm-otter); pick one with --session"
+        );
+
+        session.remove_pid();
+        assert!(!session.pid_path().exists());
+    }
+
+    #[test]
+    fn test_detached_args_drop_detach() {
+        let args = ["run", "--detach", "--no-ai", "fix the tests"].map(OsString::from);
+        assert_eq!(
+            detached_args(args),
+            ["run", "--no-ai", "fix the tests"].map(OsString::from)
+        );
+    }
+
+    #[test]
+    fn test_log_tail_reads_appended_text() {
+        use std::io::Write;
+
+        let dir = tempfile::tempdir().unwrap();
+        let path = dir.path().join("session.log");
+        std::fs::write(&path, "early\nlate\n").unwrap();
+
+        let mut tail = LogTail::new(&path, 5);
+        assert_eq!(tail.read_new().unwrap(), "late\n");
+        assert_eq!(tail.read_new().unwrap(), "");
+
+        let mut file = std::fs::OpenOptions::new()
+            .append(true)
+            .open(&path)
+            .unwrap();
+        write!(file, "more").unwrap();
+        assert_eq!(tail.read_new().unwrap(), "more");
+
+        std::fs::write(&path, "new\n").unwrap();
+        assert_eq!(tail.read_new().unwrap(), "new\n");
+    }
+}
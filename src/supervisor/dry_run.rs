@@ -0,0 +1,176 @@
+//! Policy dry runs: decisions recorded instead of enforced.
+//!
+//! In a dry run the policy engine allows every call it would deny or
+//! escalate, and records the decision it would have made, so a stricter
+//! policy can be trialled on real sessions before it blocks anything.
+
+use std::collections::BTreeMap;
+use std::path::PathBuf;
+use std::sync::{Arc, Mutex};
+
+use chrono::{DateTime, Utc};
+use serde::{Deserialize, Serialize};
+use uuid::Uuid;
+
+use super::{PolicyDecision, ToolAliases};
+use crate::audit::{AuditEvent, EventType};
+
+/// Environment variable that puts the policy engines of a session's hooks
+/// in a dry run.
+pub const POLICY_DRY_RUN_ENV: &str = "CLAUDE_SUPERVISOR_POLICY_DRY_RUN";
+
+/// A call allowed in a dry run, with the decision the policy would have made.
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct DryRunRecord {
+    /// Name the tool was called by.
+    pub tool_name: String,
+    /// Input the tool was called with.
+    pub tool_input: serde_json::Value,
+    /// The denial or escalation that was not enforced.
+    pub decision: PolicyDecision,
+    /// Name of the rule behind the decision, as in rule firings.
+    pub rule: String,
+    /// When the call was allowed.
+    pub at: DateTime<Utc>,
+}
+
+impl DryRunRecord {
+    /// Audit event of `session_id` recording the decision that was not
+    /// enforced.
+    #[must_use]
+    pub fn audit_event(&self, session_id: Uuid, aliases: &ToolAliases) -> AuditEvent {
+        let reason = match self.decision {
+            PolicyDecision::Deny(ref reason) => reason.as_str(),
+            PolicyDecision::Escalate(ref reason) => reason.as_str(),
+            PolicyDecision::Allow | PolicyDecision::AllowWithModification(_) => "",
+        };
+        AuditEvent::builder(session_id, EventType::DryRun)
+            .timestamp(self.at)
+            .called_tool(&self.tool_name, aliases)
+            .tool_input(self.tool_input.clone())
+            .decision((&self.decision).into())
+            .reason(reason)
+            .rule(&self.rule)
+            .build()
+    }
+}
+
+/// How many calls a dry run let through that would have been blocked.
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq)]
+pub struct DryRunCounts {
+    /// Calls that would have been denied.
+    pub denied: usize,
+    /// Calls that would have been escalated.
+    pub escalated: usize,
+}
+
+impl DryRunCounts {
+    fn add(&mut self, decision: &PolicyDecision) {
+        match decision {
+            PolicyDecision::Deny(_) => self.denied += 1,
+            PolicyDecision::Escalate(_) => self.escalated += 1,
+            PolicyDecision::Allow | PolicyDecision::AllowWithModification(_) => {}
+        }
+    }
+}
+
+/// Calls allowed in a dry run, shared by the clones of a policy engine.
+#[derive(Debug, Clone, Default)]
+pub struct DryRunLog {
+    records: Arc<Mutex<Vec<DryRunRecord>>>,
+}
+
+impl DryRunLog {
+    /// Default directory for the audit session IDs of hook sessions.
+    ///
+    /// This is `state/dry-run` in the [data directory](crate::config::data_dir).
+    #[must_use]
+    pub fn default_dir() -> PathBuf {
+        crate::config::state_dir().join("dry-run")
+    }
+
+    /// Record a call allowed in the dry run.
+    ///
+    /// # Panics
+    ///
+    /// Panics if the internal `Mutex` is poisoned.
+    pub fn record(&self, record: DryRunRecord) {
+        self.records.lock().expect("Mutex poisoned").push(record);
+    }
+
+    /// The calls allowed so far, oldest first.
+    ///
+    /// # Panics
+    ///
+    /// Panics if the internal `Mutex` is poisoned.
+    #[must_use]
+    pub fn records(&self) -> Vec<DryRunRecord> {
+        self.records.lock().expect("Mutex poisoned").clone()
+    }
+
+    /// Counts of the calls allowed so far.
+    #[must_use]
+    pub fn totals(&self) -> DryRunCounts {
+        let mut counts = DryRunCounts::default();
+        for record in self.records() {
+            counts.add(&record.decision);
+        }
+        counts
+    }
+
+    /// Counts of the calls allowed so far, keyed by rule.
+    #[must_use]
+    pub fn by_rule(&self) -> BTreeMap<String, DryRunCounts> {
+        let mut counts: BTreeMap<String, DryRunCounts> = BTreeMap::new();
+        for record in self.records() {
+            counts.entry(record.rule).or_default().add(&record.decision);
+        }
+        counts
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn record(rule: &str, decision: PolicyDecision) -> DryRunRecord {
+        DryRunRecord {
+            tool_name: "bash".to_string(),
+            tool_input: serde_json::json!({"command": "rm -rf /"}),
+            decision,
+            rule: rule.to_string(),
+            at: Utc::now(),
+        }
+    }
+
+    #[test]
+    fn test_dry_run_log_counts_by_rule() {
+        let log = DryRunLog::default();
+        let clone = log.clone();
+        clone.record(record("blocklist: rm", PolicyDecision::deny("Blocked")));
+        log.record(record("blocklist: rm", PolicyDecision::deny("Blocked")));
+        log.record(record(
+            "git gate",
+            PolicyDecision::Escalate("Push".to_string()),
+        ));
+
+        assert_eq!(
+            log.totals(),
+            DryRunCounts {
+                denied: 2,
+                escalated: 1
+            }
+        );
+        let by_rule = log.by_rule();
+        assert_eq!(by_rule["blocklist: rm"].denied, 2);
+        assert_eq!(by_rule["git gate"].escalated, 1);
+
+        let event = log.records()[0].audit_event(Uuid::nil(), &ToolAliases::with_defaults());
+        assert_eq!(event.event_type, EventType::DryRun);
+        assert_eq!(event.decision, Some(crate::audit::Decision::Deny));
+        assert_eq!(event.tool_name.as_deref(), Some("Bash"));
+        assert_eq!(event.tool_alias.as_deref(), Some("bash"));
+        assert_eq!(event.reason.as_deref(), Some("Blocked"));
+        assert_eq!(event.rule.as_deref(), Some("blocklist: rm"));
+    }
+}
@@ -0,0 +1,327 @@
+//! Policy rules on what Edit and `MultiEdit` calls change.
+//!
+//! The dangerous part of an edit is usually what it changes, not which file
+//! it touches: deleting a safety check, moving a version pin, rewriting a
+//! license header. An [`EditRule`] looks at each hunk of an edit, the lines
+//! it removes from `old_string` and the lines it adds in `new_string`, and
+//! at the edited file. Lines on both sides of a hunk are context and count
+//! as neither removed nor added.
+
+use std::collections::HashMap;
+
+use regex::Regex;
+
+use super::overrides::glob_to_regex;
+use crate::config::{EditRuleAction, EditRuleConfig};
+use crate::display;
+use crate::supervisor::PolicyDecision;
+
+/// Prefix of every edit rule denial or escalation reason.
+pub const EDIT_RULE_REASON: &str = "edit rule";
+
+/// Name of the edit rule that gave `reason`, if an edit rule gave it.
+#[must_use]
+pub fn edit_rule_name(reason: &str) -> Option<&str> {
+    let (name, _) = reason
+        .strip_prefix(EDIT_RULE_REASON)?
+        .strip_prefix(" '")?
+        .split_once("': ")?;
+    Some(name)
+}
+
+/// Longest matched line quoted in a reason.
+const MAX_QUOTED_LINE: usize = 120;
+
+/// Error type for compiling edit rules.
+#[derive(thiserror::Error, Debug, Clone, PartialEq, Eq)]
+pub enum EditRuleError {
+    /// The rule sets no condition and would match every edit.
+    #[error("Edit rule '{0}' has no conditions and would match every edit")]
+    NoConditions(String),
+    /// A glob or regex does not compile.
+    #[error("Invalid pattern in edit rule '{rule}': {message}")]
+    InvalidPattern { rule: String, message: String },
+}
+
+/// One replacement of an Edit or `MultiEdit` call.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct EditHunk<'a> {
+    /// Text being replaced.
+    pub old: &'a str,
+    /// Replacement text.
+    pub new: &'a str,
+}
+
+impl EditHunk<'_> {
+    /// Lines of the old text that are not in the new one.
+    #[must_use]
+    pub fn removed_lines(&self) -> Vec<&str> {
+        unmatched_lines(self.old, self.new)
+    }
+
+    /// Lines of the new text that are not in the old one.
+    #[must_use]
+    pub fn added_lines(&self) -> Vec<&str> {
+        unmatched_lines(self.new, self.old)
+    }
+}
+
+/// Hunks of an Edit (`old_string`, `new_string`) or `MultiEdit` (`edits`)
+/// input; empty for any other input.
+#[must_use]
+pub fn edit_hunks(tool_input: &serde_json::Value) -> Vec<EditHunk<'_>> {
+    match tool_input
+        .get("edits")
+        .and_then(serde_json::Value::as_array)
+    {
+        Some(edits) => edits.iter().filter_map(edit_hunk).collect(),
+        None => edit_hunk(tool_input).into_iter().collect(),
+    }
+}
+
+/// The hunk of one Edit input or `MultiEdit` entry.
+fn edit_hunk(value: &serde_json::Value) -> Option<EditHunk<'_>> {
+    let field = |name: &str| value.get(name).and_then(serde_json::Value::as_str);
+    Some(EditHunk {
+        old: field("old_string")?,
+        new: field("new_string").unwrap_or_default(),
+    })
+}
+
+/// Lines of `text` left after taking out each line of `other` once.
+fn unmatched_lines<'a>(text: &'a str, other: &str) -> Vec<&'a str> {
+    let mut remaining: HashMap<&str, usize> = HashMap::new();
+    for line in other.lines() {
+        *remaining.entry(line).or_default() += 1;
+    }
+    text.lines()
+        .filter(|line| match remaining.get_mut(line) {
+            Some(count) if *count > 0 => {
+                *count -= 1;
+                false
+            }
+            _ => true,
+        })
+        .collect()
+}
+
+/// A compiled [`EditRuleConfig`].
+#[derive(Debug, Clone)]
+pub struct EditRule {
+    name: String,
+    action: EditRuleAction,
+    file_glob: Option<Regex>,
+    removed: Option<Regex>,
+    added: Option<Regex>,
+}
+
+impl EditRule {
+    /// Compile a configured rule.
+    ///
+    /// # Errors
+    ///
+    /// Returns an error if the rule sets no condition or a pattern does not
+    /// compile.
+    pub fn from_config(config: &EditRuleConfig) -> Result<Self, EditRuleError> {
+        if config.file_glob.is_none()
+            && config.content_removed_matches.is_none()
+            && config.content_added_matches.is_none()
+        {
+            return Err(EditRuleError::NoConditions(config.name.clone()));
+        }
+        let compile = |pattern: &str| {
+            Regex::new(pattern).map_err(|e| EditRuleError::InvalidPattern {
+                rule: config.name.clone(),
+                message: e.to_string(),
+            })
+        };
+        Ok(Self {
+            name: config.name.clone(),
+            action: config.action,
+            file_glob: config
+                .file_glob
+                .as_deref()
+                .map(|glob| compile(&glob_to_regex(glob, true)))
+                .transpose()?,
+            removed: config
+                .content_removed_matches
+                .as_deref()
+                .map(compile)
+                .transpose()?,
+            added: config
+                .content_added_matches
+                .as_deref()
+                .map(compile)
+                .transpose()?,
+        })
+    }
+
+    /// Name of the rule.
+    #[must_use]
+    pub fn name(&self) -> &str {
+        &self.name
+    }
+
+    /// What happens to a matching call.
+    #[must_use]
+    pub fn action(&self) -> EditRuleAction {
+        self.action
+    }
+
+    /// Check an edit of the file known as any of `paths` with `hunks`.
+    ///
+    /// Returns what the first matching hunk does, for the reason.
+    #[must_use]
+    pub fn check(&self, paths: &[&str], hunks: &[EditHunk<'_>]) -> Option<String> {
+        if let Some(ref glob) = self.file_glob {
+            if !paths.iter().any(|path| glob.is_match(path)) {
+                return None;
+            }
+        }
+        hunks.iter().find_map(|hunk| self.check_hunk(hunk))
+    }
+
+    fn check_hunk(&self, hunk: &EditHunk<'_>) -> Option<String> {
+        let find = |regex: &Option<Regex>, lines: Vec<&str>| match regex {
+            Some(regex) => lines
+                .into_iter()
+                .find(|line| regex.is_match(line))
+                .map(|line| Some(display::truncate(line.trim(), MAX_QUOTED_LINE, false))),
+            None => Some(None),
+        };
+        let removed = find(&self.removed, hunk.removed_lines())?;
+        let added = find(&self.added, hunk.added_lines())?;
+        Some(match (removed, added) {
+            (Some(removed), Some(added)) => format!("removes `{removed}` and adds `{added}`"),
+            (Some(removed), None) => format!("removes `{removed}`"),
+            (None, Some(added)) => format!("adds `{added}`"),
+            (None, None) => "edits it".to_string(),
+        })
+    }
+
+    /// Decision for a matching call of `tool_name` on `path`, doing `what`.
+    #[must_use]
+    pub fn decision(&self, tool_name: &str, path: &str, what: &str) -> PolicyDecision {
+        let reason = format!(
+            "{EDIT_RULE_REASON} '{}': {tool_name} of {path} {what}",
+            self.name
+        );
+        match self.action {
+            EditRuleAction::Deny => PolicyDecision::deny(reason),
+            EditRuleAction::Escalate => PolicyDecision::Escalate(reason),
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use serde_json::json;
+
+    use super::*;
+
+    fn rule(file_glob: Option<&str>, removed: Option<&str>, added: Option<&str>) -> EditRule {
+        EditRule::from_config(&EditRuleConfig {
+            name: "test".to_string(),
+            file_glob: file_glob.map(String::from),
+            content_removed_matches: removed.map(String::from),
+            content_added_matches: added.map(String::from),
+            action: EditRuleAction::Escalate,
+        })
+        .unwrap()
+    }
+
+    #[test]
+    fn test_removed_lines_skip_context() {
+        let hunk = EditHunk {
+            old: "#![forbid(unsafe_code)]\n\nmod a;\nmod b;",
+            new: "\nmod a;\nmod b;\nmod c;",
+        };
+        assert_eq!(hunk.removed_lines(), ["#![forbid(unsafe_code)]"]);
+        assert_eq!(hunk.added_lines(), ["mod c;"]);
+    }
+
+    #[test]
+    fn test_edit_hunks_single_and_multi() {
+        let single = json!({
+            "file_path": "/repo/src/lib.rs",
+            "old_string": "fn a() {}",
+            "new_string": "fn b() {}",
+        });
+        assert_eq!(
+            edit_hunks(&single),
+            [EditHunk {
+                old: "fn a() {}",
+                new: "fn b() {}"
+            }]
+        );
+
+        let multi = json!({
+            "file_path": "/repo/src/lib.rs",
+            "edits": [
+                {"old_string": "use a;", "new_string": "use b;"},
+                {"old_string": "fn c() {}", "new_string": "", "replace_all": true},
+            ],
+        });
+        let hunks = edit_hunks(&multi);
+        assert_eq!(hunks.len(), 2);
+        assert_eq!(hunks[1].removed_lines(), ["fn c() {}"]);
+        assert!(edit_hunks(&json!({"file_path": "/repo/a", "content": "x"})).is_empty());
+    }
+
+    #[test]
+    fn test_rule_requires_every_condition() {
+        let forbid = rule(Some("**/*.rs"), Some(r"#!?\[forbid\(unsafe_code\)\]"), None);
+        let removing = [EditHunk {
+            old: "#![forbid(unsafe_code)]\n//! Crate docs.",
+            new: "//! Crate docs.",
+        }];
+        assert_eq!(
+            forbid.check(&["/repo/src/lib.rs"], &removing).as_deref(),
+            Some("removes `#![forbid(unsafe_code)]`")
+        );
+        assert_eq!(forbid.check(&["/repo/README.md"], &removing), None);
+
+        // Moving the attribute keeps it: context on both sides
+        let moving = [EditHunk {
+            old: "//! Crate docs.\n#![forbid(unsafe_code)]",
+            new: "#![forbid(unsafe_code)]\n//! Crate docs.",
+        }];
+        assert_eq!(forbid.check(&["/repo/src/lib.rs"], &moving), None);
+
+        let pin = rule(None, Some(r#"^serde = "=1\.0\.\d+""#), Some("^serde = "));
+        let bump = [EditHunk {
+            old: "serde = \"=1.0.188\"",
+            new: "serde = \"1\"",
+        }];
+        assert_eq!(
+            pin.check(&["/repo/Cargo.toml"], &bump).as_deref(),
+            Some("removes `serde = \"=1.0.188\"` and adds `serde = \"1\"`")
+        );
+        let removal = [EditHunk {
+            old: "serde = \"=1.0.188\"",
+            new: "",
+        }];
+        assert_eq!(pin.check(&["/repo/Cargo.toml"], &removal), None);
+    }
+
+    #[test]
+    fn test_invalid_rules() {
+        let empty = EditRuleConfig {
+            name: "empty".to_string(),
+            ..EditRuleConfig::default()
+        };
+        assert_eq!(
+            EditRule::from_config(&empty).unwrap_err(),
+            EditRuleError::NoConditions("empty".to_string())
+        );
+        let invalid = EditRuleConfig {
+            name: "invalid".to_string(),
+            content_added_matches: Some("(".to_string()),
+            ..EditRuleConfig::default()
+        };
+        assert!(matches!(
+            EditRule::from_config(&invalid),
+            Err(EditRuleError::InvalidPattern { .. })
+        ));
+    }
+}
@@ -0,0 +1,507 @@
+//! Moving deleted files to the trash instead of deleting them.
+//!
+//! With escrow enabled, a Bash `rm` of paths inside the project is rewritten
+//! into moves to the session's [trash](crate::trash), leaving the rest of the
+//! command line as it was, so a cleanup does not have to be denied to be
+//! undoable. Globs are expanded when the call is decided so that every file
+//! moved is checked. A deletion the rewrite cannot be sure about, such as
+//! `rm` behind `sudo` or `xargs`, `find -delete`, an unknown option, a shell
+//! variable, or an `rm` after a change of directory, is escalated rather than
+//! run as it is.
+
+use std::path::{Path, PathBuf};
+
+use regex::Regex;
+use serde_json::Value;
+
+use super::overrides::glob_to_regex;
+use super::protect::normalize;
+use super::sandbox::{command_binaries, quote};
+use super::shell::{split_commands, ShellCommand, ShellWord};
+use crate::config::EscrowConfig;
+use crate::trash::TrashStore;
+
+/// Prefix of the reasons given for deletions the escrow refuses to rewrite.
+pub const ESCROW_REASON: &str = "Escrow";
+
+/// Session directory used when the session is not known.
+const UNKNOWN_SESSION: &str = "unknown";
+
+/// Binaries that delete the files they are given.
+const DELETING_BINARIES: &[&str] = &["rm", "unlink", "shred"];
+
+/// Outcome of rewriting a tool call's deletions.
+#[derive(Debug, Clone, PartialEq)]
+pub enum EscrowRewrite {
+    /// The call deletes nothing inside the project.
+    Unchanged,
+    /// The call with its deletions turned into moves to the trash.
+    Rewritten(Value),
+    /// The call deletes files in a way that cannot be rewritten safely.
+    Refused(String),
+}
+
+/// Rewrites deletions inside the project into moves to the trash.
+#[derive(Debug, Clone)]
+pub struct Escrow {
+    dir: PathBuf,
+    session: Option<String>,
+}
+
+impl Escrow {
+    /// Create an escrow moving files to `dir`, resolved against the cwd.
+    #[must_use]
+    pub fn new(dir: impl Into<PathBuf>) -> Self {
+        Self {
+            dir: dir.into(),
+            session: None,
+        }
+    }
+
+    /// Create an escrow from configuration, if enabled.
+    #[must_use]
+    pub fn from_config(config: &EscrowConfig) -> Option<Self> {
+        config.enabled.then(|| Self::new(&config.dir))
+    }
+
+    /// Set the session whose trash files are moved to (builder pattern).
+    #[must_use]
+    pub fn with_session(mut self, session: impl Into<String>) -> Self {
+        self.session = Some(session.into());
+        self
+    }
+
+    /// Get the session whose trash files are moved to, if known.
+    #[must_use]
+    pub fn session(&self) -> Option<&str> {
+        self.session.as_deref()
+    }
+
+    /// Trash of the project at `cwd`.
+    #[must_use]
+    pub fn store(&self, cwd: &Path) -> TrashStore {
+        TrashStore::new(&self.dir, cwd)
+    }
+
+    /// Rewrite the deletions of a Bash call.
+    ///
+    /// `checked` is the sanitized form of `tool_input` (see
+    /// [`sanitize_tool_input`](super::sanitize_tool_input)); the original
+    /// command is what gets rewritten.
+    #[must_use]
+    pub fn rewrite_bash(&self, checked: &Value, tool_input: &Value, cwd: &Path) -> EscrowRewrite {
+        let Some(command) = tool_input.get("command").and_then(Value::as_str) else {
+            return EscrowRewrite::Unchanged;
+        };
+        let Some(commands) = split_commands(command) else {
+            let checked = checked.get("command").and_then(Value::as_str);
+            return match checked
+                .into_iter()
+                .flat_map(command_binaries)
+                .find(|binary| DELETING_BINARIES.contains(binary))
+            {
+                Some(binary) => refused(&format!(
+                    "`{binary}` in a command line that cannot be split"
+                )),
+                None => EscrowRewrite::Unchanged,
+            };
+        };
+
+        let store = self.store(cwd);
+        let changes_dir = commands
+            .iter()
+            .any(|simple| matches!(simple.words[0].text.as_str(), "cd" | "pushd" | "popd"));
+        let mut edits = Vec::new();
+        for simple in &commands {
+            let Some(deletion) = deletion(simple) else {
+                continue;
+            };
+            if deletion != "rm" || simple.words[0].text != "rm" {
+                return refused(&format!("`{deletion}` is not a plain `rm`"));
+            }
+            if changes_dir {
+                return refused("`rm` after a change of directory");
+            }
+            if simple.redirects {
+                return refused("`rm` with redirections");
+            }
+            match self.moves(simple, &store) {
+                Ok(Some(replacement)) => edits.push((simple.span.clone(), replacement)),
+                Ok(None) => {}
+                Err(reason) => return refused(&reason),
+            }
+        }
+        if edits.is_empty() {
+            return EscrowRewrite::Unchanged;
+        }
+
+        let mut rewritten_command = command.to_string();
+        for (span, replacement) in edits.into_iter().rev() {
+            let replacement = if commands.len() > 1 {
+                format!("{{ {replacement}; }}")
+            } else {
+                replacement
+            };
+            rewritten_command.replace_range(span, &replacement);
+        }
+        let mut rewritten = tool_input.clone();
+        rewritten["command"] = Value::String(rewritten_command);
+        EscrowRewrite::Rewritten(rewritten)
+    }
+
+    /// Commands moving the files an `rm` deletes inside the project to the
+    /// trash, or `None` if it deletes nothing there.
+    fn moves(&self, simple: &ShellCommand, store: &TrashStore) -> Result<Option<String>, String> {
+        let rm = RmCall::parse(&simple.words[1..])?;
+        let cwd = store.project();
+        let mut paths = Vec::new();
+        for operand in rm.operands {
+            paths.extend(expand(operand, cwd, rm.force)?);
+        }
+        // A file inside a directory already moved goes with it
+        paths.sort();
+        paths.dedup();
+        let paths: Vec<PathBuf> = paths
+            .iter()
+            .filter(|path| {
+                !paths
+                    .iter()
+                    .any(|other| other != *path && path.starts_with(other))
+            })
+            .cloned()
+            .collect();
+
+        let session = self.session.as_deref().unwrap_or(UNKNOWN_SESSION);
+        let mut inside = Vec::new();
+        let mut outside = false;
+        for path in paths {
+            if store.project().starts_with(&path) || store.root().starts_with(&path) {
+                return Err(format!(
+                    "`rm` of {}, which holds the project or its trash",
+                    path.display()
+                ));
+            }
+            if store.relative(&path).is_err() || path.starts_with(store.root()) {
+                outside = true;
+                continue;
+            }
+            let Ok(metadata) = path.symlink_metadata() else {
+                if rm.force {
+                    continue;
+                }
+                return Err(format!("`rm` of {}, which does not exist", path.display()));
+            };
+            if metadata.is_dir() && !rm.recursive {
+                let empty =
+                    std::fs::read_dir(&path).is_ok_and(|mut entries| entries.next().is_none());
+                if !(rm.dir && empty) {
+                    return Err(format!(
+                        "`rm` of the directory {} without -r",
+                        path.display()
+                    ));
+                }
+            }
+            let trashed = store
+                .trashed_path(session, &path)
+                .map_err(|e| e.to_string())?;
+            if trashed.symlink_metadata().is_ok() {
+                return Err(format!(
+                    "`rm` of {}, which is already in the trash",
+                    path.display()
+                ));
+            }
+            inside.push((path, trashed));
+        }
+
+        match (inside.is_empty(), outside) {
+            (false, true) => Err("`rm` of files both inside and outside the project".to_string()),
+            (true, true) => Ok(None),
+            (true, false) => Ok(Some("true".to_string())),
+            (false, false) => {
+                let mut parents: Vec<&Path> = inside
+                    .iter()
+                    .filter_map(|(_, trashed)| trashed.parent())
+                    .collect();
+                parents.dedup();
+                let mkdir = parents
+                    .iter()
+                    .map(|parent| quote(&parent.to_string_lossy()));
+                let mut commands = vec![format!(
+                    "mkdir -p -- {}",
+                    mkdir.collect::<Vec<_>>().join(" ")
+                )];
+                commands.extend(inside.iter().map(|(path, trashed)| {
+                    format!(
+                        "mv -- {} {}",
+                        quote(&path.to_string_lossy()),
+                        quote(&trashed.to_string_lossy())
+                    )
+                }));
+                Ok(Some(commands.join(" && ")))
+            }
+        }
+    }
+}
+
+/// Options and operands of an `rm`.
+#[derive(Debug, Default)]
+struct RmCall<'a> {
+    recursive: bool,
+    force: bool,
+    dir: bool,
+    operands: Vec<&'a ShellWord>,
+}
+
+impl<'a> RmCall<'a> {
+    /// Parse the arguments of an `rm`, refusing options it does not know.
+    fn parse(args: &'a [ShellWord]) -> Result<Self, String> {
+        let mut rm = Self::default();
+        let mut options = true;
+        for arg in args {
+            let text = arg.text.as_str();
+            if !options || arg.quoted || !text.starts_with('-') || text == "-" {
+                rm.operands.push(arg);
+                continue;
+            }
+            match text {
+                "--" => options = false,
+                "--recursive" => rm.recursive = true,
+                "--force" => rm.force = true,
+                "--dir" => rm.dir = true,
+                "--verbose" => {}
+                _ if text.starts_with("--") => return Err(format!("unknown option `rm {text}`")),
+                _ => {
+                    for flag in text[1..].chars() {
+                        match flag {
+                            'r' | 'R' => rm.recursive = true,
+                            'f' => rm.force = true,
+                            'd' => rm.dir = true,
+                            'v' => {}
+                            _ => return Err(format!("unknown option `rm -{flag}`")),
+                        }
+                    }
+                }
+            }
+        }
+        Ok(rm)
+    }
+}
+
+/// Name of the deletion a simple command makes, if any.
+fn deletion(simple: &ShellCommand) -> Option<String> {
+    let words: Vec<&str> = simple.texts().collect();
+    // Skip `VAR=value` prefixes and wrappers that run the next word
+    let start = words.iter().position(|word| {
+        !word.contains('=')
+            && !word.starts_with('-')
+            && !matches!(
+                *word,
+                "sudo" | "doas" | "env" | "exec" | "command" | "nohup" | "nice" | "time" | "xargs"
+            )
+    })?;
+    let binary = words[start].rsplit('/').next().unwrap_or(words[start]);
+    match binary {
+        _ if DELETING_BINARIES.contains(&binary) => Some(binary.to_string()),
+        "git" if words.get(start + 1) == Some(&"rm") => Some("git rm".to_string()),
+        "find" if words.contains(&"-delete") => Some("find -delete".to_string()),
+        _ => None,
+    }
+}
+
+/// Absolute paths an `rm` operand names, expanding a glob in its last
+/// component.
+fn expand(operand: &ShellWord, cwd: &Path, force: bool) -> Result<Vec<PathBuf>, String> {
+    let text = operand.text.as_str();
+    let glob = text.contains(['*', '?', '[']);
+    if text.contains('$') || (!operand.quoted && text.starts_with('~')) {
+        return Err(format!("`rm {text}`, which the shell expands"));
+    }
+    if !glob {
+        return Ok(vec![normalize(&cwd.join(text))]);
+    }
+    if operand.quoted || text.contains('[') {
+        return Err(format!("`rm {text}`, which may be a glob"));
+    }
+    let path = Path::new(text);
+    let (Some(parent), Some(name)) = (path.parent(), path.file_name().and_then(|n| n.to_str()))
+    else {
+        return Err(format!("`rm {text}`"));
+    };
+    if parent.to_string_lossy().contains(['*', '?']) {
+        return Err(format!("`rm {text}`, which globs directories"));
+    }
+
+    let dir = normalize(&cwd.join(parent));
+    let pattern = Regex::new(&glob_to_regex(name, true)).map_err(|e| e.to_string())?;
+    let mut matches: Vec<PathBuf> = std::fs::read_dir(&dir)
+        .into_iter()
+        .flatten()
+        .filter_map(Result::ok)
+        .filter_map(|entry| entry.file_name().to_str().map(ToString::to_string))
+        // Like the shell, `*` does not match hidden files
+        .filter(|file| pattern.is_match(file) && (!file.starts_with('.') || name.starts_with('.')))
+        .map(|file| dir.join(file))
+        .collect();
+    if matches.is_empty() && !force {
+        return Err(format!("`rm {text}`, which matches no files"));
+    }
+    matches.sort();
+    Ok(matches)
+}
+
+/// Reason for refusing to rewrite a deletion.
+fn refused(what: &str) -> EscrowRewrite {
+    EscrowRewrite::Refused(format!(
+        "{ESCROW_REASON}: deletion not moved to the trash: {what}"
+    ))
+}
+
+#[cfg(test)]
+mod tests {
+    use std::process::Command;
+
+    use super::*;
+    use serde_json::json;
+
+    fn setup() -> (tempfile::TempDir, Escrow) {
+        let dir = tempfile::tempdir().unwrap();
+        for file in ["a.log", "b.log", "keep.txt", ".hidden.log", "build/out/x.o"] {
+            let path = dir.path().join(file);
+            std::fs::create_dir_all(path.parent().unwrap()).unwrap();
+            std::fs::write(path, file).unwrap();
+        }
+        (
+            dir,
+            Escrow::new(".claude-supervisor/trash").with_session("s1"),
+        )
+    }
+
+    /// Rewrite `command` and run the result in `cwd`.
+    fn run(escrow: &Escrow, command: &str, cwd: &Path) -> String {
+        let input = json!({ "command": command });
+        let EscrowRewrite::Rewritten(rewritten) = escrow.rewrite_bash(&input, &input, cwd) else {
+            panic!("{command} was not rewritten");
+        };
+        let rewritten = rewritten["command"].as_str().unwrap().to_string();
+        let output = Command::new("sh")
+            .arg("-c")
+            .arg(&rewritten)
+            .current_dir(cwd)
+            .output()
+            .unwrap();
+        assert!(output.status.success(), "{rewritten}: {output:?}");
+        String::from_utf8(output.stdout).unwrap()
+    }
+
+    fn trashed(escrow: &Escrow, cwd: &Path) -> Vec<String> {
+        escrow
+            .store(cwd)
+            .list(Some("s1"))
+            .unwrap()
+            .into_iter()
+            .map(|entry| entry.path.to_string_lossy().into_owned())
+            .collect()
+    }
+
+    #[test]
+    fn test_single_rm_moves_file_to_trash() {
+        let (dir, escrow) = setup();
+        run(&escrow, "rm a.log", dir.path());
+        assert!(!dir.path().join("a.log").exists());
+        assert_eq!(trashed(&escrow, dir.path()), ["a.log"]);
+        assert_eq!(
+            std::fs::read_to_string(dir.path().join(".claude-supervisor/trash/s1/a.log")).unwrap(),
+            "a.log"
+        );
+    }
+
+    #[test]
+    fn test_recursive_rm_keeps_rest_of_command_line() {
+        let (dir, escrow) = setup();
+        let output = run(
+            &escrow,
+            "echo start && rm -rf build ./missing | cat; echo done",
+            dir.path(),
+        );
+        assert_eq!(output, "start\ndone\n");
+        assert!(!dir.path().join("build").exists());
+        assert_eq!(trashed(&escrow, dir.path()), ["build/out/x.o"]);
+    }
+
+    #[test]
+    fn test_globbed_rm_moves_matches_and_restores() {
+        let (dir, escrow) = setup();
+        run(&escrow, "rm -v -- *.log", dir.path());
+        assert_eq!(trashed(&escrow, dir.path()), ["a.log", "b.log"]);
+        assert!(dir.path().join(".hidden.log").exists());
+        assert!(dir.path().join("keep.txt").exists());
+
+        // Deleting again in the same session cannot overwrite the trash
+        std::fs::write(dir.path().join("a.log"), "again").unwrap();
+        let input = json!({ "command": "rm a.log" });
+        assert!(matches!(
+            escrow.rewrite_bash(&input, &input, dir.path()),
+            EscrowRewrite::Refused(_)
+        ));
+        std::fs::remove_file(dir.path().join("a.log")).unwrap();
+
+        let restored = escrow.store(dir.path()).restore("s1", None).unwrap();
+        assert_eq!(restored.len(), 2);
+        assert_eq!(
+            std::fs::read_to_string(dir.path().join("b.log")).unwrap(),
+            "b.log"
+        );
+        assert!(trashed(&escrow, dir.path()).is_empty());
+    }
+
+    #[test]
+    fn test_unsafe_deletions_are_refused() {
+        let (dir, escrow) = setup();
+        for command in [
+            "sudo rm a.log",
+            "find . -name '*.log' -delete",
+            "ls | xargs rm",
+            "git rm keep.txt",
+            "cd build && rm -r out",
+            "rm -i a.log",
+            "rm $FILE",
+            "rm \"*.log\"",
+            "rm build",
+            "rm missing.txt",
+            "rm nothing*.txt",
+            "rm a.log /tmp/elsewhere",
+            "rm -rf .",
+            "rm -rf .claude-supervisor",
+            "rm a.log 2>/dev/null",
+            "echo $(rm a.log)",
+        ] {
+            let input = json!({ "command": command });
+            assert!(
+                matches!(
+                    escrow.rewrite_bash(&input, &input, dir.path()),
+                    EscrowRewrite::Refused(ref reason) if reason.starts_with(ESCROW_REASON)
+                ),
+                "{command}"
+            );
+        }
+        for command in [
+            "ls -la",
+            "rm -rf /tmp/elsewhere",
+            "rmdir build/empty",
+            "echo $(date)",
+        ] {
+            let input = json!({ "command": command });
+            assert_eq!(
+                escrow.rewrite_bash(&input, &input, dir.path()),
+                EscrowRewrite::Unchanged,
+                "{command}"
+            );
+        }
+        // Forced deletions of missing files do nothing
+        let input = json!({ "command": "rm -f missing.txt" });
+        assert_eq!(
+            escrow.rewrite_bash(&input, &input, dir.path()),
+            EscrowRewrite::Rewritten(json!({ "command": "true" }))
+        );
+    }
+}
@@ -0,0 +1,991 @@
+//! Gating git commits, pushes, tags and history rewrites.
+//!
+//! Each simple command of a Bash call is checked for a git operation that
+//! publishes or rewrites history: a commit, a push, a tag, a deleted branch,
+//! a force push, a rebase or a history rewrite (`commit --amend`,
+//! `rebase -i`, `filter-branch`, `filter-repo`, or a push after a
+//! `reset --hard` in the same command line). Each kind has its own action,
+//! and operations on a protected branch are held to a stricter one: commits
+//! and pushes are escalated, while force pushes, rebases, rewrites and
+//! deletions are denied unless `protect_history` is turned off. The branch a
+//! commit or a push without a refspec targets is the one checked out in the
+//! working directory; when it cannot be read the branch counts as protected.
+
+use std::fmt::Write as _;
+use std::path::{Path, PathBuf};
+use std::time::Duration;
+
+use regex::RegexSet;
+
+use super::overrides::glob_to_regex;
+use super::sandbox::command_binaries;
+use super::shell::{split_commands, ShellCommand};
+use super::PolicyDecision;
+use crate::config::{GitAction, GitConfig};
+
+/// Prefix of the reasons given for gated git operations.
+pub const GIT_GATE_REASON: &str = "Git gate";
+
+/// How long each git command run for escalation context may take.
+pub const GIT_CONTEXT_TIMEOUT: Duration = Duration::from_secs(3);
+
+/// Git subcommands that may be gated, for command lines that cannot be split.
+const GATED_SUBCOMMANDS: &[&str] = &[
+    "commit",
+    "push",
+    "tag",
+    "branch",
+    "rebase",
+    "filter-branch",
+    "filter-repo",
+];
+
+/// Programs that run the command in their arguments, skipped with their
+/// options as the blocklist's command start skips them.
+const COMMAND_PREFIXES: &[&str] = &[
+    "sudo", "env", "command", "exec", "nohup", "time", "nice", "builtin",
+];
+
+/// Git options taking a value in the next word.
+const GIT_VALUE_OPTIONS: &[&str] = &[
+    "-C",
+    "-c",
+    "--git-dir",
+    "--work-tree",
+    "--namespace",
+    "--exec-path",
+    "--config-env",
+];
+
+/// `git rebase` options taking a value in the next word.
+const REBASE_VALUE_OPTIONS: &[&str] = &[
+    "--onto",
+    "-s",
+    "--strategy",
+    "-X",
+    "--strategy-option",
+    "-x",
+    "--exec",
+];
+
+/// `git rebase` options acting on a rebase in progress.
+const REBASE_CONTROL_OPTIONS: &[&str] = &[
+    "--continue",
+    "--skip",
+    "--abort",
+    "--quit",
+    "--edit-todo",
+    "--show-current-patch",
+];
+
+/// `git push` options taking a value in the next word.
+const PUSH_VALUE_OPTIONS: &[&str] = &["-o", "--push-option", "--repo", "--receive-pack", "--exec"];
+
+/// `git tag` options that only list or verify tags.
+const TAG_LIST_OPTIONS: &[&str] = &[
+    "-l",
+    "--list",
+    "-v",
+    "--verify",
+    "--contains",
+    "--no-contains",
+    "--merged",
+    "--no-merged",
+    "--points-at",
+];
+
+/// Kind of a gated git operation.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum GitOperationKind {
+    /// `git commit`.
+    Commit,
+    /// `git push` without force.
+    Push,
+    /// `git push` with `--force`, `--force-with-lease`, `--mirror` or a
+    /// `+` refspec.
+    ForcePush,
+    /// `git tag` creating or deleting a tag.
+    Tag,
+    /// `git branch -d` or a push deleting a remote branch.
+    BranchDelete,
+    /// `git rebase` without `--interactive`.
+    Rebase,
+    /// `git commit --amend`, `git rebase -i`, `git filter-branch`,
+    /// `git filter-repo` or a push after a `git reset --hard`.
+    HistoryRewrite,
+}
+
+impl GitOperationKind {
+    /// Name of the kind in reasons.
+    #[must_use]
+    pub fn name(self) -> &'static str {
+        match self {
+            Self::Commit => "commit",
+            Self::Push => "push",
+            Self::ForcePush => "force push",
+            Self::Tag => "tag",
+            Self::BranchDelete => "branch deletion",
+            Self::Rebase => "rebase",
+            Self::HistoryRewrite => "history rewrite",
+        }
+    }
+}
+
+/// A gated git operation in a Bash command.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct GitOperation {
+    /// Kind of the operation.
+    pub kind: GitOperationKind,
+    /// Remote pushed to, if named.
+    pub remote: Option<String>,
+    /// Branches the operation targets; empty for the checked-out branch.
+    pub branches: Vec<String>,
+    /// Directory given with `git -C`, if any.
+    pub dir: Option<String>,
+    /// The simple command running the operation.
+    pub command: String,
+}
+
+impl GitOperation {
+    fn new(kind: GitOperationKind, dir: Option<&str>, command: &str) -> Self {
+        Self {
+            kind,
+            remote: None,
+            branches: Vec::new(),
+            dir: dir.map(str::to_string),
+            command: command.to_string(),
+        }
+    }
+
+    /// Directory the operation runs in, relative to `cwd`.
+    fn work_dir(&self, cwd: &Path) -> PathBuf {
+        self.dir
+            .as_ref()
+            .map_or_else(|| cwd.to_path_buf(), |dir| cwd.join(dir))
+    }
+
+    /// Branches the operation targets, quoted for a reason: the ones it
+    /// names, or else the checked-out one if it can be read.
+    fn target_names(&self, cwd: &Path) -> Option<String> {
+        if !self.branches.is_empty() {
+            let names: Vec<String> = self
+                .branches
+                .iter()
+                .map(|branch| format!("`{branch}`"))
+                .collect();
+            return Some(names.join(", "));
+        }
+        // Tags and remote deletions by `--delete` alone are on no branch
+        if matches!(
+            self.kind,
+            GitOperationKind::Tag | GitOperationKind::BranchDelete
+        ) {
+            return None;
+        }
+        current_branch(&self.work_dir(cwd)).map(|branch| format!("`{branch}`"))
+    }
+}
+
+/// Git operations run by `command`, or `None` if it cannot be split.
+///
+/// A push after a `git reset --hard` publishes the rewritten history, so it
+/// counts as a history rewrite.
+#[must_use]
+pub fn git_operations(command: &str) -> Option<Vec<GitOperation>> {
+    let commands = split_commands(command)?;
+    let mut reset = false;
+    let mut operations = Vec::new();
+    for simple in &commands {
+        let Some((dir, subcommand, args)) = git_invocation(simple) else {
+            continue;
+        };
+        let text = &command[simple.span.clone()];
+        reset |= subcommand == "reset" && args.contains(&"--hard");
+        operations.extend(
+            subcommand_operations(dir, subcommand, &args, text)
+                .into_iter()
+                .map(|mut operation| {
+                    if reset && operation.kind == GitOperationKind::Push {
+                        operation.kind = GitOperationKind::HistoryRewrite;
+                    }
+                    operation
+                }),
+        );
+    }
+    Some(operations)
+}
+
+/// The `-C` directory, subcommand and arguments of a simple command running
+/// git, or `None` if it runs something else.
+///
+/// `VAR=value` assignments and [`COMMAND_PREFIXES`] with their options are
+/// skipped, so `command git push` and `env -i git push` run git.
+fn git_invocation(simple: &ShellCommand) -> Option<(Option<&str>, &str, Vec<&str>)> {
+    let mut prefixed = false;
+    let mut words = simple.texts().skip_while(|word| {
+        let prefix = COMMAND_PREFIXES.contains(word);
+        let skip = prefix || word.contains('=') || (prefixed && word.starts_with('-'));
+        prefixed |= prefix;
+        skip
+    });
+    if words
+        .next()
+        .map(|program| program.rsplit('/').next().unwrap_or(program))
+        != Some("git")
+    {
+        return None;
+    }
+
+    let mut dir = None;
+    let subcommand = loop {
+        let word = words.next()?;
+        if GIT_VALUE_OPTIONS.contains(&word) {
+            let value = words.next();
+            if word == "-C" {
+                dir = value;
+            }
+        } else if !word.starts_with('-') {
+            break word;
+        }
+    };
+    Some((dir, subcommand, words.collect()))
+}
+
+/// Git operations run by `git subcommand args` in the simple command `text`.
+fn subcommand_operations(
+    dir: Option<&str>,
+    subcommand: &str,
+    args: &[&str],
+    text: &str,
+) -> Vec<GitOperation> {
+    let operation = |kind| GitOperation::new(kind, dir, text);
+
+    match subcommand {
+        "commit" if args.contains(&"--amend") => vec![operation(GitOperationKind::HistoryRewrite)],
+        "commit" => vec![operation(GitOperationKind::Commit)],
+        "push" => push_operations(args, operation),
+        "tag" => tag_changes(args)
+            .then(|| operation(GitOperationKind::Tag))
+            .into_iter()
+            .collect(),
+        "branch"
+            if args.iter().any(|arg| {
+                is_short_flag(arg, 'd') || is_short_flag(arg, 'D') || *arg == "--delete"
+            }) =>
+        {
+            let mut deletion = operation(GitOperationKind::BranchDelete);
+            deletion.branches = args
+                .iter()
+                .filter(|arg| !arg.starts_with('-'))
+                .map(ToString::to_string)
+                .collect();
+            vec![deletion]
+        }
+        "rebase" => rebase_operation(args, operation).into_iter().collect(),
+        "filter-branch" | "filter-repo" => vec![operation(GitOperationKind::HistoryRewrite)],
+        _ => Vec::new(),
+    }
+}
+
+/// Operations of a `git push` with arguments `args`.
+///
+/// Refspecs deleting a branch become a branch deletion and the rest a push,
+/// forced if any of them or the options force it.
+fn push_operations(
+    args: &[&str],
+    operation: impl Fn(GitOperationKind) -> GitOperation,
+) -> Vec<GitOperation> {
+    let mut force = false;
+    let mut delete = false;
+    let mut positional = Vec::new();
+    let mut args = args.iter();
+    while let Some(&arg) = args.next() {
+        if PUSH_VALUE_OPTIONS.contains(&arg) {
+            args.next();
+        } else if arg == "--force"
+            || arg == "--mirror"
+            || arg.starts_with("--force-with-lease")
+            || is_short_flag(arg, 'f')
+        {
+            force = true;
+        } else if arg == "--delete" || is_short_flag(arg, 'd') {
+            delete = true;
+        } else if !arg.starts_with('-') {
+            positional.push(arg);
+        }
+    }
+
+    let remote = positional.first().map(ToString::to_string);
+    let mut pushed = Vec::new();
+    let mut deleted = Vec::new();
+    for refspec in positional.iter().skip(1) {
+        let (refspec, forced) = match refspec.strip_prefix('+') {
+            Some(refspec) => (refspec, true),
+            None => (*refspec, false),
+        };
+        force |= forced;
+        match refspec.split_once(':') {
+            Some(("", target)) => deleted.push(branch_name(target)),
+            Some((_, target)) if delete => deleted.push(branch_name(target)),
+            Some((_, target)) => pushed.push(branch_name(target)),
+            None if delete => deleted.push(branch_name(refspec)),
+            None => pushed.push(branch_name(refspec)),
+        }
+    }
+
+    let mut operations = Vec::new();
+    if !deleted.is_empty() || delete {
+        let mut deletion = operation(GitOperationKind::BranchDelete);
+        deletion.remote.clone_from(&remote);
+        deletion.branches = deleted;
+        operations.push(deletion);
+    }
+    if !pushed.is_empty() || (!delete && positional.len() <= 1) {
+        let kind = if force {
+            GitOperationKind::ForcePush
+        } else {
+            GitOperationKind::Push
+        };
+        let mut push = operation(kind);
+        push.remote = remote;
+        // `HEAD` is the checked-out branch, as is an empty target list
+        push.branches = pushed
+            .into_iter()
+            .filter(|branch| branch != "HEAD")
+            .collect();
+        operations.push(push);
+    }
+    operations
+}
+
+/// Operation of a `git rebase` with arguments `args`, targeting the branch
+/// it rewrites: the one named after the upstream, or with `--root` the first
+/// one named, or else the checked-out one.
+fn rebase_operation(
+    args: &[&str],
+    operation: impl Fn(GitOperationKind) -> GitOperation,
+) -> Option<GitOperation> {
+    let mut interactive = false;
+    let mut root = false;
+    let mut positional = Vec::new();
+    let mut args = args.iter();
+    while let Some(&arg) = args.next() {
+        if REBASE_CONTROL_OPTIONS.contains(&arg) {
+            return None;
+        } else if REBASE_VALUE_OPTIONS.contains(&arg) {
+            args.next();
+        } else if arg == "--interactive" || is_short_flag(arg, 'i') {
+            interactive = true;
+        } else if arg == "--root" {
+            root = true;
+        } else if !arg.starts_with('-') {
+            positional.push(arg);
+        }
+    }
+
+    let mut rebase = operation(if interactive {
+        GitOperationKind::HistoryRewrite
+    } else {
+        GitOperationKind::Rebase
+    });
+    rebase.branches = positional
+        .get(usize::from(!root))
+        .map(|branch| branch_name(branch))
+        .into_iter()
+        .collect();
+    Some(rebase)
+}
+
+/// Whether a `git tag` with arguments `args` creates or deletes a tag.
+fn tag_changes(args: &[&str]) -> bool {
+    if args
+        .iter()
+        .any(|arg| *arg == "--delete" || is_short_flag(arg, 'd'))
+    {
+        return true;
+    }
+    !args.is_empty()
+        && !args.iter().any(|arg| {
+            TAG_LIST_OPTIONS.contains(arg)
+                || arg.starts_with("--contains=")
+                || arg.starts_with("--points-at=")
+                || arg.starts_with("-n")
+        })
+}
+
+/// Whether `arg` is a group of short options containing `flag`.
+fn is_short_flag(arg: &str, flag: char) -> bool {
+    arg.strip_prefix('-')
+        .is_some_and(|flags| !flags.starts_with('-') && flags.contains(flag))
+}
+
+/// Branch name of a push target.
+fn branch_name(target: &str) -> String {
+    target
+        .strip_prefix("refs/heads/")
+        .unwrap_or(target)
+        .to_string()
+}
+
+/// Branch checked out in the repository containing `dir`, read from its
+/// `HEAD`; `None` outside a repository or with a detached `HEAD`.
+#[must_use]
+pub fn current_branch(dir: &Path) -> Option<String> {
+    let git_dir = dir.ancestors().find_map(|ancestor| {
+        let dot_git = ancestor.join(".git");
+        if dot_git.is_dir() {
+            return Some(dot_git);
+        }
+        // A worktree's `.git` file points at its git directory
+        let link = std::fs::read_to_string(&dot_git).ok()?;
+        let target = link.strip_prefix("gitdir:")?.trim();
+        Some(ancestor.join(target))
+    })?;
+    let head = std::fs::read_to_string(git_dir.join("HEAD")).ok()?;
+    head.trim()
+        .strip_prefix("ref: refs/heads/")
+        .map(ToString::to_string)
+}
+
+/// Gates git operations in Bash commands.
+#[derive(Debug, Clone)]
+pub struct GitGate {
+    config: GitConfig,
+    protected: RegexSet,
+}
+
+impl GitGate {
+    /// Create a gate from configuration, if enabled.
+    ///
+    /// # Errors
+    ///
+    /// Returns an error if a protected branch glob cannot be compiled.
+    pub fn from_config(config: &GitConfig) -> Result<Option<Self>, regex::Error> {
+        if !config.enabled {
+            return Ok(None);
+        }
+        let protected = RegexSet::new(
+            config
+                .protected_branches
+                .iter()
+                .map(|glob| glob_to_regex(glob, true)),
+        )?;
+        Ok(Some(Self {
+            config: config.clone(),
+            protected,
+        }))
+    }
+
+    /// Whether `branch` is protected.
+    #[must_use]
+    pub fn is_protected(&self, branch: &str) -> bool {
+        self.protected.is_match(branch)
+    }
+
+    /// Decide a Bash command run in `cwd`.
+    ///
+    /// Returns `None` when every git operation in it is allowed, leaving the
+    /// command to the other rules.
+    #[must_use]
+    pub fn evaluate(&self, command: &str, cwd: &Path) -> Option<PolicyDecision> {
+        let Some(operations) = git_operations(command) else {
+            let gated = command_binaries(command).any(|binary| binary == "git")
+                && GATED_SUBCOMMANDS
+                    .iter()
+                    .any(|subcommand| command.contains(subcommand));
+            return gated.then(|| {
+                PolicyDecision::Escalate(format!(
+                    "{GIT_GATE_REASON}: git in a command line that cannot be split"
+                ))
+            });
+        };
+
+        let mut strictest: Option<(GitAction, String)> = None;
+        for operation in &operations {
+            let (action, reason) = self.decide(operation, cwd);
+            if strictest
+                .as_ref()
+                .is_none_or(|(strictest, _)| action > *strictest)
+            {
+                strictest = Some((action, reason));
+            }
+        }
+        match strictest? {
+            (GitAction::Allow, _) => None,
+            (GitAction::Escalate, reason) => Some(PolicyDecision::Escalate(reason)),
+            (GitAction::Deny, reason) => Some(PolicyDecision::deny(reason)),
+        }
+    }
+
+    /// Action for one operation and the reason given for it.
+    fn decide(&self, operation: &GitOperation, cwd: &Path) -> (GitAction, String) {
+        let kind = operation.kind;
+        let action = match kind {
+            GitOperationKind::Commit if self.config.allow_commit => GitAction::Allow,
+            GitOperationKind::Commit => GitAction::Escalate,
+            GitOperationKind::Push => self.config.allow_push,
+            GitOperationKind::ForcePush => self.config.force_push,
+            GitOperationKind::Tag => self.config.allow_tag,
+            GitOperationKind::BranchDelete => self.config.allow_branch_delete,
+            // Rebasing an unprotected branch only changes local history
+            GitOperationKind::Rebase => GitAction::Allow,
+            GitOperationKind::HistoryRewrite => self.config.history_rewrite,
+        };
+        // Tags are not on a branch
+        let protected = (kind != GitOperationKind::Tag)
+            .then(|| self.protected_target(operation, cwd))
+            .flatten();
+        let action = match (kind, &protected) {
+            (_, None) => action,
+            (GitOperationKind::Commit | GitOperationKind::Push, Some(_)) => {
+                action.max(GitAction::Escalate)
+            }
+            (_, Some(_)) if self.config.protect_history => GitAction::Deny,
+            (_, Some(_)) => action.max(GitAction::Escalate),
+        };
+
+        let reason = match protected {
+            None => format!(
+                "{GIT_GATE_REASON}: {}{} {} (`{}`)",
+                kind.name(),
+                operation
+                    .target_names(cwd)
+                    .map(|names| format!(" of {names}"))
+                    .unwrap_or_default(),
+                if action == GitAction::Deny {
+                    "is not allowed"
+                } else {
+                    "requires supervisor approval"
+                },
+                operation.command
+            ),
+            Some(branch) => format!(
+                "{GIT_GATE_REASON}: {} targets protected branch `{branch}` (`{}`)",
+                kind.name(),
+                operation.command
+            ),
+        };
+        (action, reason)
+    }
+
+    /// First protected branch `operation` targets, if any.
+    ///
+    /// An operation on the checked-out branch targets a protected one when
+    /// that branch cannot be read.
+    fn protected_target(&self, operation: &GitOperation, cwd: &Path) -> Option<String> {
+        if operation.branches.is_empty() {
+            // Deleting only remote branches named by `--delete` targets none
+            if operation.kind == GitOperationKind::BranchDelete {
+                return None;
+            }
+            return match current_branch(&operation.work_dir(cwd)) {
+                Some(branch) => self.is_protected(&branch).then_some(branch),
+                None => Some("HEAD".to_string()),
+            };
+        }
+        operation
+            .branches
+            .iter()
+            .find(|branch| self.is_protected(branch))
+            .cloned()
+    }
+
+    /// Context on the git operations of `command` for an escalation: the
+    /// checked-out branch, where each push goes and the staged diff stat.
+    ///
+    /// Git is run in `cwd`, each command for at most
+    /// [`GIT_CONTEXT_TIMEOUT`]. Returns `None` if the command runs no gated
+    /// operation.
+    pub async fn context(&self, command: &str, cwd: &Path) -> Option<String> {
+        let operations = git_operations(command)?;
+        let first = operations.first()?;
+        let dir = first.work_dir(cwd);
+
+        let mut context = String::new();
+        for operation in &operations {
+            let _ = writeln!(
+                context,
+                "- {}: `{}`",
+                operation.kind.name(),
+                operation.command
+            );
+        }
+        let branch = git_output(&dir, &["symbolic-ref", "--short", "-q", "HEAD"]).await;
+        let _ = writeln!(
+            context,
+            "\nCurrent branch: {}",
+            branch.as_deref().unwrap_or("unknown")
+        );
+        for operation in &operations {
+            let pushes = match operation.kind {
+                GitOperationKind::Push | GitOperationKind::ForcePush => true,
+                // A local branch deletion has no remote
+                GitOperationKind::BranchDelete => operation.remote.is_some(),
+                _ => false,
+            };
+            if !pushes {
+                continue;
+            }
+            let target = match (&operation.remote, operation.branches.is_empty()) {
+                (Some(remote), false) => format!("{remote} {}", operation.branches.join(" ")),
+                (Some(remote), true) => format!("{remote} {}", branch.as_deref().unwrap_or("HEAD")),
+                (None, _) => git_output(&dir, &["rev-parse", "--abbrev-ref", "@{upstream}"])
+                    .await
+                    .unwrap_or_else(|| "no upstream".to_string()),
+            };
+            let _ = writeln!(context, "Target of {}: {target}", operation.kind.name());
+        }
+        if let Some(stat) = git_output(&dir, &["diff", "--cached", "--stat"]).await {
+            let _ = write!(context, "\nStaged changes:\n{stat}");
+        }
+        Some(context.trim_end().to_string())
+    }
+}
+
+/// Trimmed standard output of `git args` in `dir`, or `None` if it fails,
+/// prints nothing or takes longer than [`GIT_CONTEXT_TIMEOUT`].
+async fn git_output(dir: &Path, args: &[&str]) -> Option<String> {
+    let output = tokio::process::Command::new("git")
+        .args(args)
+        .current_dir(dir)
+        .stdin(std::process::Stdio::null())
+        .kill_on_drop(true)
+        .output();
+    let output = tokio::time::timeout(GIT_CONTEXT_TIMEOUT, output)
+        .await
+        .ok()?
+        .ok()?;
+    let stdout = String::from_utf8_lossy(&output.stdout).trim().to_string();
+    (output.status.success() && !stdout.is_empty()).then_some(stdout)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn kinds(command: &str) -> Vec<(GitOperationKind, Vec<String>)> {
+        git_operations(command)
+            .unwrap()
+            .into_iter()
+            .map(|operation| (operation.kind, operation.branches))
+            .collect()
+    }
+
+    fn repo(branch: &str) -> tempfile::TempDir {
+        let dir = tempfile::tempdir().unwrap();
+        std::fs::create_dir(dir.path().join(".git")).unwrap();
+        std::fs::write(
+            dir.path().join(".git/HEAD"),
+            format!("ref: refs/heads/{branch}\n"),
+        )
+        .unwrap();
+        dir
+    }
+
+    #[test]
+    fn test_classifies_git_command_variants() {
+        use GitOperationKind::{
+            BranchDelete, Commit, ForcePush, HistoryRewrite, Push, Rebase, Tag,
+        };
+        let none = Vec::<String>::new;
+        let cases = [
+            ("git push", vec![(Push, none())]),
+            ("git push origin", vec![(Push, none())]),
+            ("git push --force", vec![(ForcePush, none())]),
+            (
+                "git push -fu origin feature",
+                vec![(ForcePush, vec!["feature".to_string()])],
+            ),
+            (
+                "git push --force-with-lease origin main",
+                vec![(ForcePush, vec!["main".to_string()])],
+            ),
+            (
+                "git push origin +main",
+                vec![(ForcePush, vec!["main".to_string()])],
+            ),
+            (
+                "git push origin HEAD:main",
+                vec![(Push, vec!["main".to_string()])],
+            ),
+            (
+                "git push origin HEAD:refs/heads/release/1.0",
+                vec![(Push, vec!["release/1.0".to_string()])],
+            ),
+            ("git push origin HEAD", vec![(Push, none())]),
+            (
+                "git push origin :old",
+                vec![(BranchDelete, vec!["old".to_string()])],
+            ),
+            (
+                "git push --delete origin old",
+                vec![(BranchDelete, vec!["old".to_string()])],
+            ),
+            ("git commit -m 'fix: push'", vec![(Commit, none())]),
+            (
+                "git commit --amend --no-edit",
+                vec![(HistoryRewrite, none())],
+            ),
+            ("git tag v1.0", vec![(Tag, none())]),
+            ("git tag -d v1.0", vec![(Tag, none())]),
+            ("git tag -l 'v*'", vec![]),
+            ("git tag", vec![]),
+            (
+                "git branch -D feature",
+                vec![(BranchDelete, vec!["feature".to_string()])],
+            ),
+            ("git branch --list", vec![]),
+            ("git rebase -i HEAD~3", vec![(HistoryRewrite, none())]),
+            ("git rebase main", vec![(Rebase, none())]),
+            (
+                "git rebase --onto main a feature",
+                vec![(Rebase, vec!["feature".to_string()])],
+            ),
+            (
+                "git rebase --root main",
+                vec![(Rebase, vec!["main".to_string()])],
+            ),
+            ("git rebase --continue", vec![]),
+            (
+                "git reset --hard origin/main && git push origin main",
+                vec![(HistoryRewrite, vec!["main".to_string()])],
+            ),
+            ("git reset --hard && git status", vec![]),
+            ("git push && git reset --hard", vec![(Push, none())]),
+            (
+                "git filter-branch --tree-filter 'rm x' HEAD",
+                vec![(HistoryRewrite, none())],
+            ),
+            ("git -C repo -c user.name=x push", vec![(Push, none())]),
+            (
+                "GIT_SSH_COMMAND=ssh /usr/bin/git push",
+                vec![(Push, none())],
+            ),
+            (
+                "cargo test && git add . && git commit -m x",
+                vec![(Commit, none())],
+            ),
+            ("command git push", vec![(Push, none())]),
+            ("env -i HOME=/tmp git commit -m x", vec![(Commit, none())]),
+            ("exec git tag v1.0", vec![(Tag, none())]),
+            ("command -v git", vec![]),
+            ("git status; echo git push", vec![]),
+        ];
+        for (command, expected) in cases {
+            assert_eq!(kinds(command), expected, "{command}");
+        }
+        let operation = &git_operations("git -C repo push upstream").unwrap()[0];
+        assert_eq!(operation.remote.as_deref(), Some("upstream"));
+        assert_eq!(operation.dir.as_deref(), Some("repo"));
+    }
+
+    #[test]
+    fn test_protected_branches_tighten_decisions() {
+        let config = GitConfig {
+            protected_branches: vec!["main".to_string(), "release/*".to_string()],
+            ..GitConfig::default()
+        };
+        let gate = GitGate::from_config(&config).unwrap().unwrap();
+        let feature = repo("feature");
+        let main = repo("main");
+
+        let decide = |command: &str, dir: &tempfile::TempDir| gate.evaluate(command, dir.path());
+        assert_eq!(decide("git commit -m x", &feature), None);
+        assert!(matches!(
+            decide("git commit -m x", &main),
+            Some(PolicyDecision::Escalate(reason)) if reason.contains("protected branch `main`")
+        ));
+        assert!(matches!(
+            decide("git push origin HEAD:release/2.0", &feature),
+            Some(PolicyDecision::Escalate(reason)) if reason.contains("`release/2.0`")
+        ));
+        assert!(matches!(
+            decide("git push", &feature),
+            Some(PolicyDecision::Escalate(_))
+        ));
+        assert!(matches!(
+            decide("git push --force origin feature", &feature),
+            Some(PolicyDecision::Deny(reason)) if reason.starts_with(GIT_GATE_REASON)
+        ));
+        assert!(matches!(
+            decide("git commit --amend", &main),
+            Some(PolicyDecision::Deny(_))
+        ));
+        assert!(matches!(
+            decide("git commit --amend", &feature),
+            Some(PolicyDecision::Escalate(_))
+        ));
+        assert!(matches!(
+            decide("git branch -d release/1.0", &feature),
+            Some(PolicyDecision::Deny(_))
+        ));
+        assert_eq!(decide("git tag v1", &main), None);
+
+        // The branch of a directory outside any repository cannot be read
+        let outside = tempfile::tempdir().unwrap();
+        assert!(matches!(
+            gate.evaluate("git commit -m x", outside.path()),
+            Some(PolicyDecision::Escalate(reason)) if reason.contains("`HEAD`")
+        ));
+        assert!(matches!(
+            gate.evaluate("git push $(git remote)", outside.path()),
+            Some(PolicyDecision::Escalate(_))
+        ));
+        assert_eq!(
+            gate.evaluate("git log $(git merge-base a b)", outside.path()),
+            None
+        );
+        assert!(GitGate::from_config(&GitConfig {
+            enabled: false,
+            ..GitConfig::default()
+        })
+        .unwrap()
+        .is_none());
+    }
+
+    #[test]
+    fn test_history_of_protected_branches_is_denied_in_any_argument_order() {
+        let config = GitConfig {
+            protected_branches: vec![
+                "main".to_string(),
+                "master".to_string(),
+                "release/*".to_string(),
+            ],
+            ..GitConfig::default()
+        };
+        let gate = GitGate::from_config(&config).unwrap().unwrap();
+        let feature = repo("feature");
+        let main = repo("main");
+
+        let denied = [
+            ("git push origin main --force", "main"),
+            ("git push origin main -f", "main"),
+            ("git push -f origin main", "main"),
+            ("git push --force-with-lease origin HEAD:main", "main"),
+            ("git push origin +master", "master"),
+            ("git push origin --delete release/1.0", "release/1.0"),
+            ("git push --delete origin main", "main"),
+            ("git push origin :main", "main"),
+            ("git branch -D main", "main"),
+            ("git branch main -D", "main"),
+            ("git branch --delete --force release/1.0", "release/1.0"),
+            ("git reset --hard HEAD~2 && git push origin main", "main"),
+            ("git rebase origin/main main", "main"),
+            ("git rebase -i HEAD~3 master", "master"),
+        ];
+        for (command, branch) in denied {
+            assert!(
+                matches!(
+                    gate.evaluate(command, feature.path()),
+                    Some(PolicyDecision::Deny(reason)) if reason.contains(&format!("`{branch}`"))
+                ),
+                "{command}"
+            );
+        }
+        // Prefixes that run git are looked through
+        let prefixed = [
+            ("command git push --force origin main", "main"),
+            ("command -p git push origin main -f", "main"),
+            ("env GIT_TRACE=1 git push -f origin main", "main"),
+            ("env -i git push --delete origin main", "main"),
+            ("exec git branch -D master", "master"),
+            ("nice -n5 git rebase origin/main main", "main"),
+            (
+                "cd repo && command git push origin +release/1.0",
+                "release/1.0",
+            ),
+        ];
+        for (command, branch) in prefixed {
+            assert!(
+                matches!(
+                    gate.evaluate(command, feature.path()),
+                    Some(PolicyDecision::Deny(reason)) if reason.contains(&format!("`{branch}`"))
+                ),
+                "{command}"
+            );
+        }
+        assert!(matches!(
+            gate.evaluate("command git commit --amend", feature.path()),
+            Some(PolicyDecision::Escalate(reason)) if reason.contains("history rewrite")
+        ));
+
+        // The checked-out branch is the target when none is named
+        for command in [
+            "git push --force",
+            "git rebase origin/main",
+            "git push -f origin",
+        ] {
+            assert!(
+                matches!(
+                    gate.evaluate(command, main.path()),
+                    Some(PolicyDecision::Deny(reason)) if reason.contains("`main`")
+                ),
+                "{command}"
+            );
+        }
+
+        assert_eq!(gate.evaluate("git rebase main", feature.path()), None);
+        assert_eq!(
+            gate.evaluate("git rebase --onto main a feature", feature.path()),
+            None
+        );
+        assert!(matches!(
+            gate.evaluate("git push --force origin feature", main.path()),
+            Some(PolicyDecision::Deny(reason)) if reason.contains("force push of `feature`")
+        ));
+        assert!(matches!(
+            gate.evaluate("git reset --hard HEAD~1 && git push", feature.path()),
+            Some(PolicyDecision::Escalate(reason)) if reason.contains("history rewrite of `feature`")
+        ));
+
+        // Turning protection off leaves the configured actions, escalated at least
+        let unprotected = GitGate::from_config(&GitConfig {
+            protect_history: false,
+            ..config
+        })
+        .unwrap()
+        .unwrap();
+        assert!(matches!(
+            unprotected.evaluate("git branch -D main", feature.path()),
+            Some(PolicyDecision::Escalate(reason)) if reason.contains("`main`")
+        ));
+        assert!(matches!(
+            unprotected.evaluate("git rebase main", main.path()),
+            Some(PolicyDecision::Escalate(_))
+        ));
+        assert!(matches!(
+            unprotected.evaluate("git push origin main --force", feature.path()),
+            Some(PolicyDecision::Deny(_))
+        ));
+    }
+
+    #[tokio::test]
+    async fn test_context_lists_target_and_staged_changes() {
+        let dir = tempfile::tempdir().unwrap();
+        let git = |args: &[&str]| {
+            std::process::Command::new("git")
+                .args(args)
+                .current_dir(dir.path())
+                .output()
+                .unwrap()
+        };
+        if !git(&["init", "-q", "-b", "main"]).status.success() {
+            return;
+        }
+        std::fs::write(dir.path().join("lib.rs"), "fn main() {}\n").unwrap();
+        git(&["add", "lib.rs"]);
+
+        let gate = GitGate::from_config(&GitConfig::default())
+            .unwrap()
+            .unwrap();
+        let context = gate
+            .context("git commit -m x && git push origin HEAD:main", dir.path())
+            .await
+            .unwrap();
+        assert!(context.contains("- commit: `git commit -m x`"), "{context}");
+        assert!(context.contains("Current branch: main"), "{context}");
+        assert!(context.contains("Target of push: origin main"), "{context}");
+        assert!(context.contains("lib.rs | 1 +"), "{context}");
+        assert!(gate.context("cargo test", dir.path()).await.is_none());
+    }
+}
@@ -0,0 +1,402 @@
+//! Health of a running supervisor.
+//!
+//! A process that exists is not necessarily supervising: its event loop may
+//! be stuck, Claude may have exited, the audit log may have become read-only
+//! or the AI provider unreachable. A [`HealthMonitor`] is shared between the
+//! supervisor, which beats it on every loop iteration, and whatever reports
+//! health: [`Supervisor::health`](super::Supervisor::health), the
+//! dashboard's `GET /healthz` route and the IPC status of a detached session.
+
+use std::path::{Path, PathBuf};
+use std::sync::{Arc, Mutex, PoisonError};
+use std::time::Duration;
+
+use serde::{Deserialize, Serialize};
+use tokio::time::Instant;
+
+use crate::ai::AiClient;
+
+/// Name of the check that the event loop is iterating.
+pub const EVENT_LOOP_CHECK: &str = "event_loop";
+
+/// Name of the check that the Claude process is alive.
+pub const CLAUDE_PROCESS_CHECK: &str = "claude_process";
+
+/// Name of the check that the audit log can be written.
+pub const AUDIT_CHECK: &str = "audit";
+
+/// Name of the check that the AI provider answers.
+pub const AI_PROVIDER_CHECK: &str = "ai_provider";
+
+/// How long the event loop may go without iterating before it counts as
+/// stalled.
+pub const DEFAULT_MAX_LOOP_AGE: Duration = Duration::from_secs(30);
+
+/// How often an idle event loop iterates to show it is alive.
+pub const HEARTBEAT_INTERVAL: Duration = Duration::from_secs(5);
+
+/// How long the result of asking the AI provider is reused.
+pub const DEFAULT_AI_CHECK_INTERVAL: Duration = Duration::from_mins(5);
+
+/// How long the AI provider has to answer a health check.
+const AI_CHECK_TIMEOUT: Duration = Duration::from_secs(10);
+
+/// Outcome of one health check.
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct HealthCheck {
+    /// Name of the check, such as [`EVENT_LOOP_CHECK`].
+    pub name: String,
+    /// Whether the check passed.
+    pub healthy: bool,
+    /// What was found, mostly given for failed checks.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub detail: Option<String>,
+}
+
+impl HealthCheck {
+    /// A passed check.
+    #[must_use]
+    pub fn pass(name: impl Into<String>) -> Self {
+        Self {
+            name: name.into(),
+            healthy: true,
+            detail: None,
+        }
+    }
+
+    /// A failed check and why it failed.
+    #[must_use]
+    pub fn fail(name: impl Into<String>, detail: impl Into<String>) -> Self {
+        Self {
+            name: name.into(),
+            healthy: false,
+            detail: Some(detail.into()),
+        }
+    }
+
+    /// A check passed if `result` is `Ok`, failed with its error otherwise.
+    fn from_result(name: &str, result: Result<(), String>) -> Self {
+        match result {
+            Ok(()) => Self::pass(name),
+            Err(detail) => Self::fail(name, detail),
+        }
+    }
+}
+
+/// Outcome of every health check of a supervisor.
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct HealthReport {
+    /// Whether every check passed.
+    pub healthy: bool,
+    /// The checks run, in order.
+    pub checks: Vec<HealthCheck>,
+}
+
+impl HealthReport {
+    /// Report of `checks`, healthy if all of them passed.
+    #[must_use]
+    pub fn new(checks: Vec<HealthCheck>) -> Self {
+        Self {
+            healthy: checks.iter().all(|check| check.healthy),
+            checks,
+        }
+    }
+
+    /// The checks that failed.
+    pub fn failing(&self) -> impl Iterator<Item = &HealthCheck> {
+        self.checks.iter().filter(|check| !check.healthy)
+    }
+
+    /// The check named `name`, if it was run.
+    #[must_use]
+    pub fn check(&self, name: &str) -> Option<&HealthCheck> {
+        self.checks.iter().find(|check| check.name == name)
+    }
+}
+
+/// The Claude process the supervisor watches.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+enum Child {
+    /// The process is managed elsewhere and not checked.
+    Untracked,
+    /// The process with this ID.
+    Running(u32),
+    /// The process has exited.
+    Exited,
+}
+
+/// Parts of the monitor the supervisor updates.
+#[derive(Debug)]
+struct Liveness {
+    last_beat: Option<Instant>,
+    child: Child,
+}
+
+/// Last answer of the AI provider.
+#[derive(Debug)]
+struct AiStatus {
+    checked_at: Instant,
+    result: Result<(), String>,
+}
+
+/// Health of a supervisor, shared by its clones.
+///
+/// Settings are copied into clones, so set them before handing clones out;
+/// heartbeats, the watched process and the cached AI answer are shared.
+#[derive(Debug, Clone)]
+pub struct HealthMonitor {
+    max_loop_age: Duration,
+    ai_check_interval: Duration,
+    audit_path: Option<PathBuf>,
+    ai: Option<AiClient>,
+    liveness: Arc<Mutex<Liveness>>,
+    ai_status: Arc<tokio::sync::Mutex<Option<AiStatus>>>,
+}
+
+impl Default for HealthMonitor {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+impl HealthMonitor {
+    /// A monitor that checks the event loop and the Claude process.
+    #[must_use]
+    pub fn new() -> Self {
+        Self {
+            max_loop_age: DEFAULT_MAX_LOOP_AGE,
+            ai_check_interval: DEFAULT_AI_CHECK_INTERVAL,
+            audit_path: None,
+            ai: None,
+            liveness: Arc::new(Mutex::new(Liveness {
+                last_beat: None,
+                child: Child::Untracked,
+            })),
+            ai_status: Arc::new(tokio::sync::Mutex::new(None)),
+        }
+    }
+
+    /// Count the event loop as stalled after `age` without iterating.
+    #[must_use]
+    pub fn with_max_loop_age(mut self, age: Duration) -> Self {
+        self.max_loop_age = age;
+        self
+    }
+
+    /// Also check that the audit database at `path` can be written.
+    #[must_use]
+    pub fn with_audit_path(mut self, path: impl Into<PathBuf>) -> Self {
+        self.audit_path = Some(path.into());
+        self
+    }
+
+    /// Also check that `client`'s provider answers, asking it again at most
+    /// every [`DEFAULT_AI_CHECK_INTERVAL`].
+    #[must_use]
+    pub fn with_ai_client(mut self, client: AiClient) -> Self {
+        self.ai = Some(client);
+        self
+    }
+
+    /// Ask the AI provider again at most every `interval`.
+    #[must_use]
+    pub fn with_ai_check_interval(mut self, interval: Duration) -> Self {
+        self.ai_check_interval = interval;
+        self
+    }
+
+    /// How long the event loop may go without iterating.
+    #[must_use]
+    pub fn max_loop_age(&self) -> Duration {
+        self.max_loop_age
+    }
+
+    /// Record an iteration of the event loop.
+    pub fn beat(&self) {
+        self.liveness().last_beat = Some(Instant::now());
+    }
+
+    /// Watch the Claude process with ID `pid`, or record that it exited if
+    /// `None`.
+    pub fn set_child(&self, pid: Option<u32>) {
+        self.liveness().child = pid.map_or(Child::Exited, Child::Running);
+    }
+
+    /// Run every check.
+    ///
+    /// The AI provider is only asked when its last answer is older than the
+    /// check interval; concurrent reports share one request.
+    pub async fn report(&self) -> HealthReport {
+        let (last_beat, child) = {
+            let liveness = self.liveness();
+            (liveness.last_beat, liveness.child)
+        };
+
+        let mut checks = vec![self.check_event_loop(last_beat)];
+        match child {
+            Child::Untracked => {}
+            Child::Running(pid) if process_exists(pid) => {
+                checks.push(HealthCheck::pass(CLAUDE_PROCESS_CHECK));
+            }
+            Child::Running(pid) => checks.push(HealthCheck::fail(
+                CLAUDE_PROCESS_CHECK,
+                format!("Claude process {pid} is gone"),
+            )),
+            Child::Exited => checks.push(HealthCheck::fail(
+                CLAUDE_PROCESS_CHECK,
+                "Claude process has exited",
+            )),
+        }
+        if let Some(ref path) = self.audit_path {
+            checks.push(HealthCheck::from_result(AUDIT_CHECK, audit_writable(path)));
+        }
+        if let Some(ref client) = self.ai {
+            checks.push(HealthCheck::from_result(
+                AI_PROVIDER_CHECK,
+                self.ai_reachable(client).await,
+            ));
+        }
+        HealthReport::new(checks)
+    }
+
+    fn check_event_loop(&self, last_beat: Option<Instant>) -> HealthCheck {
+        let Some(last_beat) = last_beat else {
+            return HealthCheck::fail(EVENT_LOOP_CHECK, "event loop has not started");
+        };
+        let age = Instant::now().saturating_duration_since(last_beat);
+        if age > self.max_loop_age {
+            HealthCheck::fail(
+                EVENT_LOOP_CHECK,
+                format!(
+                    "no loop iteration for {}s (limit {}s)",
+                    age.as_secs(),
+                    self.max_loop_age.as_secs()
+                ),
+            )
+        } else {
+            HealthCheck::pass(EVENT_LOOP_CHECK)
+        }
+    }
+
+    /// The provider's last answer, asking again if it is stale.
+    async fn ai_reachable(&self, client: &AiClient) -> Result<(), String> {
+        let mut status = self.ai_status.lock().await;
+        if let Some(ref status) = *status {
+            if status.checked_at.elapsed() < self.ai_check_interval {
+                return status.result.clone();
+            }
+        }
+        let result = match tokio::time::timeout(AI_CHECK_TIMEOUT, client.validate_model()).await {
+            Ok(result) => result.map_err(|e| e.to_string()),
+            Err(_) => Err(format!("no answer within {}s", AI_CHECK_TIMEOUT.as_secs())),
+        };
+        *status = Some(AiStatus {
+            checked_at: Instant::now(),
+            result: result.clone(),
+        });
+        result
+    }
+
+    fn liveness(&self) -> std::sync::MutexGuard<'_, Liveness> {
+        self.liveness.lock().unwrap_or_else(PoisonError::into_inner)
+    }
+}
+
+/// Whether a process with ID `pid` exists.
+#[cfg(unix)]
+fn process_exists(pid: u32) -> bool {
+    use nix::errno::Errno;
+    use nix::sys::signal::kill;
+    use nix::unistd::Pid;
+
+    let Ok(pid) = i32::try_from(pid) else {
+        return false;
+    };
+    // Signal 0 only checks that the process can be signalled
+    !matches!(kill(Pid::from_raw(pid), None), Err(Errno::ESRCH))
+}
+
+/// Whether a process with ID `pid` exists.
+#[cfg(not(unix))]
+fn process_exists(_pid: u32) -> bool {
+    true
+}
+
+/// Whether the audit database at `path`, or the directory it will be
+/// created in, can be written.
+fn audit_writable(path: &Path) -> Result<(), String> {
+    match std::fs::OpenOptions::new().append(true).open(path) {
+        Ok(_) => Ok(()),
+        Err(e) if e.kind() == std::io::ErrorKind::NotFound => {
+            let dir = path.parent().unwrap_or(Path::new("."));
+            match std::fs::metadata(dir) {
+                Ok(metadata) if metadata.is_dir() && !metadata.permissions().readonly() => Ok(()),
+                Ok(_) => Err(format!("{} is not a writable directory", dir.display())),
+                Err(e) => Err(format!("{}: {e}", dir.display())),
+            }
+        }
+        Err(e) => Err(format!("{}: {e}", path.display())),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[tokio::test(start_paused = true)]
+    async fn test_stalled_loop_fails_event_loop_check() {
+        let monitor = HealthMonitor::new().with_max_loop_age(Duration::from_secs(30));
+        let report = monitor.report().await;
+        assert!(!report.healthy);
+        assert_eq!(
+            report
+                .failing()
+                .map(|check| check.name.as_str())
+                .collect::<Vec<_>>(),
+            [EVENT_LOOP_CHECK]
+        );
+
+        monitor.clone().beat();
+        tokio::time::advance(Duration::from_secs(20)).await;
+        assert!(monitor.report().await.healthy);
+
+        tokio::time::advance(Duration::from_secs(20)).await;
+        let report = monitor.report().await;
+        assert!(!report.healthy);
+        let check = report.check(EVENT_LOOP_CHECK).unwrap();
+        assert!(check.detail.as_deref().unwrap().contains("40s"));
+    }
+
+    #[tokio::test]
+    async fn test_child_and_audit_checks() {
+        let dir = tempfile::tempdir().unwrap();
+        let monitor = HealthMonitor::new().with_audit_path(dir.path().join("audit.db"));
+        monitor.beat();
+        monitor.set_child(Some(std::process::id()));
+        let report = monitor.report().await;
+        assert!(report.healthy, "{report:?}");
+        assert!(report.check(CLAUDE_PROCESS_CHECK).unwrap().healthy);
+        assert!(report.check(AUDIT_CHECK).unwrap().healthy);
+
+        monitor.set_child(None);
+        let report = monitor.report().await;
+        assert!(!report.check(CLAUDE_PROCESS_CHECK).unwrap().healthy);
+
+        let missing = HealthMonitor::new().with_audit_path(dir.path().join("gone/audit.db"));
+        let report = missing.report().await;
+        assert!(!report.check(AUDIT_CHECK).unwrap().healthy);
+        assert!(report.check(CLAUDE_PROCESS_CHECK).is_none());
+    }
+
+    #[test]
+    fn test_report_serializes_failing_details_only() {
+        let report = HealthReport::new(vec![
+            HealthCheck::pass(EVENT_LOOP_CHECK),
+            HealthCheck::fail(AUDIT_CHECK, "read-only"),
+        ]);
+        let json = serde_json::to_value(&report).unwrap();
+        assert_eq!(json["healthy"], false);
+        assert!(json["checks"][0].get("detail").is_none());
+        assert_eq!(json["checks"][1]["detail"], "read-only");
+    }
+}
@@ -0,0 +1,425 @@
+//! Bounded event history used as escalation context.
+//!
+//! The history keeps the most recent events in memory, capped by count and
+//! by total serialized size. Oversized tool results are truncated on the way
+//! in, keeping a pointer to the event's position in the session transcript
+//! (the stream-json the supervisor prints, one event per line). Evicted
+//! events can optionally be written to a ring of temporary files so older
+//! context can still be read back.
+
+use std::collections::VecDeque;
+use std::fs::{self, File, OpenOptions};
+use std::io::{self, BufRead, BufReader, Write};
+use std::path::{Path, PathBuf};
+
+use serde::{Deserialize, Serialize};
+
+use crate::cli::{ClaudeEvent, ToolResult};
+use crate::config::HistoryConfig;
+
+/// An event kept in the history.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+struct HistoryEntry {
+    /// Position of the event in the session transcript.
+    seq: u64,
+    /// The event, with oversized tool results truncated.
+    event: ClaudeEvent,
+    /// Serialized size of the event.
+    #[serde(skip)]
+    bytes: usize,
+}
+
+/// Recent session events, capped by count and size.
+#[derive(Debug, Default)]
+pub struct EventHistory {
+    config: HistoryConfig,
+    entries: VecDeque<HistoryEntry>,
+    bytes: usize,
+    next_seq: u64,
+    spill: Option<SpillRing>,
+}
+
+impl EventHistory {
+    /// Create a history with the given limits.
+    ///
+    /// If spilling is enabled but the spill directory cannot be created,
+    /// evicted events are dropped instead.
+    #[must_use]
+    pub fn new(config: HistoryConfig) -> Self {
+        let spill = if config.spill {
+            SpillRing::create(config.spill_files, config.spill_file_bytes)
+                .inspect_err(|e| {
+                    tracing::warn!(error = %e, "Failed to create event history spill directory");
+                })
+                .ok()
+        } else {
+            None
+        };
+        Self {
+            config,
+            entries: VecDeque::new(),
+            bytes: 0,
+            next_seq: 0,
+            spill,
+        }
+    }
+
+    /// Get the history limits.
+    #[must_use]
+    pub fn config(&self) -> &HistoryConfig {
+        &self.config
+    }
+
+    /// Record an event, evicting the oldest ones beyond the limits.
+    ///
+    /// The newest event is always kept, even if it alone exceeds `max_bytes`.
+    pub fn push(&mut self, event: &ClaudeEvent) {
+        let seq = self.next_seq;
+        self.next_seq += 1;
+
+        let event = compact(event, seq, self.config.max_result_bytes);
+        let bytes = serde_json::to_string(&event).map_or(0, |json| json.len());
+        self.bytes += bytes;
+        self.entries.push_back(HistoryEntry { seq, event, bytes });
+
+        while self.entries.len() > 1
+            && (self.entries.len() > self.config.max_events || self.bytes > self.config.max_bytes)
+        {
+            let Some(evicted) = self.entries.pop_front() else {
+                break;
+            };
+            self.bytes -= evicted.bytes;
+            if let Some(ref mut spill) = self.spill {
+                if let Err(e) = spill.write(&evicted) {
+                    tracing::warn!(error = %e, "Failed to spill event history");
+                }
+            }
+        }
+    }
+
+    /// Iterate over the in-memory events, oldest first.
+    #[must_use]
+    pub fn iter(&self) -> impl DoubleEndedIterator<Item = &ClaudeEvent> {
+        self.entries.iter().map(|entry| &entry.event)
+    }
+
+    /// Number of events in memory.
+    #[must_use]
+    pub fn len(&self) -> usize {
+        self.entries.len()
+    }
+
+    /// Whether no events are in memory.
+    #[must_use]
+    pub fn is_empty(&self) -> bool {
+        self.entries.is_empty()
+    }
+
+    /// Number of events ever recorded, including evicted ones.
+    #[must_use]
+    pub fn recorded(&self) -> usize {
+        usize::try_from(self.next_seq).unwrap_or(usize::MAX)
+    }
+
+    /// Serialized size of the events in memory.
+    #[must_use]
+    pub fn bytes(&self) -> usize {
+        self.bytes
+    }
+
+    /// Read back up to `n` of the most recently evicted events, oldest first.
+    ///
+    /// Returns nothing if spilling is disabled.
+    ///
+    /// # Errors
+    ///
+    /// Returns an error if a spill file cannot be read.
+    pub fn spilled(&self, n: usize) -> io::Result<Vec<ClaudeEvent>> {
+        match self.spill {
+            Some(ref spill) => spill.read_last(n),
+            None => Ok(Vec::new()),
+        }
+    }
+
+    /// At least `min` events for context, if available: the in-memory events,
+    /// preceded by spilled ones when the in-memory window is shorter.
+    #[must_use]
+    pub fn context_events(&self, min: usize) -> Vec<ClaudeEvent> {
+        let missing = min.saturating_sub(self.entries.len());
+        let mut events = if missing == 0 {
+            Vec::new()
+        } else {
+            self.spilled(missing).unwrap_or_else(|e| {
+                tracing::warn!(error = %e, "Failed to read spilled event history");
+                Vec::new()
+            })
+        };
+        events.extend(self.iter().cloned());
+        events
+    }
+}
+
+/// Truncate a tool result longer than `max_bytes`, pointing at the full event.
+fn compact(event: &ClaudeEvent, seq: u64, max_bytes: usize) -> ClaudeEvent {
+    match event {
+        ClaudeEvent::ToolResult(result) if result.content.len() > max_bytes => {
+            let mut end = max_bytes;
+            while !result.content.is_char_boundary(end) {
+                end -= 1;
+            }
+            let omitted = result.content.len() - end;
+            ClaudeEvent::ToolResult(ToolResult {
+                tool_use_id: result.tool_use_id.clone(),
+                content: format!(
+                    "{}\n[... {omitted} bytes truncated; full result is transcript event {seq}]",
+                    &result.content[..end]
+                ),
+                is_error: result.is_error,
+            })
+        }
+        _ => event.clone(),
+    }
+}
+
+/// Ring of JSON Lines files holding evicted events.
+///
+/// The directory is removed when the ring is dropped.
+#[derive(Debug)]
+struct SpillRing {
+    dir: PathBuf,
+    /// Files in the ring, oldest first; the last one is being written.
+    files: VecDeque<PathBuf>,
+    next_file: u64,
+    current_bytes: u64,
+    max_files: usize,
+    max_file_bytes: u64,
+}
+
+impl SpillRing {
+    /// Create a ring in a new directory under the system temp directory.
+    fn create(max_files: usize, max_file_bytes: u64) -> io::Result<Self> {
+        let dir = std::env::temp_dir().join(format!(
+            "claude-supervisor-history-{}",
+            uuid::Uuid::new_v4()
+        ));
+        Self::create_in(dir, max_files, max_file_bytes)
+    }
+
+    fn create_in(dir: PathBuf, max_files: usize, max_file_bytes: u64) -> io::Result<Self> {
+        fs::create_dir_all(&dir)?;
+        let mut ring = Self {
+            dir,
+            files: VecDeque::new(),
+            next_file: 0,
+            current_bytes: 0,
+            max_files: max_files.max(1),
+            max_file_bytes,
+        };
+        ring.rotate()?;
+        Ok(ring)
+    }
+
+    /// Start a new file, deleting the oldest beyond the ring size.
+    fn rotate(&mut self) -> io::Result<()> {
+        let path = self.dir.join(format!("{:06}.jsonl", self.next_file));
+        File::create(&path)?;
+        self.next_file += 1;
+        self.files.push_back(path);
+        self.current_bytes = 0;
+        while self.files.len() > self.max_files {
+            if let Some(oldest) = self.files.pop_front() {
+                fs::remove_file(oldest)?;
+            }
+        }
+        Ok(())
+    }
+
+    /// Append an entry to the current file.
+    fn write(&mut self, entry: &HistoryEntry) -> io::Result<()> {
+        if self.current_bytes >= self.max_file_bytes {
+            self.rotate()?;
+        }
+        let mut line = serde_json::to_string(entry)?;
+        line.push('\n');
+        let Some(path) = self.files.back() else {
+            return Ok(());
+        };
+        OpenOptions::new()
+            .append(true)
+            .open(path)?
+            .write_all(line.as_bytes())?;
+        self.current_bytes += line.len() as u64;
+        Ok(())
+    }
+
+    /// Read up to `n` of the newest entries, oldest first.
+    fn read_last(&self, n: usize) -> io::Result<Vec<ClaudeEvent>> {
+        let mut events: VecDeque<ClaudeEvent> = VecDeque::with_capacity(n);
+        if n == 0 {
+            return Ok(Vec::new());
+        }
+        for path in self.files.iter().rev() {
+            let mut file_events = read_entries(path)?;
+            while let Some(event) = file_events.pop() {
+                events.push_front(event);
+                if events.len() == n {
+                    return Ok(events.into());
+                }
+            }
+        }
+        Ok(events.into())
+    }
+}
+
+impl Drop for SpillRing {
+    fn drop(&mut self) {
+        let _ = fs::remove_dir_all(&self.dir);
+    }
+}
+
+/// Read every parseable entry of a spill file, in order.
+fn read_entries(path: &Path) -> io::Result<Vec<ClaudeEvent>> {
+    let mut events = Vec::new();
+    for line in BufReader::new(File::open(path)?).lines() {
+        if let Ok(entry) = serde_json::from_str::<HistoryEntry>(&line?) {
+            events.push(entry.event);
+        }
+    }
+    Ok(events)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn result(id: &str, content: &str) -> ClaudeEvent {
+        ClaudeEvent::ToolResult(ToolResult {
+            tool_use_id: id.to_string(),
+            content: content.to_string(),
+            is_error: false,
+        })
+    }
+
+    fn content(event: &ClaudeEvent) -> &str {
+        match event {
+            ClaudeEvent::ToolResult(result) => &result.content,
+            _ => "",
+        }
+    }
+
+    #[test]
+    fn test_count_cap_evicts_oldest() {
+        let mut history = EventHistory::new(HistoryConfig {
+            max_events: 3,
+            ..HistoryConfig::default()
+        });
+        for i in 0..5 {
+            history.push(&result(&format!("t{i}"), "ok"));
+        }
+        assert_eq!(history.len(), 3);
+        assert_eq!(history.recorded(), 5);
+        let ids: Vec<&str> = history
+            .iter()
+            .map(|event| match event {
+                ClaudeEvent::ToolResult(result) => result.tool_use_id.as_str(),
+                _ => "",
+            })
+            .collect();
+        assert_eq!(ids, ["t2", "t3", "t4"]);
+    }
+
+    #[test]
+    fn test_byte_cap_evicts_oldest() {
+        let mut history = EventHistory::new(HistoryConfig {
+            max_bytes: 500,
+            ..HistoryConfig::default()
+        });
+        for i in 0..10 {
+            history.push(&result(&format!("t{i}"), &"x".repeat(100)));
+        }
+        assert!(history.bytes() <= 500);
+        assert!(history.len() < 10);
+        assert_eq!(
+            history.bytes(),
+            history
+                .iter()
+                .map(|event| serde_json::to_string(event).unwrap().len())
+                .sum::<usize>()
+        );
+
+        // A single event over the cap is still kept
+        history.push(&result("big", &"y".repeat(1000)));
+        assert_eq!(history.len(), 1);
+    }
+
+    #[test]
+    fn test_oversized_result_is_compacted() {
+        let mut history = EventHistory::new(HistoryConfig {
+            max_result_bytes: 10,
+            ..HistoryConfig::default()
+        });
+        history.push(&result("t0", "short"));
+        history.push(&result("t1", &"é".repeat(20)));
+
+        let events: Vec<&ClaudeEvent> = history.iter().collect();
+        assert_eq!(content(events[0]), "short");
+        let compacted = content(events[1]);
+        assert!(compacted.starts_with("ééééé\n"));
+        assert!(compacted.contains("30 bytes truncated"));
+        assert!(compacted.contains("transcript event 1"));
+    }
+
+    #[test]
+    fn test_spill_and_readback() {
+        let mut history = EventHistory::new(HistoryConfig {
+            max_events: 2,
+            spill: true,
+            spill_files: 2,
+            spill_file_bytes: 200,
+            ..HistoryConfig::default()
+        });
+        for i in 0..12 {
+            history.push(&result(&format!("t{i}"), "ok"));
+        }
+
+        let spilled = history.spilled(3).unwrap();
+        let contents: Vec<String> = spilled
+            .iter()
+            .map(|event| match event {
+                ClaudeEvent::ToolResult(result) => result.tool_use_id.clone(),
+                _ => String::new(),
+            })
+            .collect();
+        assert_eq!(contents, ["t7", "t8", "t9"]);
+
+        // Only the newest files of the ring are kept
+        assert!(history.spilled(100).unwrap().len() < 10);
+
+        let context = history.context_events(5);
+        assert_eq!(context.len(), 5);
+        assert_eq!(context[4], result("t11", "ok"));
+    }
+
+    #[test]
+    fn test_spill_directory_removed_on_drop() {
+        let dir = tempfile::tempdir().unwrap();
+        let spill_dir = dir.path().join("spill");
+        let ring = SpillRing::create_in(spill_dir.clone(), 2, 100).unwrap();
+        assert!(spill_dir.exists());
+        drop(ring);
+        assert!(!spill_dir.exists());
+    }
+
+    #[test]
+    fn test_without_spill_context_is_in_memory_window() {
+        let mut history = EventHistory::new(HistoryConfig {
+            max_events: 2,
+            ..HistoryConfig::default()
+        });
+        for i in 0..5 {
+            history.push(&result(&format!("t{i}"), "ok"));
+        }
+        assert!(history.spilled(3).unwrap().is_empty());
+        assert_eq!(history.context_events(5).len(), 2);
+    }
+}
@@ -0,0 +1,182 @@
+//! Nudging sessions that go quiet mid-task.
+//!
+//! Claude sometimes stops producing events without ending the session or
+//! firing a Stop hook: the process is alive but waiting on nothing. After
+//! the configured idle period, with no tool call running, the session is
+//! resumed with a short prompt, at most `max_nudges` times per run. Claude
+//! Code in print mode does not read stdin, so a nudge replaces the process
+//! with one resuming the same session, as context recovery does, instead of
+//! interrupting it.
+
+use std::collections::HashSet;
+use std::time::Duration;
+
+use chrono::{DateTime, Utc};
+use tokio::time::Instant;
+
+use crate::config::IdleNudgeConfig;
+
+/// Prompt an idle session is resumed with by default.
+pub const DEFAULT_NUDGE_MESSAGE: &str = "Please continue with the task.";
+
+/// A nudge sent to an idle session, recorded in the audit log.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct IdleNudge {
+    /// Nudge number, from 1.
+    pub number: u32,
+    /// Most nudges per run.
+    pub max_nudges: u32,
+    /// Claude session that was resumed.
+    pub session_id: String,
+    /// How long the session had been idle.
+    pub idle_for: Duration,
+    /// When the nudge was sent.
+    pub sent_at: DateTime<Utc>,
+}
+
+impl IdleNudge {
+    /// Human-readable description used in logs and the audit log.
+    #[must_use]
+    pub fn describe(&self) -> String {
+        format!(
+            "Session idle for {}s; nudge {} of {} sent by resuming the session",
+            self.idle_for.as_secs(),
+            self.number,
+            self.max_nudges
+        )
+    }
+}
+
+/// Watches a session for idle periods and counts the nudges sent.
+#[derive(Debug, Clone)]
+pub struct IdleWatch {
+    after: Duration,
+    max_nudges: u32,
+    message: String,
+    last_activity: Instant,
+    running_tools: HashSet<String>,
+    nudges: Vec<IdleNudge>,
+}
+
+impl IdleWatch {
+    /// Create a watch from the configuration; `None` when nudging is off.
+    #[must_use]
+    pub fn from_config(config: &IdleNudgeConfig) -> Option<Self> {
+        let after = Duration::from_secs(config.after_secs.filter(|&secs| secs > 0)?);
+        Some(Self::new(after, config.max_nudges, config.message.clone()))
+    }
+
+    /// Create a watch nudging after `after` without events, at most
+    /// `max_nudges` times, with `message`.
+    #[must_use]
+    pub fn new(after: Duration, max_nudges: u32, message: impl Into<String>) -> Self {
+        Self {
+            after,
+            max_nudges,
+            message: message.into(),
+            last_activity: Instant::now(),
+            running_tools: HashSet::new(),
+            nudges: Vec::new(),
+        }
+    }
+
+    /// Prompt the idle session is resumed with.
+    #[must_use]
+    pub fn message(&self) -> &str {
+        &self.message
+    }
+
+    /// Note an event of the session at `now`.
+    pub fn record_activity(&mut self, now: Instant) {
+        self.last_activity = now;
+    }
+
+    /// Note that an approved tool call started; a session waiting on a
+    /// tool is not idle.
+    pub fn tool_started(&mut self, tool_use_id: &str) {
+        self.running_tools.insert(tool_use_id.to_string());
+    }
+
+    /// Note that a tool call returned its result.
+    pub fn tool_finished(&mut self, tool_use_id: &str) {
+        self.running_tools.remove(tool_use_id);
+    }
+
+    /// When the session counts as idle, if it can still be nudged.
+    #[must_use]
+    pub fn deadline(&self) -> Option<Instant> {
+        let can_nudge = self.nudges.len() < self.max_nudges as usize;
+        (can_nudge && self.running_tools.is_empty()).then(|| self.last_activity + self.after)
+    }
+
+    /// Record a nudge of `session_id` at `now`; the idle clock starts over.
+    pub fn nudge(&mut self, now: Instant, session_id: impl Into<String>) -> &IdleNudge {
+        let nudge = IdleNudge {
+            number: u32::try_from(self.nudges.len() + 1).unwrap_or(u32::MAX),
+            max_nudges: self.max_nudges,
+            session_id: session_id.into(),
+            idle_for: now.saturating_duration_since(self.last_activity),
+            sent_at: Utc::now(),
+        };
+        self.last_activity = now;
+        self.running_tools.clear();
+        self.nudges.push(nudge);
+        &self.nudges[self.nudges.len() - 1]
+    }
+
+    /// Nudges sent so far, oldest first.
+    #[must_use]
+    pub fn nudges(&self) -> &[IdleNudge] {
+        &self.nudges
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_deadline_follows_activity_and_limit() {
+        let after = Duration::from_secs(45);
+        let mut watch = IdleWatch::new(after, 2, DEFAULT_NUDGE_MESSAGE);
+        let start = Instant::now();
+
+        watch.record_activity(start);
+        assert_eq!(watch.deadline(), Some(start + after));
+
+        // A running tool call is not idleness
+        watch.tool_started("toolu_1");
+        assert_eq!(watch.deadline(), None);
+        watch.tool_finished("toolu_1");
+
+        let idle = start + after;
+        let nudge = watch.nudge(idle, "s-1").clone();
+        assert_eq!(nudge.number, 1);
+        assert_eq!(nudge.idle_for, after);
+        assert_eq!(
+            nudge.describe(),
+            "Session idle for 45s; nudge 1 of 2 sent by resuming the session"
+        );
+        assert_eq!(watch.deadline(), Some(idle + after));
+
+        watch.nudge(idle + after, "s-1");
+        assert_eq!(watch.nudges().len(), 2);
+        assert_eq!(watch.deadline(), None);
+    }
+
+    #[test]
+    fn test_from_config() {
+        assert!(IdleWatch::from_config(&IdleNudgeConfig::default()).is_none());
+        let config = IdleNudgeConfig {
+            after_secs: Some(0),
+            ..IdleNudgeConfig::default()
+        };
+        assert!(IdleWatch::from_config(&config).is_none());
+        let config = IdleNudgeConfig {
+            after_secs: Some(300),
+            ..IdleNudgeConfig::default()
+        };
+        let watch = IdleWatch::from_config(&config).unwrap();
+        assert_eq!(watch.message(), DEFAULT_NUDGE_MESSAGE);
+    }
+}
@@ -0,0 +1,138 @@
+//! Kill-reason taxonomy and retry advice for killed sessions.
+
+use std::fmt;
+
+use serde::{Deserialize, Serialize};
+
+/// Why the supervisor killed a session.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum KillCause {
+    /// The policy engine denied a tool call (blocklist, denied tool, sensitive path).
+    PolicyDeny,
+    /// A tool call needed escalation but no AI supervisor was configured.
+    EscalationUnavailable,
+    /// The AI supervisor denied an escalated tool call.
+    AiDenial,
+    /// The AI supervisor failed or timed out while reviewing a tool call.
+    AiError,
+    /// A stuck pattern (loop, repeated failures) was detected.
+    StuckPattern,
+    /// An approved tool call produced no result before its timeout.
+    ToolTimeout,
+    /// A cost, token or time budget was exhausted.
+    Budget,
+    /// An operator stopped the session (dashboard or signal).
+    Operator,
+}
+
+impl KillCause {
+    /// Returns the string representation for storage and display.
+    #[must_use]
+    pub fn as_str(&self) -> &'static str {
+        match self {
+            Self::PolicyDeny => "policy_deny",
+            Self::EscalationUnavailable => "escalation_unavailable",
+            Self::AiDenial => "ai_denial",
+            Self::AiError => "ai_error",
+            Self::StuckPattern => "stuck_pattern",
+            Self::ToolTimeout => "tool_timeout",
+            Self::Budget => "budget",
+            Self::Operator => "operator",
+        }
+    }
+
+    /// Process exit code for a session killed with this cause.
+    ///
+    /// Codes start at 10 so they never collide with generic failures (1) or
+    /// the argument errors clap reports (2).
+    #[must_use]
+    pub fn exit_code(&self) -> i32 {
+        match self {
+            Self::PolicyDeny => 10,
+            Self::EscalationUnavailable => 11,
+            Self::AiDenial => 12,
+            Self::AiError => 13,
+            Self::StuckPattern => 14,
+            Self::Budget => 15,
+            Self::Operator => 16,
+            Self::ToolTimeout => 17,
+        }
+    }
+}
+
+impl fmt::Display for KillCause {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.write_str(self.as_str())
+    }
+}
+
+/// Machine-readable advice on whether a killed session can be retried.
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(tag = "action", rename_all = "snake_case")]
+pub enum RetryHint {
+    /// Retrying unchanged is expected to be killed again.
+    DoNotRetry,
+    /// The failure was transient; retrying unchanged may succeed.
+    RetryAsIs,
+    /// Safe to retry once the tool is pre-approved with `--allowed-tools`.
+    AllowTool {
+        /// Tool that needs approval.
+        tool: String,
+    },
+}
+
+impl fmt::Display for RetryHint {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            Self::DoNotRetry => f.write_str("do not retry"),
+            Self::RetryAsIs => f.write_str("safe to retry as is"),
+            Self::AllowTool { tool } => write!(f, "safe to retry with --allowed-tools {tool}"),
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_kill_cause_as_str_matches_serde() {
+        for cause in [
+            KillCause::PolicyDeny,
+            KillCause::EscalationUnavailable,
+            KillCause::AiDenial,
+            KillCause::AiError,
+            KillCause::StuckPattern,
+            KillCause::ToolTimeout,
+            KillCause::Budget,
+            KillCause::Operator,
+        ] {
+            let json = serde_json::to_string(&cause).unwrap();
+            assert_eq!(json, format!("\"{}\"", cause.as_str()));
+            assert!(cause.exit_code() >= 10);
+        }
+    }
+
+    #[test]
+    fn test_retry_hint_display() {
+        let hint = RetryHint::AllowTool {
+            tool: "Bash".to_string(),
+        };
+        assert_eq!(hint.to_string(), "safe to retry with --allowed-tools Bash");
+        assert_eq!(RetryHint::DoNotRetry.to_string(), "do not retry");
+    }
+
+    #[test]
+    fn test_retry_hint_serialize() {
+        let hint = RetryHint::AllowTool {
+            tool: "Write".to_string(),
+        };
+        let json = serde_json::to_value(&hint).unwrap();
+        assert_eq!(json["action"], "allow_tool");
+        assert_eq!(json["tool"], "Write");
+
+        let parsed: RetryHint = serde_json::from_value(json).unwrap();
+        assert_eq!(parsed, hint);
+    }
+}
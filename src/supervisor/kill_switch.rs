@@ -0,0 +1,99 @@
+//! Machine-wide kill switch.
+//!
+//! While the kill switch file (by default `<data_dir>/KILL`, see
+//! [`kill_switch_path`](crate::config::kill_switch_path)) exists, every
+//! supervisor on the machine stops approving tool calls: runners deny with
+//! [`KILL_SWITCH_REASON`], terminate Claude and end with
+//! [`SupervisorResult::Halted`](super::SupervisorResult::Halted), and the
+//! hook denies every call. Remove the file to let new sessions run again.
+
+use std::path::{Path, PathBuf};
+use std::time::Duration;
+
+/// Process exit code of a session halted by the kill switch, next to
+/// [`CONTEXT_EXHAUSTED_EXIT_CODE`](super::CONTEXT_EXHAUSTED_EXIT_CODE).
+pub const HALTED_EXIT_CODE: i32 = 20;
+
+/// Reason given for every call denied while the kill switch is engaged.
+pub const KILL_SWITCH_REASON: &str = "global kill switch engaged";
+
+/// How often a running session looks for the kill switch file.
+pub const DEFAULT_KILL_SWITCH_POLL: Duration = Duration::from_secs(1);
+
+/// A kill switch file and how often to look for it.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct KillSwitch {
+    path: PathBuf,
+    poll_interval: Duration,
+}
+
+impl KillSwitch {
+    /// Watch for the file at `path`.
+    #[must_use]
+    pub fn new(path: impl Into<PathBuf>) -> Self {
+        Self {
+            path: path.into(),
+            poll_interval: DEFAULT_KILL_SWITCH_POLL,
+        }
+    }
+
+    /// Look for the file every `interval` instead of every second.
+    #[must_use]
+    pub fn with_poll_interval(mut self, interval: Duration) -> Self {
+        self.poll_interval = interval;
+        self
+    }
+
+    /// The kill switch file.
+    #[must_use]
+    pub fn path(&self) -> &Path {
+        &self.path
+    }
+
+    /// How often a running session looks for the file.
+    #[must_use]
+    pub fn poll_interval(&self) -> Duration {
+        self.poll_interval
+    }
+
+    /// Whether the file exists.
+    #[must_use]
+    pub fn is_engaged(&self) -> bool {
+        self.path.exists()
+    }
+
+    /// Wait until the file exists.
+    pub async fn engaged(&self) {
+        let mut interval = tokio::time::interval(self.poll_interval);
+        interval.set_missed_tick_behavior(tokio::time::MissedTickBehavior::Delay);
+        loop {
+            interval.tick().await;
+            if self.is_engaged() {
+                return;
+            }
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[tokio::test]
+    async fn test_engaged_waits_for_file() {
+        let dir = tempfile::tempdir().unwrap();
+        let switch =
+            KillSwitch::new(dir.path().join("KILL")).with_poll_interval(Duration::from_millis(10));
+        assert!(!switch.is_engaged());
+
+        let path = switch.path().to_path_buf();
+        tokio::spawn(async move {
+            tokio::time::sleep(Duration::from_millis(50)).await;
+            std::fs::write(path, b"").unwrap();
+        });
+        tokio::time::timeout(Duration::from_secs(5), switch.engaged())
+            .await
+            .unwrap();
+        assert!(switch.is_engaged());
+    }
+}
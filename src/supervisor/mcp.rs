@@ -0,0 +1,105 @@
+//! Tools provided by MCP servers.
+//!
+//! Claude Code names MCP tools `mcp__<server>__<tool>`. The server part lets
+//! rules be scoped to one server, and the status the session declared for the
+//! server in its init event tells the AI supervisor whether the tool comes
+//! from a server the session knows about.
+
+use crate::cli::McpServer;
+
+use super::MCP_TOOL_PREFIX;
+
+/// Separator between the server and tool parts of an MCP tool name.
+const MCP_NAME_SEPARATOR: &str = "__";
+
+/// An MCP tool name split into its server and tool parts.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct McpTool<'a> {
+    /// Name of the server providing the tool.
+    pub server: &'a str,
+    /// Name of the tool on the server.
+    pub tool: &'a str,
+}
+
+impl<'a> McpTool<'a> {
+    /// Split `mcp__<server>__<tool>` into its parts.
+    ///
+    /// The server part ends at the first `__`, so a tool name may itself
+    /// contain double underscores. Returns `None` for names without the
+    /// prefix or with an empty server or tool part.
+    #[must_use]
+    pub fn parse(tool_name: &'a str) -> Option<Self> {
+        let rest = tool_name.strip_prefix(MCP_TOOL_PREFIX)?;
+        let (server, tool) = rest.split_once(MCP_NAME_SEPARATOR)?;
+        (!server.is_empty() && !tool.is_empty()).then_some(Self { server, tool })
+    }
+}
+
+/// Describe the server behind an MCP tool for an escalation.
+///
+/// Uses the status the session declared for the server, or notes that the
+/// session did not declare it. Returns `None` for tools that are not MCP tools.
+#[must_use]
+pub fn mcp_server_context(tool_name: &str, servers: &[McpServer]) -> Option<String> {
+    let mcp = McpTool::parse(tool_name)?;
+    let context = match servers.iter().find(|server| server.name == mcp.server) {
+        Some(server) => format!("MCP server `{}` status: {}", mcp.server, server.status),
+        None => format!(
+            "MCP server `{}` was not declared by the session",
+            mcp.server
+        ),
+    };
+    Some(context)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_parse_mcp_tool_names() {
+        assert_eq!(
+            McpTool::parse("mcp__github__create_issue"),
+            Some(McpTool {
+                server: "github",
+                tool: "create_issue"
+            })
+        );
+        assert_eq!(
+            McpTool::parse("mcp__my_server__run__query"),
+            Some(McpTool {
+                server: "my_server",
+                tool: "run__query"
+            })
+        );
+        assert_eq!(
+            McpTool::parse("mcp__db___private"),
+            Some(McpTool {
+                server: "db",
+                tool: "_private"
+            })
+        );
+        assert_eq!(McpTool::parse("mcp__github"), None);
+        assert_eq!(McpTool::parse("mcp____create_issue"), None);
+        assert_eq!(McpTool::parse("mcp__github__"), None);
+        assert_eq!(McpTool::parse("Bash"), None);
+        assert_eq!(McpTool::parse("MCP__github__get_issue"), None);
+    }
+
+    #[test]
+    fn test_server_context_uses_declared_status() {
+        let servers = [McpServer {
+            name: "github".to_string(),
+            status: "connected".to_string(),
+        }];
+        assert_eq!(
+            mcp_server_context("mcp__github__get_issue", &servers).as_deref(),
+            Some("MCP server `github` status: connected")
+        );
+        assert_eq!(
+            mcp_server_context("mcp__jira__get_issue", &servers).as_deref(),
+            Some("MCP server `jira` was not declared by the session")
+        );
+        assert_eq!(mcp_server_context("Read", &servers), None);
+    }
+}
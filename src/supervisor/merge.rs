@@ -0,0 +1,409 @@
+//! Merging Claude's stdout stream with the session transcript on disk.
+//!
+//! Some events only reach the session JSONL Claude Code writes, and others
+//! only stdout. Once the session id is known the supervisor tails the
+//! transcript, rebuilds its tool calls and results as [`ClaudeEvent`]s and
+//! merges them with stdout, so policy evaluation sees the union.
+//!
+//! Events are de-duplicated by tool use id. A transcript event is held for a
+//! short reorder window before it is released, so when stdout carries the
+//! same event its copy wins and the session keeps stdout's order; an event
+//! seen from either source is never processed twice.
+
+use std::collections::{HashSet, VecDeque};
+use std::path::PathBuf;
+use std::time::Duration;
+
+use serde_json::{json, Value};
+use tokio::time::Instant;
+
+use crate::cli::{ClaudeEvent, ToolUse};
+use crate::watcher::{ContentBlock, JournalEntry, JsonlTailer, MessageContent, WatcherError};
+
+use super::tool_result_ids;
+
+/// How long a transcript event waits for stdout to deliver it first.
+pub const DEFAULT_REORDER_WINDOW: Duration = Duration::from_millis(250);
+
+/// How often the transcript is checked for new entries.
+pub const TRANSCRIPT_POLL_INTERVAL: Duration = Duration::from_millis(200);
+
+/// Most event keys remembered; the oldest are forgotten first.
+const MAX_SEEN_KEYS: usize = 4096;
+
+/// Keys of an event that may arrive from both sources.
+///
+/// Tool calls are keyed by their id, tool results by the ids of the calls
+/// they answer; a message answering several calls has a key for each. Other
+/// events only come from stdout, have no keys and are never merged.
+#[must_use]
+pub fn event_keys(event: &ClaudeEvent) -> Vec<String> {
+    match event {
+        ClaudeEvent::ToolUse(tool_use) => vec![format!("tool_use:{}", tool_use.id)],
+        ClaudeEvent::ToolResult(result) => vec![format!("tool_result:{}", result.tool_use_id)],
+        ClaudeEvent::User { message, .. } => tool_result_ids(message)
+            .into_iter()
+            .map(|id| format!("tool_result:{id}"))
+            .collect(),
+        _ => Vec::new(),
+    }
+}
+
+/// Rebuild the tool calls and results of a transcript entry as events.
+///
+/// Tool results keep the shape of stdout's `user` events, so they are
+/// verified against the approved input the same way.
+#[must_use]
+pub fn journal_events(entry: &JournalEntry) -> Vec<ClaudeEvent> {
+    match entry {
+        JournalEntry::Assistant(assistant) => assistant
+            .message
+            .content
+            .iter()
+            .filter_map(|block| match block {
+                ContentBlock::ToolUse { id, name, input } => Some(ClaudeEvent::ToolUse(ToolUse {
+                    id: id.clone(),
+                    name: name.clone(),
+                    input: input.clone(),
+                })),
+                _ => None,
+            })
+            .collect(),
+        JournalEntry::User(user) => {
+            let mut results: Vec<Value> = match user.message.content {
+                MessageContent::Blocks(ref blocks) => blocks
+                    .iter()
+                    .filter_map(|block| match block {
+                        ContentBlock::ToolResult {
+                            tool_use_id,
+                            content,
+                        } => Some(json!({
+                            "type": "tool_result",
+                            "tool_use_id": tool_use_id,
+                            "content": content,
+                        })),
+                        _ => None,
+                    })
+                    .collect(),
+                MessageContent::Text(_) => Vec::new(),
+            };
+            // Older transcripts only name the call on the entry
+            if results.is_empty() {
+                if let Some(ref tool_use_id) = user.source_tool_use_id {
+                    results.push(json!({
+                        "type": "tool_result",
+                        "tool_use_id": tool_use_id,
+                        "content": user.tool_use_result,
+                    }));
+                }
+            }
+            if results.is_empty() {
+                return Vec::new();
+            }
+            vec![ClaudeEvent::User {
+                message: json!({"role": "user", "content": results}),
+                tool_use_result: user.tool_use_result.clone(),
+            }]
+        }
+        _ => Vec::new(),
+    }
+}
+
+/// De-duplicates events arriving from stdout and the transcript.
+#[derive(Debug)]
+pub struct EventMerger {
+    window: Duration,
+    seen: HashSet<String>,
+    seen_order: VecDeque<String>,
+    /// Transcript events waiting out the reorder window, oldest first.
+    pending: VecDeque<(Instant, Vec<String>, ClaudeEvent)>,
+    duplicates: usize,
+}
+
+impl Default for EventMerger {
+    fn default() -> Self {
+        Self::new(DEFAULT_REORDER_WINDOW)
+    }
+}
+
+impl EventMerger {
+    /// Create a merger holding transcript events for `window`.
+    #[must_use]
+    pub fn new(window: Duration) -> Self {
+        Self {
+            window,
+            seen: HashSet::new(),
+            seen_order: VecDeque::new(),
+            pending: VecDeque::new(),
+            duplicates: 0,
+        }
+    }
+
+    /// Accept an event from stdout, returning it unless it was already
+    /// processed.
+    ///
+    /// Transcript copies still waiting in the reorder window are dropped.
+    pub fn from_stdout(&mut self, event: ClaudeEvent) -> Option<ClaudeEvent> {
+        let keys = event_keys(&event);
+        if keys.is_empty() {
+            return Some(event);
+        }
+        if self.all_seen(&keys) {
+            self.duplicates += 1;
+            return None;
+        }
+        self.mark_seen(&keys);
+        let before = self.pending.len();
+        let seen = &self.seen;
+        self.pending
+            .retain(|(_, pending, _)| !pending.iter().all(|key| seen.contains(key)));
+        self.duplicates += before - self.pending.len();
+        Some(event)
+    }
+
+    /// Queue an event from the transcript, received at `now`.
+    ///
+    /// Events without keys, already processed or already queued are dropped.
+    pub fn from_transcript(&mut self, event: ClaudeEvent, now: Instant) {
+        let keys = event_keys(&event);
+        if keys.is_empty() {
+            return;
+        }
+        if self.all_seen(&keys) || self.pending.iter().any(|(_, pending, _)| *pending == keys) {
+            self.duplicates += 1;
+            return;
+        }
+        self.pending.push_back((now + self.window, keys, event));
+    }
+
+    /// Release the next transcript event whose reorder window ended by `now`.
+    pub fn pop_ready(&mut self, now: Instant) -> Option<ClaudeEvent> {
+        if self.pending.front()?.0 > now {
+            return None;
+        }
+        self.pop_any()
+    }
+
+    /// Release the next queued transcript event without waiting.
+    pub fn pop_any(&mut self) -> Option<ClaudeEvent> {
+        let (_, keys, event) = self.pending.pop_front()?;
+        self.mark_seen(&keys);
+        Some(event)
+    }
+
+    /// When the oldest queued transcript event is released.
+    #[must_use]
+    pub fn next_deadline(&self) -> Option<Instant> {
+        self.pending.front().map(|(deadline, _, _)| *deadline)
+    }
+
+    /// Events dropped because the other source already delivered them.
+    #[must_use]
+    pub fn duplicates(&self) -> usize {
+        self.duplicates
+    }
+
+    /// Whether every key was already processed.
+    fn all_seen(&self, keys: &[String]) -> bool {
+        keys.iter().all(|key| self.seen.contains(key))
+    }
+
+    /// Remember `keys` as processed.
+    fn mark_seen(&mut self, keys: &[String]) {
+        for key in keys {
+            if self.seen.insert(key.clone()) {
+                self.seen_order.push_back(key.clone());
+            }
+        }
+        while self.seen_order.len() > MAX_SEEN_KEYS {
+            if let Some(oldest) = self.seen_order.pop_front() {
+                self.seen.remove(&oldest);
+            }
+        }
+    }
+}
+
+/// The session transcript tailed alongside stdout.
+#[derive(Debug, Default)]
+pub struct TranscriptMerge {
+    tailer: Option<JsonlTailer>,
+    merger: EventMerger,
+}
+
+impl TranscriptMerge {
+    /// Create a merge holding transcript events for `window`.
+    #[must_use]
+    pub fn new(window: Duration) -> Self {
+        Self {
+            tailer: None,
+            merger: EventMerger::new(window),
+        }
+    }
+
+    /// Start tailing the transcript at `path` from its beginning.
+    ///
+    /// Replaces the transcript of an earlier session; events already
+    /// processed stay de-duplicated.
+    pub fn attach(&mut self, path: PathBuf) {
+        tracing::debug!(path = %path.display(), "Merging session transcript");
+        self.tailer = Some(JsonlTailer::new(path));
+    }
+
+    /// The transcript being tailed, if any.
+    #[must_use]
+    pub fn path(&self) -> Option<&PathBuf> {
+        self.tailer.as_ref().map(JsonlTailer::path)
+    }
+
+    /// Whether a transcript is being tailed.
+    #[must_use]
+    pub fn is_attached(&self) -> bool {
+        self.tailer.is_some()
+    }
+
+    /// Read new transcript entries and queue their events.
+    ///
+    /// A transcript Claude has not created yet is not an error.
+    pub async fn poll(&mut self) {
+        let Some(ref mut tailer) = self.tailer else {
+            return;
+        };
+        let entries = match tailer.read_new_entries().await {
+            Ok(entries) => entries,
+            Err(WatcherError::FileDeleted(_)) => return,
+            Err(e) => {
+                tracing::warn!(path = %tailer.path().display(), error = %e, "Failed to read session transcript");
+                return;
+            }
+        };
+        let now = Instant::now();
+        for event in entries.iter().flat_map(journal_events) {
+            self.merger.from_transcript(event, now);
+        }
+    }
+
+    /// The merger de-duplicating the two sources.
+    #[must_use]
+    pub fn merger(&self) -> &EventMerger {
+        &self.merger
+    }
+
+    /// Mutable access to the merger.
+    pub fn merger_mut(&mut self) -> &mut EventMerger {
+        &mut self.merger
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::watcher::parse_jsonl_content;
+
+    fn tool_use(id: &str) -> ClaudeEvent {
+        ClaudeEvent::ToolUse(ToolUse {
+            id: id.to_string(),
+            name: "Bash".to_string(),
+            input: json!({"command": "ls"}),
+        })
+    }
+
+    fn ids(events: &[ClaudeEvent]) -> Vec<String> {
+        events.iter().flat_map(event_keys).collect()
+    }
+
+    #[test]
+    fn test_journal_events_rebuild_calls_and_results() {
+        let entries = parse_jsonl_content(concat!(
+            r#"{"type":"assistant","uuid":"a1","parentUuid":null,"sessionId":"s","timestamp":"t","message":{"role":"assistant","content":[{"type":"text","text":"Listing"},{"type":"tool_use","id":"t1","name":"Bash","input":{"command":"ls"}}]},"cwd":"/tmp","version":"2.1.25"}"#,
+            "\n",
+            r#"{"type":"user","uuid":"u1","parentUuid":"a1","sessionId":"s","timestamp":"t","message":{"role":"user","content":[{"type":"tool_result","tool_use_id":"t1","content":"Cargo.toml"}]},"userType":"external","cwd":"/tmp","version":"2.1.25","toolUseResult":{"stdout":"Cargo.toml"}}"#,
+            "\n",
+            r#"{"type":"user","uuid":"u2","parentUuid":"a1","sessionId":"s","timestamp":"t","message":{"role":"user","content":"Tool result"},"userType":"tool_result","cwd":"/tmp","version":"2.1.25","sourceToolUseId":"t2","toolUseResult":{"is_error":true}}"#,
+        ));
+        let events: Vec<_> = entries.iter().flat_map(journal_events).collect();
+        assert_eq!(
+            ids(&events),
+            ["tool_use:t1", "tool_result:t1", "tool_result:t2"]
+        );
+        let ClaudeEvent::User {
+            tool_use_result, ..
+        } = &events[1]
+        else {
+            panic!("expected a user event, got {:?}", events[1]);
+        };
+        assert_eq!(tool_use_result, &Some(json!({"stdout": "Cargo.toml"})));
+    }
+
+    #[test]
+    fn test_stdout_copy_wins_within_window() {
+        let now = Instant::now();
+        let mut merger = EventMerger::new(Duration::from_millis(100));
+        merger.from_transcript(tool_use("t1"), now);
+        assert!(merger.pop_ready(now).is_none());
+
+        assert!(merger.from_stdout(tool_use("t1")).is_some());
+        assert!(merger.pop_ready(now + Duration::from_secs(1)).is_none());
+        assert_eq!(merger.duplicates(), 1);
+    }
+
+    #[test]
+    fn test_each_event_is_processed_once() {
+        let now = Instant::now();
+        let window = Duration::from_millis(100);
+        let mut merger = EventMerger::new(window);
+        let mut processed = Vec::new();
+
+        // Overlapping: stdout first, then the transcript copy
+        processed.extend(merger.from_stdout(tool_use("t1")));
+        merger.from_transcript(tool_use("t1"), now);
+        // Disjoint: transcript only, queued twice, and stdout only
+        merger.from_transcript(tool_use("t2"), now);
+        merger.from_transcript(tool_use("t2"), now);
+        processed.extend(merger.from_stdout(tool_use("t3")));
+        // Events without a key always pass
+        processed.extend(merger.from_stdout(ClaudeEvent::MessageStop));
+
+        assert_eq!(merger.next_deadline(), Some(now + window));
+        processed.extend(std::iter::from_fn(|| merger.pop_ready(now + window)));
+        // Released transcript events are not processed again from stdout
+        processed.extend(merger.from_stdout(tool_use("t2")));
+
+        assert_eq!(
+            ids(&processed),
+            ["tool_use:t1", "tool_use:t3", "tool_use:t2"]
+        );
+        assert_eq!(processed.len(), 4);
+        assert_eq!(merger.duplicates(), 3);
+    }
+
+    #[test]
+    fn test_batched_results_cover_single_results() {
+        let result = |ids: &[&str]| ClaudeEvent::User {
+            message: json!({
+                "content": ids
+                    .iter()
+                    .map(|id| json!({"type": "tool_result", "tool_use_id": id, "content": "ok"}))
+                    .collect::<Vec<_>>(),
+            }),
+            tool_use_result: None,
+        };
+        let now = Instant::now();
+        let mut merger = EventMerger::new(Duration::from_millis(100));
+        merger.from_transcript(result(&["t1"]), now);
+        merger.from_transcript(result(&["t2"]), now);
+
+        assert!(merger.from_stdout(result(&["t1", "t2"])).is_some());
+        assert!(merger.pop_any().is_none());
+        assert_eq!(merger.duplicates(), 2);
+    }
+
+    #[test]
+    fn test_pop_any_ignores_window() {
+        let now = Instant::now();
+        let mut merger = EventMerger::new(Duration::from_mins(1));
+        merger.from_transcript(tool_use("t1"), now);
+        assert!(merger.pop_ready(now).is_none());
+        assert!(merger.pop_any().is_some());
+        assert!(merger.from_stdout(tool_use("t1")).is_none());
+    }
+}
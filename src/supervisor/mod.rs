@@ -1,13 +1,110 @@
 //! Supervisor module for policy enforcement and state management.
 
+mod adopt;
+mod appeal;
+mod bash_allowlist;
+mod best_effort;
+mod blast_radius;
 mod blocklist;
+mod budget;
+mod containment;
+mod context_limit;
+mod deny_reason;
+mod detach;
+mod dry_run;
+mod edit_rules;
+mod escrow;
+mod exfiltration;
+mod git_gate;
+mod health;
+mod history;
+mod idle_nudge;
+mod kill;
+mod kill_switch;
+mod mcp;
+mod merge;
 mod multi;
+mod naming;
+mod novel_binary;
+mod overrides;
+mod path_rules;
 mod policy;
+mod policy_cases;
+mod project;
+mod protect;
+mod rate_limit;
+mod resume;
+mod rewrite;
+mod risk;
+mod rule_firings;
 mod runner;
+mod sampling;
+mod sandbox;
+mod sanitize;
+mod secrets;
+mod shell;
+mod startup;
 mod state;
+mod time_box;
+mod todos;
+mod tool_aliases;
+mod tool_errors;
+mod tool_patterns;
+mod tool_timeout;
+mod trace;
+mod validator;
+mod verify;
 
+pub use adopt::*;
+pub use appeal::*;
+pub use bash_allowlist::*;
+pub use best_effort::*;
+pub use blast_radius::*;
 pub use blocklist::*;
+pub use budget::*;
+pub use containment::*;
+pub use context_limit::*;
+pub use deny_reason::*;
+pub use detach::*;
+pub use dry_run::*;
+pub use edit_rules::*;
+pub use escrow::*;
+pub use exfiltration::*;
+pub use git_gate::*;
+pub use health::*;
+pub use history::*;
+pub use idle_nudge::*;
+pub use kill::*;
+pub use kill_switch::*;
+pub use mcp::*;
+pub use merge::*;
 pub use multi::*;
+pub use naming::*;
+pub use novel_binary::*;
+pub use overrides::*;
+pub use path_rules::*;
 pub use policy::*;
+pub use policy_cases::*;
+pub use project::*;
+pub use protect::*;
+pub use rate_limit::*;
+pub use resume::*;
+pub use rewrite::*;
+pub use risk::*;
+pub use rule_firings::*;
 pub use runner::*;
+pub use sampling::*;
+pub use sandbox::*;
+pub use sanitize::*;
+pub use secrets::*;
+pub use startup::*;
 pub use state::*;
+pub use time_box::*;
+pub use todos::*;
+pub use tool_aliases::*;
+pub use tool_errors::*;
+pub use tool_patterns::*;
+pub use tool_timeout::*;
+pub use trace::*;
+pub use validator::*;
+pub use verify::*;
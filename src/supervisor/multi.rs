@@ -1,6 +1,6 @@
 //! Multi-session supervisor for parallel Claude Code execution.
 
-use std::collections::HashMap;
+use std::collections::{BTreeMap, HashMap};
 use std::sync::Arc;
 use std::time::{Duration, Instant};
 
@@ -9,7 +9,11 @@ use tokio::task::JoinSet;
 use tokio_util::sync::CancellationToken;
 use uuid::Uuid;
 
-use crate::supervisor::{PolicyEngine, SessionStats, SupervisorError, SupervisorResult};
+use crate::ai::{AiClient, AiStats, SessionQuota, DEFAULT_SESSION_WEIGHT};
+use crate::supervisor::{
+    DecisionBreakdown, KillCause, KillSwitch, PolicyEngine, SessionStats, SupervisorError,
+    SupervisorResult,
+};
 
 /// Error type for multi-session operations.
 #[derive(thiserror::Error, Debug)]
@@ -42,6 +46,8 @@ pub struct SessionMeta {
     pub started_at: Instant,
     /// Cancellation token for stopping the session.
     cancel: CancellationToken,
+    /// AI client whose requests count against this session's share.
+    ai_client: Option<AiClient>,
 }
 
 impl SessionMeta {
@@ -53,9 +59,16 @@ impl SessionMeta {
             task,
             started_at: Instant::now(),
             cancel: CancellationToken::new(),
+            ai_client: None,
         }
     }
 
+    /// AI client bound to this session, if the supervisor has one.
+    #[must_use]
+    pub fn ai_client(&self) -> Option<&AiClient> {
+        self.ai_client.as_ref()
+    }
+
     /// Get a clone of the cancellation token.
     #[must_use]
     pub fn cancellation_token(&self) -> CancellationToken {
@@ -85,6 +98,8 @@ pub struct SessionResult {
     pub result: Result<SupervisorResult, SupervisorError>,
     /// Session statistics.
     pub stats: SessionStats,
+    /// Claude Code version the session ran under, if known.
+    pub claude_code_version: Option<String>,
 }
 
 /// Aggregated statistics across all sessions.
@@ -100,6 +115,12 @@ pub struct AggregatedStats {
     pub total_approvals: usize,
     /// Total denials across all sessions.
     pub total_denials: usize,
+    /// Approvals and denials across all sessions by who made them.
+    pub by_source: DecisionBreakdown,
+    /// Killed sessions grouped by kill cause.
+    pub kills_by_cause: HashMap<KillCause, usize>,
+    /// Events no session acted on, by event type.
+    pub unhandled_events: BTreeMap<String, usize>,
 }
 
 impl AggregatedStats {
@@ -113,6 +134,15 @@ impl AggregatedStats {
         self.total_tool_calls += stats.tool_calls;
         self.total_approvals += stats.approvals;
         self.total_denials += stats.denials;
+        self.by_source.merge(&stats.by_source);
+        for (kind, count) in &stats.unhandled_events {
+            *self.unhandled_events.entry(kind.clone()).or_insert(0) += count;
+        }
+    }
+
+    /// Record a session killed with the given cause.
+    pub fn record_kill(&mut self, cause: KillCause) {
+        *self.kills_by_cause.entry(cause).or_insert(0) += 1;
     }
 }
 
@@ -127,10 +157,14 @@ pub struct MultiSessionSupervisor {
     semaphore: Arc<Semaphore>,
     /// Shared policy engine.
     policy: Arc<PolicyEngine>,
+    /// AI client shared by every session, so they share one connection pool.
+    ai_client: Option<Arc<AiClient>>,
     /// Maximum concurrent sessions.
     max_sessions: usize,
     /// Aggregated statistics.
     stats: AggregatedStats,
+    /// Kill switch halting every session once engaged.
+    kill_switch: Option<KillSwitch>,
 }
 
 impl MultiSessionSupervisor {
@@ -142,8 +176,10 @@ impl MultiSessionSupervisor {
             join_set: JoinSet::new(),
             semaphore: Arc::new(Semaphore::new(max_sessions)),
             policy: Arc::new(policy),
+            ai_client: None,
             max_sessions,
             stats: AggregatedStats::default(),
+            kill_switch: None,
         }
     }
 
@@ -177,6 +213,47 @@ impl MultiSessionSupervisor {
         Arc::clone(&self.policy)
     }
 
+    /// Share an AI client across all sessions (builder pattern).
+    ///
+    /// Each session gets a clone bound to it, so a client built
+    /// [`with_fair_share`](AiClient::with_fair_share) shares its limit
+    /// fairly between the sessions.
+    #[must_use]
+    pub fn with_ai_client(mut self, client: impl Into<Arc<AiClient>>) -> Self {
+        self.ai_client = Some(client.into());
+        self
+    }
+
+    /// Halt every session once `kill_switch` is engaged (builder pattern).
+    #[must_use]
+    pub fn with_kill_switch(mut self, kill_switch: KillSwitch) -> Self {
+        self.kill_switch = Some(kill_switch);
+        self
+    }
+
+    /// Get the AI client shared by the sessions, if any.
+    #[must_use]
+    pub fn ai_client(&self) -> Option<Arc<AiClient>> {
+        self.ai_client.clone()
+    }
+
+    /// Get AI latency and error statistics across all sessions.
+    #[must_use]
+    pub fn ai_stats(&self) -> Option<AiStats> {
+        self.ai_client.as_ref().map(|client| client.stats())
+    }
+
+    /// Get the AI requests in flight and queued by session, if the AI client
+    /// shares its limit fairly; empty otherwise.
+    #[must_use]
+    pub fn ai_quotas(&self) -> Vec<SessionQuota> {
+        self.ai_client
+            .as_ref()
+            .and_then(|client| client.fair_share())
+            .map(crate::ai::FairShareLimiter::quotas)
+            .unwrap_or_default()
+    }
+
     /// Get the aggregated statistics.
     #[must_use]
     pub fn stats(&self) -> &AggregatedStats {
@@ -197,6 +274,23 @@ impl MultiSessionSupervisor {
     ///
     /// Returns error if session spawning fails.
     pub async fn spawn_session(&mut self, task: String) -> Result<String, MultiSessionError> {
+        self.spawn_weighted_session(task, DEFAULT_SESSION_WEIGHT)
+            .await
+    }
+
+    /// Spawn a new session whose share of the AI request limit is `weight`
+    /// times that of a session of weight one.
+    ///
+    /// This method waits for a semaphore permit if at capacity.
+    ///
+    /// # Errors
+    ///
+    /// Returns error if session spawning fails.
+    pub async fn spawn_weighted_session(
+        &mut self,
+        task: String,
+        weight: u32,
+    ) -> Result<String, MultiSessionError> {
         // Acquire semaphore permit (waits if at capacity)
         let permit = self.semaphore.clone().acquire_owned().await.map_err(|_| {
             MultiSessionError::MaxSessionsReached {
@@ -204,7 +298,7 @@ impl MultiSessionSupervisor {
             }
         })?;
 
-        Ok(self.spawn_session_internal(&task, permit))
+        Ok(self.spawn_session_internal(&task, weight, permit))
     }
 
     /// Try to spawn a new session without waiting.
@@ -224,17 +318,22 @@ impl MultiSessionSupervisor {
             }
         })?;
 
-        Ok(self.spawn_session_internal(task, permit))
+        Ok(self.spawn_session_internal(task, DEFAULT_SESSION_WEIGHT, permit))
     }
 
     /// Internal session spawning logic.
     fn spawn_session_internal(
         &mut self,
         task: &str,
+        weight: u32,
         permit: tokio::sync::OwnedSemaphorePermit,
     ) -> String {
         let id = Uuid::new_v4().to_string();
-        let meta = SessionMeta::new(id.clone(), task.to_string());
+        let mut meta = SessionMeta::new(id.clone(), task.to_string());
+        meta.ai_client = self
+            .ai_client
+            .as_ref()
+            .map(|client| client.for_session(&id, weight));
         let cancel = meta.cancellation_token();
 
         // Store metadata
@@ -243,6 +342,7 @@ impl MultiSessionSupervisor {
         // Spawn the session task
         let session_id = id.clone();
         let session_task = task.to_string();
+        let kill_switch = self.kill_switch.clone();
 
         self.join_set.spawn(async move {
             // Hold permit for duration of session
@@ -252,6 +352,13 @@ impl MultiSessionSupervisor {
                 tool_calls: 0,
                 approvals: 0,
                 denials: 0,
+                turns: 0,
+                escalations: 0,
+                tool_errors: 0,
+                pattern_tool_errors: 0,
+                by_source: DecisionBreakdown::default(),
+                unhandled_events: BTreeMap::new(),
+                risk_score: 0,
             };
 
             // Wait for cancellation or simulate completion
@@ -262,6 +369,21 @@ impl MultiSessionSupervisor {
                         task: session_task,
                         result: Ok(SupervisorResult::Cancelled),
                         stats,
+                        claude_code_version: None,
+                    }
+                }
+                () = async {
+                    match kill_switch {
+                        Some(ref kill_switch) => kill_switch.engaged().await,
+                        None => std::future::pending().await,
+                    }
+                } => {
+                    SessionResult {
+                        id: session_id,
+                        task: session_task,
+                        result: Ok(SupervisorResult::Halted { session_id: None }),
+                        stats,
+                        claude_code_version: None,
                     }
                 }
                 () = tokio::time::sleep(Duration::from_millis(100)) => {
@@ -270,6 +392,7 @@ impl MultiSessionSupervisor {
                         task: session_task,
                         result: Ok(SupervisorResult::ProcessExited),
                         stats,
+                        claude_code_version: None,
                     }
                 }
             }
@@ -303,6 +426,14 @@ impl MultiSessionSupervisor {
         }
     }
 
+    /// Forget a completed session.
+    fn finish_session(&mut self, id: &str) {
+        self.sessions.remove(id);
+        if let Some(fair_share) = self.ai_client.as_ref().and_then(|c| c.fair_share()) {
+            fair_share.remove_session(id);
+        }
+    }
+
     /// Wait for all sessions to complete and collect results.
     ///
     /// This consumes all pending session results and updates aggregated stats.
@@ -313,11 +444,14 @@ impl MultiSessionSupervisor {
             match join_result {
                 Ok(session_result) => {
                     // Remove from active sessions
-                    self.sessions.remove(&session_result.id);
+                    self.finish_session(&session_result.id);
 
                     // Update aggregated stats
                     let success = session_result.result.is_ok();
                     self.stats.add(&session_result.stats, success);
+                    if let Ok(SupervisorResult::Killed { cause, .. }) = &session_result.result {
+                        self.stats.record_kill(*cause);
+                    }
 
                     tracing::info!(
                         session_id = %session_result.id,
@@ -346,9 +480,12 @@ impl MultiSessionSupervisor {
 
         match join_result {
             Ok(session_result) => {
-                self.sessions.remove(&session_result.id);
+                self.finish_session(&session_result.id);
                 let success = session_result.result.is_ok();
                 self.stats.add(&session_result.stats, success);
+                if let Ok(SupervisorResult::Killed { cause, .. }) = &session_result.result {
+                    self.stats.record_kill(*cause);
+                }
 
                 tracing::info!(
                     session_id = %session_result.id,
@@ -0,0 +1,124 @@
+//! Human-friendly session names.
+//!
+//! A session can be given a name with `run --name`; otherwise it gets an
+//! adjective-noun slug such as `brisk-otter`. Names are unique per day: a
+//! repeat gets a numeric suffix (`brisk-otter-2`).
+
+use uuid::Uuid;
+
+const ADJECTIVES: &[&str] = &[
+    "amber", "bold", "brisk", "calm", "clever", "cosmic", "crisp", "daring", "eager", "fancy",
+    "gentle", "golden", "happy", "humble", "jolly", "keen", "lively", "lucky", "merry", "mighty",
+    "nimble", "noble", "plucky", "proud", "quick", "quiet", "rapid", "shiny", "silent", "snappy",
+    "steady", "sunny", "swift", "tidy", "vivid", "witty",
+];
+
+const NOUNS: &[&str] = &[
+    "badger", "beacon", "cedar", "comet", "condor", "ember", "falcon", "fern", "glacier", "harbor",
+    "heron", "lantern", "lynx", "maple", "meadow", "nebula", "orchid", "otter", "panda", "pebble",
+    "pine", "quasar", "raven", "river", "sparrow", "spruce", "summit", "thistle", "tiger",
+    "walrus", "willow", "wren",
+];
+
+/// Errors for user-supplied session names.
+#[derive(Debug, Clone, PartialEq, Eq, thiserror::Error)]
+pub enum SessionNameError {
+    /// The name is empty or longer than [`MAX_SESSION_NAME_LEN`].
+    #[error("Session name must be 1 to {MAX_SESSION_NAME_LEN} characters")]
+    Length,
+    /// The name contains characters other than ASCII letters, digits, `-` and `_`.
+    #[error("Session name '{0}' may only contain letters, digits, '-' and '_'")]
+    Characters(String),
+}
+
+/// Longest accepted session name.
+pub const MAX_SESSION_NAME_LEN: usize = 64;
+
+/// Generate a random adjective-noun session name.
+#[must_use]
+pub fn generate_session_name() -> String {
+    let bytes = Uuid::new_v4().into_bytes();
+    let adjective = ADJECTIVES[usize::from(bytes[0]) % ADJECTIVES.len()];
+    let noun = NOUNS[usize::from(bytes[1]) % NOUNS.len()];
+    format!("{adjective}-{noun}")
+}
+
+/// Check that a user-supplied session name is usable.
+///
+/// Names end up in file names and shell commands, so only ASCII letters,
+/// digits, `-` and `_` are accepted.
+///
+/// # Errors
+///
+/// Returns `SessionNameError` describing why the name is rejected.
+pub fn validate_session_name(name: &str) -> Result<(), SessionNameError> {
+    if name.is_empty() || name.len() > MAX_SESSION_NAME_LEN {
+        return Err(SessionNameError::Length);
+    }
+    if !name
+        .chars()
+        .all(|c| c.is_ascii_alphanumeric() || c == '-' || c == '_')
+    {
+        return Err(SessionNameError::Characters(name.to_string()));
+    }
+    Ok(())
+}
+
+/// Make `base` unique among `taken` by appending `-2`, `-3`, ...
+#[must_use]
+pub fn unique_session_name(base: &str, taken: &[String]) -> String {
+    if !taken.iter().any(|name| name == base) {
+        return base.to_string();
+    }
+    // One of the first `taken.len() + 1` suffixes is always free
+    let mut suffix = 2;
+    loop {
+        let candidate = format!("{base}-{suffix}");
+        if !taken.contains(&candidate) {
+            return candidate;
+        }
+        suffix += 1;
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_generated_names_are_valid_slugs() {
+        for _ in 0..50 {
+            let name = generate_session_name();
+            assert!(validate_session_name(&name).is_ok(), "{name}");
+            let (adjective, noun) = name.split_once('-').unwrap();
+            assert!(ADJECTIVES.contains(&adjective));
+            assert!(NOUNS.contains(&noun));
+        }
+    }
+
+    #[test]
+    fn test_validate_session_name() {
+        assert!(validate_session_name("fix_login-2").is_ok());
+        assert_eq!(validate_session_name(""), Err(SessionNameError::Length));
+        assert_eq!(
+            validate_session_name(&"a".repeat(MAX_SESSION_NAME_LEN + 1)),
+            Err(SessionNameError::Length)
+        );
+        assert!(matches!(
+            validate_session_name("rm -rf"),
+            Err(SessionNameError::Characters(_))
+        ));
+    }
+
+    #[test]
+    fn test_unique_session_name_appends_suffix() {
+        let taken = vec![
+            "brisk-otter".to_string(),
+            "brisk-otter-2".to_string(),
+            "calm-wren".to_string(),
+        ];
+        assert_eq!(unique_session_name("brisk-otter", &taken), "brisk-otter-3");
+        assert_eq!(unique_session_name("calm-wren-2", &taken), "calm-wren-2");
+        assert_eq!(unique_session_name("swift-fern", &[]), "swift-fern");
+    }
+}
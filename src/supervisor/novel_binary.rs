@@ -0,0 +1,155 @@
+//! First use of a binary in a project.
+//!
+//! A session that suddenly runs `terraform` or `aws` in a project that has
+//! only ever seen `cargo` and `git` deserves a second look even when the
+//! policy would allow it. The baseline of known binaries is built from the
+//! project's session history, the sandbox's known-safe list and the
+//! configured allowlist; the first command of a session that runs anything
+//! else is flagged, and the binary is then known for the rest of the session.
+
+use std::collections::HashSet;
+
+use crate::config::{NovelBinaryConfig, NovelBinaryMode};
+
+use super::sandbox::{command_binaries, KNOWN_BINARIES};
+
+/// Tracks which binaries a project has run before.
+#[derive(Debug, Clone)]
+pub struct NovelBinaryTracker {
+    mode: NovelBinaryMode,
+    known: HashSet<String>,
+    /// Binaries first used in this session, in order.
+    novel: Vec<String>,
+}
+
+impl Default for NovelBinaryTracker {
+    fn default() -> Self {
+        Self::from_config(&NovelBinaryConfig::default())
+    }
+}
+
+impl NovelBinaryTracker {
+    /// Create a tracker whose baseline is the known-safe list and the
+    /// configured allowlist.
+    #[must_use]
+    pub fn from_config(config: &NovelBinaryConfig) -> Self {
+        let known = KNOWN_BINARIES
+            .iter()
+            .map(ToString::to_string)
+            .chain(config.allowed.iter().cloned())
+            .collect();
+        Self {
+            mode: config.mode,
+            known,
+            novel: Vec::new(),
+        }
+    }
+
+    /// What the tracker does with a new binary.
+    #[must_use]
+    pub fn mode(&self) -> NovelBinaryMode {
+        self.mode
+    }
+
+    /// Add the binaries run by past `commands` to the baseline.
+    pub fn learn<'a>(&mut self, commands: impl IntoIterator<Item = &'a str>) {
+        for command in commands {
+            self.known
+                .extend(command_binaries(command).map(ToString::to_string));
+        }
+    }
+
+    /// Whether `binary` is in the baseline or was already used this session.
+    #[must_use]
+    pub fn is_known(&self, binary: &str) -> bool {
+        self.known.contains(binary)
+    }
+
+    /// Return the first binary `command` runs that the project has not run
+    /// before, and remember every binary it runs.
+    ///
+    /// Always `None` when the mode is [`NovelBinaryMode::Off`].
+    pub fn check(&mut self, command: &str) -> Option<String> {
+        if self.mode == NovelBinaryMode::Off {
+            return None;
+        }
+        let mut first = None;
+        for binary in command_binaries(command) {
+            if self.known.insert(binary.to_string()) {
+                self.novel.push(binary.to_string());
+                first.get_or_insert_with(|| binary.to_string());
+            }
+        }
+        first
+    }
+
+    /// Binaries first used in this session, in order.
+    #[must_use]
+    pub fn novel(&self) -> &[String] {
+        &self.novel
+    }
+}
+
+/// Escalation reason for the first use of `binary`.
+#[must_use]
+pub fn novel_binary_reason(binary: &str) -> String {
+    format!("First use of `{binary}` in this project")
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn tracker(history: &[&str]) -> NovelBinaryTracker {
+        let mut tracker = NovelBinaryTracker::from_config(&NovelBinaryConfig {
+            mode: NovelBinaryMode::Escalate,
+            allowed: vec!["just".to_string()],
+        });
+        tracker.learn(history.iter().copied());
+        tracker
+    }
+
+    #[test]
+    fn test_history_and_allowlist_are_known() {
+        let mut tracker = tracker(&["docker compose up -d", "kubectl get pods | head"]);
+        assert!(tracker.is_known("docker"));
+        assert!(tracker.is_known("kubectl"));
+        assert!(tracker.is_known("cargo"));
+        assert!(tracker.is_known("just"));
+        assert_eq!(tracker.check("docker ps && just build"), None);
+        assert!(tracker.novel().is_empty());
+    }
+
+    #[test]
+    fn test_first_use_is_flagged_once() {
+        let mut tracker = tracker(&["docker build ."]);
+        assert_eq!(
+            tracker.check("cd infra && terraform apply | head"),
+            Some("terraform".to_string())
+        );
+        assert_eq!(tracker.check("terraform plan"), None);
+        assert_eq!(
+            tracker.check("FOO=1 sudo /usr/local/bin/aws s3 ls"),
+            Some("aws".to_string())
+        );
+        assert_eq!(tracker.novel(), ["terraform", "aws"]);
+    }
+
+    #[test]
+    fn test_off_mode_never_flags() {
+        let mut tracker = NovelBinaryTracker::from_config(&NovelBinaryConfig {
+            mode: NovelBinaryMode::Off,
+            allowed: Vec::new(),
+        });
+        assert_eq!(tracker.check("terraform apply"), None);
+        assert!(tracker.novel().is_empty());
+    }
+
+    #[test]
+    fn test_reason_names_the_binary() {
+        assert_eq!(
+            novel_binary_reason("terraform"),
+            "First use of `terraform` in this project"
+        );
+    }
+}
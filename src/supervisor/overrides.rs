@@ -0,0 +1,285 @@
+//! Allow and deny rules for a single session, given at launch.
+//!
+//! `run --allow-once 'Bash:docker *' --deny-once 'Write:**/prod/**'` adds
+//! one-off exceptions without touching config. A rule is `Tool` or
+//! `Tool:pattern`, where the pattern is a glob or, prefixed with `re:`, a
+//! regex. They are matched against the Bash command, the file paths a tool
+//! touches, or a fetched URL, after
+//! [`sanitize_tool_input`](super::sanitize_tool_input): paths are absolute,
+//! so a path glob usually starts with `**/`.
+//!
+//! Overrides take precedence over configured rules: deny overrides win over
+//! everything, allow overrides over everything except deny overrides and
+//! self-protection. They live only in the engine of the session they were
+//! given to and are never written to config.
+
+use std::fmt;
+
+use regex::Regex;
+use serde::{Deserialize, Serialize};
+
+use crate::cli::input_paths;
+
+/// Environment variable carrying a session's overrides to its hook processes.
+pub const SESSION_OVERRIDES_ENV: &str = "CLAUDE_SUPERVISOR_SESSION_OVERRIDES";
+
+/// Prefix marking an override pattern as a regex rather than a glob.
+pub const REGEX_PATTERN_PREFIX: &str = "re:";
+
+/// Whether an override allows or denies matching calls.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "lowercase")]
+pub enum OverrideEffect {
+    Allow,
+    Deny,
+}
+
+/// Error type for parsing session overrides.
+#[derive(thiserror::Error, Debug, Clone, PartialEq, Eq)]
+pub enum OverrideError {
+    /// The rule has no tool name before the colon.
+    #[error("Override '{0}' is missing a tool name (expected Tool or Tool:pattern)")]
+    MissingTool(String),
+    /// The pattern is not a valid glob or regex.
+    #[error("Invalid pattern in override '{rule}': {message}")]
+    InvalidPattern { rule: String, message: String },
+}
+
+/// How an override matches a tool's input.
+#[derive(Debug, Clone)]
+enum Matcher {
+    /// Every call of the tool.
+    Any,
+    /// A glob, compiled for commands (`*` crosses `/`) and for paths.
+    Glob { command: Regex, path: Regex },
+    /// A regex, matched anywhere in the subject.
+    Regex(Regex),
+}
+
+/// An allow or deny rule that applies to one session only.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(try_from = "OverrideSpec", into = "OverrideSpec")]
+pub struct SessionOverride {
+    effect: OverrideEffect,
+    rule: String,
+    tool: String,
+    matcher: Matcher,
+}
+
+impl PartialEq for SessionOverride {
+    /// Overrides are equal if they have the same effect and rule; the
+    /// matcher is compiled from the rule.
+    fn eq(&self, other: &Self) -> bool {
+        self.effect == other.effect && self.rule == other.rule
+    }
+}
+
+/// Serialized form of a [`SessionOverride`].
+#[derive(Serialize, Deserialize)]
+struct OverrideSpec {
+    effect: OverrideEffect,
+    rule: String,
+}
+
+impl SessionOverride {
+    /// Parse a `Tool` or `Tool:pattern` rule.
+    ///
+    /// # Errors
+    ///
+    /// Returns an error if the tool name is empty or the pattern does not
+    /// compile.
+    pub fn parse(effect: OverrideEffect, rule: &str) -> Result<Self, OverrideError> {
+        let (tool, pattern) = rule.split_once(':').unwrap_or((rule, ""));
+        let tool = tool.trim();
+        if tool.is_empty() {
+            return Err(OverrideError::MissingTool(rule.to_string()));
+        }
+        let invalid = |e: regex::Error| OverrideError::InvalidPattern {
+            rule: rule.to_string(),
+            message: e.to_string(),
+        };
+
+        let matcher = if pattern.is_empty() {
+            Matcher::Any
+        } else if let Some(regex) = pattern.strip_prefix(REGEX_PATTERN_PREFIX) {
+            Matcher::Regex(Regex::new(regex).map_err(invalid)?)
+        } else {
+            Matcher::Glob {
+                command: Regex::new(&glob_to_regex(pattern, false)).map_err(invalid)?,
+                path: Regex::new(&glob_to_regex(pattern, true)).map_err(invalid)?,
+            }
+        };
+
+        Ok(Self {
+            effect,
+            rule: rule.to_string(),
+            tool: tool.to_string(),
+            matcher,
+        })
+    }
+
+    /// Whether matching calls are allowed or denied.
+    #[must_use]
+    pub fn effect(&self) -> OverrideEffect {
+        self.effect
+    }
+
+    /// The rule as given on the command line.
+    #[must_use]
+    pub fn rule(&self) -> &str {
+        &self.rule
+    }
+
+    /// Name of the tool the rule applies to.
+    #[must_use]
+    pub fn tool(&self) -> &str {
+        &self.tool
+    }
+
+    /// Whether the rule applies to a call of `tool_name` with `tool_input`.
+    ///
+    /// A pattern never matches a call with nothing to match against.
+    #[must_use]
+    pub fn matches(&self, tool_name: &str, tool_input: &serde_json::Value) -> bool {
+        if !self.tool.eq_ignore_ascii_case(tool_name) {
+            return false;
+        }
+        let field = |name: &str| tool_input.get(name).and_then(serde_json::Value::as_str);
+        let command = field("command");
+        match &self.matcher {
+            Matcher::Any => true,
+            Matcher::Glob {
+                command: glob,
+                path,
+            } => match command {
+                Some(command) => glob.is_match(command),
+                None => input_paths(tool_input)
+                    .chain(field("url"))
+                    .any(|subject| path.is_match(subject)),
+            },
+            Matcher::Regex(regex) => command
+                .into_iter()
+                .chain(input_paths(tool_input))
+                .chain(field("url"))
+                .any(|subject| regex.is_match(subject)),
+        }
+    }
+}
+
+impl fmt::Display for SessionOverride {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        let flag = match self.effect {
+            OverrideEffect::Allow => "allow-once",
+            OverrideEffect::Deny => "deny-once",
+        };
+        write!(f, "{flag} {}", self.rule)
+    }
+}
+
+impl TryFrom<OverrideSpec> for SessionOverride {
+    type Error = OverrideError;
+
+    fn try_from(spec: OverrideSpec) -> Result<Self, Self::Error> {
+        Self::parse(spec.effect, &spec.rule)
+    }
+}
+
+impl From<SessionOverride> for OverrideSpec {
+    fn from(rule: SessionOverride) -> Self {
+        Self {
+            effect: rule.effect,
+            rule: rule.rule,
+        }
+    }
+}
+
+/// Anchored regex for a glob.
+///
+/// `**` matches anything and `?` one character. `*` matches anything in a
+/// command but stops at `/` in a path, and `**/` also matches no directory.
+pub(crate) fn glob_to_regex(glob: &str, path: bool) -> String {
+    let star = if path { "[^/]*" } else { ".*" };
+    let any = if path { "[^/]" } else { "." };
+    let mut regex = String::from("^");
+    let mut rest = glob;
+    while let Some(c) = rest.chars().next() {
+        if let Some(tail) = rest.strip_prefix("**/") {
+            regex.push_str("(?:.*/)?");
+            rest = tail;
+        } else if let Some(tail) = rest.strip_prefix("**") {
+            regex.push_str(".*");
+            rest = tail;
+        } else {
+            match c {
+                '*' => regex.push_str(star),
+                '?' => regex.push_str(any),
+                _ => regex.push_str(&regex::escape(c.encode_utf8(&mut [0; 4]))),
+            }
+            rest = &rest[c.len_utf8()..];
+        }
+    }
+    regex.push('$');
+    regex
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use serde_json::json;
+
+    #[test]
+    fn test_glob_matches_command() {
+        let rule = SessionOverride::parse(OverrideEffect::Allow, "Bash:docker *").unwrap();
+        assert!(rule.matches("Bash", &json!({"command": "docker run -v /a:/b img"})));
+        assert!(!rule.matches("Bash", &json!({"command": "sudo docker ps"})));
+        assert!(!rule.matches("Write", &json!({"command": "docker ps"})));
+    }
+
+    #[test]
+    fn test_glob_matches_paths() {
+        let rule = SessionOverride::parse(OverrideEffect::Deny, "Write:**/prod/**").unwrap();
+        assert!(rule.matches("Write", &json!({"file_path": "/srv/prod/app.yml"})));
+        assert!(rule.matches("Write", &json!({"file_path": "prod/app.yml"})));
+        assert!(!rule.matches("Write", &json!({"file_path": "/srv/staging/app.yml"})));
+
+        let single = SessionOverride::parse(OverrideEffect::Deny, "Edit:src/*.rs").unwrap();
+        assert!(single.matches("Edit", &json!({"file_path": "src/main.rs"})));
+        assert!(!single.matches("Edit", &json!({"file_path": "src/cli/mod.rs"})));
+    }
+
+    #[test]
+    fn test_regex_and_bare_tool() {
+        let rule = SessionOverride::parse(OverrideEffect::Deny, r"Bash:re:git\s+push").unwrap();
+        assert!(rule.matches("Bash", &json!({"command": "cd repo && git  push"})));
+
+        let any = SessionOverride::parse(OverrideEffect::Allow, "WebFetch").unwrap();
+        assert!(any.matches("WebFetch", &json!({"url": "https://example.com"})));
+        assert!(any.matches("webfetch", &json!({})));
+    }
+
+    #[test]
+    fn test_parse_errors() {
+        assert_eq!(
+            SessionOverride::parse(OverrideEffect::Allow, ":docker *").unwrap_err(),
+            OverrideError::MissingTool(":docker *".to_string())
+        );
+        assert!(matches!(
+            SessionOverride::parse(OverrideEffect::Allow, "Bash:re:(unclosed"),
+            Err(OverrideError::InvalidPattern { .. })
+        ));
+    }
+
+    #[test]
+    fn test_serde_round_trip() {
+        let rule = SessionOverride::parse(OverrideEffect::Deny, "Write:**/prod/**").unwrap();
+        let json = serde_json::to_value(&rule).unwrap();
+        assert_eq!(json, json!({"effect": "deny", "rule": "Write:**/prod/**"}));
+
+        let back: SessionOverride = serde_json::from_value(json).unwrap();
+        assert_eq!(back.to_string(), "deny-once Write:**/prod/**");
+        assert!(
+            serde_json::from_value::<SessionOverride>(json!({"effect": "allow", "rule": ""}))
+                .is_err()
+        );
+    }
+}
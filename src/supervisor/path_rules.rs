@@ -0,0 +1,172 @@
+//! Policy rules scoped to the paths that file writes target.
+//!
+//! A [`PathRule`] decides Write, Edit, `MultiEdit` and `NotebookEdit` calls
+//! by the file they target. Its glob is matched against the path relative
+//! to the session cwd, which starts with `../` for files outside it, and
+//! against the absolute path. Both are normalized first, so a write to
+//! `../../etc/passwd` is seen as `/etc/passwd` and `../../etc/passwd`, and
+//! neither `src/../Cargo.lock` nor `./Cargo.lock` slips past a rule for
+//! `Cargo.lock`. When several rules match, the strictest decides.
+
+use std::path::{Component, Path, PathBuf};
+
+use regex::Regex;
+
+use super::overrides::glob_to_regex;
+use super::protect::normalize;
+use crate::config::PathRuleAction;
+use crate::supervisor::PolicyDecision;
+
+/// Prefix of every path rule denial or escalation reason.
+pub const PATH_RULE_REASON: &str = "path rule";
+
+/// Tools whose target path the rules decide.
+const PATH_RULE_TOOLS: &[&str] = &[
+    "Write",
+    "Edit",
+    "MultiEdit",
+    "NotebookEdit",
+    "write",
+    "edit",
+];
+
+/// A compiled path rule.
+#[derive(Debug, Clone)]
+pub struct PathRule {
+    pattern: String,
+    action: PathRuleAction,
+    regex: Regex,
+}
+
+impl PathRule {
+    /// Compile a rule deciding writes to paths matching the glob `pattern`.
+    ///
+    /// # Errors
+    ///
+    /// Returns an error if the glob does not compile.
+    pub fn new(pattern: impl Into<String>, action: PathRuleAction) -> Result<Self, regex::Error> {
+        let pattern = pattern.into();
+        let regex = Regex::new(&glob_to_regex(&pattern, true))?;
+        Ok(Self {
+            pattern,
+            action,
+            regex,
+        })
+    }
+
+    /// Glob the rule matches.
+    #[must_use]
+    pub fn pattern(&self) -> &str {
+        &self.pattern
+    }
+
+    /// What happens to a matching call.
+    #[must_use]
+    pub fn action(&self) -> PathRuleAction {
+        self.action
+    }
+
+    /// Whether any of `paths` matches the rule.
+    #[must_use]
+    pub fn matches(&self, paths: &[String]) -> bool {
+        paths.iter().any(|path| self.regex.is_match(path))
+    }
+
+    /// Decision for a matching call of `tool_name` on `path`.
+    #[must_use]
+    pub fn decision(&self, tool_name: &str, path: &str) -> PolicyDecision {
+        let reason = |outcome: &str| {
+            format!(
+                "{PATH_RULE_REASON} '{}': {tool_name} of {path} {outcome}",
+                self.pattern
+            )
+        };
+        match self.action {
+            PathRuleAction::Allow => PolicyDecision::Allow,
+            PathRuleAction::Escalate => {
+                PolicyDecision::Escalate(reason("requires supervisor approval"))
+            }
+            PathRuleAction::Deny => PolicyDecision::deny(reason("is denied")),
+        }
+    }
+}
+
+/// Whether path rules decide calls of `tool_name`.
+#[must_use]
+pub fn is_path_rule_tool(tool_name: &str) -> bool {
+    PATH_RULE_TOOLS.contains(&tool_name)
+}
+
+/// Pattern of the path rule that gave `reason`, if a path rule gave it.
+#[must_use]
+pub fn path_rule_pattern(reason: &str) -> Option<&str> {
+    let (pattern, _) = reason
+        .strip_prefix(PATH_RULE_REASON)?
+        .strip_prefix(" '")?
+        .split_once("': ")?;
+    Some(pattern)
+}
+
+/// Forms of `path` the rules are matched against: relative to `cwd`, then
+/// absolute, both normalized.
+#[must_use]
+pub fn rule_paths(path: &str, cwd: &Path) -> Vec<String> {
+    let cwd = normalize(cwd);
+    let absolute = normalize(&cwd.join(path));
+    vec![
+        relative_to(&absolute, &cwd).to_string_lossy().into_owned(),
+        absolute.to_string_lossy().into_owned(),
+    ]
+}
+
+/// `path` relative to `base`, leaving `base` through `..` as needed; both
+/// are absolute and normalized.
+fn relative_to(path: &Path, base: &Path) -> PathBuf {
+    let path: Vec<Component<'_>> = path.components().collect();
+    let base: Vec<Component<'_>> = base.components().collect();
+    let common = path.iter().zip(&base).take_while(|(a, b)| a == b).count();
+    let mut relative: PathBuf = base[common..]
+        .iter()
+        .map(|_| Component::ParentDir)
+        .collect();
+    relative.extend(&path[common..]);
+    relative
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_rule_paths_normalize_against_cwd() {
+        let cwd = Path::new("/repo/crates/core");
+        assert_eq!(
+            rule_paths("../../../etc/passwd", cwd),
+            ["../../../etc/passwd", "/etc/passwd"]
+        );
+        assert_eq!(
+            rule_paths("./src/../Cargo.lock", cwd),
+            ["Cargo.lock", "/repo/crates/core/Cargo.lock"]
+        );
+        assert_eq!(
+            rule_paths("/repo/README.md", cwd),
+            ["../../README.md", "/repo/README.md"]
+        );
+    }
+
+    #[test]
+    fn test_rule_matches_any_form() {
+        let outside = PathRule::new("../**", PathRuleAction::Deny).unwrap();
+        let etc = PathRule::new("/etc/**", PathRuleAction::Deny).unwrap();
+        let paths = rule_paths("../../etc/passwd", Path::new("/repo/src"));
+        assert!(outside.matches(&paths));
+        assert!(etc.matches(&paths));
+        assert!(!outside.matches(&rule_paths("lib.rs", Path::new("/repo/src"))));
+
+        let decision = etc.decision("Write", "../../etc/passwd");
+        let PolicyDecision::Deny(reason) = decision else {
+            panic!("expected Deny, got {decision:?}");
+        };
+        assert_eq!(path_rule_pattern(&reason), Some("/etc/**"));
+    }
+}
@@ -0,0 +1,266 @@
+//! Assert-style test cases for policy configurations.
+//!
+//! A case file lists tool calls with the decision the policy should make:
+//!
+//! ```toml
+//! cases = [
+//!     { tool = "Bash", input = { command = "rm -rf /" }, expect = "deny" },
+//!     { name = "reads are fine", tool = "Read", input = { file_path = "src/main.rs" }, expect = "allow" },
+//! ]
+//! ```
+//!
+//! `claude-supervisor policy test` evaluates every case against the active
+//! config and reports mismatches.
+
+use std::fmt;
+use std::path::Path;
+use std::str::FromStr;
+
+use serde::{Deserialize, Serialize};
+
+use super::{PolicyDecision, PolicyEngine};
+
+/// Errors loading a policy case file.
+#[derive(thiserror::Error, Debug)]
+pub enum PolicyCaseError {
+    /// The file could not be read.
+    #[error("Failed to read policy cases: {0}")]
+    Io(#[from] std::io::Error),
+    /// The file is not valid TOML or has malformed cases.
+    #[error("Invalid policy cases: {0}")]
+    Parse(#[from] toml::de::Error),
+}
+
+/// Decision a policy case expects.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum ExpectedDecision {
+    /// Allowed unchanged.
+    Allow,
+    /// Denied.
+    Deny,
+    /// Escalated to the decision backend.
+    Escalate,
+    /// Allowed with modified input (e.g. wrapped in the sandbox).
+    AllowModified,
+}
+
+impl ExpectedDecision {
+    /// Name used in case files.
+    #[must_use]
+    pub fn as_str(&self) -> &'static str {
+        match self {
+            Self::Allow => "allow",
+            Self::Deny => "deny",
+            Self::Escalate => "escalate",
+            Self::AllowModified => "allow_modified",
+        }
+    }
+}
+
+impl fmt::Display for ExpectedDecision {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.write_str(self.as_str())
+    }
+}
+
+impl FromStr for ExpectedDecision {
+    type Err = String;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        match s.trim().to_lowercase().replace('-', "_").as_str() {
+            "allow" => Ok(Self::Allow),
+            "deny" => Ok(Self::Deny),
+            "escalate" => Ok(Self::Escalate),
+            "allow_modified" => Ok(Self::AllowModified),
+            other => Err(format!(
+                "unknown decision '{other}' (use allow, deny, escalate or allow_modified)"
+            )),
+        }
+    }
+}
+
+impl From<&PolicyDecision> for ExpectedDecision {
+    fn from(decision: &PolicyDecision) -> Self {
+        match decision {
+            PolicyDecision::Allow => Self::Allow,
+            PolicyDecision::AllowWithModification(_) => Self::AllowModified,
+            PolicyDecision::Deny(_) => Self::Deny,
+            PolicyDecision::Escalate(_) => Self::Escalate,
+        }
+    }
+}
+
+/// One tool call and the decision the policy should make for it.
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct PolicyCase {
+    /// Optional label shown in the report.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub name: Option<String>,
+    /// Tool name.
+    pub tool: String,
+    /// Tool input.
+    #[serde(default)]
+    pub input: serde_json::Value,
+    /// Expected decision.
+    pub expect: ExpectedDecision,
+}
+
+impl PolicyCase {
+    /// Label for the report: the name, or the tool and its input.
+    #[must_use]
+    pub fn label(&self) -> String {
+        self.name
+            .clone()
+            .unwrap_or_else(|| format!("{} {}", self.tool, self.input))
+    }
+}
+
+/// A file of policy cases.
+#[derive(Debug, Clone, Default, PartialEq, Serialize, Deserialize)]
+pub struct PolicyCaseFile {
+    /// The cases, in file order.
+    #[serde(default)]
+    pub cases: Vec<PolicyCase>,
+}
+
+impl PolicyCaseFile {
+    /// Parse a case file from TOML.
+    ///
+    /// # Errors
+    ///
+    /// Returns `PolicyCaseError::Parse` if the TOML or a case is malformed.
+    pub fn parse(content: &str) -> Result<Self, PolicyCaseError> {
+        Ok(toml::from_str(content)?)
+    }
+
+    /// Load a case file.
+    ///
+    /// # Errors
+    ///
+    /// Returns an error if the file cannot be read or parsed.
+    pub fn load(path: &Path) -> Result<Self, PolicyCaseError> {
+        Self::parse(&std::fs::read_to_string(path)?)
+    }
+}
+
+/// Outcome of one policy case.
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct PolicyCaseResult {
+    /// The case.
+    pub case: PolicyCase,
+    /// Decision the policy made.
+    pub actual: ExpectedDecision,
+    /// Rule that produced the decision.
+    pub rule: String,
+}
+
+impl PolicyCaseResult {
+    /// Whether the policy made the expected decision.
+    #[must_use]
+    pub fn passed(&self) -> bool {
+        self.actual == self.case.expect
+    }
+}
+
+/// Outcomes of a case file.
+#[derive(Debug, Clone, Default, PartialEq, Serialize, Deserialize)]
+pub struct PolicyCaseReport {
+    /// One result per case, in file order.
+    pub results: Vec<PolicyCaseResult>,
+}
+
+impl PolicyCaseReport {
+    /// Number of cases that failed.
+    #[must_use]
+    pub fn failures(&self) -> usize {
+        self.results.iter().filter(|r| !r.passed()).count()
+    }
+
+    /// Whether every case passed.
+    #[must_use]
+    pub fn all_passed(&self) -> bool {
+        self.failures() == 0
+    }
+}
+
+/// Evaluate policy cases against an engine.
+#[must_use]
+pub fn run_policy_cases(cases: &[PolicyCase], engine: &PolicyEngine) -> PolicyCaseReport {
+    let results = cases
+        .iter()
+        .map(|case| {
+            let decision = engine.evaluate(&case.tool, &case.input);
+            PolicyCaseResult {
+                case: case.clone(),
+                actual: ExpectedDecision::from(&decision),
+                rule: engine.rule_name(&case.tool, &case.input, &decision),
+            }
+        })
+        .collect();
+    PolicyCaseReport { results }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::supervisor::PolicyLevel;
+
+    #[test]
+    fn test_expected_decision_from_str() {
+        assert_eq!("deny".parse(), Ok(ExpectedDecision::Deny));
+        assert_eq!(
+            "Allow-Modified".parse(),
+            Ok(ExpectedDecision::AllowModified)
+        );
+        assert!("maybe".parse::<ExpectedDecision>().is_err());
+    }
+
+    #[test]
+    fn test_parse_case_file() {
+        let file = PolicyCaseFile::parse(
+            r#"
+            cases = [
+                { tool = "Bash", input = { command = "rm -rf /" }, expect = "deny" },
+                { name = "read", tool = "Read", expect = "allow_modified" },
+            ]
+            "#,
+        )
+        .unwrap();
+        assert_eq!(file.cases.len(), 2);
+        assert_eq!(file.cases[0].input["command"], "rm -rf /");
+        assert_eq!(file.cases[1].expect, ExpectedDecision::AllowModified);
+        assert_eq!(file.cases[1].label(), "read");
+    }
+
+    #[test]
+    fn test_parse_rejects_unknown_decision() {
+        let err =
+            PolicyCaseFile::parse(r#"cases = [{ tool = "Read", expect = "maybe" }]"#).unwrap_err();
+        assert!(matches!(err, PolicyCaseError::Parse(_)));
+    }
+
+    #[test]
+    fn test_run_policy_cases_reports_rule_and_failures() {
+        let mut engine = PolicyEngine::new(PolicyLevel::Permissive);
+        engine.deny_tool("WebFetch");
+        let cases = PolicyCaseFile::parse(
+            r#"
+            cases = [
+                { tool = "WebFetch", input = { url = "https://example.com" }, expect = "deny" },
+                { tool = "Read", input = { file_path = "src/lib.rs" }, expect = "deny" },
+            ]
+            "#,
+        )
+        .unwrap()
+        .cases;
+
+        let report = run_policy_cases(&cases, &engine);
+        assert!(report.results[0].passed());
+        assert_eq!(report.results[0].rule, "denied tool");
+        assert!(!report.results[1].passed());
+        assert_eq!(report.results[1].actual, ExpectedDecision::Allow);
+        assert_eq!(report.failures(), 1);
+        assert!(!report.all_passed());
+    }
+}
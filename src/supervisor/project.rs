@@ -0,0 +1,138 @@
+//! Policy rules declared by a project in its CLAUDE.md.
+//!
+//! A repository can state its norms for the supervisor in a fenced
+//! `supervisor-policy` block:
+//!
+//! ````markdown
+//! ```supervisor-policy
+//! # Tools to auto-approve
+//! allow = ["Read", "Grep"]
+//! # Tools to always deny
+//! deny = ["WebFetch"]
+//!
+//! # Bash commands to block
+//! [[rules]]
+//! pattern = 'git\s+push\s+\S+\s+main\b'
+//! description = "Never push directly to main"
+//! ```
+//! ````
+//!
+//! CLAUDE.md is writable by the supervised session, so these rules can only
+//! add restrictions: deny entries and rules are added to the policy, while an
+//! allowed tool never overrides a denied tool or a blocklist rule.
+
+use serde::{Deserialize, Serialize};
+
+/// Info string of the fenced code block holding project policy.
+pub const PROJECT_POLICY_BLOCK: &str = "supervisor-policy";
+
+/// A Bash command pattern blocked by the project.
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(deny_unknown_fields)]
+pub struct ProjectRule {
+    /// Regex matched against the command.
+    pub pattern: String,
+    /// Reason shown when a command is blocked.
+    pub description: String,
+}
+
+/// Policy rules from `supervisor-policy` blocks.
+#[derive(Debug, Clone, Default, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(default, deny_unknown_fields)]
+pub struct ProjectPolicy {
+    /// Tools to auto-approve: read-only tools, or tools the configuration
+    /// already allows, named exactly.
+    pub allow: Vec<String>,
+    /// Tools to always deny.
+    pub deny: Vec<String>,
+    /// Bash command patterns to block.
+    pub rules: Vec<ProjectRule>,
+}
+
+impl ProjectPolicy {
+    /// Parse the TOML content of a `supervisor-policy` block.
+    ///
+    /// Unknown keys are rejected, so a block cannot appear to switch off
+    /// rules it has no power over.
+    ///
+    /// # Errors
+    ///
+    /// Returns an error if the block is not valid policy TOML.
+    pub fn parse(block: &str) -> Result<Self, toml::de::Error> {
+        toml::from_str(block)
+    }
+
+    /// Parse every block, skipping and logging invalid ones, and merge them.
+    #[must_use]
+    pub fn from_blocks<S: AsRef<str>>(blocks: &[S]) -> Self {
+        let mut policy = Self::default();
+        for block in blocks {
+            match Self::parse(block.as_ref()) {
+                Ok(parsed) => policy.merge(parsed),
+                Err(e) => {
+                    tracing::warn!(error = %e, "Ignoring invalid {PROJECT_POLICY_BLOCK} block");
+                }
+            }
+        }
+        policy
+    }
+
+    /// Add another block's entries, skipping duplicates.
+    pub fn merge(&mut self, other: Self) {
+        for tool in other.allow {
+            if !self.allow.contains(&tool) {
+                self.allow.push(tool);
+            }
+        }
+        for tool in other.deny {
+            if !self.deny.contains(&tool) {
+                self.deny.push(tool);
+            }
+        }
+        for rule in other.rules {
+            if !self.rules.contains(&rule) {
+                self.rules.push(rule);
+            }
+        }
+    }
+
+    /// Whether the policy declares nothing.
+    #[must_use]
+    pub fn is_empty(&self) -> bool {
+        self.allow.is_empty() && self.deny.is_empty() && self.rules.is_empty()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_parse_block() {
+        let policy = ProjectPolicy::parse(
+            "allow = [\"Read\"]\ndeny = [\"WebFetch\"]\n\n[[rules]]\npattern = 'git\\s+push'\ndescription = \"No pushes\"\n",
+        )
+        .unwrap();
+        assert_eq!(policy.allow, ["Read"]);
+        assert_eq!(policy.deny, ["WebFetch"]);
+        assert_eq!(policy.rules[0].pattern, r"git\s+push");
+    }
+
+    #[test]
+    fn test_unknown_keys_are_rejected() {
+        assert!(ProjectPolicy::parse("block_destructive = false\n").is_err());
+        assert!(ProjectPolicy::parse("[bash]\nblocked_patterns = []\n").is_err());
+    }
+
+    #[test]
+    fn test_from_blocks_merges_and_skips_invalid() {
+        let policy = ProjectPolicy::from_blocks(&[
+            "deny = [\"WebFetch\"]",
+            "not toml =",
+            "deny = [\"WebFetch\", \"Task\"]\nallow = [\"Read\"]",
+        ]);
+        assert_eq!(policy.deny, ["WebFetch", "Task"]);
+        assert_eq!(policy.allow, ["Read"]);
+        assert!(ProjectPolicy::from_blocks::<&str>(&[]).is_empty());
+    }
+}
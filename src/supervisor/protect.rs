@@ -0,0 +1,463 @@
+//! Self-protection for the supervisor's own files.
+//!
+//! A supervised session that can rewrite `.claude-supervisor.toml`, truncate
+//! the audit database or strip the hook entries from `settings.json` can
+//! switch its own supervision off. [`SelfProtection`] denies any Write, Edit
+//! or Bash call that targets those files, however the path is spelled.
+
+use std::ffi::OsString;
+use std::path::{Component, Path, PathBuf};
+
+use crate::audit::default_audit_path;
+use crate::config::{ClaudeSettings, ConfigLoader, WorktreeConfig};
+use crate::worktree::WorktreeRegistry;
+
+/// Prefix of every self-protection denial reason.
+pub const SELF_PROTECTION_REASON: &str = "self-protection";
+
+/// Project-relative Claude settings files that can install or disable hooks.
+const PROJECT_SETTINGS_FILES: &[&str] = &[".claude/settings.json", ".claude/settings.local.json"];
+
+/// Characters that separate words in a shell command for path extraction.
+const SHELL_SEPARATORS: &[char] = &[';', '|', '&', '<', '>', '(', ')', '`', '\n'];
+
+/// Characters that separate the commands of a shell command line.
+const COMMAND_SEPARATORS: &[char] = &[';', '|', '&', '(', ')', '`', '\n'];
+
+/// Commands that remove, move or overwrite whole directory trees, so naming
+/// a directory holding a protected file modifies that file.
+const TREE_COMMANDS: &[&str] = &["rm", "rmdir", "mv", "cp", "unlink", "shred", "rsync"];
+
+/// Words that run the command after them.
+const COMMAND_PREFIXES: &[&str] = &["sudo", "env", "command", "exec", "nohup", "time"];
+
+/// Guard that denies modifications to the supervisor's own files.
+///
+/// Relative protected paths are resolved against the session cwd at check
+/// time; absolute ones are fixed. Bash commands are not parsed for intent:
+/// any word that resolves to a protected file denies the command, and so
+/// does a directory holding one given to a command that removes, moves or
+/// copies over whole trees, such as `rm -r` or `mv`.
+#[derive(Debug, Clone, Default)]
+pub struct SelfProtection {
+    protected: Vec<PathBuf>,
+}
+
+impl SelfProtection {
+    /// Create a guard that protects nothing.
+    #[must_use]
+    pub fn disabled() -> Self {
+        Self::default()
+    }
+
+    /// Create a guard for the supervisor's config search paths, audit
+    /// database, worktree registry, Claude settings and hook binary.
+    #[must_use]
+    pub fn with_default_paths() -> Self {
+        let mut guard = Self::default();
+        for path in ConfigLoader::new().search_paths() {
+            guard.protect(path);
+        }
+        guard.protect(default_audit_path());
+        guard.protect(WorktreeRegistry::default_path(
+            &WorktreeConfig::default().worktree_dir,
+        ));
+        if let Some(settings) = ClaudeSettings::default_path() {
+            guard.protect(settings);
+        }
+        for settings in PROJECT_SETTINGS_FILES {
+            guard.protect(settings);
+        }
+        if let Ok(exe) = std::env::current_exe() {
+            guard.protect(exe);
+        }
+        guard
+    }
+
+    /// Add a path to protect.
+    pub fn protect(&mut self, path: impl Into<PathBuf>) {
+        self.protected.push(path.into());
+    }
+
+    /// Check whether the guard protects anything.
+    #[must_use]
+    pub fn is_enabled(&self) -> bool {
+        !self.protected.is_empty()
+    }
+
+    /// Get the protected paths as configured.
+    #[must_use]
+    pub fn protected_paths(&self) -> &[PathBuf] {
+        &self.protected
+    }
+
+    /// Return the protected file that `path` resolves to, or that is inside
+    /// the directory `path` resolves to, if any.
+    #[must_use]
+    pub fn check_path(&self, path: &str, cwd: &Path) -> Option<PathBuf> {
+        self.find_protected(path, cwd, true)
+    }
+
+    /// Return the protected file referenced by any word of a shell command,
+    /// or inside a directory named to a command in [`TREE_COMMANDS`].
+    #[must_use]
+    pub fn check_command(&self, command: &str, cwd: &Path) -> Option<PathBuf> {
+        if !self.is_enabled() {
+            return None;
+        }
+        let files = command_words(command).find_map(|word| self.find_protected(word, cwd, false));
+        files.or_else(|| {
+            command
+                .split(COMMAND_SEPARATORS)
+                .filter(|segment| is_tree_command(segment))
+                .flat_map(command_words)
+                .find_map(|word| self.find_protected(word, cwd, true))
+        })
+    }
+
+    /// The protected file `path` resolves to or, with `ancestors`, one inside
+    /// the directory it resolves to.
+    ///
+    /// The filesystem root holds every file; commands on it are left to the
+    /// blocklist's destructive command rules.
+    fn find_protected(&self, path: &str, cwd: &Path, ancestors: bool) -> Option<PathBuf> {
+        if path.is_empty() || !self.is_enabled() {
+            return None;
+        }
+        let target = resolve(Path::new(path), cwd);
+        let ancestors = ancestors && target.parent().is_some();
+        self.protected
+            .iter()
+            .map(|protected| resolve(protected, cwd))
+            .find(|protected| {
+                targets(&target, protected) || (ancestors && protected.starts_with(&target))
+            })
+    }
+
+    /// Build the denial reason for a protected path.
+    #[must_use]
+    pub fn deny_reason(tool_name: &str, protected: &Path) -> String {
+        format!(
+            "{SELF_PROTECTION_REASON}: {tool_name} may not modify supervisor file {}",
+            protected.display()
+        )
+    }
+}
+
+/// The words of a shell command that may name a path.
+fn command_words(command: &str) -> impl Iterator<Item = &str> {
+    command
+        .split(|c: char| c.is_whitespace() || SHELL_SEPARATORS.contains(&c))
+        .map(|word| word.trim_matches(|c| c == '"' || c == '\''))
+        .flat_map(|word| {
+            // `--output=path` and `VAR=path` carry the path after the `=`.
+            let value = word.rsplit_once('=').map(|(_, v)| v);
+            std::iter::once(word).chain(value)
+        })
+}
+
+/// Whether a single command runs one of [`TREE_COMMANDS`], or `find` with
+/// `-delete`.
+fn is_tree_command(segment: &str) -> bool {
+    let mut words = segment
+        .split_whitespace()
+        .skip_while(|word| word.contains('=') || COMMAND_PREFIXES.contains(word));
+    let Some(binary) = words.next() else {
+        return false;
+    };
+    let binary = binary.rsplit('/').next().unwrap_or(binary);
+    TREE_COMMANDS.contains(&binary) || (binary == "find" && words.any(|word| word == "-delete"))
+}
+
+/// Whether writing `target` would modify `protected`.
+///
+/// `SQLite` sidecars (`audit.db-wal`, `audit.db-journal`) count as the database.
+fn targets(target: &Path, protected: &Path) -> bool {
+    if target == protected {
+        return true;
+    }
+    match (
+        target.parent(),
+        protected.parent(),
+        target.file_name(),
+        protected.file_name(),
+    ) {
+        (Some(tp), Some(pp), Some(tn), Some(pn)) if tp == pp => {
+            let (tn, pn) = (tn.to_string_lossy(), pn.to_string_lossy());
+            tn.strip_prefix(pn.as_ref())
+                .is_some_and(|rest| rest.starts_with('-'))
+        }
+        _ => false,
+    }
+}
+
+/// Resolve a path the way the shell and filesystem would.
+///
+/// Expands `~` and `$HOME`, anchors relative paths at `cwd`, removes `.` and
+/// `..` lexically, then follows symlinks in the longest existing prefix so
+/// files that do not exist yet still resolve through symlinked directories.
+fn resolve(path: &Path, cwd: &Path) -> PathBuf {
+    let expanded = expand_home(path);
+    let absolute = if expanded.is_absolute() {
+        expanded
+    } else {
+        cwd.join(expanded)
+    };
+    canonicalize_existing(&normalize(&absolute))
+}
+
+/// Expand a leading `~` or `$HOME` to the home directory.
+pub(crate) fn expand_home(path: &Path) -> PathBuf {
+    let Some(home) = dirs::home_dir() else {
+        return path.to_path_buf();
+    };
+    let text = path.to_string_lossy();
+    for prefix in ["~", "$HOME", "${HOME}"] {
+        if text == prefix {
+            return home;
+        }
+        if let Some(rest) = text.strip_prefix(&format!("{prefix}/")) {
+            return home.join(rest);
+        }
+    }
+    path.to_path_buf()
+}
+
+/// Remove `.` and `..` components without touching the filesystem.
+pub(crate) fn normalize(path: &Path) -> PathBuf {
+    let mut normalized = PathBuf::new();
+    for component in path.components() {
+        match component {
+            Component::CurDir => {}
+            Component::ParentDir => {
+                normalized.pop();
+            }
+            other => normalized.push(other),
+        }
+    }
+    normalized
+}
+
+/// Canonicalize the longest existing ancestor of `path` and re-append the rest.
+fn canonicalize_existing(path: &Path) -> PathBuf {
+    let mut existing = path;
+    let mut rest: Vec<OsString> = Vec::new();
+    loop {
+        if let Ok(canonical) = existing.canonicalize() {
+            return rest
+                .iter()
+                .rev()
+                .fold(canonical, |acc, part| acc.join(part));
+        }
+        match (existing.parent(), existing.file_name()) {
+            (Some(parent), Some(name)) => {
+                rest.push(name.to_os_string());
+                existing = parent;
+            }
+            _ => return path.to_path_buf(),
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn guard_for(dir: &Path) -> (SelfProtection, PathBuf) {
+        let config = dir.join("supervisor").join("config.toml");
+        std::fs::create_dir_all(config.parent().unwrap()).unwrap();
+        std::fs::write(&config, "level = \"strict\"").unwrap();
+        let mut guard = SelfProtection::disabled();
+        guard.protect(&config);
+        (guard, config.canonicalize().unwrap())
+    }
+
+    #[test]
+    fn test_absolute_path_is_protected() {
+        let dir = tempfile::tempdir().unwrap();
+        let (guard, config) = guard_for(dir.path());
+        assert_eq!(
+            guard.check_path(config.to_str().unwrap(), Path::new("/")),
+            Some(config)
+        );
+    }
+
+    #[test]
+    fn test_relative_path_resolves_against_cwd() {
+        let dir = tempfile::tempdir().unwrap();
+        let (guard, config) = guard_for(dir.path());
+        let cwd = dir.path().join("project");
+        std::fs::create_dir_all(&cwd).unwrap();
+
+        assert_eq!(
+            guard.check_path("../supervisor/./config.toml", &cwd),
+            Some(config)
+        );
+        assert!(guard.check_path("config.toml", &cwd).is_none());
+    }
+
+    #[test]
+    fn test_relative_protected_path_follows_cwd() {
+        let dir = tempfile::tempdir().unwrap();
+        let mut guard = SelfProtection::disabled();
+        guard.protect(".claude-supervisor.toml");
+
+        assert!(guard
+            .check_path(".claude-supervisor.toml", dir.path())
+            .is_some());
+        let absolute = dir.path().join(".claude-supervisor.toml");
+        assert!(guard
+            .check_path(absolute.to_str().unwrap(), dir.path())
+            .is_some());
+    }
+
+    #[cfg(unix)]
+    #[test]
+    fn test_symlinks_are_followed() {
+        let dir = tempfile::tempdir().unwrap();
+        let (guard, config) = guard_for(dir.path());
+
+        let file_link = dir.path().join("innocent.toml");
+        std::os::unix::fs::symlink(&config, &file_link).unwrap();
+        assert_eq!(
+            guard.check_path(file_link.to_str().unwrap(), dir.path()),
+            Some(config.clone())
+        );
+
+        let dir_link = dir.path().join("elsewhere");
+        std::os::unix::fs::symlink(config.parent().unwrap(), &dir_link).unwrap();
+        assert_eq!(
+            guard.check_path("elsewhere/config.toml", dir.path()),
+            Some(config)
+        );
+    }
+
+    #[test]
+    fn test_bash_redirect_and_arguments() {
+        let dir = tempfile::tempdir().unwrap();
+        let (guard, config) = guard_for(dir.path());
+        let cwd = dir.path();
+
+        for command in [
+            "echo 'level = \"permissive\"' > supervisor/config.toml",
+            "echo x >>supervisor/config.toml",
+            "cat /dev/null 2>supervisor/config.toml",
+            "sed -i 's/strict/permissive/' \"supervisor/config.toml\"",
+            "cp /tmp/evil.toml ./supervisor/config.toml && ls",
+            "tool --output=supervisor/config.toml",
+        ] {
+            assert_eq!(
+                guard.check_command(command, cwd),
+                Some(config.clone()),
+                "{command}"
+            );
+        }
+        assert!(guard
+            .check_command("cargo test && ls supervisor", cwd)
+            .is_none());
+    }
+
+    #[test]
+    fn test_removing_or_moving_a_parent_directory() {
+        let dir = tempfile::tempdir().unwrap();
+        let (guard, config) = guard_for(dir.path());
+        let cwd = dir.path();
+
+        for command in [
+            "rm -rf supervisor",
+            "rm -r ./supervisor/",
+            "rm -rf .",
+            "cd /tmp && rm -rf ..",
+            "mv supervisor .x",
+            "sudo mv supervisor /tmp/elsewhere",
+            "/bin/rm -r supervisor",
+            "cp -r /tmp/evil supervisor",
+            "find . -delete",
+        ] {
+            assert_eq!(
+                guard.check_command(command, cwd),
+                Some(config.clone()),
+                "{command}"
+            );
+        }
+        let nested = cwd.join("supervisor");
+        assert_eq!(
+            guard.check_command("rm -rf ..", &nested),
+            Some(config.clone())
+        );
+        let outside = dir.path().join("other");
+        std::fs::create_dir_all(&outside).unwrap();
+        assert_eq!(
+            guard.check_command(&format!("rm -rf {}", dir.path().display()), &outside),
+            Some(config.clone())
+        );
+
+        // Reading a parent directory, or removing a sibling, is fine
+        for command in [
+            "ls supervisor",
+            "find . -name '*.rs'",
+            "rm -rf target",
+            "mv a b",
+        ] {
+            assert!(guard.check_command(command, cwd).is_none(), "{command}");
+        }
+
+        // Project settings resolve against the session cwd
+        let mut settings = SelfProtection::disabled();
+        settings.protect(".claude/settings.json");
+        for command in ["mv .claude .x", "rm -rf .claude", "rm -rf ."] {
+            assert!(settings.check_command(command, cwd).is_some(), "{command}");
+        }
+
+        // File tools cannot name the directory either
+        assert_eq!(guard.check_path("supervisor", cwd), Some(config));
+    }
+
+    #[test]
+    fn test_sqlite_sidecars_are_protected() {
+        let dir = tempfile::tempdir().unwrap();
+        let mut guard = SelfProtection::disabled();
+        guard.protect(dir.path().join("audit.db"));
+
+        assert!(guard.check_path("audit.db-wal", dir.path()).is_some());
+        assert!(guard.check_path("audit.db-journal", dir.path()).is_some());
+        assert!(guard.check_path("audit.dbx", dir.path()).is_none());
+    }
+
+    #[test]
+    fn test_home_expansion() {
+        let Some(home) = dirs::home_dir() else {
+            return;
+        };
+        let mut guard = SelfProtection::disabled();
+        guard.protect(home.join(".config/claude-supervisor/config.toml"));
+
+        assert!(guard
+            .check_command("rm ~/.config/claude-supervisor/config.toml", Path::new("/"))
+            .is_some());
+        assert!(guard
+            .check_command("rm -rf ~/.config/claude-supervisor", Path::new("/"))
+            .is_some());
+        assert!(guard
+            .check_path(
+                "$HOME/.config/claude-supervisor/config.toml",
+                Path::new("/")
+            )
+            .is_some());
+    }
+
+    #[test]
+    fn test_default_paths_cover_supervisor_files() {
+        let guard = SelfProtection::with_default_paths();
+        let cwd = std::env::temp_dir();
+
+        assert!(guard.check_path(".claude-supervisor.toml", &cwd).is_some());
+        assert!(guard.check_path(".worktrees/state.json", &cwd).is_some());
+        assert!(guard
+            .check_path(".claude/settings.local.json", &cwd)
+            .is_some());
+        let audit = default_audit_path();
+        assert!(guard.check_path(audit.to_str().unwrap(), &cwd).is_some());
+        assert!(guard.check_path("src/main.rs", &cwd).is_none());
+    }
+}
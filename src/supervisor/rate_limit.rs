@@ -0,0 +1,222 @@
+//! Per-tool rate limits on tool calls.
+//!
+//! Each limited tool gets a sliding window: a call is escalated when more
+//! than `max` calls to the tool were made in the last `per_seconds`,
+//! counting itself. The runner keeps the windows in memory; hooks run once
+//! per call, so they keep them in a small state file per session, read and
+//! rewritten on every call. Concurrent hooks may lose each other's calls,
+//! which makes their limits approximate.
+
+use std::collections::{BTreeMap, VecDeque};
+use std::path::{Path, PathBuf};
+use std::sync::{Arc, Mutex};
+use std::time::{Duration, SystemTime, UNIX_EPOCH};
+
+use super::PolicyDecision;
+use crate::config::RateLimitConfig;
+
+/// Prefix of the reasons given for calls over a rate limit.
+pub const RATE_LIMIT_REASON: &str = "Rate limit exceeded";
+
+/// Call times in milliseconds since the Unix epoch, keyed by tool name.
+type CallTimes = BTreeMap<String, VecDeque<u64>>;
+
+/// Sliding-window rate limiter shared by the clones of a policy engine.
+#[derive(Debug, Clone, Default)]
+pub struct RateLimiter {
+    limits: BTreeMap<String, RateLimitConfig>,
+    calls: Arc<Mutex<CallTimes>>,
+    state_file: Option<PathBuf>,
+}
+
+impl RateLimiter {
+    /// Create a limiter for `limits` keyed by tool name, or `None` if there
+    /// are none.
+    #[must_use]
+    pub fn from_config(limits: &BTreeMap<String, RateLimitConfig>) -> Option<Self> {
+        (!limits.is_empty()).then(|| Self {
+            limits: limits.clone(),
+            ..Self::default()
+        })
+    }
+
+    /// A limiter with the same limits that counts its calls apart from this
+    /// one, in memory.
+    #[must_use]
+    pub fn with_own_calls(&self) -> Self {
+        Self {
+            limits: self.limits.clone(),
+            ..Self::default()
+        }
+    }
+
+    /// Keep call times in `path` instead of memory (builder pattern).
+    #[must_use]
+    pub fn with_state_file(mut self, path: impl Into<PathBuf>) -> Self {
+        self.state_file = Some(path.into());
+        self
+    }
+
+    /// Default directory for the state files of hook sessions.
+    ///
+    /// This is `state/rate-limits` in the [data directory](crate::config::data_dir).
+    #[must_use]
+    pub fn default_dir() -> PathBuf {
+        crate::config::state_dir().join("rate-limits")
+    }
+
+    /// Path of the state file for `session_id` in `dir`.
+    ///
+    /// Characters other than ASCII letters, digits, `-` and `_` are replaced
+    /// so a session ID cannot escape `dir`.
+    #[must_use]
+    pub fn path_in(dir: &Path, session_id: &str) -> PathBuf {
+        let file: String = session_id
+            .chars()
+            .map(|c| {
+                if c.is_ascii_alphanumeric() || c == '-' || c == '_' {
+                    c
+                } else {
+                    '_'
+                }
+            })
+            .collect();
+        dir.join(format!("{file}.json"))
+    }
+
+    /// The limit on a tool, if any.
+    #[must_use]
+    pub fn limit(&self, tool_name: &str) -> Option<&RateLimitConfig> {
+        self.limits.get(tool_name)
+    }
+
+    /// Record a call to `tool_name` now; an escalation if it is over the
+    /// tool's limit.
+    #[must_use]
+    pub fn record(&self, tool_name: &str) -> Option<PolicyDecision> {
+        self.record_at(tool_name, SystemTime::now())
+    }
+
+    /// Record a call to `tool_name` at `now`; an escalation if it is over
+    /// the tool's limit.
+    ///
+    /// # Panics
+    ///
+    /// Panics if the internal `Mutex` is poisoned.
+    #[must_use]
+    pub fn record_at(&self, tool_name: &str, now: SystemTime) -> Option<PolicyDecision> {
+        let limit = self.limits.get(tool_name)?;
+        let mut calls = self.calls.lock().expect("Mutex poisoned");
+        if let Some(ref path) = self.state_file {
+            *calls = load(path);
+        }
+
+        let now = millis(now);
+        for (tool, times) in calls.iter_mut() {
+            let window = self.limits.get(tool).map_or(0, |limit| limit.per_seconds);
+            let start = now.saturating_sub(window.saturating_mul(1000));
+            while times.front().is_some_and(|&time| time <= start) {
+                times.pop_front();
+            }
+        }
+        calls.retain(|_, times| !times.is_empty());
+        let times = calls.entry(tool_name.to_string()).or_default();
+        times.push_back(now);
+        let count = times.len();
+
+        if let Some(ref path) = self.state_file {
+            if let Err(e) = save(path, &calls) {
+                tracing::warn!(path = %path.display(), error = %e, "Failed to save rate limit state");
+            }
+        }
+        (count > usize::try_from(limit.max).unwrap_or(usize::MAX)).then(|| {
+            PolicyDecision::Escalate(format!(
+                "{RATE_LIMIT_REASON} for {tool_name}: more than {} calls in {}s",
+                limit.max, limit.per_seconds
+            ))
+        })
+    }
+}
+
+/// Milliseconds since the Unix epoch.
+fn millis(time: SystemTime) -> u64 {
+    time.duration_since(UNIX_EPOCH)
+        .unwrap_or(Duration::ZERO)
+        .as_millis()
+        .try_into()
+        .unwrap_or(u64::MAX)
+}
+
+/// Call times saved in `path`; none if it is missing or unreadable.
+fn load(path: &Path) -> CallTimes {
+    std::fs::read(path)
+        .ok()
+        .and_then(|bytes| serde_json::from_slice(&bytes).ok())
+        .unwrap_or_default()
+}
+
+/// Save call times to `path`, replacing it atomically.
+fn save(path: &Path, calls: &CallTimes) -> std::io::Result<()> {
+    if let Some(dir) = path.parent() {
+        std::fs::create_dir_all(dir)?;
+    }
+    let tmp = path.with_extension(format!("json.{}.tmp", std::process::id()));
+    std::fs::write(&tmp, serde_json::to_vec(calls)?)?;
+    std::fs::rename(&tmp, path)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn limiter() -> RateLimiter {
+        let limit = RateLimitConfig {
+            max: 2,
+            per_seconds: 60,
+        };
+        RateLimiter::from_config(&BTreeMap::from([("Bash".to_string(), limit)])).unwrap()
+    }
+
+    #[test]
+    fn test_calls_over_the_limit_escalate_until_the_window_slides() {
+        let limiter = limiter();
+        let start = UNIX_EPOCH + Duration::from_secs(1_000_000);
+        let at = |secs| start + Duration::from_secs(secs);
+
+        assert_eq!(limiter.record_at("Bash", at(0)), None);
+        assert_eq!(limiter.record_at("Bash", at(10)), None);
+        assert_eq!(limiter.record_at("Read", at(11)), None);
+        let Some(PolicyDecision::Escalate(reason)) = limiter.record_at("Bash", at(20)) else {
+            panic!("expected an escalation");
+        };
+        assert_eq!(
+            reason,
+            "Rate limit exceeded for Bash: more than 2 calls in 60s"
+        );
+
+        // Escalated calls count too, until they leave the window
+        assert!(limiter.record_at("Bash", at(61)).is_some());
+        assert_eq!(limiter.record_at("Bash", at(81)), None);
+        assert!(RateLimiter::from_config(&BTreeMap::new()).is_none());
+    }
+
+    #[test]
+    fn test_state_file_is_shared_between_limiters() {
+        let dir = tempfile::tempdir().unwrap();
+        let path = RateLimiter::path_in(dir.path(), "../session/1");
+        assert_eq!(path, dir.path().join("___session_1.json"));
+        let now = SystemTime::now();
+
+        // Each hook builds its own limiter
+        for _ in 0..2 {
+            let limiter = limiter().with_state_file(&path);
+            assert_eq!(limiter.record_at("Bash", now), None);
+        }
+        let third = limiter().with_state_file(&path);
+        assert!(third.record_at("Bash", now).is_some());
+        assert!(limiter()
+            .with_state_file(dir.path().join("other.json"))
+            .record_at("Bash", now)
+            .is_none());
+    }
+}
@@ -0,0 +1,161 @@
+//! Supervisor context carried across `run --resume`.
+//!
+//! Claude keeps its own memory when a session is resumed, but the supervisor
+//! would start cold. At session end the supervisor saves a [`ResumeContext`]
+//! keyed by the Claude session ID; `run --resume` loads it so stats, cost
+//! and escalation context continue where they left off.
+
+use std::path::{Path, PathBuf};
+
+use chrono::{DateTime, Utc};
+use serde::{Deserialize, Serialize};
+
+use crate::cli::ClaudeEvent;
+use crate::supervisor::SessionStats;
+
+/// Most recent events kept in a resume context.
+pub const RESUME_CONTEXT_EVENTS: usize = 50;
+
+/// Errors reading or writing a resume context.
+#[derive(thiserror::Error, Debug)]
+pub enum ResumeError {
+    /// The context file could not be read or written.
+    #[error("Resume context I/O error: {0}")]
+    Io(#[from] std::io::Error),
+    /// The context file is not valid JSON.
+    #[error("Invalid resume context: {0}")]
+    Json(#[from] serde_json::Error),
+}
+
+/// Supervisor-side state of a session, saved for resuming it.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct ResumeContext {
+    /// Claude session ID the context belongs to.
+    pub session_id: String,
+    /// Original task, as opposed to the `continue` prompt of a resume.
+    #[serde(default)]
+    pub task: Option<String>,
+    /// Supervisor-assigned session name.
+    #[serde(default)]
+    pub name: Option<String>,
+    /// Tool call counts, cumulative over all runs of the session.
+    pub stats: SessionStats,
+    /// Assistant turns, cumulative over all runs of the session.
+    #[serde(default)]
+    pub turns: usize,
+    /// Cost in micro-dollars, cumulative over all runs of the session.
+    #[serde(default)]
+    pub cost_micros: u64,
+    /// Most recent events, oldest first, as kept by the event history.
+    #[serde(default)]
+    pub events: Vec<ClaudeEvent>,
+    /// When the context was saved.
+    pub saved_at: DateTime<Utc>,
+}
+
+impl ResumeContext {
+    /// Default directory for resume contexts.
+    ///
+    /// This is `state/resume` in the [data directory](crate::config::data_dir).
+    #[must_use]
+    pub fn default_dir() -> PathBuf {
+        crate::config::state_dir().join("resume")
+    }
+
+    /// Path of the context for `session_id` in `dir`.
+    ///
+    /// Characters other than ASCII letters, digits, `-` and `_` are replaced
+    /// so a session ID cannot escape `dir`.
+    #[must_use]
+    pub fn path_in(dir: &Path, session_id: &str) -> PathBuf {
+        let file: String = session_id
+            .chars()
+            .map(|c| {
+                if c.is_ascii_alphanumeric() || c == '-' || c == '_' {
+                    c
+                } else {
+                    '_'
+                }
+            })
+            .collect();
+        dir.join(format!("{file}.json"))
+    }
+
+    /// Save the context in `dir`, replacing any earlier one.
+    ///
+    /// # Errors
+    ///
+    /// Returns an error if the directory or file cannot be written.
+    pub fn save(&self, dir: &Path) -> Result<PathBuf, ResumeError> {
+        std::fs::create_dir_all(dir)?;
+        let path = Self::path_in(dir, &self.session_id);
+        let tmp = path.with_extension("json.tmp");
+        std::fs::write(&tmp, serde_json::to_vec_pretty(self)?)?;
+        std::fs::rename(&tmp, &path)?;
+        Ok(path)
+    }
+
+    /// Load the context for `session_id` from `dir`, if one was saved.
+    ///
+    /// # Errors
+    ///
+    /// Returns an error if the file exists but cannot be read or parsed.
+    pub fn load(dir: &Path, session_id: &str) -> Result<Option<Self>, ResumeError> {
+        let path = Self::path_in(dir, session_id);
+        match std::fs::read(&path) {
+            Ok(bytes) => Ok(Some(serde_json::from_slice(&bytes)?)),
+            Err(e) if e.kind() == std::io::ErrorKind::NotFound => Ok(None),
+            Err(e) => Err(e.into()),
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn context() -> ResumeContext {
+        ResumeContext {
+            session_id: "abc-123".to_string(),
+            task: Some("Fix the login bug".to_string()),
+            name: Some("brisk-otter".to_string()),
+            stats: SessionStats {
+                tool_calls: 7,
+                approvals: 5,
+                denials: 2,
+                turns: 4,
+                escalations: 1,
+                ..SessionStats::default()
+            },
+            turns: 4,
+            cost_micros: 120_000,
+            events: vec![ClaudeEvent::MessageStop],
+            saved_at: Utc::now(),
+        }
+    }
+
+    #[test]
+    fn test_save_and_load_round_trip() {
+        let dir = tempfile::tempdir().unwrap();
+        let path = context().save(dir.path()).unwrap();
+        assert_eq!(path, dir.path().join("abc-123.json"));
+
+        let loaded = ResumeContext::load(dir.path(), "abc-123").unwrap().unwrap();
+        assert_eq!(loaded.task.as_deref(), Some("Fix the login bug"));
+        assert_eq!(loaded.stats.tool_calls, 7);
+        assert_eq!(loaded.cost_micros, 120_000);
+        assert_eq!(loaded.events.len(), 1);
+    }
+
+    #[test]
+    fn test_load_missing_is_none() {
+        let dir = tempfile::tempdir().unwrap();
+        assert!(ResumeContext::load(dir.path(), "nope").unwrap().is_none());
+    }
+
+    #[test]
+    fn test_path_stays_in_dir() {
+        let path = ResumeContext::path_in(Path::new("/data"), "../../etc/passwd");
+        assert_eq!(path, Path::new("/data/______etc_passwd.json"));
+    }
+}
@@ -0,0 +1,372 @@
+//! Rewriting Bash commands into safer forms.
+//!
+//! A [`RewriteRule`] matches the simple commands of a Bash call that run a
+//! program, optionally with a given subcommand, and rewrites them in place:
+//! it can run another program instead, add arguments or remove them. This
+//! turns `rm` into `trash-put`, adds `--dry-run` to `terraform apply` or
+//! drops `--no-verify` from `git commit`, where a denial would only make
+//! Claude try again. Only the words a rule changes are touched; quoting,
+//! redirections and the rest of the command line stay as they were. A
+//! command line that cannot be split but may run a rewritten program is
+//! escalated instead.
+
+use std::ops::Range;
+
+use serde_json::Value;
+
+use super::sandbox::{command_binaries, quote};
+use super::shell::{split_commands, ShellWord};
+use crate::config::RewriteRuleConfig;
+
+/// Prefix of the reasons given for commands that could not be rewritten.
+pub const REWRITE_REASON: &str = "Rewrite";
+
+/// Wrappers that run the command in their arguments.
+const WRAPPERS: &[&str] = &[
+    "sudo", "doas", "env", "exec", "command", "nohup", "nice", "time", "xargs",
+];
+
+/// Error type for compiling rewrite rules.
+#[derive(thiserror::Error, Debug, Clone, PartialEq, Eq)]
+pub enum RewriteRuleError {
+    /// The rule does not name the program it applies to.
+    #[error("Rewrite rule '{0}' names no program")]
+    NoProgram(String),
+    /// The rule would leave every command as it is.
+    #[error("Rewrite rule '{0}' changes nothing")]
+    NoChanges(String),
+}
+
+/// Outcome of rewriting a Bash call.
+#[derive(Debug, Clone, PartialEq)]
+pub enum CommandRewrite {
+    /// No rule changed the call.
+    Unchanged,
+    /// The call as rewritten, with the names of the rules that changed it.
+    Rewritten {
+        /// The rewritten tool input.
+        input: Value,
+        /// Names of the rules that changed the command, in order.
+        rules: Vec<String>,
+    },
+    /// The call may run a program a rule applies to, but cannot be rewritten.
+    Refused(String),
+}
+
+/// A compiled [`RewriteRuleConfig`].
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct RewriteRule {
+    name: String,
+    program: String,
+    subcommand: Option<String>,
+    replace_program: Option<String>,
+    add_args: Vec<String>,
+    remove_args: Vec<String>,
+}
+
+impl RewriteRule {
+    /// Compile a configured rule.
+    ///
+    /// # Errors
+    ///
+    /// Returns an error if the rule names no program or changes nothing.
+    pub fn from_config(config: &RewriteRuleConfig) -> Result<Self, RewriteRuleError> {
+        if config.program.is_empty() {
+            return Err(RewriteRuleError::NoProgram(config.name.clone()));
+        }
+        if config.replace_program.is_none()
+            && config.add_args.is_empty()
+            && config.remove_args.is_empty()
+        {
+            return Err(RewriteRuleError::NoChanges(config.name.clone()));
+        }
+        Ok(Self {
+            name: config.name.clone(),
+            program: config.program.clone(),
+            subcommand: config.subcommand.clone(),
+            replace_program: config.replace_program.clone(),
+            add_args: config.add_args.clone(),
+            remove_args: config.remove_args.clone(),
+        })
+    }
+
+    /// Name of the rule.
+    #[must_use]
+    pub fn name(&self) -> &str {
+        &self.name
+    }
+
+    /// Whether a command line that cannot be split may run a command the
+    /// rule applies to.
+    fn may_apply(&self, command: &str) -> bool {
+        command_binaries(command).any(|binary| binary == self.program)
+            && self
+                .subcommand
+                .as_deref()
+                .is_none_or(|subcommand| command.split_whitespace().any(|word| word == subcommand))
+    }
+
+    /// Edits rewriting the command whose program is `words[0]`, or `None`
+    /// if the rule does not apply to it.
+    fn edits(&self, words: &[ShellWord], line: &str) -> Option<Vec<(Range<usize>, String)>> {
+        let program = &words[0];
+        if program.text.rsplit('/').next() != Some(self.program.as_str()) {
+            return None;
+        }
+        // Options after `--` are operands
+        let args = words[1..]
+            .iter()
+            .position(|word| word.text == "--" && !word.quoted)
+            .map_or(&words[1..], |end| &words[1..=end]);
+        let subcommand = args.iter().position(|word| !word.text.starts_with('-'));
+        if let Some(ref expected) = self.subcommand {
+            if subcommand.is_none_or(|index| args[index].text != *expected) {
+                return None;
+            }
+        }
+
+        let mut edits = Vec::new();
+        if let Some(ref replacement) = self.replace_program {
+            edits.push((program.span.clone(), quote(replacement)));
+        }
+        let missing: Vec<String> = self
+            .add_args
+            .iter()
+            .filter(|arg| !args.iter().any(|word| word.text == **arg))
+            .map(|arg| quote(arg))
+            .collect();
+        if !missing.is_empty() {
+            let after = match (self.subcommand.is_some(), subcommand) {
+                (true, Some(index)) => &args[index],
+                _ => program,
+            };
+            let at = after.span.end;
+            edits.push((at..at, format!(" {}", missing.join(" "))));
+        }
+        for (index, word) in args.iter().enumerate() {
+            if Some(index) == subcommand && self.subcommand.is_some() {
+                continue;
+            }
+            if self.remove_args.contains(&word.text) {
+                // Take the whitespace before the word with it
+                let start = line[..word.span.start].trim_end_matches([' ', '\t']).len();
+                edits.push((start..word.span.end, String::new()));
+            }
+        }
+        Some(edits)
+    }
+}
+
+/// Rewrites Bash commands with configured rules.
+#[derive(Debug, Clone, Default)]
+pub struct CommandRewriter {
+    rules: Vec<RewriteRule>,
+}
+
+impl CommandRewriter {
+    /// Create a rewriter without rules.
+    #[must_use]
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Add a rule; the first rule that applies to a command rewrites it.
+    pub fn add_rule(&mut self, rule: RewriteRule) {
+        self.rules.push(rule);
+    }
+
+    /// Get the rules.
+    #[must_use]
+    pub fn rules(&self) -> &[RewriteRule] {
+        &self.rules
+    }
+
+    /// Whether the rewriter has no rules.
+    #[must_use]
+    pub fn is_empty(&self) -> bool {
+        self.rules.is_empty()
+    }
+
+    /// Rewrite the commands of a Bash call.
+    #[must_use]
+    pub fn rewrite_bash(&self, tool_input: &Value) -> CommandRewrite {
+        if self.rules.is_empty() {
+            return CommandRewrite::Unchanged;
+        }
+        let Some(command) = tool_input.get("command").and_then(Value::as_str) else {
+            return CommandRewrite::Unchanged;
+        };
+        let Some(commands) = split_commands(command) else {
+            return match self.rules.iter().find(|rule| rule.may_apply(command)) {
+                Some(rule) => CommandRewrite::Refused(format!(
+                    "{REWRITE_REASON} '{}': `{}` in a command line that cannot be split",
+                    rule.name, rule.program
+                )),
+                None => CommandRewrite::Unchanged,
+            };
+        };
+
+        let mut edits = Vec::new();
+        let mut rules = Vec::new();
+        for simple in &commands {
+            let Some(start) = program_index(&simple.words) else {
+                continue;
+            };
+            let words = &simple.words[start..];
+            let Some((rule, rule_edits)) = self
+                .rules
+                .iter()
+                .find_map(|rule| Some((rule, rule.edits(words, command)?)))
+            else {
+                continue;
+            };
+            if !rule_edits.is_empty() {
+                edits.extend(rule_edits);
+                if !rules.contains(&rule.name) {
+                    rules.push(rule.name.clone());
+                }
+            }
+        }
+        if edits.is_empty() {
+            return CommandRewrite::Unchanged;
+        }
+
+        // Removals starting where an insertion is made are applied first
+        edits.sort_by_key(|(range, _)| (range.start, range.end));
+        let mut rewritten_command = command.to_string();
+        for (range, replacement) in edits.into_iter().rev() {
+            rewritten_command.replace_range(range, &replacement);
+        }
+        tracing::debug!(command = %command, rewritten = %rewritten_command, ?rules, "Rewrote Bash command");
+        let mut input = tool_input.clone();
+        input["command"] = Value::String(rewritten_command);
+        CommandRewrite::Rewritten { input, rules }
+    }
+}
+
+/// Index of the word naming the program a simple command runs, after
+/// `VAR=value` prefixes and wrappers.
+fn program_index(words: &[ShellWord]) -> Option<usize> {
+    words.iter().position(|word| {
+        let text = word.text.as_str();
+        (word.quoted || !text.contains('=')) && !text.starts_with('-') && !WRAPPERS.contains(&text)
+    })
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use serde_json::json;
+
+    fn rewriter(configs: &[RewriteRuleConfig]) -> CommandRewriter {
+        let mut rewriter = CommandRewriter::new();
+        for config in configs {
+            rewriter.add_rule(RewriteRule::from_config(config).unwrap());
+        }
+        rewriter
+    }
+
+    fn rewrite(rewriter: &CommandRewriter, command: &str) -> Option<String> {
+        match rewriter.rewrite_bash(&json!({"command": command})) {
+            CommandRewrite::Rewritten { input, .. } => Some(input["command"].to_string()),
+            CommandRewrite::Unchanged => None,
+            CommandRewrite::Refused(reason) => panic!("refused: {reason}"),
+        }
+        .map(|command| serde_json::from_str::<String>(&command).unwrap())
+    }
+
+    fn defaults() -> CommandRewriter {
+        rewriter(&[
+            RewriteRuleConfig {
+                name: "trash".to_string(),
+                program: "rm".to_string(),
+                replace_program: Some("trash-put".to_string()),
+                ..RewriteRuleConfig::default()
+            },
+            RewriteRuleConfig {
+                name: "plan first".to_string(),
+                program: "terraform".to_string(),
+                subcommand: Some("apply".to_string()),
+                add_args: vec!["--dry-run".to_string()],
+                ..RewriteRuleConfig::default()
+            },
+            RewriteRuleConfig {
+                name: "keep hooks".to_string(),
+                program: "git".to_string(),
+                subcommand: Some("commit".to_string()),
+                remove_args: vec!["--no-verify".to_string(), "-n".to_string()],
+                ..RewriteRuleConfig::default()
+            },
+        ])
+    }
+
+    #[test]
+    fn test_rewrites_matching_commands_in_place() {
+        let rewriter = defaults();
+        assert_eq!(
+            rewrite(&rewriter, "rm -rf 'build dir' 2>/dev/null && ls").as_deref(),
+            Some("trash-put -rf 'build dir' 2>/dev/null && ls")
+        );
+        assert_eq!(
+            rewrite(
+                &rewriter,
+                "cd infra && TF_LOG=debug terraform apply -auto-approve"
+            )
+            .as_deref(),
+            Some("cd infra && TF_LOG=debug terraform apply --dry-run -auto-approve")
+        );
+        assert_eq!(
+            rewrite(&rewriter, "git commit --no-verify -m \"wip: skip\" -n").as_deref(),
+            Some("git commit -m \"wip: skip\"")
+        );
+        // Already in the rewritten form, or not the subcommand
+        assert_eq!(rewrite(&rewriter, "terraform apply --dry-run"), None);
+        assert_eq!(rewrite(&rewriter, "terraform plan"), None);
+        assert_eq!(rewrite(&rewriter, "git log -n 3"), None);
+        assert_eq!(rewrite(&rewriter, "echo rm"), None);
+        // Operands after `--` are kept
+        assert_eq!(rewrite(&rewriter, "git commit -m x -- --no-verify"), None);
+    }
+
+    #[test]
+    fn test_unsplittable_command_lines_are_refused() {
+        let rewriter = defaults();
+        let refused = rewriter.rewrite_bash(&json!({"command": "rm -f $(cat list)"}));
+        assert!(
+            matches!(refused, CommandRewrite::Refused(ref reason) if reason.starts_with(REWRITE_REASON)),
+            "{refused:?}"
+        );
+        assert_eq!(
+            rewriter.rewrite_bash(&json!({"command": "echo $(date)"})),
+            CommandRewrite::Unchanged
+        );
+        assert_eq!(
+            rewriter.rewrite_bash(&json!({"command": "terraform plan $(cat vars)"})),
+            CommandRewrite::Unchanged
+        );
+    }
+
+    #[test]
+    fn test_invalid_rules_are_rejected() {
+        let nothing = RewriteRuleConfig {
+            name: "noop".to_string(),
+            program: "rm".to_string(),
+            ..RewriteRuleConfig::default()
+        };
+        assert_eq!(
+            RewriteRule::from_config(&nothing),
+            Err(RewriteRuleError::NoChanges("noop".to_string()))
+        );
+        let unnamed = RewriteRuleConfig {
+            add_args: vec!["-i".to_string()],
+            ..nothing
+        };
+        assert!(matches!(
+            RewriteRule::from_config(&RewriteRuleConfig {
+                program: String::new(),
+                ..unnamed
+            }),
+            Err(RewriteRuleError::NoProgram(_))
+        ));
+    }
+}
@@ -0,0 +1,326 @@
+//! Cumulative risk score of a session's recent tool calls.
+//!
+//! Every tool call carries some risk, most of it too small for a per-call
+//! rule to act on: one `curl` is fine, a burst of them while writing files
+//! outside the source tree is worth a second look. [`RiskScorer`] sums the
+//! weight of each call's [`RiskCategory`] over a rolling window and
+//! escalates the call that takes the sum over the threshold in
+//! [`RiskConfig`].
+
+use std::collections::VecDeque;
+use std::path::{Path, PathBuf};
+use std::time::Duration;
+
+use tokio::time::Instant;
+
+use super::{command_binaries, normalize, ToolClass, NETWORK_BINARIES};
+use crate::cli::input_paths;
+use crate::config::{RiskConfig, RiskWeights};
+
+/// Category of a tool call's risk.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum RiskCategory {
+    /// A Bash command running a network client.
+    Network,
+    /// Any other Bash command.
+    Shell,
+    /// A file write or edit outside the source directories.
+    WriteOutsideSource,
+    /// A file write or edit inside the source directories.
+    Write,
+    /// An MCP tool call.
+    Mcp,
+    /// A read-only tool call.
+    Read,
+    /// A call to any other tool.
+    Other,
+}
+
+impl RiskCategory {
+    /// Category of a tool call; file paths are resolved against `cwd` and
+    /// compared with the `source_dirs` under it.
+    #[must_use]
+    pub fn classify(
+        tool_name: &str,
+        tool_input: &serde_json::Value,
+        cwd: Option<&Path>,
+        source_dirs: &[String],
+    ) -> Self {
+        match tool_name {
+            "Bash" | "bash" => {
+                let command = tool_input
+                    .get("command")
+                    .and_then(serde_json::Value::as_str)
+                    .unwrap_or_default();
+                if command_binaries(command).any(|binary| NETWORK_BINARIES.contains(&binary)) {
+                    Self::Network
+                } else {
+                    Self::Shell
+                }
+            }
+            "Write" | "Edit" | "MultiEdit" | "NotebookEdit" | "write" | "edit" => {
+                let mut paths = input_paths(tool_input).peekable();
+                if paths.peek().is_some()
+                    && paths.all(|path| is_source(Path::new(path), cwd, source_dirs))
+                {
+                    Self::Write
+                } else {
+                    Self::WriteOutsideSource
+                }
+            }
+            _ => match ToolClass::of(tool_name) {
+                ToolClass::ReadOnly => Self::Read,
+                ToolClass::Mcp => Self::Mcp,
+                ToolClass::Mutating | ToolClass::Unknown => Self::Other,
+            },
+        }
+    }
+
+    /// Risk of this category of call.
+    #[must_use]
+    pub fn weight(self, weights: &RiskWeights) -> u32 {
+        match self {
+            Self::Network => weights.network,
+            Self::Shell => weights.shell,
+            Self::WriteOutsideSource => weights.write_outside_source,
+            Self::Write => weights.write,
+            Self::Mcp => weights.mcp,
+            Self::Read => weights.read,
+            Self::Other => weights.other,
+        }
+    }
+
+    /// Stable name used in output.
+    #[must_use]
+    pub fn as_str(self) -> &'static str {
+        match self {
+            Self::Network => "network",
+            Self::Shell => "shell",
+            Self::WriteOutsideSource => "write outside source",
+            Self::Write => "write",
+            Self::Mcp => "mcp",
+            Self::Read => "read",
+            Self::Other => "other",
+        }
+    }
+}
+
+/// Whether `path` is under one of `source_dirs` of `cwd`.
+fn is_source(path: &Path, cwd: Option<&Path>, source_dirs: &[String]) -> bool {
+    let (path, root) = match cwd {
+        Some(cwd) => (normalize(&cwd.join(path)), normalize(cwd)),
+        None if path.is_relative() => (normalize(path), PathBuf::new()),
+        None => return false,
+    };
+    source_dirs
+        .iter()
+        .any(|dir| path.starts_with(root.join(dir)))
+}
+
+/// Rolling sum of the risk of a session's tool calls.
+#[derive(Debug, Clone)]
+pub struct RiskScorer {
+    config: RiskConfig,
+    calls: VecDeque<(Instant, u32)>,
+    tripped: bool,
+}
+
+impl Default for RiskScorer {
+    fn default() -> Self {
+        Self::new(RiskConfig::default())
+    }
+}
+
+impl RiskScorer {
+    /// Create an empty score with the given weights and threshold.
+    #[must_use]
+    pub fn new(config: RiskConfig) -> Self {
+        Self {
+            config,
+            calls: VecDeque::new(),
+            tripped: false,
+        }
+    }
+
+    /// The weights and threshold in use.
+    #[must_use]
+    pub fn config(&self) -> &RiskConfig {
+        &self.config
+    }
+
+    /// Replace the weights and threshold, keeping recorded calls.
+    pub fn set_config(&mut self, config: RiskConfig) {
+        self.config = config;
+    }
+
+    /// Summed risk of the calls within the window before `now`.
+    #[must_use]
+    pub fn score(&self, now: Instant) -> u32 {
+        let window = self.window();
+        self.calls
+            .iter()
+            .filter(|(at, _)| now.saturating_duration_since(*at) <= window)
+            .fold(0, |score, (_, weight)| score.saturating_add(*weight))
+    }
+
+    /// Record a call and check the threshold; the escalation reason when
+    /// the call takes the score over it.
+    ///
+    /// Escalates once per crossing, re-arming when the score falls back to
+    /// the threshold. Never escalates when disabled.
+    pub fn record(&mut self, category: RiskCategory, now: Instant) -> Option<String> {
+        let window = self.window();
+        while self
+            .calls
+            .front()
+            .is_some_and(|(at, _)| now.saturating_duration_since(*at) > window)
+        {
+            self.calls.pop_front();
+        }
+        let weight = category.weight(&self.config.weights);
+        if weight > 0 {
+            self.calls.push_back((now, weight));
+        }
+
+        let score = self.score(now);
+        if score <= self.config.threshold {
+            self.tripped = false;
+            return None;
+        }
+        if !self.config.enabled || weight == 0 || std::mem::replace(&mut self.tripped, true) {
+            return None;
+        }
+        Some(format!(
+            "Session risk score {score} in the last {} is over the threshold {} \
+             ({} call, +{weight})",
+            describe_window(window),
+            self.config.threshold,
+            category.as_str()
+        ))
+    }
+
+    fn window(&self) -> Duration {
+        Duration::from_secs(self.config.window_secs.max(1))
+    }
+}
+
+fn describe_window(window: Duration) -> String {
+    let secs = window.as_secs();
+    if secs.is_multiple_of(60) {
+        format!("{} minutes", secs / 60)
+    } else {
+        format!("{secs} seconds")
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use serde_json::json;
+
+    fn classify(tool_name: &str, input: &serde_json::Value) -> RiskCategory {
+        RiskCategory::classify(
+            tool_name,
+            input,
+            Some(Path::new("/repo")),
+            &["src".to_string()],
+        )
+    }
+
+    #[test]
+    fn test_classify() {
+        assert_eq!(
+            classify(
+                "Bash",
+                &json!({"command": "ls && curl https://example.com"})
+            ),
+            RiskCategory::Network
+        );
+        assert_eq!(
+            classify("Bash", &json!({"command": "cargo test"})),
+            RiskCategory::Shell
+        );
+        assert_eq!(
+            classify("Write", &json!({"file_path": "src/lib.rs"})),
+            RiskCategory::Write
+        );
+        assert_eq!(
+            classify("Edit", &json!({"file_path": "/repo/src/../build.rs"})),
+            RiskCategory::WriteOutsideSource
+        );
+        assert_eq!(
+            classify("Write", &json!({"file_path": "/etc/hosts"})),
+            RiskCategory::WriteOutsideSource
+        );
+        assert_eq!(
+            classify("Read", &json!({"file_path": "/etc/hosts"})),
+            RiskCategory::Read
+        );
+        assert_eq!(
+            classify("mcp__github__create_issue", &json!({})),
+            RiskCategory::Mcp
+        );
+        assert_eq!(classify("WebFetch", &json!({})), RiskCategory::Other);
+    }
+
+    fn enabled(threshold: u32) -> RiskScorer {
+        RiskScorer::new(RiskConfig {
+            enabled: true,
+            threshold,
+            ..RiskConfig::default()
+        })
+    }
+
+    #[test]
+    fn test_escalates_once_over_threshold() {
+        let mut scorer = enabled(6);
+        let now = Instant::now();
+
+        assert_eq!(scorer.record(RiskCategory::Network, now), None);
+        assert_eq!(scorer.record(RiskCategory::Read, now), None);
+        assert_eq!(scorer.record(RiskCategory::Network, now), None);
+        assert_eq!(scorer.score(now), 6);
+        assert_eq!(
+            scorer
+                .record(RiskCategory::WriteOutsideSource, now)
+                .as_deref(),
+            Some(
+                "Session risk score 8 in the last 5 minutes is over the threshold 6 \
+                 (write outside source call, +2)"
+            )
+        );
+        // Escalated once per crossing
+        assert_eq!(scorer.record(RiskCategory::Network, now), None);
+        assert_eq!(scorer.score(now), 11);
+    }
+
+    #[test]
+    fn test_window_rolls_and_rearms() {
+        let mut scorer = enabled(4);
+        let start = Instant::now();
+        scorer.record(RiskCategory::Network, start);
+        assert!(scorer
+            .record(RiskCategory::Network, start + Duration::from_secs(10))
+            .is_some());
+
+        // The first call leaves the window; the score falls back and re-arms
+        let later = start + Duration::from_secs(305);
+        assert_eq!(scorer.record(RiskCategory::Shell, later), None);
+        assert_eq!(scorer.score(later), 4);
+        assert!(scorer.record(RiskCategory::Network, later).is_some());
+
+        let much_later = start + Duration::from_hours(1);
+        assert_eq!(scorer.score(much_later), 0);
+    }
+
+    #[test]
+    fn test_disabled_only_tracks() {
+        let mut scorer = RiskScorer::new(RiskConfig {
+            threshold: 1,
+            ..RiskConfig::default()
+        });
+        let now = Instant::now();
+        assert_eq!(scorer.record(RiskCategory::Network, now), None);
+        assert_eq!(scorer.score(now), 3);
+    }
+}
@@ -0,0 +1,53 @@
+//! Which policy rule fired on a tool call, and how the call was decided.
+//!
+//! A rule fires when it denies or escalates a call. Each firing is audited
+//! with the rule and with who made the final decision, so that rules can be
+//! judged by their outcomes: a rule whose escalations are nearly always
+//! allowed costs a round trip to the supervisor for nothing.
+
+use chrono::{DateTime, Utc};
+
+use crate::audit::Decision;
+use crate::supervisor::DecisionSource;
+
+/// Rule name of escalations and denials by the blast radius.
+pub const BLAST_RADIUS_RULE: &str = "blast radius";
+
+/// Rule name of escalations of binaries the project has not run before.
+pub const NOVEL_BINARY_RULE: &str = "novel binary";
+
+/// Rule name of escalations by the session's cumulative risk score.
+pub const RISK_SCORE_RULE: &str = "risk score";
+
+/// A policy rule that denied or escalated a tool call.
+#[derive(Debug, Clone, PartialEq)]
+pub struct RuleFiring {
+    /// Name of the rule.
+    pub rule: String,
+    /// Tool use the rule fired on.
+    pub tool_use_id: String,
+    /// Name the tool was called by.
+    pub tool_name: String,
+    /// Input the tool was called with.
+    pub tool_input: serde_json::Value,
+    /// Reason the rule gave.
+    pub reason: String,
+    /// Final decision; [`Decision::Escalate`] while an escalation is open.
+    pub decision: Decision,
+    /// Who made the final decision, once it is made.
+    pub decided_by: Option<DecisionSource>,
+    /// When the rule fired.
+    pub fired_at: DateTime<Utc>,
+}
+
+impl RuleFiring {
+    /// Record the final decision on an escalated call.
+    pub fn resolve(&mut self, allowed: bool, source: DecisionSource) {
+        self.decision = if allowed {
+            Decision::Allow
+        } else {
+            Decision::Deny
+        };
+        self.decided_by = Some(source);
+    }
+}
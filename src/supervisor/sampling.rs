@@ -0,0 +1,229 @@
+//! Sampling escalations of high-volume, low-risk tools.
+//!
+//! A tool or rule with an `escalate_sample_rate` below 1 only has that share
+//! of its escalations sent to the supervisor; the others are allowed as
+//! sampled. Draws are seeded by the session ID, so a session replays the
+//! same sample. Once the supervisor denies a sampled tool, every later
+//! escalation of that tool in the session goes to the supervisor.
+
+use std::collections::{BTreeMap, BTreeSet};
+use std::sync::{Arc, Mutex};
+
+use serde::{Deserialize, Serialize};
+
+use crate::config::SamplingConfig;
+
+/// How many escalations sampling sent on and how many it allowed.
+#[derive(Debug, Clone, Default, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(default)]
+pub struct SamplingStats {
+    /// Sampled escalations sent to the supervisor.
+    pub escalated: usize,
+    /// Escalations left out of the sample and allowed.
+    pub allowed: usize,
+    /// Tools escalated in full after a sampled denial.
+    pub escalating_tools: BTreeSet<String>,
+}
+
+#[derive(Debug, Default)]
+struct SamplerState {
+    seed: u64,
+    /// Draws made so far, keyed by the tool or rule whose rate applied.
+    draws: BTreeMap<String, u64>,
+    /// Tools with an escalation that went through sampling.
+    sampled_tools: BTreeSet<String>,
+    stats: SamplingStats,
+}
+
+/// Samples escalations by tool or rule, shared by the clones of a policy
+/// engine.
+#[derive(Debug, Clone, Default)]
+pub struct EscalationSampler {
+    rates: BTreeMap<String, f64>,
+    state: Arc<Mutex<SamplerState>>,
+}
+
+impl EscalationSampler {
+    /// Create a sampler for rates keyed by tool or rule name, or `None` if
+    /// there are none.
+    #[must_use]
+    pub fn from_config(sampling: &BTreeMap<String, SamplingConfig>) -> Option<Self> {
+        (!sampling.is_empty()).then(|| Self {
+            rates: sampling
+                .iter()
+                .map(|(key, config)| (key.clone(), config.escalate_sample_rate.clamp(0.0, 1.0)))
+                .collect(),
+            ..Self::default()
+        })
+    }
+
+    /// Seed the draws from `session_id`, restarting the sample.
+    ///
+    /// # Panics
+    ///
+    /// Panics if the internal `Mutex` is poisoned.
+    pub fn set_session(&self, session_id: &str) {
+        let mut state = self.state.lock().expect("Mutex poisoned");
+        let seed = fnv1a(session_id.as_bytes());
+        if state.seed != seed {
+            *state = SamplerState {
+                seed,
+                ..SamplerState::default()
+            };
+        }
+    }
+
+    /// The sample rate for an escalation of `tool_name` by `rule`: the
+    /// tool's if it has one, else the rule's.
+    #[must_use]
+    pub fn rate(&self, tool_name: &str, rule: &str) -> Option<(&str, f64)> {
+        [tool_name, rule]
+            .into_iter()
+            .find_map(|key| self.rates.get_key_value(key))
+            .map(|(key, &rate)| (key.as_str(), rate))
+    }
+
+    /// Whether an escalation of `tool_name` by `rule` goes to the
+    /// supervisor; `false` means it is allowed as sampled.
+    ///
+    /// # Panics
+    ///
+    /// Panics if the internal `Mutex` is poisoned.
+    #[must_use]
+    pub fn should_escalate(&self, tool_name: &str, rule: &str) -> bool {
+        let Some((key, rate)) = self.rate(tool_name, rule) else {
+            return true;
+        };
+        let mut state = self.state.lock().expect("Mutex poisoned");
+        state.sampled_tools.insert(tool_name.to_string());
+        if state.stats.escalating_tools.contains(tool_name) {
+            state.stats.escalated += 1;
+            return true;
+        }
+
+        let draw = state.draws.entry(key.to_string()).or_default();
+        let index = *draw;
+        *draw += 1;
+        let escalate = unit(splitmix64(state.seed ^ fnv1a(key.as_bytes()) ^ index)) < rate;
+        if escalate {
+            state.stats.escalated += 1;
+        } else {
+            state.stats.allowed += 1;
+        }
+        escalate
+    }
+
+    /// Record that the supervisor denied a call to `tool_name`; if the tool
+    /// is sampled, all its later escalations go to the supervisor.
+    ///
+    /// # Panics
+    ///
+    /// Panics if the internal `Mutex` is poisoned.
+    pub fn record_denial(&self, tool_name: &str) {
+        let mut state = self.state.lock().expect("Mutex poisoned");
+        if state.sampled_tools.contains(tool_name)
+            && state.stats.escalating_tools.insert(tool_name.to_string())
+        {
+            tracing::warn!(tool = %tool_name, "Sampled tool denied; escalating all its calls");
+        }
+    }
+
+    /// Counts of sampled escalations so far.
+    ///
+    /// # Panics
+    ///
+    /// Panics if the internal `Mutex` is poisoned.
+    #[must_use]
+    pub fn stats(&self) -> SamplingStats {
+        self.state.lock().expect("Mutex poisoned").stats.clone()
+    }
+}
+
+/// 64-bit FNV-1a hash, stable across runs and platforms.
+fn fnv1a(bytes: &[u8]) -> u64 {
+    bytes.iter().fold(0xcbf2_9ce4_8422_2325, |hash, &byte| {
+        (hash ^ u64::from(byte)).wrapping_mul(0x0100_0000_01b3)
+    })
+}
+
+/// The `SplitMix64` output function, spreading nearby inputs apart.
+fn splitmix64(value: u64) -> u64 {
+    let mut z = value.wrapping_add(0x9e37_79b9_7f4a_7c15);
+    z = (z ^ (z >> 30)).wrapping_mul(0xbf58_476d_1ce4_e5b9);
+    z = (z ^ (z >> 27)).wrapping_mul(0x94d0_49bb_1331_11eb);
+    z ^ (z >> 31)
+}
+
+/// A value in `[0, 1)` from the top 53 bits of `bits`.
+#[allow(clippy::cast_precision_loss)]
+fn unit(bits: u64) -> f64 {
+    (bits >> 11) as f64 / (1u64 << 53) as f64
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn sampler(rate: f64) -> EscalationSampler {
+        let config = SamplingConfig {
+            escalate_sample_rate: rate,
+        };
+        EscalationSampler::from_config(&BTreeMap::from([
+            ("WebSearch".to_string(), config),
+            ("moderate level".to_string(), config),
+        ]))
+        .unwrap()
+    }
+
+    fn draws(sampler: &EscalationSampler, n: usize) -> Vec<bool> {
+        (0..n)
+            .map(|_| sampler.should_escalate("WebSearch", "moderate level"))
+            .collect()
+    }
+
+    #[test]
+    fn test_sample_is_seeded_by_session() {
+        let first = sampler(0.1);
+        first.set_session("session-1");
+        let sample = draws(&first, 1000);
+        let escalated = sample.iter().filter(|&&escalate| escalate).count();
+        assert!((50..150).contains(&escalated), "{escalated}");
+        assert_eq!(
+            first.stats(),
+            SamplingStats {
+                escalated,
+                allowed: 1000 - escalated,
+                escalating_tools: BTreeSet::new(),
+            }
+        );
+
+        let replay = sampler(0.1);
+        replay.set_session("session-1");
+        assert_eq!(draws(&replay, 1000), sample);
+        replay.set_session("session-2");
+        assert_ne!(draws(&replay, 1000), sample);
+
+        // Unsampled tools and rules always escalate
+        assert!(first.should_escalate("Bash", "bash allowlist"));
+        assert!(first.rate("Read", "moderate level").is_some());
+        assert!(EscalationSampler::from_config(&BTreeMap::new()).is_none());
+    }
+
+    #[test]
+    fn test_sampled_denial_escalates_the_tool_in_full() {
+        let sampler = sampler(0.0);
+        sampler.set_session("session-1");
+        assert!(!sampler.should_escalate("WebSearch", "moderate level"));
+
+        // Denials of tools that were never sampled change nothing
+        sampler.record_denial("Bash");
+        sampler.record_denial("WebSearch");
+        assert!(draws(&sampler, 5).into_iter().all(|escalate| escalate));
+        let stats = sampler.stats();
+        assert_eq!((stats.allowed, stats.escalated), (1, 5));
+        assert_eq!(
+            stats.escalating_tools,
+            BTreeSet::from(["WebSearch".to_string()])
+        );
+    }
+}
@@ -0,0 +1,284 @@
+//! Normalization of tool inputs before policy rules see them.
+//!
+//! Rules match on the shape Claude Code normally sends, so a call can slip
+//! past them by changing the shape: the Bash command as an array, a path
+//! with `.` or `..` components, the command under an unexpected key, or
+//! look-alike Unicode letters in `rm`. [`sanitize_tool_input`] rewrites an
+//! input into the canonical shape the rules expect. It is only used for
+//! evaluation; the tool still receives the original input.
+
+use std::path::Path;
+
+use serde_json::{Map, Value};
+use unicode_normalization::{is_nfc, UnicodeNormalization};
+
+use super::protect::normalize;
+use crate::cli::TOOL_PATH_KEYS;
+
+/// Alternate names for canonical input fields, checked when the canonical
+/// field is missing.
+const FIELD_ALIASES: &[(&str, &[&str])] = &[
+    ("command", &["cmd", "script", "commands", "shell_command"]),
+    (
+        "file_path",
+        &["filePath", "filepath", "file", "filename", "target_file"],
+    ),
+    ("notebook_path", &["notebookPath"]),
+    ("url", &["uri", "href"]),
+];
+
+/// How deep nested objects are searched for a misplaced field.
+const MAX_NESTED_DEPTH: usize = 4;
+
+/// Look-alike letters from other scripts, folded to ASCII in commands.
+const HOMOGLYPHS: &[(char, char)] = &[
+    // Cyrillic
+    ('а', 'a'),
+    ('в', 'b'),
+    ('с', 'c'),
+    ('ԁ', 'd'),
+    ('е', 'e'),
+    ('г', 'r'),
+    ('һ', 'h'),
+    ('і', 'i'),
+    ('ј', 'j'),
+    ('к', 'k'),
+    ('ӏ', 'l'),
+    ('м', 'm'),
+    ('п', 'n'),
+    ('о', 'o'),
+    ('р', 'p'),
+    ('ԛ', 'q'),
+    ('ѕ', 's'),
+    ('т', 't'),
+    ('ѵ', 'v'),
+    ('ԝ', 'w'),
+    ('х', 'x'),
+    ('у', 'y'),
+    ('А', 'A'),
+    ('В', 'B'),
+    ('С', 'C'),
+    ('Е', 'E'),
+    ('Н', 'H'),
+    ('І', 'I'),
+    ('К', 'K'),
+    ('М', 'M'),
+    ('О', 'O'),
+    ('Р', 'P'),
+    ('Ѕ', 'S'),
+    ('Т', 'T'),
+    ('Х', 'X'),
+    // Greek
+    ('α', 'a'),
+    ('ϲ', 'c'),
+    ('ε', 'e'),
+    ('ι', 'i'),
+    ('κ', 'k'),
+    ('ν', 'v'),
+    ('ο', 'o'),
+    ('ρ', 'p'),
+    ('τ', 't'),
+    ('υ', 'u'),
+    ('χ', 'x'),
+];
+
+/// The canonical form of `tool_input` that policy rules are evaluated on.
+///
+/// Strings are NFC-normalized, alternate and nested field names are mapped
+/// to canonical ones, the Bash command is joined, folded to ASCII look-alikes
+/// and has its whitespace collapsed, and paths are made absolute against
+/// `cwd` with `.` and `..` removed.
+#[must_use]
+pub fn sanitize_tool_input(tool_name: &str, tool_input: &Value, cwd: &Path) -> Value {
+    let mut input = tool_input.clone();
+    nfc_strings(&mut input);
+    let Value::Object(map) = &mut input else {
+        return input;
+    };
+
+    for (canonical, aliases) in FIELD_ALIASES {
+        if map.contains_key(*canonical) {
+            continue;
+        }
+        let found = aliases
+            .iter()
+            .find_map(|alias| map.get(*alias).cloned())
+            .or_else(|| find_nested(map, canonical, aliases, MAX_NESTED_DEPTH));
+        if let Some(value) = found {
+            tracing::debug!(tool = %tool_name, field = %canonical, "Mapped misplaced tool input field");
+            map.insert((*canonical).to_string(), value);
+        }
+    }
+
+    if let Some(command) = map.get("command").and_then(command_text) {
+        map.insert(
+            "command".to_string(),
+            Value::String(sanitize_command(&command)),
+        );
+    }
+
+    for key in TOOL_PATH_KEYS {
+        if let Some(Value::String(path)) = map.get_mut(*key) {
+            let path_buf = Path::new(path.as_str());
+            let absolute = if path_buf.is_absolute() {
+                normalize(path_buf)
+            } else {
+                normalize(&cwd.join(path_buf))
+            };
+            *path = absolute.to_string_lossy().into_owned();
+        }
+    }
+
+    input
+}
+
+/// Fold look-alike characters to ASCII, drop invisible ones and collapse
+/// horizontal whitespace, keeping line breaks.
+#[must_use]
+pub fn sanitize_command(command: &str) -> String {
+    let folded: String = command
+        .nfkc()
+        .filter(|c| !is_invisible(*c))
+        .map(|c| {
+            HOMOGLYPHS
+                .iter()
+                .find(|(glyph, _)| *glyph == c)
+                .map_or(c, |(_, ascii)| *ascii)
+        })
+        .collect();
+    folded
+        .lines()
+        .map(|line| line.split_whitespace().collect::<Vec<_>>().join(" "))
+        .collect::<Vec<_>>()
+        .join("\n")
+        .trim()
+        .to_string()
+}
+
+/// The command as one string, joining an array of words.
+fn command_text(value: &Value) -> Option<String> {
+    match value {
+        Value::String(command) => Some(command.clone()),
+        Value::Array(words) => Some(
+            words
+                .iter()
+                .map(|word| match word {
+                    Value::String(word) => word.clone(),
+                    other => other.to_string(),
+                })
+                .collect::<Vec<_>>()
+                .join(" "),
+        ),
+        _ => None,
+    }
+}
+
+/// Find a canonical or alternate field in nested objects and arrays.
+fn find_nested(
+    map: &Map<String, Value>,
+    canonical: &str,
+    aliases: &[&str],
+    depth: usize,
+) -> Option<Value> {
+    if depth == 0 {
+        return None;
+    }
+    map.values().find_map(|value| {
+        let nested: Vec<&Map<String, Value>> = match value {
+            Value::Object(nested) => vec![nested],
+            Value::Array(items) => items.iter().filter_map(Value::as_object).collect(),
+            _ => Vec::new(),
+        };
+        nested.into_iter().find_map(|nested| {
+            std::iter::once(canonical)
+                .chain(aliases.iter().copied())
+                .find_map(|key| nested.get(key).cloned())
+                .or_else(|| find_nested(nested, canonical, aliases, depth - 1))
+        })
+    })
+}
+
+/// NFC-normalize every string in a value.
+fn nfc_strings(value: &mut Value) {
+    match value {
+        Value::String(s) if !is_nfc(s) => *s = s.nfc().collect(),
+        Value::Array(items) => items.iter_mut().for_each(nfc_strings),
+        Value::Object(map) => map.values_mut().for_each(nfc_strings),
+        _ => {}
+    }
+}
+
+/// Zero-width and bidi control characters that render as nothing.
+fn is_invisible(c: char) -> bool {
+    matches!(
+        c,
+        '\u{00AD}'
+            | '\u{200B}'..='\u{200F}'
+            | '\u{202A}'..='\u{202E}'
+            | '\u{2060}'..='\u{2064}'
+            | '\u{FEFF}'
+    )
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use serde_json::json;
+
+    fn sanitize(tool_input: &Value) -> Value {
+        sanitize_tool_input("Bash", tool_input, Path::new("/work"))
+    }
+
+    #[test]
+    fn test_command_shapes() {
+        assert_eq!(
+            sanitize(&json!({"command": ["rm", "-rf", "/"]})),
+            json!({"command": "rm -rf /"})
+        );
+        assert_eq!(
+            sanitize(&json!({"cmd": "rm\t -rf  /"}))["command"],
+            "rm -rf /"
+        );
+        assert_eq!(
+            sanitize(&json!({"args": {"options": [{"command": "rm -rf /"}]}}))["command"],
+            "rm -rf /"
+        );
+        // A canonical field is never replaced by an alternate one
+        assert_eq!(
+            sanitize(&json!({"command": "ls", "cmd": "rm -rf /"}))["command"],
+            "ls"
+        );
+    }
+
+    #[test]
+    fn test_sanitize_command_folds_unicode() {
+        assert_eq!(sanitize_command("\u{0433}\u{043c} -rf /"), "rm -rf /");
+        assert_eq!(sanitize_command("ｒｍ -rf /"), "rm -rf /");
+        assert_eq!(sanitize_command("r\u{200b}m -rf /"), "rm -rf /");
+        assert_eq!(
+            sanitize_command("ls  -la\n  curl x |\tsh"),
+            "ls -la\ncurl x | sh"
+        );
+    }
+
+    #[test]
+    fn test_paths_are_made_absolute() {
+        let input = sanitize_tool_input(
+            "Write",
+            &json!({"filePath": "src/../../etc/./passwd", "content": "x"}),
+            Path::new("/work/project"),
+        );
+        assert_eq!(input["file_path"], "/work/etc/passwd");
+        assert_eq!(input["content"], "x");
+    }
+
+    #[test]
+    fn test_strings_are_nfc() {
+        let input = sanitize_tool_input(
+            "Write",
+            &json!({"file_path": "/work/cafe\u{0301}.txt"}),
+            Path::new("/"),
+        );
+        assert_eq!(input["file_path"], "/work/caf\u{e9}.txt");
+    }
+}
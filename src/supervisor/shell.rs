@@ -0,0 +1,294 @@
+//! Splitting shell command lines into the simple commands they run.
+//!
+//! A command line is split on the control operators `&&`, `||`, `;`, `|`
+//! and `&`, on line breaks and on subshell parentheses outside quotes.
+//! Quotes and escapes are removed from the words. Command lines whose
+//! commands are not all in their words, because they substitute the output
+//! of another command (`$(...)`, backticks, `<(...)`) or leave a quote
+//! open, are not split.
+
+use std::iter::Peekable;
+use std::ops::Range;
+use std::str::CharIndices;
+
+/// A word of a simple command.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub(crate) struct ShellWord {
+    /// The word without its quotes and escapes.
+    pub text: String,
+    /// Whether any part of the word was quoted or escaped.
+    pub quoted: bool,
+    /// Byte range of the word, quotes included, in the line.
+    pub span: Range<usize>,
+}
+
+/// A simple command of a command line.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub(crate) struct ShellCommand {
+    /// Words of the command, without redirections.
+    pub words: Vec<ShellWord>,
+    /// Byte range of the command, redirections included, in the line.
+    pub span: Range<usize>,
+    /// Whether the command redirects input or output.
+    pub redirects: bool,
+    /// Whether the command writes output to a file other than `/dev/null`.
+    pub writes_file: bool,
+}
+
+impl ShellCommand {
+    /// Text of the words.
+    pub fn texts(&self) -> impl Iterator<Item = &str> {
+        self.words.iter().map(|word| word.text.as_str())
+    }
+}
+
+/// Where the next word of a command goes.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+enum Redirect {
+    /// The file output is written to.
+    Output,
+    /// A file descriptor or the file input is read from.
+    Other,
+}
+
+/// A simple command being read.
+#[derive(Debug, Default)]
+struct Builder {
+    commands: Vec<ShellCommand>,
+    words: Vec<ShellWord>,
+    word: String,
+    quoted: bool,
+    word_span: Option<Range<usize>>,
+    redirect: Option<Redirect>,
+    redirects: bool,
+    writes_file: bool,
+    span: Option<Range<usize>>,
+}
+
+impl Builder {
+    /// Extend the command's span over the bytes up to `end`, from `start`.
+    fn cover(&mut self, start: usize, end: usize) {
+        let span = self.span.get_or_insert(start..end);
+        span.end = end;
+    }
+
+    /// Extend the word's span over the bytes up to `end`, from `start`.
+    fn cover_word(&mut self, start: usize, end: usize) {
+        let span = self.word_span.get_or_insert(start..end);
+        span.end = end;
+    }
+
+    /// Finish the word being read: the target of a pending redirection, or
+    /// the next word of the command.
+    fn end_word(&mut self) {
+        let span = self.word_span.take();
+        if self.word.is_empty() && !self.quoted {
+            return;
+        }
+        let word = ShellWord {
+            text: std::mem::take(&mut self.word),
+            quoted: std::mem::take(&mut self.quoted),
+            span: span.unwrap_or_default(),
+        };
+        match self.redirect.take() {
+            Some(Redirect::Output) => self.writes_file |= word.text != "/dev/null",
+            Some(Redirect::Other) => {}
+            None => self.words.push(word),
+        }
+    }
+
+    /// Finish the command being read.
+    fn end_command(&mut self) {
+        self.end_word();
+        let span = self.span.take();
+        let redirects = std::mem::take(&mut self.redirects);
+        let writes_file = std::mem::take(&mut self.writes_file);
+        if let Some(span) = span.filter(|_| !self.words.is_empty()) {
+            self.commands.push(ShellCommand {
+                words: std::mem::take(&mut self.words),
+                span,
+                redirects,
+                writes_file,
+            });
+        }
+        self.words.clear();
+    }
+}
+
+/// Simple commands `line` runs, or `None` if they are not all in it.
+pub(crate) fn split_commands(line: &str) -> Option<Vec<ShellCommand>> {
+    let mut builder = Builder::default();
+    let mut chars = line.char_indices().peekable();
+
+    while let Some((start, c)) = chars.next() {
+        let in_word = match c {
+            '\'' => {
+                builder.quoted = true;
+                let text = single_quoted(&mut chars)?;
+                builder.word.push_str(&text);
+                true
+            }
+            '"' => {
+                builder.quoted = true;
+                let text = double_quoted(&mut chars)?;
+                builder.word.push_str(&text);
+                true
+            }
+            '\\' => match chars.next() {
+                Some((_, '\n')) | None => false,
+                Some((_, escaped)) => {
+                    builder.quoted = true;
+                    builder.word.push(escaped);
+                    true
+                }
+            },
+            '`' => return None,
+            // Command and process substitution
+            '$' | '<' | '>' if next_is(&mut chars, '(') => return None,
+            '<' | '>' => {
+                // A file descriptor number belongs to the redirection
+                let descriptor =
+                    !builder.quoted && builder.word.chars().all(|c| c.is_ascii_digit());
+                if descriptor {
+                    builder.word.clear();
+                    builder.word_span = None;
+                } else {
+                    builder.end_word();
+                }
+                builder.redirect = Some(redirection(c, &mut chars));
+                builder.redirects = true;
+                false
+            }
+            '&' if next_is(&mut chars, '>') => {
+                builder.end_word();
+                chars.next();
+                builder.redirect = Some(redirection('>', &mut chars));
+                builder.redirects = true;
+                false
+            }
+            '&' | '|' | ';' | '\n' | '(' | ')' => {
+                builder.end_command();
+                continue;
+            }
+            c if c.is_whitespace() => {
+                builder.end_word();
+                continue;
+            }
+            c => {
+                builder.word.push(c);
+                true
+            }
+        };
+        let end = chars.peek().map_or(line.len(), |&(index, _)| index);
+        builder.cover(start, end);
+        if in_word {
+            builder.cover_word(start, end);
+        }
+    }
+    builder.end_command();
+    Some(builder.commands)
+}
+
+/// Whether the next character is `c`.
+fn next_is(chars: &mut Peekable<CharIndices<'_>>, c: char) -> bool {
+    chars.peek().is_some_and(|&(_, next)| next == c)
+}
+
+/// Kind of the redirection started by `>` or `<`, consuming the rest of
+/// its operator.
+fn redirection(c: char, chars: &mut Peekable<CharIndices<'_>>) -> Redirect {
+    // A here-document's body follows on later lines, where it is split as
+    // commands
+    chars.next_if(|&(_, next)| next == c || next == '|');
+    if chars.next_if(|&(_, next)| next == '&').is_some() || c == '<' {
+        Redirect::Other
+    } else {
+        Redirect::Output
+    }
+}
+
+/// Text of a single-quoted string up to its closing quote, or `None` if it
+/// is missing.
+fn single_quoted(chars: &mut Peekable<CharIndices<'_>>) -> Option<String> {
+    let mut text = String::new();
+    loop {
+        match chars.next()?.1 {
+            '\'' => return Some(text),
+            c => text.push(c),
+        }
+    }
+}
+
+/// Text of a double-quoted string up to its closing quote, or `None` if it
+/// is missing or substitutes a command.
+fn double_quoted(chars: &mut Peekable<CharIndices<'_>>) -> Option<String> {
+    let mut text = String::new();
+    loop {
+        match chars.next()?.1 {
+            '"' => return Some(text),
+            '`' => return None,
+            '$' if next_is(chars, '(') => return None,
+            '\\' => match chars.next()?.1 {
+                escaped @ ('"' | '\\' | '$' | '`') => text.push(escaped),
+                '\n' => {}
+                other => {
+                    text.push('\\');
+                    text.push(other);
+                }
+            },
+            c => text.push(c),
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn words(line: &str) -> Vec<Vec<String>> {
+        split_commands(line)
+            .unwrap()
+            .iter()
+            .map(|command| command.texts().map(ToString::to_string).collect())
+            .collect()
+    }
+
+    #[test]
+    fn test_split_on_operators_outside_quotes() {
+        assert_eq!(
+            words("cargo test&&rm -rf x || (ls; echo 'a | b') |& tee \"log file\""),
+            [
+                vec!["cargo", "test"],
+                vec!["rm", "-rf", "x"],
+                vec!["ls"],
+                vec!["echo", "a | b"],
+                vec!["tee", "log file"],
+            ]
+        );
+        assert_eq!(words("a\\;b c\\\nd"), [vec!["a;b", "cd"]]);
+        assert!(split_commands("echo $(id)").is_none());
+        assert!(split_commands("echo \"`id`\"").is_none());
+        assert!(split_commands("diff <(ls) x").is_none());
+        assert!(split_commands("echo 'open").is_none());
+    }
+
+    #[test]
+    fn test_spans_and_redirections() {
+        let line = "cd src &&  rm -f 'a b' 2>/dev/null ; echo ok > out.txt";
+        let commands = split_commands(line).unwrap();
+        let spans: Vec<&str> = commands
+            .iter()
+            .map(|command| &line[command.span.clone()])
+            .collect();
+        assert_eq!(
+            spans,
+            ["cd src", "rm -f 'a b' 2>/dev/null", "echo ok > out.txt"]
+        );
+        assert_eq!(commands[1].texts().collect::<Vec<_>>(), ["rm", "-f", "a b"]);
+        assert!(commands[1].words[2].quoted);
+        assert_eq!(&line[commands[1].words[2].span.clone()], "'a b'");
+        assert!(commands[1].redirects && !commands[1].writes_file);
+        assert!(commands[2].redirects && commands[2].writes_file);
+        assert!(!commands[0].redirects);
+    }
+}
@@ -0,0 +1,67 @@
+//! Detection of Claude Code sessions that never start.
+//!
+//! When Claude Code is not logged in it prints a login prompt instead of
+//! emitting its `system` init event, and waits. If no init event arrives
+//! within the startup window, the supervisor looks for
+//! [`AUTH_ERROR_MARKERS`] in the captured
+//! [`StderrCapture`](crate::cli::StderrCapture).
+
+/// Default time to wait for the `system` init event, in seconds.
+pub const DEFAULT_STARTUP_TIMEOUT_SECS: u64 = 30;
+
+/// Lowercase stderr fragments that mean Claude Code is not authenticated.
+///
+/// Matched case-insensitively against the captured stderr.
+pub const AUTH_ERROR_MARKERS: &[&str] = &[
+    "not logged in",
+    "please run /login",
+    "claude login",
+    "invalid api key",
+    "missing api key",
+    "authentication_error",
+    "oauth token has expired",
+];
+
+/// Find the first auth error marker in `stderr`.
+#[must_use]
+pub fn find_auth_error(stderr: &str) -> Option<&'static str> {
+    let stderr = stderr.to_lowercase();
+    AUTH_ERROR_MARKERS
+        .iter()
+        .copied()
+        .find(|marker| stderr.contains(marker))
+}
+
+/// Hint shown when Claude Code failed to start because it is not logged in.
+#[must_use]
+pub fn auth_error_hint(marker: &str) -> String {
+    format!(
+        "Claude Code is not authenticated (stderr: \"{marker}\"). \
+         Run `claude login`, or set ANTHROPIC_API_KEY, then try again."
+    )
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_find_auth_error() {
+        assert_eq!(
+            find_auth_error("Invalid API key · Please run /login\n"),
+            Some("please run /login")
+        );
+        assert_eq!(
+            find_auth_error("Error: Not logged in"),
+            Some("not logged in")
+        );
+        assert_eq!(find_auth_error("warning: slow network"), None);
+    }
+
+    #[test]
+    fn test_markers_are_lowercase() {
+        for marker in AUTH_ERROR_MARKERS {
+            assert_eq!(*marker, marker.to_lowercase());
+        }
+    }
+}
@@ -1,6 +1,14 @@
 //! Session state machine.
 
+use std::collections::BTreeMap;
+
 use serde::{Deserialize, Serialize};
+use tokio::time::Instant;
+
+use super::{
+    BlastRadius, BlastRadiusVerdict, MutationKind, RiskCategory, RiskScorer, ToolErrorEvidence,
+};
+use crate::config::{BlastRadiusConfig, RiskConfig};
 
 /// Current state of a supervisor session.
 #[derive(Debug, Clone, Copy, Default, PartialEq, Eq, Serialize, Deserialize)]
@@ -13,6 +21,24 @@ pub enum SessionState {
     Paused,
     Completed,
     Failed,
+    Halted,
+}
+
+impl SessionState {
+    /// Name of the state in status reports.
+    #[must_use]
+    pub fn as_str(self) -> &'static str {
+        match self {
+            Self::Idle => "idle",
+            Self::Running => "running",
+            Self::WaitingForApproval => "waiting_for_approval",
+            Self::WaitingForSupervisor => "waiting_for_supervisor",
+            Self::Paused => "paused",
+            Self::Completed => "completed",
+            Self::Failed => "failed",
+            Self::Halted => "halted",
+        }
+    }
 }
 
 /// State machine for tracking session progress.
@@ -22,6 +48,14 @@ pub struct SessionStateMachine {
     tool_calls: usize,
     approvals: usize,
     denials: usize,
+    turns: usize,
+    escalations: usize,
+    tool_errors: usize,
+    pattern_tool_errors: usize,
+    by_source: DecisionBreakdown,
+    unhandled_events: BTreeMap<String, usize>,
+    blast_radius: BlastRadius,
+    risk: RiskScorer,
 }
 
 impl Default for SessionStateMachine {
@@ -38,6 +72,14 @@ impl SessionStateMachine {
             tool_calls: 0,
             approvals: 0,
             denials: 0,
+            turns: 0,
+            escalations: 0,
+            tool_errors: 0,
+            pattern_tool_errors: 0,
+            by_source: DecisionBreakdown::default(),
+            unhandled_events: BTreeMap::new(),
+            blast_radius: BlastRadius::default(),
+            risk: RiskScorer::default(),
         }
     }
 
@@ -55,12 +97,96 @@ impl SessionStateMachine {
         self.tool_calls = self.tool_calls.saturating_add(1);
     }
 
-    pub fn record_approval(&mut self) {
+    pub fn record_approval(&mut self, source: DecisionSource) {
         self.approvals = self.approvals.saturating_add(1);
+        self.by_source.record_approval(source);
     }
 
-    pub fn record_denial(&mut self) {
+    pub fn record_denial(&mut self, source: DecisionSource) {
         self.denials = self.denials.saturating_add(1);
+        self.by_source.record_denial(source);
+    }
+
+    /// Count a tool call escalated by the policy.
+    pub fn record_escalation(&mut self) {
+        self.escalations = self.escalations.saturating_add(1);
+    }
+
+    /// Count a tool call whose result reports a failure.
+    pub fn record_tool_error(&mut self, evidence: ToolErrorEvidence) {
+        self.tool_errors = self.tool_errors.saturating_add(1);
+        if evidence == ToolErrorEvidence::Pattern {
+            self.pattern_tool_errors = self.pattern_tool_errors.saturating_add(1);
+        }
+    }
+
+    /// Count the end of an assistant turn.
+    pub fn record_turn(&mut self) {
+        self.turns = self.turns.saturating_add(1);
+    }
+
+    /// Count an event of type `kind` the supervisor did not act on.
+    ///
+    /// Returns `true` the first time a type is seen.
+    pub fn record_unhandled(&mut self, kind: &str) -> bool {
+        if let Some(count) = self.unhandled_events.get_mut(kind) {
+            *count = count.saturating_add(1);
+            return false;
+        }
+        self.unhandled_events.insert(kind.to_string(), 1);
+        true
+    }
+
+    /// Use these weights and thresholds for the blast radius.
+    pub fn set_blast_radius_config(&mut self, config: BlastRadiusConfig) {
+        self.blast_radius.set_config(config);
+    }
+
+    /// Add a tool call's mutating operations to the blast radius.
+    ///
+    /// Returns a verdict when the call crosses a threshold.
+    pub fn record_mutations(
+        &mut self,
+        kinds: &[MutationKind],
+        now: Instant,
+    ) -> Option<BlastRadiusVerdict> {
+        self.blast_radius.record(kinds, now)
+    }
+
+    /// Cumulative score of the session's mutating operations.
+    #[must_use]
+    pub fn blast_radius(&self) -> &BlastRadius {
+        &self.blast_radius
+    }
+
+    /// Use these weights and threshold for the risk score.
+    pub fn set_risk_config(&mut self, config: RiskConfig) {
+        self.risk.set_config(config);
+    }
+
+    /// Add a tool call's risk to the score.
+    ///
+    /// Returns the escalation reason when the call takes the score over the
+    /// threshold.
+    pub fn record_risk(&mut self, category: RiskCategory, now: Instant) -> Option<String> {
+        self.risk.record(category, now)
+    }
+
+    /// Rolling risk score of the session's tool calls.
+    #[must_use]
+    pub fn risk(&self) -> &RiskScorer {
+        &self.risk
+    }
+
+    /// Continue counting from previously recorded stats.
+    pub fn restore_stats(&mut self, stats: SessionStats) {
+        self.tool_calls = stats.tool_calls;
+        self.approvals = stats.approvals;
+        self.denials = stats.denials;
+        self.turns = stats.turns;
+        self.escalations = stats.escalations;
+        self.by_source = stats.by_source;
+        self.unhandled_events = stats.unhandled_events;
     }
 
     #[must_use]
@@ -69,14 +195,182 @@ impl SessionStateMachine {
             tool_calls: self.tool_calls,
             approvals: self.approvals,
             denials: self.denials,
+            turns: self.turns,
+            escalations: self.escalations,
+            tool_errors: self.tool_errors,
+            pattern_tool_errors: self.pattern_tool_errors,
+            by_source: self.by_source,
+            unhandled_events: self.unhandled_events.clone(),
+            risk_score: self.risk.score(Instant::now()),
         }
     }
 }
 
 /// Session statistics.
-#[derive(Debug, Clone, Copy)]
+#[derive(Debug, Clone, Default, PartialEq, Eq, Serialize, Deserialize)]
 pub struct SessionStats {
     pub tool_calls: usize,
     pub approvals: usize,
     pub denials: usize,
+    /// Assistant turns ended by a `message_stop` event.
+    #[serde(default)]
+    pub turns: usize,
+    /// Tool calls escalated by the policy.
+    #[serde(default)]
+    pub escalations: usize,
+    /// Tool calls whose result reports a failure.
+    #[serde(default)]
+    pub tool_errors: usize,
+    /// Tool errors recognized only by a pattern on the result text, which
+    /// may misclassify ordinary output.
+    #[serde(default)]
+    pub pattern_tool_errors: usize,
+    /// Approvals and denials by who made them.
+    #[serde(default)]
+    pub by_source: DecisionBreakdown,
+    /// Events the supervisor did not act on, by event type.
+    #[serde(default, skip_serializing_if = "BTreeMap::is_empty")]
+    pub unhandled_events: BTreeMap<String, usize>,
+    /// Risk score of the tool calls within the risk window.
+    #[serde(default)]
+    pub risk_score: u32,
+}
+
+/// Who decided a tool call.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum DecisionSource {
+    /// The policy engine: deny and allow lists, tool rules and the policy
+    /// level.
+    Policy,
+    /// The hook, whose decision was already enforced.
+    Hook,
+    /// The AI supervisor.
+    Ai,
+    /// An operator, at the terminal or behind a webhook.
+    Human,
+    /// The default taken when an escalation got no answer.
+    Fallback,
+    /// Escalation sampling, which allowed an escalation left out of the
+    /// sample.
+    Sampled,
+}
+
+impl DecisionSource {
+    /// Every source, in display order.
+    pub const ALL: [Self; 6] = [
+        Self::Policy,
+        Self::Hook,
+        Self::Ai,
+        Self::Human,
+        Self::Fallback,
+        Self::Sampled,
+    ];
+
+    /// Stable name used in output and storage.
+    #[must_use]
+    pub fn as_str(self) -> &'static str {
+        match self {
+            Self::Policy => "policy",
+            Self::Hook => "hook",
+            Self::Ai => "ai",
+            Self::Human => "human",
+            Self::Fallback => "fallback",
+            Self::Sampled => "sampled",
+        }
+    }
+
+    /// The source named `name` by [`as_str`](Self::as_str).
+    #[must_use]
+    pub fn parse(name: &str) -> Option<Self> {
+        Self::ALL.into_iter().find(|source| source.as_str() == name)
+    }
+}
+
+impl std::fmt::Display for DecisionSource {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        f.write_str(self.as_str())
+    }
+}
+
+/// Approvals and denials made by one source.
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, Serialize, Deserialize)]
+pub struct DecisionCounts {
+    pub approvals: usize,
+    pub denials: usize,
+}
+
+/// Approvals and denials per [`DecisionSource`].
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(default)]
+pub struct DecisionBreakdown {
+    pub policy: DecisionCounts,
+    pub hook: DecisionCounts,
+    pub ai: DecisionCounts,
+    pub human: DecisionCounts,
+    pub fallback: DecisionCounts,
+    pub sampled: DecisionCounts,
+}
+
+impl DecisionBreakdown {
+    /// Counts of decisions made by `source`.
+    #[must_use]
+    pub fn get(&self, source: DecisionSource) -> DecisionCounts {
+        match source {
+            DecisionSource::Policy => self.policy,
+            DecisionSource::Hook => self.hook,
+            DecisionSource::Ai => self.ai,
+            DecisionSource::Human => self.human,
+            DecisionSource::Fallback => self.fallback,
+            DecisionSource::Sampled => self.sampled,
+        }
+    }
+
+    fn get_mut(&mut self, source: DecisionSource) -> &mut DecisionCounts {
+        match source {
+            DecisionSource::Policy => &mut self.policy,
+            DecisionSource::Hook => &mut self.hook,
+            DecisionSource::Ai => &mut self.ai,
+            DecisionSource::Human => &mut self.human,
+            DecisionSource::Fallback => &mut self.fallback,
+            DecisionSource::Sampled => &mut self.sampled,
+        }
+    }
+
+    pub fn record_approval(&mut self, source: DecisionSource) {
+        let counts = self.get_mut(source);
+        counts.approvals = counts.approvals.saturating_add(1);
+    }
+
+    pub fn record_denial(&mut self, source: DecisionSource) {
+        let counts = self.get_mut(source);
+        counts.denials = counts.denials.saturating_add(1);
+    }
+
+    /// Add the counts of `other`, e.g. another session's.
+    pub fn merge(&mut self, other: &Self) {
+        for source in DecisionSource::ALL {
+            let theirs = other.get(source);
+            let ours = self.get_mut(source);
+            ours.approvals = ours.approvals.saturating_add(theirs.approvals);
+            ours.denials = ours.denials.saturating_add(theirs.denials);
+        }
+    }
+
+    /// One line per source that made a decision, like `ai: 2 approved, 1 denied`.
+    #[must_use]
+    pub fn summary_lines(&self) -> Vec<String> {
+        DecisionSource::ALL
+            .into_iter()
+            .filter_map(|source| {
+                let counts = self.get(source);
+                (counts != DecisionCounts::default()).then(|| {
+                    format!(
+                        "{source}: {} approved, {} denied",
+                        counts.approvals, counts.denials
+                    )
+                })
+            })
+            .collect()
+    }
 }
@@ -0,0 +1,198 @@
+//! Wall-clock limits for a session.
+//!
+//! A time box starts at the session init event. At
+//! [`WRAP_UP_WARNING_PERCENT`] of the limit Claude is told to wrap up, and
+//! at the limit the session is shut down with
+//! [`SupervisorResult::TimedOut`](super::SupervisorResult::TimedOut).
+
+use std::time::Duration;
+
+use tokio::time::Instant;
+
+/// Share of the limit, in percent, after which Claude is told to wrap up.
+pub const WRAP_UP_WARNING_PERCENT: u32 = 80;
+
+/// Environment variable telling hook processes when the wrap-up warning
+/// is due, in seconds since the Unix epoch.
+pub const WRAP_UP_AT_ENV: &str = "CLAUDE_SUPERVISOR_WRAP_UP_AT";
+
+/// Process exit code of a session that reached its time limit, next to the
+/// [`KillCause`](super::KillCause) codes.
+pub const TIMED_OUT_EXIT_CODE: i32 = 18;
+
+/// Message telling Claude its time is nearly up.
+pub const WRAP_UP_MESSAGE: &str = "The session time limit is almost reached. Wrap up now: finish \
+     the current step, commit your progress and summarize what is left.";
+
+/// What a time box reached.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum TimeBoxEvent {
+    /// The wrap-up warning is due.
+    Warning,
+    /// The limit is reached.
+    Expired,
+}
+
+/// A wall-clock limit with a wrap-up warning.
+#[derive(Debug, Clone)]
+pub struct TimeBox {
+    limit: Duration,
+    pause_stops_clock: bool,
+    started: Option<Instant>,
+    paused_at: Option<Instant>,
+    paused_total: Duration,
+    warned: bool,
+}
+
+impl TimeBox {
+    /// Create a time box of `limit`; the clock starts with [`start`](Self::start).
+    #[must_use]
+    pub fn new(limit: Duration) -> Self {
+        Self {
+            limit,
+            pause_stops_clock: false,
+            started: None,
+            paused_at: None,
+            paused_total: Duration::ZERO,
+            warned: false,
+        }
+    }
+
+    /// Stop the clock while the session is paused.
+    #[must_use]
+    pub fn with_pause_stops_clock(mut self, pause_stops_clock: bool) -> Self {
+        self.pause_stops_clock = pause_stops_clock;
+        self
+    }
+
+    /// The configured limit.
+    #[must_use]
+    pub fn limit(&self) -> Duration {
+        self.limit
+    }
+
+    /// Elapsed time after which the wrap-up warning is due.
+    #[must_use]
+    pub fn warning_after(&self) -> Duration {
+        self.limit * WRAP_UP_WARNING_PERCENT / 100
+    }
+
+    /// Start the clock, unless it already runs.
+    pub fn start(&mut self, now: Instant) {
+        self.started.get_or_insert(now);
+    }
+
+    /// Whether the clock was started.
+    #[must_use]
+    pub fn is_started(&self) -> bool {
+        self.started.is_some()
+    }
+
+    /// Pause the clock, if pauses stop it.
+    pub fn pause(&mut self, now: Instant) {
+        if self.pause_stops_clock && self.paused_at.is_none() {
+            self.paused_at = Some(now);
+        }
+    }
+
+    /// Resume a paused clock.
+    pub fn resume(&mut self, now: Instant) {
+        if let Some(paused_at) = self.paused_at.take() {
+            self.paused_total += now.saturating_duration_since(paused_at);
+        }
+    }
+
+    /// Time counted against the limit.
+    #[must_use]
+    pub fn elapsed(&self, now: Instant) -> Duration {
+        let Some(started) = self.started else {
+            return Duration::ZERO;
+        };
+        let end = self.paused_at.unwrap_or(now);
+        end.saturating_duration_since(started)
+            .saturating_sub(self.paused_total)
+    }
+
+    /// Time left before the limit.
+    #[must_use]
+    pub fn remaining(&self, now: Instant) -> Duration {
+        self.limit.saturating_sub(self.elapsed(now))
+    }
+
+    /// When the next warning or the cutoff is due, if the clock runs.
+    #[must_use]
+    pub fn next_deadline(&self, now: Instant) -> Option<Instant> {
+        if self.started.is_none() || self.paused_at.is_some() {
+            return None;
+        }
+        let target = if self.warned {
+            self.limit
+        } else {
+            self.warning_after()
+        };
+        Some(now + target.saturating_sub(self.elapsed(now)))
+    }
+
+    /// Report the warning once when due, and the cutoff once reached.
+    pub fn poll(&mut self, now: Instant) -> Option<TimeBoxEvent> {
+        self.started?;
+        let elapsed = self.elapsed(now);
+        if elapsed >= self.limit {
+            Some(TimeBoxEvent::Expired)
+        } else if !self.warned && elapsed >= self.warning_after() {
+            self.warned = true;
+            Some(TimeBoxEvent::Warning)
+        } else {
+            None
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    const MINUTE: Duration = Duration::from_mins(1);
+
+    #[test]
+    fn test_warning_then_expiry() {
+        let start = Instant::now();
+        let mut time_box = TimeBox::new(10 * MINUTE);
+        assert_eq!(time_box.poll(start + 20 * MINUTE), None);
+
+        time_box.start(start);
+        assert_eq!(time_box.next_deadline(start), Some(start + 8 * MINUTE));
+        assert_eq!(time_box.poll(start + 7 * MINUTE), None);
+        assert_eq!(
+            time_box.poll(start + 8 * MINUTE),
+            Some(TimeBoxEvent::Warning)
+        );
+        assert_eq!(time_box.poll(start + 9 * MINUTE), None);
+        assert_eq!(
+            time_box.next_deadline(start + 9 * MINUTE),
+            Some(start + 10 * MINUTE)
+        );
+        assert_eq!(
+            time_box.poll(start + 10 * MINUTE),
+            Some(TimeBoxEvent::Expired)
+        );
+        assert_eq!(time_box.remaining(start + 11 * MINUTE), Duration::ZERO);
+    }
+
+    #[test]
+    fn test_pause_stops_clock_only_when_enabled() {
+        let start = Instant::now();
+        let mut running = TimeBox::new(10 * MINUTE);
+        running.start(start);
+        running.pause(start + MINUTE);
+        assert_eq!(running.elapsed(start + 5 * MINUTE), 5 * MINUTE);
+
+        let mut paused = TimeBox::new(10 * MINUTE).with_pause_stops_clock(true);
+        paused.start(start);
+        paused.pause(start + MINUTE);
+        assert_eq!(paused.next_deadline(start + 2 * MINUTE), None);
+        paused.resume(start + 5 * MINUTE);
+        assert_eq!(paused.elapsed(start + 6 * MINUTE), 2 * MINUTE);
+        assert_eq!(paused.remaining(start + 6 * MINUTE), 8 * MINUTE);
+    }
+}
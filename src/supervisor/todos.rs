@@ -0,0 +1,372 @@
+//! TODO ledger kept in sync with Claude's `TodoWrite` calls.
+//!
+//! Every `TodoWrite` call replaces Claude's whole plan. The ledger mirrors
+//! the latest plan, keeping when each item was first seen and last changed
+//! so the dashboard and escalations can show how the work is going, and so
+//! a stop with work left can be refused.
+//!
+//! Payloads are parsed leniently: older shapes (extra `id`/`priority`
+//! fields, a bare list, a JSON-encoded string, plain string items) are
+//! accepted and a malformed payload leaves the ledger as it was.
+
+use chrono::{DateTime, Utc};
+use serde::{Deserialize, Serialize};
+use serde_json::Value;
+
+/// Name of the tool Claude uses to record its plan.
+pub const TODO_WRITE_TOOL: &str = "TodoWrite";
+
+/// Progress of a TODO item.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum TodoState {
+    /// Not started.
+    Pending,
+    /// Being worked on.
+    InProgress,
+    /// Done.
+    Completed,
+}
+
+impl TodoState {
+    /// Parse a `TodoWrite` status, accepting common spellings.
+    #[must_use]
+    pub fn parse(status: &str) -> Option<Self> {
+        match status
+            .trim()
+            .to_lowercase()
+            .replace(['-', ' '], "_")
+            .as_str()
+        {
+            "pending" | "todo" | "not_started" => Some(Self::Pending),
+            "in_progress" | "active" | "doing" => Some(Self::InProgress),
+            "completed" | "complete" | "done" => Some(Self::Completed),
+            _ => None,
+        }
+    }
+
+    /// Whether work on the item remains.
+    #[must_use]
+    pub fn is_open(self) -> bool {
+        !matches!(self, Self::Completed)
+    }
+
+    fn marker(self) -> &'static str {
+        match self {
+            Self::Pending => "[ ]",
+            Self::InProgress => "[>]",
+            Self::Completed => "[x]",
+        }
+    }
+}
+
+/// An item of Claude's plan.
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct TodoItem {
+    /// What the item is about.
+    pub content: String,
+    /// Its progress.
+    pub state: TodoState,
+    /// Present-tense description shown while the item is in progress.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub active_form: Option<String>,
+    /// When the item first appeared in the plan.
+    pub first_seen: DateTime<Utc>,
+    /// When its state last changed.
+    pub updated_at: DateTime<Utc>,
+}
+
+/// Item counts by state.
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, Serialize, Deserialize)]
+pub struct TodoCounts {
+    /// Items not started.
+    pub pending: usize,
+    /// Items being worked on.
+    pub in_progress: usize,
+    /// Items done.
+    pub completed: usize,
+}
+
+/// The latest plan recorded with `TodoWrite`.
+#[derive(Debug, Clone, Default, PartialEq, Eq, Serialize, Deserialize)]
+pub struct TaskLedger {
+    items: Vec<TodoItem>,
+}
+
+impl TaskLedger {
+    /// Create an empty ledger.
+    #[must_use]
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Replace the plan with the list in a `TodoWrite` input.
+    ///
+    /// Items already in the ledger, matched by content, keep when they were
+    /// first seen, and their last change unless their state changed. Items
+    /// without content are skipped; an item with an unknown status keeps its
+    /// previous state, or is pending if new. Returns `false`, leaving the
+    /// ledger unchanged, if `input` holds no list.
+    pub fn update(&mut self, input: &Value, now: DateTime<Utc>) -> bool {
+        let Some(entries) = todo_entries(input) else {
+            return false;
+        };
+        let items = entries
+            .iter()
+            .filter_map(|entry| {
+                let (content, status, active_form) = parse_entry(entry)?;
+                let previous = self.items.iter().find(|item| item.content == content);
+                let state = status
+                    .or_else(|| previous.map(|item| item.state))
+                    .unwrap_or(TodoState::Pending);
+                let (first_seen, updated_at) = match previous {
+                    Some(item) if item.state == state => (item.first_seen, item.updated_at),
+                    Some(item) => (item.first_seen, now),
+                    None => (now, now),
+                };
+                Some(TodoItem {
+                    content,
+                    state,
+                    active_form,
+                    first_seen,
+                    updated_at,
+                })
+            })
+            .collect();
+        self.items = items;
+        true
+    }
+
+    /// Build a ledger from `TodoWrite` inputs, oldest first.
+    pub fn replay<'a>(inputs: impl IntoIterator<Item = &'a Value>, now: DateTime<Utc>) -> Self {
+        let mut ledger = Self::new();
+        for input in inputs {
+            ledger.update(input, now);
+        }
+        ledger
+    }
+
+    /// The items, in plan order.
+    #[must_use]
+    pub fn items(&self) -> &[TodoItem] {
+        &self.items
+    }
+
+    /// Whether the plan has no items.
+    #[must_use]
+    pub fn is_empty(&self) -> bool {
+        self.items.is_empty()
+    }
+
+    /// Items not yet completed.
+    pub fn open_items(&self) -> impl Iterator<Item = &TodoItem> {
+        self.items.iter().filter(|item| item.state.is_open())
+    }
+
+    /// Whether any item is pending or in progress.
+    #[must_use]
+    pub fn has_open(&self) -> bool {
+        self.open_items().next().is_some()
+    }
+
+    /// Item counts by state.
+    #[must_use]
+    pub fn counts(&self) -> TodoCounts {
+        let mut counts = TodoCounts::default();
+        for item in &self.items {
+            match item.state {
+                TodoState::Pending => counts.pending += 1,
+                TodoState::InProgress => counts.in_progress += 1,
+                TodoState::Completed => counts.completed += 1,
+            }
+        }
+        counts
+    }
+
+    /// One-line progress, such as `1/3 completed, 1 in progress, 1 pending`.
+    #[must_use]
+    pub fn summary(&self) -> String {
+        let counts = self.counts();
+        format!(
+            "{}/{} completed, {} in progress, {} pending",
+            counts.completed,
+            self.items.len(),
+            counts.in_progress,
+            counts.pending
+        )
+    }
+
+    /// Compact rendering, one `[x]`, `[>]` or `[ ]` line per item.
+    #[must_use]
+    pub fn render(&self) -> String {
+        self.items
+            .iter()
+            .map(|item| format!("{} {}", item.state.marker(), item.content))
+            .collect::<Vec<_>>()
+            .join("\n")
+    }
+
+    /// Reason for refusing a stop while items are open, if any are.
+    #[must_use]
+    pub fn open_reason(&self) -> Option<String> {
+        let open: Vec<&str> = self
+            .open_items()
+            .map(|item| item.content.as_str())
+            .collect();
+        if open.is_empty() {
+            return None;
+        }
+        Some(format!(
+            "{} TODO item(s) still open: {}",
+            open.len(),
+            open.join("; ")
+        ))
+    }
+}
+
+/// The list of entries in a `TodoWrite` input.
+fn todo_entries(input: &Value) -> Option<Vec<Value>> {
+    match input {
+        Value::Array(entries) => Some(entries.clone()),
+        Value::String(encoded) => match serde_json::from_str::<Value>(encoded).ok()? {
+            Value::String(_) => None,
+            decoded => todo_entries(&decoded),
+        },
+        Value::Object(map) => todo_entries(map.get("todos")?),
+        _ => None,
+    }
+}
+
+fn parse_entry(entry: &Value) -> Option<(String, Option<TodoState>, Option<String>)> {
+    let text = |value: Option<&Value>| {
+        value
+            .and_then(Value::as_str)
+            .map(str::trim)
+            .filter(|s| !s.is_empty())
+            .map(str::to_string)
+    };
+    match entry {
+        Value::String(_) => text(Some(entry)).map(|content| (content, None, None)),
+        Value::Object(map) => {
+            let content = ["content", "text", "title", "task"]
+                .iter()
+                .find_map(|key| text(map.get(*key)))?;
+            let status = ["status", "state"]
+                .iter()
+                .find_map(|key| map.get(*key).and_then(Value::as_str))
+                .and_then(TodoState::parse);
+            let active_form = text(map.get("activeForm").or_else(|| map.get("active_form")));
+            Some((content, status, active_form))
+        }
+        _ => None,
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use chrono::Duration;
+    use serde_json::json;
+
+    #[test]
+    fn test_update_tracks_states_and_timestamps() {
+        let start = Utc::now();
+        let mut ledger = TaskLedger::new();
+        assert!(ledger.update(
+            &json!({"todos": [
+                {"content": "Write parser", "status": "in_progress", "activeForm": "Writing parser"},
+                {"content": "Add tests", "status": "pending", "activeForm": "Adding tests"}
+            ]}),
+            start
+        ));
+        assert_eq!(
+            ledger.items()[0].active_form.as_deref(),
+            Some("Writing parser")
+        );
+
+        let later = start + Duration::seconds(30);
+        assert!(ledger.update(
+            &json!({"todos": [
+                {"content": "Write parser", "status": "completed", "activeForm": "Writing parser"},
+                {"content": "Add tests", "status": "pending", "activeForm": "Adding tests"},
+                {"content": "Update docs", "status": "pending", "activeForm": "Updating docs"}
+            ]}),
+            later
+        ));
+        let items = ledger.items();
+        assert_eq!(items[0].state, TodoState::Completed);
+        assert_eq!(items[0].first_seen, start);
+        assert_eq!(items[0].updated_at, later);
+        assert_eq!(items[1].updated_at, start);
+        assert_eq!(items[2].first_seen, later);
+        assert_eq!(ledger.summary(), "1/3 completed, 0 in progress, 2 pending");
+        assert_eq!(
+            ledger.render(),
+            "[x] Write parser\n[ ] Add tests\n[ ] Update docs"
+        );
+    }
+
+    #[test]
+    fn test_update_accepts_older_shapes() {
+        let now = Utc::now();
+        let mut ledger = TaskLedger::new();
+        assert!(ledger.update(
+            &json!({"todos": [
+                {"id": "1", "content": "Plan", "status": "done", "priority": "high"},
+                {"id": "2", "title": "Build", "state": "In-Progress"}
+            ]}),
+            now
+        ));
+        assert_eq!(ledger.render(), "[x] Plan\n[>] Build");
+
+        assert!(ledger.update(&json!([{"text": "Ship", "status": "pending"}]), now));
+        assert_eq!(ledger.render(), "[ ] Ship");
+
+        let encoded = json!({"todos": "[{\"content\": \"Review\", \"status\": \"completed\"}]"});
+        assert!(ledger.update(&encoded, now));
+        assert_eq!(ledger.render(), "[x] Review");
+
+        assert!(ledger.update(&json!({"todos": ["Review", "Release"]}), now));
+        assert_eq!(ledger.render(), "[x] Review\n[ ] Release");
+    }
+
+    #[test]
+    fn test_malformed_payload_leaves_ledger_unchanged() {
+        let now = Utc::now();
+        let mut ledger = TaskLedger::new();
+        ledger.update(
+            &json!({"todos": [{"content": "Plan", "status": "pending"}]}),
+            now,
+        );
+
+        assert!(!ledger.update(&json!({"todo": []}), now));
+        assert!(!ledger.update(&json!({"todos": 3}), now));
+        assert!(!ledger.update(&json!({"todos": "not json"}), now));
+        assert_eq!(ledger.render(), "[ ] Plan");
+
+        // Unusable items are skipped, unknown statuses keep the old state
+        assert!(ledger.update(
+            &json!({"todos": [{"status": "pending"}, 7, {"content": "Plan", "status": "blocked"}]}),
+            now
+        ));
+        assert_eq!(ledger.render(), "[ ] Plan");
+    }
+
+    #[test]
+    fn test_open_reason() {
+        let now = Utc::now();
+        let ledger = TaskLedger::replay(
+            [&json!({"todos": [
+                {"content": "Plan", "status": "completed"},
+                {"content": "Build", "status": "in_progress"}
+            ]})],
+            now,
+        );
+        assert!(ledger.has_open());
+        assert_eq!(
+            ledger.open_reason().as_deref(),
+            Some("1 TODO item(s) still open: Build")
+        );
+        assert_eq!(TaskLedger::new().open_reason(), None);
+    }
+}
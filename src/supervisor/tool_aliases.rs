@@ -0,0 +1,124 @@
+//! Canonical names for tools called by another name.
+//!
+//! Claude Code has renamed tools between releases, and MCP servers wrap the
+//! core tools under names of their own, so a rule written for `Edit` would
+//! miss a call to `str_replace_editor`. Policy rules see every call under
+//! its canonical name; the audit log keeps the name the call was made with
+//! next to it. Names that are not aliases pass through unchanged.
+
+use std::collections::{BTreeMap, HashMap};
+
+/// Built-in aliases, each mapped to the canonical tool name.
+pub const DEFAULT_TOOL_ALIASES: &[(&str, &str)] = &[
+    ("str_replace_editor", "Edit"),
+    ("str_replace_based_edit_tool", "Edit"),
+    ("bash", "Bash"),
+];
+
+/// Map from tool aliases to canonical tool names.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct ToolAliases {
+    aliases: HashMap<String, String>,
+}
+
+impl Default for ToolAliases {
+    fn default() -> Self {
+        Self::with_defaults()
+    }
+}
+
+impl ToolAliases {
+    /// Create a map without any aliases.
+    #[must_use]
+    pub fn new() -> Self {
+        Self {
+            aliases: HashMap::new(),
+        }
+    }
+
+    /// Create a map with the [`DEFAULT_TOOL_ALIASES`].
+    #[must_use]
+    pub fn with_defaults() -> Self {
+        let mut aliases = Self::new();
+        for (alias, canonical) in DEFAULT_TOOL_ALIASES {
+            aliases.add(*alias, *canonical);
+        }
+        aliases
+    }
+
+    /// Map `alias` to `canonical`, replacing any earlier mapping.
+    ///
+    /// An alias of itself is ignored.
+    pub fn add(&mut self, alias: impl Into<String>, canonical: impl Into<String>) {
+        let (alias, canonical) = (alias.into(), canonical.into());
+        if alias == canonical {
+            self.aliases.remove(&alias);
+        } else {
+            self.aliases.insert(alias, canonical);
+        }
+    }
+
+    /// Add the configured `tool_aliases`, which take precedence over the
+    /// built-in ones.
+    pub fn extend(&mut self, aliases: &BTreeMap<String, String>) {
+        for (alias, canonical) in aliases {
+            self.add(alias, canonical);
+        }
+    }
+
+    /// Canonical name of `tool_name`, if it is an alias.
+    #[must_use]
+    pub fn resolve(&self, tool_name: &str) -> Option<&str> {
+        self.aliases.get(tool_name).map(String::as_str)
+    }
+
+    /// Canonical name of `tool_name`; the name itself unless it is an alias.
+    #[must_use]
+    pub fn canonical<'a>(&'a self, tool_name: &'a str) -> &'a str {
+        self.resolve(tool_name).unwrap_or(tool_name)
+    }
+
+    /// Number of aliases.
+    #[must_use]
+    pub fn len(&self) -> usize {
+        self.aliases.len()
+    }
+
+    /// Whether there are no aliases.
+    #[must_use]
+    pub fn is_empty(&self) -> bool {
+        self.aliases.is_empty()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_canonical_names() {
+        let aliases = ToolAliases::default();
+        assert_eq!(aliases.canonical("str_replace_editor"), "Edit");
+        assert_eq!(aliases.canonical("Edit"), "Edit");
+        assert_eq!(
+            aliases.canonical("mcp__fs__edit_file"),
+            "mcp__fs__edit_file"
+        );
+        assert_eq!(aliases.resolve("Edit"), None);
+    }
+
+    #[test]
+    fn test_configured_aliases_take_precedence() {
+        let mut aliases = ToolAliases::default();
+        aliases.extend(&BTreeMap::from([
+            ("mcp__fs__edit_file".to_string(), "Edit".to_string()),
+            ("bash".to_string(), "bash".to_string()),
+            ("str_replace_editor".to_string(), "MultiEdit".to_string()),
+        ]));
+        assert_eq!(aliases.canonical("mcp__fs__edit_file"), "Edit");
+        assert_eq!(aliases.canonical("str_replace_editor"), "MultiEdit");
+        // Mapping a name to itself drops the built-in alias
+        assert_eq!(aliases.resolve("bash"), None);
+        assert_eq!(aliases.len(), DEFAULT_TOOL_ALIASES.len());
+    }
+}
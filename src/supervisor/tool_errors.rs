@@ -0,0 +1,205 @@
+//! Recognizing failed tool calls in the results Claude Code echoes.
+//!
+//! A tool result that says whether it failed is taken at its word: the
+//! `is_error` flag of its `tool_result` block, or an `is_error` flag or
+//! error `type` in the echoed `tool_use_result`. Searching the result text
+//! for "error" would flag every file and command output that mentions the
+//! word, so results that say neither are only matched against the
+//! configured patterns, anchored at the start of the first
+//! [`MAX_CLASSIFIED_BYTES`] of the text. Errors found that way are counted
+//! apart, since they are the ones that can be wrong.
+
+use regex::Regex;
+use serde_json::Value;
+
+use crate::config::ToolErrorConfig;
+
+/// Most bytes of a result's text matched against the patterns.
+pub const MAX_CLASSIFIED_BYTES: usize = 4096;
+
+/// How a tool result was recognized as an error.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum ToolErrorEvidence {
+    /// The result says it failed.
+    Explicit,
+    /// The result's text matched a configured pattern.
+    Pattern,
+}
+
+/// Recognizes failed tool calls in tool results.
+#[derive(Debug, Clone)]
+pub struct ToolErrorClassifier {
+    patterns: Vec<Regex>,
+}
+
+impl Default for ToolErrorClassifier {
+    fn default() -> Self {
+        Self::from_config(&ToolErrorConfig::default())
+    }
+}
+
+impl ToolErrorClassifier {
+    /// Create a classifier from the configuration; invalid patterns are
+    /// skipped with a warning.
+    #[must_use]
+    pub fn from_config(config: &ToolErrorConfig) -> Self {
+        let patterns = config
+            .patterns
+            .iter()
+            .filter_map(|pattern| match Regex::new(pattern) {
+                Ok(regex) => Some(regex),
+                Err(e) => {
+                    tracing::warn!(%pattern, error = %e, "Ignoring invalid tool error pattern");
+                    None
+                }
+            })
+            .collect();
+        Self { patterns }
+    }
+
+    /// Whether the tool result `block` of a user message, echoed as
+    /// `tool_use_result` when given, reports a failed call.
+    #[must_use]
+    pub fn classify(
+        &self,
+        block: &Value,
+        tool_use_result: Option<&Value>,
+    ) -> Option<ToolErrorEvidence> {
+        if let Some(is_error) = explicit_error(block, tool_use_result) {
+            return is_error.then_some(ToolErrorEvidence::Explicit);
+        }
+        let text = tool_use_result
+            .and_then(Value::as_str)
+            .or_else(|| result_text(block))?;
+        let text = head(text, MAX_CLASSIFIED_BYTES);
+        self.patterns
+            .iter()
+            .any(|pattern| pattern.is_match(text))
+            .then_some(ToolErrorEvidence::Pattern)
+    }
+}
+
+/// Tool result blocks of a user message.
+pub fn tool_result_blocks(message: &Value) -> impl Iterator<Item = &Value> {
+    message
+        .get("content")
+        .and_then(Value::as_array)
+        .into_iter()
+        .flatten()
+        .filter(|block| block.get("type").and_then(Value::as_str) == Some("tool_result"))
+}
+
+/// Whether the result says it failed, if it says so at all.
+fn explicit_error(block: &Value, tool_use_result: Option<&Value>) -> Option<bool> {
+    if let Some(is_error) = block.get("is_error").and_then(Value::as_bool) {
+        return Some(is_error);
+    }
+    let echoed = tool_use_result.filter(|result| result.is_object())?;
+    if let Some(is_error) = echoed.get("is_error").and_then(Value::as_bool) {
+        return Some(is_error);
+    }
+    echoed
+        .get("type")
+        .and_then(Value::as_str)
+        .map(|kind| kind == "error")
+}
+
+/// Text of a tool result block: its content string, or its first text part.
+fn result_text(block: &Value) -> Option<&str> {
+    match block.get("content")? {
+        Value::String(text) => Some(text),
+        Value::Array(parts) => parts
+            .iter()
+            .find_map(|part| part.get("text").and_then(Value::as_str)),
+        _ => None,
+    }
+}
+
+/// The first `max_bytes` of `text`, cut on a character boundary.
+fn head(text: &str, max_bytes: usize) -> &str {
+    if text.len() <= max_bytes {
+        return text;
+    }
+    let mut end = max_bytes;
+    while !text.is_char_boundary(end) {
+        end -= 1;
+    }
+    &text[..end]
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use serde_json::json;
+
+    fn block(content: Value, is_error: Option<bool>) -> Value {
+        let mut block = json!({"type": "tool_result", "tool_use_id": "toolu_1"});
+        block["content"] = content;
+        if let Some(is_error) = is_error {
+            block["is_error"] = json!(is_error);
+        }
+        block
+    }
+
+    #[test]
+    fn test_benign_error_text_is_not_an_error() {
+        let classifier = ToolErrorClassifier::default();
+        let source = "fn parse() -> Result<(), Error> {\n    Err(Error::new(\"error\"))\n}";
+        assert_eq!(classifier.classify(&block(json!(source), None), None), None);
+        assert_eq!(
+            classifier.classify(&block(json!(source), Some(false)), None),
+            None
+        );
+        // Command output mentioning errors, echoed as an object
+        let echoed = json!({"stdout": "0 errors, 2 warnings", "stderr": "", "interrupted": false});
+        assert_eq!(
+            classifier.classify(&block(json!("0 errors, 2 warnings"), None), Some(&echoed)),
+            None
+        );
+        // An explicit success is not second-guessed
+        assert_eq!(
+            classifier.classify(&block(json!("Error: none"), Some(false)), None),
+            None
+        );
+    }
+
+    #[test]
+    fn test_genuine_errors() {
+        let classifier = ToolErrorClassifier::default();
+        assert_eq!(
+            classifier.classify(&block(json!("Exit code 1"), Some(true)), None),
+            Some(ToolErrorEvidence::Explicit)
+        );
+        let echoed = json!({"type": "error", "message": "File does not exist."});
+        assert_eq!(
+            classifier.classify(&block(json!("File does not exist."), None), Some(&echoed)),
+            Some(ToolErrorEvidence::Explicit)
+        );
+        let parts = json!([{"type": "text", "text": "<tool_use_error>File has not been read yet</tool_use_error>"}]);
+        assert_eq!(
+            classifier.classify(&block(parts, None), None),
+            Some(ToolErrorEvidence::Pattern)
+        );
+        assert_eq!(
+            classifier.classify(
+                &block(json!(""), None),
+                Some(&json!("Error: permission denied"))
+            ),
+            Some(ToolErrorEvidence::Pattern)
+        );
+    }
+
+    #[test]
+    fn test_patterns_see_only_the_head_of_long_results() {
+        let config = ToolErrorConfig {
+            patterns: vec!["FAILED".to_string(), "(".to_string()],
+        };
+        let classifier = ToolErrorClassifier::from_config(&config);
+        let late = format!("{}FAILED", "é".repeat(MAX_CLASSIFIED_BYTES));
+        assert_eq!(classifier.classify(&block(json!(late), None), None), None);
+        assert_eq!(
+            classifier.classify(&block(json!("test FAILED"), None), None),
+            Some(ToolErrorEvidence::Pattern)
+        );
+    }
+}
@@ -0,0 +1,118 @@
+//! Tool lists matching names exactly or by glob.
+//!
+//! An entry of the allowed or denied tools is a tool name or a glob: `*`
+//! matches any run of characters and `?` one character, so `mcp__github__*`
+//! covers every tool of the GitHub server. When entries of both lists match
+//! a call, the most specific one decides: an exact name outranks every
+//! glob, and a glob with more literal characters outranks one with fewer.
+//! Between equally specific entries, the denial wins.
+
+use std::collections::HashSet;
+
+use regex::Regex;
+
+use super::overrides::glob_to_regex;
+
+/// How closely a tool list entry matches a tool name; greater is closer.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, PartialOrd, Ord)]
+pub struct PatternSpecificity {
+    /// Whether the entry is the tool name itself.
+    exact: bool,
+    /// Characters of the entry that are not wildcards.
+    literal: usize,
+}
+
+/// Set of tool names and globs.
+#[derive(Debug, Clone, Default)]
+pub struct ToolPatterns {
+    entries: HashSet<String>,
+    globs: Vec<(Regex, usize)>,
+}
+
+impl ToolPatterns {
+    /// Create an empty set.
+    #[must_use]
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Add a tool name or glob.
+    pub fn insert(&mut self, entry: impl Into<String>) {
+        let entry = entry.into();
+        if is_glob(&entry) && !self.entries.contains(&entry) {
+            // Escaped literals always compile
+            if let Ok(regex) = Regex::new(&glob_to_regex(&entry, false)) {
+                let literal = entry.chars().filter(|c| !matches!(c, '*' | '?')).count();
+                self.globs.push((regex, literal));
+            }
+        }
+        self.entries.insert(entry);
+    }
+
+    /// Entries as they were added.
+    #[must_use]
+    pub fn entries(&self) -> &HashSet<String> {
+        &self.entries
+    }
+
+    /// Whether `entry` was added, compared as written.
+    #[must_use]
+    pub fn contains(&self, entry: &str) -> bool {
+        self.entries.contains(entry)
+    }
+
+    /// Specificity of the closest entry matching `tool_name`, if any does.
+    #[must_use]
+    pub fn best_match(&self, tool_name: &str) -> Option<PatternSpecificity> {
+        if self.entries.contains(tool_name) && !is_glob(tool_name) {
+            return Some(PatternSpecificity {
+                exact: true,
+                literal: tool_name.chars().count(),
+            });
+        }
+        self.globs
+            .iter()
+            .filter(|(regex, _)| regex.is_match(tool_name))
+            .map(|&(_, literal)| PatternSpecificity {
+                exact: false,
+                literal,
+            })
+            .max()
+    }
+}
+
+/// Whether a tool list entry is a glob rather than a name.
+fn is_glob(entry: &str) -> bool {
+    entry.contains(['*', '?'])
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_exact_names_outrank_globs() {
+        let mut patterns = ToolPatterns::new();
+        patterns.insert("Bash*");
+        patterns.insert("mcp__*");
+        patterns.insert("mcp__github__*");
+
+        let exact = {
+            let mut exact = ToolPatterns::new();
+            exact.insert("Bash");
+            exact.best_match("Bash").unwrap()
+        };
+        let glob = patterns.best_match("Bash").unwrap();
+        assert!(exact > glob);
+        assert_eq!(patterns.best_match("BashOutput"), Some(glob));
+        assert_eq!(patterns.best_match("bash"), None);
+
+        // The longer glob is the closer match
+        let github = patterns.best_match("mcp__github__create_issue").unwrap();
+        let any_mcp = patterns.best_match("mcp__slack__post").unwrap();
+        assert!(github > any_mcp);
+
+        assert!(patterns.contains("mcp__*"));
+        assert!(!patterns.contains("mcp__slack__post"));
+    }
+}
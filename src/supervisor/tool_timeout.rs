@@ -0,0 +1,208 @@
+//! Latency tracking for approved tool calls.
+//!
+//! Long-running commands such as watch-mode servers never produce a
+//! `ToolResult`, leaving the session hanging with no terminal event. The
+//! tracker records a deadline for every approved `ToolUse` and reports the
+//! ones whose result has not arrived in time.
+
+use std::collections::HashMap;
+use std::time::Duration;
+
+use serde::Serialize;
+use tokio::time::Instant;
+
+use crate::cli::ToolUse;
+use crate::config::ToolTimeoutConfig;
+
+/// What a hung tool is said to do in [`HungTool::describe`].
+pub const HUNG_TOOL_REASON: &str = "appears hung";
+
+/// A tool call still waiting for its result.
+#[derive(Debug, Clone)]
+struct PendingTool {
+    tool_use: ToolUse,
+    started: Instant,
+    deadline: Instant,
+}
+
+/// A tool call that produced no result before its timeout.
+#[derive(Debug, Clone, Serialize)]
+pub struct HungTool {
+    /// ID of the tool call.
+    pub tool_use_id: String,
+    /// Name of the tool.
+    pub tool_name: String,
+    /// Input the tool was called with.
+    pub input: serde_json::Value,
+    /// Time since the call was approved.
+    pub elapsed: Duration,
+    /// Timeout that was exceeded.
+    pub timeout: Duration,
+}
+
+impl HungTool {
+    /// Human-readable description used in logs and kill reasons.
+    #[must_use]
+    pub fn describe(&self) -> String {
+        format!(
+            "Tool {} {HUNG_TOOL_REASON}: no result after {}s (timeout {}s)",
+            self.tool_name,
+            self.elapsed.as_secs(),
+            self.timeout.as_secs()
+        )
+    }
+}
+
+/// Tracks deadlines for approved tool calls, keyed by `tool_use_id`.
+#[derive(Debug, Clone, Default)]
+pub struct ToolTimeoutTracker {
+    config: ToolTimeoutConfig,
+    pending: HashMap<String, PendingTool>,
+}
+
+impl ToolTimeoutTracker {
+    /// Create a tracker with the given timeouts.
+    #[must_use]
+    pub fn new(config: ToolTimeoutConfig) -> Self {
+        Self {
+            config,
+            pending: HashMap::new(),
+        }
+    }
+
+    /// Get the timeout configuration.
+    #[must_use]
+    pub fn config(&self) -> &ToolTimeoutConfig {
+        &self.config
+    }
+
+    /// Start the clock for an approved tool call.
+    ///
+    /// Starting an already-pending call restarts its clock.
+    pub fn start(&mut self, tool_use: &ToolUse) {
+        if !self.config.enabled {
+            return;
+        }
+        let started = Instant::now();
+        let deadline = started + self.config.timeout_for(&tool_use.name);
+        self.pending.insert(
+            tool_use.id.clone(),
+            PendingTool {
+                tool_use: tool_use.clone(),
+                started,
+                deadline,
+            },
+        );
+    }
+
+    /// Stop the clock when a result arrives, returning the call's latency.
+    pub fn finish(&mut self, tool_use_id: &str) -> Option<Duration> {
+        self.pending
+            .remove(tool_use_id)
+            .map(|pending| pending.started.elapsed())
+    }
+
+    /// Number of tool calls waiting for a result.
+    #[must_use]
+    pub fn pending_count(&self) -> usize {
+        self.pending.len()
+    }
+
+    /// Earliest deadline among pending calls.
+    #[must_use]
+    pub fn next_deadline(&self) -> Option<Instant> {
+        self.pending.values().map(|pending| pending.deadline).min()
+    }
+
+    /// Remove and return calls whose deadline has passed, oldest first.
+    ///
+    /// The returned `ToolUse` lets callers escalate the hung call; call
+    /// [`start`](Self::start) again to keep waiting for it.
+    pub fn take_expired(&mut self, now: Instant) -> Vec<(ToolUse, HungTool)> {
+        let mut expired_ids: Vec<(Instant, String)> = self
+            .pending
+            .iter()
+            .filter(|(_, pending)| pending.deadline <= now)
+            .map(|(id, pending)| (pending.deadline, id.clone()))
+            .collect();
+        expired_ids.sort();
+
+        expired_ids
+            .into_iter()
+            .filter_map(|(_, id)| self.pending.remove(&id))
+            .map(|pending| {
+                let hung = HungTool {
+                    tool_use_id: pending.tool_use.id.clone(),
+                    tool_name: pending.tool_use.name.clone(),
+                    input: pending.tool_use.input.clone(),
+                    elapsed: now - pending.started,
+                    timeout: pending.deadline - pending.started,
+                };
+                (pending.tool_use, hung)
+            })
+            .collect()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn tool_use(id: &str, name: &str) -> ToolUse {
+        ToolUse {
+            id: id.to_string(),
+            name: name.to_string(),
+            input: serde_json::json!({}),
+        }
+    }
+
+    #[tokio::test(start_paused = true)]
+    async fn test_result_before_timeout_is_not_hung() {
+        let mut tracker = ToolTimeoutTracker::default();
+        tracker.start(&tool_use("t1", "Read"));
+
+        tokio::time::advance(Duration::from_secs(5)).await;
+        let latency = tracker.finish("t1").unwrap();
+        assert_eq!(latency, Duration::from_secs(5));
+
+        tokio::time::advance(Duration::from_mins(10)).await;
+        assert!(tracker.take_expired(Instant::now()).is_empty());
+        assert!(tracker.next_deadline().is_none());
+    }
+
+    #[tokio::test(start_paused = true)]
+    async fn test_per_tool_timeouts_expire_in_order() {
+        let mut tracker = ToolTimeoutTracker::default();
+        tracker.start(&tool_use("bash", "Bash"));
+        tracker.start(&tool_use("read", "Read"));
+
+        assert_eq!(
+            tracker.next_deadline(),
+            Some(Instant::now() + Duration::from_mins(2))
+        );
+
+        tokio::time::advance(Duration::from_secs(121)).await;
+        let expired = tracker.take_expired(Instant::now());
+        assert_eq!(expired.len(), 1);
+        assert_eq!(expired[0].1.tool_name, "Read");
+        assert_eq!(expired[0].1.timeout, Duration::from_mins(2));
+        assert_eq!(tracker.pending_count(), 1);
+
+        tokio::time::advance(Duration::from_mins(8)).await;
+        let expired = tracker.take_expired(Instant::now());
+        assert_eq!(expired.len(), 1);
+        assert_eq!(expired[0].1.tool_name, "Bash");
+        assert!(expired[0].1.describe().contains("no result after 601s"));
+    }
+
+    #[tokio::test(start_paused = true)]
+    async fn test_disabled_tracker_ignores_calls() {
+        let mut tracker = ToolTimeoutTracker::new(ToolTimeoutConfig {
+            enabled: false,
+            ..Default::default()
+        });
+        tracker.start(&tool_use("t1", "Bash"));
+        assert_eq!(tracker.pending_count(), 0);
+        assert!(tracker.next_deadline().is_none());
+    }
+}
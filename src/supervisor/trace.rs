@@ -0,0 +1,171 @@
+//! Tracing spans for a supervised session.
+//!
+//! The runner records a `session` span, a `tool_call` child span per tool
+//! use and an `escalation` span under the tool call it decides. Fields use
+//! OpenTelemetry-style dotted names; with the `otel` feature, the layer in
+//! [`crate::otel`] exports them as traces without further instrumentation.
+
+use std::collections::HashMap;
+use std::time::Duration;
+
+use tracing::field::Empty;
+use tracing::Span;
+
+use crate::cli::ToolUse;
+use crate::telemetry::{SPAN_ESCALATION, SPAN_SESSION, SPAN_TOOL_CALL};
+
+/// Spans of one session and its in-flight tool calls.
+#[derive(Debug)]
+pub struct SessionTrace {
+    session: Span,
+    tool_calls: HashMap<String, Span>,
+}
+
+impl Default for SessionTrace {
+    fn default() -> Self {
+        Self {
+            session: Span::none(),
+            tool_calls: HashMap::new(),
+        }
+    }
+}
+
+impl SessionTrace {
+    /// Open the session span, unless it is already open.
+    pub fn start_session(&mut self, session_id: &str, model: &str) {
+        if self.session.is_none() {
+            self.session = tracing::info_span!(
+                parent: None,
+                SPAN_SESSION,
+                session.id = %session_id,
+                session.model = %model,
+                session.outcome = Empty,
+            );
+        }
+    }
+
+    /// Open a span for a tool call under the session span.
+    pub fn start_tool_call(&mut self, tool_use: &ToolUse) {
+        let span = tracing::info_span!(
+            parent: &self.session,
+            SPAN_TOOL_CALL,
+            tool.name = %tool_use.name,
+            tool.use_id = %tool_use.id,
+            tool.decision = Empty,
+            tool.duration_ms = Empty,
+        );
+        self.tool_calls.insert(tool_use.id.clone(), span);
+    }
+
+    /// Record the decision taken on a tool call.
+    pub fn record_decision(&self, tool_use_id: &str, decision: &str) {
+        if let Some(span) = self.tool_calls.get(tool_use_id) {
+            span.record("tool.decision", decision);
+        }
+    }
+
+    /// Close a tool call's span once its result arrives.
+    pub fn finish_tool_call(&mut self, tool_use_id: &str, duration: Option<Duration>) {
+        if let Some(span) = self.tool_calls.remove(tool_use_id) {
+            if let Some(duration) = duration {
+                span.record("tool.duration_ms", millis(duration));
+            }
+        }
+    }
+
+    /// Open a span for the escalation of a tool call to `provider`.
+    #[must_use]
+    pub fn escalation(&self, tool_use_id: &str, provider: &str) -> Span {
+        let parent = self.tool_calls.get(tool_use_id).unwrap_or(&self.session);
+        tracing::info_span!(
+            parent: parent,
+            SPAN_ESCALATION,
+            escalation.provider = %provider,
+            escalation.latency_ms = Empty,
+            escalation.verdict = Empty,
+        )
+    }
+
+    /// Record how an escalation was decided.
+    pub fn finish_escalation(span: &Span, latency: Duration, verdict: &str) {
+        span.record("escalation.latency_ms", millis(latency));
+        span.record("escalation.verdict", verdict);
+    }
+
+    /// Record the session outcome and close every open span.
+    pub fn finish(&mut self, outcome: &str) {
+        self.tool_calls.clear();
+        self.session.record("session.outcome", outcome);
+        self.session = Span::none();
+    }
+}
+
+fn millis(duration: Duration) -> u64 {
+    u64::try_from(duration.as_millis()).unwrap_or(u64::MAX)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::sync::{Arc, Mutex};
+    use tracing::span::{Attributes, Id};
+    use tracing_subscriber::layer::{Context, Layer, SubscriberExt};
+    use tracing_subscriber::registry::LookupSpan;
+
+    /// A span's name and its parent's name.
+    type SpanEdge = (String, Option<String>);
+
+    /// Records each new span with its parent.
+    #[derive(Clone, Default)]
+    struct Hierarchy(Arc<Mutex<Vec<SpanEdge>>>);
+
+    impl<S> Layer<S> for Hierarchy
+    where
+        S: tracing::Subscriber + for<'a> LookupSpan<'a>,
+    {
+        fn on_new_span(&self, _attrs: &Attributes<'_>, id: &Id, ctx: Context<'_, S>) {
+            let span = ctx.span(id).unwrap();
+            let parent = span.parent().map(|parent| parent.name().to_string());
+            self.0
+                .lock()
+                .unwrap()
+                .push((span.name().to_string(), parent));
+        }
+    }
+
+    fn tool_use(id: &str) -> ToolUse {
+        ToolUse {
+            id: id.to_string(),
+            name: "Bash".to_string(),
+            input: serde_json::json!({"command": "ls"}),
+        }
+    }
+
+    #[test]
+    fn test_span_hierarchy() {
+        let hierarchy = Hierarchy::default();
+        let subscriber = tracing_subscriber::registry().with(hierarchy.clone());
+        tracing::subscriber::with_default(subscriber, || {
+            let mut trace = SessionTrace::default();
+            trace.start_session("s1", "claude");
+            trace.start_tool_call(&tool_use("toolu_1"));
+            trace.record_decision("toolu_1", "escalate");
+            let escalation = trace.escalation("toolu_1", "ai");
+            SessionTrace::finish_escalation(&escalation, Duration::from_millis(5), "allow");
+            trace.finish_tool_call("toolu_1", Some(Duration::from_millis(9)));
+            trace.start_tool_call(&tool_use("toolu_2"));
+            trace.finish("completed");
+        });
+
+        let session = Some("session".to_string());
+        assert_eq!(
+            *hierarchy.0.lock().unwrap(),
+            vec![
+                ("session".to_string(), None),
+                ("tool_call".to_string(), session.clone()),
+                ("escalation".to_string(), Some("tool_call".to_string())),
+                ("tool_call".to_string(), session),
+            ]
+        );
+    }
+}
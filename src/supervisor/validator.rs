@@ -0,0 +1,127 @@
+//! Custom checks on a tool's input, registered on the policy engine.
+
+use std::collections::BTreeMap;
+use std::fmt;
+use std::sync::Arc;
+
+use super::PolicyDecision;
+
+/// A check on the input of calls to one tool, such as the hosts `WebFetch`
+/// may reach.
+///
+/// Validators are registered per tool with
+/// [`PolicyEngine::register_validator`](super::PolicyEngine::register_validator).
+/// They run after the deny list, the blocklist and the path and edit rules,
+/// and before the allow list and the policy level decide the call. A
+/// validator returns [`PolicyDecision::Allow`] when it has no objection;
+/// the first validator returning anything else decides the call, and the
+/// validators registered after it do not run.
+pub trait ToolValidator: Send + Sync {
+    /// Decide a call by its input.
+    fn validate(&self, tool_input: &serde_json::Value) -> PolicyDecision;
+
+    /// Name shown as the rule of the decisions it makes.
+    fn name(&self) -> &'static str {
+        "custom"
+    }
+}
+
+/// Validators by the tool they check, in registration order.
+#[derive(Clone, Default)]
+pub(crate) struct ToolValidators {
+    by_tool: BTreeMap<String, Vec<Arc<dyn ToolValidator>>>,
+}
+
+impl ToolValidators {
+    pub(crate) fn register(&mut self, tool_name: String, validator: Arc<dyn ToolValidator>) {
+        self.by_tool.entry(tool_name).or_default().push(validator);
+    }
+
+    /// Whether any validator checks `tool_name`.
+    pub(crate) fn has(&self, tool_name: &str) -> bool {
+        self.by_tool.contains_key(tool_name)
+    }
+
+    /// The first objection to a call, tagged with the validator's rule.
+    pub(crate) fn validate(
+        &self,
+        tool_name: &str,
+        tool_input: &serde_json::Value,
+    ) -> Option<PolicyDecision> {
+        self.by_tool.get(tool_name)?.iter().find_map(|validator| {
+            match validator.validate(tool_input) {
+                PolicyDecision::Allow => None,
+                PolicyDecision::Deny(reason) if reason.rule_id.is_none() => {
+                    Some(PolicyDecision::Deny(
+                        reason.with_rule(format!("validator: {}", validator.name())),
+                    ))
+                }
+                decision => Some(decision),
+            }
+        })
+    }
+}
+
+impl fmt::Debug for ToolValidators {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.debug_map()
+            .entries(self.by_tool.iter().map(|(tool, validators)| {
+                let names: Vec<&str> = validators.iter().map(|v| v.name()).collect();
+                (tool, names)
+            }))
+            .finish()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use std::sync::atomic::{AtomicUsize, Ordering};
+
+    use super::*;
+    use serde_json::json;
+
+    struct Fixed(PolicyDecision, &'static str, AtomicUsize);
+
+    impl ToolValidator for Fixed {
+        fn validate(&self, _tool_input: &serde_json::Value) -> PolicyDecision {
+            self.2.fetch_add(1, Ordering::Relaxed);
+            self.0.clone()
+        }
+
+        fn name(&self) -> &'static str {
+            self.1
+        }
+    }
+
+    #[test]
+    fn test_validators_short_circuit_on_first_objection() {
+        let allow = Arc::new(Fixed(PolicyDecision::Allow, "allow", AtomicUsize::new(0)));
+        let deny = Arc::new(Fixed(
+            PolicyDecision::deny("no"),
+            "deny",
+            AtomicUsize::new(0),
+        ));
+        let escalate = Arc::new(Fixed(
+            PolicyDecision::Escalate("ask".to_string()),
+            "escalate",
+            AtomicUsize::new(0),
+        ));
+        let mut validators = ToolValidators::default();
+        validators.register("WebFetch".to_string(), allow.clone());
+        validators.register("WebFetch".to_string(), deny.clone());
+        validators.register("WebFetch".to_string(), escalate.clone());
+
+        let Some(PolicyDecision::Deny(reason)) = validators.validate("WebFetch", &json!({})) else {
+            panic!("expected a denial");
+        };
+        assert_eq!(reason.rule_id.as_deref(), Some("validator: deny"));
+        assert_eq!(allow.2.load(Ordering::Relaxed), 1);
+        assert_eq!(escalate.2.load(Ordering::Relaxed), 0);
+
+        assert!(validators.validate("WebSearch", &json!({})).is_none());
+        assert_eq!(
+            format!("{validators:?}"),
+            r#"{"WebFetch": ["allow", "deny", "escalate"]}"#
+        );
+    }
+}
@@ -0,0 +1,245 @@
+//! Post-hoc checks that approved tool calls ran as approved.
+//!
+//! The input of each approved `ToolUse` is kept, keyed by its ID, until the
+//! matching result arrives. Claude Code echoes part of what actually ran in
+//! the `tool_use_result` of the user event carrying the result, such as the
+//! command or the file path; a value differing from the approved input is
+//! reported as a [`ToolMismatch`].
+
+use std::collections::HashMap;
+use std::path::{Path, PathBuf};
+use std::time::Duration;
+
+use serde::Serialize;
+use serde_json::Value;
+use tokio::time::Instant;
+
+use super::protect::normalize;
+use crate::cli::{input_paths, ToolUse};
+
+/// How long an approved input is kept while waiting for its result.
+pub const APPROVAL_TTL: Duration = Duration::from_mins(30);
+
+/// Number of mismatches in a session after which it is escalated.
+pub const MISMATCH_ESCALATE_AFTER: usize = 3;
+
+/// A tool call whose result shows something other than what was approved.
+#[derive(Debug, Clone, Serialize)]
+pub struct ToolMismatch {
+    /// ID of the tool call.
+    pub tool_use_id: String,
+    /// Name of the tool.
+    pub tool_name: String,
+    /// Field that differs: `command` or `path`.
+    pub field: &'static str,
+    /// Value that was approved.
+    pub approved: String,
+    /// Value the result reports.
+    pub executed: String,
+    /// Input the tool was approved with.
+    pub input: Value,
+}
+
+impl ToolMismatch {
+    /// Human-readable description used in logs and escalation reasons.
+    #[must_use]
+    pub fn describe(&self) -> String {
+        format!(
+            "Tool {} ran with a different {} than approved: approved {:?}, ran {:?}",
+            self.tool_name, self.field, self.approved, self.executed
+        )
+    }
+}
+
+/// Approved inputs waiting for their results, keyed by `tool_use_id`.
+#[derive(Debug, Clone, Default)]
+pub struct ApprovalLedger {
+    approved: HashMap<String, (ToolUse, Instant)>,
+}
+
+impl ApprovalLedger {
+    /// Record an approved tool call, dropping approvals older than
+    /// [`APPROVAL_TTL`].
+    pub fn record(&mut self, tool_use: &ToolUse, now: Instant) {
+        self.approved.retain(|_, (_, approved_at)| {
+            now.saturating_duration_since(*approved_at) < APPROVAL_TTL
+        });
+        self.approved
+            .insert(tool_use.id.clone(), (tool_use.clone(), now));
+    }
+
+    /// Number of approvals waiting for a result.
+    #[must_use]
+    pub fn pending_count(&self) -> usize {
+        self.approved.len()
+    }
+
+    /// Compare the result of a tool call with its approved input.
+    ///
+    /// `executed` is the `tool_use_result` echoed with the result, if any;
+    /// relative paths are resolved against `cwd`. The approval is consumed
+    /// either way. Returns `None` when nothing was approved under this ID,
+    /// the approval expired, or the echoed fields match.
+    pub fn verify(
+        &mut self,
+        tool_use_id: &str,
+        executed: Option<&Value>,
+        cwd: Option<&Path>,
+        now: Instant,
+    ) -> Option<ToolMismatch> {
+        let (tool_use, approved_at) = self.approved.remove(tool_use_id)?;
+        if now.saturating_duration_since(approved_at) >= APPROVAL_TTL {
+            return None;
+        }
+        let executed = executed?;
+
+        let mismatch = |field, approved: String, ran: String| ToolMismatch {
+            tool_use_id: tool_use.id.clone(),
+            tool_name: tool_use.name.clone(),
+            field,
+            approved,
+            executed: ran,
+            input: tool_use.input.clone(),
+        };
+
+        if let (Some(approved), Some(ran)) = (
+            tool_use.input.get("command").and_then(Value::as_str),
+            executed.get("command").and_then(Value::as_str),
+        ) {
+            if approved.trim() != ran.trim() {
+                return Some(mismatch("command", approved.to_string(), ran.to_string()));
+            }
+        }
+
+        if let (Some(approved), Some(ran)) =
+            (input_paths(&tool_use.input).next(), echoed_path(executed))
+        {
+            if resolve(approved, cwd) != resolve(ran, cwd) {
+                return Some(mismatch("path", approved.to_string(), ran.to_string()));
+            }
+        }
+
+        None
+    }
+}
+
+/// File path echoed in a tool result, for the tools that report one.
+fn echoed_path(executed: &Value) -> Option<&str> {
+    ["filePath", "notebook_path", "file_path"]
+        .iter()
+        .find_map(|key| executed.get(*key))
+        .or_else(|| executed.get("file").and_then(|file| file.get("filePath")))
+        .and_then(Value::as_str)
+}
+
+fn resolve(path: &str, cwd: Option<&Path>) -> PathBuf {
+    let path = Path::new(path);
+    match cwd {
+        Some(cwd) if path.is_relative() => normalize(&cwd.join(path)),
+        _ => normalize(path),
+    }
+}
+
+/// IDs of the tool calls whose results a user message carries.
+#[must_use]
+pub fn tool_result_ids(message: &Value) -> Vec<&str> {
+    message
+        .get("content")
+        .and_then(Value::as_array)
+        .into_iter()
+        .flatten()
+        .filter(|block| block.get("type").and_then(Value::as_str) == Some("tool_result"))
+        .filter_map(|block| block.get("tool_use_id").and_then(Value::as_str))
+        .collect()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use serde_json::json;
+
+    fn tool_use(id: &str, name: &str, input: Value) -> ToolUse {
+        ToolUse {
+            id: id.to_string(),
+            name: name.to_string(),
+            input,
+        }
+    }
+
+    #[test]
+    fn test_changed_command_and_path_are_flagged() {
+        let now = Instant::now();
+        let mut ledger = ApprovalLedger::default();
+        ledger.record(&tool_use("t1", "Bash", json!({"command": "ls -la"})), now);
+        ledger.record(
+            &tool_use("t2", "Edit", json!({"file_path": "src/lib.rs"})),
+            now,
+        );
+
+        let mismatch = ledger
+            .verify("t1", Some(&json!({"command": "rm -rf ."})), None, now)
+            .unwrap();
+        assert_eq!(mismatch.field, "command");
+        assert_eq!(mismatch.executed, "rm -rf .");
+        assert!(mismatch.describe().contains("approved \"ls -la\""));
+
+        let mismatch = ledger
+            .verify(
+                "t2",
+                Some(&json!({"filePath": "/work/.env"})),
+                Some(Path::new("/work")),
+                now,
+            )
+            .unwrap();
+        assert_eq!(mismatch.field, "path");
+        assert_eq!(ledger.pending_count(), 0);
+    }
+
+    #[test]
+    fn test_matching_results_pass() {
+        let now = Instant::now();
+        let cwd = Some(Path::new("/work"));
+        let mut ledger = ApprovalLedger::default();
+        ledger.record(
+            &tool_use("t1", "Read", json!({"file_path": "./src/lib.rs"})),
+            now,
+        );
+        ledger.record(&tool_use("t2", "Bash", json!({"command": "ls"})), now);
+        ledger.record(&tool_use("t3", "Bash", json!({"command": "ls"})), now);
+
+        let read = json!({"type": "text", "file": {"filePath": "/work/src/lib.rs"}});
+        assert!(ledger.verify("t1", Some(&read), cwd, now).is_none());
+        let bash = json!({"stdout": "Cargo.toml", "stderr": ""});
+        assert!(ledger.verify("t2", Some(&bash), cwd, now).is_none());
+        assert!(ledger.verify("t3", None, cwd, now).is_none());
+        assert!(ledger.verify("unknown", Some(&bash), cwd, now).is_none());
+    }
+
+    #[test]
+    fn test_expired_approvals_are_dropped() {
+        let start = Instant::now();
+        let mut ledger = ApprovalLedger::default();
+        ledger.record(&tool_use("t1", "Bash", json!({"command": "ls"})), start);
+
+        let later = start + APPROVAL_TTL;
+        let ran = json!({"command": "rm -rf /"});
+        assert!(ledger.verify("t1", Some(&ran), None, later).is_none());
+
+        ledger.record(&tool_use("t2", "Bash", json!({"command": "ls"})), start);
+        ledger.record(&tool_use("t3", "Bash", json!({"command": "ls"})), later);
+        assert_eq!(ledger.pending_count(), 1);
+    }
+
+    #[test]
+    fn test_tool_result_ids() {
+        let message = json!({
+            "role": "user",
+            "content": [
+                {"type": "tool_result", "tool_use_id": "t1", "content": "ok"},
+                {"type": "text", "text": "note"}
+            ]
+        });
+        assert_eq!(tool_result_ids(&message), ["t1"]);
+        assert!(tool_result_ids(&json!({"content": "plain"})).is_empty());
+    }
+}
@@ -0,0 +1,275 @@
+//! Spans timing each stage of supervision, and a trace file to view them.
+//!
+//! Every stage a session's time can go to opens a span named in [`SPANS`]:
+//! reading Claude's stream, deciding tool calls, asking the AI supervisor,
+//! writing the audit log, printing and answering hooks and IPC requests.
+//! Stage spans carry `session_id` and `tool_use_id` where they are known.
+//! `--trace-file out.json` installs a [`ChromeTraceLayer`] writing the
+//! closed spans in the Chrome trace event format, which Perfetto and
+//! `chrome://tracing` load.
+
+use std::fmt;
+use std::fs::File;
+use std::io::{self, BufWriter, Write};
+use std::path::Path;
+use std::sync::atomic::{AtomicU64, Ordering};
+use std::sync::{Arc, Mutex};
+use std::time::{Duration, Instant};
+
+use serde_json::{json, Map, Value};
+use tracing::field::{Field, Visit};
+use tracing::span::{Attributes, Id, Record};
+use tracing::Subscriber;
+use tracing_subscriber::layer::{Context, Layer};
+use tracing_subscriber::registry::LookupSpan;
+
+/// A supervised session, from its init event to its outcome.
+pub const SPAN_SESSION: &str = "session";
+/// A tool call, from its request to its result.
+pub const SPAN_TOOL_CALL: &str = "tool_call";
+/// Waiting for the AI supervisor or an operator to decide an escalation.
+pub const SPAN_ESCALATION: &str = "escalation";
+/// Parsing a line of Claude's stream-json output.
+pub const SPAN_STREAM_PARSE: &str = "stream.parse";
+/// Handling one event of Claude's stream in the runner.
+pub const SPAN_RUNNER_EVENT: &str = "runner.event";
+/// Printing an event to the terminal.
+pub const SPAN_DISPLAY: &str = "display";
+/// Deciding a tool call against the policy and runner rules.
+pub const SPAN_POLICY_EVALUATE: &str = "policy.evaluate";
+/// Writing an audit record.
+pub const SPAN_AUDIT_WRITE: &str = "audit.write";
+/// Answering a hook invocation.
+pub const SPAN_HOOK_HANDLE: &str = "hook.handle";
+/// Answering one request on the supervisor socket.
+pub const SPAN_IPC_REQUEST: &str = "ipc.request";
+
+/// Every span the supervisor opens, with what it times.
+pub const SPANS: &[(&str, &str)] = &[
+    (SPAN_SESSION, "a supervised session"),
+    (SPAN_TOOL_CALL, "a tool call, from request to result"),
+    (SPAN_ESCALATION, "an escalation waiting for a decision"),
+    (SPAN_STREAM_PARSE, "parsing a line of Claude's output"),
+    (SPAN_RUNNER_EVENT, "handling a stream event in the runner"),
+    (SPAN_DISPLAY, "printing an event"),
+    (SPAN_POLICY_EVALUATE, "deciding a tool call"),
+    (SPAN_AUDIT_WRITE, "writing an audit record"),
+    (SPAN_HOOK_HANDLE, "answering a hook"),
+    (SPAN_IPC_REQUEST, "answering an IPC request"),
+];
+
+/// Thread numbers in trace events, in order of first use.
+static NEXT_THREAD: AtomicU64 = AtomicU64::new(1);
+
+thread_local! {
+    static THREAD: u64 = NEXT_THREAD.fetch_add(1, Ordering::Relaxed);
+}
+
+/// When a span was opened, and its fields.
+struct SpanTiming {
+    start: Instant,
+    thread: u64,
+    args: Map<String, Value>,
+}
+
+/// Collects span fields as trace event arguments.
+struct Args<'a>(&'a mut Map<String, Value>);
+
+impl Visit for Args<'_> {
+    fn record_str(&mut self, field: &Field, value: &str) {
+        self.0.insert(field.name().to_string(), Value::from(value));
+    }
+
+    fn record_u64(&mut self, field: &Field, value: u64) {
+        self.0.insert(field.name().to_string(), Value::from(value));
+    }
+
+    fn record_i64(&mut self, field: &Field, value: i64) {
+        self.0.insert(field.name().to_string(), Value::from(value));
+    }
+
+    fn record_bool(&mut self, field: &Field, value: bool) {
+        self.0.insert(field.name().to_string(), Value::from(value));
+    }
+
+    fn record_debug(&mut self, field: &Field, value: &dyn fmt::Debug) {
+        self.0
+            .insert(field.name().to_string(), Value::from(format!("{value:?}")));
+    }
+}
+
+/// The trace file being written.
+#[derive(Debug)]
+struct TraceWriter {
+    out: BufWriter<File>,
+    events: usize,
+    finished: bool,
+}
+
+impl TraceWriter {
+    /// Append one event, flushed so that the file is readable up to it if
+    /// the process exits without finishing it.
+    fn write(&mut self, event: &Value) -> io::Result<()> {
+        if self.finished {
+            return Ok(());
+        }
+        let separator = if self.events == 0 { "" } else { "," };
+        writeln!(self.out, "{separator}{event}")?;
+        self.events += 1;
+        self.out.flush()
+    }
+
+    /// Close the event array.
+    fn finish(&mut self) -> io::Result<()> {
+        if std::mem::replace(&mut self.finished, true) {
+            return Ok(());
+        }
+        writeln!(self.out, "]")?;
+        self.out.flush()
+    }
+}
+
+/// Layer writing closed spans to a file as Chrome trace events.
+///
+/// Each span is written as one complete (`"ph": "X"`) event on the thread
+/// that opened it, with its fields as arguments. The event array is closed
+/// when the [`TraceFileGuard`] is dropped; a file left open by an exit is
+/// still loaded, as the format allows.
+#[derive(Debug, Clone)]
+pub struct ChromeTraceLayer {
+    writer: Arc<Mutex<TraceWriter>>,
+    origin: Instant,
+}
+
+/// Closes the trace file of a [`ChromeTraceLayer`] when dropped.
+#[derive(Debug)]
+pub struct TraceFileGuard {
+    writer: Arc<Mutex<TraceWriter>>,
+}
+
+impl ChromeTraceLayer {
+    /// Create a layer writing to `path`, replacing the file.
+    ///
+    /// # Errors
+    ///
+    /// Returns an error if the file cannot be created.
+    pub fn create(path: &Path) -> io::Result<(Self, TraceFileGuard)> {
+        let mut out = BufWriter::new(File::create(path)?);
+        writeln!(out, "[")?;
+        out.flush()?;
+        let writer = Arc::new(Mutex::new(TraceWriter {
+            out,
+            events: 0,
+            finished: false,
+        }));
+        let guard = TraceFileGuard {
+            writer: Arc::clone(&writer),
+        };
+        Ok((
+            Self {
+                writer,
+                origin: Instant::now(),
+            },
+            guard,
+        ))
+    }
+
+    fn micros(duration: Duration) -> u64 {
+        u64::try_from(duration.as_micros()).unwrap_or(u64::MAX)
+    }
+}
+
+impl<S> Layer<S> for ChromeTraceLayer
+where
+    S: Subscriber + for<'a> LookupSpan<'a>,
+{
+    fn on_new_span(&self, attrs: &Attributes<'_>, id: &Id, ctx: Context<'_, S>) {
+        let Some(span) = ctx.span(id) else {
+            return;
+        };
+        let mut args = Map::new();
+        attrs.record(&mut Args(&mut args));
+        span.extensions_mut().insert(SpanTiming {
+            start: Instant::now(),
+            thread: THREAD.with(|thread| *thread),
+            args,
+        });
+    }
+
+    fn on_record(&self, id: &Id, values: &Record<'_>, ctx: Context<'_, S>) {
+        if let Some(span) = ctx.span(id) {
+            if let Some(timing) = span.extensions_mut().get_mut::<SpanTiming>() {
+                values.record(&mut Args(&mut timing.args));
+            }
+        }
+    }
+
+    fn on_close(&self, id: Id, ctx: Context<'_, S>) {
+        let Some(span) = ctx.span(&id) else {
+            return;
+        };
+        let Some(timing) = span.extensions_mut().remove::<SpanTiming>() else {
+            return;
+        };
+        let event = json!({
+            "name": span.name(),
+            "cat": span.metadata().target(),
+            "ph": "X",
+            "ts": Self::micros(timing.start.saturating_duration_since(self.origin)),
+            "dur": Self::micros(timing.start.elapsed()),
+            "pid": std::process::id(),
+            "tid": timing.thread,
+            "args": timing.args,
+        });
+        let result = self.writer.lock().expect("Mutex poisoned").write(&event);
+        if let Err(e) = result {
+            // Logging from inside the subscriber would recurse
+            eprintln!("Failed to write trace event: {e}");
+        }
+    }
+}
+
+impl Drop for TraceFileGuard {
+    fn drop(&mut self) {
+        if let Ok(mut writer) = self.writer.lock() {
+            if let Err(e) = writer.finish() {
+                eprintln!("Failed to finish trace file: {e}");
+            }
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use tracing_subscriber::layer::SubscriberExt;
+
+    #[test]
+    fn test_chrome_trace_layer_writes_complete_events() {
+        let dir = tempfile::tempdir().unwrap();
+        let path = dir.path().join("trace.json");
+        let (layer, guard) = ChromeTraceLayer::create(&path).unwrap();
+        let subscriber = tracing_subscriber::registry().with(layer);
+        tracing::subscriber::with_default(subscriber, || {
+            let outer = tracing::info_span!(SPAN_RUNNER_EVENT, session_id = "s1").entered();
+            let inner = tracing::info_span!(
+                SPAN_POLICY_EVALUATE,
+                tool_use_id = "toolu_1",
+                decision = tracing::field::Empty,
+            );
+            inner.in_scope(|| inner.record("decision", "allow"));
+            drop(inner);
+            drop(outer);
+        });
+        drop(guard);
+
+        let events: Vec<Value> =
+            serde_json::from_str(&std::fs::read_to_string(&path).unwrap()).unwrap();
+        let names: Vec<&str> = events.iter().filter_map(|e| e["name"].as_str()).collect();
+        assert_eq!(names, [SPAN_POLICY_EVALUATE, SPAN_RUNNER_EVENT]);
+        assert_eq!(events[0]["ph"], "X");
+        assert_eq!(events[0]["args"]["decision"], "allow");
+        assert_eq!(events[1]["args"]["session_id"], "s1");
+        assert!(events[1]["dur"].as_u64() >= events[0]["dur"].as_u64());
+    }
+}
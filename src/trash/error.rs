@@ -0,0 +1,31 @@
+//! Trash error types.
+
+use std::path::PathBuf;
+
+/// Errors that can occur during trash operations.
+#[derive(thiserror::Error, Debug)]
+pub enum TrashError {
+    /// Session ID cannot be used as a directory name.
+    #[error("Invalid session ID: {0}")]
+    InvalidSession(String),
+
+    /// Path is not inside the project.
+    #[error("{0} is not inside the project")]
+    OutsideProject(PathBuf),
+
+    /// Nothing in the trash matches the request.
+    #[error("No trashed {path} in session {session}")]
+    NotFound { session: String, path: PathBuf },
+
+    /// A file is in the way of a restore or escrow.
+    #[error("{0} already exists")]
+    Exists(PathBuf),
+
+    /// I/O error on a trashed or project file.
+    #[error("I/O error on {path}: {source}")]
+    Io {
+        path: PathBuf,
+        #[source]
+        source: std::io::Error,
+    },
+}
@@ -0,0 +1,12 @@
+//! Trash for files deleted under escrow.
+//!
+//! With escrow enabled, deletions inside the project are moved to
+//! `<trash>/<session>/<path relative to the project>` instead of being
+//! deleted (see [`Escrow`](crate::supervisor::Escrow)), from where they can
+//! be listed, restored and finally deleted with `claude-supervisor trash`.
+
+mod error;
+mod store;
+
+pub use error::TrashError;
+pub use store::{TrashEntry, TrashStore};
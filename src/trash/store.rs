@@ -0,0 +1,419 @@
+//! Trash directory store.
+//!
+//! Layout of a session directory:
+//!
+//! ```text
+//! <root>/<session>/<path>    a trashed file or directory, at its path
+//!                            relative to the project
+//! ```
+
+use std::path::{Path, PathBuf};
+
+use crate::config::EscrowConfig;
+use crate::supervisor::normalize;
+
+use super::TrashError;
+
+/// A trashed file.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct TrashEntry {
+    /// Session that trashed the file.
+    pub session: String,
+    /// Path of the file relative to the project.
+    pub path: PathBuf,
+    /// File size in bytes.
+    pub size: u64,
+}
+
+/// Store of trashed files grouped by session.
+#[derive(Debug, Clone)]
+pub struct TrashStore {
+    root: PathBuf,
+    project: PathBuf,
+}
+
+impl TrashStore {
+    /// Create a store rooted at `root` for the files of `project`.
+    #[must_use]
+    pub fn new(root: impl Into<PathBuf>, project: impl Into<PathBuf>) -> Self {
+        let project = normalize(&project.into());
+        Self {
+            root: normalize(&project.join(root.into())),
+            project,
+        }
+    }
+
+    /// Create a store from configuration for the project at `cwd`.
+    #[must_use]
+    pub fn from_config(config: &EscrowConfig, cwd: &Path) -> Self {
+        Self::new(&config.dir, cwd)
+    }
+
+    /// Get the store root.
+    #[must_use]
+    pub fn root(&self) -> &Path {
+        &self.root
+    }
+
+    /// Get the project whose files are trashed.
+    #[must_use]
+    pub fn project(&self) -> &Path {
+        &self.project
+    }
+
+    /// Directory holding a session's trashed files.
+    ///
+    /// # Errors
+    ///
+    /// Returns `TrashError::InvalidSession` if the session ID cannot be used
+    /// as a directory name.
+    pub fn session_dir(&self, session: &str) -> Result<PathBuf, TrashError> {
+        let valid = !session.is_empty()
+            && session
+                .chars()
+                .all(|c| c.is_ascii_alphanumeric() || c == '-' || c == '_');
+        if !valid {
+            return Err(TrashError::InvalidSession(session.to_string()));
+        }
+        Ok(self.root.join(session))
+    }
+
+    /// Path of a project file relative to the project.
+    ///
+    /// # Errors
+    ///
+    /// Returns `TrashError::OutsideProject` unless `path`, resolved against
+    /// the project, is strictly inside it.
+    pub fn relative(&self, path: &Path) -> Result<PathBuf, TrashError> {
+        let absolute = normalize(&self.project.join(path));
+        match absolute.strip_prefix(&self.project) {
+            Ok(relative) if !relative.as_os_str().is_empty() => Ok(relative.to_path_buf()),
+            _ => Err(TrashError::OutsideProject(absolute)),
+        }
+    }
+
+    /// Where a session puts a project file in the trash.
+    ///
+    /// # Errors
+    ///
+    /// Returns an error if the session ID is invalid or the path is not
+    /// inside the project.
+    pub fn trashed_path(&self, session: &str, path: &Path) -> Result<PathBuf, TrashError> {
+        Ok(self.session_dir(session)?.join(self.relative(path)?))
+    }
+
+    /// Sessions with trashed files, sorted.
+    ///
+    /// # Errors
+    ///
+    /// Returns an error if the trash directory cannot be read.
+    pub fn sessions(&self) -> Result<Vec<String>, TrashError> {
+        let mut sessions: Vec<String> = read_dir(&self.root)?
+            .into_iter()
+            .filter(|path| path.is_dir())
+            .filter_map(|path| Some(path.file_name()?.to_str()?.to_string()))
+            .filter(|session| self.session_dir(session).is_ok())
+            .collect();
+        sessions.sort();
+        Ok(sessions)
+    }
+
+    /// List the trashed files of one session or every session, sorted by
+    /// session and path.
+    ///
+    /// # Errors
+    ///
+    /// Returns an error if the session ID is invalid or the trash cannot be
+    /// read.
+    pub fn list(&self, session: Option<&str>) -> Result<Vec<TrashEntry>, TrashError> {
+        let sessions = match session {
+            Some(session) => vec![session.to_string()],
+            None => self.sessions()?,
+        };
+        let mut entries = Vec::new();
+        for session in sessions {
+            let dir = self.session_dir(&session)?;
+            let mut files = Vec::new();
+            collect_files(&dir, &mut files)?;
+            entries.extend(files.into_iter().filter_map(|(path, size)| {
+                Some(TrashEntry {
+                    session: session.clone(),
+                    path: path.strip_prefix(&dir).ok()?.to_path_buf(),
+                    size,
+                })
+            }));
+        }
+        Ok(entries)
+    }
+
+    /// Copy a project file into a session's trash, keeping its content
+    /// when it is about to be overwritten.
+    ///
+    /// # Errors
+    ///
+    /// Returns `TrashError::Exists` if the session already trashed the
+    /// file, or an error if it cannot be copied.
+    pub fn copy_in(&self, session: &str, path: &Path) -> Result<TrashEntry, TrashError> {
+        let relative = self.relative(path)?;
+        let source = self.project.join(&relative);
+        let target = self.session_dir(session)?.join(&relative);
+        if target.symlink_metadata().is_ok() {
+            return Err(TrashError::Exists(target));
+        }
+        if let Some(parent) = target.parent() {
+            std::fs::create_dir_all(parent).map_err(|e| io_error(parent, e))?;
+        }
+        let size = std::fs::copy(&source, &target).map_err(|e| io_error(&source, e))?;
+        Ok(TrashEntry {
+            session: session.to_string(),
+            path: relative,
+            size,
+        })
+    }
+
+    /// Move a session's trashed files back into the project and return the
+    /// restored paths.
+    ///
+    /// With a `path`, only that file or directory is restored. A trashed
+    /// directory is merged into one that exists again.
+    ///
+    /// # Errors
+    ///
+    /// Returns `TrashError::NotFound` if nothing matches, or
+    /// `TrashError::Exists` if a file is in the way. Files restored before
+    /// the error stay restored.
+    pub fn restore(&self, session: &str, path: Option<&Path>) -> Result<Vec<PathBuf>, TrashError> {
+        let dir = self.session_dir(session)?;
+        let relative = path.map(|path| self.relative(path)).transpose()?;
+        let sources = match &relative {
+            Some(relative) => vec![dir.join(relative)],
+            None => read_dir(&dir)?,
+        };
+        if sources.is_empty()
+            || sources
+                .iter()
+                .any(|source| source.symlink_metadata().is_err())
+        {
+            return Err(TrashError::NotFound {
+                session: session.to_string(),
+                path: relative.unwrap_or_default(),
+            });
+        }
+
+        let mut restored = Vec::new();
+        for source in sources {
+            let Ok(relative) = source.strip_prefix(&dir) else {
+                continue;
+            };
+            move_back(&source, &self.project.join(relative), &mut restored)?;
+            self.prune(&source, &dir);
+        }
+        Ok(restored)
+    }
+
+    /// Delete the trashed files of one session or every session and return
+    /// how many were deleted.
+    ///
+    /// # Errors
+    ///
+    /// Returns an error if the session ID is invalid or the files cannot be
+    /// deleted.
+    pub fn empty(&self, session: Option<&str>) -> Result<usize, TrashError> {
+        let count = self.list(session)?.len();
+        let dir = match session {
+            Some(session) => self.session_dir(session)?,
+            None => self.root.clone(),
+        };
+        match std::fs::remove_dir_all(&dir) {
+            Ok(()) => Ok(count),
+            Err(e) if e.kind() == std::io::ErrorKind::NotFound => Ok(0),
+            Err(e) => Err(io_error(&dir, e)),
+        }
+    }
+
+    /// Remove the directories left empty above a restored path, up to the
+    /// store root.
+    fn prune(&self, restored: &Path, session_dir: &Path) {
+        let mut dir = restored.parent();
+        while let Some(current) = dir {
+            if current == self.root || !current.starts_with(session_dir) {
+                break;
+            }
+            if std::fs::remove_dir(current).is_err() {
+                break;
+            }
+            dir = current.parent();
+        }
+    }
+}
+
+/// Move a trashed file or directory to `target`, merging a directory into
+/// an existing one.
+fn move_back(source: &Path, target: &Path, restored: &mut Vec<PathBuf>) -> Result<(), TrashError> {
+    match target.symlink_metadata() {
+        Err(e) if e.kind() == std::io::ErrorKind::NotFound => {
+            if let Some(parent) = target.parent() {
+                std::fs::create_dir_all(parent).map_err(|e| io_error(parent, e))?;
+            }
+            std::fs::rename(source, target).map_err(|e| io_error(source, e))?;
+            restored.push(target.to_path_buf());
+            Ok(())
+        }
+        Ok(metadata) if metadata.is_dir() && source.is_dir() => {
+            for child in read_dir(source)? {
+                if let Some(name) = child.file_name() {
+                    move_back(&child, &target.join(name), restored)?;
+                }
+            }
+            std::fs::remove_dir(source).map_err(|e| io_error(source, e))
+        }
+        Ok(_) => Err(TrashError::Exists(target.to_path_buf())),
+        Err(e) => Err(io_error(target, e)),
+    }
+}
+
+/// Paths in a directory, sorted, or none if it does not exist.
+fn read_dir(dir: &Path) -> Result<Vec<PathBuf>, TrashError> {
+    let entries = match std::fs::read_dir(dir) {
+        Ok(entries) => entries,
+        Err(e) if e.kind() == std::io::ErrorKind::NotFound => return Ok(Vec::new()),
+        Err(e) => return Err(io_error(dir, e)),
+    };
+    let mut paths = entries
+        .map(|entry| {
+            entry
+                .map(|entry| entry.path())
+                .map_err(|e| io_error(dir, e))
+        })
+        .collect::<Result<Vec<_>, _>>()?;
+    paths.sort();
+    Ok(paths)
+}
+
+/// Collect the files under `path` with their sizes, not following links.
+fn collect_files(path: &Path, files: &mut Vec<(PathBuf, u64)>) -> Result<(), TrashError> {
+    for child in read_dir(path)? {
+        let metadata = child.symlink_metadata().map_err(|e| io_error(&child, e))?;
+        if metadata.is_dir() {
+            collect_files(&child, files)?;
+        } else {
+            files.push((child, metadata.len()));
+        }
+    }
+    Ok(())
+}
+
+fn io_error(path: &Path, source: std::io::Error) -> TrashError {
+    TrashError::Io {
+        path: path.to_path_buf(),
+        source,
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn setup() -> (tempfile::TempDir, TrashStore) {
+        let dir = tempfile::tempdir().unwrap();
+        let store = TrashStore::new(".claude-supervisor/trash", dir.path());
+        (dir, store)
+    }
+
+    fn trash(store: &TrashStore, session: &str, path: &str, content: &str) {
+        let file = store.trashed_path(session, Path::new(path)).unwrap();
+        std::fs::create_dir_all(file.parent().unwrap()).unwrap();
+        std::fs::write(file, content).unwrap();
+    }
+
+    #[test]
+    fn test_list_sorts_by_session_and_path() {
+        let (_dir, store) = setup();
+        trash(&store, "s2", "b.txt", "b");
+        trash(&store, "s1", "src/z.rs", "zz");
+        trash(&store, "s1", "src/a.rs", "a");
+
+        let listed: Vec<(String, PathBuf, u64)> = store
+            .list(None)
+            .unwrap()
+            .into_iter()
+            .map(|entry| (entry.session, entry.path, entry.size))
+            .collect();
+        assert_eq!(
+            listed,
+            [
+                ("s1".into(), "src/a.rs".into(), 1),
+                ("s1".into(), "src/z.rs".into(), 2),
+                ("s2".into(), "b.txt".into(), 1),
+            ]
+        );
+        assert_eq!(store.list(Some("s2")).unwrap().len(), 1);
+        assert!(store.list(Some("missing")).unwrap().is_empty());
+        assert!(matches!(
+            store.list(Some("../etc")),
+            Err(TrashError::InvalidSession(_))
+        ));
+    }
+
+    #[test]
+    fn test_restore_merges_and_refuses_to_overwrite() {
+        let (dir, store) = setup();
+        trash(&store, "s1", "src/a.rs", "a");
+        trash(&store, "s1", "src/b.rs", "b");
+        trash(&store, "s1", "notes.txt", "old notes");
+        std::fs::create_dir_all(dir.path().join("src")).unwrap();
+        std::fs::write(dir.path().join("src/b.rs"), "new b").unwrap();
+
+        // One file, leaving no empty directories behind
+        let restored = store.restore("s1", Some(Path::new("notes.txt"))).unwrap();
+        assert_eq!(restored, [dir.path().join("notes.txt")]);
+        assert_eq!(
+            std::fs::read_to_string(dir.path().join("notes.txt")).unwrap(),
+            "old notes"
+        );
+
+        // A conflict stops the restore after the files before it
+        let err = store.restore("s1", None).unwrap_err();
+        assert!(matches!(err, TrashError::Exists(path) if path == dir.path().join("src/b.rs")));
+        assert_eq!(
+            std::fs::read_to_string(dir.path().join("src/a.rs")).unwrap(),
+            "a"
+        );
+        assert_eq!(
+            std::fs::read_to_string(dir.path().join("src/b.rs")).unwrap(),
+            "new b"
+        );
+
+        assert!(matches!(
+            store.restore("s1", Some(Path::new("missing.rs"))),
+            Err(TrashError::NotFound { .. })
+        ));
+        assert!(matches!(
+            store.restore("s1", Some(Path::new("../outside"))),
+            Err(TrashError::OutsideProject(_))
+        ));
+    }
+
+    #[test]
+    fn test_copy_in_and_empty() {
+        let (dir, store) = setup();
+        std::fs::write(dir.path().join("config.toml"), "key = 1").unwrap();
+
+        let entry = store.copy_in("s1", Path::new("config.toml")).unwrap();
+        assert_eq!(entry.path, PathBuf::from("config.toml"));
+        assert_eq!(entry.size, 7);
+        assert!(matches!(
+            store.copy_in("s1", Path::new("config.toml")),
+            Err(TrashError::Exists(_))
+        ));
+        trash(&store, "s2", "x", "x");
+
+        assert_eq!(store.sessions().unwrap(), ["s1", "s2"]);
+        assert_eq!(store.empty(Some("s1")).unwrap(), 1);
+        assert_eq!(store.sessions().unwrap(), ["s2"]);
+        assert_eq!(store.empty(None).unwrap(), 1);
+        assert!(store.list(None).unwrap().is_empty());
+        assert_eq!(store.empty(None).unwrap(), 0);
+        assert!(dir.path().join("config.toml").exists());
+    }
+}
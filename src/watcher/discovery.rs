@@ -84,6 +84,21 @@ pub fn find_session_by_id(sessions_dir: &Path, session_id: &str) -> Option<PathB
     }
 }
 
+/// Path of the transcript Claude Code writes for a session.
+///
+/// The file need not exist yet; Claude Code creates it with the first
+/// entry. Returns `None` if the home directory cannot be determined.
+#[must_use]
+pub fn session_transcript_path(project_path: &Path, session_id: &str) -> Option<PathBuf> {
+    let home = dirs::home_dir()?;
+    Some(
+        home.join(".claude")
+            .join("projects")
+            .join(project_path_hash(project_path))
+            .join(format!("{session_id}.jsonl")),
+    )
+}
+
 /// Discover the most recent session for a project.
 ///
 /// Convenience function that combines `find_project_sessions_dir` and
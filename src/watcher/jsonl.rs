@@ -125,6 +125,12 @@ pub struct AssistantMessage {
     pub content: Vec<ContentBlock>,
     #[serde(default)]
     pub model: Option<String>,
+    /// API message ID, shared by the entries of one response.
+    #[serde(default)]
+    pub id: Option<String>,
+    /// Token usage of the response, as in the stream's assistant events.
+    #[serde(default)]
+    pub usage: Option<serde_json::Value>,
 }
 
 /// Message content - can be plain text or structured blocks.
@@ -189,6 +195,46 @@ pub async fn parse_jsonl_file(path: &std::path::Path) -> std::io::Result<Vec<Jou
     Ok(parse_jsonl_content(&content))
 }
 
+/// Text of the last assistant entry that says anything, ignoring thinking
+/// and tool calls.
+#[must_use]
+pub fn last_assistant_text(entries: &[JournalEntry]) -> Option<String> {
+    entries.iter().rev().find_map(|entry| {
+        let JournalEntry::Assistant(assistant) = entry else {
+            return None;
+        };
+        let text = assistant
+            .message
+            .content
+            .iter()
+            .filter_map(|block| match block {
+                ContentBlock::Text { text } => Some(text.as_str()),
+                _ => None,
+            })
+            .collect::<Vec<_>>()
+            .join("\n");
+        (!text.trim().is_empty()).then_some(text)
+    })
+}
+
+/// Inputs of the assistant's calls to `tool`, oldest first.
+pub fn tool_use_inputs<'a>(
+    entries: &'a [JournalEntry],
+    tool: &'a str,
+) -> impl Iterator<Item = &'a serde_json::Value> {
+    entries
+        .iter()
+        .filter_map(|entry| match entry {
+            JournalEntry::Assistant(assistant) => Some(&assistant.message.content),
+            _ => None,
+        })
+        .flatten()
+        .filter_map(move |block| match block {
+            ContentBlock::ToolUse { name, input, .. } if name == tool => Some(input),
+            _ => None,
+        })
+}
+
 /// Extract text content from a message.
 impl MessageContent {
     /// Get the text content as a string.
@@ -14,6 +14,7 @@ mod tailer;
 pub use discovery::{
     discover_session, discover_subagent_files, extract_agent_id, find_latest_session,
     find_project_sessions_dir, find_session_by_id, find_subagents_dir, project_path_hash,
+    session_transcript_path,
 };
 pub use error::WatcherError;
 pub use jsonl::*;
@@ -0,0 +1,194 @@
+//! Running commands inside managed worktrees.
+
+use super::error::WorktreeError;
+use super::registry::WorktreeRegistry;
+use super::types::Worktree;
+
+/// Exit code reported for a command that could not be started, as in shells.
+pub const SPAWN_FAILED_EXIT_CODE: i32 = 127;
+
+/// Look up the managed worktree `name`.
+///
+/// # Errors
+///
+/// Returns [`WorktreeError::NotFound`], listing the managed worktrees, if
+/// none is named `name`.
+pub fn find_worktree<'a>(
+    registry: &'a WorktreeRegistry,
+    name: &str,
+) -> Result<&'a Worktree, WorktreeError> {
+    registry.get(name).ok_or_else(|| {
+        let mut names: Vec<_> = registry.list().iter().map(|wt| wt.name.as_str()).collect();
+        names.sort_unstable();
+        let managed = if names.is_empty() {
+            "no worktrees are managed".to_string()
+        } else {
+            format!("managed: {}", names.join(", "))
+        };
+        WorktreeError::NotFound(format!("'{name}' ({managed})"))
+    })
+}
+
+/// Run `command` in `worktree` with inherited stdio and return its exit code.
+///
+/// A command killed by a signal reports 128 plus the signal number, as
+/// shells do.
+///
+/// # Errors
+///
+/// Returns an error if the worktree directory no longer exists, `command`
+/// is empty, or the command cannot be started.
+pub async fn exec_in(worktree: &Worktree, command: &[String]) -> Result<i32, WorktreeError> {
+    if !worktree.path.is_dir() {
+        return Err(WorktreeError::NotFound(format!(
+            "'{}' ({} no longer exists)",
+            worktree.name,
+            worktree.path.display()
+        )));
+    }
+    let Some((program, args)) = command.split_first() else {
+        return Err(
+            std::io::Error::new(std::io::ErrorKind::InvalidInput, "No command given").into(),
+        );
+    };
+
+    let status = tokio::process::Command::new(program)
+        .args(args)
+        .current_dir(&worktree.path)
+        .status()
+        .await?;
+    Ok(exit_code(status))
+}
+
+/// Run `command` in each of `worktrees` in turn, ordered by name, printing
+/// a header before each.
+///
+/// Every worktree is visited even after a failure; a worktree the command
+/// cannot run in is reported and counts as failed.
+///
+/// Returns the first nonzero exit code, or 0 if the command succeeded
+/// everywhere.
+pub async fn exec_each<'a>(
+    worktrees: impl IntoIterator<Item = &'a Worktree>,
+    command: &[String],
+) -> i32 {
+    let mut worktrees: Vec<_> = worktrees.into_iter().collect();
+    worktrees.sort_by(|a, b| a.name.cmp(&b.name));
+
+    let mut result = 0;
+    for worktree in worktrees {
+        println!("==> {} ({})", worktree.name, worktree.path.display());
+        let code = match exec_in(worktree, command).await {
+            Ok(code) => code,
+            Err(e) => {
+                eprintln!("Failed to run command in worktree '{}': {e}", worktree.name);
+                SPAWN_FAILED_EXIT_CODE
+            }
+        };
+        if code != 0 {
+            eprintln!(
+                "Command exited with code {code} in worktree '{}'",
+                worktree.name
+            );
+            if result == 0 {
+                result = code;
+            }
+        }
+    }
+    result
+}
+
+#[cfg(unix)]
+fn exit_code(status: std::process::ExitStatus) -> i32 {
+    use std::os::unix::process::ExitStatusExt;
+
+    status
+        .code()
+        .or_else(|| status.signal().map(|signal| 128 + signal))
+        .unwrap_or(1)
+}
+
+#[cfg(not(unix))]
+fn exit_code(status: std::process::ExitStatus) -> i32 {
+    status.code().unwrap_or(1)
+}
+
+#[cfg(test)]
+mod tests {
+    use std::path::Path;
+
+    use super::*;
+
+    fn command(args: &[&str]) -> Vec<String> {
+        args.iter().map(ToString::to_string).collect()
+    }
+
+    fn registry(dir: &Path, names: &[&str]) -> WorktreeRegistry {
+        let mut registry = WorktreeRegistry::new();
+        for name in names {
+            let path = dir.join(name);
+            std::fs::create_dir(&path).unwrap();
+            registry.upsert(Worktree::new(*name, path, format!("supervisor/{name}")));
+        }
+        registry
+    }
+
+    #[tokio::test]
+    async fn test_exec_propagates_exit_code() {
+        let dir = tempfile::tempdir().unwrap();
+        let registry = registry(dir.path(), &["a"]);
+        let worktree = find_worktree(&registry, "a").unwrap();
+
+        assert_eq!(exec_in(worktree, &command(&["true"])).await.unwrap(), 0);
+        assert_eq!(exec_in(worktree, &command(&["false"])).await.unwrap(), 1);
+        let code = exec_in(worktree, &command(&["sh", "-c", "touch ran; exit 3"]))
+            .await
+            .unwrap();
+        assert_eq!(code, 3);
+        assert!(dir.path().join("a/ran").exists());
+    }
+
+    #[tokio::test]
+    async fn test_exec_each_visits_every_worktree() {
+        let dir = tempfile::tempdir().unwrap();
+        let registry = registry(dir.path(), &["a", "b", "c"]);
+
+        assert_eq!(exec_each(registry.list(), &command(&["true"])).await, 0);
+
+        // b fails; a and c still run
+        let script = "touch ran; [ \"$(basename \"$PWD\")\" != b ]";
+        let code = exec_each(registry.list(), &command(&["sh", "-c", script])).await;
+        assert_eq!(code, 1);
+        for name in ["a", "b", "c"] {
+            assert!(dir.path().join(name).join("ran").exists(), "{name}");
+        }
+    }
+
+    #[tokio::test]
+    async fn test_exec_failures() {
+        let dir = tempfile::tempdir().unwrap();
+        let registry = registry(dir.path(), &["b", "a"]);
+
+        let err = find_worktree(&registry, "missing").unwrap_err();
+        assert_eq!(
+            err.to_string(),
+            "Worktree not found: 'missing' (managed: a, b)"
+        );
+        let err = find_worktree(&WorktreeRegistry::new(), "missing").unwrap_err();
+        assert!(err.to_string().contains("no worktrees are managed"));
+
+        let worktree = find_worktree(&registry, "a").unwrap();
+        assert!(exec_in(worktree, &command(&["no-such-command-xyz"]))
+            .await
+            .is_err());
+        std::fs::remove_dir(&worktree.path).unwrap();
+        assert!(matches!(
+            exec_in(worktree, &command(&["true"])).await,
+            Err(WorktreeError::NotFound(_))
+        ));
+        assert_eq!(
+            exec_each(registry.list(), &command(&["true"])).await,
+            SPAWN_FAILED_EXIT_CODE
+        );
+    }
+}
@@ -0,0 +1,268 @@
+//! Worktree groups spanning several repositories.
+
+use std::collections::HashSet;
+use std::path::{Path, PathBuf};
+
+use crate::config::WorktreeConfig;
+
+use super::error::WorktreeError;
+use super::manager::WorktreeManager;
+use super::registry::WorktreeRegistry;
+use super::types::Worktree;
+
+/// Worktrees created together for one session, one per repository.
+///
+/// Every member is recorded in the registry of every repository in the
+/// group, so the whole group can be removed from any of them.
+#[derive(Debug, Clone)]
+pub struct WorktreeGroup {
+    /// Shared group ID.
+    id: String,
+    /// Member worktrees, the primary repository first.
+    members: Vec<Worktree>,
+    /// Configuration used to locate each repository's worktrees.
+    config: WorktreeConfig,
+}
+
+/// Outcome of removing a worktree group.
+#[derive(Debug, Default)]
+pub struct GroupRemoval {
+    /// Worktrees that were removed.
+    pub removed: Vec<Worktree>,
+    /// Worktrees that could not be removed, with the cause.
+    pub failed: Vec<(Worktree, WorktreeError)>,
+}
+
+impl WorktreeGroup {
+    /// Create a worktree in each repository and link them with a new group ID.
+    ///
+    /// The worktree in the first repository is named `name`; the others are
+    /// suffixed with their repository's directory name so registry entries
+    /// stay unique. If any creation fails, the worktrees already created are
+    /// removed again.
+    ///
+    /// # Errors
+    ///
+    /// Returns an error if a repository is not a git repository, a worktree
+    /// cannot be created, or a registry cannot be updated.
+    pub async fn create(
+        repos: &[PathBuf],
+        name: &str,
+        config: &WorktreeConfig,
+    ) -> Result<Self, WorktreeError> {
+        let id = uuid::Uuid::new_v4().to_string();
+        let managers = repos
+            .iter()
+            .map(|repo| WorktreeManager::new(repo.clone(), config.clone()))
+            .collect::<Result<Vec<_>, _>>()?;
+
+        let mut group = Self {
+            id,
+            members: Vec::with_capacity(managers.len()),
+            config: config.clone(),
+        };
+        let mut names = HashSet::new();
+        for (index, manager) in managers.iter().enumerate() {
+            let member_name = member_name(name, index, manager.repo_root(), &names);
+            match manager.create(&member_name).await {
+                Ok(worktree) => {
+                    names.insert(member_name);
+                    group.members.push(
+                        worktree
+                            .with_repo_root(manager.repo_root().clone())
+                            .with_group(group.id.clone()),
+                    );
+                }
+                Err(e) => {
+                    group.remove(true, true).await;
+                    return Err(e);
+                }
+            }
+        }
+
+        if let Err(e) = group.register() {
+            group.remove(true, true).await;
+            return Err(e);
+        }
+        Ok(group)
+    }
+
+    /// Rebuild a group from a registry's entries.
+    ///
+    /// Entries without a recorded repository are taken to belong to
+    /// `repo_root`. Returns `None` if the registry has no such group.
+    #[must_use]
+    pub fn from_registry(
+        registry: &WorktreeRegistry,
+        id: &str,
+        repo_root: &Path,
+        config: &WorktreeConfig,
+    ) -> Option<Self> {
+        let mut members: Vec<Worktree> = registry
+            .find_group(id)
+            .into_iter()
+            .cloned()
+            .map(|wt| match wt.repo_root {
+                Some(_) => wt,
+                None => wt.with_repo_root(repo_root.to_path_buf()),
+            })
+            .collect();
+        if members.is_empty() {
+            return None;
+        }
+        members.sort_by_key(|wt| wt.created_at);
+
+        Some(Self {
+            id: id.to_string(),
+            members,
+            config: config.clone(),
+        })
+    }
+
+    /// Get the group ID.
+    #[must_use]
+    pub fn id(&self) -> &str {
+        &self.id
+    }
+
+    /// Get the member worktrees, the primary repository first.
+    #[must_use]
+    pub fn worktrees(&self) -> &[Worktree] {
+        &self.members
+    }
+
+    /// Get the member worktree paths, the primary repository first.
+    #[must_use]
+    pub fn paths(&self) -> Vec<PathBuf> {
+        self.members.iter().map(|wt| wt.path.clone()).collect()
+    }
+
+    /// Remove every member worktree and drop them from all registries.
+    ///
+    /// Members that fail to remove are reported and stay registered.
+    pub async fn remove(&self, force: bool, delete_branch: bool) -> GroupRemoval {
+        let mut removal = GroupRemoval::default();
+        for worktree in &self.members {
+            match self.remove_member(worktree, force, delete_branch).await {
+                Ok(()) => removal.removed.push(worktree.clone()),
+                Err(e) => removal.failed.push((worktree.clone(), e)),
+            }
+        }
+
+        for registry_path in self.registry_paths() {
+            let updated = WorktreeRegistry::load(&registry_path).and_then(|mut registry| {
+                for worktree in &removal.removed {
+                    registry.remove(&worktree.name);
+                }
+                registry.save(&registry_path)
+            });
+            if let Err(e) = updated {
+                tracing::warn!(
+                    path = %registry_path.display(),
+                    error = %e,
+                    "Failed to update worktree registry"
+                );
+            }
+        }
+
+        removal
+    }
+
+    /// Remove one member worktree, and its branch if requested.
+    async fn remove_member(
+        &self,
+        worktree: &Worktree,
+        force: bool,
+        delete_branch: bool,
+    ) -> Result<(), WorktreeError> {
+        let repo_root = worktree
+            .repo_root
+            .clone()
+            .ok_or_else(|| WorktreeError::NotFound(worktree.name.clone()))?;
+        let manager = WorktreeManager::new(repo_root, self.config.clone())?;
+        manager.remove(&worktree.name, force).await?;
+        if delete_branch {
+            manager.delete_branch(&worktree.branch, force).await?;
+        }
+        Ok(())
+    }
+
+    /// Record every member in every member repository's registry.
+    /// Record the supervisor session name on every member and re-register.
+    ///
+    /// # Errors
+    ///
+    /// Returns an error if a registry cannot be updated.
+    pub fn set_session_name(&mut self, name: &str) -> Result<(), WorktreeError> {
+        for worktree in &mut self.members {
+            worktree.session_name = Some(name.to_string());
+        }
+        self.register()
+    }
+
+    fn register(&self) -> Result<(), WorktreeError> {
+        for registry_path in self.registry_paths() {
+            let mut registry = WorktreeRegistry::load(&registry_path)?;
+            for worktree in &self.members {
+                registry.upsert(worktree.clone());
+            }
+            registry.save(&registry_path)?;
+        }
+        Ok(())
+    }
+
+    /// Registry paths of the member repositories.
+    fn registry_paths(&self) -> Vec<PathBuf> {
+        let mut paths: Vec<PathBuf> = Vec::new();
+        for repo_root in self.members.iter().filter_map(|wt| wt.repo_root.as_ref()) {
+            let path = WorktreeRegistry::default_path(&repo_root.join(&self.config.worktree_dir));
+            if !paths.contains(&path) {
+                paths.push(path);
+            }
+        }
+        paths
+    }
+}
+
+/// Name for the worktree of the repository at `index` in a group.
+fn member_name(name: &str, index: usize, repo_root: &Path, taken: &HashSet<String>) -> String {
+    if index == 0 {
+        return name.to_string();
+    }
+    let repo_name = repo_root
+        .file_name()
+        .map_or_else(|| "repo".to_string(), |n| n.to_string_lossy().into_owned());
+    let base = format!("{name}-{repo_name}");
+    if !taken.contains(&base) {
+        return base;
+    }
+    // One of these is free since at most `taken.len()` names are used
+    (2..=taken.len() + 1)
+        .map(|n| format!("{base}-{n}"))
+        .find(|candidate| !taken.contains(candidate))
+        .unwrap_or(base)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_member_name_suffixes_other_repos() {
+        let taken = HashSet::from(["task".to_string()]);
+        assert_eq!(
+            member_name("task", 0, Path::new("/src/api"), &taken),
+            "task"
+        );
+        assert_eq!(
+            member_name("task", 1, Path::new("/src/web"), &taken),
+            "task-web"
+        );
+
+        let taken = HashSet::from(["task".to_string(), "task-web".to_string()]);
+        assert_eq!(
+            member_name("task", 2, Path::new("/other/web"), &taken),
+            "task-web-2"
+        );
+    }
+}
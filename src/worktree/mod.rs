@@ -4,11 +4,17 @@
 //! git worktrees to isolate Claude Code sessions from each other.
 
 mod error;
+mod exec;
+mod group;
 mod manager;
+mod prune;
 mod registry;
 mod types;
 
 pub use error::WorktreeError;
+pub use exec::{exec_each, exec_in, find_worktree, SPAWN_FAILED_EXIT_CODE};
+pub use group::{GroupRemoval, WorktreeGroup};
 pub use manager::WorktreeManager;
+pub use prune::{prune, prune_plan, PruneReport};
 pub use registry::WorktreeRegistry;
 pub use types::{Worktree, WorktreeStatus};
@@ -0,0 +1,98 @@
+//! Pruning stale worktrees.
+
+use super::error::WorktreeError;
+use super::manager::WorktreeManager;
+use super::registry::WorktreeRegistry;
+use super::types::Worktree;
+
+/// Outcome of pruning stale worktrees.
+#[derive(Debug, Default)]
+pub struct PruneReport {
+    /// Names of the worktrees that were removed.
+    pub removed: Vec<String>,
+    /// Worktrees that could not be removed, by name, with the cause.
+    pub failed: Vec<(String, WorktreeError)>,
+    /// Names of the worktrees left alone for their uncommitted changes.
+    pub skipped_dirty: Vec<String>,
+}
+
+impl PruneReport {
+    /// Whether every planned worktree was removed or skipped as dirty.
+    #[must_use]
+    pub fn is_success(&self) -> bool {
+        self.failed.is_empty()
+    }
+}
+
+/// Worktrees in `registry` a prune removes: the inactive ones untouched
+/// for longer than `max_age`, oldest first.
+#[must_use]
+pub fn prune_plan(registry: &WorktreeRegistry, max_age: chrono::Duration) -> Vec<Worktree> {
+    let mut plan: Vec<Worktree> = registry.find_stale(max_age).into_iter().cloned().collect();
+    plan.sort_by_key(|wt| (wt.last_accessed.unwrap_or(wt.created_at), wt.name.clone()));
+    plan
+}
+
+/// Remove each worktree in `plan` and drop the removed ones from `registry`.
+///
+/// Every removal is attempted whatever happened to the others, so the
+/// caller can save the registry once afterwards. Without `force`, worktrees
+/// with uncommitted changes are skipped rather than failed. A worktree whose
+/// directory is already gone, as after an interrupted prune, counts as
+/// removed. Grouped worktrees are removed from their own repository.
+pub async fn prune(
+    manager: &WorktreeManager,
+    registry: &mut WorktreeRegistry,
+    plan: &[Worktree],
+    force: bool,
+) -> PruneReport {
+    let mut report = PruneReport::default();
+    for worktree in plan {
+        let removed = match worktree
+            .repo_root
+            .as_ref()
+            .filter(|root| *root != manager.repo_root())
+        {
+            Some(root) => match WorktreeManager::new(root.clone(), manager.config().clone()) {
+                Ok(other) => other.remove(&worktree.name, force).await,
+                Err(e) => Err(e),
+            },
+            None => manager.remove(&worktree.name, force).await,
+        };
+        match removed {
+            Ok(()) | Err(WorktreeError::NotFound(_)) => {
+                registry.remove(&worktree.name);
+                report.removed.push(worktree.name.clone());
+            }
+            Err(WorktreeError::DirtyWorktree { .. }) => {
+                report.skipped_dirty.push(worktree.name.clone());
+            }
+            Err(e) => report.failed.push((worktree.name.clone(), e)),
+        }
+    }
+    report
+}
+
+#[cfg(test)]
+mod tests {
+    use std::path::PathBuf;
+
+    use super::*;
+
+    #[test]
+    fn test_prune_plan_is_oldest_first() {
+        let mut registry = WorktreeRegistry::new();
+        for (name, hours) in [("b", 30), ("a", 48), ("fresh", 1)] {
+            let mut wt = Worktree::new(name, PathBuf::from("/tmp").join(name), name);
+            wt.last_accessed = Some(chrono::Utc::now() - chrono::Duration::hours(hours));
+            registry.upsert(wt);
+        }
+
+        let names: Vec<String> = prune_plan(&registry, chrono::Duration::hours(24))
+            .into_iter()
+            .map(|wt| wt.name)
+            .collect();
+        assert_eq!(names, ["a", "b"]);
+        assert!(PruneReport::default().is_success());
+    }
+}
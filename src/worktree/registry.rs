@@ -126,6 +126,15 @@ impl WorktreeRegistry {
             .collect()
     }
 
+    /// Find worktrees belonging to a group.
+    #[must_use]
+    pub fn find_group(&self, group: &str) -> Vec<&Worktree> {
+        self.worktrees
+            .values()
+            .filter(|wt| wt.group.as_deref() == Some(group))
+            .collect()
+    }
+
     /// Find worktrees marked for cleanup.
     #[must_use]
     pub fn find_pending_cleanup(&self) -> Vec<&Worktree> {
@@ -208,6 +217,23 @@ mod tests {
         assert!(registry.worktrees.is_empty());
     }
 
+    #[test]
+    fn test_registry_find_group() {
+        let mut registry = WorktreeRegistry::new();
+        registry.upsert(Worktree::new("a", PathBuf::from("/tmp/a"), "a").with_group("g1"));
+        registry.upsert(Worktree::new("b", PathBuf::from("/tmp/b"), "b").with_group("g1"));
+        registry.upsert(Worktree::new("c", PathBuf::from("/tmp/c"), "c"));
+
+        let mut names: Vec<_> = registry
+            .find_group("g1")
+            .iter()
+            .map(|wt| wt.name.as_str())
+            .collect();
+        names.sort_unstable();
+        assert_eq!(names, ["a", "b"]);
+        assert!(registry.find_group("g2").is_empty());
+    }
+
     #[test]
     fn test_registry_default_path() {
         let worktree_dir = PathBuf::from("/repo/.worktrees");
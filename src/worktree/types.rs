@@ -44,6 +44,18 @@ pub struct Worktree {
     /// Session ID currently using this worktree, if any.
     #[serde(default)]
     pub session_id: Option<String>,
+
+    /// Repository this worktree was created from, when not the local one.
+    #[serde(default)]
+    pub repo_root: Option<PathBuf>,
+
+    /// Group ID shared by worktrees created together for one session.
+    #[serde(default)]
+    pub group: Option<String>,
+
+    /// Name of the supervisor session this worktree was created for.
+    #[serde(default)]
+    pub session_name: Option<String>,
 }
 
 impl Worktree {
@@ -58,9 +70,33 @@ impl Worktree {
             created_at: Utc::now(),
             last_accessed: None,
             session_id: None,
+            repo_root: None,
+            group: None,
+            session_name: None,
         }
     }
 
+    /// Record the repository this worktree belongs to.
+    #[must_use]
+    pub fn with_repo_root(mut self, repo_root: PathBuf) -> Self {
+        self.repo_root = Some(repo_root);
+        self
+    }
+
+    /// Link this worktree to a group.
+    #[must_use]
+    pub fn with_group(mut self, group: impl Into<String>) -> Self {
+        self.group = Some(group.into());
+        self
+    }
+
+    /// Record the supervisor session this worktree was created for.
+    #[must_use]
+    pub fn with_session_name(mut self, name: impl Into<String>) -> Self {
+        self.session_name = Some(name.into());
+        self
+    }
+
     /// Mark the worktree as active with a session ID.
     pub fn activate(&mut self, session_id: impl Into<String>) {
         self.status = WorktreeStatus::Active;
@@ -0,0 +1,42 @@
+//! Scripted stand-in for the `claude` binary.
+//!
+//! Plays the scenario named in `CLAUDE_SUPERVISOR_SCENARIO` (see
+//! [`Scenario`]) and exits with the scenario's exit code. Arguments are
+//! accepted like Claude's and only checked by `expect_arg` steps.
+//!
+//! ```text
+//! claude-supervisor run --claude-bin fake-claude --scenario tests/scenarios/clean_completion.jsonl "task"
+//! ```
+
+use std::io::{self, Write};
+use std::path::PathBuf;
+
+use claude_supervisor::cli::{
+    Scenario, MIN_CLAUDE_CODE_VERSION, SCENARIO_ENV, SCENARIO_FAILED_EXIT_CODE,
+};
+
+fn main() {
+    let args: Vec<String> = std::env::args().skip(1).collect();
+    if args.iter().any(|arg| arg == "--version") {
+        println!("{MIN_CLAUDE_CODE_VERSION} (fake-claude)");
+        return;
+    }
+    let Some(path) = std::env::var_os(SCENARIO_ENV).map(PathBuf::from) else {
+        eprintln!("fake-claude: {SCENARIO_ENV} is not set");
+        std::process::exit(SCENARIO_FAILED_EXIT_CODE);
+    };
+    let code = Scenario::load(&path)
+        .and_then(|scenario| {
+            scenario.play(
+                &args,
+                &mut io::stdin().lock(),
+                &mut io::stdout().lock(),
+                &mut io::stderr().lock(),
+            )
+        })
+        .unwrap_or_else(|e| {
+            let _ = writeln!(io::stderr(), "fake-claude: {e}");
+            SCENARIO_FAILED_EXIT_CODE
+        });
+    std::process::exit(code);
+}
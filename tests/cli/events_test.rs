@@ -1,6 +1,8 @@
 //! Tests for Claude event type parsing.
 
-use claude_supervisor::cli::{ClaudeEvent, ContentDelta, ResultEvent, SystemInit, ToolUse};
+use claude_supervisor::cli::{
+    AssistantMessage, ClaudeEvent, ContentBlock, ContentDelta, ResultEvent, SystemInit, ToolUse,
+};
 
 #[test]
 fn parse_system_init_event() {
@@ -221,9 +223,9 @@ fn is_terminal_for_result() {
 }
 
 #[test]
-fn is_terminal_for_message_stop() {
+fn message_stop_is_not_terminal() {
     let event = ClaudeEvent::MessageStop;
-    assert!(event.is_terminal());
+    assert!(!event.is_terminal());
 }
 
 #[test]
@@ -318,3 +320,104 @@ fn serialize_and_deserialize_content_delta() {
 
     assert_eq!(original, deserialized);
 }
+
+/// Typed assistant messages from every line of a fixture.
+fn fixture_messages(jsonl: &str) -> Vec<AssistantMessage> {
+    jsonl
+        .lines()
+        .map(|line| {
+            let event: ClaudeEvent = serde_json::from_str(line).unwrap();
+            event
+                .assistant()
+                .expect("fixture line is an assistant event")
+        })
+        .collect()
+}
+
+/// Re-serializing a typed message must not change it.
+fn assert_round_trips(message: &AssistantMessage) {
+    let value = serde_json::to_value(message).unwrap();
+    let reparsed: AssistantMessage = serde_json::from_value(value).unwrap();
+    assert_eq!(&reparsed, message);
+}
+
+#[test]
+fn assistant_message_from_v1_0_fixture() {
+    let messages = fixture_messages(include_str!("../fixtures/assistant/v1.0.jsonl"));
+
+    let first = &messages[0];
+    assert_eq!(first.id.as_deref(), Some("msg_01A"));
+    assert_eq!(first.model.as_deref(), Some("claude-sonnet-4-20250514"));
+    assert_eq!(first.text(), "I'll read the config first.");
+    let tool_uses: Vec<_> = first.tool_uses().collect();
+    assert_eq!(tool_uses.len(), 1);
+    assert_eq!(tool_uses[0].path(), Some("/repo/config.toml"));
+    let usage = first.usage.as_ref().unwrap();
+    assert_eq!(usage.cache_read_input_tokens, Some(11415));
+    assert!(usage.extra.contains_key("service_tier"));
+    assert!(first.extra.contains_key("stop_sequence"));
+
+    // Older versions sent plain string content
+    assert_eq!(messages[1].text(), "All done.");
+    assert!(messages[1].usage.is_none());
+
+    messages.iter().for_each(assert_round_trips);
+}
+
+#[test]
+fn assistant_message_from_v2_0_fixture() {
+    let messages = fixture_messages(include_str!("../fixtures/assistant/v2.0.jsonl"));
+
+    assert!(matches!(
+        &messages[0].content[..],
+        [ContentBlock::Thinking { thinking, signature: Some(_) }]
+            if thinking == "The test fails on the timeout path."
+    ));
+    assert!(messages[0].text().is_empty());
+    assert!(messages[0].extra.contains_key("context_management"));
+    assert_eq!(messages[1].stop_reason.as_deref(), Some("tool_use"));
+    assert_eq!(
+        messages[1].tool_uses().next().unwrap().command(),
+        Some("cargo test timeout")
+    );
+
+    messages.iter().for_each(assert_round_trips);
+}
+
+#[test]
+fn assistant_message_from_v2_1_fixture_keeps_unknown_blocks() {
+    let line = include_str!("../fixtures/assistant/v2.1.jsonl");
+    let messages = fixture_messages(line);
+    let message = &messages[0];
+
+    let raw: serde_json::Value = serde_json::from_str(line.trim()).unwrap();
+    let raw_blocks = raw["message"]["content"].as_array().unwrap();
+    assert_eq!(
+        message.content[0],
+        ContentBlock::Other(raw_blocks[0].clone())
+    );
+    assert_eq!(
+        message.content[1],
+        ContentBlock::Other(raw_blocks[1].clone())
+    );
+    assert_eq!(message.text(), "Found the cancellation rules.");
+    assert_eq!(message.tool_uses().count(), 0);
+
+    let serialized = serde_json::to_value(message).unwrap();
+    assert_eq!(serialized["content"][0], raw_blocks[0]);
+    assert_eq!(serialized["content"][1], raw_blocks[1]);
+    assert_eq!(
+        serialized["context_management"],
+        raw["message"]["context_management"]
+    );
+    assert_round_trips(message);
+}
+
+#[test]
+fn assistant_accessor_is_none_for_other_events() {
+    assert!(ClaudeEvent::MessageStop.assistant().is_none());
+    let event = ClaudeEvent::Assistant {
+        message: serde_json::Value::Null,
+    };
+    assert!(event.assistant().is_none());
+}
@@ -93,7 +93,7 @@ fn helper_methods_work() {
         extras: std::collections::HashMap::new(),
     });
     assert!(result_event.is_terminal());
-    assert!(ClaudeEvent::MessageStop.is_terminal());
+    assert!(!ClaudeEvent::MessageStop.is_terminal());
 
     // tool_name
     let tool_event = ClaudeEvent::ToolUse(ToolUse {
@@ -121,7 +121,9 @@ fn helper_methods_work() {
 
 #[test]
 fn error_types_are_debug() {
-    let spawn_err = SpawnError::NotFound;
+    let spawn_err = SpawnError::NotFound {
+        path: "claude".into(),
+    };
     let spawn_debug = format!("{spawn_err:?}");
     assert!(spawn_debug.contains("NotFound"));
 
@@ -132,9 +134,12 @@ fn error_types_are_debug() {
 
 #[test]
 fn error_types_display() {
-    let spawn_err = SpawnError::NotFound;
+    let spawn_err = SpawnError::NotFound {
+        path: "/opt/claude-nightly".into(),
+    };
     let spawn_display = format!("{spawn_err}");
     assert!(spawn_display.contains("not found"));
+    assert!(spawn_display.contains("/opt/claude-nightly"));
 
     let stream_err = StreamError::ChannelClosed;
     let stream_display = format!("{stream_err}");
@@ -1,6 +1,11 @@
 //! Tests for Claude process spawning and control.
 
-use claude_supervisor::cli::{ClaudeProcess, ClaudeProcessBuilder};
+use std::path::{Path, PathBuf};
+
+use claude_supervisor::cli::{
+    binary_version, is_older_than_minimum, locate_binary, parse_version, probe_binary_version,
+    ClaudeProcess, ClaudeProcessBuilder, SpawnError, SCENARIO_ENV,
+};
 
 #[test]
 fn builder_new_creates_with_prompt() {
@@ -195,6 +200,41 @@ fn builder_working_dir_not_set() {
     assert!(builder.get_working_dir().is_none());
 }
 
+#[test]
+fn builder_env() {
+    let builder = ClaudeProcessBuilder::new("task").env("CLAUDE_SUPERVISOR_NO_SANDBOX", "1");
+    assert_eq!(
+        builder.get_env(),
+        &[("CLAUDE_SUPERVISOR_NO_SANDBOX".to_string(), "1".to_string())]
+    );
+    assert!(ClaudeProcessBuilder::new("task").get_env().is_empty());
+}
+
+#[test]
+fn builder_scenario_is_passed_in_env() {
+    let builder = ClaudeProcessBuilder::new("task").scenario("/tmp/clean.jsonl");
+    assert_eq!(
+        builder.get_env(),
+        &[(SCENARIO_ENV.to_string(), "/tmp/clean.jsonl".to_string())]
+    );
+    // The real binary never sees it as an argument
+    assert!(!builder.build_args().iter().any(|arg| arg.contains("clean")));
+}
+
+#[test]
+fn builder_add_dir() {
+    let args = ClaudeProcessBuilder::new("task")
+        .add_dir("/src/web")
+        .add_dir("/src/docs")
+        .build_args();
+    let dirs: Vec<_> = args
+        .windows(2)
+        .filter(|pair| pair[0] == "--add-dir")
+        .map(|pair| pair[1].as_str())
+        .collect();
+    assert_eq!(dirs, ["/src/web", "/src/docs"]);
+}
+
 #[tokio::test]
 async fn spawn_with_working_dir() {
     use std::process::Stdio;
@@ -226,3 +266,105 @@ async fn spawn_with_working_dir() {
     let builder = ClaudeProcessBuilder::new("test").working_dir(&temp_path);
     assert_eq!(builder.get_working_dir(), Some(&temp_path));
 }
+
+#[test]
+fn builder_claude_binary_override() {
+    let builder = ClaudeProcessBuilder::new("task").claude_binary("/opt/claude-nightly");
+    assert_eq!(
+        builder.get_claude_binary(),
+        PathBuf::from("/opt/claude-nightly")
+    );
+
+    let cmd = builder.build_command("/opt/claude-nightly");
+    let cmd = cmd.as_std();
+    assert_eq!(cmd.get_program(), "script");
+    let claude_cmd = cmd.get_args().nth(2).and_then(|arg| arg.to_str()).unwrap();
+    assert!(
+        claude_cmd.starts_with("/opt/claude-nightly -p task"),
+        "{claude_cmd}"
+    );
+}
+
+#[test]
+fn spawn_missing_binary_reports_path() {
+    let builder = ClaudeProcessBuilder::new("task").claude_binary("/nonexistent/claude-1.x");
+    let err = ClaudeProcess::spawn(&builder).unwrap_err();
+
+    match err {
+        SpawnError::NotFound { path } => {
+            assert_eq!(path, PathBuf::from("/nonexistent/claude-1.x"));
+        }
+        other => panic!("expected NotFound, got {other:?}"),
+    }
+}
+
+#[cfg(unix)]
+#[test]
+fn locate_binary_checks_executable() {
+    use std::os::unix::fs::PermissionsExt;
+
+    let dir = tempfile::tempdir().unwrap();
+    let wrapper = dir.path().join("claude-wrapper");
+    std::fs::write(&wrapper, "#!/bin/sh\necho 1.0.0\n").unwrap();
+
+    std::fs::set_permissions(&wrapper, std::fs::Permissions::from_mode(0o644)).unwrap();
+    assert!(matches!(
+        locate_binary(&wrapper),
+        Err(SpawnError::NotExecutable { .. })
+    ));
+
+    std::fs::set_permissions(&wrapper, std::fs::Permissions::from_mode(0o755)).unwrap();
+    assert_eq!(locate_binary(&wrapper).unwrap(), wrapper);
+    assert_eq!(binary_version(&wrapper).as_deref(), Some("1.0.0"));
+}
+
+#[test]
+fn locate_binary_searches_path() {
+    assert!(locate_binary(Path::new("sh")).unwrap().is_absolute());
+    assert!(matches!(
+        locate_binary(Path::new("claude-does-not-exist")),
+        Err(SpawnError::NotFound { .. })
+    ));
+}
+
+#[test]
+fn parse_version_reads_claude_output() {
+    assert_eq!(parse_version("2.0.14 (Claude Code)"), Some((2, 0, 14)));
+    assert_eq!(parse_version("v1.2"), Some((1, 2, 0)));
+    assert_eq!(parse_version("1.0.3-beta.1"), Some((1, 0, 3)));
+    assert_eq!(parse_version("unknown"), None);
+}
+
+#[test]
+fn older_than_minimum_only_for_known_old_versions() {
+    assert!(is_older_than_minimum("0.2.9 (Claude Code)"));
+    assert!(!is_older_than_minimum("1.0.0"));
+    assert!(!is_older_than_minimum("2.0.14 (Claude Code)"));
+    assert!(!is_older_than_minimum("unknown"));
+}
+
+#[cfg(unix)]
+#[tokio::test]
+async fn probe_binary_version_reads_and_times_out() {
+    use std::os::unix::fs::PermissionsExt;
+    use std::time::Duration;
+
+    let dir = tempfile::tempdir().unwrap();
+    let quick = dir.path().join("claude-quick");
+    std::fs::write(&quick, "#!/bin/sh\necho '2.0.14 (Claude Code)'\n").unwrap();
+    std::fs::set_permissions(&quick, std::fs::Permissions::from_mode(0o755)).unwrap();
+    let slow = dir.path().join("claude-slow");
+    std::fs::write(&slow, "#!/bin/sh\nsleep 30\n").unwrap();
+    std::fs::set_permissions(&slow, std::fs::Permissions::from_mode(0o755)).unwrap();
+
+    assert_eq!(
+        probe_binary_version(quick, Duration::from_secs(5))
+            .await
+            .as_deref(),
+        Some("2.0.14 (Claude Code)")
+    );
+    assert_eq!(
+        probe_binary_version(slow, Duration::from_millis(100)).await,
+        None
+    );
+}
@@ -0,0 +1,41 @@
+//! Integration test for the configurable data directory.
+
+use claude_supervisor::audit::{default_audit_path, default_manifest_dir};
+use claude_supervisor::config::{
+    audit_db_path, data_dir, snapshots_dir, sockets_dir, state_dir, SnapshotConfig, DATA_DIR_ENV,
+};
+use claude_supervisor::ipc::{default_socket_path, IpcClient, IpcServer};
+use claude_supervisor::supervisor::ResumeContext;
+
+/// Every path the supervisor writes outside a repository lands under the
+/// data directory named by the environment.
+#[test]
+fn data_dir_env_var_relocates_every_path() {
+    let dir = tempfile::tempdir().unwrap();
+    std::env::set_var(DATA_DIR_ENV, dir.path());
+
+    assert_eq!(data_dir(), dir.path());
+    let paths = [
+        audit_db_path(),
+        default_audit_path(),
+        state_dir(),
+        ResumeContext::default_dir(),
+        default_manifest_dir(),
+        snapshots_dir(),
+        SnapshotConfig::default().dir,
+        sockets_dir(),
+        default_socket_path(),
+        IpcClient::new().socket_path().to_path_buf(),
+        IpcServer::with_default_path().socket_path().to_path_buf(),
+    ];
+    for path in paths {
+        assert!(
+            path.starts_with(dir.path()),
+            "{} is outside the data directory",
+            path.display()
+        );
+    }
+
+    std::env::remove_var(DATA_DIR_ENV);
+    assert!(!data_dir().starts_with(dir.path()));
+}
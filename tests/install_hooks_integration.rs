@@ -239,3 +239,46 @@ fn uninstall_idempotent() {
     let settings = ClaudeSettings::load_from(&settings_path).unwrap();
     assert!(settings.hooks.is_none());
 }
+
+/// Test installing over a settings file with duplicate supervisor hooks.
+#[test]
+fn install_over_duplicated_settings_collapses_to_one_entry() {
+    let temp_dir = TempDir::new().unwrap();
+    let settings_path = temp_dir.path().join("settings.json");
+    fs::write(
+        &settings_path,
+        include_str!("fixtures/settings/duplicate_hooks.json"),
+    )
+    .unwrap();
+
+    let installer = HookInstaller::new("/usr/local/bin/claude-supervisor".into())
+        .unwrap()
+        .with_settings_path(settings_path.clone());
+    assert_eq!(
+        installer.report().unwrap().duplicates(),
+        vec![("PreToolUse", 2), ("Stop", 2)]
+    );
+
+    // Installing twice must be stable
+    assert!(installer.install().unwrap().replaced_existing);
+    assert!(!installer.install().unwrap().replaced_existing);
+
+    let report = installer.report().unwrap();
+    assert!(report.duplicates().is_empty());
+    assert_eq!(
+        report.pre_tool_use,
+        vec!["/usr/local/bin/claude-supervisor hook pre-tool-use"]
+    );
+    assert_eq!(
+        report.stop,
+        vec!["/usr/local/bin/claude-supervisor hook stop"]
+    );
+
+    let settings = ClaudeSettings::load_from(&settings_path).unwrap();
+    let hooks = settings.hooks.as_ref().unwrap();
+    let pre_tool_use = hooks.pre_tool_use.as_ref().unwrap();
+    assert_eq!(pre_tool_use.len(), 2);
+    assert_eq!(pre_tool_use[1].command, "audit-logger --pre");
+    assert!(hooks.other.contains_key("Notification"));
+    assert_eq!(settings.other["model"], "sonnet");
+}
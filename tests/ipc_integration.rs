@@ -1,8 +1,16 @@
 //! Integration tests for IPC round-trip communication.
 
+use std::sync::{Arc, Mutex};
 use std::time::Duration;
 
-use claude_supervisor::ipc::{EscalationRequest, EscalationResponse, IpcClient, IpcServer};
+use claude_supervisor::config::EscrowConfig;
+use claude_supervisor::ipc::{
+    EscalationRequest, EscalationResponse, EvaluateRequest, HandoffState, IpcClient, IpcError,
+    IpcServer,
+};
+use claude_supervisor::supervisor::{
+    BashAllowlist, Escrow, PolicyDecision, PolicyEngine, PolicyLevel,
+};
 use serde_json::json;
 
 /// Test full IPC communication between client and server.
@@ -29,6 +37,7 @@ async fn ipc_round_trip_allow() {
         tool_name: "Read".to_string(),
         tool_input: json!({"path": "/tmp/test.txt"}),
         reason: "Test escalation".to_string(),
+        appeal: None,
     };
 
     let response = client.escalate(&request).await.expect("Escalation failed");
@@ -67,6 +76,7 @@ async fn ipc_round_trip_deny() {
         tool_name: "Bash".to_string(),
         tool_input: json!({"command": "rm -rf /"}),
         reason: "Dangerous command".to_string(),
+        appeal: None,
     };
 
     let response = client.escalate(&request).await.expect("Escalation failed");
@@ -105,6 +115,7 @@ async fn ipc_round_trip_modify() {
         tool_name: "Bash".to_string(),
         tool_input: json!({"command": "ls"}),
         reason: "Add flags".to_string(),
+        appeal: None,
     };
 
     let response = client.escalate(&request).await.expect("Escalation failed");
@@ -147,6 +158,7 @@ async fn ipc_multiple_clients() {
                 tool_name: "Test".to_string(),
                 tool_input: json!({}),
                 reason: "Concurrent test".to_string(),
+                appeal: None,
             };
             client.escalate(&request).await
         }));
@@ -191,6 +203,7 @@ async fn ipc_client_timeout() {
         tool_name: "Test".to_string(),
         tool_input: json!({}),
         reason: "Timeout test".to_string(),
+        appeal: None,
     };
 
     let result = client.escalate(&request).await;
@@ -219,3 +232,404 @@ async fn ipc_server_cleanup_on_drop() {
 
     assert!(!socket_path.exists());
 }
+
+/// Test that a denied escalation can be appealed from the hook exactly once.
+#[tokio::test]
+async fn hook_appeal_round_trip() {
+    use claude_supervisor::hooks::{HookHandler, HookInput};
+    use claude_supervisor::supervisor::{AppealGate, PolicyEngine, PolicyLevel};
+
+    let temp_dir = std::env::temp_dir();
+    let socket_path = temp_dir.join(format!("ipc-test-appeal-{}.sock", std::process::id()));
+
+    // Deny unless the escalation carries a justification
+    let gate = AppealGate::new();
+    let server = IpcServer::new(&socket_path);
+    let handle = server
+        .start(move |req| {
+            let gate = gate.clone();
+            async move {
+                gate.decide(req, |req| async move {
+                    if req.reason.contains("already backed up") {
+                        EscalationResponse::Allow
+                    } else {
+                        EscalationResponse::Deny {
+                            reason: "Unknown tool".to_string(),
+                        }
+                    }
+                })
+                .await
+            }
+        })
+        .expect("Failed to start server");
+    tokio::time::sleep(Duration::from_millis(10)).await;
+
+    let handler = HookHandler::new(PolicyEngine::new(PolicyLevel::Moderate))
+        .with_ipc_client(IpcClient::with_path(&socket_path));
+    let hook = |tool_input: serde_json::Value| -> HookInput {
+        serde_json::from_value(json!({
+            "hook_event_name": "PreToolUse",
+            "session_id": "appeal-session",
+            "tool_name": "UnknownTool",
+            "tool_input": tool_input,
+        }))
+        .unwrap()
+    };
+
+    let denied = handler
+        .handle_pre_tool_use_async(&hook(json!({"target": "backup.tar"})))
+        .await
+        .unwrap();
+    assert!(denied.should_deny);
+    let response: serde_json::Value = serde_json::from_str(&denied.response).unwrap();
+    let reason = response["hookSpecificOutput"]["permissionDecisionReason"]
+        .as_str()
+        .unwrap();
+    let token = reason
+        .split("\"appeal_token\": \"")
+        .nth(1)
+        .and_then(|rest| rest.split('"').next())
+        .expect("deny reason carries an appeal token")
+        .to_string();
+
+    let appeal = hook(json!({
+        "target": "backup.tar",
+        "appeal_token": token,
+        "appeal_justification": "the file is already backed up",
+    }));
+    let allowed = handler.handle_pre_tool_use_async(&appeal).await.unwrap();
+    assert!(!allowed.should_deny, "{}", allowed.response);
+    let response: serde_json::Value = serde_json::from_str(&allowed.response).unwrap();
+    assert_eq!(
+        response["hookSpecificOutput"]["updatedInput"],
+        json!({"target": "backup.tar"})
+    );
+
+    let again = handler.handle_pre_tool_use_async(&appeal).await.unwrap();
+    assert!(again.should_deny);
+    assert!(
+        again.response.contains("already appealed"),
+        "{}",
+        again.response
+    );
+
+    handle.shutdown();
+}
+
+/// Test that guidance from the supervisor reaches Claude with the allow.
+#[tokio::test]
+async fn hook_guide_adds_context() {
+    use claude_supervisor::hooks::{HookHandler, HookInput};
+    use claude_supervisor::supervisor::{PolicyEngine, PolicyLevel};
+
+    let temp_dir = std::env::temp_dir();
+    let socket_path = temp_dir.join(format!("ipc-test-guide-{}.sock", std::process::id()));
+
+    let server = IpcServer::new(&socket_path);
+    let handle = server
+        .start(|_req| async {
+            EscalationResponse::Guide {
+                guidance: "Keep the public API unchanged".to_string(),
+            }
+        })
+        .expect("Failed to start server");
+    tokio::time::sleep(Duration::from_millis(10)).await;
+
+    let input: HookInput = serde_json::from_value(json!({
+        "hook_event_name": "PreToolUse",
+        "session_id": "guide-session",
+        "tool_name": "UnknownTool",
+        "tool_input": {"file_path": "src/lib.rs"},
+    }))
+    .unwrap();
+    let handler = |additional_context: bool| {
+        HookHandler::new(PolicyEngine::new(PolicyLevel::Moderate))
+            .with_additional_context(additional_context)
+            .with_ipc_client(IpcClient::with_path(&socket_path))
+    };
+
+    let result = handler(true)
+        .handle_pre_tool_use_async(&input)
+        .await
+        .unwrap();
+    assert!(!result.should_deny);
+    assert_eq!(
+        result.decision,
+        Some(EscalationResponse::Guide {
+            guidance: "Keep the public API unchanged".to_string(),
+        })
+    );
+    let response: serde_json::Value = serde_json::from_str(&result.response).unwrap();
+    assert_eq!(
+        response,
+        json!({
+            "hookSpecificOutput": {
+                "hookEventName": "PreToolUse",
+                "permissionDecision": "allow",
+                "additionalContext": "Keep the public API unchanged"
+            }
+        })
+    );
+
+    // Older Claude Code versions get the guidance as the decision reason
+    let result = handler(false)
+        .handle_pre_tool_use_async(&input)
+        .await
+        .unwrap();
+    let response: serde_json::Value = serde_json::from_str(&result.response).unwrap();
+    let output = &response["hookSpecificOutput"];
+    assert_eq!(
+        output["permissionDecisionReason"],
+        "Keep the public API unchanged"
+    );
+    assert!(output.get("additionalContext").is_none());
+
+    handle.shutdown();
+}
+
+/// Test that `attach` finds a running detached session and reads its status.
+#[tokio::test]
+async fn ipc_control_reaches_detached_session() {
+    use claude_supervisor::dashboard::{create_dashboard_channels, DashboardCommand};
+    use claude_supervisor::ipc::{ControlRequest, ControlResponse, SessionControl};
+    use claude_supervisor::supervisor::DetachedSession;
+
+    let dir = tempfile::tempdir().unwrap();
+    let sockets = dir.path().join("sockets");
+    let session = DetachedSession::in_dirs("quiet-falcon", dir.path(), &sockets);
+    session.write_pid(std::process::id()).unwrap();
+
+    let (dashboard, mut handles) = create_dashboard_channels();
+    handles.status_tx.send_modify(|status| {
+        status.name = Some("quiet-falcon".to_string());
+        status.state = "running".to_string();
+        status.tool_calls = 3;
+    });
+    let handle = IpcServer::new(session.socket_path())
+        .with_control(SessionControl::new(
+            dashboard.status_rx,
+            dashboard.command_tx,
+        ))
+        .start(|_req| async { EscalationResponse::Allow })
+        .expect("Failed to start server");
+
+    let found = DetachedSession::find_running_in(dir.path(), &sockets, None).unwrap();
+    let client = IpcClient::with_path(found.socket_path());
+    match client.control(ControlRequest::Status).await.unwrap() {
+        ControlResponse::Status { status, .. } => {
+            assert_eq!(status.name.as_deref(), Some("quiet-falcon"));
+            assert_eq!(status.state, "running");
+            assert_eq!(status.tool_calls, 3);
+        }
+        other => panic!("Expected status, got {other:?}"),
+    }
+
+    let response = client.control(ControlRequest::Stop).await.unwrap();
+    assert!(matches!(response, ControlResponse::Accepted));
+    assert_eq!(
+        handles.command_rx.recv().await,
+        Some(DashboardCommand::Stop)
+    );
+
+    // Without control, the request is answered but refused
+    handle.shutdown();
+    drop(handle);
+    let handle = IpcServer::new(session.socket_path())
+        .start(|_req| async { EscalationResponse::Allow })
+        .expect("Failed to restart server");
+    let response = client.control(ControlRequest::Status).await.unwrap();
+    assert!(matches!(response, ControlResponse::Rejected { .. }));
+    handle.shutdown();
+}
+
+/// Test that calls evaluated over IPC get the verdict of local evaluation.
+#[tokio::test]
+async fn ipc_evaluate_matches_local_policy() {
+    let dir = tempfile::tempdir().unwrap();
+    let socket_path = dir.path().join("evaluate.sock");
+    let cwd = dir.path().to_string_lossy().into_owned();
+
+    let mut policy = PolicyEngine::new(PolicyLevel::Moderate);
+    policy.allow_tool("Read");
+    policy.set_bash_allowlist(BashAllowlist::new(["cargo test", "git status"]));
+    policy.set_escrow(Escrow::from_config(&EscrowConfig {
+        enabled: true,
+        ..EscrowConfig::default()
+    }));
+    policy.set_permission_mode_levels(
+        [("plan".to_string(), PolicyLevel::Strict)]
+            .into_iter()
+            .collect(),
+    );
+    let policy = Arc::new(Mutex::new(policy));
+    let handle = IpcServer::new(&socket_path)
+        .with_policy(Arc::clone(&policy))
+        .start(|_req| async { EscalationResponse::Allow })
+        .expect("Failed to start server");
+    let client = IpcClient::with_path(&socket_path);
+
+    let calls = [
+        ("Read", json!({"file_path": "src/lib.rs"}), None),
+        ("Bash", json!({"command": "cargo test --all"}), None),
+        ("Bash", json!({"command": "rm -rf /"}), None),
+        ("Bash", json!({"command": "rm notes.txt"}), None),
+        ("Bash", json!({"command": "npm publish"}), None),
+        (
+            "Write",
+            json!({"file_path": ".env", "content": "A=1"}),
+            None,
+        ),
+        (
+            "Write",
+            json!({"file_path": "src/main.rs", "content": ""}),
+            None,
+        ),
+        (
+            "Write",
+            json!({"file_path": "src/main.rs", "content": ""}),
+            Some("plan"),
+        ),
+        ("mcp__github__create_issue", json!({}), None),
+    ];
+    for (tool_name, tool_input, permission_mode) in calls {
+        let request = EvaluateRequest {
+            session_id: "test-session".to_string(),
+            tool_name: tool_name.to_string(),
+            tool_input: tool_input.clone(),
+            cwd: Some(cwd.clone()),
+            permission_mode: permission_mode.map(str::to_string),
+        };
+        let verdict = client.evaluate(&request).await.expect("Evaluation failed");
+        let local = policy
+            .lock()
+            .unwrap()
+            .for_session("test-session", permission_mode)
+            .evaluate_with_cwd(tool_name, &tool_input, Some(dir.path()));
+        assert_eq!(
+            PolicyDecision::from(verdict),
+            local,
+            "{tool_name} {tool_input}"
+        );
+    }
+    handle.shutdown();
+    drop(handle);
+
+    // Without a policy the request goes unanswered and the hook decides itself
+    let handle = IpcServer::new(&socket_path)
+        .start(|_req| async { EscalationResponse::Allow })
+        .expect("Failed to restart server");
+    let request = EvaluateRequest {
+        session_id: "test-session".to_string(),
+        tool_name: "Read".to_string(),
+        tool_input: json!({}),
+        cwd: None,
+        permission_mode: None,
+    };
+    assert!(matches!(
+        client.evaluate(&request).await,
+        Err(IpcError::InvalidResponse)
+    ));
+    handle.shutdown();
+}
+
+/// A supervisor hands its session to a second server on the same socket.
+#[tokio::test]
+async fn ipc_handoff_between_servers() {
+    let socket_path =
+        std::env::temp_dir().join(format!("ipc-test-handoff-{}.sock", std::process::id()));
+    let client = IpcClient::with_path(&socket_path);
+
+    // Without handoff state the request goes unanswered
+    let handle = IpcServer::new(&socket_path)
+        .start(|_req| async { EscalationResponse::Allow })
+        .expect("Failed to start server");
+    assert!(matches!(
+        client.request_handoff().await,
+        Err(IpcError::InvalidResponse)
+    ));
+    drop(handle);
+
+    let old_state = HandoffState::new();
+    old_state.set_child(Some(4242));
+    old_state.add_session("session-1", None);
+    old_state.set_cost(120_000, None);
+    let old = IpcServer::new(&socket_path)
+        .with_handoff(old_state.clone())
+        .start(|_req| async move {
+            // Slow enough that the handoff arrives while it is in flight
+            tokio::time::sleep(Duration::from_millis(200)).await;
+            EscalationResponse::Deny {
+                reason: "old".to_string(),
+            }
+        })
+        .expect("Failed to start old server");
+
+    let in_flight = tokio::spawn({
+        let client = client.clone();
+        async move {
+            let request = EscalationRequest {
+                session_id: "session-1".to_string(),
+                tool_name: "Bash".to_string(),
+                tool_input: json!({"command": "ls"}),
+                reason: "Needs approval".to_string(),
+                appeal: None,
+            };
+            client.escalate(&request).await
+        }
+    });
+    tokio::time::sleep(Duration::from_millis(50)).await;
+
+    let snapshot = client.request_handoff().await.expect("Handoff failed");
+    assert!(old.is_handed_off());
+    assert_eq!(snapshot.child_pid, Some(4242));
+    assert_eq!(snapshot.current_session().unwrap().session_id, "session-1");
+    assert_eq!(snapshot.cost_micros, 120_000);
+
+    // The new server takes the socket while the old one drains
+    let new_state = HandoffState::from_snapshot(&snapshot);
+    let new = IpcServer::new(&socket_path)
+        .with_handoff(new_state)
+        .start(|_req| async {
+            EscalationResponse::Deny {
+                reason: "new".to_string(),
+            }
+        })
+        .expect("Failed to start new server");
+    tokio::time::timeout(Duration::from_secs(2), old.drained())
+        .await
+        .expect("Old server did not drain");
+    let answered = in_flight.await.unwrap().expect("In-flight escalation lost");
+    assert_eq!(
+        answered,
+        EscalationResponse::Deny {
+            reason: "old".to_string()
+        }
+    );
+
+    // Dropping the old server leaves the socket to the new one
+    drop(old);
+    let request = EscalationRequest {
+        session_id: "session-1".to_string(),
+        tool_name: "Bash".to_string(),
+        tool_input: json!({"command": "ls"}),
+        reason: "Needs approval".to_string(),
+        appeal: None,
+    };
+    assert_eq!(
+        client
+            .escalate(&request)
+            .await
+            .expect("New server unreachable"),
+        EscalationResponse::Deny {
+            reason: "new".to_string()
+        }
+    );
+
+    // And the new server can hand the session on again
+    let again = client
+        .request_handoff()
+        .await
+        .expect("Second handoff failed");
+    assert_eq!(again.sessions, snapshot.sessions);
+    assert!(new.is_handed_off());
+}
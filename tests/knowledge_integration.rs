@@ -28,3 +28,33 @@ Run tests with: cargo nextest run
     assert!(context.contains("Commands"));
     assert!(context.contains("Conventions"));
 }
+
+#[tokio::test]
+async fn test_claude_md_merged_across_roots() {
+    let api = tempfile::tempdir().unwrap();
+    let web = tempfile::tempdir().unwrap();
+    std::fs::write(
+        api.path().join("CLAUDE.md"),
+        "## Commands\n\nRun cargo test\n",
+    )
+    .unwrap();
+    std::fs::write(
+        web.path().join("CLAUDE.md"),
+        "## Commands\n\nRun npm test\n",
+    )
+    .unwrap();
+
+    let roots = vec![
+        ("api".to_string(), api.path().to_path_buf()),
+        ("web".to_string(), web.path().to_path_buf()),
+    ];
+    let source = ClaudeMdSource::load_with_global_roots(&roots).await;
+
+    assert!(source.sections["[api] Commands"].contains("cargo test"));
+    assert!(source.sections["[web] Commands"].contains("npm test"));
+    assert!(source.raw_content().contains("<!-- CLAUDE.md from web -->"));
+
+    // A single root keeps plain section names
+    let source = ClaudeMdSource::load_with_global_roots(&roots[..1]).await;
+    assert!(source.sections["Commands"].contains("cargo test"));
+}
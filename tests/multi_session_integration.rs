@@ -1,5 +1,11 @@
 //! Integration tests for multi-session supervisor.
 
+use std::sync::atomic::{AtomicU64, Ordering};
+use std::sync::{Arc, Mutex};
+use std::time::Duration;
+
+use claude_supervisor::ai::{AiClient, ClaudeProvider, Provider};
+use claude_supervisor::config::AiConfig;
 use claude_supervisor::supervisor::{
     MultiSessionError, MultiSessionSupervisor, PolicyEngine, PolicyLevel, SupervisorResult,
 };
@@ -135,3 +141,184 @@ async fn test_aggregated_stats_accuracy() {
     assert_eq!(stats.sessions_completed, 3);
     assert_eq!(stats.sessions_failed, 0);
 }
+
+#[tokio::test]
+async fn test_multi_session_shared_ai_client() {
+    let provider = ClaudeProvider::new(
+        "http://127.0.0.1:9".to_string(),
+        "test-key".to_string(),
+        "claude-test".to_string(),
+        64,
+    )
+    .unwrap();
+    let client = Arc::new(AiClient::new(
+        Provider::Claude(provider),
+        AiConfig {
+            max_concurrent_requests: Some(3),
+            ..AiConfig::default()
+        },
+    ));
+
+    let policy = PolicyEngine::new(PolicyLevel::Permissive);
+    let supervisor = MultiSessionSupervisor::new(4, policy).with_ai_client(Arc::clone(&client));
+
+    let shared = supervisor.ai_client().unwrap();
+    assert!(Arc::ptr_eq(&shared, &client));
+    assert_eq!(shared.max_concurrency(), Some(3));
+    assert_eq!(supervisor.ai_stats().unwrap().requests, 0);
+}
+
+/// Mock Claude API allowing one response per permit added to the gate.
+async fn spawn_gated_provider(gate: Arc<tokio::sync::Semaphore>) -> String {
+    let app = axum::Router::new().route(
+        "/v1/messages",
+        axum::routing::post(move || {
+            let gate = Arc::clone(&gate);
+            async move {
+                gate.acquire().await.unwrap().forget();
+                axum::Json(serde_json::json!({
+                    "content": [{"text": r#"{"decision": "ALLOW", "reason": "ok"}"#}]
+                }))
+            }
+        }),
+    );
+    let listener = tokio::net::TcpListener::bind("127.0.0.1:0").await.unwrap();
+    let addr = listener.local_addr().unwrap();
+    tokio::spawn(async move { axum::serve(listener, app).await.unwrap() });
+    format!("http://{addr}")
+}
+
+#[tokio::test]
+async fn test_busy_session_does_not_starve_ai_requests_of_others() {
+    let gate = Arc::new(tokio::sync::Semaphore::new(0));
+    let base_url = spawn_gated_provider(Arc::clone(&gate)).await;
+    let provider = ClaudeProvider::new(
+        base_url,
+        "test-key".to_string(),
+        "claude-test".to_string(),
+        64,
+    )
+    .unwrap();
+    let client = AiClient::new(
+        Provider::Claude(provider),
+        AiConfig {
+            max_concurrent_requests: Some(1),
+            ..AiConfig::default()
+        },
+    )
+    .with_fair_share();
+
+    let policy = PolicyEngine::new(PolicyLevel::Permissive);
+    let mut supervisor = MultiSessionSupervisor::new(2, policy).with_ai_client(client);
+    let chatty_id = supervisor
+        .spawn_session("chatty".to_string())
+        .await
+        .unwrap();
+    let quiet_id = supervisor.spawn_session("quiet".to_string()).await.unwrap();
+    let chatty = supervisor
+        .get_session(&chatty_id)
+        .unwrap()
+        .ai_client()
+        .unwrap()
+        .clone();
+    let quiet = supervisor
+        .get_session(&quiet_id)
+        .unwrap()
+        .ai_client()
+        .unwrap()
+        .clone();
+
+    let finished = Arc::new(Mutex::new(Vec::new()));
+    let done = Arc::new(AtomicU64::new(0));
+    let mut calls = tokio::task::JoinSet::new();
+    let mut ask = |client: AiClient, name: &'static str| {
+        let (finished, done) = (Arc::clone(&finished), Arc::clone(&done));
+        calls.spawn(async move {
+            let decision = client
+                .ask_supervisor("Bash", &serde_json::json!({"command": "ls"}), "")
+                .await;
+            finished.lock().unwrap().push(name);
+            done.fetch_add(1, Ordering::SeqCst);
+            decision
+        });
+    };
+    for _ in 0..4 {
+        ask(chatty.clone(), "chatty");
+        tokio::time::sleep(Duration::from_millis(20)).await;
+    }
+    ask(quiet.clone(), "quiet");
+    tokio::time::sleep(Duration::from_millis(50)).await;
+
+    // One chatty request holds the only slot; the rest queue per session
+    let quotas = supervisor.ai_quotas();
+    let queued = |id: &str| quotas.iter().find(|q| q.session == id).unwrap().queued;
+    assert_eq!(queued(&chatty_id), 3);
+    assert_eq!(queued(&quiet_id), 1);
+    assert_eq!(quiet.stats().queued, 1);
+
+    // Answer one request at a time
+    for answered in 1..=5 {
+        gate.add_permits(1);
+        for _ in 0..200 {
+            if done.load(Ordering::SeqCst) >= answered {
+                break;
+            }
+            tokio::time::sleep(Duration::from_millis(10)).await;
+        }
+    }
+    while let Some(result) = calls.join_next().await {
+        assert!(result.unwrap().is_ok());
+    }
+
+    // The quiet session was served right after the first chatty request
+    assert_eq!(
+        *finished.lock().unwrap(),
+        vec!["chatty", "quiet", "chatty", "chatty", "chatty"]
+    );
+    supervisor.wait_all().await;
+    assert!(supervisor.ai_quotas().is_empty());
+}
+
+/// `claude-supervisor multi` with `args`, in the scratch home `home`.
+fn multi_command(home: &std::path::Path, args: &[&str]) -> std::process::Output {
+    std::process::Command::new(env!("CARGO_BIN_EXE_claude-supervisor"))
+        .arg("multi")
+        .args(args)
+        .current_dir(home)
+        .env("HOME", home)
+        .env("GEMINI_API_KEY", "test-key")
+        .output()
+        .unwrap()
+}
+
+#[test]
+fn test_multi_command_shares_one_ai_client() {
+    let home = tempfile::tempdir().unwrap();
+    let output = multi_command(
+        home.path(),
+        &[
+            "--task",
+            "one",
+            "--task",
+            "two",
+            "--weight",
+            "3",
+            "--max-ai-requests",
+            "2",
+        ],
+    );
+    let stdout = String::from_utf8_lossy(&output.stdout);
+    assert!(output.status.success(), "{stdout}");
+    assert!(
+        stdout.contains("AI requests: at most 2 in flight, shared by session weight"),
+        "{stdout}"
+    );
+    assert!(stdout.contains("AI requests: 0 (0 failed)"), "{stdout}");
+
+    let output = multi_command(
+        home.path(),
+        &["--task", "one", "--weight", "1", "--weight", "2"],
+    );
+    assert!(!output.status.success());
+    assert!(String::from_utf8_lossy(&output.stderr).contains("2 --weight values given for 1 tasks"));
+}
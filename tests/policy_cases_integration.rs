@@ -0,0 +1,61 @@
+//! Integration tests for `policy test`.
+
+use std::path::PathBuf;
+use std::process::{Command, Output};
+
+fn fixture(name: &str) -> PathBuf {
+    PathBuf::from(env!("CARGO_MANIFEST_DIR"))
+        .join("tests/fixtures/policy")
+        .join(name)
+}
+
+fn policy_test(cases: &std::path::Path) -> Output {
+    Command::new(env!("CARGO_BIN_EXE_claude-supervisor"))
+        .args(["policy", "test"])
+        .arg(cases)
+        .arg("--config")
+        .arg(fixture("config.toml"))
+        .env_remove("CLAUDE_SUPERVISOR_ROOTS")
+        .output()
+        .expect("Failed to run claude-supervisor")
+}
+
+#[test]
+fn test_example_cases_pass() {
+    let output = policy_test(&fixture("cases.toml"));
+    let stdout = String::from_utf8_lossy(&output.stdout);
+
+    assert!(output.status.success(), "example cases failed:\n{stdout}");
+    assert!(stdout.contains("6 passed, 0 failed"), "{stdout}");
+    assert!(stdout.contains("denied tool"), "{stdout}");
+}
+
+#[test]
+fn test_failing_case_exits_non_zero() {
+    let dir = tempfile::tempdir().unwrap();
+    let cases = dir.path().join("cases.toml");
+    std::fs::write(
+        &cases,
+        r#"cases = [{ name = "too strict", tool = "Read", input = { file_path = "a.rs" }, expect = "deny" }]"#,
+    )
+    .unwrap();
+
+    let output = policy_test(&cases);
+    let stdout = String::from_utf8_lossy(&output.stdout);
+
+    assert!(!output.status.success());
+    assert!(stdout.contains("FAIL"), "{stdout}");
+    assert!(stdout.contains("0 passed, 1 failed"), "{stdout}");
+}
+
+#[test]
+fn test_bypass_attempts_are_caught() {
+    let output = policy_test(&fixture("bypass_attempts.toml"));
+    let stdout = String::from_utf8_lossy(&output.stdout);
+
+    assert!(
+        output.status.success(),
+        "bypass attempts slipped through:\n{stdout}"
+    );
+    assert!(stdout.contains("17 passed, 0 failed"), "{stdout}");
+}
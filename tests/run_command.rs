@@ -30,3 +30,158 @@ fn test_run_command_help() {
         "Expected --auto-continue in help"
     );
 }
+
+#[test]
+fn test_invalid_session_override_fails_before_spawn() {
+    let output = Command::new(env!("CARGO_BIN_EXE_claude-supervisor"))
+        .args([
+            "run",
+            "--claude-bin",
+            "/nonexistent/claude",
+            "--deny-once",
+            ":docker *",
+            "task",
+        ])
+        .output()
+        .expect("Failed to execute command");
+
+    let stderr = String::from_utf8_lossy(&output.stderr);
+    assert!(!output.status.success());
+    assert!(stderr.contains("missing a tool name"), "{stderr}");
+    assert!(!stderr.contains("not found"), "{stderr}");
+}
+
+#[test]
+fn test_session_overrides_only_apply_where_given() {
+    let home = tempfile::tempdir().unwrap();
+    let input = serde_json::json!({
+        "session_id": "s1",
+        "transcript_path": "/tmp/transcript.jsonl",
+        "cwd": home.path(),
+        "hook_event_name": "PreToolUse",
+        "tool_name": "Bash",
+        "tool_input": {"command": "chmod 777 ./build"},
+        "tool_use_id": "toolu_1",
+    });
+    let hook = |overrides: Option<&str>| {
+        use std::io::Write;
+
+        let mut command = Command::new(env!("CARGO_BIN_EXE_claude-supervisor"));
+        command
+            .args(["hook", "pre-tool-use"])
+            .current_dir(home.path())
+            .env("HOME", home.path())
+            .env_remove("CLAUDE_SUPERVISOR_SESSION_OVERRIDES")
+            .stdin(std::process::Stdio::piped())
+            .stdout(std::process::Stdio::piped())
+            .stderr(std::process::Stdio::null());
+        if let Some(overrides) = overrides {
+            command.env("CLAUDE_SUPERVISOR_SESSION_OVERRIDES", overrides);
+        }
+        let mut child = command.spawn().expect("Failed to spawn hook");
+        child
+            .stdin
+            .take()
+            .unwrap()
+            .write_all(input.to_string().as_bytes())
+            .unwrap();
+        let output = child.wait_with_output().unwrap();
+        String::from_utf8_lossy(&output.stdout).into_owned()
+    };
+
+    let allowed = hook(Some(
+        r#"[{"effect": "allow", "rule": "Bash:chmod 777 ./build*"}]"#,
+    ));
+    assert!(
+        allowed.contains(r#""permissionDecision":"allow""#),
+        "{allowed}"
+    );
+
+    // Nothing is written to config, so a later session is back to the rules
+    let denied = hook(None);
+    assert!(
+        denied.contains(r#""permissionDecision":"deny""#),
+        "{denied}"
+    );
+    assert!(!home.path().join(".claude-supervisor.toml").exists());
+}
+
+/// Stand-in for Claude that starts a session and then stays quiet.
+#[cfg(unix)]
+fn quiet_claude(dir: &std::path::Path) -> std::path::PathBuf {
+    use std::os::unix::fs::PermissionsExt;
+
+    let init = r#"{"type":"system","subtype":"init","cwd":".","session_id":"s-1","model":"claude-sonnet-4-5","tools":["Bash"]}"#;
+    let path = dir.join("claude");
+    std::fs::write(&path, format!("#!/bin/sh\necho '{init}'\nexec sleep 30\n")).unwrap();
+    std::fs::set_permissions(&path, std::fs::Permissions::from_mode(0o755)).unwrap();
+    path
+}
+
+#[cfg(unix)]
+#[tokio::test]
+async fn test_run_detach_keeps_session_running_in_background() {
+    use std::time::Duration;
+
+    use claude_supervisor::ipc::{ControlRequest, ControlResponse, IpcClient};
+    use claude_supervisor::supervisor::DetachedSession;
+
+    let home = tempfile::tempdir().unwrap();
+    let data = home.path().join("data");
+    let output = Command::new(env!("CARGO_BIN_EXE_claude-supervisor"))
+        .args(["run", "--no-ai", "--detach", "--name", "detach-test"])
+        .arg("--claude-bin")
+        .arg(quiet_claude(home.path()))
+        .arg("task")
+        .current_dir(home.path())
+        .env("HOME", home.path())
+        .env("CLAUDE_SUPERVISOR_DATA_DIR", &data)
+        .env_remove("CLAUDE_SUPERVISOR_CLAUDE_BIN")
+        .env_remove("CLAUDE_SUPERVISOR_DETACHED")
+        .env("RUST_LOG", "warn")
+        .stdin(std::process::Stdio::null())
+        .output()
+        .expect("Failed to execute command");
+    let stdout = String::from_utf8_lossy(&output.stdout);
+    assert!(output.status.success(), "{stdout}");
+    assert!(stdout.contains("Session detach-test detached"), "{stdout}");
+
+    let session =
+        DetachedSession::in_dirs("detach-test", data.join("detached"), data.join("sockets"));
+    assert!(session.pid().is_some_and(|pid| pid != std::process::id()));
+    assert!(session.is_running());
+
+    // The status is published once the supervision loop runs
+    let client = IpcClient::with_path(session.socket_path());
+    let mut name = None;
+    let mut report = None;
+    for _ in 0..50 {
+        if let ControlResponse::Status { status, health } =
+            client.control(ControlRequest::Status).await.unwrap()
+        {
+            name = status.name;
+            report = health;
+        }
+        if name.is_some() {
+            break;
+        }
+        tokio::time::sleep(Duration::from_millis(100)).await;
+    }
+    assert_eq!(name.as_deref(), Some("detach-test"));
+    let report = report.expect("status includes health");
+    assert!(report
+        .check("event_loop")
+        .is_some_and(|check| check.healthy));
+
+    let response = client.control(ControlRequest::Stop).await.unwrap();
+    assert!(matches!(response, ControlResponse::Accepted));
+    for _ in 0..100 {
+        if !session.pid_path().exists() {
+            break;
+        }
+        tokio::time::sleep(Duration::from_millis(100)).await;
+    }
+    assert!(!session.pid_path().exists());
+    let log = std::fs::read_to_string(session.log_path()).unwrap();
+    assert!(log.contains("Session: detach-test"), "{log}");
+}
@@ -0,0 +1,127 @@
+//! End-to-end tests of `run` against the scripted `fake-claude` stand-in.
+//!
+//! Each test plays a scenario from `tests/scenarios` through the real
+//! binary, offline and without the AI supervisor.
+
+use std::path::Path;
+use std::process::{Command, Output, Stdio};
+use std::time::{Duration, Instant};
+
+/// `claude-supervisor run` with `fake-claude` playing `scenario`, in the
+/// scratch home and working directory `home`.
+fn scenario_command(scenario: &str, home: &Path) -> Command {
+    let scenario = Path::new(env!("CARGO_MANIFEST_DIR"))
+        .join("tests/scenarios")
+        .join(scenario);
+    let mut command = Command::new(env!("CARGO_BIN_EXE_claude-supervisor"));
+    command
+        .args([
+            "run",
+            "--no-ai",
+            "--claude-bin",
+            env!("CARGO_BIN_EXE_fake-claude"),
+        ])
+        .arg("--scenario")
+        .arg(&scenario)
+        .arg("Summarize the README")
+        .current_dir(home)
+        .env("HOME", home)
+        .env_remove("CLAUDE_SUPERVISOR_CLAUDE_BIN")
+        .env_remove("CLAUDE_SUPERVISOR_DATA_DIR")
+        .env("RUST_LOG", "warn")
+        .stdin(Stdio::null());
+    command
+}
+
+/// Run `claude-supervisor run` with `fake-claude` playing `scenario`, in a
+/// scratch home and working directory.
+fn run_scenario(scenario: &str) -> Output {
+    let home = tempfile::tempdir().unwrap();
+    scenario_command(scenario, home.path())
+        .output()
+        .expect("Failed to run supervisor")
+}
+
+#[cfg(unix)]
+#[test]
+fn test_clean_completion() {
+    let output = run_scenario("clean_completion.jsonl");
+    let stdout = String::from_utf8_lossy(&output.stdout);
+    assert!(output.status.success(), "{stdout}");
+    assert!(stdout.contains("[ALLOW]"), "{stdout}");
+    assert!(stdout.contains("policy: 1 approved, 0 denied"), "{stdout}");
+}
+
+#[cfg(unix)]
+#[test]
+fn test_dangerous_command_is_killed() {
+    let output = run_scenario("dangerous_command.jsonl");
+    let stdout = String::from_utf8_lossy(&output.stdout);
+    // The policy-deny kill cause exit code; the scenario's result is never reached
+    assert_eq!(output.status.code(), Some(10), "{stdout}");
+    assert!(stdout.contains("[DENY]"), "{stdout}");
+    assert!(!stdout.contains("Cleaned up."), "{stdout}");
+}
+
+#[cfg(unix)]
+#[test]
+fn test_stuck_loop_calls_are_each_supervised() {
+    let output = run_scenario("stuck_loop.jsonl");
+    let stdout = String::from_utf8_lossy(&output.stdout);
+    assert!(output.status.success(), "{stdout}");
+    assert!(stdout.contains("policy: 6 approved, 0 denied"), "{stdout}");
+}
+
+#[cfg(unix)]
+#[test]
+fn test_auth_failure_reports_login_hint() {
+    let output = run_scenario("auth_failure.jsonl");
+    let stderr = String::from_utf8_lossy(&output.stderr);
+    assert_eq!(output.status.code(), Some(1), "{stderr}");
+    assert!(stderr.contains("claude login"), "{stderr}");
+}
+
+#[cfg(unix)]
+#[test]
+fn test_handoff_keeps_claude_writing() {
+    let home = tempfile::tempdir().unwrap();
+    let data_dir = home.path().join("data");
+    let old = scenario_command("handoff.jsonl", home.path())
+        .args(["--name", "handoff-test"])
+        .env("CLAUDE_SUPERVISOR_DATA_DIR", &data_dir)
+        .stdout(Stdio::piped())
+        .stderr(Stdio::piped())
+        .spawn()
+        .expect("Failed to run supervisor");
+
+    // fake-claude sleeps after its init event, so the handoff lands before
+    // the rest of its output
+    let socket = data_dir.join("sockets/handoff-test.hooks.sock");
+    let deadline = Instant::now() + Duration::from_secs(10);
+    while !socket.exists() && Instant::now() < deadline {
+        std::thread::sleep(Duration::from_millis(50));
+    }
+    std::thread::sleep(Duration::from_millis(500));
+    let adopter = Command::new(env!("CARGO_BIN_EXE_claude-supervisor"))
+        .args(["upgrade-handoff", "--session", "handoff-test"])
+        .current_dir(home.path())
+        .env("HOME", home.path())
+        .env("CLAUDE_SUPERVISOR_DATA_DIR", &data_dir)
+        .env("RUST_LOG", "warn")
+        .stdin(Stdio::null())
+        .output()
+        .expect("Failed to run upgrade-handoff");
+    let old = old.wait_with_output().unwrap();
+
+    let adopter_stdout = String::from_utf8_lossy(&adopter.stdout);
+    assert!(adopter.status.success(), "{adopter_stdout}");
+    assert!(adopter_stdout.contains("Claude exited"), "{adopter_stdout}");
+    // The old supervisor read all 21 events fake-claude wrote after the
+    // handoff, up to its result
+    let old_stdout = String::from_utf8_lossy(&old.stdout);
+    assert!(old.status.success(), "{old_stdout}");
+    assert!(
+        old_stdout.contains("Claude exited after the handoff (21 more events)"),
+        "{old_stdout}"
+    );
+}
@@ -1,6 +1,6 @@
 use claude_supervisor::supervisor::{
-    AggregatedStats, MultiSessionError, MultiSessionSupervisor, PolicyEngine, PolicyLevel,
-    SessionMeta,
+    AggregatedStats, DecisionSource, KillCause, KillSwitch, MultiSessionError,
+    MultiSessionSupervisor, PolicyEngine, PolicyLevel, SessionMeta, SessionStats, SupervisorResult,
 };
 
 #[test]
@@ -35,6 +35,35 @@ fn test_aggregated_stats_default() {
     assert_eq!(stats.total_tool_calls, 0);
 }
 
+#[test]
+fn test_aggregated_stats_record_kill() {
+    let mut stats = AggregatedStats::default();
+    stats.record_kill(KillCause::PolicyDeny);
+    stats.record_kill(KillCause::PolicyDeny);
+    stats.record_kill(KillCause::AiError);
+
+    assert_eq!(stats.kills_by_cause.get(&KillCause::PolicyDeny), Some(&2));
+    assert_eq!(stats.kills_by_cause.get(&KillCause::AiError), Some(&1));
+    assert!(!stats.kills_by_cause.contains_key(&KillCause::Budget));
+}
+
+#[test]
+fn test_aggregated_stats_sum_decision_sources() {
+    let mut first = SessionStats::default();
+    first.by_source.record_approval(DecisionSource::Policy);
+    first.by_source.record_approval(DecisionSource::Ai);
+    let mut second = SessionStats::default();
+    second.by_source.record_denial(DecisionSource::Ai);
+
+    let mut stats = AggregatedStats::default();
+    stats.add(&first, true);
+    stats.add(&second, false);
+
+    assert_eq!(stats.by_source.policy.approvals, 1);
+    assert_eq!(stats.by_source.ai.approvals, 1);
+    assert_eq!(stats.by_source.ai.denials, 1);
+}
+
 #[test]
 fn test_session_meta_cancellation() {
     let meta = SessionMeta::new("test-id".to_string(), "test task".to_string());
@@ -220,3 +249,20 @@ async fn test_spawn_and_wait_all() {
 
     assert_eq!(results.len(), 3);
 }
+
+#[tokio::test]
+async fn test_kill_switch_halts_every_session() {
+    let dir = tempfile::tempdir().unwrap();
+    let kill_switch = KillSwitch::new(dir.path().join("KILL"));
+    std::fs::write(kill_switch.path(), b"").unwrap();
+    let policy = PolicyEngine::new(PolicyLevel::Permissive);
+    let mut supervisor = MultiSessionSupervisor::new(3, policy).with_kill_switch(kill_switch);
+
+    let tasks = vec!["Task 1".to_string(), "Task 2".to_string()];
+    let results = supervisor.spawn_and_wait_all(tasks).await.unwrap();
+
+    assert_eq!(results.len(), 2);
+    for result in results {
+        assert!(matches!(result.result, Ok(SupervisorResult::Halted { .. })));
+    }
+}
@@ -28,7 +28,7 @@ fn policy_level_serialization() {
 fn policy_decision_serialization() {
     let decisions = [
         PolicyDecision::Allow,
-        PolicyDecision::Deny("Test denial".to_string()),
+        PolicyDecision::deny("Test denial"),
         PolicyDecision::Escalate("Test escalation".to_string()),
     ];
 
@@ -132,3 +132,88 @@ fn policy_engine_accessors() {
     assert_eq!(engine.level(), PolicyLevel::Moderate);
     assert!(!engine.blocklist().is_empty());
 }
+
+#[test]
+fn policy_engine_rewrite_reaches_hook_output() {
+    use claude_supervisor::config::RewriteRuleConfig;
+    use claude_supervisor::hooks::HookHandler;
+    use claude_supervisor::supervisor::{CommandRewriter, RewriteRule};
+
+    let mut rewriter = CommandRewriter::new();
+    rewriter.add_rule(
+        RewriteRule::from_config(&RewriteRuleConfig {
+            name: "keep commit hooks".to_string(),
+            program: "git".to_string(),
+            subcommand: Some("commit".to_string()),
+            remove_args: vec!["--no-verify".to_string()],
+            ..RewriteRuleConfig::default()
+        })
+        .unwrap(),
+    );
+    let mut engine = PolicyEngine::new(PolicyLevel::Permissive);
+    engine.set_command_rewriter(rewriter);
+
+    let input = json!({
+        "hook_event_name": "PreToolUse",
+        "session_id": "rewrite-session",
+        "tool_name": "Bash",
+        "tool_input": {"command": "git commit --no-verify -m 'skip checks'"},
+    });
+    let result = HookHandler::new(engine)
+        .handle_json(&input.to_string())
+        .unwrap();
+    assert!(!result.should_deny);
+    let response: serde_json::Value = serde_json::from_str(&result.response).unwrap();
+    assert_eq!(
+        response["hookSpecificOutput"]["permissionDecision"],
+        json!("allow")
+    );
+    assert_eq!(
+        response["hookSpecificOutput"]["updatedInput"],
+        json!({"command": "git commit -m 'skip checks'"})
+    );
+}
+
+/// Example validator: `WebFetch` may only reach the listed hosts.
+struct HostAllowlist(Vec<&'static str>);
+
+impl claude_supervisor::supervisor::ToolValidator for HostAllowlist {
+    fn validate(&self, tool_input: &serde_json::Value) -> PolicyDecision {
+        let host = tool_input
+            .get("url")
+            .and_then(serde_json::Value::as_str)
+            .and_then(|url| url::Url::parse(url).ok())
+            .and_then(|url| url.host_str().map(str::to_string));
+        match host {
+            Some(host) if self.0.contains(&host.as_str()) => PolicyDecision::Allow,
+            Some(host) => PolicyDecision::deny(format!("WebFetch may not reach {host}")),
+            None => PolicyDecision::Escalate("WebFetch URL has no host".to_string()),
+        }
+    }
+
+    fn name(&self) -> &'static str {
+        "fetch hosts"
+    }
+}
+
+#[test]
+fn policy_engine_validator_restricts_webfetch_hosts() {
+    let mut engine = PolicyEngine::new(PolicyLevel::Permissive);
+    engine.allow_tool("WebFetch");
+    engine.register_validator("WebFetch", Box::new(HostAllowlist(vec!["docs.rs"])));
+
+    let fetch = |url: &str| engine.evaluate("WebFetch", &json!({"url": url, "prompt": "read"}));
+    assert_eq!(fetch("https://docs.rs/serde"), PolicyDecision::Allow);
+    let PolicyDecision::Deny(reason) = fetch("https://evil.example/exfil") else {
+        panic!("expected a denial");
+    };
+    assert_eq!(reason.as_str(), "WebFetch may not reach evil.example");
+    assert_eq!(reason.rule_id.as_deref(), Some("validator: fetch hosts"));
+    assert!(matches!(fetch("not a url"), PolicyDecision::Escalate(_)));
+
+    // Other tools are not validated
+    assert_eq!(
+        engine.evaluate("WebSearch", &json!({"query": "evil.example"})),
+        PolicyDecision::Allow
+    );
+}
@@ -1,8 +1,15 @@
 //! Integration tests for supervisor runner.
 
-use claude_supervisor::cli::{ClaudeEvent, ResultEvent, SystemInit, ToolUse};
+use claude_supervisor::cli::{
+    ClaudeEvent, ClaudeProcess, ClaudeProcessBuilder, ResultEvent, SystemInit, ToolUse,
+};
+use claude_supervisor::config::{
+    ContextRecoveryConfig, ContextRecoveryMode, DecisionAuthority, IdleNudgeConfig,
+};
+use claude_supervisor::ipc::{EscalationResponse, HookDecisionLog, HookDecisionReport};
 use claude_supervisor::supervisor::{
-    PolicyEngine, PolicyLevel, SessionState, Supervisor, SupervisorError, SupervisorResult,
+    KillCause, KillSwitch, PolicyEngine, PolicyLevel, RecoveryPlan, RetryHint, SessionState,
+    SessionStats, Supervisor, SupervisorError, SupervisorResult, DEFAULT_NUDGE_MESSAGE,
     DEFAULT_TERMINATE_TIMEOUT,
 };
 use serde_json::json;
@@ -29,6 +36,8 @@ fn supervisor_result_variants() {
         },
         SupervisorResult::Killed {
             reason: "Policy violation".to_string(),
+            cause: KillCause::PolicyDeny,
+            retry_hint: RetryHint::DoNotRetry,
         },
         SupervisorResult::ProcessExited,
     ];
@@ -141,8 +150,14 @@ async fn supervisor_denies_dangerous_commands() {
     let result = supervisor.run_without_process().await.unwrap();
 
     match result {
-        SupervisorResult::Killed { reason } => {
+        SupervisorResult::Killed {
+            reason,
+            cause,
+            retry_hint,
+        } => {
             assert!(reason.contains("network exfiltration"));
+            assert_eq!(cause, KillCause::PolicyDeny);
+            assert_eq!(retry_hint, RetryHint::DoNotRetry);
         }
         _ => panic!("Expected Killed result"),
     }
@@ -191,9 +206,12 @@ async fn supervisor_handles_message_stop() {
     let mut supervisor = Supervisor::new(policy, rx);
 
     tx.send(ClaudeEvent::MessageStop).await.unwrap();
+    drop(tx);
 
+    // A turn boundary does not end the session
     let result = supervisor.run_without_process().await.unwrap();
-    assert!(matches!(result, SupervisorResult::Completed { .. }));
+    assert!(matches!(result, SupervisorResult::ProcessExited));
+    assert_eq!(supervisor.stats().turns, 1);
 }
 
 #[tokio::test]
@@ -215,9 +233,20 @@ async fn supervisor_strict_policy_denies_escalation_in_phase1() {
     // Escalation should deny in Phase 1 (no AI supervisor)
     let result = supervisor.run_without_process().await.unwrap();
     match result {
-        SupervisorResult::Killed { reason } => {
+        SupervisorResult::Killed {
+            reason,
+            cause,
+            retry_hint,
+        } => {
             assert!(reason.contains("Escalation denied"));
             assert!(reason.contains("no AI supervisor"));
+            assert_eq!(cause, KillCause::EscalationUnavailable);
+            assert_eq!(
+                retry_hint,
+                RetryHint::AllowTool {
+                    tool: "CustomTool".to_string()
+                }
+            );
         }
         _ => panic!("Expected Killed result for escalation in Phase 1"),
     }
@@ -304,3 +333,428 @@ fn supervisor_result_cancelled_variant() {
     let debug = format!("{result:?}");
     assert!(debug.contains("Cancelled"));
 }
+
+/// Spawn the scripted stand-in for `claude` playing `scenario`.
+#[cfg(unix)]
+fn spawn_scenario(scenario: &std::path::Path) -> ClaudeProcess {
+    let builder = ClaudeProcessBuilder::new("task").scenario(scenario);
+    ClaudeProcess::spawn_with_binary(env!("CARGO_BIN_EXE_fake-claude"), &builder).unwrap()
+}
+
+/// Write a scenario that only prints `stderr`, then stays quiet for 30s.
+#[cfg(unix)]
+fn quiet_scenario(dir: &std::path::Path, stderr: &str) -> std::path::PathBuf {
+    let path = dir.join("quiet.jsonl");
+    let steps = [
+        json!({"fake": "stderr", "line": stderr}),
+        json!({"fake": "sleep", "ms": 30_000}),
+    ];
+    let content = steps.map(|step| step.to_string()).join("\n");
+    std::fs::write(&path, content).unwrap();
+    path
+}
+
+#[cfg(unix)]
+#[tokio::test]
+async fn supervisor_reports_login_prompt_as_startup_failure() {
+    let dir = tempfile::tempdir().unwrap();
+    let process = spawn_scenario(&quiet_scenario(
+        dir.path(),
+        "Invalid API key · Please run /login",
+    ));
+
+    let mut supervisor =
+        Supervisor::from_process(process, PolicyEngine::new(PolicyLevel::Permissive)).unwrap();
+    supervisor.set_startup_timeout(Duration::from_millis(300));
+
+    let result = tokio::time::timeout(Duration::from_secs(10), supervisor.run())
+        .await
+        .expect("startup failure detected before the child exits")
+        .unwrap();
+    let SupervisorResult::StartupFailed { hint } = result else {
+        panic!("expected StartupFailed, got {result:?}");
+    };
+    assert!(hint.contains("claude login"), "{hint}");
+    assert_eq!(supervisor.state(), SessionState::Failed);
+}
+
+#[cfg(unix)]
+#[tokio::test]
+async fn supervisor_keeps_waiting_without_auth_markers() {
+    let dir = tempfile::tempdir().unwrap();
+    let process = spawn_scenario(&quiet_scenario(dir.path(), "warning: slow network"));
+
+    let cancel = CancellationToken::new();
+    let mut supervisor =
+        Supervisor::from_process(process, PolicyEngine::new(PolicyLevel::Permissive))
+            .unwrap()
+            .with_cancellation(cancel.clone());
+    supervisor.set_startup_timeout(Duration::from_millis(100));
+    tokio::spawn(async move {
+        tokio::time::sleep(Duration::from_millis(500)).await;
+        cancel.cancel();
+    });
+
+    let result = supervisor.run().await.unwrap();
+    assert!(matches!(result, SupervisorResult::Cancelled), "{result:?}");
+}
+
+#[cfg(unix)]
+#[tokio::test]
+async fn supervisor_halts_when_kill_switch_engaged() {
+    let dir = tempfile::tempdir().unwrap();
+    let process = spawn_scenario(&quiet_scenario(dir.path(), "starting"));
+
+    let kill_switch =
+        KillSwitch::new(dir.path().join("KILL")).with_poll_interval(Duration::from_millis(50));
+    let path = kill_switch.path().to_path_buf();
+    let mut supervisor =
+        Supervisor::from_process(process, PolicyEngine::new(PolicyLevel::Permissive)).unwrap();
+    supervisor.set_kill_switch(kill_switch);
+    tokio::spawn(async move {
+        tokio::time::sleep(Duration::from_millis(300)).await;
+        std::fs::write(path, b"").unwrap();
+    });
+
+    // The child sleeps for 30s, so only the kill switch ends the session in time
+    let result = tokio::time::timeout(Duration::from_secs(10), supervisor.run())
+        .await
+        .expect("kill switch halts the session")
+        .unwrap();
+    assert!(
+        matches!(result, SupervisorResult::Halted { .. }),
+        "{result:?}"
+    );
+    assert_eq!(supervisor.state(), SessionState::Halted);
+}
+
+/// Write a stand-in for `claude` that runs out of context unless resumed,
+/// or always if `always_exhaust` is set.
+#[cfg(unix)]
+fn exhausting_claude(dir: &std::path::Path, always_exhaust: bool) -> std::path::PathBuf {
+    use std::os::unix::fs::PermissionsExt;
+
+    let exhausted =
+        r#"{"type":"result","result":"Prompt is too long","session_id":"s-1","is_error":true}"#;
+    let done = r#"{"type":"result","result":"Done","session_id":"s-1","is_error":false}"#;
+    let resumed = if always_exhaust { exhausted } else { done };
+    let script = format!(
+        "#!/bin/sh\nfor arg in \"$@\"; do\n  if [ \"$arg\" = --resume ]; then echo '{resumed}'; exit 0; fi\ndone\necho '{exhausted}'\n"
+    );
+    let path = dir.join("claude");
+    std::fs::write(&path, script).unwrap();
+    std::fs::set_permissions(&path, std::fs::Permissions::from_mode(0o755)).unwrap();
+    path
+}
+
+/// Run `binary` with context recovery by resuming, returning the result and
+/// the plans the session was respawned with.
+#[cfg(unix)]
+async fn run_with_recovery(
+    binary: &std::path::Path,
+    max_attempts: u32,
+) -> (SupervisorResult, Vec<RecoveryPlan>, usize) {
+    let binary = binary.to_str().unwrap().to_string();
+    let builder = ClaudeProcessBuilder::new("task");
+    let process = ClaudeProcess::spawn_with_binary(&binary, &builder).unwrap();
+    let mut supervisor =
+        Supervisor::from_process(process, PolicyEngine::new(PolicyLevel::Permissive)).unwrap();
+    let config = ContextRecoveryConfig {
+        mode: ContextRecoveryMode::Resume,
+        max_attempts,
+    };
+
+    let mut plans = Vec::new();
+    let result = tokio::time::timeout(
+        Duration::from_secs(10),
+        supervisor.run_with_context_recovery(&config, |plan| {
+            let builder = match &plan {
+                RecoveryPlan::Resume { session_id } => builder
+                    .clone()
+                    .with_prompt("continue")
+                    .resume(session_id.clone()),
+                RecoveryPlan::Fresh { prompt } => builder.clone().with_prompt(prompt.clone()),
+                RecoveryPlan::Nudge {
+                    session_id,
+                    message,
+                } => builder
+                    .clone()
+                    .with_prompt(message.clone())
+                    .resume(session_id.clone()),
+            };
+            plans.push(plan);
+            ClaudeProcess::spawn_with_binary(&binary, &builder)
+        }),
+    )
+    .await
+    .expect("recovered session finishes")
+    .unwrap();
+    (result, plans, supervisor.context_recoveries().len())
+}
+
+#[cfg(unix)]
+#[tokio::test]
+async fn supervisor_resumes_session_out_of_context() {
+    let dir = tempfile::tempdir().unwrap();
+    let binary = exhausting_claude(dir.path(), false);
+
+    let (result, plans, recoveries) = run_with_recovery(&binary, 1).await;
+    assert!(
+        matches!(result, SupervisorResult::Completed { .. }),
+        "{result:?}"
+    );
+    assert_eq!(
+        plans,
+        vec![RecoveryPlan::Resume {
+            session_id: "s-1".to_string()
+        }]
+    );
+    assert_eq!(recoveries, 1);
+}
+
+#[cfg(unix)]
+#[tokio::test]
+async fn supervisor_gives_up_after_max_recoveries() {
+    let dir = tempfile::tempdir().unwrap();
+    let binary = exhausting_claude(dir.path(), true);
+
+    let (result, plans, recoveries) = run_with_recovery(&binary, 1).await;
+    assert!(
+        matches!(result, SupervisorResult::ContextExhausted { .. }),
+        "{result:?}"
+    );
+    assert_eq!(plans.len(), 1);
+    assert_eq!(recoveries, 1);
+}
+
+/// Write a stand-in for `claude` that reports its session and then goes
+/// quiet, finishing only when resumed.
+#[cfg(unix)]
+fn stalling_claude(dir: &std::path::Path) -> std::path::PathBuf {
+    use std::os::unix::fs::PermissionsExt;
+
+    let init = r#"{"type":"system","subtype":"init","cwd":".","session_id":"s-1","model":"claude-sonnet-4-5","tools":["Bash"]}"#;
+    let done = r#"{"type":"result","result":"Done","session_id":"s-1","is_error":false}"#;
+    let script = format!(
+        "#!/bin/sh\nfor arg in \"$@\"; do\n  if [ \"$arg\" = --resume ]; then echo '{done}'; exit 0; fi\ndone\necho '{init}'\nexec sleep 30\n"
+    );
+    let path = dir.join("claude");
+    std::fs::write(&path, script).unwrap();
+    std::fs::set_permissions(&path, std::fs::Permissions::from_mode(0o755)).unwrap();
+    path
+}
+
+#[cfg(unix)]
+#[tokio::test]
+async fn supervisor_nudges_idle_session() {
+    let dir = tempfile::tempdir().unwrap();
+    let binary = stalling_claude(dir.path());
+    let binary = binary.to_str().unwrap().to_string();
+    let builder = ClaudeProcessBuilder::new("task");
+    let process = ClaudeProcess::spawn_with_binary(&binary, &builder).unwrap();
+    let mut supervisor =
+        Supervisor::from_process(process, PolicyEngine::new(PolicyLevel::Permissive)).unwrap();
+    supervisor.set_idle_nudge(&IdleNudgeConfig {
+        after_secs: Some(1),
+        max_nudges: 1,
+        ..IdleNudgeConfig::default()
+    });
+
+    let mut plans = Vec::new();
+    let result = tokio::time::timeout(
+        Duration::from_secs(10),
+        supervisor.run_with_context_recovery(&ContextRecoveryConfig::default(), |plan| {
+            let RecoveryPlan::Nudge {
+                ref session_id,
+                ref message,
+            } = plan
+            else {
+                panic!("unexpected plan {plan:?}");
+            };
+            let builder = builder
+                .clone()
+                .with_prompt(message.clone())
+                .resume(session_id.clone());
+            plans.push(plan);
+            ClaudeProcess::spawn_with_binary(&binary, &builder)
+        }),
+    )
+    .await
+    .expect("nudged session finishes")
+    .unwrap();
+
+    assert!(
+        matches!(result, SupervisorResult::Completed { .. }),
+        "{result:?}"
+    );
+    assert_eq!(
+        plans,
+        vec![RecoveryPlan::Nudge {
+            session_id: "s-1".to_string(),
+            message: DEFAULT_NUDGE_MESSAGE.to_string(),
+        }]
+    );
+    let nudges = supervisor.idle_nudges();
+    assert_eq!(nudges.len(), 1);
+    assert_eq!(nudges[0].session_id, "s-1");
+    assert!(nudges[0].idle_for >= Duration::from_secs(1));
+}
+
+/// Run one tool call past a runner that denies `Bash`, with the hook having
+/// reported `hook` for it after `report_delay`.
+async fn run_with_hook_decision(
+    authority: DecisionAuthority,
+    tool: &str,
+    hook: Option<EscalationResponse>,
+    report_delay: Duration,
+) -> (SupervisorResult, SessionStats) {
+    let (tx, rx) = mpsc::channel(32);
+    let mut policy = PolicyEngine::new(PolicyLevel::Permissive);
+    policy.deny_tool("Bash");
+    let mut supervisor = Supervisor::new(policy, rx);
+    let log = HookDecisionLog::new();
+    supervisor.set_decision_authority(authority);
+    supervisor.set_hook_decisions(log.clone(), Duration::from_millis(300));
+
+    if let Some(decision) = hook {
+        tokio::spawn(async move {
+            tokio::time::sleep(report_delay).await;
+            log.record(HookDecisionReport {
+                session_id: "s1".to_string(),
+                tool_use_id: "toolu_1".to_string(),
+                tool_name: "any".to_string(),
+                decision,
+            });
+        });
+    }
+    tx.send(ClaudeEvent::ToolUse(ToolUse {
+        id: "toolu_1".to_string(),
+        name: tool.to_string(),
+        input: json!({"command": "ls"}),
+    }))
+    .await
+    .unwrap();
+    drop(tx);
+
+    let result = supervisor.run_without_process().await.unwrap();
+    (result, supervisor.stats())
+}
+
+fn hook_deny() -> EscalationResponse {
+    EscalationResponse::Deny {
+        reason: "hook says no".to_string(),
+    }
+}
+
+fn is_killed(result: &SupervisorResult) -> bool {
+    matches!(
+        result,
+        SupervisorResult::Killed {
+            cause: KillCause::PolicyDeny,
+            ..
+        }
+    )
+}
+
+#[tokio::test]
+async fn runner_authority_ignores_hook_decisions() {
+    let hook_allow = Some(EscalationResponse::Allow);
+    let (result, _) = run_with_hook_decision(
+        DecisionAuthority::Runner,
+        "Bash",
+        hook_allow,
+        Duration::ZERO,
+    )
+    .await;
+    assert!(is_killed(&result), "{result:?}");
+
+    let (result, stats) = run_with_hook_decision(
+        DecisionAuthority::Runner,
+        "Read",
+        Some(hook_deny()),
+        Duration::ZERO,
+    )
+    .await;
+    assert!(matches!(result, SupervisorResult::ProcessExited));
+    assert_eq!(stats.approvals, 1);
+}
+
+#[tokio::test]
+async fn hook_authority_accepts_hook_decisions() {
+    // The hook allowed a call the runner would deny: the tool already ran
+    let hook_allow = Some(EscalationResponse::Allow);
+    let (result, stats) =
+        run_with_hook_decision(DecisionAuthority::Hook, "Bash", hook_allow, Duration::ZERO).await;
+    assert!(
+        matches!(result, SupervisorResult::ProcessExited),
+        "{result:?}"
+    );
+    assert_eq!(stats.approvals, 1);
+
+    // The hook denied a call the runner would allow: counted, not killed
+    let (result, stats) = run_with_hook_decision(
+        DecisionAuthority::Hook,
+        "Read",
+        Some(hook_deny()),
+        Duration::ZERO,
+    )
+    .await;
+    assert!(matches!(result, SupervisorResult::ProcessExited));
+    assert_eq!(stats.denials, 1);
+    assert_eq!(stats.approvals, 0);
+}
+
+#[tokio::test]
+async fn hook_authority_waits_for_late_report() {
+    let (result, stats) = run_with_hook_decision(
+        DecisionAuthority::Hook,
+        "Read",
+        Some(hook_deny()),
+        Duration::from_millis(50),
+    )
+    .await;
+    assert!(matches!(result, SupervisorResult::ProcessExited));
+    assert_eq!(stats.denials, 1);
+}
+
+#[tokio::test]
+async fn hook_authority_falls_back_to_runner_without_report() {
+    let (result, _) =
+        run_with_hook_decision(DecisionAuthority::Hook, "Bash", None, Duration::ZERO).await;
+    assert!(is_killed(&result), "{result:?}");
+}
+
+#[tokio::test]
+async fn strictest_authority_takes_either_denial() {
+    let hook_allow = Some(EscalationResponse::Allow);
+    let (result, _) = run_with_hook_decision(
+        DecisionAuthority::Strictest,
+        "Bash",
+        hook_allow,
+        Duration::ZERO,
+    )
+    .await;
+    assert!(is_killed(&result), "{result:?}");
+
+    let (result, _) = run_with_hook_decision(
+        DecisionAuthority::Strictest,
+        "Read",
+        Some(hook_deny()),
+        Duration::ZERO,
+    )
+    .await;
+    let SupervisorResult::Killed { reason, .. } = result else {
+        panic!("expected a kill, got {result:?}");
+    };
+    assert!(reason.contains("Denied by hook: hook says no"), "{reason}");
+
+    let (result, stats) = run_with_hook_decision(
+        DecisionAuthority::Strictest,
+        "Read",
+        Some(EscalationResponse::Allow),
+        Duration::ZERO,
+    )
+    .await;
+    assert!(matches!(result, SupervisorResult::ProcessExited));
+    assert_eq!(stats.approvals, 1);
+}
@@ -4,7 +4,7 @@ use std::path::PathBuf;
 
 use claude_supervisor::config::WorktreeConfig;
 use claude_supervisor::worktree::{
-    Worktree, WorktreeError, WorktreeManager, WorktreeRegistry, WorktreeStatus,
+    self, Worktree, WorktreeError, WorktreeGroup, WorktreeManager, WorktreeRegistry, WorktreeStatus,
 };
 use tempfile::TempDir;
 
@@ -254,6 +254,103 @@ async fn test_worktree_registry_integration() {
     assert_eq!(retrieved.branch, worktree.branch);
 }
 
+#[tokio::test]
+async fn test_worktree_group_create_and_remove() {
+    let api = create_test_repo().await;
+    let web = create_test_repo().await;
+    let repos = vec![api.path().to_path_buf(), web.path().to_path_buf()];
+    let config = WorktreeConfig::default();
+
+    let group = WorktreeGroup::create(&repos, "multi", &config)
+        .await
+        .unwrap();
+    let paths = group.paths();
+    assert_eq!(paths.len(), 2);
+    assert!(paths.iter().all(|path| path.exists()));
+    assert!(paths[0].starts_with(api.path()));
+    assert!(paths[1].starts_with(web.path()));
+
+    // Both registries know the whole group
+    let web_registry_path = WorktreeRegistry::default_path(&web.path().join(".worktrees"));
+    let web_registry = WorktreeRegistry::load(&web_registry_path).unwrap();
+    assert_eq!(web_registry.find_group(group.id()).len(), 2);
+
+    // The group can be removed from the second repository
+    let loaded =
+        WorktreeGroup::from_registry(&web_registry, group.id(), web.path(), &config).unwrap();
+    assert_eq!(loaded.paths(), paths);
+    let removal = loaded.remove(false, true).await;
+    assert!(removal.failed.is_empty());
+    assert_eq!(removal.removed.len(), 2);
+    assert!(paths.iter().all(|path| !path.exists()));
+
+    for repo in [api.path(), web.path()] {
+        let registry_path = WorktreeRegistry::default_path(&repo.join(".worktrees"));
+        let registry = WorktreeRegistry::load(&registry_path).unwrap();
+        assert!(registry.find_group(group.id()).is_empty());
+    }
+}
+
+#[tokio::test]
+async fn test_worktree_group_rolls_back_on_failure() {
+    let api = create_test_repo().await;
+    let not_repo = TempDir::new().unwrap();
+    let repos = vec![api.path().to_path_buf(), not_repo.path().to_path_buf()];
+
+    let result = WorktreeGroup::create(&repos, "multi", &WorktreeConfig::default()).await;
+    assert!(matches!(result, Err(WorktreeError::NotGitRepo)));
+    assert!(!api.path().join(".worktrees").join("multi").exists());
+}
+
+#[tokio::test]
+async fn test_worktree_prune_reports_partial_failure() {
+    let temp_dir = create_test_repo().await;
+    let manager =
+        WorktreeManager::new(temp_dir.path().to_path_buf(), WorktreeConfig::default()).unwrap();
+    let registry_path = WorktreeRegistry::default_path(&manager.worktree_dir());
+    let mut registry = WorktreeRegistry::new();
+    for name in ["stale-a", "stale-b", "locked", "dirty"] {
+        let mut wt = manager.create(name).await.unwrap();
+        wt.last_accessed = Some(chrono::Utc::now() - chrono::Duration::hours(48));
+        registry.upsert(wt);
+    }
+    registry.upsert(manager.create("fresh").await.unwrap());
+    registry.save(&registry_path).unwrap();
+
+    // A locked worktree cannot be removed without forcing it twice
+    let locked = manager.worktree_dir().join("locked");
+    let output = tokio::process::Command::new("git")
+        .args(["worktree", "lock"])
+        .arg(&locked)
+        .current_dir(temp_dir.path())
+        .output()
+        .await
+        .unwrap();
+    assert!(output.status.success());
+    std::fs::write(manager.worktree_dir().join("dirty/notes.txt"), "wip").unwrap();
+
+    let mut registry = WorktreeRegistry::load(&registry_path).unwrap();
+    let plan = worktree::prune_plan(&registry, chrono::Duration::hours(24));
+    assert_eq!(plan.len(), 4);
+    let report = worktree::prune(&manager, &mut registry, &plan, false).await;
+    registry.save(&registry_path).unwrap();
+
+    let mut removed = report.removed.clone();
+    removed.sort_unstable();
+    assert_eq!(removed, ["stale-a", "stale-b"]);
+    assert_eq!(report.skipped_dirty, ["dirty"]);
+    assert_eq!(report.failed.len(), 1);
+    assert_eq!(report.failed[0].0, "locked");
+    assert!(matches!(report.failed[0].1, WorktreeError::GitError(_)));
+    assert!(!report.is_success());
+    assert!(locked.exists());
+
+    let saved = WorktreeRegistry::load(&registry_path).unwrap();
+    let mut names: Vec<_> = saved.list().iter().map(|wt| wt.name.clone()).collect();
+    names.sort_unstable();
+    assert_eq!(names, ["dirty", "fresh", "locked"]);
+}
+
 #[test]
 fn test_worktree_status_transitions() {
     let mut wt = Worktree::new("test", PathBuf::from("/tmp/test"), "main");